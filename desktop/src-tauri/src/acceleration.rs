@@ -0,0 +1,317 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{settings, AppState};
+
+// nvidia-smi 偶尔会卡在驱动初始化上，超时后视为没有 CUDA 设备
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+// 纯 CPU 推理时最多使用的线程数，再多收益很小
+const MAX_CPU_THREADS: usize = 8;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AccelBackend {
+    Cpu,
+    Cuda,
+    Metal,
+    #[serde(rename = "directml")]
+    DirectMl,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AccelerationSettings {
+    // 手动指定推理后端，为空时按检测结果自动选择
+    pub backend: Option<AccelBackend>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    Whisper,
+    Embeddings,
+    Llm,
+}
+
+impl Engine {
+    // 按速度从快到慢排列引擎支持的后端
+    fn backends(self) -> &'static [AccelBackend] {
+        match self {
+            // whisper.cpp 和 llama.cpp 都没有 DirectML 后端
+            Engine::Whisper | Engine::Llm => &[AccelBackend::Cuda, AccelBackend::Metal, AccelBackend::Cpu],
+            Engine::Embeddings => &[
+                AccelBackend::Cuda,
+                AccelBackend::Metal,
+                AccelBackend::DirectMl,
+                AccelBackend::Cpu,
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct GpuInfo {
+    pub name: String,
+    pub memory_mb: Option<u64>,
+    pub driver: Option<String>,
+}
+
+// llama.cpp、whisper.cpp 等 CPU 推理依赖的指令集
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct CpuFeatures {
+    pub avx: bool,
+    pub avx2: bool,
+    pub avx512: bool,
+    pub fma: bool,
+    pub neon: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemCapabilities {
+    pub os: &'static str,
+    pub arch: &'static str,
+    pub cpu_threads: usize,
+    pub cpu: CpuFeatures,
+    pub cuda: bool,
+    pub metal: bool,
+    pub directml: bool,
+    // 目前只有 NVIDIA 显卡能列出型号和显存
+    pub gpus: Vec<GpuInfo>,
+    pub probe_ms: u64,
+}
+
+impl SystemCapabilities {
+    fn supports(&self, backend: AccelBackend) -> bool {
+        match backend {
+            AccelBackend::Cpu => true,
+            AccelBackend::Cuda => self.cuda,
+            AccelBackend::Metal => self.metal,
+            AccelBackend::DirectMl => self.directml,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CapabilityReport {
+    pub capabilities: SystemCapabilities,
+    // 设置中手动指定的后端
+    pub preferred: Option<AccelBackend>,
+    pub whisper: AccelBackend,
+    // 嵌入模型由 llama.cpp server 等外部服务运行，这里给出建议的后端供前端配置
+    pub embeddings: AccelBackend,
+    pub llm: AccelBackend,
+}
+
+static CAPABILITIES: OnceLock<SystemCapabilities> = OnceLock::new();
+
+#[cfg(target_arch = "x86_64")]
+fn cpu_features() -> CpuFeatures {
+    CpuFeatures {
+        avx: std::arch::is_x86_feature_detected!("avx"),
+        avx2: std::arch::is_x86_feature_detected!("avx2"),
+        avx512: std::arch::is_x86_feature_detected!("avx512f"),
+        fma: std::arch::is_x86_feature_detected!("fma"),
+        ..CpuFeatures::default()
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+fn cpu_features() -> CpuFeatures {
+    CpuFeatures {
+        neon: std::arch::is_aarch64_feature_detected!("neon"),
+        ..CpuFeatures::default()
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn cpu_features() -> CpuFeatures {
+    CpuFeatures::default()
+}
+
+#[cfg(windows)]
+fn system_library(name: &str) -> Option<PathBuf> {
+    let root = std::env::var_os("SystemRoot")?;
+    let path = PathBuf::from(root).join("System32").join(name);
+    path.is_file().then_some(path)
+}
+
+#[cfg(not(windows))]
+fn system_library(name: &str) -> Option<PathBuf> {
+    [
+        "/usr/lib",
+        "/usr/lib64",
+        "/usr/lib/x86_64-linux-gnu",
+        "/usr/lib/aarch64-linux-gnu",
+    ]
+    .iter()
+    .map(|dir| PathBuf::from(dir).join(name))
+    .find(|path| path.is_file())
+}
+
+// 运行检测命令并读取输出，超时或失败时返回 None
+fn probe_command(program: &str, args: &[&str]) -> Option<String> {
+    let mut command = Command::new(program);
+    command.args(args).stdout(Stdio::piped()).stderr(Stdio::null());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(crate::backend::CREATE_NO_WINDOW);
+    }
+    let mut child = command.spawn().ok()?;
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => break,
+            Ok(Some(_)) | Err(_) => return None,
+            Ok(None) if started.elapsed() > PROBE_TIMEOUT => {
+                warn!("{} did not finish within {:?}", program, PROBE_TIMEOUT);
+                child.kill().ok();
+                child.wait().ok();
+                return None;
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+        }
+    }
+    let mut output = String::new();
+    child.stdout.take()?.read_to_string(&mut output).ok()?;
+    Some(output)
+}
+
+fn nvidia_gpus() -> Vec<GpuInfo> {
+    let Some(output) = probe_command(
+        "nvidia-smi",
+        &[
+            "--query-gpu=name,memory.total,driver_version",
+            "--format=csv,noheader,nounits",
+        ],
+    ) else {
+        return Vec::new();
+    };
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(',').map(str::trim);
+            let name = fields.next().filter(|name| !name.is_empty())?.to_string();
+            Some(GpuInfo {
+                name,
+                memory_mb: fields.next().and_then(|memory| memory.parse().ok()),
+                driver: fields.next().map(str::to_string),
+            })
+        })
+        .collect()
+}
+
+fn probe() -> SystemCapabilities {
+    let started = Instant::now();
+    let gpus = nvidia_gpus();
+    let cuda_library = if cfg!(windows) { "nvcuda.dll" } else { "libcuda.so.1" };
+    let capabilities = SystemCapabilities {
+        os: std::env::consts::OS,
+        arch: std::env::consts::ARCH,
+        cpu_threads: std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1),
+        cpu: cpu_features(),
+        cuda: !gpus.is_empty() || (!cfg!(target_os = "macos") && system_library(cuda_library).is_some()),
+        // macOS 10.14 起所有机型都支持 Metal
+        metal: cfg!(target_os = "macos"),
+        // Windows 10 1903 起系统自带 DirectML.dll
+        directml: cfg!(windows) && system_library("DirectML.dll").is_some(),
+        gpus,
+        probe_ms: started.elapsed().as_millis() as u64,
+    };
+    info!(
+        "Acceleration: cuda={} metal={} directml={} avx2={} neon={} ({} ms)",
+        capabilities.cuda,
+        capabilities.metal,
+        capabilities.directml,
+        capabilities.cpu.avx2,
+        capabilities.cpu.neon,
+        capabilities.probe_ms
+    );
+    capabilities
+}
+
+// 首次调用时检测，之后返回缓存的结果；会阻塞当前线程
+pub fn capabilities() -> &'static SystemCapabilities {
+    CAPABILITIES.get_or_init(probe)
+}
+
+// 启动时在后台线程检测，避免第一次识别时等待
+pub fn start() {
+    std::thread::spawn(capabilities);
+}
+
+fn preferred(app: &AppHandle) -> Option<AccelBackend> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock();
+    settings.ok().and_then(|settings| settings.acceleration.backend)
+}
+
+// 手动指定的后端不被引擎支持时按自动选择处理；检测不到但手动指定的后端仍然使用
+fn choose(capabilities: &SystemCapabilities, engine: Engine, preferred: Option<AccelBackend>) -> AccelBackend {
+    let backends = engine.backends();
+    if let Some(backend) = preferred {
+        if backends.contains(&backend) {
+            return backend;
+        }
+        warn!("{:?} does not support {:?}, selecting automatically", engine, backend);
+    }
+    backends
+        .iter()
+        .copied()
+        .find(|backend| capabilities.supports(*backend))
+        .unwrap_or(AccelBackend::Cpu)
+}
+
+pub fn select(app: &AppHandle, engine: Engine) -> AccelBackend {
+    choose(capabilities(), engine, preferred(app))
+}
+
+// 纯 CPU 推理时使用的线程数
+pub fn cpu_threads() -> usize {
+    capabilities().cpu_threads.clamp(1, MAX_CPU_THREADS)
+}
+
+fn report(preferred: Option<AccelBackend>) -> CapabilityReport {
+    let capabilities = capabilities().clone();
+    CapabilityReport {
+        preferred,
+        whisper: choose(&capabilities, Engine::Whisper, preferred),
+        embeddings: choose(&capabilities, Engine::Embeddings, preferred),
+        llm: choose(&capabilities, Engine::Llm, preferred),
+        capabilities,
+    }
+}
+
+#[tauri::command]
+pub async fn get_system_capabilities(app: AppHandle) -> Result<CapabilityReport, AppError> {
+    let preferred = preferred(&app);
+    Ok(tauri::async_runtime::spawn_blocking(move || report(preferred)).await?)
+}
+
+// backend 为空时恢复自动选择，从下一次推理开始生效
+#[tauri::command]
+pub async fn set_acceleration_backend(
+    app: AppHandle,
+    backend: Option<AccelBackend>,
+) -> Result<CapabilityReport, AppError> {
+    if let Some(backend) = backend {
+        let capabilities = tauri::async_runtime::spawn_blocking(capabilities).await?;
+        if !capabilities.supports(backend) {
+            warn!("{:?} was not detected but is selected manually", backend);
+        }
+    }
+    {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock()?;
+        settings.acceleration.backend = backend;
+        settings::save_and_notify(&app, &settings, &["acceleration"])?;
+    }
+    info!("Acceleration backend set to {:?}", backend);
+    Ok(tauri::async_runtime::spawn_blocking(move || report(backend)).await?)
+}
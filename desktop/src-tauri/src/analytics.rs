@@ -0,0 +1,323 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::pipeline::TurnTiming;
+use crate::storage::{now_millis, DATABASE_FILE};
+use crate::AppState;
+
+pub const KIND_VOICE_TURN: &str = "voice_turn";
+pub const KIND_WAKE_WORD: &str = "wake_word";
+pub const KIND_STAGE_LATENCY: &str = "stage_latency";
+pub const KIND_FEATURE: &str = "feature";
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS usage_events (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    detail TEXT NOT NULL DEFAULT '',
+    value INTEGER,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_usage_events_kind ON usage_events(kind, created_at);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AnalyticsSettings {
+    // 只在本机记录使用情况，不会上传
+    pub enabled: bool,
+    // 允许导出匿名统计，默认关闭
+    pub allow_export: bool,
+    pub retention_days: u32,
+}
+
+impl Default for AnalyticsSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            allow_export: false,
+            retention_days: 90,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageRange {
+    Day,
+    #[default]
+    Week,
+    Month,
+    All,
+}
+
+impl UsageRange {
+    fn since(&self, now: i64) -> i64 {
+        match self {
+            UsageRange::Day => now - DAY_MS,
+            UsageRange::Week => now - 7 * DAY_MS,
+            UsageRange::Month => now - 30 * DAY_MS,
+            UsageRange::All => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct TurnCounts {
+    pub total: u32,
+    pub completed: u32,
+    pub cancelled: u32,
+    pub failed: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageLatency {
+    pub stage: String,
+    pub count: u32,
+    pub avg_ms: u64,
+    pub max_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeatureCount {
+    pub name: String,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyTurns {
+    // 本地日期 YYYY-MM-DD
+    pub day: String,
+    pub turns: u32,
+}
+
+// 只包含聚合后的计数和耗时，不含对话内容，导出时原样写入文件
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageStats {
+    pub from: i64,
+    pub to: i64,
+    pub voice_turns: TurnCounts,
+    pub wake_word_triggers: u32,
+    pub stage_latency: Vec<StageLatency>,
+    pub features: Vec<FeatureCount>,
+    pub daily_turns: Vec<DailyTurns>,
+}
+
+// 本地使用统计，与对话历史共用同一个数据库文件
+pub struct Analytics {
+    conn: Mutex<Connection>,
+}
+
+impl Analytics {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    pub fn insert(&self, kind: &str, detail: &str, value: Option<i64>) -> Result<(), AppError> {
+        self.conn()?.execute(
+            "INSERT INTO usage_events (kind, detail, value, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![kind, detail, value, now_millis()],
+        )?;
+        Ok(())
+    }
+
+    pub fn prune(&self, before: i64) -> Result<usize, AppError> {
+        let deleted = self
+            .conn()?
+            .execute("DELETE FROM usage_events WHERE created_at < ?1", params![before])?;
+        Ok(deleted)
+    }
+
+    pub fn clear(&self) -> Result<usize, AppError> {
+        let deleted = self.conn()?.execute("DELETE FROM usage_events", [])?;
+        Ok(deleted)
+    }
+
+    pub fn stats(&self, from: i64, to: i64) -> Result<UsageStats, AppError> {
+        let conn = self.conn()?;
+
+        let mut voice_turns = TurnCounts::default();
+        let mut stmt = conn.prepare(
+            "SELECT detail, COUNT(*) FROM usage_events
+             WHERE kind = ?1 AND created_at BETWEEN ?2 AND ?3 GROUP BY detail",
+        )?;
+        let rows = stmt.query_map(params![KIND_VOICE_TURN, from, to], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, u32>(1)?))
+        })?;
+        for row in rows {
+            let (outcome, count) = row?;
+            voice_turns.total += count;
+            match outcome.as_str() {
+                "completed" => voice_turns.completed += count,
+                "cancelled" => voice_turns.cancelled += count,
+                _ => voice_turns.failed += count,
+            }
+        }
+
+        let wake_word_triggers = conn.query_row(
+            "SELECT COUNT(*) FROM usage_events WHERE kind = ?1 AND created_at BETWEEN ?2 AND ?3",
+            params![KIND_WAKE_WORD, from, to],
+            |row| row.get(0),
+        )?;
+
+        let mut stmt = conn.prepare(
+            "SELECT detail, COUNT(*), CAST(AVG(value) AS INTEGER), MAX(value) FROM usage_events
+             WHERE kind = ?1 AND created_at BETWEEN ?2 AND ?3 GROUP BY detail ORDER BY detail",
+        )?;
+        let rows = stmt.query_map(params![KIND_STAGE_LATENCY, from, to], |row| {
+            Ok(StageLatency {
+                stage: row.get(0)?,
+                count: row.get(1)?,
+                avg_ms: row.get::<_, Option<i64>>(2)?.unwrap_or(0).max(0) as u64,
+                max_ms: row.get::<_, Option<i64>>(3)?.unwrap_or(0).max(0) as u64,
+            })
+        })?;
+        let stage_latency = rows.collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT detail, COUNT(*) FROM usage_events
+             WHERE kind = ?1 AND created_at BETWEEN ?2 AND ?3 GROUP BY detail ORDER BY COUNT(*) DESC",
+        )?;
+        let rows = stmt.query_map(params![KIND_FEATURE, from, to], |row| {
+            Ok(FeatureCount {
+                name: row.get(0)?,
+                count: row.get(1)?,
+            })
+        })?;
+        let features = rows.collect::<Result<Vec<_>, _>>()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT date(created_at / 1000, 'unixepoch', 'localtime') AS day, COUNT(*) FROM usage_events
+             WHERE kind = ?1 AND created_at BETWEEN ?2 AND ?3 GROUP BY day ORDER BY day",
+        )?;
+        let rows = stmt.query_map(params![KIND_VOICE_TURN, from, to], |row| {
+            Ok(DailyTurns {
+                day: row.get(0)?,
+                turns: row.get(1)?,
+            })
+        })?;
+        let daily_turns = rows.collect::<Result<Vec<_>, _>>()?;
+
+        Ok(UsageStats {
+            from,
+            to,
+            voice_turns,
+            wake_word_triggers,
+            stage_latency,
+            features,
+            daily_turns,
+        })
+    }
+}
+
+// 关闭统计时不记录；记录失败只写日志，不影响调用方
+pub fn record(app: &AppHandle, kind: &str, detail: &str, value: Option<i64>) {
+    let state = app.state::<AppState>();
+    let enabled = state.settings.lock().is_ok_and(|settings| settings.analytics.enabled);
+    if !enabled {
+        return;
+    }
+    if let Err(e) = state.analytics.insert(kind, detail, value) {
+        warn!("Failed to record usage event {}: {}", kind, e);
+    }
+}
+
+// 一轮对话结束时记录结果和各阶段耗时
+pub fn record_turn(app: &AppHandle, timing: &TurnTiming) {
+    record(
+        app,
+        KIND_VOICE_TURN,
+        timing.outcome.as_str(),
+        Some(timing.total_ms as i64),
+    );
+    if let Some(first_audio_ms) = timing.first_audio_ms {
+        record(app, KIND_STAGE_LATENCY, "first_audio", Some(first_audio_ms as i64));
+    }
+    for timing in &timing.stages {
+        record(
+            app,
+            KIND_STAGE_LATENCY,
+            timing.stage.as_str(),
+            Some(timing.duration_ms as i64),
+        );
+    }
+}
+
+// 在 setup 中调用：清理超过保留天数的记录
+pub fn prune_expired(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(retention_days) = state.settings.lock().map(|settings| settings.analytics.retention_days) else {
+        return;
+    };
+    if retention_days == 0 {
+        return;
+    }
+    match state.analytics.prune(now_millis() - retention_days as i64 * DAY_MS) {
+        Ok(0) => {}
+        Ok(deleted) => info!("Pruned {} usage events", deleted),
+        Err(e) => warn!("Failed to prune usage events: {}", e),
+    }
+}
+
+// 前端功能的使用记录，名称只能包含小写字母、数字和 . _ -
+#[tauri::command]
+pub fn record_feature_usage(app: AppHandle, feature: String) -> Result<(), AppError> {
+    let valid = !feature.is_empty()
+        && feature.len() <= 64
+        && feature
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(AppError::invalid(format!("功能名称无效: {}", feature)));
+    }
+    record(&app, KIND_FEATURE, &feature, None);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_usage_stats(state: State<'_, AppState>, range: Option<UsageRange>) -> Result<UsageStats, AppError> {
+    let now = now_millis();
+    state.analytics.stats(range.unwrap_or_default().since(now), now)
+}
+
+#[tauri::command]
+pub fn clear_usage_stats(state: State<'_, AppState>) -> Result<usize, AppError> {
+    state.analytics.clear()
+}
+
+// 需要在设置中开启 allow_export；只写入本地文件，用户取消保存时返回 None
+#[tauri::command]
+pub async fn export_usage_stats(
+    state: State<'_, AppState>,
+    range: Option<UsageRange>,
+) -> Result<Option<String>, AppError> {
+    if !state.settings.lock()?.analytics.allow_export {
+        return Err(AppError::PermissionDenied("未开启使用统计导出".to_string()));
+    }
+    let now = now_millis();
+    let stats = state.analytics.stats(range.unwrap_or_default().since(now), now)?;
+
+    let path = tauri::api::dialog::blocking::FileDialogBuilder::new()
+        .set_file_name(&format!("lingecho-usage-{}.json", now / 1000))
+        .add_filter("JSON", &["json"])
+        .save_file();
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    std::fs::write(&path, serde_json::to_vec_pretty(&stats)?).map_err(|e| AppError::io("写入统计文件失败", e))?;
+    info!("Usage stats exported to {}", path.display());
+    Ok(Some(path.to_string_lossy().to_string()))
+}
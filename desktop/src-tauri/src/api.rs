@@ -0,0 +1,190 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{debug, warn};
+
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::{network, secrets};
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+const MAX_TIMEOUT: Duration = Duration::from_secs(300);
+const MAX_ATTEMPTS: u32 = 3;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Method> {
+        match value {
+            "GET" => Some(Method::Get),
+            "POST" => Some(Method::Post),
+            "PUT" => Some(Method::Put),
+            "PATCH" => Some(Method::Patch),
+            "DELETE" => Some(Method::Delete),
+            _ => None,
+        }
+    }
+
+    fn as_reqwest(self) -> reqwest::Method {
+        match self {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+        }
+    }
+
+    // 幂等请求失败后可以安全重发
+    fn idempotent(self) -> bool {
+        matches!(self, Method::Get | Method::Put | Method::Delete)
+    }
+}
+
+// 后端统一响应格式 { code, msg, data }，HTTP 状态码始终为 200
+#[derive(Debug, Deserialize)]
+struct Envelope {
+    code: u16,
+    #[serde(default)]
+    msg: String,
+}
+
+// 只允许访问后端的路径，不能借此请求任意地址
+pub fn check_path(path: &str) -> Result<(), AppError> {
+    if !path.starts_with('/') || path.starts_with("//") || path.contains("://") {
+        return Err(AppError::invalid(format!("无效的后端路径: {}", path)));
+    }
+    Ok(())
+}
+
+// 所有发往本地后端的 HTTP 请求都经过这里，统一处理鉴权、超时和重试
+pub async fn request(
+    app: &AppHandle,
+    method: Method,
+    path: &str,
+    body: Option<&serde_json::Value>,
+    query: Option<&HashMap<String, String>>,
+    timeout: Duration,
+) -> Result<serde_json::Value, AppError> {
+    check_path(path)?;
+    let url = format!("{}{}", app.state::<BackendManager>().url(), path);
+    let token = secrets::get(secrets::BACKEND_TOKEN)?;
+    let client = network::client(app);
+
+    let mut attempt = 1;
+    loop {
+        match send(&client, method, &url, body, query, token.as_deref(), timeout).await {
+            Ok(value) => return Ok(value),
+            Err((e, sent)) if attempt < MAX_ATTEMPTS && e.retryable() && (!sent || method.idempotent()) => {
+                let delay = RETRY_BASE_DELAY * 2u32.pow(attempt - 1);
+                warn!(
+                    "{:?} {} failed (attempt {}): {}, retrying in {:?}",
+                    method, path, attempt, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+            Err((e, _)) => return Err(e),
+        }
+    }
+}
+
+// 失败时同时返回请求是否可能已到达后端，未发出的请求总是可以重试
+async fn send(
+    client: &reqwest::Client,
+    method: Method,
+    url: &str,
+    body: Option<&serde_json::Value>,
+    query: Option<&HashMap<String, String>>,
+    token: Option<&str>,
+    timeout: Duration,
+) -> Result<serde_json::Value, (AppError, bool)> {
+    let mut request = client.request(method.as_reqwest(), url).timeout(timeout);
+    if let Some(query) = query {
+        request = request.query(query);
+    }
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+
+    let response = request.send().await.map_err(|e| {
+        let sent = !e.is_connect();
+        (AppError::network("无法连接后端服务", e), sent)
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err((AppError::status("后端服务返回错误", status), true));
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| (AppError::network("读取后端响应失败", e), true))?;
+    if text.is_empty() {
+        return Ok(serde_json::Value::Null);
+    }
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(&text) else {
+        return Ok(serde_json::Value::String(text));
+    };
+    if let Ok(envelope) = Envelope::deserialize(&value) {
+        if envelope.code != 200 {
+            debug!("Backend returned code {}: {}", envelope.code, envelope.msg);
+            return Err((envelope_error(envelope), true));
+        }
+    }
+    Ok(value)
+}
+
+// 响应体中的业务状态码沿用 HTTP 状态码的含义；后端业务失败统一返回 500，不属于暂时性错误
+fn envelope_error(envelope: Envelope) -> AppError {
+    let message = if envelope.msg.is_empty() {
+        format!("后端服务返回错误: {}", envelope.code)
+    } else {
+        envelope.msg
+    };
+    match envelope.code {
+        400 | 422 => AppError::InvalidInput(message),
+        401 | 403 => AppError::PermissionDenied(message),
+        404 => AppError::NotFound(message),
+        _ => AppError::Internal(message),
+    }
+}
+
+#[tauri::command]
+pub async fn backend_request(
+    app: AppHandle,
+    method: Method,
+    path: String,
+    body: Option<serde_json::Value>,
+    query: Option<HashMap<String, String>>,
+    timeout_ms: Option<u64>,
+) -> Result<serde_json::Value, AppError> {
+    let timeout = timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(DEFAULT_TIMEOUT)
+        .min(MAX_TIMEOUT);
+    request(&app, method, &path, body.as_ref(), query.as_ref(), timeout).await
+}
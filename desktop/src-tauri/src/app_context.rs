@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::debug;
+
+use crate::error::AppError;
+use crate::{privacy, settings, AppState};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AppContextSettings {
+    // 默认关闭，开启后助手回答时可以参考前台应用和窗口标题
+    pub enabled: bool,
+    // 只报告这些应用，按进程名或应用名匹配（不区分大小写）；为空时不报告任何应用
+    pub allowed_apps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppContext {
+    pub app_name: String,
+    pub title: String,
+    pub pid: u32,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContextUnavailable {
+    Disabled,
+    PrivacyMode,
+    NotAllowed,
+    // 无法获取前台窗口，例如 Wayland 下
+    Unknown,
+}
+
+// context 为空时 reason 说明原因，前端据此提示用户
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveAppContext {
+    pub context: Option<AppContext>,
+    pub reason: Option<ContextUnavailable>,
+}
+
+impl ActiveAppContext {
+    fn unavailable(reason: ContextUnavailable) -> Self {
+        Self {
+            context: None,
+            reason: Some(reason),
+        }
+    }
+}
+
+fn describe(window: &xcap::Window) -> Option<AppContext> {
+    Some(AppContext {
+        app_name: window.app_name().ok()?,
+        title: window.title().unwrap_or_default(),
+        pid: window.pid().ok()?,
+    })
+}
+
+// 从快捷提问等本应用窗口发起时，前台是自己的窗口，这时取最上层的其他应用窗口
+fn foreground() -> Option<AppContext> {
+    let own = std::process::id();
+    let windows: Vec<xcap::Window> = xcap::Window::all()
+        .ok()?
+        .into_iter()
+        .filter(|window| window.pid().is_ok_and(|pid| pid != own))
+        .collect();
+    if let Some(focused) = windows.iter().find(|window| window.is_focused().unwrap_or(false)) {
+        return describe(focused);
+    }
+    windows
+        .iter()
+        .filter(|window| !window.is_minimized().unwrap_or(true))
+        .filter(|window| window.title().is_ok_and(|title| !title.is_empty()))
+        .max_by_key(|window| window.z().unwrap_or(i32::MIN))
+        .and_then(describe)
+}
+
+fn allowed(settings: &AppContextSettings, app_name: &str) -> bool {
+    let name = app_name.to_lowercase();
+    settings
+        .allowed_apps
+        .iter()
+        .any(|app| name.contains(&app.to_lowercase()))
+}
+
+// 在阻塞线程中调用
+pub fn active_context(app: &AppHandle) -> Result<ActiveAppContext, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.app_context.clone();
+    if !settings.enabled {
+        return Ok(ActiveAppContext::unavailable(ContextUnavailable::Disabled));
+    }
+    if privacy::is_active(app) {
+        return Ok(ActiveAppContext::unavailable(ContextUnavailable::PrivacyMode));
+    }
+    let Some(context) = foreground() else {
+        return Ok(ActiveAppContext::unavailable(ContextUnavailable::Unknown));
+    };
+    if !allowed(&settings, &context.app_name) {
+        debug!("Foreground app {} is not in the context allowlist", context.app_name);
+        return Ok(ActiveAppContext::unavailable(ContextUnavailable::NotAllowed));
+    }
+    Ok(ActiveAppContext {
+        context: Some(context),
+        reason: None,
+    })
+}
+
+#[tauri::command]
+pub async fn get_active_app_context(app: AppHandle) -> Result<ActiveAppContext, AppError> {
+    tauri::async_runtime::spawn_blocking(move || active_context(&app)).await?
+}
+
+#[tauri::command]
+pub fn set_app_context(
+    app: AppHandle,
+    enabled: Option<bool>,
+    allowed_apps: Option<Vec<String>>,
+) -> Result<AppContextSettings, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    if let Some(enabled) = enabled {
+        settings.app_context.enabled = enabled;
+    }
+    if let Some(apps) = allowed_apps {
+        settings.app_context.allowed_apps = apps
+            .into_iter()
+            .map(|app| app.trim().to_string())
+            .filter(|app| !app.is_empty())
+            .collect();
+    }
+    settings::save_and_notify(&app, &settings, &["app_context"])?;
+    Ok(settings.app_context.clone())
+}
@@ -0,0 +1,560 @@
+use base64::Engine;
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{Device, SampleFormat, SizedSample, StreamConfig};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{error, info, warn};
+
+use super::indicator::{self, MicSource};
+use super::meter::{AudioMeter, LevelMeter, LevelSource};
+use super::processing::{self, CaptureChain};
+use super::vad::{EnergyVad, VadConfig, VadEvent};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::pet::state::PetEvent;
+use crate::{pet, privacy, AppState};
+
+// 每个推送给前端的音频块时长
+const CHUNK_MS: u32 = 100;
+// 单次录音在内存中保留的最长时长，超出后只推流不再累积
+const MAX_RECORDING_SECS: u32 = 600;
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// 请求麦克风权限时打开设备的时长
+const PROBE_DURATION: Duration = Duration::from_millis(300);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct InputDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+    pub channels: u16,
+    pub default_sample_rate: u32,
+    pub min_sample_rate: u32,
+    pub max_sample_rate: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingInfo {
+    pub device: String,
+    pub sample_rate: u32,
+    pub device_channels: u16,
+}
+
+// 推送给前端的 PCM 数据块：单声道 16 位小端，base64 编码
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioChunk {
+    pub seq: u64,
+    pub sample_rate: u32,
+    pub samples: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RecordingSummary {
+    pub device: String,
+    pub sample_rate: u32,
+    pub samples: usize,
+    pub duration_ms: u64,
+}
+
+// 一次完整录音的单声道 PCM 数据
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub sample_rate: u32,
+    pub samples: Vec<i16>,
+}
+
+impl Recording {
+    pub fn duration_ms(&self) -> u64 {
+        if self.sample_rate == 0 {
+            return 0;
+        }
+        self.samples.len() as u64 * 1000 / self.sample_rate as u64
+    }
+}
+
+struct CaptureSession {
+    info: RecordingInfo,
+    stop: Arc<AtomicBool>,
+    // 本次录音中 VAD 是否已检测到说话
+    heard: Arc<AtomicBool>,
+    worker: JoinHandle<Recording>,
+}
+
+// cpal 的 Stream 不能跨线程传递，由独立采集线程持有，这里只保存控制句柄
+#[derive(Default)]
+pub struct AudioCapture {
+    session: Mutex<Option<CaptureSession>>,
+    // 最近一次完成的录音，供语音识别使用
+    last: Mutex<Option<Recording>>,
+}
+
+impl AudioCapture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(
+        &self,
+        app: AppHandle,
+        device_id: Option<String>,
+        sample_rate: Option<u32>,
+        vad: VadConfig,
+    ) -> Result<RecordingInfo, AppError> {
+        if privacy::is_active(&app) {
+            return Err(AppError::PermissionDenied("隐私模式下已禁用录音".to_string()));
+        }
+        let mut session = self.session.lock()?;
+        if let Some(current) = session.as_ref() {
+            return Err(AppError::unavailable(
+                "麦克风正在使用中",
+                format!("正在使用 {} 录音", current.info.device),
+            ));
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let heard = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (worker_stop, worker_heard) = (stop.clone(), heard.clone());
+        let worker = std::thread::spawn(move || {
+            run_capture(app, device_id, sample_rate, vad, worker_stop, worker_heard, ready_tx)
+        });
+
+        // 等待采集线程打开设备，失败时把错误返回给调用方
+        let info = match ready_rx.recv() {
+            Ok(Ok(info)) => info,
+            Ok(Err(e)) => {
+                worker.join().ok();
+                return Err(e);
+            }
+            Err(_) => {
+                worker.join().ok();
+                return Err(AppError::Internal("音频采集线程意外退出".to_string()));
+            }
+        };
+
+        info!("Recording started on {} at {} Hz", info.device, info.sample_rate);
+        *session = Some(CaptureSession {
+            info: info.clone(),
+            stop,
+            heard,
+            worker,
+        });
+        Ok(info)
+    }
+
+    pub fn stop(&self) -> Result<Option<RecordingSummary>, AppError> {
+        let Some(session) = self.session.lock()?.take() else {
+            return Ok(None);
+        };
+
+        session.stop.store(true, Ordering::SeqCst);
+        let recording = session
+            .worker
+            .join()
+            .map_err(|_| "音频采集线程异常退出".to_string())?;
+
+        let summary = RecordingSummary {
+            device: session.info.device,
+            sample_rate: recording.sample_rate,
+            samples: recording.samples.len(),
+            duration_ms: recording.duration_ms(),
+        };
+        info!("Recording stopped after {} ms", summary.duration_ms);
+        if let Ok(mut last) = self.last.lock() {
+            *last = Some(recording);
+        }
+        Ok(Some(summary))
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.session.lock().map(|s| s.is_some()).unwrap_or(false)
+    }
+
+    pub fn speech_detected(&self) -> bool {
+        self.session
+            .lock()
+            .ok()
+            .and_then(|s| s.as_ref().map(|s| s.heard.load(Ordering::SeqCst)))
+            .unwrap_or(false)
+    }
+
+    pub fn last_recording(&self) -> Option<Recording> {
+        self.last.lock().ok().and_then(|last| last.clone())
+    }
+}
+
+pub(super) fn host_device_id(device: &Device) -> String {
+    device.name().unwrap_or_else(|_| "unknown".to_string())
+}
+
+pub fn list_devices() -> Result<Vec<InputDevice>, AppError> {
+    let host = cpal::default_host();
+    let default_name = host.default_input_device().map(|device| host_device_id(&device));
+    let devices = host.input_devices()?;
+
+    let mut result = Vec::new();
+    for device in devices {
+        let name = host_device_id(&device);
+        let Ok(default_config) = device.default_input_config() else {
+            continue;
+        };
+        let (min_rate, max_rate) = device
+            .supported_input_configs()
+            .map(|configs| {
+                configs.fold((u32::MAX, 0), |(min, max), config| {
+                    (min.min(config.min_sample_rate().0), max.max(config.max_sample_rate().0))
+                })
+            })
+            .unwrap_or((default_config.sample_rate().0, default_config.sample_rate().0));
+
+        result.push(InputDevice {
+            id: name.clone(),
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+            channels: default_config.channels(),
+            default_sample_rate: default_config.sample_rate().0,
+            min_sample_rate: min_rate.min(max_rate),
+            max_sample_rate: max_rate,
+        });
+    }
+    Ok(result)
+}
+
+pub(super) fn find_device(device_id: Option<&str>) -> Result<Device, AppError> {
+    let host = cpal::default_host();
+    match device_id {
+        Some(id) => host
+            .input_devices()?
+            .find(|device| host_device_id(device) == id)
+            .ok_or_else(|| AppError::NotFound(format!("找不到输入设备: {}", id))),
+        None => host
+            .default_input_device()
+            .ok_or_else(|| AppError::unavailable("没有可用的输入设备", "未检测到输入设备")),
+    }
+}
+
+// 设备支持时使用请求的采样率，否则退回设备默认配置
+pub(super) fn pick_config(device: &Device, sample_rate: Option<u32>) -> Result<(StreamConfig, SampleFormat), AppError> {
+    if let Some(rate) = sample_rate {
+        let supported = device
+            .supported_input_configs()?
+            .filter(|config| config.min_sample_rate().0 <= rate && rate <= config.max_sample_rate().0)
+            .min_by_key(|config| match config.sample_format() {
+                SampleFormat::F32 => 0,
+                SampleFormat::I16 => 1,
+                _ => 2,
+            });
+        if let Some(config) = supported {
+            let config = config.with_sample_rate(cpal::SampleRate(rate));
+            return Ok((config.config(), config.sample_format()));
+        }
+        warn!("Sample rate {} Hz not supported, using device default", rate);
+    }
+
+    let config = device.default_input_config()?;
+    Ok((config.config(), config.sample_format()))
+}
+
+pub(super) fn build_stream<T>(
+    device: &Device,
+    config: &StreamConfig,
+    tx: mpsc::Sender<Vec<f32>>,
+) -> Result<cpal::Stream, AppError>
+where
+    T: SizedSample,
+    f32: cpal::FromSample<T>,
+{
+    let channels = config.channels.max(1) as usize;
+    device
+        .build_input_stream::<T, _, _>(
+            config,
+            move |data: &[T], _| {
+                // 多声道取平均混为单声道
+                let mono = data
+                    .chunks(channels)
+                    .map(|frame| frame.iter().map(|s| s.to_sample::<f32>()).sum::<f32>() / channels as f32)
+                    .collect();
+                tx.send(mono).ok();
+            },
+            |e| error!("Audio input stream error: {}", e),
+            None,
+        )
+        .map_err(AppError::from)
+}
+
+// 短暂打开默认输入设备并丢弃数据，第一次调用时系统会弹出麦克风授权
+pub fn probe_input(app: &AppHandle) -> Result<(), AppError> {
+    if privacy::is_active(app) {
+        return Err(AppError::PermissionDenied("隐私模式下已禁用录音".to_string()));
+    }
+    let device = find_device(None)?;
+    let config = device.default_input_config()?;
+    let stream = device
+        .build_input_stream_raw(
+            &config.config(),
+            config.sample_format(),
+            |_, _| {},
+            |e| error!("Audio input stream error: {}", e),
+            None,
+        )
+        .map_err(AppError::from)?;
+    stream.play()?;
+    let _mic = indicator::acquire(app, MicSource::Recording);
+    std::thread::sleep(PROBE_DURATION);
+    Ok(())
+}
+
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+fn encode_chunk(samples: &[i16]) -> String {
+    let bytes: Vec<u8> = samples.iter().flat_map(|s| s.to_le_bytes()).collect();
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+fn run_capture(
+    app: AppHandle,
+    device_id: Option<String>,
+    sample_rate: Option<u32>,
+    vad_config: VadConfig,
+    stop: Arc<AtomicBool>,
+    heard: Arc<AtomicBool>,
+    ready: mpsc::Sender<Result<RecordingInfo, AppError>>,
+) -> Recording {
+    let empty = Recording {
+        sample_rate: sample_rate.unwrap_or(0),
+        samples: Vec::new(),
+    };
+
+    let (tx, rx) = mpsc::channel::<Vec<f32>>();
+    let opened = find_device(device_id.as_deref()).and_then(|device| {
+        let (config, format) = pick_config(&device, sample_rate)?;
+        let stream = match format {
+            SampleFormat::F32 => build_stream::<f32>(&device, &config, tx),
+            SampleFormat::I16 => build_stream::<i16>(&device, &config, tx),
+            SampleFormat::U16 => build_stream::<u16>(&device, &config, tx),
+            other => Err(AppError::unavailable(
+                "音频设备不可用",
+                format!("不支持的采样格式: {:?}", other),
+            )),
+        }?;
+        stream.play()?;
+        let info = RecordingInfo {
+            device: host_device_id(&device),
+            sample_rate: config.sample_rate.0,
+            device_channels: config.channels,
+        };
+        Ok((stream, info))
+    });
+
+    let (stream, info) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            ready.send(Err(e)).ok();
+            return empty;
+        }
+    };
+    let _mic = indicator::acquire(&app, MicSource::Recording);
+    ready.send(Ok(info.clone())).ok();
+
+    let meter = LevelMeter::new(
+        LevelSource::Recording,
+        info.sample_rate,
+        app.state::<AudioMeter>().flag(),
+    );
+    let chain = match processing::processing_settings(&app) {
+        Ok(settings) => CaptureChain::new(info.sample_rate, &settings),
+        Err(e) => {
+            warn!("Failed to read audio processing settings: {}", e);
+            None
+        }
+    };
+    let mut processor = CaptureProcessor::new(app.clone(), info.sample_rate, vad_config, heard, meter, chain);
+    while !stop.load(Ordering::SeqCst) {
+        match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(frame) => {
+                if processor.handle(frame) {
+                    break;
+                }
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    // 停止设备后处理残留数据
+    drop(stream);
+    while let Ok(frame) = rx.try_recv() {
+        processor.handle(frame);
+    }
+
+    // 静音自动结束：会话句柄由 AudioCapture 持有，交给其他线程回收本线程
+    if processor.auto_stopped {
+        let handle = app.clone();
+        std::thread::spawn(move || {
+            if let Err(e) = finish_recording(&handle) {
+                warn!("Failed to finalize recording: {}", e);
+            }
+        });
+    }
+
+    processor.finish()
+}
+
+// 采集线程中逐帧处理音频：回声消除与降噪、计算电平、检测语音活动、分块推送、累积完整录音
+struct CaptureProcessor {
+    app: AppHandle,
+    sample_rate: u32,
+    chunk_len: usize,
+    max_samples: usize,
+    samples: Vec<i16>,
+    pending: Vec<i16>,
+    seq: u64,
+    vad: Option<EnergyVad>,
+    meter: LevelMeter,
+    // 回声消除与降噪，设置中都关闭时为 None
+    chain: Option<CaptureChain>,
+    heard: Arc<AtomicBool>,
+    auto_stopped: bool,
+}
+
+impl CaptureProcessor {
+    fn new(
+        app: AppHandle,
+        sample_rate: u32,
+        vad: VadConfig,
+        heard: Arc<AtomicBool>,
+        meter: LevelMeter,
+        chain: Option<CaptureChain>,
+    ) -> Self {
+        let chunk_len = (sample_rate * CHUNK_MS / 1000).max(1) as usize;
+        Self {
+            app,
+            sample_rate,
+            chunk_len,
+            max_samples: (sample_rate * MAX_RECORDING_SECS) as usize,
+            samples: Vec::new(),
+            pending: Vec::with_capacity(chunk_len),
+            seq: 0,
+            vad: vad.enabled.then(|| EnergyVad::new(vad, sample_rate)),
+            meter,
+            chain,
+            heard,
+            auto_stopped: false,
+        }
+    }
+
+    // 返回 true 表示检测到一句话结束且需要自动停止录音
+    fn handle(&mut self, frame: Vec<f32>) -> bool {
+        let frame = match self.chain.as_mut() {
+            Some(chain) => chain.process(&frame),
+            None => frame,
+        };
+        for level in self.meter.push(&frame) {
+            events::publish(&self.app, AppEvent::AudioLevel(level));
+        }
+        if let Some(vad) = self.vad.as_mut() {
+            for event in vad.push(&frame) {
+                match event {
+                    VadEvent::SpeechStarted => {
+                        self.heard.store(true, Ordering::SeqCst);
+                        events::publish(&self.app, AppEvent::SpeechStarted);
+                    }
+                    VadEvent::SpeechEnded { duration_ms } => {
+                        events::publish(&self.app, AppEvent::SpeechEnded { duration_ms });
+                        self.auto_stopped |= vad.auto_stop();
+                    }
+                }
+            }
+        }
+
+        for sample in frame {
+            let sample = to_i16(sample);
+            self.pending.push(sample);
+            if self.samples.len() < self.max_samples {
+                self.samples.push(sample);
+            }
+            if self.pending.len() >= self.chunk_len {
+                self.emit_chunk();
+            }
+        }
+        self.auto_stopped
+    }
+
+    fn emit_chunk(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let chunk = AudioChunk {
+            seq: self.seq,
+            sample_rate: self.sample_rate,
+            samples: encode_chunk(&self.pending),
+        };
+        self.seq += 1;
+        events::publish(&self.app, AppEvent::AudioChunk(chunk));
+        self.pending.clear();
+    }
+
+    fn finish(mut self) -> Recording {
+        self.emit_chunk();
+        Recording {
+            sample_rate: self.sample_rate,
+            samples: self.samples,
+        }
+    }
+}
+
+pub fn finish_recording(app: &AppHandle) -> Result<Option<RecordingSummary>, AppError> {
+    let summary = app.state::<AudioCapture>().stop()?;
+    if let Some(summary) = &summary {
+        events::publish(app, AppEvent::RecordingStopped(summary.clone()));
+        pet::state::notify(app, PetEvent::ListeningStopped);
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn list_input_devices() -> Result<Vec<InputDevice>, AppError> {
+    list_devices()
+}
+
+pub fn vad_settings(app: &AppHandle) -> Result<VadConfig, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.audio.vad.clone())
+}
+
+pub fn begin_recording(
+    app: &AppHandle,
+    device_id: Option<String>,
+    sample_rate: Option<u32>,
+    vad: VadConfig,
+) -> Result<RecordingInfo, AppError> {
+    let info = app.state::<AudioCapture>().start(app.clone(), device_id, sample_rate, vad)?;
+    events::publish(app, AppEvent::RecordingStarted(info.clone()));
+    pet::state::notify(app, PetEvent::ListeningStarted);
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn start_recording(
+    app: AppHandle,
+    device_id: Option<String>,
+    sample_rate: Option<u32>,
+    auto_stop: Option<bool>,
+) -> Result<RecordingInfo, AppError> {
+    let mut vad = vad_settings(&app)?;
+    if let Some(auto_stop) = auto_stop {
+        vad.auto_stop = auto_stop;
+    }
+    begin_recording(&app, device_id, sample_rate, vad)
+}
+
+#[tauri::command]
+pub fn stop_recording(app: AppHandle) -> Result<Option<RecordingSummary>, AppError> {
+    finish_recording(&app)
+}
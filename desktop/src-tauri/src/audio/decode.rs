@@ -0,0 +1,117 @@
+use std::fs::File;
+use std::path::Path;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+use super::Resampler;
+use crate::error::AppError;
+
+// 导入的录音最长 4 小时，16 kHz 单声道约占 460 MB 内存
+const MAX_DURATION_SECS: u64 = 4 * 60 * 60;
+
+fn decode_error(path: &Path, e: impl std::fmt::Display) -> AppError {
+    AppError::invalid(format!("无法解码音频文件 {}: {}", path.display(), e))
+}
+
+fn too_long() -> AppError {
+    AppError::invalid(format!("音频文件过长，最多支持 {} 小时", MAX_DURATION_SECS / 3600))
+}
+
+// 解码 WAV、MP3、M4A（AAC）等文件，混为单声道并重采样到 target_rate；on_progress 参数为 0 - 100
+pub fn decode_file(path: &Path, target_rate: u32, on_progress: &mut dyn FnMut(u8)) -> Result<Vec<i16>, AppError> {
+    let file = File::open(path).map_err(|e| AppError::io(format!("无法打开音频文件 {}", path.display()), e))?;
+    let stream = MediaSourceStream::new(Box::new(file), Default::default());
+    let mut hint = Hint::new();
+    if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(extension);
+    }
+    let probed = symphonia::default::get_probe()
+        .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+        .map_err(|e| decode_error(path, e))?;
+    let mut format = probed.format;
+
+    let track = format
+        .tracks()
+        .iter()
+        .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+        .ok_or_else(|| decode_error(path, "没有音频轨道"))?;
+    let track_id = track.id;
+    let total_frames = track.codec_params.n_frames;
+    let sample_rate = track
+        .codec_params
+        .sample_rate
+        // 个别容器写入的采样率为 0，同样视为未知
+        .filter(|rate| *rate > 0)
+        .ok_or_else(|| decode_error(path, "未知的采样率"))?;
+    if total_frames.is_some_and(|frames| frames / sample_rate as u64 > MAX_DURATION_SECS) {
+        return Err(too_long());
+    }
+    let mut decoder = symphonia::default::get_codecs()
+        .make(&track.codec_params, &DecoderOptions::default())
+        .map_err(|e| decode_error(path, e))?;
+
+    let mut resampler = Resampler::new(sample_rate, target_rate);
+    let max_samples = (MAX_DURATION_SECS * target_rate as u64) as usize;
+    let mut output: Vec<i16> = Vec::new();
+    let mut buffer: Option<SampleBuffer<f32>> = None;
+    let mut last_progress = 0;
+    loop {
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == std::io::ErrorKind::UnexpectedEof => break,
+            Err(SymphoniaError::ResetRequired) => break,
+            Err(e) => return Err(decode_error(path, e)),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            // 个别损坏的数据包直接跳过
+            Err(SymphoniaError::DecodeError(_)) => continue,
+            Err(e) => return Err(decode_error(path, e)),
+        };
+
+        let spec = *decoded.spec();
+        let channels = spec.channels.count().max(1);
+        if buffer
+            .as_ref()
+            .is_none_or(|buffer| buffer.capacity() < decoded.capacity() * channels)
+        {
+            buffer = Some(SampleBuffer::new(decoded.capacity() as u64, spec));
+        }
+        let Some(buffer) = buffer.as_mut() else {
+            continue;
+        };
+        buffer.copy_interleaved_ref(decoded);
+        let mono: Vec<f32> = buffer
+            .samples()
+            .chunks(channels)
+            .map(|frame| frame.iter().sum::<f32>() / channels as f32)
+            .collect();
+        output.extend(
+            resampler
+                .process(&mono)
+                .into_iter()
+                .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16),
+        );
+        if output.len() > max_samples {
+            return Err(too_long());
+        }
+
+        if let Some(total) = total_frames.filter(|total| *total > 0) {
+            let progress = (packet.ts().saturating_add(packet.dur) * 100 / total).min(100) as u8;
+            if progress != last_progress {
+                last_progress = progress;
+                on_progress(progress);
+            }
+        }
+    }
+    on_progress(100);
+    Ok(output)
+}
@@ -0,0 +1,182 @@
+use serde::Serialize;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, State, WindowBuilder, WindowUrl};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::privacy;
+
+// 麦克风占用提示：采集线程打开设备后持有 MicGuard，释放（包括线程异常退出）时撤销；
+// 托盘图标和提示窗口都由这里维护，前端无法关闭
+
+pub const INDICATOR_LABEL: &str = "mic-indicator";
+
+// 提示窗口被隐藏或关闭后重新显示的检查间隔
+const ENFORCE_INTERVAL: Duration = Duration::from_secs(1);
+// 红点的大小和距屏幕右上角的距离（逻辑像素）
+const DOT_SIZE: f64 = 14.0;
+const DOT_MARGIN: f64 = 8.0;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MicSource {
+    Recording,
+    WakeWord,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct MicStatus {
+    pub active: bool,
+    pub sources: Vec<MicSource>,
+}
+
+#[derive(Default)]
+pub struct MicIndicator {
+    // 每个正在采集的线程一项
+    sources: Mutex<Vec<MicSource>>,
+    changed: Condvar,
+}
+
+impl MicIndicator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> MicStatus {
+        let mut sources = self.sources.lock().map(|sources| sources.clone()).unwrap_or_default();
+        sources.dedup();
+        MicStatus {
+            active: !sources.is_empty(),
+            sources,
+        }
+    }
+
+    fn update(&self, change: impl FnOnce(&mut Vec<MicSource>)) {
+        if let Ok(mut sources) = self.sources.lock() {
+            change(&mut sources);
+            sources.sort_by_key(|source| *source as u8);
+            self.changed.notify_all();
+        }
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.try_state::<MicIndicator>()
+        .is_some_and(|indicator| indicator.status().active)
+}
+
+pub struct MicGuard {
+    app: AppHandle,
+    source: MicSource,
+}
+
+impl Drop for MicGuard {
+    fn drop(&mut self) {
+        let source = self.source;
+        self.app.state::<MicIndicator>().update(|sources| {
+            if let Some(index) = sources.iter().position(|s| *s == source) {
+                sources.remove(index);
+            }
+        });
+    }
+}
+
+// 在设备开始采集后立即调用，返回值与音频流一起持有
+pub fn acquire(app: &AppHandle, source: MicSource) -> MicGuard {
+    app.state::<MicIndicator>().update(|sources| sources.push(source));
+    MicGuard {
+        app: app.clone(),
+        source,
+    }
+}
+
+fn place(window: &tauri::Window) -> Result<(), AppError> {
+    let Some(monitor) = window.primary_monitor()? else {
+        return Ok(());
+    };
+    let scale = monitor.scale_factor();
+    let size = (DOT_SIZE * scale).round() as u32;
+    let margin = (DOT_MARGIN * scale).round() as i32;
+    window.set_size(PhysicalSize::new(size, size))?;
+    window.set_position(PhysicalPosition::new(
+        monitor.position().x + monitor.size().width as i32 - size as i32 - margin,
+        monitor.position().y + margin,
+    ))?;
+    Ok(())
+}
+
+fn show_overlay(app: &AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_window(INDICATOR_LABEL) {
+        if !window.is_visible()? {
+            place(&window)?;
+            window.show()?;
+        }
+        return Ok(());
+    }
+    // 静态页面（public/mic-indicator.html）只画一个红点，不需要加载前端应用
+    let window = WindowBuilder::new(app, INDICATOR_LABEL, WindowUrl::App("mic-indicator.html".into()))
+        .title("")
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .visible(false)
+        .build()?;
+    window.set_ignore_cursor_events(true)?;
+    place(&window)?;
+    window.show()?;
+    Ok(())
+}
+
+fn hide_overlay(app: &AppHandle) -> Result<(), AppError> {
+    match app.get_window(INDICATOR_LABEL) {
+        Some(window) => window.hide().map_err(AppError::from),
+        None => Ok(()),
+    }
+}
+
+fn apply(app: &AppHandle, status: &MicStatus, changed: bool) {
+    let result = if status.active {
+        show_overlay(app)
+    } else {
+        hide_overlay(app)
+    };
+    if let Err(e) = result {
+        warn!("Failed to update microphone indicator: {}", e);
+    }
+    if changed {
+        info!("Microphone sources changed: {:?}", status.sources);
+        privacy::update_tray(app, privacy::is_active(app));
+        events::publish(app, AppEvent::MicActivityChanged(status.clone()));
+    }
+}
+
+// 窗口和托盘的操作需要主线程处理，不能在等待采集线程的命令中直接调用，由独立线程执行
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        let indicator = handle.state::<MicIndicator>();
+        let mut shown = MicStatus::default();
+        loop {
+            if let Ok(sources) = indicator.sources.lock() {
+                indicator.changed.wait_timeout(sources, ENFORCE_INTERVAL).ok();
+            }
+            let status = indicator.status();
+            let changed = status != shown;
+            // 未变化时只在采集中重新确认提示窗口可见
+            if changed || status.active {
+                apply(&handle, &status, changed);
+            }
+            shown = status;
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_mic_status(indicator: State<'_, MicIndicator>) -> MicStatus {
+    indicator.status()
+}
@@ -0,0 +1,127 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::State;
+use tracing::info;
+
+use crate::error::AppError;
+
+// 每个电平窗口的时长
+const WINDOW_MS: u32 = 50;
+// 静音时 dBFS 的下限
+const FLOOR_DB: f32 = -100.0;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LevelSource {
+    Recording,
+    WakeWord,
+}
+
+// rms、peak 为 0 - 1 的线性幅度，db 为 rms 对应的 dBFS
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioLevel {
+    pub source: LevelSource,
+    pub rms: f32,
+    pub peak: f32,
+    pub db: f32,
+}
+
+// 电平推送开关，与录音和唤醒词监听的启停相互独立
+#[derive(Default)]
+pub struct AudioMeter {
+    enabled: Arc<AtomicBool>,
+}
+
+impl AudioMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        if self.enabled.swap(enabled, Ordering::SeqCst) != enabled {
+            info!("Audio level metering {}", if enabled { "started" } else { "stopped" });
+        }
+    }
+
+    // 交给采集线程持有，线程中无需再访问托管状态
+    pub fn flag(&self) -> Arc<AtomicBool> {
+        self.enabled.clone()
+    }
+}
+
+// 在采集线程中按固定窗口累计原始样本，关闭时不做任何计算
+pub struct LevelMeter {
+    source: LevelSource,
+    enabled: Arc<AtomicBool>,
+    window_len: usize,
+    count: usize,
+    sum_squares: f64,
+    peak: f32,
+}
+
+impl LevelMeter {
+    pub fn new(source: LevelSource, sample_rate: u32, enabled: Arc<AtomicBool>) -> Self {
+        Self {
+            source,
+            enabled,
+            window_len: (sample_rate * WINDOW_MS / 1000).max(1) as usize,
+            count: 0,
+            sum_squares: 0.0,
+            peak: 0.0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+        self.sum_squares = 0.0;
+        self.peak = 0.0;
+    }
+
+    // 返回本批样本中凑满的窗口电平，通常为零或一个
+    pub fn push(&mut self, samples: &[f32]) -> Vec<AudioLevel> {
+        if !self.enabled.load(Ordering::Relaxed) {
+            if self.count > 0 {
+                self.reset();
+            }
+            return Vec::new();
+        }
+
+        let mut levels = Vec::new();
+        for &sample in samples {
+            let sample = sample.clamp(-1.0, 1.0);
+            self.sum_squares += (sample * sample) as f64;
+            self.peak = self.peak.max(sample.abs());
+            self.count += 1;
+            if self.count >= self.window_len {
+                let rms = (self.sum_squares / self.count as f64).sqrt() as f32;
+                let db = if rms > 0.0 {
+                    (20.0 * rms.log10()).max(FLOOR_DB)
+                } else {
+                    FLOOR_DB
+                };
+                levels.push(AudioLevel {
+                    source: self.source,
+                    rms,
+                    peak: self.peak,
+                    db,
+                });
+                self.reset();
+            }
+        }
+        levels
+    }
+}
+
+// 开始后录音和唤醒词监听期间每 50 ms 推送一次 audio-level 事件
+#[tauri::command]
+pub fn start_audio_metering(meter: State<'_, AudioMeter>) -> Result<(), AppError> {
+    meter.set_enabled(true);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn stop_audio_metering(meter: State<'_, AudioMeter>) -> Result<(), AppError> {
+    meter.set_enabled(false);
+    Ok(())
+}
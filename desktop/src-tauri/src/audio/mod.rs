@@ -0,0 +1,60 @@
+// 原生音频子系统：采集、处理与播放
+pub mod capture;
+pub mod decode;
+pub mod indicator;
+pub mod meter;
+pub mod playback;
+pub mod processing;
+pub mod tts_stream;
+pub mod vad;
+pub mod wakeword;
+
+pub use capture::AudioCapture;
+pub use indicator::MicIndicator;
+pub use meter::AudioMeter;
+pub use playback::AudioPlayer;
+pub use tts_stream::TtsStreamer;
+pub use wakeword::WakeWordListener;
+
+// 流式线性插值重采样，用于把设备采样率转换为识别引擎需要的采样率
+pub struct Resampler {
+    step: f64,
+    pos: f64,
+    prev: Option<f32>,
+}
+
+impl Resampler {
+    pub fn new(from_rate: u32, to_rate: u32) -> Self {
+        Self {
+            step: from_rate as f64 / to_rate.max(1) as f64,
+            pos: 0.0,
+            prev: None,
+        }
+    }
+
+    pub fn process(&mut self, input: &[f32]) -> Vec<f32> {
+        if input.is_empty() {
+            return Vec::new();
+        }
+        if (self.step - 1.0).abs() < f64::EPSILON {
+            return input.to_vec();
+        }
+
+        // 把上一批的最后一个样本放在最前面，保证批次之间插值连续
+        let mut buffer = Vec::with_capacity(input.len() + 1);
+        buffer.push(self.prev.unwrap_or(input[0]));
+        buffer.extend_from_slice(input);
+
+        let last = (buffer.len() - 1) as f64;
+        let mut output = Vec::with_capacity((input.len() as f64 / self.step) as usize + 1);
+        while self.pos < last {
+            let index = self.pos as usize;
+            let frac = (self.pos - index as f64) as f32;
+            output.push(buffer[index] * (1.0 - frac) + buffer[index + 1] * frac);
+            self.pos += self.step;
+        }
+        self.pos -= last;
+        self.prev = input.last().copied();
+        output
+    }
+}
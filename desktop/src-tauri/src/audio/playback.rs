@@ -0,0 +1,464 @@
+use base64::Engine;
+use cpal::traits::{DeviceTrait, HostTrait};
+use rodio::buffer::SamplesBuffer;
+use rodio::{Decoder, OutputStream, Sink, Source};
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+
+use super::processing::EchoTap;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::pet::state::PetEvent;
+use crate::{pet, settings, AppState};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// 裸 PCM 未指定参数时使用的采样率（与常见 TTS 输出一致）
+const DEFAULT_PCM_RATE: u32 = 22_050;
+// 播放进度每累计这么多个采样更新一次
+const PROGRESS_FLUSH: u64 = 512;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PlaybackSettings {
+    // 输出设备名称，None 表示系统默认设备
+    pub device: Option<String>,
+    // 0 - 1
+    pub volume: f32,
+}
+
+impl Default for PlaybackSettings {
+    fn default() -> Self {
+        Self {
+            device: None,
+            volume: 1.0,
+        }
+    }
+}
+
+// 待播放的音频：文件路径、base64 字符串或字节数组
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AudioInput {
+    Path { path: String },
+    Base64 { data: String },
+    Bytes(Vec<u8>),
+}
+
+impl AudioInput {
+    fn into_bytes(self) -> Result<Vec<u8>, AppError> {
+        match self {
+            AudioInput::Path { path } => {
+                std::fs::read(&path).map_err(|e| AppError::io(format!("无法读取音频文件 {}", path), e))
+            }
+            AudioInput::Base64 { data } => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| AppError::invalid(format!("音频数据不是有效的 base64: {}", e))),
+            AudioInput::Bytes(bytes) => Ok(bytes),
+        }
+    }
+}
+
+// 音频格式；未指定时根据数据内容自动识别。pcm 为 16 位小端裸数据
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AudioFormat {
+    Wav,
+    Mp3,
+    Flac,
+    Ogg,
+    Pcm {
+        sample_rate: Option<u32>,
+        channels: Option<u16>,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OutputDevice {
+    pub id: String,
+    pub name: String,
+    pub is_default: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PlaybackFinished {
+    pub id: u64,
+    // 被 stop_audio 或新的播放打断时为 true
+    pub interrupted: bool,
+}
+
+struct PlaybackSession {
+    id: u64,
+    sink: Arc<Sink>,
+    stop: Arc<AtomicBool>,
+    played: Arc<AtomicU64>,
+}
+
+// 统计输出设备已取走的音频时长（纳秒），字幕等需要跟随播放进度的功能据此计时
+struct Progress<S> {
+    source: S,
+    played: Arc<AtomicU64>,
+    pending: u64,
+}
+
+impl<S: Source<Item = i16>> Progress<S> {
+    fn new(source: S, played: Arc<AtomicU64>) -> Self {
+        Self {
+            source,
+            played,
+            pending: 0,
+        }
+    }
+
+    fn flush(&mut self) {
+        let per_second = self.source.sample_rate() as u64 * self.source.channels().max(1) as u64;
+        if self.pending > 0 && per_second > 0 {
+            self.played
+                .fetch_add(self.pending * 1_000_000_000 / per_second, Ordering::Relaxed);
+        }
+        self.pending = 0;
+    }
+}
+
+impl<S: Source<Item = i16>> Iterator for Progress<S> {
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next();
+        match sample {
+            Some(_) => {
+                self.pending += 1;
+                if self.pending >= PROGRESS_FLUSH {
+                    self.flush();
+                }
+            }
+            None => self.flush(),
+        }
+        sample
+    }
+}
+
+impl<S: Source<Item = i16>> Source for Progress<S> {
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+// 流式播放句柄：数据到达时追加到同一个 Sink，句柄释放后播放完剩余数据即结束
+pub struct PlaybackStream {
+    id: u64,
+    sink: Arc<Sink>,
+    open: Arc<AtomicBool>,
+    stop: Arc<AtomicBool>,
+    played: Arc<AtomicU64>,
+}
+
+impl PlaybackStream {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // 被 stop_audio 或新的播放打断后不再需要继续推送数据
+    pub fn is_cancelled(&self) -> bool {
+        self.stop.load(Ordering::SeqCst)
+    }
+
+    pub fn push_pcm(&self, samples: Vec<i16>, sample_rate: u32, channels: u16) -> Result<(), AppError> {
+        if !samples.is_empty() {
+            let source = pcm_source(samples, sample_rate, channels)?;
+            self.sink.append(Progress::new(EchoTap::new(source), self.played.clone()));
+        }
+        Ok(())
+    }
+
+    pub fn push_encoded(&self, bytes: Vec<u8>, format: Option<AudioFormat>) -> Result<(), AppError> {
+        append_source(&self.sink, &self.played, bytes, format)
+    }
+}
+
+impl Drop for PlaybackStream {
+    fn drop(&mut self) {
+        self.open.store(false, Ordering::SeqCst);
+    }
+}
+
+// 与采集一样，rodio 的 OutputStream 不能跨线程，由播放线程持有
+#[derive(Default)]
+pub struct AudioPlayer {
+    session: Mutex<Option<PlaybackSession>>,
+    next_id: AtomicU64,
+}
+
+impl AudioPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn play(
+        &self,
+        app: AppHandle,
+        bytes: Vec<u8>,
+        format: Option<AudioFormat>,
+        settings: PlaybackSettings,
+    ) -> Result<u64, AppError> {
+        let stream = self.open_stream(app, settings)?;
+        if let Err(e) = stream.push_encoded(bytes, format) {
+            self.stop();
+            return Err(e);
+        }
+        Ok(stream.id())
+    }
+
+    // 打开输出设备并返回流式句柄，新的播放会打断当前播放
+    pub fn open_stream(&self, app: AppHandle, settings: PlaybackSettings) -> Result<PlaybackStream, AppError> {
+        self.stop();
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        let stop = Arc::new(AtomicBool::new(false));
+        let open = Arc::new(AtomicBool::new(true));
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let (worker_stop, worker_open) = (stop.clone(), open.clone());
+        let handle = app.clone();
+        std::thread::spawn(move || run_playback(app, id, settings, worker_stop, worker_open, ready_tx));
+
+        let sink = ready_rx
+            .recv()
+            .map_err(|_| AppError::Internal("音频播放线程意外退出".to_string()))??;
+        let played = Arc::new(AtomicU64::new(0));
+        *self.session.lock()? = Some(PlaybackSession {
+            id,
+            sink: sink.clone(),
+            stop: stop.clone(),
+            played: played.clone(),
+        });
+        pet::state::notify(&handle, PetEvent::SpeakingStarted);
+        Ok(PlaybackStream {
+            id,
+            sink,
+            open,
+            stop,
+            played,
+        })
+    }
+
+    pub fn stop(&self) -> Option<u64> {
+        let session = self.session.lock().ok()?.take()?;
+        session.stop.store(true, Ordering::SeqCst);
+        session.sink.stop();
+        Some(session.id)
+    }
+
+    pub fn current_id(&self) -> Option<u64> {
+        self.session.lock().ok().and_then(|s| s.as_ref().map(|s| s.id))
+    }
+
+    // 指定的播放已经播出的时长；播放已结束或被打断时返回 None
+    pub fn position(&self, id: u64) -> Option<Duration> {
+        let session = self.session.lock().ok()?;
+        let session = session.as_ref().filter(|s| s.id == id)?;
+        Some(Duration::from_nanos(session.played.load(Ordering::Relaxed)))
+    }
+
+    // 只在指定的播放仍在进行时停止
+    pub fn stop_if(&self, id: u64) -> bool {
+        self.current_id() == Some(id) && self.stop().is_some()
+    }
+
+    pub fn set_volume(&self, volume: f32) {
+        if let Ok(session) = self.session.lock() {
+            if let Some(session) = session.as_ref() {
+                session.sink.set_volume(volume);
+            }
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.session.lock().map(|s| s.is_some()).unwrap_or(false)
+    }
+
+    // 播放线程结束时清理自己的会话，避免覆盖之后开始的播放
+    fn finish(&self, id: u64) {
+        if let Ok(mut session) = self.session.lock() {
+            if session.as_ref().is_some_and(|s| s.id == id) {
+                session.take();
+            }
+        }
+    }
+}
+
+fn output_device(device_id: Option<&str>) -> Result<cpal::Device, AppError> {
+    let host = cpal::default_host();
+    match device_id {
+        Some(id) => host
+            .output_devices()?
+            .find(|device| device.name().ok().as_deref() == Some(id))
+            .ok_or_else(|| AppError::NotFound(format!("找不到输出设备: {}", id))),
+        None => host
+            .default_output_device()
+            .ok_or_else(|| AppError::unavailable("没有可用的输出设备", "未检测到输出设备")),
+    }
+}
+
+// SamplesBuffer 在采样率为 0 时会 panic，先检查
+fn pcm_source(samples: Vec<i16>, sample_rate: u32, channels: u16) -> Result<SamplesBuffer<i16>, AppError> {
+    if sample_rate == 0 {
+        return Err(AppError::invalid("音频采样率无效"));
+    }
+    Ok(SamplesBuffer::new(channels.max(1), sample_rate, samples))
+}
+
+fn append_source(
+    sink: &Sink,
+    played: &Arc<AtomicU64>,
+    bytes: Vec<u8>,
+    format: Option<AudioFormat>,
+) -> Result<(), AppError> {
+    let cursor = Cursor::new(bytes);
+    let decoded = match format {
+        None => Decoder::new(cursor),
+        Some(AudioFormat::Wav) => Decoder::new_wav(cursor),
+        Some(AudioFormat::Mp3) => Decoder::new_mp3(cursor),
+        Some(AudioFormat::Flac) => Decoder::new_flac(cursor),
+        Some(AudioFormat::Ogg) => Decoder::new_vorbis(cursor),
+        Some(AudioFormat::Pcm { sample_rate, channels }) => {
+            let samples: Vec<i16> = cursor
+                .into_inner()
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            let source = pcm_source(samples, sample_rate.unwrap_or(DEFAULT_PCM_RATE), channels.unwrap_or(1))?;
+            sink.append(Progress::new(EchoTap::new(source), played.clone()));
+            return Ok(());
+        }
+    };
+    let decoded = decoded.map_err(|e| AppError::invalid(format!("无法解码音频: {}", e)))?;
+    sink.append(Progress::new(EchoTap::new(decoded), played.clone()));
+    Ok(())
+}
+
+fn run_playback(
+    app: AppHandle,
+    id: u64,
+    settings: PlaybackSettings,
+    stop: Arc<AtomicBool>,
+    open: Arc<AtomicBool>,
+    ready: mpsc::Sender<Result<Arc<Sink>, AppError>>,
+) {
+    let opened = output_device(settings.device.as_deref()).and_then(|device| {
+        let (stream, handle) = OutputStream::try_from_device(&device)?;
+        let sink = Sink::try_new(&handle)?;
+        sink.set_volume(settings.volume);
+        Ok((stream, Arc::new(sink)))
+    });
+
+    let (stream, sink) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            ready.send(Err(e)).ok();
+            return;
+        }
+    };
+    ready.send(Ok(sink.clone())).ok();
+
+    // 流式句柄仍在推送数据时，队列暂时为空也继续等待
+    while (!sink.empty() || open.load(Ordering::SeqCst)) && !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    let interrupted = stop.load(Ordering::SeqCst);
+    drop(stream);
+
+    let player = app.state::<AudioPlayer>();
+    player.finish(id);
+    // 被新的播放打断时保持说话状态
+    if !player.is_playing() {
+        pet::state::notify(&app, PetEvent::SpeakingFinished);
+    }
+    events::publish(&app, AppEvent::PlaybackFinished(PlaybackFinished { id, interrupted }));
+}
+
+pub fn playback_settings(app: &AppHandle) -> Result<PlaybackSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.audio.playback.clone())
+}
+
+#[tauri::command]
+pub fn list_output_devices() -> Result<Vec<OutputDevice>, AppError> {
+    let host = cpal::default_host();
+    let default_name = host.default_output_device().and_then(|device| device.name().ok());
+    let devices = host.output_devices()?;
+    Ok(devices
+        .filter_map(|device| device.name().ok())
+        .map(|name| OutputDevice {
+            id: name.clone(),
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+        })
+        .collect())
+}
+
+// 返回本次播放的 id，playback-finished 事件会带上同一个 id
+#[tauri::command]
+pub fn play_audio(
+    app: AppHandle,
+    player: State<'_, AudioPlayer>,
+    input: AudioInput,
+    format: Option<AudioFormat>,
+    device_id: Option<String>,
+    volume: Option<f32>,
+) -> Result<u64, AppError> {
+    let mut settings = playback_settings(&app)?;
+    if device_id.is_some() {
+        settings.device = device_id;
+    }
+    if let Some(volume) = volume {
+        settings.volume = volume.clamp(0.0, 1.0);
+    }
+
+    let bytes = input.into_bytes()?;
+    player.play(app.clone(), bytes, format, settings)
+}
+
+#[tauri::command]
+pub fn stop_audio(player: State<'_, AudioPlayer>) -> Option<u64> {
+    player.stop()
+}
+
+#[tauri::command]
+pub fn set_playback_volume(app: AppHandle, player: State<'_, AudioPlayer>, volume: f32) -> Result<(), AppError> {
+    if !(0.0..=1.0).contains(&volume) {
+        return Err(AppError::invalid("音量需在 0 - 1 之间"));
+    }
+    player.set_volume(volume);
+
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.audio.playback.volume = volume;
+    settings::save_and_notify(&app, &settings, &["audio"])
+}
+
+#[tauri::command]
+pub fn set_playback_device(app: AppHandle, device_id: Option<String>) -> Result<(), AppError> {
+    if let Some(id) = device_id.as_deref() {
+        output_device(Some(id))?;
+    }
+
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.audio.playback.device = device_id;
+    settings::save_and_notify(&app, &settings, &["audio"])
+}
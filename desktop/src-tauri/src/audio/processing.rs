@@ -0,0 +1,300 @@
+use aec3::nodes::audio::AudioFormat as Aec3Format;
+use aec3::pipelines::linear::{self, LinearPipeline};
+use nnnoiseless::DenoiseState;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use super::Resampler;
+use crate::error::AppError;
+use crate::{settings, AppState};
+
+// 回声消除和降噪都以 48 kHz 单声道、10 ms 一帧处理
+const PROCESS_RATE: u32 = 48_000;
+const FRAME_LEN: usize = DenoiseState::FRAME_SIZE;
+// 参考信号最多缓存 1 秒，采集端跟不上时丢弃最旧的数据
+const MAX_REFERENCE: usize = PROCESS_RATE as usize;
+// 播放器每累计这么多样本才写入一次参考信号，减少输出回调中的加锁次数
+const TAP_FLUSH: usize = 1024;
+// 扬声器到麦克风的初始延迟估计，之后由 AEC3 自动跟踪
+const INITIAL_DELAY_MS: i32 = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProcessingSettings {
+    // 回声消除：去掉麦克风录到的助手自己的语音
+    pub echo_cancellation: bool,
+    // 降噪（RNNoise）：抑制键盘、风扇等背景噪声
+    pub noise_suppression: bool,
+}
+
+impl ProcessingSettings {
+    fn enabled(&self) -> bool {
+        self.echo_cancellation || self.noise_suppression
+    }
+}
+
+// 播放出去的声音，已转换为 48 kHz 单声道，供回声消除作为参考
+struct RenderBuffer {
+    samples: VecDeque<f32>,
+    sample_rate: u32,
+    resampler: Option<Resampler>,
+}
+
+static REFERENCE: Mutex<RenderBuffer> = Mutex::new(RenderBuffer {
+    samples: VecDeque::new(),
+    sample_rate: 0,
+    resampler: None,
+});
+// 正在使用回声消除的采集数，为 0 时播放器不写入参考信号
+static REFERENCE_USERS: AtomicUsize = AtomicUsize::new(0);
+
+fn reference_active() -> bool {
+    REFERENCE_USERS.load(Ordering::Relaxed) > 0
+}
+
+// samples 为交错的多声道数据
+fn feed_reference(samples: &[f32], channels: u16, sample_rate: u32) {
+    let channels = channels.max(1) as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    let Ok(mut buffer) = REFERENCE.lock() else {
+        return;
+    };
+    if buffer.sample_rate != sample_rate || buffer.resampler.is_none() {
+        buffer.sample_rate = sample_rate;
+        buffer.resampler = Some(Resampler::new(sample_rate, PROCESS_RATE));
+    }
+    let resampled = buffer.resampler.as_mut().map(|r| r.process(&mono)).unwrap_or_default();
+    buffer.samples.extend(resampled);
+    let excess = buffer.samples.len().saturating_sub(MAX_REFERENCE);
+    buffer.samples.drain(..excess);
+}
+
+fn take_reference(frame: &mut [f32]) {
+    frame.fill(0.0);
+    if let Ok(mut buffer) = REFERENCE.lock() {
+        let available = frame.len().min(buffer.samples.len());
+        for (slot, sample) in frame.iter_mut().zip(buffer.samples.drain(..available)) {
+            *slot = sample;
+        }
+    }
+}
+
+fn clear_reference() {
+    if let Ok(mut buffer) = REFERENCE.lock() {
+        buffer.samples.clear();
+        buffer.resampler = None;
+    }
+}
+
+// 包装播放源，在输出设备取样时把样本复制给回声消除
+pub struct EchoTap<S> {
+    source: S,
+    pending: Vec<f32>,
+}
+
+impl<S> EchoTap<S>
+where
+    S: Source<Item = i16>,
+{
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            pending: Vec::with_capacity(TAP_FLUSH),
+        }
+    }
+
+    fn flush(&mut self) {
+        if !self.pending.is_empty() {
+            feed_reference(&self.pending, self.source.channels(), self.source.sample_rate());
+            self.pending.clear();
+        }
+    }
+}
+
+impl<S> Iterator for EchoTap<S>
+where
+    S: Source<Item = i16>,
+{
+    type Item = i16;
+
+    fn next(&mut self) -> Option<i16> {
+        let sample = self.source.next();
+        if !reference_active() {
+            self.pending.clear();
+            return sample;
+        }
+        match sample {
+            Some(sample) => {
+                self.pending.push(sample as f32 / i16::MAX as f32);
+                if self.pending.len() >= TAP_FLUSH {
+                    self.flush();
+                }
+            }
+            None => self.flush(),
+        }
+        sample
+    }
+}
+
+impl<S> Source for EchoTap<S>
+where
+    S: Source<Item = i16>,
+{
+    fn current_frame_len(&self) -> Option<usize> {
+        self.source.current_frame_len()
+    }
+
+    fn channels(&self) -> u16 {
+        self.source.channels()
+    }
+
+    fn sample_rate(&self) -> u32 {
+        self.source.sample_rate()
+    }
+
+    fn total_duration(&self) -> Option<Duration> {
+        self.source.total_duration()
+    }
+}
+
+fn echo_canceller() -> Option<LinearPipeline> {
+    let format = Aec3Format::ten_ms(PROCESS_RATE, 1);
+    // 降噪由 RNNoise 负责，这里只保留高通滤波和回声消除
+    let built = linear::builder(format, format)
+        .enable_noise_suppression(false)
+        .enable_gain_controller2(false)
+        .initial_delay_ms(INITIAL_DELAY_MS)
+        .build();
+    match built {
+        Ok(pipeline) => Some(pipeline),
+        Err(e) => {
+            warn!("Failed to create echo canceller: {}", e);
+            None
+        }
+    }
+}
+
+// 采集线程中的处理链：设备采样率 -> 48 kHz -> 回声消除 -> 降噪 -> 设备采样率
+pub struct CaptureChain {
+    to_process: Resampler,
+    from_process: Resampler,
+    pending: Vec<f32>,
+    echo: Option<LinearPipeline>,
+    denoise: Option<Box<DenoiseState<'static>>>,
+    render: Vec<f32>,
+    frame: Vec<f32>,
+    output: Vec<f32>,
+}
+
+impl CaptureChain {
+    // 两项处理都关闭时返回 None，采集数据原样使用
+    pub fn new(device_rate: u32, settings: &ProcessingSettings) -> Option<Self> {
+        if !settings.enabled() {
+            return None;
+        }
+        let echo = settings.echo_cancellation.then(echo_canceller).flatten();
+        if echo.is_some() {
+            REFERENCE_USERS.fetch_add(1, Ordering::SeqCst);
+        }
+        info!(
+            "Capture processing enabled (echo cancellation: {}, noise suppression: {})",
+            echo.is_some(),
+            settings.noise_suppression
+        );
+        Some(Self {
+            to_process: Resampler::new(device_rate, PROCESS_RATE),
+            from_process: Resampler::new(PROCESS_RATE, device_rate),
+            pending: Vec::with_capacity(FRAME_LEN * 2),
+            echo,
+            denoise: settings.noise_suppression.then(DenoiseState::new),
+            render: vec![0.0; FRAME_LEN],
+            frame: vec![0.0; FRAME_LEN],
+            output: vec![0.0; FRAME_LEN],
+        })
+    }
+
+    fn process_frame(&mut self) {
+        if let Some(echo) = self.echo.as_mut() {
+            take_reference(&mut self.render);
+            let result = echo
+                .handle_render_frame(&self.render)
+                .and_then(|_| echo.process_capture_frame(&self.frame, &mut self.output));
+            match result {
+                Ok(true) => self.frame.copy_from_slice(&self.output),
+                Ok(false) => {}
+                Err(e) => {
+                    // 出错后本次录音不再做回声消除，避免持续刷日志
+                    warn!("Echo cancellation failed, disabling it for this recording: {}", e);
+                    self.echo = None;
+                    self.release_reference();
+                }
+            }
+        }
+        if let Some(denoise) = self.denoise.as_mut() {
+            // RNNoise 要求 16 位整数范围的浮点数
+            for sample in self.frame.iter_mut() {
+                *sample *= i16::MAX as f32;
+            }
+            denoise.process_frame(&mut self.output, &self.frame);
+            for (sample, denoised) in self.frame.iter_mut().zip(&self.output) {
+                *sample = denoised / i16::MAX as f32;
+            }
+        }
+    }
+
+    // 输入为设备采样率的单声道样本，输出同样采样率的处理结果，约有 10 ms 延迟
+    pub fn process(&mut self, samples: &[f32]) -> Vec<f32> {
+        let resampled = self.to_process.process(samples);
+        self.pending.extend(resampled);
+        let mut processed = Vec::with_capacity(self.pending.len());
+        let mut offset = 0;
+        while self.pending.len() - offset >= FRAME_LEN {
+            self.frame.copy_from_slice(&self.pending[offset..offset + FRAME_LEN]);
+            self.process_frame();
+            processed.extend_from_slice(&self.frame);
+            offset += FRAME_LEN;
+        }
+        self.pending.drain(..offset);
+        self.from_process.process(&processed)
+    }
+
+    fn release_reference(&mut self) {
+        if REFERENCE_USERS.fetch_sub(1, Ordering::SeqCst) == 1 {
+            clear_reference();
+        }
+    }
+}
+
+impl Drop for CaptureChain {
+    fn drop(&mut self) {
+        if self.echo.is_some() {
+            self.release_reference();
+        }
+    }
+}
+
+pub fn processing_settings(app: &AppHandle) -> Result<ProcessingSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.audio.processing.clone())
+}
+
+// 新设置从下一次录音开始生效
+#[tauri::command]
+pub fn set_audio_processing(app: AppHandle, processing: ProcessingSettings) -> Result<(), AppError> {
+    info!(
+        "Audio processing updated (echo cancellation: {}, noise suppression: {})",
+        processing.echo_cancellation, processing.noise_suppression
+    );
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.audio.processing = processing;
+    settings::save_and_notify(&app, &settings, &["audio"])
+}
@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use super::playback::{self, AudioFormat, AudioPlayer, PlaybackStream};
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{network, AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TtsStreamSettings {
+    // 后端流式合成接口路径，返回分块传输的音频
+    pub stream_path: String,
+    pub voice: Option<String>,
+    // 请求 PCM 输出时的采样率，响应头中带有 rate 时以响应为准
+    pub sample_rate: u32,
+}
+
+impl Default for TtsStreamSettings {
+    fn default() -> Self {
+        Self {
+            stream_path: "/api/voice/synthesize/stream".to_string(),
+            voice: None,
+            sample_rate: 24_000,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct StreamRequest<'a> {
+    text: &'a str,
+    voice: Option<&'a str>,
+    format: &'a str,
+    sample_rate: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TtsStreamError {
+    pub id: u64,
+    pub error: String,
+}
+
+// 记录当前正在下载的合成流，cancel_tts 只打断 TTS 播放
+#[derive(Default)]
+pub struct TtsStreamer {
+    active: AtomicU64,
+}
+
+impl TtsStreamer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// 响应的音频格式：PCM 可以边收边播，其他格式需要完整数据后解码
+enum StreamFormat {
+    Pcm { sample_rate: u32, channels: u16 },
+    Encoded(Option<AudioFormat>),
+}
+
+fn parse_content_type(content_type: &str, default_rate: u32) -> StreamFormat {
+    let mut parts = content_type.split(';').map(|part| part.trim().to_ascii_lowercase());
+    let mime = parts.next().unwrap_or_default();
+    match mime.as_str() {
+        "audio/pcm" | "audio/l16" | "audio/raw" | "application/octet-stream" => {
+            let (mut sample_rate, mut channels) = (default_rate, 1);
+            // 远端给出的参数不可信，为 0 或无法解析时使用默认值
+            for param in parts {
+                if let Some((key, value)) = param.split_once('=') {
+                    match key.trim() {
+                        "rate" => sample_rate = value.trim().parse().ok().filter(|v| *v > 0).unwrap_or(sample_rate),
+                        "channels" => channels = value.trim().parse().ok().filter(|v| *v > 0).unwrap_or(channels),
+                        _ => {}
+                    }
+                }
+            }
+            StreamFormat::Pcm { sample_rate, channels }
+        }
+        "audio/mpeg" | "audio/mp3" => StreamFormat::Encoded(Some(AudioFormat::Mp3)),
+        "audio/wav" | "audio/x-wav" | "audio/wave" => StreamFormat::Encoded(Some(AudioFormat::Wav)),
+        "audio/ogg" => StreamFormat::Encoded(Some(AudioFormat::Ogg)),
+        "audio/flac" => StreamFormat::Encoded(Some(AudioFormat::Flac)),
+        _ => StreamFormat::Encoded(None),
+    }
+}
+
+async fn pump(mut response: reqwest::Response, stream: &PlaybackStream, format: StreamFormat) -> Result<(), AppError> {
+    let mut encoded = Vec::new();
+    // 分块边界可能落在一个样本中间，多出的字节留到下一块
+    let mut carry: Option<u8> = None;
+
+    while let Some(chunk) = response.chunk().await? {
+        if stream.is_cancelled() {
+            return Ok(());
+        }
+        match format {
+            StreamFormat::Pcm { sample_rate, channels } => {
+                let mut bytes = Vec::with_capacity(chunk.len() + 1);
+                bytes.extend(carry.take());
+                bytes.extend_from_slice(&chunk);
+                if bytes.len() % 2 == 1 {
+                    carry = bytes.pop();
+                }
+                let samples = bytes
+                    .chunks_exact(2)
+                    .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                    .collect();
+                stream.push_pcm(samples, sample_rate, channels)?;
+            }
+            StreamFormat::Encoded(_) => encoded.extend_from_slice(&chunk),
+        }
+    }
+
+    if let StreamFormat::Encoded(format) = format {
+        stream.push_encoded(encoded, format)?;
+    }
+    Ok(())
+}
+
+// 返回播放 id：播放结束时收到 playback-finished，下载出错时收到 tts-stream-error
+pub async fn speak(
+    app: AppHandle,
+    text: String,
+    voice: Option<String>,
+    token: Option<String>,
+) -> Result<u64, AppError> {
+    let text = text.trim().to_string();
+    if text.is_empty() {
+        return Err(AppError::invalid("合成文本不能为空"));
+    }
+
+    let settings = app.state::<AppState>().settings.lock()?.audio.tts.clone();
+    let url = format!("{}{}", app.state::<BackendManager>().url(), settings.stream_path);
+    let body = StreamRequest {
+        text: &text,
+        voice: voice.as_deref().or(settings.voice.as_deref()),
+        format: "pcm",
+        sample_rate: settings.sample_rate,
+    };
+
+    let mut request = network::client(&app).post(&url).json(&body);
+    if let Some(token) = token {
+        request = request.bearer_auth(token);
+    }
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::network("无法连接合成服务", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("合成服务返回错误", response.status()));
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("audio/pcm")
+        .to_string();
+    let format = parse_content_type(&content_type, settings.sample_rate);
+    if let StreamFormat::Pcm { sample_rate: 0, .. } = format {
+        return Err(AppError::invalid("合成音频的采样率无效"));
+    }
+
+    let player = app.state::<AudioPlayer>();
+    let stream = player.open_stream(app.clone(), playback::playback_settings(&app)?)?;
+    let id = stream.id();
+    app.state::<TtsStreamer>().active.store(id, Ordering::SeqCst);
+    info!("Streaming TTS ({} chars, {})", text.chars().count(), content_type);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = pump(response, &stream, format).await {
+            warn!("TTS stream {} failed: {}", id, e);
+            events::publish(
+                &handle,
+                AppEvent::TtsStreamError(TtsStreamError {
+                    id,
+                    error: e.to_string(),
+                }),
+            );
+        }
+        handle
+            .state::<TtsStreamer>()
+            .active
+            .compare_exchange(id, 0, Ordering::SeqCst, Ordering::SeqCst)
+            .ok();
+    });
+
+    Ok(id)
+}
+
+#[tauri::command]
+pub async fn stream_tts(
+    app: AppHandle,
+    text: String,
+    voice: Option<String>,
+    token: Option<String>,
+) -> Result<u64, AppError> {
+    speak(app, text, voice, token).await
+}
+
+// 用户打断时停止下载和播放
+#[tauri::command]
+pub fn cancel_tts(streamer: State<'_, TtsStreamer>, player: State<'_, AudioPlayer>) -> bool {
+    let active = streamer.active.swap(0, Ordering::SeqCst);
+    if active == 0 {
+        return false;
+    }
+    player.stop_if(active)
+}
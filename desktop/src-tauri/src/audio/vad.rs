@@ -0,0 +1,137 @@
+use serde::{Deserialize, Serialize};
+
+// 基于短时能量的语音活动检测，噪声底随环境自适应
+const FRAME_MS: u32 = 20;
+// 连续多少帧超过阈值才判定为开始说话，过滤键盘声等短促噪声
+const START_FRAMES: u32 = 3;
+// 语音需比噪声底高出的分贝数
+const NOISE_MARGIN_DB: f32 = 10.0;
+const SILENCE_DB: f32 = -100.0;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct VadConfig {
+    pub enabled: bool,
+    // 绝对能量阈值（dBFS），低于该值一律视为静音
+    pub threshold_db: f32,
+    // 说话结束后持续静音多久判定为一句话结束
+    pub trailing_silence_ms: u32,
+    // 一句话结束后自动结束录音
+    pub auto_stop: bool,
+}
+
+impl Default for VadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_db: -45.0,
+            trailing_silence_ms: 800,
+            auto_stop: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VadEvent {
+    SpeechStarted,
+    SpeechEnded { duration_ms: u64 },
+}
+
+pub struct EnergyVad {
+    config: VadConfig,
+    frame_len: usize,
+    frame: Vec<f32>,
+    noise_floor_db: f32,
+    speaking: bool,
+    loud_frames: u32,
+    silent_ms: u32,
+    speech_ms: u64,
+}
+
+pub fn rms_db(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return SILENCE_DB;
+    }
+    let energy = samples.iter().map(|s| s * s).sum::<f32>() / samples.len() as f32;
+    if energy <= 0.0 {
+        return SILENCE_DB;
+    }
+    (10.0 * energy.log10()).max(SILENCE_DB)
+}
+
+impl EnergyVad {
+    pub fn new(config: VadConfig, sample_rate: u32) -> Self {
+        let frame_len = (sample_rate * FRAME_MS / 1000).max(1) as usize;
+        Self {
+            config,
+            frame_len,
+            frame: Vec::with_capacity(frame_len),
+            noise_floor_db: -60.0,
+            speaking: false,
+            loud_frames: 0,
+            silent_ms: 0,
+            speech_ms: 0,
+        }
+    }
+
+    pub fn auto_stop(&self) -> bool {
+        self.config.auto_stop
+    }
+
+    // 输入任意长度的样本，按帧检测并返回期间产生的事件
+    pub fn push(&mut self, samples: &[f32]) -> Vec<VadEvent> {
+        let mut events = Vec::new();
+        for &sample in samples {
+            self.frame.push(sample);
+            if self.frame.len() >= self.frame_len {
+                let db = rms_db(&self.frame);
+                self.frame.clear();
+                if let Some(event) = self.process_frame(db) {
+                    events.push(event);
+                }
+            }
+        }
+        events
+    }
+
+    fn process_frame(&mut self, db: f32) -> Option<VadEvent> {
+        let threshold = self.config.threshold_db.max(self.noise_floor_db + NOISE_MARGIN_DB);
+        let loud = db > threshold;
+
+        // 噪声底只在静音时跟踪：下降快、上升慢
+        if !loud {
+            let rate = if db < self.noise_floor_db { 0.2 } else { 0.01 };
+            self.noise_floor_db += (db - self.noise_floor_db) * rate;
+        }
+
+        if self.speaking {
+            self.speech_ms += FRAME_MS as u64;
+            if loud {
+                self.silent_ms = 0;
+                return None;
+            }
+            self.silent_ms += FRAME_MS;
+            if self.silent_ms >= self.config.trailing_silence_ms {
+                self.speaking = false;
+                self.silent_ms = 0;
+                let duration_ms = self.speech_ms.saturating_sub(self.config.trailing_silence_ms as u64);
+                self.speech_ms = 0;
+                return Some(VadEvent::SpeechEnded { duration_ms });
+            }
+            return None;
+        }
+
+        if loud {
+            self.loud_frames += 1;
+            if self.loud_frames >= START_FRAMES {
+                self.speaking = true;
+                self.loud_frames = 0;
+                self.speech_ms = (START_FRAMES * FRAME_MS) as u64;
+                return Some(VadEvent::SpeechStarted);
+            }
+        } else {
+            self.loud_frames = 0;
+        }
+        None
+    }
+}
@@ -0,0 +1,482 @@
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{FromSample, SizedSample};
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::{error, info, warn};
+
+use super::capture::{build_stream, find_device, host_device_id, pick_config};
+use super::indicator::{self, MicSource};
+use super::meter::{AudioMeter, LevelMeter, LevelSource};
+use super::Resampler;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::pet::state::PetEvent;
+use crate::power::{self, PowerConsumer};
+use crate::{analytics, dnd, pet, privacy, resources, settings, tray, AppState};
+
+// 唤醒词引擎统一使用 16 kHz 单声道输入
+pub const ENGINE_SAMPLE_RATE: u32 = 16_000;
+// 触发后的冷却时间，避免一次唤醒被重复识别
+const TRIGGER_COOLDOWN: Duration = Duration::from_secs(2);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+// 资源节流时降低处理频率，积压的音频合并成一批交给引擎
+const THROTTLED_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WakeWordConfig {
+    pub enabled: bool,
+    pub keyword: String,
+    // 0 - 1，越高越容易触发
+    pub sensitivity: f32,
+    // 外部检测程序的命令行，参数中的 {keyword}、{sensitivity} 会被替换
+    pub engine: Vec<String>,
+    pub ack_sound: bool,
+}
+
+impl Default for WakeWordConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            keyword: "小驭小驭".to_string(),
+            sensitivity: 0.5,
+            engine: Vec::new(),
+            ack_sound: true,
+        }
+    }
+}
+
+impl WakeWordConfig {
+    fn threshold(&self) -> f32 {
+        1.0 - self.sensitivity.clamp(0.0, 1.0)
+    }
+}
+
+// 可插拔的唤醒词检测引擎
+pub trait WakeWordEngine: Send {
+    fn name(&self) -> &str;
+    // 输入 16 kHz 单声道样本，返回本批数据中检测到的最高置信度（0 - 1）
+    fn process(&mut self, samples: &[f32]) -> Option<f32>;
+}
+
+// 通过子进程运行的检测引擎（如 openWakeWord、Porcupine 的封装脚本）：
+// 标准输入接收 16 位小端 PCM，每检测到一次在标准输出打印一行置信度
+pub struct SidecarEngine {
+    name: String,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    scores: mpsc::Receiver<f32>,
+}
+
+impl SidecarEngine {
+    pub fn spawn(config: &WakeWordConfig) -> Result<Self, AppError> {
+        let (program, args) = config
+            .engine
+            .split_first()
+            .ok_or_else(|| "未配置唤醒词引擎".to_string())?;
+        let args: Vec<String> = args
+            .iter()
+            .map(|arg| {
+                arg.replace("{keyword}", &config.keyword)
+                    .replace("{sensitivity}", &config.sensitivity.to_string())
+            })
+            .collect();
+
+        let mut child = Command::new(program)
+            .args(&args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("无法启动唤醒词引擎 {}: {}", program, e))?;
+
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().ok_or("无法读取唤醒词引擎输出")?;
+        let (tx, scores) = mpsc::channel();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if let Some(score) = parse_score(&line) {
+                    if tx.send(score).is_err() {
+                        break;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            name: program.clone(),
+            child,
+            stdin,
+            scores,
+        })
+    }
+}
+
+// 支持 "0.83" 或 "detected 0.83" 两种输出，只有 "detected" 时视为置信度 1
+fn parse_score(line: &str) -> Option<f32> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    match line.split_whitespace().last()?.parse::<f32>() {
+        Ok(score) => Some(score.clamp(0.0, 1.0)),
+        Err(_) if line.eq_ignore_ascii_case("detected") => Some(1.0),
+        Err(_) => None,
+    }
+}
+
+impl WakeWordEngine for SidecarEngine {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn process(&mut self, samples: &[f32]) -> Option<f32> {
+        if let Some(stdin) = self.stdin.as_mut() {
+            let bytes: Vec<u8> = samples
+                .iter()
+                .flat_map(|s| ((s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes())
+                .collect();
+            if let Err(e) = stdin.write_all(&bytes) {
+                warn!("Wake word engine stopped accepting audio: {}", e);
+                self.stdin = None;
+            }
+        }
+
+        let mut best: Option<f32> = None;
+        while let Ok(score) = self.scores.try_recv() {
+            best = Some(best.map_or(score, |b: f32| b.max(score)));
+        }
+        best
+    }
+}
+
+impl Drop for SidecarEngine {
+    fn drop(&mut self) {
+        self.stdin = None;
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeWordDetected {
+    pub keyword: String,
+    pub score: f32,
+    pub engine: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WakeWordStatus {
+    pub enabled: bool,
+    pub running: bool,
+    pub keyword: String,
+    pub sensitivity: f32,
+    pub engine_configured: bool,
+}
+
+struct ListenerHandle {
+    stop: Arc<AtomicBool>,
+    worker: JoinHandle<()>,
+}
+
+// 后台持续监听麦克风并交给唤醒词引擎检测
+#[derive(Default)]
+pub struct WakeWordListener {
+    handle: Mutex<Option<ListenerHandle>>,
+}
+
+impl WakeWordListener {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.handle
+            .lock()
+            .map(|handle| handle.as_ref().is_some_and(|h| !h.worker.is_finished()))
+            .unwrap_or(false)
+    }
+
+    pub fn start(&self, app: AppHandle, config: WakeWordConfig) -> Result<(), AppError> {
+        if privacy::is_active(&app) {
+            return Err(AppError::PermissionDenied("隐私模式下已暂停唤醒词监听".to_string()));
+        }
+        self.stop();
+        let engine = SidecarEngine::spawn(&config)?;
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let (ready_tx, ready_rx) = mpsc::channel();
+        let worker_stop = stop.clone();
+        let worker = std::thread::spawn(move || {
+            run_listener(app, config, Box::new(engine), worker_stop, ready_tx);
+        });
+
+        match ready_rx.recv() {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => {
+                worker.join().ok();
+                return Err(e);
+            }
+            Err(_) => {
+                worker.join().ok();
+                return Err(AppError::Internal("唤醒词监听线程意外退出".to_string()));
+            }
+        }
+
+        *self.handle.lock()? = Some(ListenerHandle { stop, worker });
+        Ok(())
+    }
+
+    pub fn stop(&self) {
+        let handle = self.handle.lock().ok().and_then(|mut handle| handle.take());
+        if let Some(handle) = handle {
+            handle.stop.store(true, Ordering::SeqCst);
+            handle.worker.join().ok();
+            info!("Wake word listener stopped");
+        }
+    }
+}
+
+fn run_listener(
+    app: AppHandle,
+    config: WakeWordConfig,
+    mut engine: Box<dyn WakeWordEngine>,
+    stop: Arc<AtomicBool>,
+    ready: mpsc::Sender<Result<(), AppError>>,
+) {
+    let (tx, rx) = mpsc::channel::<Vec<f32>>();
+    let opened = find_device(None).and_then(|device| {
+        // 尽量直接以 16 kHz 采集，降低开销
+        let (stream_config, format) = pick_config(&device, Some(ENGINE_SAMPLE_RATE))?;
+        let stream = match format {
+            cpal::SampleFormat::F32 => build_stream::<f32>(&device, &stream_config, tx),
+            cpal::SampleFormat::I16 => build_stream::<i16>(&device, &stream_config, tx),
+            cpal::SampleFormat::U16 => build_stream::<u16>(&device, &stream_config, tx),
+            other => Err(AppError::unavailable(
+                "音频设备不可用",
+                format!("不支持的采样格式: {:?}", other),
+            )),
+        }?;
+        stream.play()?;
+        info!(
+            "Wake word listener started on {} ({} Hz, engine {})",
+            host_device_id(&device),
+            stream_config.sample_rate.0,
+            engine.name()
+        );
+        Ok((stream, stream_config.sample_rate.0))
+    });
+
+    let (stream, device_rate) = match opened {
+        Ok(opened) => opened,
+        Err(e) => {
+            ready.send(Err(e)).ok();
+            return;
+        }
+    };
+    let _mic = indicator::acquire(&app, MicSource::WakeWord);
+    ready.send(Ok(())).ok();
+
+    let threshold = config.threshold();
+    let mut resampler = Resampler::new(device_rate, ENGINE_SAMPLE_RATE);
+    let mut meter = LevelMeter::new(LevelSource::WakeWord, device_rate, app.state::<AudioMeter>().flag());
+    let mut last_trigger: Option<Instant> = None;
+
+    while !stop.load(Ordering::SeqCst) {
+        let mut frame = match rx.recv_timeout(POLL_INTERVAL) {
+            Ok(frame) => frame,
+            Err(mpsc::RecvTimeoutError::Timeout) => continue,
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+        };
+        if resources::is_throttled(&app) {
+            std::thread::sleep(THROTTLED_POLL_INTERVAL);
+            frame.extend(rx.try_iter().flatten());
+        }
+
+        for level in meter.push(&frame) {
+            events::publish(&app, AppEvent::AudioLevel(level));
+        }
+        let samples = resampler.process(&frame);
+        let Some(score) = engine.process(&samples) else {
+            continue;
+        };
+        let cooling = last_trigger.is_some_and(|at| at.elapsed() < TRIGGER_COOLDOWN);
+        if score < threshold || cooling {
+            continue;
+        }
+
+        last_trigger = Some(Instant::now());
+        on_detected(&app, &config, score, engine.name());
+    }
+
+    drop(stream);
+}
+
+fn on_detected(app: &AppHandle, config: &WakeWordConfig, score: f32, engine: &str) {
+    if dnd::is_active(app) {
+        info!("Wake word ignored during do not disturb (score {:.2})", score);
+        return;
+    }
+    info!("Wake word detected (score {:.2})", score);
+    analytics::record(app, analytics::KIND_WAKE_WORD, engine, None);
+    tray::show_main_window(app);
+    if config.ack_sound {
+        std::thread::spawn(|| {
+            if let Err(e) = play_ack_tone() {
+                warn!("Failed to play wake word acknowledgment: {}", e);
+            }
+        });
+    }
+
+    let payload = WakeWordDetected {
+        keyword: config.keyword.clone(),
+        score,
+        engine: engine.to_string(),
+    };
+    pet::state::notify(app, PetEvent::ListeningStarted);
+    events::publish(app, AppEvent::WakeWordDetected(payload));
+}
+
+// 唤醒提示音：带淡入淡出的短促正弦波
+fn play_ack_tone() -> Result<(), AppError> {
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("没有可用的输出设备")?;
+    let config = device.default_output_config()?;
+    let stream = match config.sample_format() {
+        cpal::SampleFormat::F32 => tone_stream::<f32>(&device, &config.config()),
+        cpal::SampleFormat::I16 => tone_stream::<i16>(&device, &config.config()),
+        cpal::SampleFormat::U16 => tone_stream::<u16>(&device, &config.config()),
+        other => Err(AppError::unavailable(
+            "音频设备不可用",
+            format!("不支持的采样格式: {:?}", other),
+        )),
+    }?;
+    stream.play()?;
+    std::thread::sleep(Duration::from_millis(250));
+    Ok(())
+}
+
+fn tone_stream<T>(device: &cpal::Device, config: &cpal::StreamConfig) -> Result<cpal::Stream, AppError>
+where
+    T: SizedSample + FromSample<f32>,
+{
+    const FREQUENCY: f32 = 880.0;
+    const DURATION_SECS: f32 = 0.18;
+    let rate = config.sample_rate.0 as f32;
+    let channels = config.channels.max(1) as usize;
+    let total = (rate * DURATION_SECS) as usize;
+    let mut index = 0usize;
+
+    device
+        .build_output_stream::<T, _, _>(
+            config,
+            move |data: &mut [T], _| {
+                for frame in data.chunks_mut(channels) {
+                    let value = if index < total {
+                        let t = index as f32 / rate;
+                        let fade = (index.min(total - index) as f32 / (rate * 0.02)).min(1.0);
+                        (t * FREQUENCY * std::f32::consts::TAU).sin() * 0.3 * fade
+                    } else {
+                        0.0
+                    };
+                    index += 1;
+                    for sample in frame.iter_mut() {
+                        *sample = T::from_sample(value);
+                    }
+                }
+            },
+            |e| error!("Audio output stream error: {}", e),
+            None,
+        )
+        .map_err(AppError::from)
+}
+
+fn wake_word_config(app: &AppHandle) -> Result<WakeWordConfig, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.audio.wake_word.clone())
+}
+
+fn save_wake_word_config(app: &AppHandle, config: &WakeWordConfig) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.audio.wake_word = config.clone();
+    settings::save_and_notify(app, &settings, &["audio"])
+}
+
+// 启动时按设置恢复唤醒词监听
+pub fn start_if_enabled(app: &AppHandle) {
+    let Ok(config) = wake_word_config(app) else {
+        return;
+    };
+    if !config.enabled || privacy::is_active(app) || !power::allowed(app, PowerConsumer::WakeWord) {
+        return;
+    }
+    if let Err(e) = app.state::<WakeWordListener>().start(app.clone(), config) {
+        warn!("Failed to start wake word listener: {}", e);
+    }
+}
+
+// 电源策略变化时暂停或恢复监听，不修改 enabled
+pub fn on_power_changed(app: &AppHandle, allowed: bool) {
+    if allowed {
+        start_if_enabled(app);
+    } else {
+        app.state::<WakeWordListener>().stop();
+    }
+}
+
+#[tauri::command]
+pub fn get_wake_word_status(app: AppHandle, listener: State<'_, WakeWordListener>) -> Result<WakeWordStatus, AppError> {
+    let config = wake_word_config(&app)?;
+    Ok(WakeWordStatus {
+        enabled: config.enabled,
+        running: listener.is_running(),
+        keyword: config.keyword,
+        sensitivity: config.sensitivity,
+        engine_configured: !config.engine.is_empty(),
+    })
+}
+
+#[tauri::command]
+pub fn set_wake_word_enabled(
+    app: AppHandle,
+    listener: State<'_, WakeWordListener>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let mut config = wake_word_config(&app)?;
+    // 电源策略暂不允许时只保存开关，切换到允许的状态后自动开始监听
+    if enabled && power::allowed(&app, PowerConsumer::WakeWord) {
+        listener.start(app.clone(), config.clone())?;
+    } else if !enabled {
+        listener.stop();
+    }
+    config.enabled = enabled;
+    save_wake_word_config(&app, &config)
+}
+
+// 灵敏度会传给外部引擎，运行中修改时重启监听
+#[tauri::command]
+pub fn set_wake_word_sensitivity(
+    app: AppHandle,
+    listener: State<'_, WakeWordListener>,
+    sensitivity: f32,
+) -> Result<(), AppError> {
+    if !(0.0..=1.0).contains(&sensitivity) {
+        return Err(AppError::invalid("灵敏度需在 0 - 1 之间"));
+    }
+    let mut config = wake_word_config(&app)?;
+    config.sensitivity = sensitivity;
+    save_wake_word_config(&app, &config)?;
+
+    if listener.is_running() {
+        listener.start(app.clone(), config)?;
+    }
+    Ok(())
+}
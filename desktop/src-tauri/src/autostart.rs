@@ -0,0 +1,70 @@
+use auto_launch::{AutoLaunch, AutoLaunchBuilder};
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::{settings, AppState};
+
+// 开机自启时附带的参数，启动后只显示桌宠，主窗口隐藏到托盘
+pub const MINIMIZED_ARG: &str = "--minimized";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    pub minimized: bool,
+}
+
+fn auto_launch(minimized: bool) -> Result<AutoLaunch, AppError> {
+    let exe = std::env::current_exe()?;
+    let args: &[&str] = if minimized { &[MINIMIZED_ARG] } else { &[] };
+
+    AutoLaunchBuilder::new()
+        .set_app_name("LingEcho")
+        .set_app_path(&exe.to_string_lossy())
+        .set_use_launch_agent(true)
+        .set_args(args)
+        .build()
+        .map_err(launch_error)
+}
+
+fn launch_error(error: auto_launch::Error) -> AppError {
+    AppError::unavailable("无法访问系统自启动设置", error)
+}
+
+pub fn launched_minimized() -> bool {
+    std::env::args().any(|arg| arg == MINIMIZED_ARG)
+}
+
+pub fn is_enabled(app: &AppHandle) -> Result<bool, AppError> {
+    let minimized = app.state::<AppState>().settings.lock()?.autostart_minimized;
+    auto_launch(minimized)?.is_enabled().map_err(launch_error)
+}
+
+#[tauri::command]
+pub fn get_autostart(app: AppHandle) -> Result<AutostartStatus, AppError> {
+    let enabled = is_enabled(&app)?;
+    let minimized = app.state::<AppState>().settings.lock()?.autostart_minimized;
+    Ok(AutostartStatus { enabled, minimized })
+}
+
+// 启动参数写在系统的自启项中，切换 minimized 时需要重新注册
+#[tauri::command]
+pub fn set_autostart(app: AppHandle, enabled: bool, minimized: Option<bool>) -> Result<AutostartStatus, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let minimized = minimized.unwrap_or(settings.autostart_minimized);
+
+    let launcher = auto_launch(minimized)?;
+    if launcher.is_enabled().map_err(launch_error)? {
+        launcher.disable().map_err(launch_error)?;
+    }
+    if enabled {
+        launcher.enable().map_err(launch_error)?;
+    }
+
+    settings.autostart_minimized = minimized;
+    settings::save_and_notify(&app, &settings, &["autostart_minimized"])?;
+    info!("Autostart enabled: {}, minimized: {}", enabled, minimized);
+    Ok(AutostartStatus { enabled, minimized })
+}
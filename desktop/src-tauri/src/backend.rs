@@ -0,0 +1,497 @@
+use std::collections::VecDeque;
+use std::io::{BufRead, BufReader, Read};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{error, info, warn};
+
+use serde::Serialize;
+use tauri::Manager;
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::network;
+use crate::settings::BackendSettings;
+
+// 停止后端时等待进程退出的最长时间，超时后强制结束
+const STOP_TIMEOUT: Duration = Duration::from_secs(3);
+
+// Windows 下避免子进程弹出控制台窗口
+#[cfg(windows)]
+pub(crate) const CREATE_NO_WINDOW: u32 = 0x0800_0000;
+
+// 环境变量优先于设置文件中的端口
+const PORT_ENV: &str = "LINGECHO_BACKEND_PORT";
+// 首选端口被占用时，先在其后若干端口中查找，仍不可用再交给系统分配
+const PORT_SCAN_RANGE: u16 = 20;
+const HEALTH_PATH: &str = "/api/system/health";
+
+// 健康检查间隔与连续失败多少次后自动重启
+const HEALTH_INTERVAL: Duration = Duration::from_secs(5);
+const HEALTH_TIMEOUT: Duration = Duration::from_secs(2);
+const RESTART_AFTER_FAILURES: u32 = 3;
+// 启动后的宽限期，go run 首次编译较慢，期间的失败不计入重启判断
+const STARTUP_GRACE: Duration = Duration::from_secs(30);
+const RESTART_BACKOFF_BASE: Duration = Duration::from_secs(2);
+const RESTART_BACKOFF_MAX: Duration = Duration::from_secs(60);
+
+// 内存中保留的后端日志行数
+const LOG_BUFFER_LINES: usize = 2000;
+
+// 打包进资源目录的后端程序所在子目录
+const BUNDLED_DIR: &str = "backend";
+const BUNDLED_NAME: &str = "lingecho-server";
+
+// 后端进程的启动方式
+#[derive(Debug, Clone)]
+pub struct BackendLaunch {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub cwd: PathBuf,
+    pub port: u16,
+}
+
+impl BackendLaunch {
+    // 优先使用资源目录中按平台命名的后端程序，开发构建下回退到 go run ../server
+    pub fn resolve(app: &tauri::AppHandle, settings: &BackendSettings) -> Result<Self, AppError> {
+        let port = select_port(preferred_port(settings))?;
+        let mut args = vec![format!("-mode={}", settings.mode), format!("-addr=:{}", port)];
+        args.extend(settings.extra_args.iter().cloned());
+
+        if let Some(program) = bundled_binary(app, BUNDLED_DIR, BUNDLED_NAME) {
+            let cwd = program
+                .parent()
+                .map(Path::to_path_buf)
+                .unwrap_or_else(|| PathBuf::from("."));
+            return Ok(Self { program, args, cwd, port });
+        }
+
+        if cfg!(debug_assertions) {
+            return Self::go_run(args, port);
+        }
+
+        Err(AppError::NotFound(format!(
+            "Bundled backend binary not found in resources/{}",
+            BUNDLED_DIR
+        )))
+    }
+
+    fn go_run(args: Vec<String>, port: u16) -> Result<Self, AppError> {
+        // 检查 Go 是否安装
+        let go_available = Command::new("go").arg("version").output().is_ok();
+        if !go_available {
+            return Err(AppError::NotFound("Go is not installed or not in PATH".to_string()));
+        }
+
+        // 检查 server 目录是否存在
+        let server_path = Path::new("../server");
+        if !server_path.exists() {
+            return Err(AppError::NotFound("Server directory not found".to_string()));
+        }
+
+        let mut go_args = vec!["run".to_string(), "cmd/server/main.go".to_string()];
+        go_args.extend(args);
+        Ok(Self {
+            program: PathBuf::from("go"),
+            args: go_args,
+            cwd: server_path.to_path_buf(),
+            port,
+        })
+    }
+}
+
+fn preferred_port(settings: &BackendSettings) -> u16 {
+    match std::env::var(PORT_ENV).map(|value| value.parse::<u16>()) {
+        Ok(Ok(port)) if port != 0 => port,
+        Ok(_) => {
+            warn!("Ignoring invalid {}, using port {}", PORT_ENV, settings.port);
+            settings.port
+        }
+        Err(_) => settings.port,
+    }
+}
+
+fn port_available(port: u16) -> bool {
+    TcpListener::bind(("127.0.0.1", port)).is_ok() && TcpListener::bind(("0.0.0.0", port)).is_ok()
+}
+
+fn select_port(preferred: u16) -> Result<u16, AppError> {
+    if port_available(preferred) {
+        return Ok(preferred);
+    }
+
+    let scanned = (1..=PORT_SCAN_RANGE)
+        .filter_map(|offset| preferred.checked_add(offset))
+        .find(|port| port_available(*port));
+    let port = match scanned {
+        Some(port) => port,
+        None => TcpListener::bind(("127.0.0.1", 0))
+            .and_then(|listener| listener.local_addr())
+            .map(|addr| addr.port())
+            .map_err(|e| format!("Failed to find a free port: {}", e))?,
+    };
+
+    info!("Port {} is in use, backend will listen on {}", preferred, port);
+    Ok(port)
+}
+
+// 依次查找 lingecho-server-<os>-<arch> 与 lingecho-server
+// 资源目录 dir 下按平台命名的程序 name-<os>-<arch>，其次是不带平台后缀的 name
+pub(crate) fn bundled_binary(app: &tauri::AppHandle, dir: &str, name: &str) -> Option<PathBuf> {
+    let suffix = std::env::consts::EXE_SUFFIX;
+    let candidates = [
+        format!(
+            "{}-{}-{}{}",
+            name,
+            std::env::consts::OS,
+            std::env::consts::ARCH,
+            suffix
+        ),
+        format!("{}{}", name, suffix),
+    ];
+
+    candidates.iter().find_map(|name| {
+        app.path_resolver()
+            .resolve_resource(format!("{}/{}", dir, name))
+            .filter(|path| path.is_file())
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendLogLine {
+    pub stream: &'static str,
+    pub line: String,
+    pub timestamp: u64,
+}
+
+type LogBuffer = Arc<Mutex<VecDeque<BackendLogLine>>>;
+
+// 逐行读取后端输出，写入环形缓冲区并转发给前端
+fn spawn_log_reader<R: Read + Send + 'static>(
+    reader: R,
+    stream: &'static str,
+    buffer: LogBuffer,
+    app: Option<tauri::AppHandle>,
+) {
+    std::thread::spawn(move || {
+        let mut reader = BufReader::new(reader);
+        let mut raw = Vec::new();
+        loop {
+            raw.clear();
+            match reader.read_until(b'\n', &mut raw) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+
+            let line = String::from_utf8_lossy(&raw).trim_end().to_string();
+            info!(target: "backend", "[{}] {}", stream, line);
+            let entry = BackendLogLine {
+                stream,
+                line,
+                timestamp: crate::data::now_secs(),
+            };
+
+            if let Some(app) = &app {
+                events::publish(app, AppEvent::BackendLog(entry.clone()));
+            }
+            if let Ok(mut buffer) = buffer.lock() {
+                if buffer.len() >= LOG_BUFFER_LINES {
+                    buffer.pop_front();
+                }
+                buffer.push_back(entry);
+            }
+        }
+    });
+}
+
+// 管理 Go 后端子进程的生命周期
+#[derive(Default)]
+pub struct BackendManager {
+    child: Mutex<Option<Child>>,
+    launch: Mutex<Option<BackendLaunch>>,
+    app: Mutex<Option<tauri::AppHandle>>,
+    logs: LogBuffer,
+    // 用户期望后端处于运行状态，手动停止后健康检查不会自动拉起
+    desired: AtomicBool,
+    started_at: Mutex<Option<Instant>>,
+}
+
+impl BackendManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn port(&self) -> u16 {
+        self.launch
+            .lock()
+            .ok()
+            .and_then(|launch| launch.as_ref().map(|launch| launch.port))
+            .unwrap_or(BackendSettings::default().port)
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://localhost:{}", self.port())
+    }
+
+    pub fn configure(&self, app: tauri::AppHandle, launch: BackendLaunch) -> Result<(), AppError> {
+        info!("Backend launch: {} {}", launch.program.display(), launch.args.join(" "));
+        *self.launch.lock()? = Some(launch);
+        *self.app.lock()? = Some(app);
+        Ok(())
+    }
+
+    // 返回最近的 lines 行后端日志
+    pub fn recent_logs(&self, lines: usize) -> Vec<BackendLogLine> {
+        let Ok(buffer) = self.logs.lock() else {
+            return Vec::new();
+        };
+        let skip = buffer.len().saturating_sub(lines);
+        buffer.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        let mut guard = self.child.lock().ok()?;
+        let child = guard.as_mut()?;
+        matches!(child.try_wait(), Ok(None)).then(|| child.id())
+    }
+
+    pub fn is_running(&self) -> bool {
+        let Ok(mut guard) = self.child.lock() else {
+            return false;
+        };
+        match guard.as_mut().map(|child| child.try_wait()) {
+            Some(Ok(None)) => true,
+            Some(_) => {
+                // 进程已退出，清理句柄
+                *guard = None;
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn in_startup_grace(&self) -> bool {
+        self.started_at
+            .lock()
+            .ok()
+            .and_then(|started| *started)
+            .map(|started| started.elapsed() < STARTUP_GRACE)
+            .unwrap_or(false)
+    }
+
+    pub fn start(&self) -> Result<u32, AppError> {
+        self.desired.store(true, Ordering::SeqCst);
+        let mut guard = self.child.lock()?;
+        if let Some(child) = guard.as_mut() {
+            if let Ok(None) = child.try_wait() {
+                return Ok(child.id());
+            }
+        }
+
+        let launch = self.launch.lock()?.clone().ok_or("Backend launch is not configured")?;
+
+        let mut command = Command::new(&launch.program);
+        command
+            .args(&launch.args)
+            .current_dir(&launch.cwd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // go run 会再派生编译出的服务进程，放到独立进程组中以便整体结束
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+            command.process_group(0);
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+
+        let mut child = command
+            .spawn()
+            .map_err(|e| format!("Failed to start backend server: {}", e))?;
+        let pid = child.id();
+
+        // 必须持续读取管道，否则缓冲区写满后后端会阻塞
+        let app = self.app.lock().ok().and_then(|app| app.clone());
+        if let Some(stdout) = child.stdout.take() {
+            spawn_log_reader(stdout, "stdout", self.logs.clone(), app.clone());
+        }
+        if let Some(stderr) = child.stderr.take() {
+            spawn_log_reader(stderr, "stderr", self.logs.clone(), app);
+        }
+        info!("Backend server started on port {} (pid {})", launch.port, pid);
+        *guard = Some(child);
+        if let Ok(mut started) = self.started_at.lock() {
+            *started = Some(Instant::now());
+        }
+        Ok(pid)
+    }
+
+    pub fn stop(&self) -> Result<(), AppError> {
+        self.desired.store(false, Ordering::SeqCst);
+        let mut guard = self.child.lock()?;
+        let Some(mut child) = guard.take() else {
+            return Ok(());
+        };
+
+        if let Ok(Some(_)) = child.try_wait() {
+            return Ok(());
+        }
+
+        let pid = child.id();
+        terminate_tree(pid);
+
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        while Instant::now() < deadline {
+            if let Ok(Some(_)) = child.try_wait() {
+                info!("Go backend server stopped (pid {})", pid);
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        // 超时仍未退出，强制结束
+        kill_tree(pid);
+        child.kill().ok();
+        child.wait()?;
+        warn!("Go backend server killed after timeout (pid {})", pid);
+        Ok(())
+    }
+
+    pub fn restart(&self) -> Result<u32, AppError> {
+        self.stop()?;
+        self.start()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BackendStatus {
+    pub online: bool,
+    pub running: bool,
+    pub consecutive_failures: u32,
+}
+
+pub async fn ping(client: &reqwest::Client, base_url: &str) -> bool {
+    match client
+        .get(format!("{}{}", base_url, HEALTH_PATH))
+        .timeout(HEALTH_TIMEOUT)
+        .send()
+        .await
+    {
+        Ok(response) => response.status().is_success(),
+        Err(_) => false,
+    }
+}
+
+// 后台定期检查后端健康状态，状态变化时通知所有窗口，连续失败后按指数退避自动重启
+pub fn spawn_health_monitor(app: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut last_status: Option<BackendStatus> = None;
+        let mut failures = 0u32;
+        let mut restarts = 0u32;
+        let mut next_restart = Instant::now();
+
+        loop {
+            tokio::time::sleep(HEALTH_INTERVAL).await;
+
+            let manager = app.state::<BackendManager>();
+            let online = ping(&network::client(&app), &manager.url()).await;
+            if online {
+                failures = 0;
+                restarts = 0;
+            } else if !manager.in_startup_grace() {
+                failures += 1;
+            }
+
+            let status = BackendStatus {
+                online,
+                running: manager.is_running(),
+                consecutive_failures: failures,
+            };
+            if last_status.as_ref() != Some(&status) {
+                events::publish(&app, AppEvent::BackendStatusChanged(status.clone()));
+                last_status = Some(status);
+            }
+
+            let should_restart = failures >= RESTART_AFTER_FAILURES
+                && manager.desired.load(Ordering::SeqCst)
+                && Instant::now() >= next_restart;
+            if !should_restart {
+                continue;
+            }
+
+            let backoff = RESTART_BACKOFF_BASE
+                .saturating_mul(2u32.saturating_pow(restarts))
+                .min(RESTART_BACKOFF_MAX);
+            restarts = restarts.saturating_add(1);
+            next_restart = Instant::now() + backoff;
+            failures = 0;
+            warn!(
+                "Backend unhealthy, restarting (attempt {}, next retry in {:?})",
+                restarts, backoff
+            );
+
+            let handle = app.clone();
+            let result = tauri::async_runtime::spawn_blocking(move || {
+                handle.state::<BackendManager>().restart()
+            })
+            .await;
+            match result {
+                Ok(Ok(pid)) => info!("Backend restarted (pid {})", pid),
+                Ok(Err(e)) => warn!("Failed to restart backend: {}", e),
+                Err(e) => error!("Backend restart task failed: {}", e),
+            }
+        }
+    });
+}
+
+impl Drop for BackendManager {
+    fn drop(&mut self) {
+        self.stop().ok();
+    }
+}
+
+// 请求整个进程树正常退出
+#[cfg(unix)]
+fn terminate_tree(pid: u32) {
+    Command::new("kill")
+        .arg("-TERM")
+        .arg(format!("-{}", pid))
+        .status()
+        .ok();
+}
+
+#[cfg(windows)]
+fn terminate_tree(pid: u32) {
+    taskkill(&["/T", "/PID", &pid.to_string()]);
+}
+
+#[cfg(unix)]
+fn kill_tree(pid: u32) {
+    Command::new("kill")
+        .arg("-KILL")
+        .arg(format!("-{}", pid))
+        .status()
+        .ok();
+}
+
+#[cfg(windows)]
+fn kill_tree(pid: u32) {
+    taskkill(&["/T", "/F", "/PID", &pid.to_string()]);
+}
+
+#[cfg(windows)]
+fn taskkill(args: &[&str]) {
+    use std::os::windows::process::CommandExt;
+    Command::new("taskkill")
+        .args(args)
+        .creation_flags(CREATE_NO_WINDOW)
+        .status()
+        .ok();
+}
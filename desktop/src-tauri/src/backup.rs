@@ -0,0 +1,427 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+use zip::write::FileOptions;
+
+use crate::data::now_secs;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::profiles;
+use crate::settings::{Settings, SettingsStore};
+use crate::storage::DATABASE_FILE;
+use crate::{shutdown, AppState};
+
+pub const BACKUP_VERSION: u32 = 1;
+
+const DEFAULT_DIR: &str = "backups";
+const FILE_PREFIX: &str = "lingecho-backup-";
+const ZIP_MANIFEST: &str = "manifest.json";
+const ZIP_DATABASE: &str = "lingecho.db";
+const ZIP_SETTINGS: &str = "settings.json";
+// 待恢复的文件先放在数据目录下，下次启动、打开数据库之前替换
+const RESTORE_DIR: &str = "restore-pending";
+// 恢复时旧文件先改名为此后缀，失败时改回
+const RESTORE_ASIDE: &str = ".restore-old";
+// 校验时整个读入内存，限制单个文件的大小，避免异常的备份占满内存
+const MAX_MANIFEST_BYTES: u64 = 1024 * 1024;
+const MAX_ENTRY_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+// 后台任务最长休眠的时间，修改备份设置后最迟在这之后生效
+const IDLE_POLL: Duration = Duration::from_secs(60 * 60);
+
+// 同一时间只进行一次备份或恢复
+static BACKUP_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    // 备份目录，None 表示应用数据目录下的 backups
+    pub directory: Option<String>,
+    pub interval_hours: u32,
+    // 保留最近的备份数量，0 表示全部保留
+    pub keep: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            directory: None,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupEntry {
+    name: String,
+    size: u64,
+    sha256: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BackupManifest {
+    version: u32,
+    app_version: String,
+    created_at: u64,
+    files: Vec<BackupEntry>,
+}
+
+// created_at 为秒级时间戳
+#[derive(Debug, Clone, Serialize)]
+pub struct BackupInfo {
+    pub path: String,
+    pub file_name: String,
+    pub created_at: u64,
+    pub size: u64,
+}
+
+fn sha256(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn zip_error(error: zip::result::ZipError) -> AppError {
+    match error {
+        zip::result::ZipError::Io(e) => e.into(),
+        other => AppError::invalid(format!("备份文件格式无效: {}", other)),
+    }
+}
+
+fn data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    profiles::data_dir(app)
+}
+
+pub fn backup_dir(app: &AppHandle, settings: &BackupSettings) -> Result<PathBuf, AppError> {
+    match settings.directory.as_deref().filter(|dir| !dir.trim().is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => Ok(data_dir(app)?.join(DEFAULT_DIR)),
+    }
+}
+
+fn backup_info(path: &Path) -> Option<BackupInfo> {
+    let file_name = path.file_name()?.to_string_lossy().to_string();
+    let created_at = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(".zip")?
+        .parse()
+        .ok()?;
+    Some(BackupInfo {
+        path: path.to_string_lossy().to_string(),
+        file_name,
+        created_at,
+        size: std::fs::metadata(path).map(|meta| meta.len()).unwrap_or(0),
+    })
+}
+
+// 按时间从新到旧排列
+pub fn list(dir: &Path) -> Result<Vec<BackupInfo>, AppError> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    let mut backups: Vec<BackupInfo> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| backup_info(&entry.path()))
+        .collect();
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created_at));
+    Ok(backups)
+}
+
+fn prune(dir: &Path, keep: u32) -> Result<(), AppError> {
+    if keep == 0 {
+        return Ok(());
+    }
+    for backup in list(dir)?.into_iter().skip(keep as usize) {
+        match std::fs::remove_file(&backup.path) {
+            Ok(()) => info!("Removed old backup {}", backup.file_name),
+            Err(e) => warn!("Failed to remove old backup {}: {}", backup.file_name, e),
+        }
+    }
+    Ok(())
+}
+
+// 数据库使用 WAL 模式，通过 VACUUM INTO 得到一致的快照，不会阻塞正在进行的写入
+fn snapshot_database(data_dir: &Path, target: &Path) -> Result<Vec<u8>, AppError> {
+    let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+    conn.execute("VACUUM INTO ?1", [target.to_string_lossy()])?;
+    drop(conn);
+    let bytes = std::fs::read(target);
+    std::fs::remove_file(target).ok();
+    Ok(bytes?)
+}
+
+// 在阻塞线程中调用
+pub fn create(app: &AppHandle) -> Result<BackupInfo, AppError> {
+    let _guard = BACKUP_LOCK.lock()?;
+    let state = app.state::<AppState>();
+    let (settings, backup_settings) = {
+        let settings = state.settings.lock()?;
+        (serde_json::to_vec_pretty(&*settings)?, settings.backup.clone())
+    };
+    let dir = backup_dir(app, &backup_settings)?;
+    std::fs::create_dir_all(&dir).map_err(|e| AppError::io(format!("无法创建备份目录 {}", dir.display()), e))?;
+
+    let created_at = now_secs();
+    let snapshot = std::env::temp_dir().join(format!("lingecho-backup-{}.db", uuid::Uuid::new_v4()));
+    let database = snapshot_database(&data_dir(app)?, &snapshot)?;
+
+    let files = [(ZIP_DATABASE, database), (ZIP_SETTINGS, settings)];
+    let manifest = BackupManifest {
+        version: BACKUP_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at,
+        files: files
+            .iter()
+            .map(|(name, content)| BackupEntry {
+                name: name.to_string(),
+                size: content.len() as u64,
+                sha256: sha256(content),
+            })
+            .collect(),
+    };
+
+    // 先写临时文件，完整写入后再改名，避免留下不完整的备份
+    let path = dir.join(format!("{}{}.zip", FILE_PREFIX, created_at));
+    let partial = path.with_extension("zip.partial");
+    let written = (|| -> Result<(), AppError> {
+        let mut zip = zip::ZipWriter::new(File::create(&partial)?);
+        let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        zip.start_file(ZIP_MANIFEST, options).map_err(zip_error)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+        for (name, content) in &files {
+            zip.start_file(*name, options).map_err(zip_error)?;
+            zip.write_all(content)?;
+        }
+        zip.finish().map_err(zip_error)?;
+        Ok(())
+    })();
+    if let Err(e) = written {
+        std::fs::remove_file(&partial).ok();
+        return Err(e);
+    }
+    std::fs::rename(&partial, &path)?;
+
+    if let Err(e) = prune(&dir, backup_settings.keep) {
+        warn!("Failed to prune backups: {}", e);
+    }
+    let info = backup_info(&path).ok_or_else(|| AppError::Internal("备份文件名无效".to_string()))?;
+    info!("Backup written to {} ({} bytes)", info.path, info.size);
+    events::publish(app, AppEvent::BackupCreated(info.clone()));
+    Ok(info)
+}
+
+// 校验清单中每个文件的大小和 SHA-256，并检查数据库与设置能否正常读取
+fn verify(path: &Path) -> Result<Vec<(String, Vec<u8>)>, AppError> {
+    let file = File::open(path).map_err(|e| AppError::io(format!("无法打开备份文件 {}", path.display()), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(zip_error)?;
+    let mut read_entry = |name: &str, limit: u64| -> Result<Vec<u8>, AppError> {
+        let entry = archive
+            .by_name(name)
+            .map_err(|e| AppError::invalid(format!("备份文件缺少 {}: {}", name, e)))?;
+        // 压缩包头中的大小不可信，按实际读出的字节数判断
+        let mut buf = Vec::new();
+        entry.take(limit + 1).read_to_end(&mut buf)?;
+        if buf.len() as u64 > limit {
+            return Err(AppError::invalid(format!("备份文件已损坏：{} 超出预期大小", name)));
+        }
+        Ok(buf)
+    };
+
+    let manifest: BackupManifest = serde_json::from_slice(&read_entry(ZIP_MANIFEST, MAX_MANIFEST_BYTES)?)
+        .map_err(|e| AppError::invalid(format!("{} 格式无效: {}", ZIP_MANIFEST, e)))?;
+    if manifest.version == 0 || manifest.version > BACKUP_VERSION {
+        return Err(AppError::invalid(format!(
+            "不支持的备份版本 {}（当前支持 1 - {}）",
+            manifest.version, BACKUP_VERSION
+        )));
+    }
+
+    let mut files = Vec::new();
+    for name in [ZIP_DATABASE, ZIP_SETTINGS] {
+        let entry = manifest
+            .files
+            .iter()
+            .find(|entry| entry.name == name)
+            .ok_or_else(|| AppError::invalid(format!("备份清单缺少 {}", name)))?;
+        if entry.size > MAX_ENTRY_BYTES {
+            return Err(AppError::invalid(format!("备份中的 {} 过大", name)));
+        }
+        let content = read_entry(name, entry.size)?;
+        if content.len() as u64 != entry.size || sha256(&content) != entry.sha256 {
+            return Err(AppError::invalid(format!("备份文件已损坏：{} 校验失败", name)));
+        }
+        files.push((name.to_string(), content));
+    }
+
+    serde_json::from_slice::<Settings>(&files[1].1)
+        .map_err(|e| AppError::invalid(format!("{} 格式无效: {}", ZIP_SETTINGS, e)))?;
+    let check = std::env::temp_dir().join(format!("lingecho-restore-{}.db", uuid::Uuid::new_v4()));
+    std::fs::write(&check, &files[0].1)?;
+    let result = Connection::open(&check)
+        .and_then(|conn| conn.query_row("PRAGMA integrity_check", [], |row| row.get::<_, String>(0)));
+    std::fs::remove_file(&check).ok();
+    match result {
+        Ok(status) if status == "ok" => Ok(files),
+        Ok(status) => Err(AppError::invalid(format!("备份中的数据库已损坏: {}", status))),
+        Err(e) => Err(AppError::invalid(format!("备份中的数据库无法打开: {}", e))),
+    }
+}
+
+// 校验通过后把文件放到待恢复目录，重启应用后生效；恢复前先备份当前数据
+pub fn stage_restore(app: &AppHandle, path: &Path) -> Result<(), AppError> {
+    let files = verify(path)?;
+    if let Err(e) = create(app) {
+        warn!("Failed to back up current data before restore: {}", e);
+    }
+
+    let _guard = BACKUP_LOCK.lock()?;
+    let staging = data_dir(app)?.join(RESTORE_DIR);
+    std::fs::remove_dir_all(&staging).ok();
+    std::fs::create_dir_all(&staging)?;
+    for (name, content) in files {
+        std::fs::write(staging.join(name), content)?;
+    }
+    info!("Backup {} verified and staged for restore", path.display());
+    Ok(())
+}
+
+// 在 setup 中、打开数据库和加载设置之前调用。旧文件先改名保留，任何一步失败都改回原样，
+// 待恢复目录也保留下来，下次启动时重试
+pub fn apply_pending_restore(data_dir: &Path, config_dir: &Path) {
+    let staging = data_dir.join(RESTORE_DIR);
+    if !staging.exists() {
+        return;
+    }
+    let database = data_dir.join(DATABASE_FILE);
+    let settings = SettingsStore::new(config_dir).path().to_path_buf();
+    // 旧数据库的 WAL 文件会被应用到新数据库上，必须一并移走
+    let mut originals = vec![database.clone(), settings.clone()];
+    for suffix in ["-wal", "-shm"] {
+        let mut sidecar = database.clone().into_os_string();
+        sidecar.push(suffix);
+        originals.push(PathBuf::from(sidecar));
+    }
+
+    let mut moved = Vec::new();
+    let mut database_placed = false;
+    let result = (|| -> Result<(), AppError> {
+        for original in &originals {
+            if original.exists() {
+                let aside = aside_path(original);
+                std::fs::rename(original, &aside)?;
+                moved.push((original.clone(), aside));
+            }
+        }
+        std::fs::rename(staging.join(ZIP_DATABASE), &database)?;
+        database_placed = true;
+        std::fs::create_dir_all(config_dir)?;
+        // 配置目录与数据目录可能不在同一个文件系统上，不能直接改名
+        std::fs::copy(staging.join(ZIP_SETTINGS), &settings)?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            for (_, aside) in &moved {
+                std::fs::remove_file(aside).ok();
+            }
+            std::fs::remove_dir_all(&staging).ok();
+            info!("Restored data from backup");
+        }
+        Err(e) => {
+            warn!("Failed to apply pending restore, keeping current data: {}", e);
+            if database_placed {
+                if let Err(e) = std::fs::rename(&database, staging.join(ZIP_DATABASE)) {
+                    warn!("Failed to return restored database to staging: {}", e);
+                }
+                // 旧设置已经移走，这里只可能是复制了一半的新设置
+                std::fs::remove_file(&settings).ok();
+            }
+            for (original, aside) in moved.iter().rev() {
+                if let Err(e) = std::fs::rename(aside, original) {
+                    warn!("Failed to roll back {}: {}", original.display(), e);
+                }
+            }
+        }
+    }
+}
+
+fn aside_path(path: &Path) -> PathBuf {
+    let mut aside = path.as_os_str().to_os_string();
+    aside.push(RESTORE_ASIDE);
+    PathBuf::from(aside)
+}
+
+fn last_backup(app: &AppHandle, settings: &BackupSettings) -> Option<u64> {
+    let dir = backup_dir(app, settings).ok()?;
+    list(&dir).ok()?.first().map(|backup| backup.created_at)
+}
+
+// 在 setup 中调用：按设置的间隔定期备份，上次备份时间取自备份目录中最新的文件
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = match app.state::<AppState>().settings.lock() {
+                Ok(settings) => settings.backup.clone(),
+                Err(_) => return,
+            };
+            if !settings.enabled {
+                tokio::time::sleep(IDLE_POLL).await;
+                continue;
+            }
+            let interval = u64::from(settings.interval_hours.max(1)) * 60 * 60;
+            let wait = match last_backup(&app, &settings) {
+                Some(last) => (last + interval).saturating_sub(now_secs()),
+                None => 0,
+            };
+            if wait > 0 {
+                tokio::time::sleep(Duration::from_secs(wait).min(IDLE_POLL)).await;
+                continue;
+            }
+
+            let handle = app.clone();
+            let failed = match tauri::async_runtime::spawn_blocking(move || create(&handle)).await {
+                Ok(Ok(_)) => false,
+                Ok(Err(e)) => {
+                    warn!("Scheduled backup failed: {}", e);
+                    true
+                }
+                Err(e) => {
+                    warn!("Scheduled backup task failed: {}", e);
+                    true
+                }
+            };
+            // 失败后等待一段时间再重试，避免反复失败
+            if failed {
+                tokio::time::sleep(IDLE_POLL).await;
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn backup_now(app: AppHandle) -> Result<BackupInfo, AppError> {
+    tauri::async_runtime::spawn_blocking(move || create(&app)).await?
+}
+
+#[tauri::command]
+pub fn list_backups(app: AppHandle) -> Result<Vec<BackupInfo>, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.backup.clone();
+    list(&backup_dir(&app, &settings)?)
+}
+
+// 校验并恢复指定的备份，成功后重启应用
+#[tauri::command]
+pub async fn restore_backup(app: AppHandle, path: String) -> Result<(), AppError> {
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || stage_restore(&handle, Path::new(&path))).await??;
+    shutdown::restart(&app);
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use chrono::DateTime;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use reqwest::header::{CONTENT_TYPE, IF_NONE_MATCH};
+use reqwest::Method;
+
+use crate::error::AppError;
+
+// CalDAV 日历集合的地址需要由用户填写，这里不做服务发现
+pub struct CalDavClient<'a> {
+    pub client: reqwest::Client,
+    pub url: &'a str,
+    pub username: Option<&'a str>,
+    pub password: Option<String>,
+}
+
+fn format_utc(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn query(from: i64, to: i64) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<c:calendar-query xmlns:d="DAV:" xmlns:c="urn:ietf:params:xml:ns:caldav">
+  <d:prop><d:getetag/><c:calendar-data/></d:prop>
+  <c:filter>
+    <c:comp-filter name="VCALENDAR">
+      <c:comp-filter name="VEVENT">
+        <c:time-range start="{}" end="{}"/>
+      </c:comp-filter>
+    </c:comp-filter>
+  </c:filter>
+</c:calendar-query>"#,
+        format_utc(from),
+        format_utc(to)
+    )
+}
+
+// Multi-Status 响应中每个 <calendar-data> 是一份完整的 ICS 文本，命名空间前缀因服务器而异
+fn calendar_data(xml: &str) -> Result<Vec<String>, AppError> {
+    let mut reader = Reader::from_str(xml);
+    let mut calendars = Vec::new();
+    let mut current: Option<String> = None;
+    loop {
+        let event = reader
+            .read_event()
+            .map_err(|e| AppError::invalid(format!("无法解析 CalDAV 响应: {}", e)))?;
+        match event {
+            Event::Start(e) if e.local_name().as_ref() == b"calendar-data" => current = Some(String::new()),
+            Event::End(e) if e.local_name().as_ref() == b"calendar-data" => {
+                calendars.extend(current.take().filter(|data| !data.trim().is_empty()));
+            }
+            Event::Text(e) => {
+                if let Some(data) = current.as_mut() {
+                    let text = e
+                        .unescape()
+                        .map_err(|e| AppError::invalid(format!("无法解析 CalDAV 响应: {}", e)))?;
+                    data.push_str(&text);
+                }
+            }
+            Event::CData(e) => {
+                if let Some(data) = current.as_mut() {
+                    data.push_str(&String::from_utf8_lossy(&e));
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(calendars)
+}
+
+impl CalDavClient<'_> {
+    fn request(&self, method: Method, url: &str) -> reqwest::RequestBuilder {
+        let request = self.client.request(method, url);
+        match self.username {
+            Some(username) => request.basic_auth(username, self.password.as_deref()),
+            None => request,
+        }
+    }
+
+    // 返回时间范围内有事件的日历对象（ICS 文本），重复事件由调用方展开
+    pub async fn fetch(&self, from: i64, to: i64) -> Result<Vec<String>, AppError> {
+        let method = Method::from_bytes(b"REPORT").map_err(|e| e.to_string())?;
+        let response = self
+            .request(method, self.url)
+            .header("Depth", "1")
+            .header(CONTENT_TYPE, "application/xml; charset=utf-8")
+            .body(query(from, to))
+            .send()
+            .await
+            .map_err(|e| AppError::network("无法连接 CalDAV 服务器", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("CalDAV 服务器返回错误", response.status()));
+        }
+        let body = response
+            .text()
+            .await
+            .map_err(|e| AppError::network("无法连接 CalDAV 服务器", e))?;
+        calendar_data(&body)
+    }
+
+    // 以 <uid>.ics 新建日历对象，已存在同名对象时失败而不是覆盖
+    pub async fn create(&self, uid: &str, ics: String) -> Result<(), AppError> {
+        let url = format!("{}/{}.ics", self.url.trim_end_matches('/'), uid);
+        let response = self
+            .request(Method::PUT, &url)
+            .header(CONTENT_TYPE, "text/calendar; charset=utf-8")
+            .header(IF_NONE_MATCH, "*")
+            .body(ics)
+            .send()
+            .await
+            .map_err(|e| AppError::network("无法连接 CalDAV 服务器", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("无法创建日历事件", response.status()));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,747 @@
+use chrono::{DateTime, Datelike, Local, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Utc, Weekday};
+
+use crate::error::AppError;
+
+// 重复事件最多展开的次数，防止异常规则导致死循环
+const MAX_OCCURRENCES: usize = 2000;
+// INTERVAL 的上限，更大的间隔在可表示的日期范围内几乎不会重复
+const MAX_INTERVAL: u32 = 1000;
+const PRODID: &str = "-//LingEcho//Desktop//ZH";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+impl Frequency {
+    fn max_millis(self) -> i64 {
+        let days = match self {
+            Frequency::Daily => 1,
+            Frequency::Weekly => 7,
+            Frequency::Monthly => 31,
+            Frequency::Yearly => 366,
+        };
+        days * 86_400_000
+    }
+}
+
+// 只支持常见的 FREQ、INTERVAL、COUNT、UNTIL 和每周的 BYDAY
+#[derive(Debug, Clone)]
+struct RecurrenceRule {
+    frequency: Frequency,
+    interval: u32,
+    count: Option<usize>,
+    until: Option<i64>,
+    by_day: Vec<Weekday>,
+}
+
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    // 毫秒时间戳；全天事件为当天本地零点
+    pub start: i64,
+    pub end: Option<i64>,
+    pub all_day: bool,
+    duration: Option<i64>,
+    rule: Option<RecurrenceRule>,
+    exdates: Vec<i64>,
+    recurrence_id: Option<i64>,
+}
+
+// 展开后的一次发生
+#[derive(Debug, Clone)]
+pub struct Occurrence {
+    pub uid: String,
+    pub summary: String,
+    pub description: Option<String>,
+    pub location: Option<String>,
+    pub start: i64,
+    pub end: i64,
+    pub all_day: bool,
+}
+
+// 新建事件时写入的内容
+pub struct NewEvent<'a> {
+    pub uid: &'a str,
+    pub summary: &'a str,
+    pub description: Option<&'a str>,
+    pub location: Option<&'a str>,
+    pub start: i64,
+    pub end: i64,
+    pub all_day: bool,
+}
+
+fn invalid(message: &str) -> AppError {
+    AppError::invalid(format!("无法解析日历数据: {}", message))
+}
+
+// 以空格或制表符开头的行是上一行的续行
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in text.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if let Some(rest) = line.strip_prefix([' ', '\t']) {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(rest);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+// 把 NAME;PARAM=VALUE:VALUE 拆为名称、参数和值，参数值可能带引号
+fn split_line(line: &str) -> Option<ContentLine> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+    let (head, value) = (&line[..colon], &line[colon + 1..]);
+    let mut parts = head.split(';');
+    let name = parts.next()?.to_ascii_uppercase();
+    let params = parts
+        .filter_map(|param| {
+            let (key, value) = param.split_once('=')?;
+            Some((key.to_ascii_uppercase(), value.trim_matches('"').to_string()))
+        })
+        .collect();
+    Some(ContentLine {
+        name,
+        params,
+        value: value.to_string(),
+    })
+}
+
+fn unescape(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => result.push('\n'),
+            Some(other) => result.push(other),
+            None => {}
+        }
+    }
+    result
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn local_millis(naive: NaiveDateTime) -> Option<i64> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|time| time.timestamp_millis())
+}
+
+fn local_naive(millis: i64) -> Option<NaiveDateTime> {
+    DateTime::from_timestamp_millis(millis).map(|time| time.with_timezone(&Local).naive_local())
+}
+
+// 返回 (毫秒时间戳, 是否全天)；带 TZID 的时间按本地时间处理
+fn parse_time(value: &str, params: &[(String, String)]) -> Option<(i64, bool)> {
+    let value = value.trim();
+    let date_only = params.iter().any(|(key, value)| key == "VALUE" && value == "DATE") || value.len() == 8;
+    if date_only {
+        let date = NaiveDate::parse_from_str(value, "%Y%m%d").ok()?;
+        return local_millis(date.and_hms_opt(0, 0, 0)?).map(|millis| (millis, true));
+    }
+    if let Some(utc) = value.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some((Utc.from_utc_datetime(&naive).timestamp_millis(), false));
+    }
+    let naive = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    local_millis(naive).map(|millis| (millis, false))
+}
+
+// P1D、PT1H30M 之类的时长，返回毫秒
+fn parse_duration(value: &str) -> Option<i64> {
+    let (sign, value) = match value.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let value = value.strip_prefix('P')?;
+    let mut total = 0i64;
+    let mut number = String::new();
+    for c in value.chars() {
+        match c {
+            '0'..='9' => number.push(c),
+            'T' => {}
+            unit => {
+                let amount: i64 = number.parse().ok()?;
+                number.clear();
+                let seconds = match unit {
+                    'W' => 7 * 86_400,
+                    'D' => 86_400,
+                    'H' => 3_600,
+                    'M' => 60,
+                    'S' => 1,
+                    _ => return None,
+                };
+                total = amount
+                    .checked_mul(seconds * 1000)
+                    .and_then(|millis| total.checked_add(millis))?;
+            }
+        }
+    }
+    Some(sign * total)
+}
+
+fn parse_weekday(value: &str) -> Option<Weekday> {
+    // BYDAY 可以带序号（如 1MO），这里只取星期部分
+    let day = value.trim_start_matches(|c: char| c.is_ascii_digit() || c == '+' || c == '-');
+    match day {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+fn parse_rule(value: &str) -> Option<RecurrenceRule> {
+    let mut rule = RecurrenceRule {
+        frequency: Frequency::Daily,
+        interval: 1,
+        count: None,
+        until: None,
+        by_day: Vec::new(),
+    };
+    let mut frequency = None;
+    for part in value.split(';') {
+        let Some((key, value)) = part.split_once('=') else {
+            continue;
+        };
+        match key.to_ascii_uppercase().as_str() {
+            "FREQ" => {
+                frequency = match value.to_ascii_uppercase().as_str() {
+                    "DAILY" => Some(Frequency::Daily),
+                    "WEEKLY" => Some(Frequency::Weekly),
+                    "MONTHLY" => Some(Frequency::Monthly),
+                    "YEARLY" => Some(Frequency::Yearly),
+                    _ => None,
+                }
+            }
+            "INTERVAL" => rule.interval = value.parse().unwrap_or(1).clamp(1, MAX_INTERVAL),
+            "COUNT" => rule.count = value.parse().ok(),
+            "UNTIL" => rule.until = parse_time(value, &[]).map(|(millis, _)| millis),
+            "BYDAY" => rule.by_day = value.split(',').filter_map(parse_weekday).collect(),
+            _ => {}
+        }
+    }
+    rule.frequency = frequency?;
+    Some(rule)
+}
+
+// 解析 ICS 文本中的全部 VEVENT，无法识别的属性忽略
+pub fn parse(text: &str) -> Result<Vec<IcsEvent>, AppError> {
+    let lines = unfold(text);
+    if !lines.iter().any(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err(invalid("缺少 VCALENDAR"));
+    }
+
+    let mut events = Vec::new();
+    let mut current: Option<IcsEvent> = None;
+    // VEVENT 内嵌的 VALARM 等组件中的属性不属于事件本身
+    let mut nested = 0;
+    for line in &lines {
+        let Some(ContentLine { name, params, value }) = split_line(line) else {
+            continue;
+        };
+        match (name.as_str(), value.to_ascii_uppercase().as_str()) {
+            ("BEGIN", "VEVENT") => {
+                current = Some(IcsEvent {
+                    uid: String::new(),
+                    summary: String::new(),
+                    description: None,
+                    location: None,
+                    start: 0,
+                    end: None,
+                    all_day: false,
+                    duration: None,
+                    rule: None,
+                    exdates: Vec::new(),
+                    recurrence_id: None,
+                });
+                nested = 0;
+                continue;
+            }
+            ("END", "VEVENT") => {
+                if let Some(event) = current.take().filter(|event| event.start != 0) {
+                    events.push(event);
+                }
+                continue;
+            }
+            ("BEGIN", _) if current.is_some() => nested += 1,
+            ("END", _) if current.is_some() => nested -= 1,
+            _ => {}
+        }
+        let Some(event) = current.as_mut().filter(|_| nested == 0) else {
+            continue;
+        };
+        match name.as_str() {
+            "UID" => event.uid = value,
+            "SUMMARY" => event.summary = unescape(&value),
+            "DESCRIPTION" => event.description = Some(unescape(&value)),
+            "LOCATION" => event.location = Some(unescape(&value)),
+            "DTSTART" => {
+                if let Some((start, all_day)) = parse_time(&value, &params) {
+                    event.start = start;
+                    event.all_day = all_day;
+                }
+            }
+            "DTEND" => event.end = parse_time(&value, &params).map(|(end, _)| end),
+            "DURATION" => event.duration = parse_duration(&value),
+            "RRULE" => event.rule = parse_rule(&value),
+            "EXDATE" => event.exdates.extend(
+                value
+                    .split(',')
+                    .filter_map(|value| parse_time(value, &params).map(|(millis, _)| millis)),
+            ),
+            "RECURRENCE-ID" => event.recurrence_id = parse_time(&value, &params).map(|(millis, _)| millis),
+            _ => {}
+        }
+    }
+    Ok(events)
+}
+
+// 超出可表示的日期范围时返回 None，该月没有对应日期时返回 Some(None)
+fn add_months(time: NaiveDateTime, months: i64) -> Option<Option<NaiveDateTime>> {
+    let total = (time.year() as i64 * 12 + time.month0() as i64).checked_add(months)?;
+    let year = i32::try_from(total.div_euclid(12)).ok()?;
+    let month0 = total.rem_euclid(12) as u32;
+    NaiveDate::from_ymd_opt(year, month0 + 1, 1)?;
+    // 没有对应日期的月份（如 2 月 30 日）按 RFC 5545 跳过
+    Some(NaiveDate::from_ymd_opt(year, month0 + 1, time.day()).map(|date| date.and_time(time.time())))
+}
+
+fn add_days(time: NaiveDateTime, days: i64) -> Option<NaiveDateTime> {
+    time.checked_add_signed(TimeDelta::try_days(days)?)
+}
+
+// 按规则依次生成开始时间（本地时间），第 index 个周期；超出可表示的日期范围时返回 None，展开随之结束
+fn period_starts(rule: &RecurrenceRule, base: NaiveDateTime, index: i64) -> Option<Vec<NaiveDateTime>> {
+    let step = index.checked_mul(rule.interval as i64)?;
+    let starts = match rule.frequency {
+        Frequency::Daily => vec![add_days(base, step)?],
+        Frequency::Weekly if rule.by_day.is_empty() => vec![base.checked_add_signed(TimeDelta::try_weeks(step)?)?],
+        Frequency::Weekly => {
+            let week_start = add_days(base, -(base.weekday().num_days_from_monday() as i64))?
+                .checked_add_signed(TimeDelta::try_weeks(step)?)?;
+            let mut days = Vec::new();
+            for day in &rule.by_day {
+                let start = add_days(week_start, day.num_days_from_monday() as i64)?;
+                if start >= base {
+                    days.push(start);
+                }
+            }
+            days.sort();
+            days
+        }
+        Frequency::Monthly => add_months(base, step)?.into_iter().collect(),
+        Frequency::Yearly => add_months(base, step.checked_mul(12)?)?.into_iter().collect(),
+    };
+    Some(starts)
+}
+
+impl IcsEvent {
+    fn length(&self) -> i64 {
+        match (self.end, self.duration) {
+            (Some(end), _) if end > self.start => end - self.start,
+            (_, Some(duration)) if duration > 0 => duration,
+            // 没有结束时间的全天事件持续一天，其他事件视为瞬时
+            _ if self.all_day => 86_400_000,
+            _ => 0,
+        }
+    }
+
+    fn occurrence(&self, start: i64) -> Occurrence {
+        Occurrence {
+            uid: self.uid.clone(),
+            summary: self.summary.clone(),
+            description: self.description.clone(),
+            location: self.location.clone(),
+            start,
+            end: start + self.length(),
+            all_day: self.all_day,
+        }
+    }
+
+    // 展开与 [from, to) 有交集的全部发生
+    fn expand(&self, from: i64, to: i64, overridden: &[i64]) -> Vec<Occurrence> {
+        let length = self.length();
+        let overlaps = |start: i64| start < to && start + length.max(1) > from;
+        let Some(rule) = self.rule.as_ref() else {
+            return if overlaps(self.start) {
+                vec![self.occurrence(self.start)]
+            } else {
+                Vec::new()
+            };
+        };
+        let Some(base) = local_naive(self.start) else {
+            return Vec::new();
+        };
+
+        // 没有 COUNT 时直接跳到范围附近，按最长的周期估算以免漏掉
+        let first = match rule.count {
+            Some(_) => 0,
+            None => (from.saturating_sub(self.start).saturating_sub(length)
+                / (rule.frequency.max_millis() * rule.interval as i64)
+                - 1)
+            .max(0),
+        };
+        let mut occurrences = Vec::new();
+        let mut generated = 0;
+        for index in first..first + MAX_OCCURRENCES as i64 {
+            let Some(starts) = period_starts(rule, base, index) else {
+                break;
+            };
+            let mut past_end = false;
+            for start in starts {
+                let Some(start) = local_millis(start) else {
+                    continue;
+                };
+                if start >= to || rule.until.is_some_and(|until| start > until) {
+                    past_end = true;
+                    break;
+                }
+                generated += 1;
+                if rule.count.is_some_and(|count| generated > count) {
+                    past_end = true;
+                    break;
+                }
+                if overlaps(start) && !self.exdates.contains(&start) && !overridden.contains(&start) {
+                    occurrences.push(self.occurrence(start));
+                }
+            }
+            if past_end || occurrences.len() >= MAX_OCCURRENCES {
+                break;
+            }
+        }
+        occurrences
+    }
+}
+
+// 展开时间范围内的全部事件；带 RECURRENCE-ID 的单次修改替换重复事件中对应的那一次
+pub fn occurrences(events: &[IcsEvent], from: i64, to: i64) -> Vec<Occurrence> {
+    let mut result = Vec::new();
+    for event in events {
+        if event.recurrence_id.is_some() {
+            let start = event.start;
+            if start < to && start + event.length().max(1) > from {
+                result.push(event.occurrence(start));
+            }
+            continue;
+        }
+        let overridden: Vec<i64> = events
+            .iter()
+            .filter(|other| other.uid == event.uid)
+            .filter_map(|other| other.recurrence_id)
+            .collect();
+        result.extend(event.expand(from, to, &overridden));
+    }
+    result.sort_by_key(|occurrence| occurrence.start);
+    result
+}
+
+fn format_utc(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn format_date(millis: i64) -> String {
+    local_naive(millis)
+        .map(|time| time.format("%Y%m%d").to_string())
+        .unwrap_or_default()
+}
+
+// 每行最多 75 字节，续行以空格开头
+fn fold(line: &str, output: &mut String) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            output.push_str("\r\n ");
+            width = 1;
+        }
+        output.push(c);
+        width += c.len_utf8();
+    }
+    output.push_str("\r\n");
+}
+
+pub fn write_event(event: &NewEvent, output: &mut String) {
+    let mut lines = vec![
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}", event.uid),
+        format!("DTSTAMP:{}", format_utc(Utc::now().timestamp_millis())),
+    ];
+    if event.all_day {
+        lines.push(format!("DTSTART;VALUE=DATE:{}", format_date(event.start)));
+        lines.push(format!("DTEND;VALUE=DATE:{}", format_date(event.end)));
+    } else {
+        lines.push(format!("DTSTART:{}", format_utc(event.start)));
+        lines.push(format!("DTEND:{}", format_utc(event.end)));
+    }
+    lines.push(format!("SUMMARY:{}", escape(event.summary)));
+    if let Some(location) = event.location {
+        lines.push(format!("LOCATION:{}", escape(location)));
+    }
+    if let Some(description) = event.description {
+        lines.push(format!("DESCRIPTION:{}", escape(description)));
+    }
+    lines.push("END:VEVENT".to_string());
+    for line in lines {
+        fold(&line, output);
+    }
+}
+
+pub fn write_calendar(event: &NewEvent) -> String {
+    let mut output = String::new();
+    fold("BEGIN:VCALENDAR", &mut output);
+    fold("VERSION:2.0", &mut output);
+    fold(&format!("PRODID:{}", PRODID), &mut output);
+    write_event(event, &mut output);
+    fold("END:VCALENDAR", &mut output);
+    output
+}
+
+// 把事件加入已有的日历文件内容，原内容不是日历时新建
+pub fn append_event(existing: &str, event: &NewEvent) -> String {
+    match existing.rfind("END:VCALENDAR") {
+        Some(index) => {
+            let mut output = existing[..index].to_string();
+            if !output.ends_with('\n') {
+                output.push_str("\r\n");
+            }
+            write_event(event, &mut output);
+            output.push_str(&existing[index..]);
+            output
+        }
+        None => write_calendar(event),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> i64 {
+        let naive = NaiveDate::from_ymd_opt(year, month, day)
+            .and_then(|date| date.and_hms_opt(hour, minute, 0))
+            .unwrap();
+        local_millis(naive).unwrap()
+    }
+
+    fn calendar(events: &str) -> String {
+        format!("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n{}END:VCALENDAR\r\n", events)
+    }
+
+    fn starts(text: &str, from: i64, to: i64) -> Vec<i64> {
+        let events = parse(&calendar(text)).unwrap();
+        occurrences(&events, from, to)
+            .into_iter()
+            .map(|occurrence| occurrence.start)
+            .collect()
+    }
+
+    #[test]
+    fn parse_reads_event_properties() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\n\
+             UID:a@test\r\n\
+             SUMMARY:Team\\, weekly\r\n\
+             DESCRIPTION:long\r\n  text\r\n\
+             DTSTART:20240105T093000\r\n\
+             DTEND:20240105T103000\r\n\
+             BEGIN:VALARM\r\n\
+             DESCRIPTION:reminder\r\n\
+             END:VALARM\r\n\
+             END:VEVENT\r\n",
+        ))
+        .unwrap();
+        assert_eq!(events.len(), 1);
+        let event = &events[0];
+        assert_eq!(event.uid, "a@test");
+        assert_eq!(event.summary, "Team, weekly");
+        assert_eq!(event.description.as_deref(), Some("long text"));
+        assert_eq!(event.start, local(2024, 1, 5, 9, 30));
+        assert_eq!(event.end, Some(local(2024, 1, 5, 10, 30)));
+        assert!(!event.all_day);
+    }
+
+    #[test]
+    fn parse_requires_calendar() {
+        assert!(parse("BEGIN:VEVENT\r\nEND:VEVENT\r\n").is_err());
+    }
+
+    #[test]
+    fn parse_all_day_event() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:b\r\nDTSTART;VALUE=DATE:20240301\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        assert!(events[0].all_day);
+        assert_eq!(events[0].start, local(2024, 3, 1, 0, 0));
+        assert_eq!(events[0].length(), 86_400_000);
+    }
+
+    #[test]
+    fn parse_clamps_interval() {
+        let rule = parse_rule("FREQ=DAILY;INTERVAL=4294967295").unwrap();
+        assert_eq!(rule.interval, MAX_INTERVAL);
+        let rule = parse_rule("FREQ=DAILY;INTERVAL=0").unwrap();
+        assert_eq!(rule.interval, 1);
+        assert!(parse_rule("INTERVAL=2").is_none());
+    }
+
+    #[test]
+    fn expand_daily_with_count() {
+        let found = starts(
+            "BEGIN:VEVENT\r\nUID:c\r\nDTSTART:20240110T080000\r\nRRULE:FREQ=DAILY;INTERVAL=2;COUNT=3\r\nEND:VEVENT\r\n",
+            local(2024, 1, 1, 0, 0),
+            local(2024, 2, 1, 0, 0),
+        );
+        assert_eq!(
+            found,
+            vec![
+                local(2024, 1, 10, 8, 0),
+                local(2024, 1, 12, 8, 0),
+                local(2024, 1, 14, 8, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_weekly_by_day_until() {
+        // 2024-01-01 是星期一
+        let found = starts(
+            "BEGIN:VEVENT\r\nUID:d\r\nDTSTART:20240101T090000\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,WE;UNTIL=20240110T235959\r\nEND:VEVENT\r\n",
+            local(2024, 1, 1, 0, 0),
+            local(2024, 2, 1, 0, 0),
+        );
+        assert_eq!(
+            found,
+            vec![
+                local(2024, 1, 1, 9, 0),
+                local(2024, 1, 3, 9, 0),
+                local(2024, 1, 8, 9, 0),
+                local(2024, 1, 10, 9, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_monthly_skips_missing_days() {
+        let found = starts(
+            "BEGIN:VEVENT\r\nUID:e\r\nDTSTART:20240131T090000\r\nRRULE:FREQ=MONTHLY;COUNT=3\r\nEND:VEVENT\r\n",
+            local(2024, 1, 1, 0, 0),
+            local(2025, 1, 1, 0, 0),
+        );
+        assert_eq!(
+            found,
+            vec![
+                local(2024, 1, 31, 9, 0),
+                local(2024, 3, 31, 9, 0),
+                local(2024, 5, 31, 9, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_skips_exdates() {
+        let found = starts(
+            "BEGIN:VEVENT\r\nUID:f\r\nDTSTART:20240101T090000\r\nRRULE:FREQ=DAILY;COUNT=3\r\n\
+             EXDATE:20240102T090000\r\nEND:VEVENT\r\n",
+            local(2024, 1, 1, 0, 0),
+            local(2024, 2, 1, 0, 0),
+        );
+        assert_eq!(found, vec![local(2024, 1, 1, 9, 0), local(2024, 1, 3, 9, 0)]);
+    }
+
+    #[test]
+    fn recurrence_id_replaces_occurrence() {
+        let events = parse(&calendar(
+            "BEGIN:VEVENT\r\nUID:g\r\nSUMMARY:Standup\r\nDTSTART:20240101T090000\r\n\
+             RRULE:FREQ=DAILY;COUNT=3\r\nEND:VEVENT\r\n\
+             BEGIN:VEVENT\r\nUID:g\r\nSUMMARY:Moved\r\nRECURRENCE-ID:20240102T090000\r\n\
+             DTSTART:20240102T140000\r\nEND:VEVENT\r\n",
+        ))
+        .unwrap();
+        let found: Vec<(i64, String)> = occurrences(&events, local(2024, 1, 1, 0, 0), local(2024, 2, 1, 0, 0))
+            .into_iter()
+            .map(|occurrence| (occurrence.start, occurrence.summary))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (local(2024, 1, 1, 9, 0), "Standup".to_string()),
+                (local(2024, 1, 2, 14, 0), "Moved".to_string()),
+                (local(2024, 1, 3, 9, 0), "Standup".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn expand_stops_at_date_range_limit() {
+        for rule in [
+            "FREQ=DAILY;INTERVAL=4294967295",
+            "FREQ=WEEKLY;INTERVAL=1000;BYDAY=MO,FR",
+            "FREQ=MONTHLY;INTERVAL=1000",
+            "FREQ=YEARLY;INTERVAL=1000",
+        ] {
+            let text = format!(
+                "BEGIN:VEVENT\r\nUID:h\r\nDTSTART:20240101T090000\r\nRRULE:{}\r\nEND:VEVENT\r\n",
+                rule
+            );
+            let found = starts(&text, local(2024, 1, 1, 0, 0), i64::MAX);
+            assert!(!found.is_empty() && found.len() <= MAX_OCCURRENCES, "{}", rule);
+            assert_eq!(found[0], local(2024, 1, 1, 9, 0));
+            // 范围起点在极远的未来时直接结束，不会溢出
+            assert!(starts(&text, i64::MAX - 1, i64::MAX).is_empty(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn parse_duration_rejects_overflow() {
+        assert_eq!(parse_duration("PT1H30M"), Some(5_400_000));
+        assert_eq!(parse_duration("-P1D"), Some(-86_400_000));
+        assert_eq!(parse_duration("P99999999999999999W"), None);
+    }
+}
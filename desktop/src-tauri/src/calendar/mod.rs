@@ -0,0 +1,485 @@
+// 日历：读取本地 ICS 文件或 CalDAV 日历，展开后缓存在本地数据库供日程查询
+pub mod caldav;
+pub mod ics;
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::storage::{now_millis, DATABASE_FILE};
+use crate::{network, secrets, settings, AppState};
+use caldav::CalDavClient;
+use ics::{NewEvent, Occurrence};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+// 同步时缓存的时间范围：过去 1 天到未来 90 天
+const SYNC_PAST_DAYS: i64 = 1;
+const SYNC_AHEAD_DAYS: i64 = 90;
+const DEFAULT_QUERY_DAYS: u32 = 7;
+const DEFAULT_EVENT_MS: i64 = 60 * 60 * 1000;
+const MIN_SYNC_INTERVAL_MINS: u32 = 5;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS calendar_events (
+    source_id TEXT NOT NULL,
+    uid TEXT NOT NULL,
+    start_at INTEGER NOT NULL,
+    end_at INTEGER NOT NULL,
+    all_day INTEGER NOT NULL DEFAULT 0,
+    summary TEXT NOT NULL,
+    location TEXT,
+    description TEXT,
+    PRIMARY KEY (source_id, uid, start_at)
+);
+CREATE INDEX IF NOT EXISTS idx_calendar_events_start ON calendar_events(start_at);
+";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CalendarKind {
+    #[default]
+    Ics,
+    Caldav,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CalendarSource {
+    pub id: String,
+    pub name: String,
+    pub kind: CalendarKind,
+    // ICS 文件路径或 CalDAV 日历集合的 URL
+    pub location: String,
+    // CalDAV 用户名，密码保存在系统钥匙串中
+    pub username: Option<String>,
+    pub enabled: bool,
+}
+
+impl Default for CalendarSource {
+    fn default() -> Self {
+        Self {
+            id: String::new(),
+            name: String::new(),
+            kind: CalendarKind::Ics,
+            location: String::new(),
+            username: None,
+            enabled: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CalendarSettings {
+    pub sources: Vec<CalendarSource>,
+    pub sync_interval_mins: u32,
+}
+
+impl Default for CalendarSettings {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            sync_interval_mins: 15,
+        }
+    }
+}
+
+// 时间均为毫秒时间戳
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarEvent {
+    pub source_id: String,
+    pub uid: String,
+    pub summary: String,
+    pub location: Option<String>,
+    pub description: Option<String>,
+    pub start: i64,
+    pub end: i64,
+    pub all_day: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CalendarSyncStatus {
+    pub source_id: String,
+    pub events: usize,
+    pub synced_at: i64,
+    pub error: Option<String>,
+}
+
+fn event_from_row(row: &rusqlite::Row) -> rusqlite::Result<CalendarEvent> {
+    Ok(CalendarEvent {
+        source_id: row.get(0)?,
+        uid: row.get(1)?,
+        start: row.get(2)?,
+        end: row.get(3)?,
+        all_day: row.get(4)?,
+        summary: row.get(5)?,
+        location: row.get(6)?,
+        description: row.get(7)?,
+    })
+}
+
+pub struct Calendar {
+    conn: Mutex<Connection>,
+    // 日历源变化时唤醒后台任务立即同步
+    wake: Notify,
+    status: Mutex<HashMap<String, CalendarSyncStatus>>,
+}
+
+impl Calendar {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            wake: Notify::new(),
+            status: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    fn insert(tx: &Connection, source_id: &str, occurrence: &Occurrence) -> Result<(), AppError> {
+        tx.execute(
+            "INSERT OR REPLACE INTO calendar_events
+                 (source_id, uid, start_at, end_at, all_day, summary, location, description)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                source_id,
+                occurrence.uid,
+                occurrence.start,
+                occurrence.end,
+                occurrence.all_day,
+                occurrence.summary,
+                occurrence.location,
+                occurrence.description
+            ],
+        )?;
+        Ok(())
+    }
+
+    // 用同步结果整体替换该日历源的缓存
+    fn replace(&self, source_id: &str, occurrences: &[Occurrence]) -> Result<(), AppError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM calendar_events WHERE source_id = ?1", params![source_id])?;
+        for occurrence in occurrences {
+            Self::insert(&tx, source_id, occurrence)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn add(&self, source_id: &str, occurrence: &Occurrence) -> Result<(), AppError> {
+        Self::insert(&*self.conn()?, source_id, occurrence)
+    }
+
+    fn remove_source(&self, source_id: &str) -> Result<(), AppError> {
+        self.conn()?
+            .execute("DELETE FROM calendar_events WHERE source_id = ?1", params![source_id])?;
+        if let Ok(mut status) = self.status.lock() {
+            status.remove(source_id);
+        }
+        Ok(())
+    }
+
+    // 与 [from, to) 有交集的事件，按开始时间排序
+    pub fn between(&self, from: i64, to: i64) -> Result<Vec<CalendarEvent>, AppError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT source_id, uid, start_at, end_at, all_day, summary, location, description
+             FROM calendar_events WHERE start_at < ?2 AND end_at > ?1 ORDER BY start_at, summary",
+        )?;
+        let rows = stmt.query_map(params![from, to], event_from_row)?;
+        let events = rows.collect::<Result<Vec<_>, _>>();
+        events.map_err(AppError::from)
+    }
+
+    fn record(&self, status: CalendarSyncStatus) {
+        if let Ok(mut all) = self.status.lock() {
+            all.insert(status.source_id.clone(), status);
+        }
+    }
+
+    fn statuses(&self) -> Vec<CalendarSyncStatus> {
+        let mut statuses: Vec<CalendarSyncStatus> = self
+            .status
+            .lock()
+            .map(|all| all.values().cloned().collect())
+            .unwrap_or_default();
+        statuses.sort_by(|a, b| a.source_id.cmp(&b.source_id));
+        statuses
+    }
+}
+
+fn password_key(source_id: &str) -> String {
+    format!("calendar.{}.password", source_id)
+}
+
+// 日历源保存在钥匙串中的密码名称，清除数据时使用
+pub fn secret_names(settings: &CalendarSettings) -> Vec<String> {
+    settings.sources.iter().map(|source| password_key(&source.id)).collect()
+}
+
+fn calendar_settings(app: &AppHandle) -> Result<CalendarSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.calendar.clone())
+}
+
+fn caldav_client<'a>(app: &AppHandle, source: &'a CalendarSource) -> Result<CalDavClient<'a>, AppError> {
+    Ok(CalDavClient {
+        client: network::client(app),
+        url: &source.location,
+        username: source.username.as_deref(),
+        password: secrets::get(&password_key(&source.id))?,
+    })
+}
+
+fn read_ics(path: &Path) -> Result<String, AppError> {
+    std::fs::read_to_string(path).map_err(|e| AppError::io(format!("无法读取日历文件 {}", path.display()), e))
+}
+
+async fn fetch_source(
+    app: &AppHandle,
+    source: &CalendarSource,
+    from: i64,
+    to: i64,
+) -> Result<Vec<Occurrence>, AppError> {
+    let documents = match source.kind {
+        CalendarKind::Ics => vec![read_ics(Path::new(&source.location))?],
+        CalendarKind::Caldav => caldav_client(app, source)?.fetch(from, to).await?,
+    };
+    let mut occurrences = Vec::new();
+    for document in documents {
+        let events = ics::parse(&document)?;
+        occurrences.extend(ics::occurrences(&events, from, to));
+    }
+    Ok(occurrences)
+}
+
+// 同步全部启用的日历源，停用的日历源清除缓存
+pub async fn sync_all(app: &AppHandle) -> Vec<CalendarSyncStatus> {
+    let settings = match calendar_settings(app) {
+        Ok(settings) => settings,
+        Err(e) => {
+            warn!("Failed to read calendar settings: {}", e);
+            return Vec::new();
+        }
+    };
+    let calendar = app.state::<Calendar>();
+    let now = now_millis();
+    let (from, to) = (now - SYNC_PAST_DAYS * DAY_MS, now + SYNC_AHEAD_DAYS * DAY_MS);
+    for source in &settings.sources {
+        if !source.enabled {
+            if let Err(e) = calendar.remove_source(&source.id) {
+                warn!("Failed to clear calendar {}: {}", source.id, e);
+            }
+            continue;
+        }
+        let result = match fetch_source(app, source, from, to).await {
+            Ok(occurrences) => calendar.replace(&source.id, &occurrences).map(|_| occurrences.len()),
+            Err(e) => Err(e),
+        };
+        let status = match result {
+            Ok(events) => {
+                info!("Synced {} events from calendar {}", events, source.name);
+                CalendarSyncStatus {
+                    source_id: source.id.clone(),
+                    events,
+                    synced_at: now,
+                    error: None,
+                }
+            }
+            // 同步失败时保留上次的缓存
+            Err(e) => {
+                warn!("Failed to sync calendar {}: {}", source.name, e);
+                CalendarSyncStatus {
+                    source_id: source.id.clone(),
+                    events: 0,
+                    synced_at: now,
+                    error: Some(crate::i18n::translate(e.message())),
+                }
+            }
+        };
+        calendar.record(status);
+    }
+    let statuses = calendar.statuses();
+    events::publish(app, AppEvent::CalendarSynced(statuses.clone()));
+    statuses
+}
+
+// 在 setup 中调用：按设置的间隔在后台同步，日历源变化时立即同步
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = calendar_settings(&app).unwrap_or_default();
+            if !settings.sources.is_empty() {
+                sync_all(&app).await;
+            }
+            let interval = settings.sync_interval_mins.max(MIN_SYNC_INTERVAL_MINS) as u64 * 60;
+            let calendar = app.state::<Calendar>();
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = calendar.wake.notified() => {}
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_upcoming_events(calendar: State<'_, Calendar>, days: Option<u32>) -> Result<Vec<CalendarEvent>, AppError> {
+    let days = days.unwrap_or(DEFAULT_QUERY_DAYS).clamp(1, SYNC_AHEAD_DAYS as u32);
+    let now = now_millis();
+    calendar.between(now, now + days as i64 * DAY_MS)
+}
+
+// 未指定 source_id 时写入第一个启用的日历；end 省略时普通事件持续 1 小时，全天事件持续 1 天
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn create_event(
+    app: AppHandle,
+    calendar: State<'_, Calendar>,
+    source_id: Option<String>,
+    summary: String,
+    start: i64,
+    end: Option<i64>,
+    all_day: Option<bool>,
+    location: Option<String>,
+    description: Option<String>,
+) -> Result<CalendarEvent, AppError> {
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err(AppError::invalid("事件标题不能为空"));
+    }
+    let all_day = all_day.unwrap_or(false);
+    let end = end.unwrap_or(start + if all_day { DAY_MS } else { DEFAULT_EVENT_MS });
+    if end <= start {
+        return Err(AppError::invalid("结束时间必须晚于开始时间"));
+    }
+    let settings = calendar_settings(&app)?;
+    let source = settings
+        .sources
+        .iter()
+        .filter(|source| source.enabled)
+        .find(|source| source_id.as_ref().is_none_or(|id| *id == source.id))
+        .ok_or_else(|| AppError::NotFound("没有可写入的日历".to_string()))?;
+
+    let uid = format!("{}@lingecho", uuid::Uuid::new_v4());
+    let event = NewEvent {
+        uid: &uid,
+        summary,
+        description: description.as_deref().filter(|text| !text.is_empty()),
+        location: location.as_deref().filter(|text| !text.is_empty()),
+        start,
+        end,
+        all_day,
+    };
+    match source.kind {
+        CalendarKind::Ics => {
+            let path = PathBuf::from(&source.location);
+            let existing = if path.exists() { read_ics(&path)? } else { String::new() };
+            std::fs::write(&path, ics::append_event(&existing, &event))
+                .map_err(|e| AppError::io(format!("无法写入日历文件 {}", path.display()), e))?;
+        }
+        CalendarKind::Caldav => {
+            caldav_client(&app, source)?
+                .create(&uid, ics::write_calendar(&event))
+                .await?
+        }
+    }
+
+    let occurrence = Occurrence {
+        uid: uid.clone(),
+        summary: summary.to_string(),
+        description: event.description.map(str::to_string),
+        location: event.location.map(str::to_string),
+        start,
+        end,
+        all_day,
+    };
+    calendar.add(&source.id, &occurrence)?;
+    info!("Created calendar event {} in {}", uid, source.name);
+    Ok(CalendarEvent {
+        source_id: source.id.clone(),
+        uid,
+        summary: occurrence.summary,
+        location: occurrence.location,
+        description: occurrence.description,
+        start,
+        end,
+        all_day,
+    })
+}
+
+#[tauri::command]
+pub async fn sync_calendars(app: AppHandle) -> Result<Vec<CalendarSyncStatus>, AppError> {
+    Ok(sync_all(&app).await)
+}
+
+// password 只用于 CalDAV，保存在系统钥匙串中
+#[tauri::command]
+pub fn add_calendar_source(
+    app: AppHandle,
+    calendar: State<'_, Calendar>,
+    name: String,
+    kind: CalendarKind,
+    location: String,
+    username: Option<String>,
+    password: Option<String>,
+) -> Result<CalendarSource, AppError> {
+    let location = location.trim().to_string();
+    match kind {
+        CalendarKind::Ics if location.is_empty() => return Err(AppError::invalid("需要指定日历文件路径")),
+        CalendarKind::Caldav if !location.starts_with("http://") && !location.starts_with("https://") => {
+            return Err(AppError::invalid("CalDAV 地址必须以 http:// 或 https:// 开头"))
+        }
+        _ => {}
+    }
+    let source = CalendarSource {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.trim().to_string(),
+        kind,
+        location,
+        username: username
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty()),
+        enabled: true,
+    };
+    if let Some(password) = password.filter(|password| !password.is_empty()) {
+        secrets::store(&password_key(&source.id), &password)?;
+    }
+
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.calendar.sources.push(source.clone());
+    settings::save_and_notify(&app, &settings, &["calendar"])?;
+    calendar.wake.notify_one();
+    Ok(source)
+}
+
+#[tauri::command]
+pub fn remove_calendar_source(app: AppHandle, calendar: State<'_, Calendar>, id: String) -> Result<bool, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let before = settings.calendar.sources.len();
+    settings.calendar.sources.retain(|source| source.id != id);
+    if settings.calendar.sources.len() == before {
+        return Ok(false);
+    }
+    settings::save_and_notify(&app, &settings, &["calendar"])?;
+    drop(settings);
+
+    if let Err(e) = secrets::delete(&password_key(&id)) {
+        warn!("{}", e);
+    }
+    calendar.remove_source(&id)?;
+    Ok(true)
+}
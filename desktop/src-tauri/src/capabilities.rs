@@ -0,0 +1,130 @@
+use tauri::Invoke;
+use tracing::warn;
+
+use crate::captions::CAPTIONS_LABEL;
+use crate::error::AppError;
+use crate::pet::bubble::BUBBLE_LABEL;
+use crate::pet::PET_LABEL;
+use crate::profiles::PICKER_LABEL;
+use crate::quick_ask::QUICK_ASK_LABEL;
+use crate::window_state::MAIN_LABEL;
+
+// 桌宠窗口只能控制桌宠本身、进行语音交互和读取少量状态，不能导出数据或修改设置
+const PET_COMMANDS: &[&str] = &[
+    "get_app_info",
+    "get_theme",
+    "get_settings",
+    "check_backend_status",
+    "show_main_window",
+    "start_pet_drag",
+    "set_pet_position",
+    "get_pet_position",
+    "list_monitors",
+    "move_pet_to_monitor",
+    "set_pet_click_through",
+    "toggle_pet_click_through",
+    "show_desktop_pet",
+    "hide_desktop_pet",
+    "toggle_desktop_pet",
+    "set_pet_size",
+    "get_display_capabilities",
+    "capture_pet_backdrop",
+    "show_pet_bubble",
+    "hide_pet_bubble",
+    "show_pet_menu",
+    "select_pet_menu_item",
+    "set_pet_state",
+    "get_pet_state",
+    "get_active_persona",
+    "list_timers",
+    "cancel_timer",
+    "list_input_devices",
+    "get_mic_status",
+    "start_recording",
+    "stop_recording",
+    "start_audio_metering",
+    "stop_audio_metering",
+    "list_output_devices",
+    "play_audio",
+    "stop_audio",
+    "stream_tts",
+    "cancel_tts",
+    "get_wake_word_status",
+    "transcribe",
+    "synthesize",
+    "run_turn",
+    "cancel_turn",
+    "set_event_filter",
+];
+
+// 字幕窗口只读取设置用于排版
+const CAPTIONS_COMMANDS: &[&str] = &["get_app_info", "get_theme", "get_settings", "set_event_filter"];
+
+// 桌宠气泡窗口只读取当前消息和关闭气泡
+const BUBBLE_COMMANDS: &[&str] = &[
+    "get_app_info",
+    "get_theme",
+    "get_settings",
+    "get_pet_bubble",
+    "hide_pet_bubble",
+    "set_event_filter",
+];
+
+// 快速提问窗口只能提交问题、录音识别和关闭自身
+const QUICK_ASK_COMMANDS: &[&str] = &[
+    "get_app_info",
+    "get_theme",
+    "get_settings",
+    "start_recording",
+    "stop_recording",
+    "start_audio_metering",
+    "stop_audio_metering",
+    "transcribe",
+    "submit_quick_ask",
+    "close_quick_ask",
+    "get_active_app_context",
+    "set_event_filter",
+];
+
+// 配置选择窗口只能列出、新建和切换配置
+const PICKER_COMMANDS: &[&str] = &[
+    "get_app_info",
+    "get_theme",
+    "list_profiles",
+    "create_profile",
+    "switch_profile",
+    "set_profile_picker",
+];
+
+// 主窗口可以调用全部命令，其他窗口只能调用各自列表中的命令，未知窗口一律拒绝
+pub fn check(label: &str, command: &str) -> Result<(), AppError> {
+    let allowed = match label {
+        MAIN_LABEL => return Ok(()),
+        PET_LABEL => PET_COMMANDS,
+        QUICK_ASK_LABEL => QUICK_ASK_COMMANDS,
+        CAPTIONS_LABEL => CAPTIONS_COMMANDS,
+        BUBBLE_LABEL => BUBBLE_COMMANDS,
+        PICKER_LABEL => PICKER_COMMANDS,
+        _ => &[],
+    };
+    if allowed.contains(&command) {
+        return Ok(());
+    }
+    warn!("Rejected command {} from window {}", command, label);
+    Err(AppError::PermissionDenied(format!("窗口 {} 无权调用 {}", label, command)))
+}
+
+// 包装 generate_handler! 生成的处理函数，在分发命令之前检查调用窗口
+pub fn guard<F>(handler: F) -> impl Fn(Invoke) + Send + Sync + 'static
+where
+    F: Fn(Invoke) + Send + Sync + 'static,
+{
+    move |invoke: Invoke| {
+        let message = &invoke.message;
+        if let Err(e) = check(message.window_ref().label(), message.command()) {
+            invoke.resolver.reject(e);
+            return;
+        }
+        handler(invoke)
+    }
+}
@@ -0,0 +1,328 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Monitor, PhysicalPosition, PhysicalSize, WindowBuilder, WindowUrl};
+use tracing::{info, warn};
+
+use crate::audio::AudioPlayer;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::pet::MonitorSelector;
+use crate::window_state::MAIN_LABEL;
+use crate::{settings, AppState};
+
+pub const CAPTIONS_LABEL: &str = "captions";
+
+const MIN_FONT_SIZE: u32 = 12;
+const MAX_FONT_SIZE: u32 = 72;
+// 字幕条占显示器宽度的比例，以及距屏幕底部的距离（逻辑像素）
+const WIDTH_RATIO: f64 = 0.8;
+const BOTTOM_MARGIN: f64 = 60.0;
+// 朗读结束后字幕的停留时间
+const LINGER: Duration = Duration::from_millis(800);
+// 等待播放进度的检查间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(30);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CaptionSettings {
+    pub enabled: bool,
+    // 显示器名称，None 表示主显示器
+    pub monitor: Option<String>,
+    // 字号（逻辑像素）
+    pub font_size: u32,
+    // 语音合成不提供逐词时间戳，按语速估算每个词在音频中的位置：每秒汉字数，英文按字母数折算
+    pub chars_per_second: f32,
+}
+
+impl Default for CaptionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            monitor: None,
+            font_size: 28,
+            chars_per_second: 4.5,
+        }
+    }
+}
+
+impl CaptionSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(MIN_FONT_SIZE..=MAX_FONT_SIZE).contains(&self.font_size) {
+            return Err(AppError::invalid(format!(
+                "字幕字号需在 {} - {} 之间",
+                MIN_FONT_SIZE, MAX_FONT_SIZE
+            )));
+        }
+        if !(1.0..=20.0).contains(&self.chars_per_second) {
+            return Err(AppError::invalid("字幕语速需在 1 - 20 字/秒之间"));
+        }
+        Ok(())
+    }
+}
+
+// 播放进度到达一个词的估算位置时推送，sentence 为当前整句，offset 为该词结束位置（字符数）用于高亮已读部分
+#[derive(Debug, Clone, Serialize)]
+pub struct CaptionCue {
+    pub playback: u64,
+    pub sentence: String,
+    pub word: String,
+    pub offset: usize,
+    pub start_ms: u64,
+    pub duration_ms: u64,
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{3040}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ac00}'..='\u{d7af}')
+}
+
+fn ends_sentence(c: char) -> bool {
+    matches!(c, '。' | '！' | '？' | '；' | '.' | '!' | '?' | ';')
+}
+
+// 按句切分，每句再切成词：汉字逐字，其他文字按空白分词，标点并入前一个词
+fn segment(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut sentences = Vec::new();
+    let mut sentence = String::new();
+    let mut words: Vec<String> = Vec::new();
+    let mut word = String::new();
+
+    let mut flush_sentence = |sentence: &mut String, words: &mut Vec<String>| {
+        let trimmed = sentence.trim();
+        if !trimmed.is_empty() && !words.is_empty() {
+            sentences.push((trimmed.to_string(), std::mem::take(words)));
+        }
+        words.clear();
+        sentence.clear();
+    };
+
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            if c == '\n' {
+                flush_sentence(&mut sentence, &mut words);
+            } else if !sentence.is_empty() {
+                sentence.push(' ');
+            }
+            continue;
+        }
+        sentence.push(c);
+        if is_cjk(c) {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            words.push(c.to_string());
+        } else if c.is_alphanumeric() || !word.is_empty() {
+            word.push(c);
+        } else if let Some(last) = words.last_mut() {
+            last.push(c);
+        } else {
+            word.push(c);
+        }
+        if ends_sentence(c) {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            flush_sentence(&mut sentence, &mut words);
+        }
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    flush_sentence(&mut sentence, &mut words);
+    sentences
+}
+
+// 估算一个词的朗读时长，标点处额外停顿
+fn word_duration(word: &str, chars_per_second: f32) -> Duration {
+    let weight: f32 = word
+        .chars()
+        .map(|c| {
+            if is_cjk(c) {
+                1.0
+            } else if c.is_alphanumeric() {
+                0.35
+            } else {
+                0.5
+            }
+        })
+        .sum();
+    Duration::from_secs_f32(weight.max(0.5) / chars_per_second)
+}
+
+// 词的位置按语速估算，时钟取实际播放进度，合成延迟或流式数据卡顿时字幕随之等待；播放结束或被打断时清空字幕
+pub fn feed(app: &AppHandle, playback: u64, text: &str) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.captions.clone(),
+        Err(_) => return,
+    };
+    if !settings.enabled {
+        return;
+    }
+
+    let app = app.clone();
+    let sentences = segment(text);
+    tauri::async_runtime::spawn(async move {
+        let player = app.state::<AudioPlayer>();
+        let mut elapsed = Duration::ZERO;
+        'sentences: for (sentence, words) in sentences {
+            let mut offset = 0;
+            for word in words {
+                loop {
+                    match player.position(playback) {
+                        Some(position) if position >= elapsed => break,
+                        Some(_) => tokio::time::sleep(POLL_INTERVAL).await,
+                        None => break 'sentences,
+                    }
+                }
+                let duration = word_duration(&word, settings.chars_per_second);
+                // 词前后的空白已被去掉，找不到时保持上一个位置
+                if let Some(found) = sentence[offset..].find(word.as_str()) {
+                    offset += found + word.len();
+                }
+                let cue = CaptionCue {
+                    playback,
+                    sentence: sentence.clone(),
+                    offset: sentence[..offset].chars().count(),
+                    word,
+                    start_ms: elapsed.as_millis() as u64,
+                    duration_ms: duration.as_millis() as u64,
+                };
+                events::publish(&app, AppEvent::Caption(cue));
+                elapsed += duration;
+            }
+        }
+        // 估算偏快时等到播放结束再清空
+        while player.current_id() == Some(playback) {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+        tokio::time::sleep(LINGER).await;
+        events::publish(&app, AppEvent::CaptionCleared { playback });
+    });
+}
+
+fn target_monitor(window: &tauri::Window, name: Option<&str>) -> Option<Monitor> {
+    let by_name = name.and_then(|name| {
+        window
+            .available_monitors()
+            .unwrap_or_default()
+            .into_iter()
+            .find(|monitor| monitor.name().map(String::as_str) == Some(name))
+    });
+    by_name.or_else(|| window.primary_monitor().ok().flatten())
+}
+
+// 字幕条高度按三行字计算，宽度随显示器变化
+fn place(window: &tauri::Window, settings: &CaptionSettings) -> Result<(), AppError> {
+    let Some(monitor) = target_monitor(window, settings.monitor.as_deref()) else {
+        return Ok(());
+    };
+    let scale = monitor.scale_factor();
+    let origin = monitor.position();
+    let screen = monitor.size();
+    let width = (screen.width as f64 * WIDTH_RATIO).round() as u32;
+    let height = (settings.font_size as f64 * 3.0 * scale).round() as u32;
+    let margin = (BOTTOM_MARGIN * scale).round() as i32;
+    window.set_size(PhysicalSize::new(width, height))?;
+    window.set_position(PhysicalPosition::new(
+        origin.x + (screen.width - width) as i32 / 2,
+        origin.y + screen.height as i32 - height as i32 - margin,
+    ))?;
+    Ok(())
+}
+
+fn open(app: &AppHandle, settings: &CaptionSettings) -> Result<(), AppError> {
+    if let Some(window) = app.get_window(CAPTIONS_LABEL) {
+        return place(&window, settings);
+    }
+
+    let window = WindowBuilder::new(app, CAPTIONS_LABEL, WindowUrl::App("captions-window".into()))
+        .title("")
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .visible(false)
+        .build()?;
+    // 字幕条不拦截鼠标，下方的窗口照常操作
+    window.set_ignore_cursor_events(true)?;
+    place(&window, settings)?;
+    window.show()?;
+    info!("Caption window opened");
+    Ok(())
+}
+
+// 按设置打开、移动或关闭字幕窗口，启动时和字幕设置变化时调用
+// 调用方可能是在主线程执行的同步命令（包括 update_settings），Windows 上在其中创建窗口会死锁，因此放到异步任务中执行
+pub fn apply(app: &AppHandle, settings: &CaptionSettings) {
+    let app = app.clone();
+    let settings = settings.clone();
+    tauri::async_runtime::spawn(async move {
+        let result = match (settings.enabled, app.get_window(CAPTIONS_LABEL)) {
+            (true, _) => open(&app, &settings),
+            (false, Some(window)) => window.close().map_err(AppError::from),
+            (false, None) => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!("Failed to update caption window: {}", e);
+        }
+    });
+}
+
+pub fn start_if_enabled(app: &AppHandle) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.captions.clone(),
+        Err(_) => return,
+    };
+    if settings.enabled {
+        apply(app, &settings);
+    }
+}
+
+fn update(app: &AppHandle, change: impl FnOnce(&mut CaptionSettings)) -> Result<CaptionSettings, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let mut captions = settings.captions.clone();
+    change(&mut captions);
+    captions.validate()?;
+    settings.captions = captions.clone();
+    settings::save_and_notify(app, &settings, &["captions"])?;
+    drop(settings);
+
+    apply(app, &captions);
+    Ok(captions)
+}
+
+#[tauri::command]
+pub fn set_captions_enabled(app: AppHandle, enabled: bool) -> Result<CaptionSettings, AppError> {
+    update(&app, |captions| captions.enabled = enabled)
+}
+
+// monitor 为 None 时使用主显示器
+#[tauri::command]
+pub fn set_caption_monitor(app: AppHandle, monitor: Option<MonitorSelector>) -> Result<CaptionSettings, AppError> {
+    let name = match monitor {
+        Some(selector) => {
+            let window = app
+                .get_window(MAIN_LABEL)
+                .ok_or_else(|| AppError::Window("主窗口不存在".to_string()))?;
+            let monitors = window.available_monitors()?;
+            let target = match &selector {
+                MonitorSelector::Index(index) => monitors.get(*index),
+                MonitorSelector::Name(name) => monitors.iter().find(|monitor| monitor.name() == Some(name)),
+            }
+            .ok_or_else(|| AppError::NotFound(format!("找不到显示器: {:?}", selector)))?;
+            target.name().cloned()
+        }
+        None => None,
+    };
+    update(&app, |captions| captions.monitor = name)
+}
+
+#[tauri::command]
+pub fn set_caption_font_size(app: AppHandle, size: u32) -> Result<CaptionSettings, AppError> {
+    update(&app, |captions| captions.font_size = size)
+}
@@ -0,0 +1,109 @@
+use clap::error::ErrorKind;
+use clap::Parser;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::data::{self, ExportBundle, ExportFormat};
+use crate::deep_link::{self, DeepLink};
+use crate::error::AppError;
+use crate::AppState;
+
+// 命令行参数：首个实例在 setup 中执行，后续实例把参数转发给首个实例执行
+#[derive(Debug, Clone, Default, Parser)]
+#[command(name = "lingecho", about = "声驭智核 - 智能语音助手", version)]
+pub struct CliArgs {
+    #[arg(long, value_name = "TEXT", help = "向助手提问")]
+    pub ask: Option<String>,
+    // 按扩展名选择 zip 或 json 格式
+    #[arg(long, value_name = "PATH", help = "导出设置和聊天记录")]
+    pub export: Option<PathBuf>,
+    #[arg(long, help = "显示或隐藏桌宠")]
+    pub toggle_pet: bool,
+    #[arg(long, help = "启动时不显示主窗口")]
+    pub minimized: bool,
+    // 已有实例运行时切换到该配置并重启
+    #[arg(long, value_name = "NAME", help = "使用指定的用户配置")]
+    pub profile: Option<String>,
+    #[arg(value_name = "URL", help = "lingecho:// 链接")]
+    pub url: Option<String>,
+}
+
+impl CliArgs {
+    // 解析本进程的参数，--help / --version 打印信息后退出；
+    // 旧版 macOS 从 Finder 启动时会附带 -psn_ 参数，需要忽略
+    pub fn from_env() -> Result<Self, AppError> {
+        let argv = std::env::args().filter(|arg| !arg.starts_with("-psn_"));
+        match Self::try_parse_from(argv) {
+            Ok(args) => Ok(args),
+            Err(e) if matches!(e.kind(), ErrorKind::DisplayHelp | ErrorKind::DisplayVersion) => e.exit(),
+            Err(e) => Err(AppError::invalid(e.to_string())),
+        }
+    }
+
+    // 解析其他实例转发的参数（不含程序名）
+    pub fn from_forwarded(args: &[String], cwd: &str) -> Result<Self, AppError> {
+        let argv = std::iter::once("lingecho".to_string())
+            .chain(args.iter().filter(|arg| !arg.starts_with("-psn_")).cloned());
+        let mut parsed = Self::try_parse_from(argv).map_err(|e| AppError::invalid(e.to_string()))?;
+        // 相对路径相对于转发方的工作目录
+        if let Some(path) = parsed.export.as_mut() {
+            if path.is_relative() && !cwd.is_empty() {
+                *path = PathBuf::from(cwd).join(&*path);
+            }
+        }
+        Ok(parsed)
+    }
+
+    pub fn has_actions(&self) -> bool {
+        self.ask.is_some() || self.export.is_some() || self.toggle_pet || self.url.is_some()
+    }
+
+    // 只导出数据时不显示窗口，完成后退出
+    pub fn is_headless(&self) -> bool {
+        self.export.is_some() && self.ask.is_none() && !self.toggle_pet && self.url.is_none()
+    }
+}
+
+fn export_to(app: &AppHandle, path: &std::path::Path) -> Result<(), AppError> {
+    let format = match path.extension().and_then(|ext| ext.to_str()) {
+        Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+        _ => ExportFormat::Zip,
+    };
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock()?.clone();
+    let bundle = ExportBundle::new(settings, state.storage.export_history()?, data::collect_pet_state(app));
+    bundle.write_to(path, format, None)?;
+    info!("Data exported to {}", path.display());
+    Ok(())
+}
+
+// 执行参数中的动作，返回是否执行了任何动作
+pub fn run(app: &AppHandle, args: &CliArgs) -> bool {
+    if let Some(path) = &args.export {
+        if let Err(e) = export_to(app, path) {
+            warn!("Failed to export data to {}: {}", path.display(), e);
+        }
+    }
+    let mut links = Vec::new();
+    if args.toggle_pet {
+        links.push(DeepLink::Pet {
+            command: "toggle".to_string(),
+        });
+    }
+    if let Some(text) = &args.ask {
+        links.push(DeepLink::Ask { text: text.clone() });
+    }
+    if let Some(url) = &args.url {
+        match deep_link::parse(url) {
+            Ok(link) => links.push(link),
+            Err(e) => warn!("{}", e),
+        }
+    }
+    for link in links {
+        if let Err(e) = deep_link::dispatch(app, &link) {
+            warn!("Failed to run command line action: {}", e);
+        }
+    }
+    args.has_actions()
+}
@@ -0,0 +1,161 @@
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use base64::Engine;
+
+use crate::error::AppError;
+
+// 密码加密的文件格式：魔数 | Argon2id 参数 m、t、p（u32 小端）| 盐 | nonce | AES-256-GCM 密文。
+// 魔数和参数作为附加数据参与认证，被篡改时解密失败
+const MAGIC: &[u8; 8] = b"LINGENC1";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 12 + SALT_LEN;
+// 解密时接受的最大参数，避免构造的文件耗尽内存或让密钥派生长时间占用 CPU
+const MAX_MEMORY_KIB: u32 = 1 << 20;
+const MAX_TIME_COST: u32 = 16;
+const MAX_PARALLELISM: u32 = 16;
+
+pub fn is_encrypted(raw: &[u8]) -> bool {
+    raw.starts_with(MAGIC)
+}
+
+fn derive_key(password: &str, salt: &[u8], params: Params) -> Result<[u8; 32], AppError> {
+    let mut key = [0u8; 32];
+    Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| AppError::Internal(format!("密钥派生失败: {}", e)))?;
+    Ok(key)
+}
+
+pub fn encrypt_with_password(plain: &[u8], password: &str) -> Result<Vec<u8>, AppError> {
+    if password.is_empty() {
+        return Err(AppError::invalid("密码不能为空"));
+    }
+    let params = Params::default();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let mut output = Vec::with_capacity(HEADER_LEN + NONCE_LEN + plain.len() + 16);
+    output.extend_from_slice(MAGIC);
+    for value in [params.m_cost(), params.t_cost(), params.p_cost()] {
+        output.extend_from_slice(&value.to_le_bytes());
+    }
+    output.extend_from_slice(&salt);
+
+    let key = derive_key(password, &salt, params)?;
+    let cipher = Aes256Gcm::new((&key).into());
+    let payload = Payload {
+        msg: plain,
+        aad: &output[..HEADER_LEN],
+    };
+    let sealed = cipher
+        .encrypt(&Nonce::from(nonce), payload)
+        .map_err(|_| AppError::Internal("加密失败".to_string()))?;
+    output.extend_from_slice(&nonce);
+    output.extend_from_slice(&sealed);
+    Ok(output)
+}
+
+// 密码错误与文件被篡改无法区分，统一返回 PasswordRequired 由前端重新询问密码
+pub fn decrypt_with_password(raw: &[u8], password: &str) -> Result<Vec<u8>, AppError> {
+    if !is_encrypted(raw) || raw.len() < HEADER_LEN + NONCE_LEN {
+        return Err(AppError::invalid("不是有效的加密文件"));
+    }
+    let field = |index: usize| {
+        let start = MAGIC.len() + index * 4;
+        u32::from_le_bytes([raw[start], raw[start + 1], raw[start + 2], raw[start + 3]])
+    };
+    let (m_cost, t_cost, p_cost) = (field(0), field(1), field(2));
+    if m_cost > MAX_MEMORY_KIB || t_cost > MAX_TIME_COST || p_cost > MAX_PARALLELISM {
+        return Err(AppError::invalid("加密文件的密钥参数无效"));
+    }
+    let params = Params::new(m_cost, t_cost, p_cost, Some(32))
+        .map_err(|e| AppError::invalid(format!("加密文件的密钥参数无效: {}", e)))?;
+
+    let salt = &raw[HEADER_LEN - SALT_LEN..HEADER_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce.copy_from_slice(&raw[HEADER_LEN..HEADER_LEN + NONCE_LEN]);
+    let key = derive_key(password, salt, params)?;
+    let cipher = Aes256Gcm::new((&key).into());
+    let payload = Payload {
+        msg: &raw[HEADER_LEN + NONCE_LEN..],
+        aad: &raw[..HEADER_LEN],
+    };
+    cipher
+        .decrypt(&Nonce::from(nonce), payload)
+        .map_err(|_| AppError::PasswordRequired("密码错误或文件已损坏".to_string()))
+}
+
+// 字段级加密的密文前缀，之后为 base64 编码的 nonce | 密文；没有前缀的值视为明文
+const FIELD_PREFIX: &str = "enc1:";
+
+pub fn is_encrypted_field(value: &str) -> bool {
+    value.starts_with(FIELD_PREFIX)
+}
+
+// 使用随机密钥逐字段加密，密钥由调用方保存在系统钥匙串中
+#[derive(Clone)]
+pub struct FieldCipher {
+    cipher: Aes256Gcm,
+}
+
+impl FieldCipher {
+    // 返回 base64 编码的新密钥
+    pub fn generate_key() -> String {
+        let mut key = [0u8; 32];
+        OsRng.fill_bytes(&mut key);
+        base64::engine::general_purpose::STANDARD.encode(key)
+    }
+
+    pub fn from_key(encoded: &str) -> Result<Self, AppError> {
+        let key = base64::engine::general_purpose::STANDARD
+            .decode(encoded.trim())
+            .ok()
+            .filter(|key| key.len() == 32)
+            .ok_or_else(|| AppError::invalid("数据库密钥格式无效"))?;
+        let cipher = Aes256Gcm::new_from_slice(&key).map_err(|_| AppError::invalid("数据库密钥格式无效"))?;
+        Ok(Self { cipher })
+    }
+
+    // 空字符串保持为空，方便按是否为空判断字段是否有值
+    pub fn encrypt(&self, plain: &str) -> Result<String, AppError> {
+        if plain.is_empty() {
+            return Ok(String::new());
+        }
+        let mut nonce = [0u8; NONCE_LEN];
+        OsRng.fill_bytes(&mut nonce);
+        let sealed = self
+            .cipher
+            .encrypt(&Nonce::from(nonce), plain.as_bytes())
+            .map_err(|_| AppError::Internal("加密失败".to_string()))?;
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&sealed);
+        Ok(format!(
+            "{}{}",
+            FIELD_PREFIX,
+            base64::engine::general_purpose::STANDARD.encode(payload)
+        ))
+    }
+
+    pub fn decrypt(&self, value: &str) -> Result<String, AppError> {
+        let Some(encoded) = value.strip_prefix(FIELD_PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let payload = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .ok()
+            .filter(|payload| payload.len() > NONCE_LEN)
+            .ok_or_else(|| AppError::invalid("密文格式无效"))?;
+        let mut nonce = [0u8; NONCE_LEN];
+        nonce.copy_from_slice(&payload[..NONCE_LEN]);
+        let plain = self
+            .cipher
+            .decrypt(&Nonce::from(nonce), &payload[NONCE_LEN..])
+            .map_err(|_| AppError::invalid("无法解密：密钥不匹配或数据已损坏"))?;
+        String::from_utf8(plain).map_err(|_| AppError::invalid("解密结果不是有效的文本"))
+    }
+}
@@ -0,0 +1,385 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::Manager;
+use zip::write::FileOptions;
+
+use crate::crypto;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::pet::PET_LABEL;
+use crate::settings::Settings;
+use crate::storage::Storage;
+
+// 导出数据格式版本，导入时用于兼容性检查
+pub const EXPORT_VERSION: u32 = 1;
+
+const ZIP_MANIFEST: &str = "manifest.json";
+const ZIP_SETTINGS: &str = "settings.json";
+const ZIP_HISTORY: &str = "history.json";
+const ZIP_PET: &str = "pet.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Json,
+    #[default]
+    Zip,
+}
+
+impl ExportFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Zip => "zip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportManifest {
+    pub version: u32,
+    pub app_version: String,
+    pub exported_at: u64,
+}
+
+// 完整的导出内容，JSON 格式直接序列化该结构
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportBundle {
+    #[serde(flatten)]
+    pub manifest: ExportManifest,
+    pub settings: Settings,
+    #[serde(default)]
+    pub history: Vec<serde_json::Value>,
+    #[serde(default)]
+    pub pet: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportStrategy {
+    // 与本地数据合并，冲突时保留本地数据
+    #[default]
+    Merge,
+    // 用导入数据整体覆盖本地数据
+    Replace,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportConflict {
+    pub section: String,
+    pub key: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub strategy: ImportStrategy,
+    pub source_version: u32,
+    pub imported: usize,
+    pub skipped: usize,
+    pub conflicts: Vec<ImportConflict>,
+}
+
+impl ImportReport {
+    fn new(strategy: ImportStrategy, source_version: u32) -> Self {
+        Self {
+            strategy,
+            source_version,
+            imported: 0,
+            skipped: 0,
+            conflicts: Vec::new(),
+        }
+    }
+
+    fn conflict(&mut self, section: &str, key: &str, reason: &str) {
+        self.skipped += 1;
+        self.conflicts.push(ImportConflict {
+            section: section.to_string(),
+            key: key.to_string(),
+            reason: reason.to_string(),
+        });
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ExportProgress {
+    pub stage: String,
+    pub progress: u8,
+}
+
+pub fn emit_progress(app: &tauri::AppHandle, event: fn(ExportProgress) -> AppEvent, stage: &str, progress: u8) {
+    let payload = ExportProgress {
+        stage: stage.to_string(),
+        progress,
+    };
+    events::publish(app, event(payload));
+}
+
+pub fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl ExportBundle {
+    pub fn new(settings: Settings, history: Vec<serde_json::Value>, pet: serde_json::Value) -> Self {
+        Self {
+            manifest: ExportManifest {
+                version: EXPORT_VERSION,
+                app_version: env!("CARGO_PKG_VERSION").to_string(),
+                exported_at: now_secs(),
+            },
+            settings,
+            history,
+            pet,
+        }
+    }
+
+    // 设置了密码时整个文件加密后再写入
+    pub fn write_to(&self, path: &Path, format: ExportFormat, password: Option<&str>) -> Result<(), AppError> {
+        let content = match format {
+            ExportFormat::Json => serde_json::to_vec_pretty(self)?,
+            ExportFormat::Zip => self.zip_bytes()?,
+        };
+        let content = match password {
+            Some(password) => crypto::encrypt_with_password(&content, password)?,
+            None => content,
+        };
+        std::fs::write(path, content).map_err(AppError::from)
+    }
+
+    // 根据文件头识别加密文件、ZIP 或 JSON 格式并读取导出内容
+    pub fn read_from(path: &Path, password: Option<&str>) -> Result<Self, AppError> {
+        let mut raw = std::fs::read(path)?;
+        if crypto::is_encrypted(&raw) {
+            let password =
+                password.ok_or_else(|| AppError::PasswordRequired("导出文件已加密，请输入密码".to_string()))?;
+            raw = crypto::decrypt_with_password(&raw, password)?;
+        }
+        let bundle = if raw.starts_with(b"PK") {
+            Self::read_zip(&raw)?
+        } else {
+            serde_json::from_slice(&raw).map_err(|e| AppError::invalid(format!("导出文件格式无效: {}", e)))?
+        };
+        bundle.validate()?;
+        Ok(bundle)
+    }
+
+    fn read_zip(raw: &[u8]) -> Result<Self, AppError> {
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(raw))
+            .map_err(|e| AppError::invalid(format!("导出文件格式无效: {}", e)))?;
+
+        let mut read_entry = |name: &str, required: bool| -> Result<Option<Vec<u8>>, AppError> {
+            match archive.by_name(name) {
+                Ok(mut entry) => {
+                    let mut buf = Vec::new();
+                    entry.read_to_end(&mut buf)?;
+                    Ok(Some(buf))
+                }
+                Err(zip::result::ZipError::FileNotFound) if !required => Ok(None),
+                Err(e) => Err(AppError::invalid(format!("导出文件缺少 {}: {}", name, e))),
+            }
+        };
+
+        let manifest = read_entry(ZIP_MANIFEST, true)?.unwrap_or_default();
+        let settings = read_entry(ZIP_SETTINGS, true)?.unwrap_or_default();
+        let history = read_entry(ZIP_HISTORY, false)?;
+        let pet = read_entry(ZIP_PET, false)?;
+
+        let parse_err = |name: &str, e: serde_json::Error| AppError::invalid(format!("{} 格式无效: {}", name, e));
+        Ok(Self {
+            manifest: serde_json::from_slice(&manifest).map_err(|e| parse_err(ZIP_MANIFEST, e))?,
+            settings: serde_json::from_slice(&settings).map_err(|e| parse_err(ZIP_SETTINGS, e))?,
+            history: match history {
+                Some(raw) => serde_json::from_slice(&raw).map_err(|e| parse_err(ZIP_HISTORY, e))?,
+                None => Vec::new(),
+            },
+            pet: match pet {
+                Some(raw) => serde_json::from_slice(&raw).map_err(|e| parse_err(ZIP_PET, e))?,
+                None => serde_json::Value::Null,
+            },
+        })
+    }
+
+    fn validate(&self) -> Result<(), AppError> {
+        let version = self.manifest.version;
+        if version == 0 || version > EXPORT_VERSION {
+            return Err(AppError::invalid(format!(
+                "不支持的导出文件版本 {}（当前支持 1 - {}）",
+                version, EXPORT_VERSION
+            )));
+        }
+        if self.history.iter().any(|item| !item.is_object()) {
+            return Err(AppError::invalid("历史记录格式无效：每条记录必须是对象"));
+        }
+        Ok(())
+    }
+
+    // ZIP 内每个数据分区单独存放，方便手动查看和后续增量扩展
+    fn zip_bytes(&self) -> Result<Vec<u8>, AppError> {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        let options = FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+        let entries = [
+            (ZIP_MANIFEST, serde_json::to_vec_pretty(&self.manifest)),
+            (ZIP_SETTINGS, serde_json::to_vec_pretty(&self.settings)),
+            (ZIP_HISTORY, serde_json::to_vec_pretty(&self.history)),
+            (ZIP_PET, serde_json::to_vec_pretty(&self.pet)),
+        ];
+
+        for (name, content) in entries {
+            let content = content?;
+            zip.start_file(name, options).map_err(zip_error)?;
+            zip.write_all(&content)?;
+        }
+
+        let cursor = zip.finish().map_err(zip_error)?;
+        Ok(cursor.into_inner())
+    }
+}
+
+fn zip_error(error: zip::result::ZipError) -> AppError {
+    match error {
+        zip::result::ZipError::Io(e) => e.into(),
+        other => AppError::Internal(other.to_string()),
+    }
+}
+
+// 采集桌宠窗口的当前状态（位置、可见性）
+pub fn collect_pet_state(app: &tauri::AppHandle) -> serde_json::Value {
+    let Some(window) = app.get_window(PET_LABEL) else {
+        return serde_json::Value::Null;
+    };
+
+    let position = window.outer_position().ok().map(|p| serde_json::json!({ "x": p.x, "y": p.y }));
+    let visible = window.is_visible().unwrap_or(false);
+
+    serde_json::json!({
+        "position": position,
+        "visible": visible,
+    })
+}
+
+// 将导入的桌宠状态应用到当前窗口
+fn apply_pet_state(app: &tauri::AppHandle, pet: &serde_json::Value) -> Result<bool, AppError> {
+    let Some(window) = app.get_window(PET_LABEL) else {
+        return Ok(false);
+    };
+
+    let x = pet.pointer("/position/x").and_then(|v| v.as_i64());
+    let y = pet.pointer("/position/y").and_then(|v| v.as_i64());
+    if let (Some(x), Some(y)) = (x, y) {
+        window.set_position(tauri::PhysicalPosition::new(x as i32, y as i32))?;
+    }
+
+    match pet.get("visible").and_then(|v| v.as_bool()) {
+        Some(true) => window.show()?,
+        Some(false) => window.hide()?,
+        None => {}
+    }
+    Ok(true)
+}
+
+// 历史记录以 id 作为唯一键，没有 id 的记录按完整内容去重
+fn history_key(item: &serde_json::Value) -> String {
+    match item.get("id") {
+        Some(serde_json::Value::String(id)) => id.clone(),
+        Some(id) if !id.is_null() => id.to_string(),
+        _ => item.to_string(),
+    }
+}
+
+fn merge_settings(local: &Settings, incoming: &Settings, report: &mut ImportReport) -> Result<Settings, AppError> {
+    let to_map = |settings: &Settings| -> Result<serde_json::Map<String, serde_json::Value>, AppError> {
+        match serde_json::to_value(settings)? {
+            serde_json::Value::Object(map) => Ok(map),
+            _ => Err(AppError::Internal("设置序列化结果不是对象".to_string())),
+        }
+    };
+
+    let defaults = to_map(&Settings::default())?;
+    let mut merged = to_map(local)?;
+
+    for (key, value) in to_map(incoming)? {
+        let current = merged.get(&key).cloned().unwrap_or(serde_json::Value::Null);
+        if current == value {
+            report.skipped += 1;
+        } else if defaults.get(&key) == Some(&current) {
+            // 本地仍是默认值，说明用户未修改过，直接采用导入值
+            merged.insert(key, value);
+            report.imported += 1;
+        } else {
+            report.conflict("settings", &key, "本地已修改该设置，保留本地值");
+        }
+    }
+
+    serde_json::from_value(serde_json::Value::Object(merged)).map_err(AppError::from)
+}
+
+pub fn apply_import(
+    app: &tauri::AppHandle,
+    bundle: ExportBundle,
+    strategy: ImportStrategy,
+    settings: &mut Settings,
+    storage: &Storage,
+) -> Result<ImportReport, AppError> {
+    let mut report = ImportReport::new(strategy, bundle.manifest.version);
+
+    match strategy {
+        ImportStrategy::Replace => {
+            // 先校验设置，无效时不清除任何本地数据
+            bundle.settings.validate()?;
+            *settings = bundle.settings;
+            report.imported += 1;
+
+            storage.clear_history()?;
+            for item in bundle.history {
+                let key = history_key(&item);
+                if storage.import_record(item)? {
+                    report.imported += 1;
+                } else {
+                    report.conflict("history", &key, "记录格式无效，已跳过");
+                }
+            }
+
+            if !bundle.pet.is_null() {
+                if apply_pet_state(app, &bundle.pet)? {
+                    report.imported += 1;
+                } else {
+                    report.skipped += 1;
+                }
+            }
+        }
+        ImportStrategy::Merge => {
+            let merged = merge_settings(settings, &bundle.settings, &mut report)?;
+            merged.validate()?;
+            *settings = merged;
+
+            let existing: HashMap<String, serde_json::Value> = storage
+                .export_history()?
+                .into_iter()
+                .map(|item| (history_key(&item), item))
+                .collect();
+            for item in bundle.history {
+                let key = history_key(&item);
+                match existing.get(&key) {
+                    Some(current) if *current == item => report.skipped += 1,
+                    Some(_) => report.conflict("history", &key, "同一记录内容不一致，保留本地版本"),
+                    None if storage.import_record(item)? => report.imported += 1,
+                    None => report.conflict("history", &key, "记录格式无效，已跳过"),
+                }
+            }
+
+            // 桌宠位置与显示器布局相关，合并模式下保留本机状态
+            if !bundle.pet.is_null() {
+                report.skipped += 1;
+            }
+        }
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,164 @@
+use serde::Serialize;
+use tauri::{AppHandle, Url};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{pet, tray};
+
+pub const SCHEME: &str = "lingecho";
+
+// 解析后的链接动作
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum DeepLink {
+    // lingecho://ask?text=...
+    Ask { text: String },
+    // lingecho://pet/toggle、pet/show、pet/hide
+    Pet { command: String },
+    // 其他链接交给前端路由，例如 lingecho://note/123 对应 /note/123
+    Navigate { route: String },
+}
+
+pub fn parse(url: &str) -> Result<DeepLink, AppError> {
+    let url = Url::parse(url).map_err(|e| AppError::invalid(format!("无效的链接 {}: {}", url, e)))?;
+    if url.scheme() != SCHEME {
+        return Err(AppError::invalid(format!("不支持的链接协议: {}", url.scheme())));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let segments: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    match host {
+        "ask" => {
+            let text = url
+                .query_pairs()
+                .find(|(key, _)| key == "text" || key == "q")
+                .map(|(_, value)| value.trim().to_string())
+                .filter(|text| !text.is_empty())
+                .ok_or_else(|| AppError::invalid("ask 链接缺少 text 参数"))?;
+            Ok(DeepLink::Ask { text })
+        }
+        "pet" => {
+            let command = segments.first().copied().unwrap_or("toggle");
+            match command {
+                "toggle" | "show" | "hide" => Ok(DeepLink::Pet {
+                    command: command.to_string(),
+                }),
+                other => Err(AppError::invalid(format!("未知的桌宠操作: {}", other))),
+            }
+        }
+        "" => Err(AppError::invalid("链接缺少目标")),
+        _ => {
+            let mut route = format!("/{}", host);
+            for segment in segments {
+                route.push('/');
+                route.push_str(segment);
+            }
+            if let Some(query) = url.query() {
+                route.push('?');
+                route.push_str(query);
+            }
+            Ok(DeepLink::Navigate { route })
+        }
+    }
+}
+
+fn run_pet_command(app: &AppHandle, command: &str) {
+    let handle = app.clone();
+    let command = command.to_string();
+    tauri::async_runtime::spawn(async move {
+        let result = match command.as_str() {
+            "show" => pet::show_desktop_pet(handle).await,
+            "hide" => pet::hide_desktop_pet(handle),
+            _ => pet::toggle_desktop_pet(handle).await.map(|_| ()),
+        };
+        if let Err(e) = result {
+            warn!("Failed to run pet command {}: {}", command, e);
+        }
+    });
+}
+
+// 桌宠操作直接执行，其余显示主窗口后以 deep-link 事件交给前端
+pub fn dispatch(app: &AppHandle, link: &DeepLink) -> Result<(), AppError> {
+    match link {
+        DeepLink::Pet { command } => run_pet_command(app, command),
+        DeepLink::Ask { .. } | DeepLink::Navigate { .. } => {
+            tray::show_main_window(app);
+            events::publish(app, AppEvent::DeepLink(link.clone()));
+        }
+    }
+    Ok(())
+}
+
+pub fn open(app: &AppHandle, url: &str) -> Result<DeepLink, AppError> {
+    let link = parse(url)?;
+    info!("Opening deep link {}", url);
+    dispatch(app, &link)?;
+    Ok(link)
+}
+
+#[cfg(windows)]
+fn reg_add(args: &[&str]) -> Result<(), AppError> {
+    use std::os::windows::process::CommandExt;
+
+    let status = std::process::Command::new("reg")
+        .arg("add")
+        .args(args)
+        .arg("/f")
+        .creation_flags(crate::backend::CREATE_NO_WINDOW)
+        .status()?;
+    if !status.success() {
+        return Err(AppError::Internal(format!("注册链接协议失败: reg add {:?}", args)));
+    }
+    Ok(())
+}
+
+// 把 lingecho:// 协议注册到当前用户，macOS 通过打包时的 Info.plist 注册
+#[cfg(windows)]
+pub fn register_scheme() -> Result<(), AppError> {
+    let exe = std::env::current_exe()?;
+    let key = format!("HKCU\\Software\\Classes\\{}", SCHEME);
+    let command = format!("\"{}\" \"%1\"", exe.display());
+    reg_add(&[&key, "/ve", "/d", "URL:LingEcho"])?;
+    reg_add(&[&key, "/v", "URL Protocol", "/d", ""])?;
+    reg_add(&[&format!("{}\\shell\\open\\command", key), "/ve", "/d", &command])
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+pub fn register_scheme() -> Result<(), AppError> {
+    let exe = std::env::current_exe()?;
+    let dir = tauri::api::path::data_dir()
+        .ok_or("无法获取数据目录")?
+        .join("applications");
+    std::fs::create_dir_all(&dir)?;
+
+    let file_name = format!("{}-handler.desktop", SCHEME);
+    let entry = format!(
+        "[Desktop Entry]\nType=Application\nName=声驭智核\nExec=\"{}\" %u\nNoDisplay=true\nMimeType=x-scheme-handler/{};\n",
+        exe.display(),
+        SCHEME
+    );
+    std::fs::write(dir.join(&file_name), entry)?;
+
+    std::process::Command::new("xdg-mime")
+        .args(["default", &file_name, &format!("x-scheme-handler/{}", SCHEME)])
+        .status()
+        .map_err(|e| format!("无法运行 xdg-mime: {}", e))?;
+    Ok(())
+}
+
+// macOS 通过 Apple Event 而不是命令行参数传递链接，Tauri 1 没有暴露该事件，
+// 目前只通过 Info.plist 注册协议，链接由 open_deep_link 命令处理
+#[cfg(target_os = "macos")]
+pub fn register_scheme() -> Result<(), AppError> {
+    Ok(())
+}
+
+#[tauri::command]
+pub fn open_deep_link(app: AppHandle, url: String) -> Result<DeepLink, AppError> {
+    open(&app, &url)
+}
@@ -0,0 +1,221 @@
+use chrono::{Datelike, Local, Timelike};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{settings, AppState};
+
+// 检查日程和系统专注模式的间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+// 每周重复的免打扰时段，end 早于 start 时跨越午夜，相同时表示全天
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DndPeriod {
+    // 1 = 周一 … 7 = 周日，为空表示每天；跨午夜的时段按开始的那天计
+    #[serde(default)]
+    pub days: Vec<u8>,
+    // HH:MM
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DndSettings {
+    // 手动开启的免打扰，重启后保持
+    pub enabled: bool,
+    pub schedule: Vec<DndPeriod>,
+    // 系统开启专注模式/勿扰时同时进入免打扰（能检测到时）
+    pub follow_system: bool,
+}
+
+impl Default for DndSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            schedule: Vec::new(),
+            follow_system: true,
+        }
+    }
+}
+
+impl DndSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        for period in &self.schedule {
+            parse_time(&period.start)?;
+            parse_time(&period.end)?;
+            if period.days.iter().any(|day| !(1..=7).contains(day)) {
+                return Err(AppError::invalid("免打扰日期需在 1 - 7 之间"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DndStatus {
+    pub active: bool,
+    pub manual: bool,
+    pub scheduled: bool,
+    pub system: bool,
+}
+
+// 免打扰期间不发送通知、忽略唤醒词、桌宠不做空闲动作；手动语音交互不受影响
+#[derive(Default)]
+pub struct Dnd {
+    active: AtomicBool,
+    scheduled: AtomicBool,
+    system: AtomicBool,
+}
+
+impl Dnd {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<Dnd>().active.load(Ordering::SeqCst)
+}
+
+// 返回一天中的第几分钟
+fn parse_time(value: &str) -> Result<u32, AppError> {
+    let invalid = || AppError::invalid(format!("时间格式应为 HH:MM: {}", value));
+    let (hour, minute) = value.trim().split_once(':').ok_or_else(invalid)?;
+    let hour: u32 = hour.parse().map_err(|_| invalid())?;
+    let minute: u32 = minute.parse().map_err(|_| invalid())?;
+    if hour > 23 || minute > 59 {
+        return Err(invalid());
+    }
+    Ok(hour * 60 + minute)
+}
+
+// weekday 为 1 - 7，minute 为当天的第几分钟
+fn in_period(period: &DndPeriod, weekday: u8, minute: u32) -> bool {
+    let (Ok(start), Ok(end)) = (parse_time(&period.start), parse_time(&period.end)) else {
+        return false;
+    };
+    let on = |day: u8| period.days.is_empty() || period.days.contains(&day);
+    let yesterday = if weekday == 1 { 7 } else { weekday - 1 };
+    match start.cmp(&end) {
+        std::cmp::Ordering::Less => on(weekday) && (start..end).contains(&minute),
+        std::cmp::Ordering::Greater => (on(weekday) && minute >= start) || (on(yesterday) && minute < end),
+        std::cmp::Ordering::Equal => on(weekday),
+    }
+}
+
+fn scheduled_now(schedule: &[DndPeriod]) -> bool {
+    let now = Local::now();
+    let weekday = now.weekday().number_from_monday() as u8;
+    let minute = now.hour() * 60 + now.minute();
+    schedule.iter().any(|period| in_period(period, weekday, minute))
+}
+
+// Windows 只能检测到“安静时间”等系统状态，专注助手本身没有公开接口
+#[cfg(windows)]
+fn system_dnd() -> bool {
+    use windows_sys::Win32::UI::Shell::{SHQueryUserNotificationState, QUNS_QUIET_TIME};
+
+    let mut state = 0;
+    // SAFETY: state 是有效的输出指针
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+    result == 0 && state == QUNS_QUIET_TIME
+}
+
+// macOS 12 之前的勿扰开关；之后的专注模式没有公开的读取方式
+#[cfg(target_os = "macos")]
+fn system_dnd() -> bool {
+    std::process::Command::new("defaults")
+        .args(["-currentHost", "read", "com.apple.notificationcenterui", "doNotDisturb"])
+        .output()
+        .is_ok_and(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "1")
+}
+
+// GNOME 的“请勿打扰”即关闭通知横幅
+#[cfg(all(unix, not(target_os = "macos")))]
+fn system_dnd() -> bool {
+    std::process::Command::new("gsettings")
+        .args(["get", "org.gnome.desktop.notifications", "show-banners"])
+        .output()
+        .is_ok_and(|output| output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "false")
+}
+
+fn status(app: &AppHandle) -> Result<DndStatus, AppError> {
+    let manual = app.state::<AppState>().settings.lock()?.dnd.enabled;
+    let dnd = app.state::<Dnd>();
+    Ok(DndStatus {
+        active: dnd.active.load(Ordering::SeqCst),
+        manual,
+        scheduled: dnd.scheduled.load(Ordering::SeqCst),
+        system: dnd.system.load(Ordering::SeqCst),
+    })
+}
+
+// 重新计算日程和系统状态，变化时通知所有窗口
+pub fn refresh(app: &AppHandle) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.dnd.clone(),
+        Err(_) => return,
+    };
+    let scheduled = scheduled_now(&settings.schedule);
+    let system = settings.follow_system && system_dnd();
+    let active = settings.enabled || scheduled || system;
+
+    let dnd = app.state::<Dnd>();
+    dnd.scheduled.store(scheduled, Ordering::SeqCst);
+    dnd.system.store(system, Ordering::SeqCst);
+    if dnd.active.swap(active, Ordering::SeqCst) != active {
+        info!("Do not disturb {}", if active { "on" } else { "off" });
+        if let Ok(status) = status(app) {
+            events::publish(app, AppEvent::DndChanged(status));
+        }
+    }
+}
+
+// 在 setup 中调用：立即计算一次状态，之后在后台线程中定期检查
+pub fn start(app: &AppHandle) {
+    refresh(app);
+    let handle = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        refresh(&handle);
+    });
+}
+
+// 只修改传入的字段
+#[tauri::command]
+pub fn set_dnd(
+    app: AppHandle,
+    enabled: Option<bool>,
+    schedule: Option<Vec<DndPeriod>>,
+    follow_system: Option<bool>,
+) -> Result<DndStatus, AppError> {
+    let state = app.state::<AppState>();
+    {
+        let mut settings = state.settings.lock()?;
+        let mut dnd = settings.dnd.clone();
+        if let Some(enabled) = enabled {
+            dnd.enabled = enabled;
+        }
+        if let Some(schedule) = schedule {
+            dnd.schedule = schedule;
+        }
+        if let Some(follow_system) = follow_system {
+            dnd.follow_system = follow_system;
+        }
+        dnd.validate()?;
+        settings.dnd = dnd;
+        settings::save_and_notify(&app, &settings, &["dnd"])?;
+    }
+    refresh(&app);
+    status(&app)
+}
+
+#[tauri::command]
+pub fn get_dnd_status(app: AppHandle) -> Result<DndStatus, AppError> {
+    status(&app)
+}
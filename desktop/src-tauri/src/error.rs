@@ -0,0 +1,234 @@
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+// 所有命令统一返回的错误，序列化为 { code, message, details, retryable } 交给前端
+#[derive(Debug, Clone, PartialEq)]
+pub enum AppError {
+    // 参数或输入数据不合法
+    InvalidInput(String),
+    NotFound(String),
+    PermissionDenied(String),
+    // 文件已加密，需要（正确的）密码才能打开
+    PasswordRequired(String),
+    // 被用户取消或被新的操作打断
+    Cancelled(String),
+    // 后端服务、外部引擎或设备暂时不可用，稍后可以重试
+    Unavailable { message: String, details: Option<String> },
+    Timeout(String),
+    Network { message: String, details: Option<String> },
+    Io { message: String, details: Option<String> },
+    Window(String),
+    Database(String),
+    Internal(String),
+}
+
+impl AppError {
+    pub fn invalid(message: impl Into<String>) -> Self {
+        AppError::InvalidInput(message.into())
+    }
+
+    pub fn unavailable(message: impl Into<String>, details: impl fmt::Display) -> Self {
+        AppError::Unavailable {
+            message: message.into(),
+            details: Some(details.to_string()),
+        }
+    }
+
+    pub fn io(message: impl Into<String>, error: std::io::Error) -> Self {
+        AppError::Io {
+            message: message.into(),
+            details: Some(error.to_string()),
+        }
+    }
+
+    // 带上下文的网络错误，超时和连接失败可以重试
+    pub fn network(message: impl Into<String>, error: reqwest::Error) -> Self {
+        let message = message.into();
+        let details = Some(error.to_string());
+        if error.is_timeout() {
+            AppError::Timeout(message)
+        } else if error.is_connect() {
+            AppError::Unavailable { message, details }
+        } else {
+            AppError::Network { message, details }
+        }
+    }
+
+    // 服务返回的非成功状态码
+    pub fn status(message: impl Into<String>, status: reqwest::StatusCode) -> Self {
+        let message = format!("{}: {}", message.into(), status);
+        match status.as_u16() {
+            401 | 403 => AppError::PermissionDenied(message),
+            404 => AppError::NotFound(message),
+            408 | 429 | 500..=599 => AppError::Unavailable { message, details: None },
+            _ => AppError::Network { message, details: None },
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::InvalidInput(_) => "invalid_input",
+            AppError::NotFound(_) => "not_found",
+            AppError::PermissionDenied(_) => "permission_denied",
+            AppError::PasswordRequired(_) => "password_required",
+            AppError::Cancelled(_) => "cancelled",
+            AppError::Unavailable { .. } => "unavailable",
+            AppError::Timeout(_) => "timeout",
+            AppError::Network { .. } => "network",
+            AppError::Io { .. } => "io",
+            AppError::Window(_) => "window",
+            AppError::Database(_) => "database",
+            AppError::Internal(_) => "internal",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            AppError::InvalidInput(message)
+            | AppError::NotFound(message)
+            | AppError::PermissionDenied(message)
+            | AppError::PasswordRequired(message)
+            | AppError::Cancelled(message)
+            | AppError::Timeout(message)
+            | AppError::Window(message)
+            | AppError::Database(message)
+            | AppError::Internal(message) => message,
+            AppError::Unavailable { message, .. }
+            | AppError::Network { message, .. }
+            | AppError::Io { message, .. } => message,
+        }
+    }
+
+    pub fn details(&self) -> Option<&str> {
+        match self {
+            AppError::Unavailable { details, .. }
+            | AppError::Network { details, .. }
+            | AppError::Io { details, .. } => details.as_deref(),
+            _ => None,
+        }
+    }
+
+    pub fn retryable(&self) -> bool {
+        matches!(
+            self,
+            AppError::Unavailable { .. } | AppError::Timeout(_) | AppError::Network { .. }
+        )
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.details() {
+            Some(details) => write!(f, "{}: {}", self.message(), details),
+            None => f.write_str(self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 4)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &crate::i18n::translate(self.message()))?;
+        state.serialize_field("details", &self.details())?;
+        state.serialize_field("retryable", &self.retryable())?;
+        state.end()
+    }
+}
+
+// 仍以字符串描述的错误归为内部错误
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Internal(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        AppError::Internal(message.to_string())
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        use std::io::ErrorKind;
+        match error.kind() {
+            ErrorKind::NotFound => AppError::NotFound(error.to_string()),
+            ErrorKind::PermissionDenied => AppError::PermissionDenied(error.to_string()),
+            ErrorKind::TimedOut => AppError::Timeout(error.to_string()),
+            kind => AppError::Io {
+                message: error.to_string(),
+                details: Some(format!("{:?}", kind)),
+            },
+        }
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        match error {
+            tauri::Error::JoinError(e) => e.into(),
+            tauri::Error::Io(e) => e.into(),
+            other => AppError::Window(other.to_string()),
+        }
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(error: reqwest::Error) -> Self {
+        match error.status() {
+            Some(status) => AppError::status("请求失败", status),
+            None => AppError::network("请求失败", error),
+        }
+    }
+}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(error: rusqlite::Error) -> Self {
+        AppError::Database(error.to_string())
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::InvalidInput(format!("数据格式错误: {}", error))
+    }
+}
+
+impl<T> From<std::sync::PoisonError<T>> for AppError {
+    fn from(error: std::sync::PoisonError<T>) -> Self {
+        AppError::Internal(error.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        AppError::Internal(error.to_string())
+    }
+}
+
+// 音频设备被占用、拔出或不支持时，稍后可能恢复
+macro_rules! device_unavailable {
+    ($($error:ty),+) => {
+        $(
+            impl From<$error> for AppError {
+                fn from(error: $error) -> Self {
+                    AppError::unavailable("音频设备不可用", error)
+                }
+            }
+        )+
+    };
+}
+
+device_unavailable!(
+    cpal::DevicesError,
+    cpal::SupportedStreamConfigsError,
+    cpal::DefaultStreamConfigError,
+    cpal::BuildStreamError,
+    cpal::PlayStreamError,
+    rodio::StreamError,
+    rodio::PlayError
+);
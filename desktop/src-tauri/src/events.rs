@@ -0,0 +1,310 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State, Window};
+use tracing::warn;
+
+use crate::audio::capture::{AudioChunk, RecordingInfo, RecordingSummary};
+use crate::audio::indicator::{MicStatus, INDICATOR_LABEL};
+use crate::audio::meter::AudioLevel;
+use crate::audio::playback::PlaybackFinished;
+use crate::audio::tts_stream::TtsStreamError;
+use crate::audio::wakeword::WakeWordDetected;
+use crate::backend::{BackendLogLine, BackendStatus};
+use crate::backup::BackupInfo;
+use crate::calendar::CalendarSyncStatus;
+use crate::captions::{CaptionCue, CAPTIONS_LABEL};
+use crate::data::ExportProgress;
+use crate::deep_link::DeepLink;
+use crate::dnd::DndStatus;
+use crate::error::AppError;
+use crate::file_search::FileIndexStatus;
+use crate::fullscreen::FullscreenStatus;
+use crate::knowledge::embeddings::EmbeddingProgress;
+use crate::knowledge::ImportProgress;
+use crate::llm::stream::ChatFinished;
+use crate::llm::ChatToken;
+use crate::models::ModelDownloadProgress;
+use crate::mqtt::{MqttMessage, MqttStatus};
+use crate::offline::ConnectivityStatus;
+use crate::persona::PersonaChanged;
+use crate::pet::bubble::{BubbleMessage, BUBBLE_LABEL};
+use crate::pet::idle::IdleBehavior;
+use crate::pet::menu::{PetMenu, PetMenuAction};
+use crate::pet::state::PetStateChanged;
+use crate::pet::PET_LABEL;
+use crate::pipeline::{StageEvent, TurnResult};
+use crate::power::PowerState;
+use crate::privacy::PrivacyStatus;
+use crate::quick_ask::QUICK_ASK_LABEL;
+use crate::resources::ThrottleStatus;
+use crate::scheduler::ReminderFired;
+use crate::settings::{SettingsChanged, SettingsFileError};
+use crate::single_instance::InstanceMessage;
+use crate::stt::file::TranscriptionProgress;
+use crate::stt::PartialTranscript;
+use crate::timers::TimerStatus;
+use crate::updater::UpdateProgress;
+use crate::window_state::MAIN_LABEL;
+use crate::ws_bridge::WsStatus;
+
+// 推送给前端的全部事件，序列化结果即事件的 payload，事件名见 name()
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum AppEvent {
+    VoiceActivation,
+    SecondInstance(InstanceMessage),
+    DeepLink(DeepLink),
+    SettingsChanged(Box<SettingsChanged>),
+    SettingsFileError(SettingsFileError),
+    BackendLog(BackendLogLine),
+    BackendStatusChanged(BackendStatus),
+    ConnectivityChanged(ConnectivityStatus),
+    WsStatusChanged(WsStatus),
+    WsMessage(serde_json::Value),
+    MqttStatusChanged(MqttStatus),
+    MqttMessage(MqttMessage),
+    RecordingStarted(RecordingInfo),
+    RecordingStopped(RecordingSummary),
+    MicActivityChanged(MicStatus),
+    ResourceThrottleChanged(ThrottleStatus),
+    PowerStateChanged(PowerState),
+    AudioChunk(AudioChunk),
+    AudioLevel(AudioLevel),
+    SpeechStarted,
+    SpeechEnded { duration_ms: u64 },
+    PlaybackFinished(PlaybackFinished),
+    TtsStreamError(TtsStreamError),
+    WakeWordDetected(WakeWordDetected),
+    SttPartial(PartialTranscript),
+    TranscriptionProgress(TranscriptionProgress),
+    ModelDownloadProgress(ModelDownloadProgress),
+    PipelineStage(StageEvent),
+    PipelineTurnFinished(TurnResult),
+    ChatToken(ChatToken),
+    ChatFinished(ChatFinished),
+    PersonaChanged(PersonaChanged),
+    PetStateChanged(PetStateChanged),
+    PetIdleBehavior(IdleBehavior),
+    PetVisibilityChanged(bool),
+    PetClickThroughChanged(bool),
+    PetBubble(BubbleMessage),
+    PetMenuOpened(PetMenu),
+    PetMenuSelected(PetMenuAction),
+    FullscreenChanged(FullscreenStatus),
+    PrivacyModeChanged(PrivacyStatus),
+    ReminderFired(ReminderFired),
+    TimerTick(Vec<TimerStatus>),
+    TimerFinished(TimerStatus),
+    CalendarSynced(Vec<CalendarSyncStatus>),
+    ExportProgress(ExportProgress),
+    ImportProgress(ExportProgress),
+    KnowledgeImportProgress(ImportProgress),
+    FileIndexUpdated(FileIndexStatus),
+    EmbeddingProgress(EmbeddingProgress),
+    #[cfg_attr(not(feature = "updater"), allow(dead_code))]
+    UpdateProgress(UpdateProgress),
+    BackupCreated(BackupInfo),
+    Caption(CaptionCue),
+    CaptionCleared { playback: u64 },
+    DndChanged(DndStatus),
+}
+
+impl AppEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AppEvent::VoiceActivation => "voice-activation",
+            AppEvent::SecondInstance(_) => "second-instance",
+            AppEvent::DeepLink(_) => "deep-link",
+            AppEvent::SettingsChanged(_) => "settings-changed",
+            AppEvent::SettingsFileError(_) => "settings-file-error",
+            AppEvent::BackendLog(_) => "backend-log",
+            AppEvent::BackendStatusChanged(_) => "backend-status-changed",
+            AppEvent::ConnectivityChanged(_) => "connectivity-changed",
+            AppEvent::WsStatusChanged(_) => "ws-status-changed",
+            AppEvent::WsMessage(_) => "ws-message",
+            AppEvent::MqttStatusChanged(_) => "mqtt-status-changed",
+            AppEvent::MqttMessage(_) => "mqtt-message",
+            AppEvent::RecordingStarted(_) => "recording-started",
+            AppEvent::RecordingStopped(_) => "recording-stopped",
+            AppEvent::MicActivityChanged(_) => "mic-activity-changed",
+            AppEvent::ResourceThrottleChanged(_) => "resource-throttle-changed",
+            AppEvent::PowerStateChanged(_) => "power-state-changed",
+            AppEvent::AudioChunk(_) => "audio-chunk",
+            AppEvent::AudioLevel(_) => "audio-level",
+            AppEvent::SpeechStarted => "speech-started",
+            AppEvent::SpeechEnded { .. } => "speech-ended",
+            AppEvent::PlaybackFinished(_) => "playback-finished",
+            AppEvent::TtsStreamError(_) => "tts-stream-error",
+            AppEvent::WakeWordDetected(_) => "wake-word-detected",
+            AppEvent::SttPartial(_) => "stt-partial",
+            AppEvent::TranscriptionProgress(_) => "transcription-progress",
+            AppEvent::ModelDownloadProgress(_) => "model-download-progress",
+            AppEvent::PipelineStage(_) => "pipeline-stage",
+            AppEvent::PipelineTurnFinished(_) => "pipeline-turn-finished",
+            AppEvent::ChatToken(_) => "chat-token",
+            AppEvent::ChatFinished(_) => "chat-finished",
+            AppEvent::PersonaChanged(_) => "persona-changed",
+            AppEvent::PetStateChanged(_) => "pet-state-changed",
+            AppEvent::PetIdleBehavior(_) => "pet-idle-behavior",
+            AppEvent::PetVisibilityChanged(_) => "pet-visibility-changed",
+            AppEvent::PetClickThroughChanged(_) => "pet-click-through-changed",
+            AppEvent::PetBubble(_) => "pet-bubble",
+            AppEvent::PetMenuOpened(_) => "pet-menu-opened",
+            AppEvent::PetMenuSelected(_) => "pet-menu-selected",
+            AppEvent::FullscreenChanged(_) => "fullscreen-changed",
+            AppEvent::PrivacyModeChanged(_) => "privacy-mode-changed",
+            AppEvent::ReminderFired(_) => "reminder-fired",
+            AppEvent::TimerTick(_) => "timer-tick",
+            AppEvent::TimerFinished(_) => "timer-finished",
+            AppEvent::CalendarSynced(_) => "calendar-synced",
+            AppEvent::ExportProgress(_) => "export-progress",
+            AppEvent::ImportProgress(_) => "import-progress",
+            AppEvent::KnowledgeImportProgress(_) => "knowledge-import-progress",
+            AppEvent::FileIndexUpdated(_) => "file-index-updated",
+            AppEvent::EmbeddingProgress(_) => "embedding-progress",
+            AppEvent::UpdateProgress(_) => "update-progress",
+            AppEvent::BackupCreated(_) => "backup-created",
+            AppEvent::Caption(_) => "caption",
+            AppEvent::CaptionCleared { .. } => "caption-cleared",
+            AppEvent::DndChanged(_) => "dnd-changed",
+        }
+    }
+}
+
+// 桌宠窗口只接收桌宠、语音交互和少量状态事件，不接收日志、消息内容和数据导入导出进度
+const PET_EVENTS: &[&str] = &[
+    "voice-activation",
+    "settings-changed",
+    "backend-status-changed",
+    "connectivity-changed",
+    "recording-started",
+    "recording-stopped",
+    "mic-activity-changed",
+    "resource-throttle-changed",
+    "audio-chunk",
+    "audio-level",
+    "speech-started",
+    "speech-ended",
+    "playback-finished",
+    "tts-stream-error",
+    "wake-word-detected",
+    "stt-partial",
+    "pipeline-stage",
+    "pipeline-turn-finished",
+    "pet-state-changed",
+    "pet-idle-behavior",
+    "pet-visibility-changed",
+    "pet-click-through-changed",
+    "pet-menu-opened",
+    "pet-menu-selected",
+    "persona-changed",
+    "fullscreen-changed",
+    "privacy-mode-changed",
+    "dnd-changed",
+    "reminder-fired",
+    "timer-tick",
+    "timer-finished",
+];
+
+// 快速提问窗口只接收录音和识别相关事件
+const QUICK_ASK_EVENTS: &[&str] = &[
+    "settings-changed",
+    "recording-started",
+    "recording-stopped",
+    "mic-activity-changed",
+    "audio-chunk",
+    "audio-level",
+    "speech-started",
+    "speech-ended",
+    "stt-partial",
+];
+
+// 字幕窗口只接收字幕和设置变化（字号、显示器）
+const CAPTIONS_EVENTS: &[&str] = &["settings-changed", "caption", "caption-cleared"];
+
+// 桌宠气泡窗口只接收气泡内容和设置变化（主题、语言）
+const BUBBLE_EVENTS: &[&str] = &["settings-changed", "pet-bubble"];
+
+// 麦克风提示窗口只接收麦克风状态变化
+const INDICATOR_EVENTS: &[&str] = &["mic-activity-changed"];
+
+// 窗口可以接收的事件，None 表示全部；未列出的窗口不接收任何事件
+fn allowed_events(label: &str) -> Option<&'static [&'static str]> {
+    match label {
+        MAIN_LABEL => None,
+        PET_LABEL => Some(PET_EVENTS),
+        QUICK_ASK_LABEL => Some(QUICK_ASK_EVENTS),
+        CAPTIONS_LABEL => Some(CAPTIONS_EVENTS),
+        BUBBLE_LABEL => Some(BUBBLE_EVENTS),
+        INDICATOR_LABEL => Some(INDICATOR_EVENTS),
+        _ => Some(&[]),
+    }
+}
+
+// 各窗口自行设置的订阅过滤，只能在窗口允许接收的事件范围内缩小
+pub struct EventBus {
+    filters: Mutex<HashMap<String, HashSet<String>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self {
+            filters: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn accepts(&self, label: &str, event: &str) -> bool {
+        if allowed_events(label).is_some_and(|allowed| !allowed.contains(&event)) {
+            return false;
+        }
+        match self.filters.lock() {
+            Ok(filters) => filters.get(label).is_none_or(|filter| filter.contains(event)),
+            Err(_) => true,
+        }
+    }
+}
+
+// 所有子系统都通过这里推送事件，按各窗口的订阅过滤分发
+pub fn publish(app: &AppHandle, event: AppEvent) {
+    let name = event.name();
+    let bus = app.state::<EventBus>();
+    if let Err(e) = app.emit_filter(name, &event, |window| bus.accepts(window.label(), name)) {
+        warn!("Failed to emit {}: {}", name, e);
+    }
+}
+
+// events 为 None 时恢复为接收该窗口允许的全部事件
+#[tauri::command]
+pub fn set_event_filter(
+    window: Window,
+    bus: State<'_, EventBus>,
+    events: Option<Vec<String>>,
+) -> Result<Vec<String>, AppError> {
+    let label = window.label().to_string();
+    let mut filters = bus.filters.lock()?;
+    let Some(events) = events else {
+        filters.remove(&label);
+        return Ok(allowed_events(&label)
+            .map(|allowed| allowed.iter().map(|event| event.to_string()).collect())
+            .unwrap_or_default());
+    };
+
+    let allowed = allowed_events(&label);
+    let rejected: Vec<&str> = events
+        .iter()
+        .map(String::as_str)
+        .filter(|event| allowed.is_some_and(|allowed| !allowed.contains(event)))
+        .collect();
+    if !rejected.is_empty() {
+        return Err(AppError::PermissionDenied(format!(
+            "窗口 {} 无权订阅 {}",
+            label,
+            rejected.join(", ")
+        )));
+    }
+    let mut subscribed: Vec<String> = events.into_iter().collect::<HashSet<_>>().into_iter().collect();
+    subscribed.sort();
+    filters.insert(label, subscribed.iter().cloned().collect());
+    Ok(subscribed)
+}
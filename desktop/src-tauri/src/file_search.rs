@@ -0,0 +1,554 @@
+use rusqlite::types::Value;
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::knowledge::{self, extract};
+use crate::power::{self, PowerConsumer};
+use crate::storage::{self, now_millis, DATABASE_FILE};
+use crate::{resources, settings, AppState};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS files (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL UNIQUE,
+    root TEXT NOT NULL,
+    name TEXT NOT NULL,
+    extension TEXT,
+    size INTEGER NOT NULL,
+    modified INTEGER,
+    content_indexed INTEGER NOT NULL DEFAULT 0,
+    seen_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_files_root ON files(root, seen_at);
+CREATE INDEX IF NOT EXISTS idx_files_modified ON files(modified);
+CREATE VIRTUAL TABLE IF NOT EXISTS files_fts USING fts5(name, content, tokenize = 'trigram');
+";
+// 每处理这么多文件提交一次，避免长时间占用数据库连接
+const BATCH_SIZE: usize = 500;
+// 单个目录下最多索引的文件数，防止误选整个磁盘
+const MAX_FILES_PER_ROOT: usize = 200_000;
+const MIN_RESCAN_INTERVAL_MINS: u32 = 10;
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 100;
+// 除隐藏目录外，这些依赖和缓存目录也不索引
+const SKIP_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "__pycache__",
+    "$RECYCLE.BIN",
+    "System Volume Information",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FileSearchSettings {
+    // 用户授权索引的目录，为空时不扫描任何文件
+    pub folders: Vec<String>,
+    // 同时索引 PDF、Word、文本等文档的内容，默认只索引文件名
+    pub index_content: bool,
+    // 超过该大小的文件只索引文件名
+    pub max_content_mb: u32,
+    pub rescan_interval_mins: u32,
+}
+
+impl Default for FileSearchSettings {
+    fn default() -> Self {
+        Self {
+            folders: Vec::new(),
+            index_content: false,
+            max_content_mb: 10,
+            rescan_interval_mins: 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct FileSearchFilters {
+    // 不带点的扩展名，如 ["pdf", "docx"]
+    pub extensions: Vec<String>,
+    // 修改时间范围，毫秒时间戳
+    pub modified_after: Option<i64>,
+    pub modified_before: Option<i64>,
+    // 只搜索该目录下的文件
+    pub folder: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileHit {
+    pub path: String,
+    pub name: String,
+    pub extension: Option<String>,
+    pub size: i64,
+    pub modified: Option<i64>,
+    // 内容命中时的片段，匹配词用 <mark> 包裹
+    pub snippet: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileIndexStatus {
+    pub files: u64,
+    pub indexing: bool,
+    pub last_indexed_at: Option<i64>,
+    pub last_duration_ms: Option<u64>,
+    pub error: Option<String>,
+}
+
+struct Scanned {
+    path: String,
+    name: String,
+    extension: Option<String>,
+    size: i64,
+    modified: Option<i64>,
+    content: Option<String>,
+}
+
+// 已索引文件的 (大小, 修改时间, 是否索引了内容)
+type Known = HashMap<String, (i64, Option<i64>, bool)>;
+
+// 用户授权目录的文件名和内容索引，与对话历史共用同一个数据库文件
+pub struct FileIndex {
+    conn: Mutex<Connection>,
+    // 目录设置变化或手动重建时唤醒后台任务
+    wake: Notify,
+    status: Mutex<FileIndexStatus>,
+}
+
+fn modified_millis(metadata: &std::fs::Metadata) -> Option<i64> {
+    let modified = metadata.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+fn skip_dir(name: &str) -> bool {
+    name.starts_with('.') || SKIP_DIRS.contains(&name)
+}
+
+// 去掉重复和嵌套的目录：同一个文件只能属于一个索引目录，否则每次扫描都会在两个目录间来回改写
+fn distinct_roots(folders: &[String]) -> Vec<String> {
+    let mut roots: Vec<String> = Vec::new();
+    for folder in folders {
+        if roots.iter().any(|root| Path::new(folder).starts_with(root)) {
+            continue;
+        }
+        roots.retain(|root| !Path::new(root).starts_with(folder));
+        roots.push(folder.clone());
+    }
+    roots
+}
+
+// 目录过滤条件：目录本身或其下的路径，避免 /docs 匹配到 /docs-old
+fn folder_prefix(folder: &str) -> String {
+    let mut prefix = folder.to_string();
+    if !prefix.ends_with(std::path::is_separator) {
+        prefix.push(std::path::MAIN_SEPARATOR);
+    }
+    prefix
+}
+
+impl FileIndex {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            wake: Notify::new(),
+            status: Mutex::new(FileIndexStatus::default()),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    fn count(&self) -> Result<u64, AppError> {
+        let count: i64 = self
+            .conn()?
+            .query_row("SELECT COUNT(*) FROM files", [], |row| row.get(0))?;
+        Ok(count as u64)
+    }
+
+    fn known(&self, root: &str) -> Result<Known, AppError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT path, size, modified, content_indexed FROM files WHERE root = ?1")?;
+        let known = stmt
+            .query_map(params![root], |row| {
+                Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))
+            })
+            .and_then(|rows| rows.collect::<Result<Known, _>>());
+        known.map_err(AppError::from)
+    }
+
+    fn write_batch(&self, root: &str, seen: &[String], changed: &[Scanned], seen_at: i64) -> Result<(), AppError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        {
+            let mut touch = tx.prepare("UPDATE files SET seen_at = ?1 WHERE path = ?2")?;
+            for path in seen {
+                touch.execute(params![seen_at, path])?;
+            }
+            let mut upsert = tx.prepare(
+                "INSERT INTO files (path, root, name, extension, size, modified, content_indexed, seen_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                     ON CONFLICT(path) DO UPDATE SET root = excluded.root, name = excluded.name,
+                         extension = excluded.extension, size = excluded.size, modified = excluded.modified,
+                         content_indexed = excluded.content_indexed, seen_at = excluded.seen_at
+                     RETURNING id",
+            )?;
+            let mut remove_fts = tx.prepare("DELETE FROM files_fts WHERE rowid = ?1")?;
+            let mut insert_fts = tx.prepare("INSERT INTO files_fts (rowid, name, content) VALUES (?1, ?2, ?3)")?;
+            for file in changed {
+                let id: i64 = upsert.query_row(
+                    params![
+                        file.path,
+                        root,
+                        file.name,
+                        file.extension,
+                        file.size,
+                        file.modified,
+                        file.content.is_some(),
+                        seen_at
+                    ],
+                    |row| row.get(0),
+                )?;
+                remove_fts.execute(params![id])?;
+                insert_fts.execute(params![id, file.name, file.content.as_deref().unwrap_or("")])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    // 删除本次扫描没有再见到的文件；seen_before 为 i64::MAX 时删除整个目录
+    fn prune(&self, root: &str, seen_before: i64) -> Result<usize, AppError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "DELETE FROM files_fts WHERE rowid IN (SELECT id FROM files WHERE root = ?1 AND seen_at < ?2)",
+            params![root, seen_before],
+        )?;
+        let removed = tx.execute(
+            "DELETE FROM files WHERE root = ?1 AND seen_at < ?2",
+            params![root, seen_before],
+        )?;
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    fn roots(&self) -> Result<Vec<String>, AppError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare("SELECT DISTINCT root FROM files")?;
+        let roots = stmt
+            .query_map([], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<String>, _>>());
+        roots.map_err(AppError::from)
+    }
+
+    fn scan_root(&self, settings: &FileSearchSettings, root: &str, started: i64) -> Result<usize, AppError> {
+        let mut known = self.known(root)?;
+        let max_content = settings.max_content_mb as u64 * 1024 * 1024;
+        let mut seen = Vec::new();
+        let mut changed = Vec::new();
+        let mut total = 0;
+        let mut stack = vec![PathBuf::from(root)];
+
+        while let Some(dir) = stack.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                // 不跟随符号链接，避免循环和扫描到授权目录之外
+                let Ok(file_type) = entry.file_type() else {
+                    continue;
+                };
+                if file_type.is_dir() {
+                    if !skip_dir(&name) {
+                        stack.push(entry.path());
+                    }
+                    continue;
+                }
+                if !file_type.is_file() || name.starts_with('.') {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                let path = entry.path();
+                let path_text = path.to_string_lossy().to_string();
+                let size = metadata.len() as i64;
+                let modified = modified_millis(&metadata);
+                let wants_content = settings.index_content
+                    && metadata.len() <= max_content
+                    && extract::DocumentKind::from_path(&path).is_some();
+
+                total += 1;
+                match known.remove(&path_text) {
+                    Some(previous) if previous == (size, modified, wants_content) => seen.push(path_text),
+                    _ => {
+                        let content = wants_content.then(|| extract::extract_text(&path).unwrap_or_default());
+                        changed.push(Scanned {
+                            extension: path.extension().map(|ext| ext.to_string_lossy().to_ascii_lowercase()),
+                            name,
+                            path: path_text,
+                            size,
+                            modified,
+                            content,
+                        });
+                    }
+                }
+                if seen.len() + changed.len() >= BATCH_SIZE {
+                    self.write_batch(root, &seen, &changed, started)?;
+                    seen.clear();
+                    changed.clear();
+                }
+                if total >= MAX_FILES_PER_ROOT {
+                    warn!("Stopped indexing {} after {} files", root, total);
+                    stack.clear();
+                    break;
+                }
+            }
+        }
+        self.write_batch(root, &seen, &changed, started)?;
+        // 达到上限时未扫描到的文件保留原样
+        if total < MAX_FILES_PER_ROOT {
+            self.prune(root, started)?;
+        }
+        Ok(total)
+    }
+
+    // 在阻塞线程中调用。对话历史加密时只索引文件名，全文索引无法加密，已索引的内容在本次扫描中清除
+    fn rebuild(&self, settings: &FileSearchSettings, encrypted: bool) -> Result<(), AppError> {
+        let started = now_millis();
+        let mut settings = settings.clone();
+        settings.folders = distinct_roots(&settings.folders);
+        if encrypted {
+            self.conn()?.execute_batch("PRAGMA secure_delete = ON;")?;
+            if settings.index_content {
+                info!("Database encryption is enabled, indexing file names only");
+                settings.index_content = false;
+            }
+        }
+        let settings = &settings;
+        for root in self.roots()? {
+            if !settings.folders.contains(&root) {
+                let removed = self.prune(&root, i64::MAX)?;
+                info!("Removed {} files of {} from the file index", removed, root);
+            }
+        }
+        for root in &settings.folders {
+            if !Path::new(root).is_dir() {
+                warn!("File search folder {} is not available", root);
+                continue;
+            }
+            let files = self.scan_root(settings, root, started)?;
+            info!("Indexed {} files in {}", files, root);
+        }
+        Ok(())
+    }
+
+    fn search(&self, query: &str, filters: &FileSearchFilters) -> Result<Vec<FileHit>, AppError> {
+        let limit = filters.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let mut clauses = Vec::new();
+        let mut values = Vec::new();
+
+        let expression = knowledge::match_query(query);
+        let (from, snippet, order) = match &expression {
+            Some(expression) => {
+                clauses.push("files_fts MATCH ?".to_string());
+                values.push(Value::Text(expression.clone()));
+                (
+                    "files_fts JOIN files f ON f.id = files_fts.rowid",
+                    "snippet(files_fts, 1, char(1), char(2), '…', 16)",
+                    // 文件名命中比内容命中更相关
+                    "bm25(files_fts, 10.0, 1.0)",
+                )
+            }
+            None => {
+                if !query.is_empty() {
+                    clauses.push("f.name LIKE ? ESCAPE '\\'".to_string());
+                    values.push(Value::Text(storage::like_pattern(query)));
+                }
+                ("files f", "NULL", "f.modified DESC")
+            }
+        };
+
+        let extensions: Vec<String> = filters
+            .extensions
+            .iter()
+            .map(|ext| ext.trim().trim_start_matches('.').to_ascii_lowercase())
+            .filter(|ext| !ext.is_empty())
+            .collect();
+        if !extensions.is_empty() {
+            clauses.push(format!("f.extension IN ({})", vec!["?"; extensions.len()].join(", ")));
+            values.extend(extensions.into_iter().map(Value::Text));
+        }
+        if let Some(after) = filters.modified_after {
+            clauses.push("f.modified >= ?".to_string());
+            values.push(Value::Integer(after));
+        }
+        if let Some(before) = filters.modified_before {
+            clauses.push("f.modified < ?".to_string());
+            values.push(Value::Integer(before));
+        }
+        if let Some(folder) = filters.folder.as_deref().filter(|folder| !folder.is_empty()) {
+            clauses.push("(f.path = ? OR instr(f.path, ?) = 1)".to_string());
+            values.push(Value::Text(folder.to_string()));
+            values.push(Value::Text(folder_prefix(folder)));
+        }
+        values.push(Value::Integer(limit as i64));
+
+        let sql = format!(
+            "SELECT f.path, f.name, f.extension, f.size, f.modified, {} FROM {} {} ORDER BY {} LIMIT ?",
+            snippet,
+            from,
+            if clauses.is_empty() {
+                String::new()
+            } else {
+                format!("WHERE {}", clauses.join(" AND "))
+            },
+            order
+        );
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&sql)?;
+        let hits = stmt
+            .query_map(params_from_iter(values), |row| {
+                let snippet: Option<String> = row.get(5)?;
+                Ok(FileHit {
+                    path: row.get(0)?,
+                    name: row.get(1)?,
+                    extension: row.get(2)?,
+                    size: row.get(3)?,
+                    modified: row.get(4)?,
+                    // 只命中文件名时 snippet 是内容开头，没有意义
+                    snippet: snippet
+                        .filter(|snippet| snippet.contains(knowledge::MARK_START))
+                        .map(|snippet| knowledge::highlight(&snippet)),
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+        hits.map_err(AppError::from)
+    }
+}
+
+fn file_search_settings(app: &AppHandle) -> Result<FileSearchSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.file_search.clone())
+}
+
+async fn index_once(app: &AppHandle, settings: FileSearchSettings) {
+    let index = app.state::<FileIndex>();
+    if let Ok(mut status) = index.status.lock() {
+        status.indexing = true;
+    }
+    let started = Instant::now();
+    let handle = app.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let encrypted = handle.state::<AppState>().storage.encryption()?.enabled;
+        handle.state::<FileIndex>().rebuild(&settings, encrypted)
+    })
+    .await
+        .map_err(AppError::from)
+        .and_then(|result| result);
+    let status = {
+        let Ok(mut status) = index.status.lock() else {
+            return;
+        };
+        status.indexing = false;
+        status.files = index.count().unwrap_or(status.files);
+        status.last_indexed_at = Some(now_millis());
+        status.last_duration_ms = Some(started.elapsed().as_millis() as u64);
+        status.error = result.err().map(|e| {
+            warn!("Failed to index files: {}", e);
+            e.to_string()
+        });
+        status.clone()
+    };
+    events::publish(app, AppEvent::FileIndexUpdated(status));
+}
+
+// 在 setup 中调用：启动时扫描一次授权目录，之后定期增量更新
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            // 节流期间或电源策略不允许时推迟扫描
+            resources::wait_unthrottled(&app).await;
+            power::wait_allowed(&app, PowerConsumer::Indexing).await;
+            let settings = file_search_settings(&app).unwrap_or_default();
+            index_once(&app, settings.clone()).await;
+            let interval = settings.rescan_interval_mins.max(MIN_RESCAN_INTERVAL_MINS) as u64 * 60;
+            let index = app.state::<FileIndex>();
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = index.wake.notified() => {}
+            }
+        }
+    });
+}
+
+// query 匹配文件名（开启内容索引时也匹配内容），可以为空只按过滤条件查找，结果按相关度或修改时间排序
+#[tauri::command]
+pub async fn search_files(
+    app: AppHandle,
+    query: Option<String>,
+    filters: Option<FileSearchFilters>,
+) -> Result<Vec<FileHit>, AppError> {
+    let query = query.unwrap_or_default().trim().to_string();
+    let filters = filters.unwrap_or_default();
+    tauri::async_runtime::spawn_blocking(move || app.state::<FileIndex>().search(&query, &filters)).await?
+}
+
+#[tauri::command]
+pub fn set_file_search_folders(
+    app: AppHandle,
+    index: State<'_, FileIndex>,
+    folders: Vec<String>,
+    index_content: Option<bool>,
+) -> Result<FileSearchSettings, AppError> {
+    let mut normalized = Vec::new();
+    for folder in folders {
+        let folder = folder.trim().to_string();
+        let path = Path::new(&folder);
+        if !path.is_absolute() || !path.is_dir() {
+            return Err(AppError::invalid(format!("目录不存在: {}", folder)));
+        }
+        normalized.push(folder);
+    }
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.file_search.folders = distinct_roots(&normalized);
+    if let Some(index_content) = index_content {
+        settings.file_search.index_content = index_content;
+    }
+    settings::save_and_notify(&app, &settings, &["file_search"])?;
+    index.wake.notify_one();
+    Ok(settings.file_search.clone())
+}
+
+// 对话历史启用加密后调用，尽快清除已索引的文件内容
+pub fn refresh(app: &AppHandle) {
+    app.state::<FileIndex>().wake.notify_one();
+}
+
+#[tauri::command]
+pub fn reindex_files(index: State<'_, FileIndex>) -> Result<(), AppError> {
+    index.wake.notify_one();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_file_index_status(index: State<'_, FileIndex>) -> Result<FileIndexStatus, AppError> {
+    let mut status = index.status.lock()?.clone();
+    if !status.indexing {
+        status.files = index.count()?;
+    }
+    Ok(status)
+}
@@ -0,0 +1,168 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::pet::{self, PET_LABEL};
+use crate::{settings, AppState};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct FullscreenSettings {
+    // 前台有全屏应用（游戏、演示）时隐藏桌宠并暂停通知
+    pub auto_hide: bool,
+}
+
+impl Default for FullscreenSettings {
+    fn default() -> Self {
+        Self { auto_hide: true }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FullscreenStatus {
+    pub active: bool,
+    // 桌宠是否由全屏检测隐藏，退出全屏时只恢复这种情况
+    pub pet_hidden: bool,
+}
+
+#[derive(Default)]
+pub struct FullscreenWatcher {
+    active: AtomicBool,
+    pet_hidden: AtomicBool,
+}
+
+impl FullscreenWatcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> FullscreenStatus {
+        FullscreenStatus {
+            active: self.active.load(Ordering::SeqCst),
+            pet_hidden: self.pet_hidden.load(Ordering::SeqCst),
+        }
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<FullscreenWatcher>().active.load(Ordering::SeqCst)
+}
+
+// 系统报告的免打扰状态：独占全屏的 D3D 程序、演示模式或其他全屏应用
+#[cfg(windows)]
+fn system_busy() -> bool {
+    use windows_sys::Win32::UI::Shell::{
+        SHQueryUserNotificationState, QUNS_BUSY, QUNS_PRESENTATION_MODE, QUNS_RUNNING_D3D_FULL_SCREEN,
+    };
+
+    let mut state = 0;
+    // SAFETY: state 是有效的输出指针
+    let result = unsafe { SHQueryUserNotificationState(&mut state) };
+    result == 0 && matches!(state, QUNS_BUSY | QUNS_RUNNING_D3D_FULL_SCREEN | QUNS_PRESENTATION_MODE)
+}
+
+#[cfg(not(windows))]
+fn system_busy() -> bool {
+    false
+}
+
+// 前台窗口覆盖了所在显示器的全部区域
+fn foreground_covers_monitor() -> bool {
+    let Ok(windows) = xcap::Window::all() else {
+        return false;
+    };
+    let pid = std::process::id();
+    let Some(window) = windows
+        .into_iter()
+        .find(|w| w.is_focused().unwrap_or(false) && w.pid().is_ok_and(|p| p != pid))
+    else {
+        return false;
+    };
+    let Ok(monitor) = window.current_monitor() else {
+        return false;
+    };
+
+    let covers = || -> xcap::XCapResult<bool> {
+        Ok(window.x()? <= monitor.x()?
+            && window.y()? <= monitor.y()?
+            && window.width()? >= monitor.width()?
+            && window.height()? >= monitor.height()?)
+    };
+    covers().unwrap_or(false)
+}
+
+fn detect() -> bool {
+    system_busy() || foreground_covers_monitor()
+}
+
+fn auto_hide_enabled(app: &AppHandle) -> bool {
+    match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.fullscreen.auto_hide,
+        Err(_) => false,
+    }
+}
+
+fn set_active(app: &AppHandle, active: bool) {
+    let watcher = app.state::<FullscreenWatcher>();
+    if watcher.active.swap(active, Ordering::SeqCst) == active {
+        return;
+    }
+
+    if active {
+        info!("Fullscreen app detected, hiding desktop pet");
+        let visible = app
+            .get_window(PET_LABEL)
+            .and_then(|window| window.is_visible().ok())
+            .unwrap_or(false);
+        if visible {
+            match pet::hide_desktop_pet(app.clone()) {
+                Ok(()) => watcher.pet_hidden.store(true, Ordering::SeqCst),
+                Err(e) => warn!("Failed to hide desktop pet: {}", e),
+            }
+        }
+    } else if watcher.pet_hidden.swap(false, Ordering::SeqCst) {
+        info!("Fullscreen app closed, restoring desktop pet");
+        let handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = pet::show_desktop_pet(handle).await {
+                warn!("Failed to show desktop pet: {}", e);
+            }
+        });
+    }
+
+    events::publish(app, AppEvent::FullscreenChanged(watcher.status()));
+}
+
+// 在 setup 中调用，关闭自动隐藏时不做检测
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        let active = auto_hide_enabled(&app) && detect();
+        set_active(&app, active);
+    });
+}
+
+#[tauri::command]
+pub fn get_fullscreen_status(watcher: State<'_, FullscreenWatcher>) -> FullscreenStatus {
+    watcher.status()
+}
+
+#[tauri::command]
+pub fn set_fullscreen_auto_hide(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    {
+        let mut settings = state.settings.lock()?;
+        settings.fullscreen.auto_hide = enabled;
+        settings::save_and_notify(&app, &settings, &["fullscreen"])?;
+    }
+    if !enabled {
+        set_active(&app, false);
+    }
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tauri::{AppHandle, GlobalShortcutManager, Manager};
+use tracing::warn;
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{pet, quick_ask, settings, tray, AppState};
+
+// 可绑定全局快捷键的动作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HotkeyAction {
+    VoiceActivation,
+    TogglePet,
+    TogglePetClickThrough,
+    QuickAsk,
+}
+
+impl HotkeyAction {
+    pub const ALL: [HotkeyAction; 4] = [
+        HotkeyAction::VoiceActivation,
+        HotkeyAction::TogglePet,
+        HotkeyAction::TogglePetClickThrough,
+        HotkeyAction::QuickAsk,
+    ];
+
+    pub fn default_accelerator(&self) -> Option<&'static str> {
+        match self {
+            HotkeyAction::VoiceActivation => Some("CmdOrCtrl+Shift+Space"),
+            HotkeyAction::TogglePet => None,
+            // 开启点击穿透后无法再点击桌宠，默认提供快捷键作为恢复入口
+            HotkeyAction::TogglePetClickThrough => Some("CmdOrCtrl+Alt+P"),
+            HotkeyAction::QuickAsk => Some("CmdOrCtrl+Alt+Space"),
+        }
+    }
+}
+
+// 设置中保存用户自定义的绑定，None 表示用户禁用了该快捷键，未出现的动作使用默认值
+pub type HotkeyBindings = BTreeMap<HotkeyAction, Option<String>>;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HotkeyBinding {
+    pub action: HotkeyAction,
+    pub accelerator: Option<String>,
+}
+
+fn binding_for(bindings: &HotkeyBindings, action: HotkeyAction) -> Option<String> {
+    match bindings.get(&action) {
+        Some(accelerator) => accelerator.clone(),
+        None => action.default_accelerator().map(str::to_string),
+    }
+}
+
+// 已成功注册的快捷键，被禁用或注册失败时为 None
+pub fn registered(app: &AppHandle, action: HotkeyAction) -> Option<String> {
+    let bindings = app.state::<AppState>().settings.lock().ok()?.hotkeys.clone();
+    binding_for(&bindings, action).filter(|accelerator| {
+        app.global_shortcut_manager()
+            .is_registered(accelerator)
+            .unwrap_or(false)
+    })
+}
+
+fn trigger(app: &AppHandle, action: HotkeyAction) {
+    match action {
+        HotkeyAction::VoiceActivation => {
+            tray::show_main_window(app);
+            events::publish(app, AppEvent::VoiceActivation);
+        }
+        HotkeyAction::TogglePet => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = pet::toggle_desktop_pet(handle).await {
+                    warn!("Failed to toggle desktop pet: {}", e);
+                }
+            });
+        }
+        HotkeyAction::TogglePetClickThrough => {
+            if let Err(e) = pet::toggle_pet_click_through(app.clone()) {
+                warn!("Failed to toggle pet click-through: {}", e);
+            }
+        }
+        HotkeyAction::QuickAsk => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = quick_ask::show_quick_ask(handle).await {
+                    warn!("Failed to show quick ask: {}", e);
+                }
+            });
+        }
+    }
+}
+
+fn register(app: &AppHandle, action: HotkeyAction, accelerator: &str) -> Result<(), AppError> {
+    let handle = app.clone();
+    app.global_shortcut_manager()
+        .register(accelerator, move || trigger(&handle, action))
+        .map_err(|e| AppError::invalid(format!("无法注册快捷键 {}: {}", accelerator, e)))
+}
+
+fn unregister(app: &AppHandle, accelerator: &str) {
+    if let Err(e) = app.global_shortcut_manager().unregister(accelerator) {
+        warn!("Failed to unregister {}: {}", accelerator, e);
+    }
+}
+
+// 启动时按设置注册所有快捷键，单个失败不影响其他快捷键
+pub fn register_all(app: &AppHandle) {
+    let bindings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.hotkeys.clone(),
+        Err(_) => return,
+    };
+
+    for action in HotkeyAction::ALL {
+        if let Some(accelerator) = binding_for(&bindings, action) {
+            if let Err(e) = register(app, action, &accelerator) {
+                warn!("{}", e);
+            }
+        }
+    }
+}
+
+// 设置整体更新后，注销旧的快捷键并按新设置重新注册
+pub fn rebind(app: &AppHandle, previous: &HotkeyBindings) {
+    for action in HotkeyAction::ALL {
+        if let Some(accelerator) = binding_for(previous, action) {
+            unregister(app, &accelerator);
+        }
+    }
+    register_all(app);
+}
+
+fn save_binding(app: &AppHandle, action: HotkeyAction, accelerator: Option<String>) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.hotkeys.insert(action, accelerator);
+    settings::save_and_notify(app, &settings, &["hotkeys"])
+}
+
+fn current_binding(app: &AppHandle, action: HotkeyAction) -> Result<Option<String>, AppError> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock()?;
+    Ok(binding_for(&settings.hotkeys, action))
+}
+
+#[tauri::command]
+pub fn list_hotkeys(app: AppHandle) -> Result<Vec<HotkeyBinding>, AppError> {
+    HotkeyAction::ALL
+        .into_iter()
+        .map(|action| {
+            Ok(HotkeyBinding {
+                action,
+                accelerator: current_binding(&app, action)?,
+            })
+        })
+        .collect()
+}
+
+// 注册新快捷键失败时恢复原有绑定
+#[tauri::command]
+pub fn register_hotkey(app: AppHandle, action: HotkeyAction, accel: String) -> Result<(), AppError> {
+    let previous = current_binding(&app, action)?;
+    if previous.as_deref() == Some(accel.as_str()) {
+        return Ok(());
+    }
+
+    if let Some(previous) = &previous {
+        unregister(&app, previous);
+    }
+    if let Err(e) = register(&app, action, &accel) {
+        if let Some(previous) = &previous {
+            register(&app, action, previous).ok();
+        }
+        return Err(e);
+    }
+
+    save_binding(&app, action, Some(accel))
+}
+
+#[tauri::command]
+pub fn unregister_hotkey(app: AppHandle, action: HotkeyAction) -> Result<(), AppError> {
+    if let Some(previous) = current_binding(&app, action)? {
+        unregister(&app, &previous);
+    }
+    save_binding(&app, action, None)
+}
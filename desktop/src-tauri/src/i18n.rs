@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::privacy::{self, Privacy};
+use crate::window_state::MAIN_LABEL;
+use crate::{settings, tray, AppState};
+
+// 源码中的文案即简体中文，找不到翻译时原样显示
+pub const DEFAULT_LOCALE: &str = "zh-CN";
+// 设置中保存该值时跟随系统语言
+pub const SYSTEM_LOCALE: &str = "system";
+
+const CATALOGS: &[(&str, &str)] = &[
+    ("zh-CN", include_str!("../locales/zh-CN.json")),
+    ("en-US", include_str!("../locales/en-US.json")),
+];
+
+// ui 为按键名查找的界面文案；messages 以中文原文为键，用于翻译错误消息，{} 为占位符
+#[derive(Debug, Default, Deserialize)]
+struct Catalog {
+    #[serde(default)]
+    ui: HashMap<String, String>,
+    #[serde(default)]
+    messages: HashMap<String, String>,
+    // 带占位符的消息，字面量越长越具体，匹配时优先
+    #[serde(skip)]
+    templates: Vec<(String, String)>,
+}
+
+impl Catalog {
+    fn index_templates(&mut self) {
+        let mut templates: Vec<(String, String)> = self
+            .messages
+            .iter()
+            .filter(|(source, _)| source.contains("{}"))
+            .map(|(source, target)| (source.clone(), target.clone()))
+            .collect();
+        // 长度相同时按原文排序，保证结果稳定
+        templates.sort_by(|(a, _), (b, _)| {
+            let literal = |source: &str| source.len() - source.matches("{}").count() * 2;
+            literal(b).cmp(&literal(a)).then_with(|| a.cmp(b))
+        });
+        self.templates = templates;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LocaleInfo {
+    // 设置中保存的值，可能为 system
+    pub requested: String,
+    pub resolved: String,
+    pub available: Vec<String>,
+}
+
+static CURRENT: RwLock<String> = RwLock::new(String::new());
+
+fn catalogs() -> &'static HashMap<&'static str, Catalog> {
+    static LOADED: OnceLock<HashMap<&'static str, Catalog>> = OnceLock::new();
+    LOADED.get_or_init(|| {
+        CATALOGS
+            .iter()
+            .filter_map(|(locale, source)| match serde_json::from_str::<Catalog>(source) {
+                Ok(mut catalog) => {
+                    catalog.index_templates();
+                    Some((*locale, catalog))
+                }
+                Err(e) => {
+                    warn!("Invalid locale catalog {}: {}", locale, e);
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+pub fn current() -> String {
+    match CURRENT.read() {
+        Ok(locale) if !locale.is_empty() => locale.clone(),
+        _ => DEFAULT_LOCALE.to_string(),
+    }
+}
+
+pub fn available() -> Vec<String> {
+    CATALOGS.iter().map(|(locale, _)| locale.to_string()).collect()
+}
+
+// 按语言前缀匹配支持的语言，例如 zh-Hans-CN、zh_TW 都归到 zh-CN
+fn supported(tag: &str) -> Option<&'static str> {
+    let tag = tag.trim().replace('_', "-").to_lowercase();
+    let language = tag.split('-').next().unwrap_or_default();
+    CATALOGS
+        .iter()
+        .map(|(locale, _)| *locale)
+        .find(|locale| locale.to_lowercase() == tag)
+        .or_else(|| {
+            CATALOGS.iter().map(|(locale, _)| *locale).find(|locale| {
+                locale
+                    .split('-')
+                    .next()
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(language))
+            })
+        })
+}
+
+// 设置值为 system 或不支持的语言时使用系统语言，系统语言也不支持时使用简体中文
+pub fn resolve(requested: &str) -> &'static str {
+    let explicit = (!requested.eq_ignore_ascii_case(SYSTEM_LOCALE))
+        .then(|| supported(requested))
+        .flatten();
+    explicit
+        .or_else(|| sys_locale::get_locales().find_map(|tag| supported(&tag)))
+        .unwrap_or(DEFAULT_LOCALE)
+}
+
+pub fn validate(requested: &str) -> Result<(), AppError> {
+    if requested.eq_ignore_ascii_case(SYSTEM_LOCALE) || supported(requested).is_some() {
+        Ok(())
+    } else {
+        Err(AppError::invalid(format!("不支持的界面语言: {}", requested)))
+    }
+}
+
+// 切换当前语言，在加载设置后和语言设置变化时调用
+pub fn init(requested: &str) {
+    let resolved = resolve(requested);
+    if let Ok(mut locale) = CURRENT.write() {
+        *locale = resolved.to_string();
+    }
+    info!("Locale set to {} (requested {})", resolved, requested);
+}
+
+// 按键名查找界面文案：当前语言 → 简体中文 → 键名本身
+pub fn t(key: &str) -> String {
+    let catalogs = catalogs();
+    [current().as_str(), DEFAULT_LOCALE]
+        .iter()
+        .find_map(|locale| catalogs.get(locale).and_then(|catalog| catalog.ui.get(key)))
+        .cloned()
+        .unwrap_or_else(|| key.to_string())
+}
+
+// 依次替换文案中的 {} 占位符
+pub fn tf(key: &str, args: &[&str]) -> String {
+    fill(&t(key), args)
+}
+
+fn fill(template: &str, args: &[&str]) -> String {
+    let mut parts = template.split("{}");
+    let mut result = parts.next().unwrap_or_default().to_string();
+    for (index, part) in parts.enumerate() {
+        result.push_str(args.get(index).copied().unwrap_or_default());
+        result.push_str(part);
+    }
+    result
+}
+
+// 按模板匹配消息，返回各占位符对应的内容
+fn captures<'a>(template: &str, message: &'a str) -> Option<Vec<&'a str>> {
+    let mut literals = template.split("{}");
+    let mut remaining = message.strip_prefix(literals.next()?)?;
+    let literals: Vec<&str> = literals.collect();
+    let mut values = Vec::new();
+    for (index, literal) in literals.iter().enumerate() {
+        let end = if index + 1 == literals.len() {
+            // 最后一段字面量必须位于消息末尾
+            remaining.strip_suffix(literal)?.len()
+        } else if literal.is_empty() {
+            // 相邻的占位符无法区分，前一个取空
+            0
+        } else {
+            remaining.find(literal)?
+        };
+        values.push(&remaining[..end]);
+        remaining = &remaining[end + literal.len()..];
+    }
+    Some(values)
+}
+
+fn translate_with(catalog: &Catalog, message: &str) -> Option<String> {
+    if let Some(exact) = catalog.messages.get(message) {
+        return Some(exact.clone());
+    }
+    let templated = catalog
+        .templates
+        .iter()
+        .find_map(|(source, target)| captures(source, message).map(|values| fill(target, &values)));
+    if templated.is_some() {
+        return templated;
+    }
+    // 带有状态码等后缀的消息，只翻译前半部分
+    let (head, tail) = message.split_once(": ")?;
+    catalog.messages.get(head).map(|head| format!("{}: {}", head, tail))
+}
+
+// 把源码中的中文消息翻译为当前语言，没有对应翻译时原样返回
+pub fn translate(message: &str) -> String {
+    let locale = current();
+    if locale == DEFAULT_LOCALE {
+        return message.to_string();
+    }
+    catalogs()
+        .get(locale.as_str())
+        .and_then(|catalog| translate_with(catalog, message))
+        .unwrap_or_else(|| message.to_string())
+}
+
+// 语言变化后刷新已经显示的托盘菜单、提示和主窗口标题
+pub fn apply(app: &AppHandle) {
+    let requested = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.language.clone(),
+        Err(_) => return,
+    };
+    init(&requested);
+    tray::retitle(app);
+    privacy::update_tray(app, app.state::<Privacy>().status().active);
+    if let Some(window) = app.get_window(MAIN_LABEL) {
+        if let Err(e) = window.set_title(&t("app.name")) {
+            warn!("Failed to update window title: {}", e);
+        }
+    }
+}
+
+fn locale_info(app: &AppHandle) -> Result<LocaleInfo, AppError> {
+    let requested = app.state::<AppState>().settings.lock()?.language.clone();
+    Ok(LocaleInfo {
+        requested,
+        resolved: current(),
+        available: available(),
+    })
+}
+
+// locale 为 None 时跟随系统语言
+#[tauri::command]
+pub fn set_locale(app: AppHandle, locale: Option<String>) -> Result<LocaleInfo, AppError> {
+    let locale = locale.unwrap_or_else(|| SYSTEM_LOCALE.to_string());
+    validate(&locale)?;
+    {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock()?;
+        settings.language = locale;
+        settings::save_and_notify(&app, &settings, &["language"])?;
+    }
+    apply(&app);
+    locale_info(&app)
+}
+
+#[tauri::command]
+pub fn get_locale(app: AppHandle) -> Result<LocaleInfo, AppError> {
+    locale_info(&app)
+}
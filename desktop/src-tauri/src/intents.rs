@@ -0,0 +1,535 @@
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::deep_link::{self, DeepLink};
+use crate::error::AppError;
+use crate::knowledge::embeddings::{self, Embedder};
+use crate::timers::{self, Timers};
+use crate::{i18n, launcher, mqtt, network, settings, system_control, webhooks, AppState};
+
+// 缓存的示例句向量上限，规则修改后旧的向量不再使用
+const MAX_CACHED_VECTORS: usize = 512;
+
+// regex 使用正则表达式；keyword 为不区分大小写的包含匹配；
+// embedding 把 patterns 作为示例句，与说的话语义相近时命中
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchKind {
+    Regex,
+    Keyword,
+    Embedding,
+}
+
+// 命中后执行的本地命令；参数可以来自正则的命名分组
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum IntentAction {
+    // 时长取自 amount 和 unit 分组，例如 "5" 和 "分钟"，也可以固定 duration_secs
+    StartTimer {
+        #[serde(default)]
+        duration_secs: Option<u64>,
+    },
+    StartStopwatch,
+    // 前端路由，例如 /notes
+    Navigate {
+        route: String,
+    },
+    // 应用名取自 app 分组，或固定为 name
+    LaunchApp {
+        #[serde(default)]
+        name: Option<String>,
+    },
+    OpenUrl {
+        url: String,
+    },
+    // 音量取自 level 分组，或固定为 level
+    SetVolume {
+        #[serde(default)]
+        level: Option<u32>,
+    },
+    MediaPlayPause,
+    MediaNext,
+    MediaPrevious,
+    LockScreen,
+    // 发布 MQTT 消息，payload 中的 {分组名} 替换为对应分组的内容
+    MqttPublish {
+        topic: String,
+        #[serde(default)]
+        payload: String,
+    },
+    // 执行 Webhook，vars 与正则分组一起作为模板变量，分组优先
+    RunWebhook {
+        name: String,
+        #[serde(default)]
+        vars: HashMap<String, String>,
+    },
+    // 直接回答固定的文本
+    Reply {
+        text: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct IntentRule {
+    pub name: String,
+    pub kind: MatchKind,
+    pub patterns: Vec<String>,
+    pub action: IntentAction,
+}
+
+impl IntentRule {
+    fn regex(name: &str, patterns: &[&str], action: IntentAction) -> Self {
+        Self {
+            name: name.to_string(),
+            kind: MatchKind::Regex,
+            patterns: patterns.iter().map(|pattern| pattern.to_string()).collect(),
+            action,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct IntentSettings {
+    pub enabled: bool,
+    // embedding 规则需要的最低相似度（0 - 1）
+    pub min_similarity: f32,
+    // 按顺序匹配，先命中的规则生效
+    pub rules: Vec<IntentRule>,
+}
+
+// 默认规则要求整句匹配，避免把提问误当成命令
+fn default_rules() -> Vec<IntentRule> {
+    const NUMBER: &str = "[0-9零一二两三四五六七八九十百]+";
+    vec![
+        IntentRule::regex(
+            "timer",
+            &[
+                r"(?i)\b(?:set|start)\s+(?:a\s+)?(?:timer|countdown)\s+(?:for\s+)?(?P<amount>\d+)\s*(?P<unit>seconds?|secs?|minutes?|mins?|hours?|hrs?)\b",
+                r"(?i)\b(?P<amount>\d+)[\s-]*(?P<unit>seconds?|secs?|minutes?|mins?|hours?|hrs?)\s+timer\b",
+                &format!(
+                    r"(?:设|定|设置|来)(?:一个|个)?(?P<amount>{})\s*(?P<unit>秒钟?|分钟|分|个?小时)的?(?:计时器?|倒计时|闹钟)",
+                    NUMBER
+                ),
+                &format!(r"倒计时\s*(?P<amount>{})\s*(?P<unit>秒钟?|分钟|分|个?小时)", NUMBER),
+            ],
+            IntentAction::StartTimer { duration_secs: None },
+        ),
+        IntentRule::regex(
+            "stopwatch",
+            &[
+                r"(?i)^\s*(?:start|open)\s+(?:a\s+|the\s+)?stopwatch[\s\p{P}]*$",
+                r"^\s*(?:开始|打开|启动)秒表[\s\p{P}]*$",
+            ],
+            IntentAction::StartStopwatch,
+        ),
+        IntentRule::regex(
+            "open_notes",
+            &[
+                r"(?i)^\s*(?:please\s+)?(?:open|show)\s+(?:my\s+|the\s+)?notes[\s\p{P}]*$",
+                r"^\s*(?:请)?(?:打开|查看)(?:我的)?笔记[\s\p{P}]*$",
+            ],
+            IntentAction::Navigate {
+                route: "/notes".to_string(),
+            },
+        ),
+        IntentRule::regex(
+            "volume",
+            &[
+                r"(?i)\bset\s+(?:the\s+)?volume\s+to\s+(?P<level>\d{1,3})\b",
+                &format!(r"音量(?:调到|调成|设为|设置为|设成)\s*(?P<level>{})", NUMBER),
+            ],
+            IntentAction::SetVolume { level: None },
+        ),
+        IntentRule::regex(
+            "play_pause",
+            &[
+                r"(?i)^\s*(?:pause|resume|play)\s+(?:the\s+)?music[\s\p{P}]*$",
+                r"^\s*(?:暂停|继续|播放)音乐[\s\p{P}]*$",
+            ],
+            IntentAction::MediaPlayPause,
+        ),
+        IntentRule::regex(
+            "next_track",
+            &[
+                r"(?i)^\s*(?:next|skip\s+(?:this\s+)?)\s*(?:song|track)[\s\p{P}]*$",
+                r"^\s*(?:下一首|切歌)[\s\p{P}]*$",
+            ],
+            IntentAction::MediaNext,
+        ),
+        IntentRule::regex(
+            "previous_track",
+            &[
+                r"(?i)^\s*previous\s+(?:song|track)[\s\p{P}]*$",
+                r"^\s*上一首[\s\p{P}]*$",
+            ],
+            IntentAction::MediaPrevious,
+        ),
+        IntentRule::regex(
+            "lock_screen",
+            &[
+                r"(?i)^\s*lock\s+(?:the\s+|my\s+)?(?:screen|computer)[\s\p{P}]*$",
+                r"^\s*(?:锁屏|锁定屏幕|锁定电脑)[\s\p{P}]*$",
+            ],
+            IntentAction::LockScreen,
+        ),
+    ]
+}
+
+impl Default for IntentSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_similarity: 0.8,
+            rules: default_rules(),
+        }
+    }
+}
+
+impl IntentSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if !(0.0..=1.0).contains(&self.min_similarity) {
+            return Err(AppError::invalid("相似度需在 0 - 1 之间"));
+        }
+        for rule in &self.rules {
+            if rule.name.trim().is_empty() {
+                return Err(AppError::invalid("意图规则名称不能为空"));
+            }
+            if rule.kind == MatchKind::Regex {
+                for pattern in &rule.patterns {
+                    Regex::new(pattern).map_err(|e| AppError::invalid(format!("意图规则无效 {}: {}", pattern, e)))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// 命中的规则；captures 为正则的命名分组，score 为语义相似度，其他方式为 1
+#[derive(Debug, Clone, Serialize)]
+pub struct IntentMatch {
+    pub rule: String,
+    pub kind: MatchKind,
+    pub action: IntentAction,
+    pub captures: HashMap<String, String>,
+    pub score: f32,
+}
+
+impl IntentMatch {
+    fn new(rule: &IntentRule, captures: HashMap<String, String>, score: f32) -> Self {
+        Self {
+            rule: rule.name.clone(),
+            kind: rule.kind,
+            action: rule.action.clone(),
+            captures,
+            score,
+        }
+    }
+}
+
+// 按嵌入模型缓存示例句的向量，避免每句话都重新计算
+#[derive(Default)]
+pub struct IntentRouter {
+    vectors: Mutex<HashMap<(String, String), Vec<f32>>>,
+}
+
+impl IntentRouter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn named_captures(regex: &Regex, captures: &Captures<'_>) -> HashMap<String, String> {
+    regex
+        .capture_names()
+        .flatten()
+        .filter_map(|name| Some((name.to_string(), captures.name(name)?.as_str().trim().to_string())))
+        .collect()
+}
+
+fn match_literal(rule: &IntentRule, text: &str, lowered: &str) -> Option<HashMap<String, String>> {
+    match rule.kind {
+        MatchKind::Regex => rule.patterns.iter().find_map(|pattern| {
+            let regex = Regex::new(pattern).ok()?;
+            let captures = regex.captures(text)?;
+            Some(named_captures(&regex, &captures))
+        }),
+        MatchKind::Keyword => rule
+            .patterns
+            .iter()
+            .map(|keyword| keyword.trim().to_lowercase())
+            .any(|keyword| !keyword.is_empty() && lowered.contains(&keyword))
+            .then(HashMap::new),
+        MatchKind::Embedding => None,
+    }
+}
+
+async fn match_embedding(
+    app: &AppHandle,
+    rules: &[&IntentRule],
+    text: &str,
+    min_similarity: f32,
+) -> Result<Option<IntentMatch>, AppError> {
+    let embedder = Embedder::new(embeddings::embedding_settings(app)?, network::client(app));
+    let model = embedder.model_id();
+    let router = app.state::<IntentRouter>();
+    let examples: Vec<String> = rules
+        .iter()
+        .flat_map(|rule| rule.patterns.iter())
+        .map(|example| example.trim().to_string())
+        .filter(|example| !example.is_empty())
+        .collect();
+    let missing: Vec<String> = {
+        let vectors = router.vectors.lock()?;
+        examples
+            .iter()
+            .filter(|example| !vectors.contains_key(&(model.clone(), (*example).clone())))
+            .cloned()
+            .collect()
+    };
+    let mut inputs = missing.clone();
+    inputs.push(text.to_string());
+    let mut computed = embedder.embed(&inputs).await?;
+    let Some(query) = computed.pop() else {
+        return Ok(None);
+    };
+
+    let mut vectors = router.vectors.lock()?;
+    if vectors.len() + missing.len() > MAX_CACHED_VECTORS {
+        vectors.clear();
+    }
+    for (example, vector) in missing.into_iter().zip(computed) {
+        vectors.insert((model.clone(), example), vector);
+    }
+    let best = rules
+        .iter()
+        .flat_map(|rule| rule.patterns.iter().map(move |example| (*rule, example.trim())))
+        .filter_map(|(rule, example)| {
+            let vector = vectors.get(&(model.clone(), example.to_string()))?;
+            Some((rule, embeddings::cosine(&query, vector)))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1));
+    Ok(best
+        .filter(|(_, score)| *score >= min_similarity)
+        .map(|(rule, score)| IntentMatch::new(rule, HashMap::new(), score)))
+}
+
+// 先按顺序尝试正则和关键词规则，都没有命中时再做语义匹配
+pub async fn route(app: &AppHandle, text: &str) -> Result<Option<IntentMatch>, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.intents.clone();
+    let text = text.trim();
+    if text.is_empty() {
+        return Ok(None);
+    }
+    let lowered = text.to_lowercase();
+    if let Some(found) = settings
+        .rules
+        .iter()
+        .find_map(|rule| Some(IntentMatch::new(rule, match_literal(rule, text, &lowered)?, 1.0)))
+    {
+        return Ok(Some(found));
+    }
+    let semantic: Vec<&IntentRule> = settings
+        .rules
+        .iter()
+        .filter(|rule| rule.kind == MatchKind::Embedding)
+        .collect();
+    if semantic.is_empty() {
+        return Ok(None);
+    }
+    match_embedding(app, &semantic, text, settings.min_similarity).await
+}
+
+// 支持阿拉伯数字和常见的中文数字，如 "十五"、"两百"
+fn parse_number(text: &str) -> Option<u64> {
+    if let Ok(number) = text.parse() {
+        return Some(number);
+    }
+    let (mut total, mut current) = (0u64, None::<u64>);
+    for c in text.chars() {
+        match c {
+            '零' => current = Some(0),
+            '一' => current = Some(1),
+            '二' | '两' => current = Some(2),
+            '三' => current = Some(3),
+            '四' => current = Some(4),
+            '五' => current = Some(5),
+            '六' => current = Some(6),
+            '七' => current = Some(7),
+            '八' => current = Some(8),
+            '九' => current = Some(9),
+            '十' => total += current.take().unwrap_or(1) * 10,
+            '百' => total += current.take().unwrap_or(1) * 100,
+            _ => return None,
+        }
+    }
+    Some(total + current.unwrap_or(0))
+}
+
+fn unit_secs(unit: &str) -> Option<u64> {
+    let unit = unit.to_lowercase();
+    if unit.starts_with('s') || unit.starts_with('秒') {
+        Some(1)
+    } else if unit.starts_with('m') || unit.starts_with('分') {
+        Some(60)
+    } else if unit.starts_with('h') || unit.contains("小时") {
+        Some(3600)
+    } else {
+        None
+    }
+}
+
+fn timer_secs(captures: &HashMap<String, String>) -> Option<u64> {
+    let amount = parse_number(captures.get("amount")?)?;
+    // 超出范围的时长视为没有识别出来
+    amount.checked_mul(unit_secs(captures.get("unit")?)?)
+}
+
+fn describe_duration(secs: u64) -> String {
+    let parts = [
+        (secs / 3600, "intents.hours"),
+        (secs % 3600 / 60, "intents.minutes"),
+        (secs % 60, "intents.seconds"),
+    ];
+    parts
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, key)| i18n::tf(key, &[&value.to_string()]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// 执行命中的命令，返回由助手说出的确认
+async fn execute(app: &AppHandle, found: &IntentMatch) -> Result<String, AppError> {
+    let captured = |name: &str| found.captures.get(name).filter(|value| !value.is_empty()).cloned();
+    match &found.action {
+        IntentAction::StartTimer { duration_secs } => {
+            let secs = duration_secs
+                .or_else(|| timer_secs(&found.captures))
+                .ok_or_else(|| AppError::invalid("没有识别出计时时长"))?;
+            let timer = timers::start_timer(app.state::<Timers>(), secs, captured("label"))?;
+            Ok(i18n::tf(
+                "intents.timer_started",
+                &[&timer.label, &describe_duration(secs)],
+            ))
+        }
+        IntentAction::StartStopwatch => {
+            timers::start_stopwatch(app.state::<Timers>(), captured("label"))?;
+            Ok(i18n::t("intents.stopwatch_started"))
+        }
+        IntentAction::Navigate { route } => {
+            deep_link::dispatch(app, &DeepLink::Navigate { route: route.clone() })?;
+            Ok(i18n::t("intents.opened"))
+        }
+        IntentAction::LaunchApp { name } => {
+            let name = name
+                .clone()
+                .or_else(|| captured("app"))
+                .ok_or_else(|| AppError::invalid("没有识别出应用名称"))?;
+            let entry = launcher::launch_app(app.clone(), name).await?;
+            Ok(i18n::tf("intents.launched", &[&entry.name]))
+        }
+        IntentAction::OpenUrl { url } => {
+            launcher::open_url(url.clone()).await?;
+            Ok(i18n::t("intents.opened"))
+        }
+        IntentAction::SetVolume { level } => {
+            let level = level
+                .or_else(|| {
+                    captured("level")
+                        .and_then(|level| parse_number(&level))
+                        .and_then(|level| u32::try_from(level).ok())
+                })
+                .ok_or_else(|| AppError::invalid("没有识别出音量"))?;
+            let volume = system_control::set_system_volume(app.clone(), Some(level), None, None).await?;
+            Ok(i18n::tf("intents.volume_set", &[&volume.level.to_string()]))
+        }
+        IntentAction::MediaPlayPause => {
+            system_control::media_play_pause(app.clone()).await?;
+            Ok(i18n::t("intents.done"))
+        }
+        IntentAction::MediaNext => {
+            system_control::media_next(app.clone()).await?;
+            Ok(i18n::t("intents.done"))
+        }
+        IntentAction::MediaPrevious => {
+            system_control::media_prev(app.clone()).await?;
+            Ok(i18n::t("intents.done"))
+        }
+        IntentAction::LockScreen => {
+            system_control::lock_screen(app.clone()).await?;
+            Ok(i18n::t("intents.done"))
+        }
+        IntentAction::MqttPublish { topic, payload } => {
+            let payload = found.captures.iter().fold(payload.clone(), |payload, (name, value)| {
+                payload.replace(&format!("{{{}}}", name), value)
+            });
+            mqtt::publish(app, topic, Value::String(payload), 0, false)?;
+            Ok(i18n::t("intents.done"))
+        }
+        IntentAction::RunWebhook { name, vars } => {
+            let mut vars = vars.clone();
+            vars.extend(found.captures.clone());
+            let run = webhooks::run(app, name, &vars, false).await?;
+            match run.status {
+                Some(status) if (200..300).contains(&status) => Ok(i18n::t("intents.done")),
+                status => Err(AppError::unavailable(
+                    format!("Webhook {} 返回错误", name),
+                    status.unwrap_or_default(),
+                )),
+            }
+        }
+        IntentAction::Reply { text } => Ok(text.clone()),
+    }
+}
+
+// 在调用模型之前执行：命中规则时执行对应命令并返回回答，没有命中时返回 None
+pub async fn handle(app: &AppHandle, text: &str) -> Result<Option<String>, AppError> {
+    if !app.state::<AppState>().settings.lock()?.intents.enabled {
+        return Ok(None);
+    }
+    let found = match route(app, text).await {
+        Ok(Some(found)) => found,
+        Ok(None) => return Ok(None),
+        // 语义匹配依赖的嵌入服务不可用时直接交给模型
+        Err(e) => {
+            warn!("Intent routing failed: {}", e);
+            return Ok(None);
+        }
+    };
+    info!(
+        "Matched intent {} ({:?}, score {:.2})",
+        found.rule, found.kind, found.score
+    );
+    execute(app, &found).await.map(Some)
+}
+
+// 只匹配不执行，用于调试规则
+#[tauri::command]
+pub async fn test_intent(app: AppHandle, text: String) -> Result<Option<IntentMatch>, AppError> {
+    route(&app, &text).await
+}
+
+// 规则修改后清空示例句向量缓存
+#[tauri::command]
+pub fn set_intent_rules(
+    app: AppHandle,
+    router: State<'_, IntentRouter>,
+    rules: Vec<IntentRule>,
+) -> Result<IntentSettings, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let mut intents = settings.intents.clone();
+    intents.rules = rules;
+    intents.validate()?;
+    settings.intents = intents.clone();
+    settings::save_and_notify(&app, &settings, &["intents"])?;
+    router.vectors.lock()?.clear();
+    info!("Saved {} intent rules", intents.rules.len());
+    Ok(intents)
+}
@@ -0,0 +1,78 @@
+// 按段落和句子切分文档，供检索与向量化使用
+pub const CHUNK_CHARS: usize = 800;
+// 相邻分块之间保留的重叠字符数，避免句子被切断后丢失上下文
+pub const CHUNK_OVERLAP: usize = 100;
+
+const SENTENCE_ENDS: &[char] = &['。', '！', '？', '；', '.', '!', '?', ';', '\n'];
+
+#[derive(Debug, Clone)]
+pub struct Chunk {
+    pub seq: usize,
+    pub content: String,
+}
+
+// 把过长的段落拆成句子，单句仍超长时按字符硬切
+fn split_units(text: &str, max_chars: usize) -> Vec<String> {
+    let mut units = Vec::new();
+    for paragraph in text.split("\n\n").map(str::trim).filter(|p| !p.is_empty()) {
+        if paragraph.chars().count() <= max_chars {
+            units.push(paragraph.to_string());
+            continue;
+        }
+
+        let mut sentence = String::new();
+        for c in paragraph.chars() {
+            sentence.push(c);
+            if SENTENCE_ENDS.contains(&c) {
+                units.push(std::mem::take(&mut sentence));
+            }
+        }
+        if !sentence.trim().is_empty() {
+            units.push(sentence);
+        }
+    }
+
+    units
+        .into_iter()
+        .flat_map(|unit| {
+            let chars: Vec<char> = unit.chars().collect();
+            chars
+                .chunks(max_chars)
+                .map(|part| part.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn tail(text: &str, chars: usize) -> String {
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(chars)).collect()
+}
+
+pub fn chunk_text(text: &str) -> Vec<Chunk> {
+    let mut chunks: Vec<Chunk> = Vec::new();
+    let mut current = String::new();
+
+    // 重叠部分和换行之后仍要放得下一个完整的单元，分块才不会超过 CHUNK_CHARS
+    for unit in split_units(text, CHUNK_CHARS - CHUNK_OVERLAP - 1) {
+        if !current.is_empty() && current.chars().count() + 1 + unit.chars().count() > CHUNK_CHARS {
+            let overlap = tail(&current, CHUNK_OVERLAP);
+            chunks.push(Chunk {
+                seq: chunks.len(),
+                content: std::mem::replace(&mut current, overlap),
+            });
+        }
+        if !current.is_empty() {
+            current.push('\n');
+        }
+        current.push_str(&unit);
+    }
+
+    if !current.trim().is_empty() {
+        chunks.push(Chunk {
+            seq: chunks.len(),
+            content: current,
+        });
+    }
+    chunks
+}
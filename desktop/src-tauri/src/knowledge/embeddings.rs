@@ -0,0 +1,350 @@
+use rusqlite::params;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use super::KnowledgeBase;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::power::{self, PowerConsumer};
+use crate::{network, resources, secrets, AppState};
+
+// 本地哈希向量的维度
+const HASH_DIMENSIONS: usize = 512;
+// 每次请求嵌入接口的分块数量
+const EMBED_BATCH: usize = 32;
+const DEFAULT_TOP_K: usize = 5;
+const MAX_TOP_K: usize = 50;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS chunk_embeddings (
+    chunk_id INTEGER NOT NULL REFERENCES chunks(id) ON DELETE CASCADE,
+    model TEXT NOT NULL,
+    vector BLOB NOT NULL,
+    PRIMARY KEY (chunk_id, model)
+);
+";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EmbeddingProvider {
+    // 字符 n-gram 特征哈希，完全离线，不需要模型文件
+    #[default]
+    Local,
+    // OpenAI 兼容的 /embeddings 接口（Ollama、llama.cpp server 等）
+    Http,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct EmbeddingSettings {
+    pub provider: EmbeddingProvider,
+    pub url: String,
+    pub model: String,
+    // 旧版本的明文配置，启动时迁移到系统钥匙串；迁移成功后不再写回设置文件
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+}
+
+impl Default for EmbeddingSettings {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProvider::Local,
+            url: "http://localhost:11434/v1/embeddings".to_string(),
+            model: "nomic-embed-text".to_string(),
+            api_key: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SemanticHit {
+    pub chunk_id: i64,
+    pub document_id: i64,
+    pub title: String,
+    pub source: String,
+    pub content: String,
+    pub score: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingProgress {
+    pub model: String,
+    pub embedded: usize,
+    pub pending: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingItem {
+    embedding: Vec<f32>,
+}
+
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+fn normalize(vector: &mut [f32]) {
+    let norm = vector.iter().map(|v| v * v).sum::<f32>().sqrt();
+    if norm > 0.0 {
+        vector.iter_mut().for_each(|v| *v /= norm);
+    }
+}
+
+// 字符二元、三元组哈希到固定维度，中文无需分词也能体现字面相似度
+fn hash_embedding(text: &str) -> Vec<f32> {
+    let chars: Vec<char> = text
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .collect();
+    let mut vector = vec![0.0f32; HASH_DIMENSIONS];
+    for n in 2..=3 {
+        for gram in chars.windows(n) {
+            let hash = fnv1a(gram.iter().collect::<String>().as_bytes());
+            let index = (hash % HASH_DIMENSIONS as u64) as usize;
+            let sign = if hash >> 63 == 0 { 1.0 } else { -1.0 };
+            vector[index] += sign;
+        }
+    }
+    normalize(&mut vector);
+    vector
+}
+
+pub fn cosine(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_blob(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+pub struct Embedder {
+    settings: EmbeddingSettings,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl Embedder {
+    pub fn new(settings: EmbeddingSettings, client: reqwest::Client) -> Self {
+        let api_key = match settings.provider {
+            EmbeddingProvider::Http => secrets::get(secrets::EMBEDDING_API_KEY)
+                .unwrap_or_else(|e| {
+                    warn!("{}", e);
+                    None
+                })
+                .or_else(|| settings.api_key.clone()),
+            EmbeddingProvider::Local => None,
+        };
+        Self {
+            settings,
+            api_key,
+            client,
+        }
+    }
+
+    // 不同模型的向量不能混用，按模型标识分别存储
+    pub fn model_id(&self) -> String {
+        match self.settings.provider {
+            EmbeddingProvider::Local => format!("local-hash-{}", HASH_DIMENSIONS),
+            EmbeddingProvider::Http => format!("http:{}", self.settings.model),
+        }
+    }
+
+    pub async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, AppError> {
+        match self.settings.provider {
+            EmbeddingProvider::Local => Ok(texts.iter().map(|text| hash_embedding(text)).collect()),
+            EmbeddingProvider::Http => {
+                let mut request = self.client.post(&self.settings.url).json(&serde_json::json!({
+                    "model": self.settings.model,
+                    "input": texts,
+                }));
+                if let Some(key) = self.api_key.as_deref() {
+                    request = request.bearer_auth(key);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .map_err(|e| AppError::network("无法连接嵌入服务", e))?;
+                if !response.status().is_success() {
+                    return Err(AppError::status("嵌入服务返回错误", response.status()));
+                }
+                let body: EmbeddingResponse = response.json().await?;
+                if body.data.len() != texts.len() {
+                    return Err(AppError::Internal("嵌入服务返回的向量数量不匹配".to_string()));
+                }
+                Ok(body
+                    .data
+                    .into_iter()
+                    .map(|item| {
+                        let mut vector = item.embedding;
+                        normalize(&mut vector);
+                        vector
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+impl KnowledgeBase {
+    pub(super) fn init_embeddings(conn: &rusqlite::Connection) -> Result<(), AppError> {
+        conn.execute_batch(SCHEMA).map_err(AppError::from)
+    }
+
+    fn pending_chunks(&self, model: &str, limit: usize) -> Result<(Vec<(i64, String)>, usize), AppError> {
+        let conn = self.conn.lock()?;
+        let pending: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM chunks c
+                 WHERE NOT EXISTS (SELECT 1 FROM chunk_embeddings e WHERE e.chunk_id = c.id AND e.model = ?1)",
+            params![model],
+            |row| row.get(0),
+        )?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.content FROM chunks c
+                 WHERE NOT EXISTS (SELECT 1 FROM chunk_embeddings e WHERE e.chunk_id = c.id AND e.model = ?1)
+                 ORDER BY c.id LIMIT ?2",
+        )?;
+        let chunks = stmt
+            .query_map(params![model, limit as i64], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+        Ok((chunks?, pending as usize))
+    }
+
+    fn store_embeddings(&self, model: &str, vectors: &[(i64, Vec<f32>)]) -> Result<(), AppError> {
+        let mut conn = self.conn.lock()?;
+        let tx = conn.transaction()?;
+        {
+            let mut insert =
+                tx.prepare("INSERT OR REPLACE INTO chunk_embeddings (chunk_id, model, vector) VALUES (?1, ?2, ?3)")?;
+            for (chunk_id, vector) in vectors {
+                insert.execute(params![chunk_id, model, to_blob(vector)])?;
+            }
+        }
+        tx.commit().map_err(AppError::from)
+    }
+
+    // 向量规模在桌面端通常只有几千条，直接全量计算余弦相似度
+    fn nearest(&self, model: &str, query: &[f32], top_k: usize) -> Result<Vec<SemanticHit>, AppError> {
+        let conn = self.conn.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.document_id, d.title, d.source, c.content, e.vector
+                 FROM chunk_embeddings e
+                 JOIN chunks c ON c.id = e.chunk_id
+                 JOIN documents d ON d.id = c.document_id
+                 WHERE e.model = ?1",
+        )?;
+        let rows = stmt
+            .query_map(params![model], |row| {
+                let vector: Vec<u8> = row.get(5)?;
+                Ok(SemanticHit {
+                    chunk_id: row.get(0)?,
+                    document_id: row.get(1)?,
+                    title: row.get(2)?,
+                    source: row.get(3)?,
+                    content: row.get(4)?,
+                    score: cosine(query, &from_blob(&vector)),
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+
+        let mut hits = rows?;
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        hits.truncate(top_k);
+        Ok(hits)
+    }
+}
+
+pub fn embedding_settings(app: &AppHandle) -> Result<EmbeddingSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.knowledge.embedding.clone())
+}
+
+// 为还没有向量的分块补算向量，返回本次新增数量
+pub async fn embed_pending(app: &AppHandle) -> Result<usize, AppError> {
+    let embedder = Embedder::new(embedding_settings(app)?, network::client(app));
+    let model = embedder.model_id();
+    let state = app.state::<AppState>();
+    let mut embedded = 0;
+
+    loop {
+        let (batch, pending) = state.knowledge.pending_chunks(&model, EMBED_BATCH)?;
+        if batch.is_empty() {
+            break;
+        }
+        let texts: Vec<String> = batch.iter().map(|(_, content)| content.clone()).collect();
+        let vectors = embedder.embed(&texts).await?;
+        let pairs: Vec<(i64, Vec<f32>)> = batch.iter().map(|(id, _)| *id).zip(vectors).collect();
+        state.knowledge.store_embeddings(&model, &pairs)?;
+
+        embedded += pairs.len();
+        let progress = EmbeddingProgress {
+            model: model.clone(),
+            embedded,
+            pending: pending.saturating_sub(pairs.len()),
+        };
+        events::publish(app, AppEvent::EmbeddingProgress(progress));
+    }
+
+    if embedded > 0 {
+        info!("Computed {} chunk embeddings with {}", embedded, model);
+    }
+    Ok(embedded)
+}
+
+// 导入文档后在后台补算向量
+pub fn spawn_embed_pending(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        resources::wait_unthrottled(&app).await;
+        power::wait_allowed(&app, PowerConsumer::Indexing).await;
+        if let Err(e) = embed_pending(&app).await {
+            warn!("Failed to compute embeddings: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn semantic_search(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    query: String,
+    top_k: Option<usize>,
+) -> Result<Vec<SemanticHit>, AppError> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    embed_pending(&app).await?;
+    let embedder = Embedder::new(embedding_settings(&app)?, network::client(&app));
+    let vector = embedder
+        .embed(std::slice::from_ref(&query))
+        .await?
+        .pop()
+        .ok_or("嵌入服务没有返回向量")?;
+    state.knowledge.nearest(
+        &embedder.model_id(),
+        &vector,
+        top_k.unwrap_or(DEFAULT_TOP_K).clamp(1, MAX_TOP_K),
+    )
+}
+
+#[tauri::command]
+pub async fn build_embeddings(app: AppHandle) -> Result<usize, AppError> {
+    embed_pending(&app).await
+}
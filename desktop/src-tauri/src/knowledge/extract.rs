@@ -0,0 +1,103 @@
+use quick_xml::events::Event;
+use quick_xml::Reader;
+use std::io::Read;
+use std::path::Path;
+
+use crate::error::AppError;
+
+// 可导入知识库的文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentKind {
+    Pdf,
+    Docx,
+    Markdown,
+    Text,
+}
+
+impl DocumentKind {
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_string_lossy().to_ascii_lowercase();
+        match extension.as_str() {
+            "pdf" => Some(DocumentKind::Pdf),
+            "docx" => Some(DocumentKind::Docx),
+            "md" | "markdown" => Some(DocumentKind::Markdown),
+            "txt" | "text" | "log" | "csv" | "json" | "html" | "htm" => Some(DocumentKind::Text),
+            _ => None,
+        }
+    }
+}
+
+// 提取文件的纯文本内容，不支持的格式按 UTF-8 文本尝试读取
+pub fn extract_text(path: &Path) -> Result<String, AppError> {
+    let text = match DocumentKind::from_path(path) {
+        Some(DocumentKind::Pdf) => extract_pdf(path)?,
+        Some(DocumentKind::Docx) => extract_docx(path)?,
+        Some(DocumentKind::Markdown) | Some(DocumentKind::Text) | None => {
+            std::fs::read_to_string(path).map_err(|e| format!("无法读取文档 {}: {}", path.display(), e))?
+        }
+    };
+    Ok(normalize(&text))
+}
+
+fn extract_pdf(path: &Path) -> Result<String, AppError> {
+    let bytes = std::fs::read(path).map_err(|e| format!("无法读取文档 {}: {}", path.display(), e))?;
+    // pdf-extract 遇到格式异常的文件可能直接 panic，这里转换为普通错误
+    std::panic::catch_unwind(|| pdf_extract::extract_text_from_mem(&bytes))
+        .map_err(|_| AppError::invalid(format!("无法解析 PDF: {}", path.display())))?
+        .map_err(|e| AppError::invalid(format!("无法解析 PDF {}: {}", path.display(), e)))
+}
+
+// DOCX 是 zip 包，正文在 word/document.xml 的 <w:t> 节点中，<w:p> 为段落
+fn extract_docx(path: &Path) -> Result<String, AppError> {
+    let file = std::fs::File::open(path).map_err(|e| format!("无法读取文档 {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::invalid(format!("无法解析 DOCX {}: {}", path.display(), e)))?;
+    let mut xml = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| AppError::invalid(format!("DOCX 缺少正文: {}", e)))?
+        .read_to_string(&mut xml)?;
+
+    let mut reader = Reader::from_str(&xml);
+    let mut text = String::new();
+    let mut in_text = false;
+    loop {
+        match reader
+            .read_event()
+            .map_err(|e| AppError::invalid(format!("无法解析 DOCX 正文: {}", e)))?
+        {
+            Event::Start(e) if e.name().as_ref() == b"w:t" => in_text = true,
+            Event::End(e) if e.name().as_ref() == b"w:t" => in_text = false,
+            Event::End(e) if e.name().as_ref() == b"w:p" => text.push('\n'),
+            Event::Empty(e) if e.name().as_ref() == b"w:tab" => text.push('\t'),
+            Event::Empty(e) if e.name().as_ref() == b"w:br" => text.push('\n'),
+            Event::Text(e) if in_text => text.push_str(
+                &e.unescape()
+                    .map_err(|e| AppError::invalid(format!("无法解析 DOCX 正文: {}", e)))?,
+            ),
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(text)
+}
+
+// 统一换行并压缩多余空行
+fn normalize(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+    for line in text.replace("\r\n", "\n").replace('\r', "\n").lines() {
+        let line = line.trim_end();
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            if blank_lines > 1 {
+                continue;
+            }
+        } else {
+            blank_lines = 0;
+        }
+        result.push_str(line);
+        result.push('\n');
+    }
+    result.trim().to_string()
+}
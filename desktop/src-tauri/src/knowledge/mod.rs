@@ -0,0 +1,715 @@
+pub mod chunk;
+pub mod embeddings;
+pub mod extract;
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::platform::{self, TaskbarProgress};
+use crate::storage::{self, now_millis, DATABASE_FILE};
+use crate::{i18n, AppState};
+
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 50;
+// trigram 分词要求每个检索词至少 3 个字符，更短的查询退回 LIKE
+const TRIGRAM_MIN_CHARS: usize = 3;
+const SNIPPET_CHARS: usize = 40;
+
+// trigram 分词对中文等没有空格分隔的文本也能做子串匹配
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS documents (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source TEXT NOT NULL UNIQUE,
+    title TEXT NOT NULL,
+    content TEXT NOT NULL,
+    modified INTEGER,
+    indexed_at INTEGER NOT NULL
+);
+CREATE VIRTUAL TABLE IF NOT EXISTS documents_fts USING fts5(title, content, tokenize = 'trigram');
+CREATE TABLE IF NOT EXISTS chunks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    document_id INTEGER NOT NULL REFERENCES documents(id) ON DELETE CASCADE,
+    seq INTEGER NOT NULL,
+    content TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_chunks_document ON chunks(document_id, seq);
+";
+// 每写入这么多分块上报一次导入进度
+const PROGRESS_EVERY: usize = 20;
+// 音频转写的来源前缀，后接原始文件路径；不参与文件监听和重新索引
+pub const AUDIO_SOURCE_PREFIX: &str = "audio:";
+// 笔记的来源前缀，后接笔记 ID；笔记更新时覆盖同一来源的索引
+pub const NOTE_SOURCE_PREFIX: &str = "note:";
+
+// 待索引的内容：本地文件路径或直接传入的文本
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum DocumentInput {
+    Path { path: String },
+    Text { text: String, title: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexedDocument {
+    pub id: i64,
+    pub source: String,
+    pub title: String,
+    pub chars: usize,
+    pub chunks: usize,
+    // 内容与上次索引相同时为 false
+    pub changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportProgress {
+    pub path: String,
+    pub index: usize,
+    pub total: usize,
+    pub stage: String,
+    pub progress: u8,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeDocument {
+    pub id: i64,
+    pub source: String,
+    pub title: String,
+    pub chars: usize,
+    pub indexed_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgeHit {
+    pub id: i64,
+    pub source: String,
+    pub title: String,
+    // 命中位置附近的片段，匹配词用 <mark> 包裹
+    pub snippet: String,
+    pub score: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReindexReport {
+    pub updated: u32,
+    pub removed: u32,
+    pub unchanged: u32,
+}
+
+fn file_modified(path: &Path) -> Option<i64> {
+    let modified = std::fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_millis() as i64)
+}
+
+// 提取文件文本作为索引内容，标题取文件名
+pub fn read_document(path: &Path) -> Result<(String, String), AppError> {
+    let content = extract::extract_text(path)?;
+    let title = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+    Ok((title, content))
+}
+
+// 把用户输入拆成带引号的检索词，避免 FTS5 把符号当作查询语法
+pub fn match_query(query: &str) -> Option<String> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    if terms.is_empty() || terms.iter().any(|term| term.chars().count() < TRIGRAM_MIN_CHARS) {
+        return None;
+    }
+    Some(
+        terms
+            .iter()
+            .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+// 结果中的摘要由前端按 HTML 显示，正文需要转义，只保留高亮标签
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+// FTS 的 snippet() 用控制字符标记命中位置，转义后再换成 <mark>
+pub const MARK_START: &str = "\u{1}";
+pub const MARK_END: &str = "\u{2}";
+
+pub fn highlight(snippet: &str) -> String {
+    escape_html(snippet)
+        .replace(MARK_START, "<mark>")
+        .replace(MARK_END, "</mark>")
+}
+
+// LIKE 不区分 ASCII 大小写，这里同样忽略大小写查找；只有标题命中时不加高亮
+fn like_snippet(content: &str, query: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let needle: Vec<char> = query.chars().collect();
+    let found = if needle.is_empty() {
+        None
+    } else {
+        chars.windows(needle.len()).position(|window| {
+            window
+                .iter()
+                .zip(&needle)
+                .all(|(a, b)| a.to_lowercase().eq(b.to_lowercase()))
+        })
+    };
+    let text = |start: usize, end: usize| escape_html(&chars[start..end].iter().collect::<String>());
+    let (start, end, body) = match found {
+        Some(position) => {
+            let start = position.saturating_sub(SNIPPET_CHARS / 2);
+            let matched = position + needle.len();
+            let end = (matched + SNIPPET_CHARS / 2).min(chars.len());
+            let body = format!(
+                "{}<mark>{}</mark>{}",
+                text(start, position),
+                text(position, matched),
+                text(matched, end)
+            );
+            (start, end, body)
+        }
+        None => {
+            let end = SNIPPET_CHARS.min(chars.len());
+            (0, end, text(0, end))
+        }
+    };
+    format!(
+        "{}{}{}",
+        if start > 0 { "…" } else { "" },
+        body,
+        if end < chars.len() { "…" } else { "" }
+    )
+}
+
+// 知识库全文索引，与对话历史共用同一个数据库文件
+pub struct KnowledgeBase {
+    conn: Mutex<Connection>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    watched: Mutex<HashSet<PathBuf>>,
+}
+
+impl KnowledgeBase {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch("PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+        Self::init_embeddings(&conn)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            watcher: Mutex::new(None),
+            watched: Mutex::new(HashSet::new()),
+        })
+    }
+
+    fn index_content(
+        &self,
+        source: &str,
+        title: &str,
+        content: &str,
+        modified: Option<i64>,
+        on_progress: &dyn Fn(usize, usize),
+    ) -> Result<IndexedDocument, AppError> {
+        let mut conn = self.conn.lock()?;
+        let tx = conn.transaction()?;
+
+        let existing: Option<(i64, String, String)> = tx
+            .query_row(
+                "SELECT id, title, content FROM documents WHERE source = ?1",
+                params![source],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?;
+        if let Some((id, old_title, old_content)) = &existing {
+            if old_title == title && old_content == content {
+                tx.execute(
+                    "UPDATE documents SET modified = ?1 WHERE id = ?2",
+                    params![modified, id],
+                )?;
+                let chunks: i64 = tx.query_row(
+                    "SELECT COUNT(*) FROM chunks WHERE document_id = ?1",
+                    params![id],
+                    |row| row.get(0),
+                )?;
+                tx.commit()?;
+                return Ok(IndexedDocument {
+                    id: *id,
+                    source: source.to_string(),
+                    title: title.to_string(),
+                    chars: content.chars().count(),
+                    chunks: chunks as usize,
+                    changed: false,
+                });
+            }
+        }
+
+        let id: i64 = tx.query_row(
+            "INSERT INTO documents (source, title, content, modified, indexed_at) VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT(source) DO UPDATE SET title = excluded.title, content = excluded.content,
+                     modified = excluded.modified, indexed_at = excluded.indexed_at
+                 RETURNING id",
+            params![source, title, content, modified, now_millis()],
+            |row| row.get(0),
+        )?;
+        tx.execute("DELETE FROM documents_fts WHERE rowid = ?1", params![id])?;
+        tx.execute(
+            "INSERT INTO documents_fts (rowid, title, content) VALUES (?1, ?2, ?3)",
+            params![id, title, content],
+        )?;
+
+        let chunks = chunk::chunk_text(content);
+        tx.execute("DELETE FROM chunks WHERE document_id = ?1", params![id])?;
+        {
+            let mut insert = tx.prepare("INSERT INTO chunks (document_id, seq, content) VALUES (?1, ?2, ?3)")?;
+            for chunk in &chunks {
+                insert.execute(params![id, chunk.seq as i64, chunk.content])?;
+                if (chunk.seq + 1) % PROGRESS_EVERY == 0 {
+                    on_progress(chunk.seq + 1, chunks.len());
+                }
+            }
+        }
+        tx.commit()?;
+        on_progress(chunks.len(), chunks.len());
+
+        Ok(IndexedDocument {
+            id,
+            source: source.to_string(),
+            title: title.to_string(),
+            chars: content.chars().count(),
+            chunks: chunks.len(),
+            changed: true,
+        })
+    }
+
+    // 加入分块之前导入的文档没有分块，启动时补上；每篇文档单独提交，不长时间占用连接
+    pub fn backfill_chunks(&self) -> Result<usize, AppError> {
+        let ids: Vec<i64> = {
+            let conn = self.conn.lock()?;
+            let mut stmt = conn.prepare(
+                "SELECT id FROM documents d
+                     WHERE content != '' AND NOT EXISTS (SELECT 1 FROM chunks c WHERE c.document_id = d.id)",
+            )?;
+            let ids = stmt
+                .query_map([], |row| row.get(0))
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+            ids?
+        };
+        for id in &ids {
+            let mut conn = self.conn.lock()?;
+            let tx = conn.transaction()?;
+            let content: String =
+                tx.query_row("SELECT content FROM documents WHERE id = ?1", params![id], |row| row.get(0))?;
+            {
+                let mut insert = tx.prepare("INSERT INTO chunks (document_id, seq, content) VALUES (?1, ?2, ?3)")?;
+                for chunk in chunk::chunk_text(&content) {
+                    insert.execute(params![id, chunk.seq as i64, chunk.content])?;
+                }
+            }
+            tx.commit()?;
+        }
+        Ok(ids.len())
+    }
+
+    pub fn index_path(&self, path: &Path) -> Result<IndexedDocument, AppError> {
+        self.import_path(path, &|_, _| {}, &|_| {})
+    }
+
+    // on_stage 在提取完成后调用，on_progress 上报分块写入进度
+    fn import_path(
+        &self,
+        path: &Path,
+        on_progress: &dyn Fn(usize, usize),
+        on_stage: &dyn Fn(&str),
+    ) -> Result<IndexedDocument, AppError> {
+        let (title, content) = read_document(path)?;
+        if content.is_empty() {
+            return Err(AppError::invalid(format!("文档没有可提取的文本: {}", path.display())));
+        }
+        on_stage("indexing");
+        let source = path.to_string_lossy().to_string();
+        self.index_content(&source, &title, &content, file_modified(path), on_progress)
+    }
+
+    // 保存音频文件的转写文本，标题取文件名
+    pub fn index_transcript(
+        &self,
+        path: &Path,
+        text: &str,
+        on_progress: &dyn Fn(usize, usize),
+    ) -> Result<IndexedDocument, AppError> {
+        let source = format!("{}{}", AUDIO_SOURCE_PREFIX, path.to_string_lossy());
+        let title = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        self.index_content(&source, &title, text, file_modified(path), on_progress)
+    }
+
+    pub fn index_note(&self, note_id: &str, title: &str, body: &str) -> Result<IndexedDocument, AppError> {
+        let source = format!("{}{}", NOTE_SOURCE_PREFIX, note_id);
+        self.index_content(&source, title, body, None, &|_, _| {})
+    }
+
+    pub fn remove_note(&self, note_id: &str) -> Result<bool, AppError> {
+        self.remove_source(&format!("{}{}", NOTE_SOURCE_PREFIX, note_id))
+    }
+
+    pub fn index_text(&self, title: Option<String>, text: &str) -> Result<IndexedDocument, AppError> {
+        let source = format!("text:{}", uuid::Uuid::new_v4());
+        let title = title.unwrap_or_else(|| text.trim().chars().take(30).collect());
+        self.index_content(&source, &title, text, None, &|_, _| {})
+    }
+
+    pub fn remove(&self, id: i64) -> Result<bool, AppError> {
+        let mut conn = self.conn.lock()?;
+        let tx = conn.transaction()?;
+        tx.execute("DELETE FROM documents_fts WHERE rowid = ?1", params![id])?;
+        tx.execute("DELETE FROM chunks WHERE document_id = ?1", params![id])?;
+        let deleted = tx.execute("DELETE FROM documents WHERE id = ?1", params![id])?;
+        tx.commit()?;
+        Ok(deleted > 0)
+    }
+
+    fn remove_source(&self, source: &str) -> Result<bool, AppError> {
+        let id: Option<i64> = self
+            .conn
+            .lock()?
+            .query_row("SELECT id FROM documents WHERE source = ?1", params![source], |row| row.get(0))
+            .optional()?;
+        match id {
+            Some(id) => self.remove(id),
+            None => Ok(false),
+        }
+    }
+
+    pub fn list(&self) -> Result<Vec<KnowledgeDocument>, AppError> {
+        let conn = self.conn.lock()?;
+        let mut stmt = conn
+            .prepare("SELECT id, source, title, length(content), indexed_at FROM documents ORDER BY indexed_at DESC")?;
+        let documents = stmt
+            .query_map([], |row| {
+                Ok(KnowledgeDocument {
+                    id: row.get(0)?,
+                    source: row.get(1)?,
+                    title: row.get(2)?,
+                    chars: row.get::<_, i64>(3)? as usize,
+                    indexed_at: row.get(4)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+        documents.map_err(AppError::from)
+    }
+
+    fn file_sources(&self) -> Result<Vec<(String, Option<i64>)>, AppError> {
+        let conn = self.conn.lock()?;
+        let mut stmt = conn.prepare(
+            "SELECT source, modified FROM documents
+                 WHERE source NOT LIKE 'text:%' AND source NOT LIKE 'audio:%' AND source NOT LIKE 'note:%'",
+        )?;
+        let sources = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+        sources.map_err(AppError::from)
+    }
+
+    pub fn search(&self, query: &str, limit: u32) -> Result<Vec<KnowledgeHit>, AppError> {
+        let limit = limit.clamp(1, MAX_LIMIT);
+        let conn = self.conn.lock()?;
+
+        let hits = match match_query(query) {
+            Some(expression) => {
+                let mut stmt = conn.prepare(
+                    "SELECT d.id, d.source, d.title,
+                                snippet(documents_fts, 1, char(1), char(2), '…', 24),
+                                bm25(documents_fts)
+                         FROM documents_fts JOIN documents d ON d.id = documents_fts.rowid
+                         WHERE documents_fts MATCH ?1
+                         ORDER BY bm25(documents_fts)
+                         LIMIT ?2",
+                )?;
+                let hits = stmt
+                    .query_map(params![expression, limit], |row| {
+                        Ok(KnowledgeHit {
+                            id: row.get(0)?,
+                            source: row.get(1)?,
+                            title: row.get(2)?,
+                            snippet: highlight(&row.get::<_, String>(3)?),
+                            // bm25 越小越相关，取反后分数越大越相关
+                            score: -row.get::<_, f64>(4)?,
+                        })
+                    })
+                    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+                hits?
+            }
+            None => {
+                let mut stmt = conn.prepare(
+                    "SELECT id, source, title, content FROM documents
+                         WHERE title LIKE ?1 ESCAPE '\\' OR content LIKE ?1 ESCAPE '\\'
+                         ORDER BY indexed_at DESC
+                         LIMIT ?2",
+                )?;
+                let hits = stmt
+                    .query_map(params![storage::like_pattern(query), limit], |row| {
+                        let content: String = row.get(3)?;
+                        Ok(KnowledgeHit {
+                            id: row.get(0)?,
+                            source: row.get(1)?,
+                            title: row.get(2)?,
+                            snippet: like_snippet(&content, query),
+                            score: 0.0,
+                        })
+                    })
+                    .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+                hits?
+            }
+        };
+        Ok(hits)
+    }
+
+    // 检查所有文件来源：修改过的重新索引，已删除的移出索引
+    pub fn reindex(&self) -> Result<ReindexReport, AppError> {
+        let mut report = ReindexReport::default();
+        for (source, modified) in self.file_sources()? {
+            let path = Path::new(&source);
+            if !path.exists() {
+                self.remove_source(&source)?;
+                report.removed += 1;
+                continue;
+            }
+            if modified.is_some() && file_modified(path) == modified {
+                report.unchanged += 1;
+                continue;
+            }
+            match self.index_path(path) {
+                Ok(document) if document.changed => report.updated += 1,
+                Ok(_) => report.unchanged += 1,
+                Err(e) => warn!("Failed to reindex {}: {}", source, e),
+            }
+        }
+        Ok(report)
+    }
+
+    fn refresh_source(&self, path: &Path) {
+        let source = path.to_string_lossy().to_string();
+        let known = self
+            .file_sources()
+            .map(|sources| sources.iter().any(|(known, _)| *known == source))
+            .unwrap_or(false);
+        if !known {
+            return;
+        }
+
+        let result = if path.exists() {
+            self.index_path(path).map(|document| document.changed)
+        } else {
+            self.remove_source(&source)
+        };
+        match result {
+            Ok(true) => info!("Knowledge document refreshed: {}", source),
+            Ok(false) => {}
+            Err(e) => warn!("Failed to refresh knowledge document {}: {}", source, e),
+        }
+    }
+
+    // 监听已索引文件所在目录，文件变化时增量更新索引
+    pub fn watch(&self, app: AppHandle) -> Result<(), AppError> {
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let Ok(event) = event else {
+                return;
+            };
+            if event.kind.is_access() {
+                return;
+            }
+            let state = app.state::<AppState>();
+            for path in &event.paths {
+                state.knowledge.refresh_source(path);
+            }
+        })
+        .map_err(|e| AppError::unavailable("无法监听文档目录", e))?;
+        *self.watcher.lock()? = Some(watcher);
+
+        for (source, _) in self.file_sources()? {
+            self.watch_path(Path::new(&source));
+        }
+        Ok(())
+    }
+
+    fn watch_path(&self, path: &Path) {
+        let Some(dir) = path.parent().map(Path::to_path_buf) else {
+            return;
+        };
+        let (Ok(mut watcher), Ok(mut watched)) = (self.watcher.lock(), self.watched.lock()) else {
+            return;
+        };
+        let Some(watcher) = watcher.as_mut() else {
+            return;
+        };
+        if watched.contains(&dir) {
+            return;
+        }
+        match watcher.watch(&dir, RecursiveMode::NonRecursive) {
+            Ok(()) => {
+                watched.insert(dir);
+            }
+            Err(e) => warn!("Failed to watch {}: {}", dir.display(), e),
+        }
+    }
+}
+
+// 启动时先补做离线期间的变更，再开始监听文件变化
+pub fn start_watching(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let state = app.state::<AppState>();
+        match state.knowledge.reindex() {
+            Ok(report) if report.updated + report.removed > 0 => info!(
+                "Knowledge index updated: {} updated, {} removed",
+                report.updated, report.removed
+            ),
+            Ok(_) => {}
+            Err(e) => warn!("Failed to reindex knowledge base: {}", e),
+        }
+        match state.knowledge.backfill_chunks() {
+            Ok(0) => {}
+            Ok(documents) => info!("Chunked {} previously imported documents", documents),
+            Err(e) => warn!("Failed to chunk existing documents: {}", e),
+        }
+        if let Err(e) = state.knowledge.watch(app.clone()) {
+            warn!("Failed to watch knowledge documents: {}", e);
+        }
+        embeddings::spawn_embed_pending(&app);
+    });
+}
+
+#[tauri::command]
+pub async fn index_document(state: State<'_, AppState>, input: DocumentInput) -> Result<IndexedDocument, AppError> {
+    match input {
+        DocumentInput::Path { path } => {
+            let path = PathBuf::from(path);
+            let document = state.knowledge.index_path(&path)?;
+            state.knowledge.watch_path(&path);
+            Ok(document)
+        }
+        DocumentInput::Text { text, title } => {
+            if text.trim().is_empty() {
+                return Err(AppError::invalid("文档内容不能为空"));
+            }
+            state.knowledge.index_text(title, &text)
+        }
+    }
+}
+
+fn emit_import_progress(app: &AppHandle, progress: ImportProgress) {
+    events::publish(app, AppEvent::KnowledgeImportProgress(progress));
+}
+
+// 批量导入本地文件，未指定路径时弹出多选对话框；单个文件失败不影响其他文件
+#[tauri::command]
+pub async fn import_documents(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    paths: Option<Vec<String>>,
+) -> Result<Vec<IndexedDocument>, AppError> {
+    let paths: Vec<PathBuf> = match paths {
+        Some(paths) => paths.into_iter().map(PathBuf::from).collect(),
+        None => tauri::api::dialog::blocking::FileDialogBuilder::new()
+            .add_filter(
+                i18n::t("dialog.documents_filter"),
+                &["pdf", "docx", "md", "markdown", "txt"],
+            )
+            .pick_files()
+            .unwrap_or_default(),
+    };
+
+    let total = paths.len();
+    let mut imported = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        platform::set_progress(&app, TaskbarProgress::Normal((index * 100 / total) as u8));
+        let report = |stage: &str, progress: u8| {
+            emit_import_progress(
+                &app,
+                ImportProgress {
+                    path: path.to_string_lossy().to_string(),
+                    index,
+                    total,
+                    stage: stage.to_string(),
+                    progress,
+                },
+            )
+        };
+
+        report("extracting", 0);
+        let result = state.knowledge.import_path(
+            path,
+            // 提取占前 30%，分块写入占剩余部分
+            &|done, chunks| report("indexing", (30 + done * 70 / chunks.max(1)).min(100) as u8),
+            &|stage| report(stage, 30),
+        );
+        match result {
+            Ok(document) => {
+                report("done", 100);
+                state.knowledge.watch_path(path);
+                imported.push(document);
+            }
+            Err(e) => {
+                warn!("Failed to import {}: {}", path.display(), e);
+                report("failed", 100);
+            }
+        }
+    }
+
+    info!("Imported {} of {} documents into knowledge base", imported.len(), total);
+    if total > 0 {
+        let counts = [imported.len().to_string(), total.to_string()];
+        let body = i18n::tf("toast.import_summary", &[&counts[0], &counts[1]]);
+        if imported.len() < total {
+            platform::failed(&app, "import-documents", i18n::t("toast.import_failed"), body);
+        } else {
+            platform::finished(&app, i18n::t("toast.import_done"), body, None);
+        }
+    }
+    if !imported.is_empty() {
+        embeddings::spawn_embed_pending(&app);
+    }
+    Ok(imported)
+}
+
+#[tauri::command]
+pub fn search_knowledge(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<KnowledgeHit>, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    state.knowledge.search(query, limit.unwrap_or(DEFAULT_LIMIT))
+}
+
+#[tauri::command]
+pub fn list_knowledge_documents(state: State<'_, AppState>) -> Result<Vec<KnowledgeDocument>, AppError> {
+    state.knowledge.list()
+}
+
+#[tauri::command]
+pub fn remove_knowledge_document(state: State<'_, AppState>, id: i64) -> Result<bool, AppError> {
+    state.knowledge.remove(id)
+}
+
+#[tauri::command]
+pub async fn reindex_knowledge(state: State<'_, AppState>) -> Result<ReindexReport, AppError> {
+    state.knowledge.reindex()
+}
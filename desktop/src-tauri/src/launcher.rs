@@ -0,0 +1,439 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, Url};
+use tracing::{debug, info};
+
+use crate::error::AppError;
+
+// 已安装应用的索引在这段时间内复用，之后下次查询时重新扫描
+const INDEX_TTL: Duration = Duration::from_secs(10 * 60);
+// 匹配分数低于该值时认为没有找到
+const MIN_SCORE: u32 = 30;
+const MAX_DEPTH: usize = 4;
+const LIST_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppEntry {
+    pub name: String,
+    // 本地化名称、关键词等也参与匹配，比如 "微信" 和 "WeChat"
+    pub aliases: Vec<String>,
+    // Windows 下是开始菜单快捷方式，macOS 下是 .app，Linux 下是 .desktop 文件
+    pub path: PathBuf,
+    // .desktop 文件里的启动命令
+    #[cfg(all(unix, not(target_os = "macos")))]
+    #[serde(skip)]
+    exec: Option<String>,
+}
+
+impl AppEntry {
+    fn new(name: String, aliases: Vec<String>, path: PathBuf) -> Self {
+        Self {
+            name,
+            aliases,
+            path,
+            #[cfg(all(unix, not(target_os = "macos")))]
+            exec: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AppMatch {
+    #[serde(flatten)]
+    pub entry: AppEntry,
+    pub score: u32,
+}
+
+#[derive(Default)]
+pub struct AppLauncher {
+    index: Mutex<Option<(Instant, Vec<AppEntry>)>>,
+}
+
+impl AppLauncher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 在阻塞线程中调用
+    fn entries(&self, refresh: bool) -> Result<Vec<AppEntry>, AppError> {
+        if !refresh {
+            if let Some((at, entries)) = self.index.lock()?.as_ref() {
+                if at.elapsed() < INDEX_TTL {
+                    return Ok(entries.clone());
+                }
+            }
+        }
+        let started = Instant::now();
+        let entries = scan();
+        info!("Indexed {} applications in {:?}", entries.len(), started.elapsed());
+        *self.index.lock()? = Some((Instant::now(), entries.clone()));
+        Ok(entries)
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// 完全相同 > 前缀 > 包含 > 按顺序出现的子序列；越接近全名分数越高
+fn score_name(query: &str, name: &str) -> u32 {
+    let name = normalize(name);
+    if query.is_empty() || name.is_empty() {
+        return 0;
+    }
+    let coverage = |base: u32| base + (20 * query.chars().count() / name.chars().count().max(1)) as u32;
+    if name == query {
+        100
+    } else if name.starts_with(query) {
+        coverage(70)
+    } else if name.contains(query) {
+        coverage(50)
+    } else {
+        let mut chars = name.chars();
+        let subsequence = query.chars().all(|c| chars.any(|n| n == c));
+        if subsequence {
+            coverage(20)
+        } else {
+            0
+        }
+    }
+}
+
+fn score(query: &str, entry: &AppEntry) -> u32 {
+    std::iter::once(&entry.name)
+        .chain(&entry.aliases)
+        .map(|name| score_name(query, name))
+        .max()
+        .unwrap_or(0)
+}
+
+fn search(entries: Vec<AppEntry>, query: &str) -> Vec<AppMatch> {
+    let query = normalize(query);
+    let mut matches: Vec<AppMatch> = entries
+        .into_iter()
+        .map(|entry| AppMatch {
+            score: score(&query, &entry),
+            entry,
+        })
+        .filter(|m| m.score >= MIN_SCORE)
+        .collect();
+    matches.sort_by(|a, b| {
+        b.score
+            .cmp(&a.score)
+            .then_with(|| a.entry.name.len().cmp(&b.entry.name.len()))
+    });
+    matches
+}
+
+// 目录不存在或无权访问时跳过
+fn walk(dir: &Path, depth: usize, visit: &mut dyn FnMut(&Path) -> bool) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        // visit 返回 true 表示已作为应用处理，例如 .app 目录不再深入
+        if visit(&path) {
+            continue;
+        }
+        if depth < MAX_DEPTH && entry.file_type().is_ok_and(|t| t.is_dir()) {
+            walk(&path, depth + 1, visit);
+        }
+    }
+}
+
+fn dedup(mut entries: Vec<AppEntry>) -> Vec<AppEntry> {
+    entries.sort_by_key(|entry| entry.name.to_lowercase());
+    entries.dedup_by(|a, b| a.name.eq_ignore_ascii_case(&b.name));
+    entries
+}
+
+#[cfg(windows)]
+fn scan() -> Vec<AppEntry> {
+    let roots = ["ProgramData", "APPDATA"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .map(|base| PathBuf::from(base).join("Microsoft\\Windows\\Start Menu\\Programs"));
+    let mut entries = Vec::new();
+    for root in roots {
+        walk(&root, 0, &mut |path| {
+            let is_link = path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("lnk"));
+            let Some(name) = path.file_stem().and_then(|stem| stem.to_str()).filter(|_| is_link) else {
+                return false;
+            };
+            // 开始菜单里常见的卸载、帮助快捷方式不作为应用
+            let lower = name.to_lowercase();
+            if !["uninstall", "卸载", "readme", "help"]
+                .iter()
+                .any(|skip| lower.contains(skip))
+            {
+                entries.push(AppEntry::new(name.to_string(), Vec::new(), path.to_path_buf()));
+            }
+            true
+        });
+    }
+    dedup(entries)
+}
+
+#[cfg(target_os = "macos")]
+fn scan() -> Vec<AppEntry> {
+    let mut roots = vec![PathBuf::from("/Applications"), PathBuf::from("/System/Applications")];
+    roots.extend(tauri::api::path::home_dir().map(|home| home.join("Applications")));
+    let mut entries = Vec::new();
+    for root in roots {
+        walk(&root, 0, &mut |path| {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("app") {
+                return false;
+            }
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                entries.push(AppEntry::new(name.to_string(), bundle_names(path), path.to_path_buf()));
+            }
+            true
+        });
+    }
+    dedup(entries)
+}
+
+// Info.plist 里的显示名称，多数应用的 XML plist 可以直接按行读取
+#[cfg(target_os = "macos")]
+fn bundle_names(app: &Path) -> Vec<String> {
+    let Ok(plist) = std::fs::read_to_string(app.join("Contents/Info.plist")) else {
+        return Vec::new();
+    };
+    let mut names = Vec::new();
+    let mut lines = plist.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line == "<key>CFBundleDisplayName</key>" || line == "<key>CFBundleName</key>" {
+            if let Some(value) = lines
+                .next()
+                .and_then(|value| value.strip_prefix("<string>"))
+                .and_then(|value| value.strip_suffix("</string>"))
+            {
+                names.push(value.to_string());
+            }
+        }
+    }
+    names
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn scan() -> Vec<AppEntry> {
+    let mut roots: Vec<PathBuf> = std::env::var("XDG_DATA_DIRS")
+        .unwrap_or_else(|_| "/usr/local/share:/usr/share".to_string())
+        .split(':')
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| Path::new(dir).join("applications"))
+        .collect();
+    roots.extend(tauri::api::path::data_dir().map(|dir| dir.join("applications")));
+    roots.push(PathBuf::from("/var/lib/flatpak/exports/share/applications"));
+    let mut entries = Vec::new();
+    for root in roots {
+        walk(&root, 0, &mut |path| {
+            if path.extension().and_then(|ext| ext.to_str()) != Some("desktop") {
+                return false;
+            }
+            entries.extend(parse_desktop_file(path));
+            true
+        });
+    }
+    dedup(entries)
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn parse_desktop_file(path: &Path) -> Option<AppEntry> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let mut in_entry = false;
+    let mut name = None;
+    let mut aliases = Vec::new();
+    let mut exec = None;
+    for line in content.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_entry = line == "[Desktop Entry]";
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=').filter(|_| in_entry) else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "Type" if value != "Application" => return None,
+            "NoDisplay" | "Hidden" if value == "true" => return None,
+            "Name" => name = Some(value.to_string()),
+            "Exec" => exec = Some(value.to_string()),
+            "GenericName" => aliases.push(value.to_string()),
+            "Keywords" => aliases.extend(value.split(';').filter(|k| !k.is_empty()).map(str::to_string)),
+            key if key.starts_with("Name[") || key.starts_with("GenericName[") => aliases.push(value.to_string()),
+            _ => {}
+        }
+    }
+    Some(AppEntry {
+        exec: Some(exec?),
+        ..AppEntry::new(name?, aliases, path.to_path_buf())
+    })
+}
+
+// 去掉 %f %U 等字段代码后按空白和引号拆分 Exec
+#[cfg(all(unix, not(target_os = "macos")))]
+fn exec_args(exec: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut chars = exec.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => quoted = !quoted,
+            '\\' if quoted => current.extend(chars.next()),
+            '%' => {
+                if chars.next() == Some('%') {
+                    current.push('%');
+                }
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    args.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        args.push(current);
+    }
+    args
+}
+
+#[cfg(windows)]
+pub fn open_native(target: &str) -> Result<(), AppError> {
+    use windows_sys::Win32::UI::Shell::ShellExecuteW;
+    use windows_sys::Win32::UI::WindowsAndMessaging::SW_SHOWNORMAL;
+
+    let wide = |text: &str| text.encode_utf16().chain(std::iter::once(0)).collect::<Vec<u16>>();
+    let operation = wide("open");
+    let file = wide(target);
+    // SAFETY: 字符串以 NUL 结尾且在调用期间有效
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            operation.as_ptr(),
+            file.as_ptr(),
+            std::ptr::null(),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // 返回值大于 32 表示成功
+    if result as isize <= 32 {
+        return Err(AppError::unavailable(
+            format!("无法打开: {}", target),
+            std::io::Error::last_os_error(),
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+pub fn open_native(target: &str) -> Result<(), AppError> {
+    let program = if cfg!(target_os = "macos") { "open" } else { "xdg-open" };
+    let status = std::process::Command::new(program)
+        .arg(target)
+        .status()
+        .map_err(|e| AppError::unavailable(format!("无法打开: {}", target), e))?;
+    if !status.success() {
+        return Err(AppError::unavailable(format!("无法打开: {}", target), status));
+    }
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+fn launch_entry(entry: &AppEntry) -> Result<(), AppError> {
+    let args = entry.exec.as_deref().map(exec_args).unwrap_or_default();
+    let Some((program, args)) = args.split_first() else {
+        return open_native(&entry.path.to_string_lossy());
+    };
+    let mut child = std::process::Command::new(program)
+        .args(args)
+        .spawn()
+        .map_err(|e| AppError::unavailable(format!("无法打开: {}", entry.name), e))?;
+    // 回收子进程，避免退出后留下僵尸进程
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+fn launch_entry(entry: &AppEntry) -> Result<(), AppError> {
+    open_native(&entry.path.to_string_lossy())
+}
+
+// name_or_path 可以是可执行文件、快捷方式的路径，也可以是应用名称（模糊匹配）
+#[tauri::command]
+pub async fn launch_app(app: AppHandle, name_or_path: String) -> Result<AppEntry, AppError> {
+    let query = name_or_path.trim().to_string();
+    if query.is_empty() {
+        return Err(AppError::invalid("需要指定应用名称"));
+    }
+    tauri::async_runtime::spawn_blocking(move || {
+        let path = Path::new(&query);
+        if path.is_absolute() && path.exists() {
+            open_native(&query)?;
+            info!("Launched {}", query);
+            let name = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            return Ok(AppEntry::new(name, Vec::new(), path.to_path_buf()));
+        }
+        let entries = app.state::<AppLauncher>().entries(false)?;
+        let best = search(entries, &query)
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound(format!("找不到应用: {}", query)))?;
+        debug!("Matched {} to {} (score {})", query, best.entry.name, best.score);
+        launch_entry(&best.entry)?;
+        info!("Launched {}", best.entry.name);
+        Ok(best.entry)
+    })
+    .await?
+}
+
+// 用系统默认程序打开文件或文件夹
+#[tauri::command]
+pub async fn open_path(path: String) -> Result<(), AppError> {
+    let target = PathBuf::from(path.trim());
+    if !target.exists() {
+        return Err(AppError::NotFound(format!("路径不存在: {}", target.display())));
+    }
+    tauri::async_runtime::spawn_blocking(move || open_native(&target.to_string_lossy())).await?
+}
+
+// 只允许网页和邮件链接，其他协议可能会启动任意程序
+#[tauri::command]
+pub async fn open_url(url: String) -> Result<(), AppError> {
+    let url = Url::parse(url.trim()).map_err(|e| AppError::invalid(format!("链接无效: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https" | "mailto") {
+        return Err(AppError::invalid(format!("不支持的链接协议: {}", url.scheme())));
+    }
+    tauri::async_runtime::spawn_blocking(move || open_native(url.as_str())).await?
+}
+
+// 不带 query 时返回全部已安装应用，refresh 为 true 时重新扫描
+#[tauri::command]
+pub async fn list_apps(
+    app: AppHandle,
+    query: Option<String>,
+    refresh: Option<bool>,
+) -> Result<Vec<AppMatch>, AppError> {
+    let entries =
+        tauri::async_runtime::spawn_blocking(move || app.state::<AppLauncher>().entries(refresh.unwrap_or(false)))
+            .await??;
+    Ok(
+        match query.as_deref().map(str::trim).filter(|query| !query.is_empty()) {
+            Some(query) => search(entries, query).into_iter().take(LIST_LIMIT).collect(),
+            None => entries.into_iter().map(|entry| AppMatch { entry, score: 0 }).collect(),
+        },
+    )
+}
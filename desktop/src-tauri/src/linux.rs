@@ -0,0 +1,292 @@
+use serde::Serialize;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, PhysicalPosition, Window};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::pet;
+use crate::screenshot::{self, CaptureOutput, Screenshot};
+
+// 隐藏桌宠后等待合成器重绘再截取背景
+const BACKDROP_DELAY: Duration = Duration::from_millis(120);
+
+// GTK 实际使用的显示后端；Wayland 会话中通过 XWayland 运行时为 x11
+// 只有 Linux 会检测到 X11 或 Wayland
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionType {
+    X11,
+    Wayland,
+    // Windows、macOS 或无法识别
+    Other,
+}
+
+#[cfg_attr(not(all(unix, not(target_os = "macos"))), allow(dead_code))]
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Positioning {
+    // 可以按屏幕坐标移动窗口
+    Absolute,
+    // Wayland 下通过 layer-shell 锚定到显示器左上角再用边距定位
+    Anchored,
+    // Wayland 下普通窗口不能自行定位，只能由合成器决定位置
+    Unsupported,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct DisplayCapabilities {
+    pub session: SessionType,
+    // 没有合成器时透明窗口会显示黑底，桌宠改用矩形窗口并以截取的背景模拟透明
+    pub transparent: bool,
+    pub positioning: Positioning,
+}
+
+impl Default for DisplayCapabilities {
+    fn default() -> Self {
+        Self {
+            session: SessionType::Other,
+            transparent: true,
+            positioning: Positioning::Absolute,
+        }
+    }
+}
+
+static CAPABILITIES: OnceLock<DisplayCapabilities> = OnceLock::new();
+// layer-shell 窗口取不到自身位置，按窗口标签记录最后一次设置的坐标
+static ANCHORED_POSITIONS: Mutex<Vec<(String, PhysicalPosition<i32>)>> = Mutex::new(Vec::new());
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod native {
+    use gtk::prelude::*;
+
+    use super::{DisplayCapabilities, Positioning, SessionType};
+
+    pub fn detect() -> DisplayCapabilities {
+        let backend = gtk::gdk::Display::default().map(|display| display.type_().name().to_string());
+        let session = match backend.as_deref() {
+            Some("GdkWaylandDisplay") => SessionType::Wayland,
+            Some("GdkX11Display") => SessionType::X11,
+            _ if std::env::var_os("WAYLAND_DISPLAY").is_some() => SessionType::Wayland,
+            _ if std::env::var_os("DISPLAY").is_some() => SessionType::X11,
+            _ => SessionType::Other,
+        };
+        let transparent = match session {
+            // Wayland 合成器总是支持透明
+            SessionType::Wayland => true,
+            _ => gtk::gdk::Screen::default().is_some_and(|screen| screen.is_composited()),
+        };
+        let positioning = match session {
+            SessionType::Wayland if layer_shell_supported() => Positioning::Anchored,
+            SessionType::Wayland => Positioning::Unsupported,
+            _ => Positioning::Absolute,
+        };
+        DisplayCapabilities {
+            session,
+            transparent,
+            positioning,
+        }
+    }
+
+    #[cfg(feature = "layer-shell")]
+    fn layer_shell_supported() -> bool {
+        gtk_layer_shell::is_supported()
+    }
+
+    #[cfg(not(feature = "layer-shell"))]
+    fn layer_shell_supported() -> bool {
+        false
+    }
+
+    // 必须在窗口 realize 之前初始化，Tauri 创建窗口时已经 realize，先撤销再初始化
+    #[cfg(feature = "layer-shell")]
+    pub fn init_layer(window: &gtk::ApplicationWindow) {
+        use gtk_layer_shell::{Edge, Layer};
+
+        let visible = window.is_visible();
+        window.hide();
+        if window.is_realized() {
+            window.unrealize();
+        }
+        gtk_layer_shell::init_for_window(window);
+        gtk_layer_shell::set_layer(window, Layer::Overlay);
+        gtk_layer_shell::set_namespace(window, "lingecho-pet");
+        gtk_layer_shell::set_keyboard_interactivity(window, false);
+        gtk_layer_shell::set_anchor(window, Edge::Top, true);
+        gtk_layer_shell::set_anchor(window, Edge::Left, true);
+        if visible {
+            window.show();
+        }
+    }
+
+    // x、y 为 GDK 的全局逻辑坐标，换算成所在显示器的边距
+    #[cfg(feature = "layer-shell")]
+    pub fn anchor(window: &gtk::ApplicationWindow, x: i32, y: i32) {
+        use gtk_layer_shell::Edge;
+
+        let Some(monitor) = gtk::gdk::Display::default().and_then(|display| display.monitor_at_point(x, y)) else {
+            return;
+        };
+        let geometry = monitor.geometry();
+        gtk_layer_shell::set_monitor(window, &monitor);
+        gtk_layer_shell::set_margin(window, Edge::Left, x - geometry.x());
+        gtk_layer_shell::set_margin(window, Edge::Top, y - geometry.y());
+    }
+
+    #[cfg(not(feature = "layer-shell"))]
+    pub fn init_layer(_window: &gtk::ApplicationWindow) {}
+
+    #[cfg(not(feature = "layer-shell"))]
+    pub fn anchor(_window: &gtk::ApplicationWindow, _x: i32, _y: i32) {}
+
+    // GDK 给出的是全局逻辑坐标，按所在显示器的缩放比例换算成物理像素
+    pub fn cursor_position() -> Option<(i32, i32)> {
+        let display = gtk::gdk::Display::default()?;
+        let (_, x, y) = display.default_seat()?.pointer()?.position();
+        let scale = display.monitor_at_point(x, y)?.scale_factor();
+        Some((x * scale, y * scale))
+    }
+}
+
+// 在 setup 中调用（主线程）：检测显示后端、合成器和窗口定位方式
+pub fn detect() {
+    #[cfg(all(unix, not(target_os = "macos")))]
+    let capabilities = native::detect();
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let capabilities = DisplayCapabilities::default();
+
+    if capabilities.session != SessionType::Other {
+        info!(
+            "Display session {:?}, transparent: {}, positioning: {:?}",
+            capabilities.session, capabilities.transparent, capabilities.positioning
+        );
+    }
+    if capabilities.positioning == Positioning::Unsupported {
+        warn!("Window positioning is not supported in this Wayland session, the pet will stay where the compositor places it");
+    }
+    CAPABILITIES.set(capabilities).ok();
+}
+
+pub fn capabilities() -> DisplayCapabilities {
+    CAPABILITIES.get().copied().unwrap_or_default()
+}
+
+fn unsupported() -> AppError {
+    AppError::Unavailable {
+        message: "当前桌面环境不支持移动窗口".to_string(),
+        details: Some("Wayland".to_string()),
+    }
+}
+
+// 桌宠窗口创建后调用，需要时把窗口转换为 layer-shell 窗口
+pub fn prepare_pet(window: &Window) {
+    if capabilities().positioning != Positioning::Anchored {
+        return;
+    }
+    #[cfg(all(unix, not(target_os = "macos")))]
+    {
+        let target = window.clone();
+        let result = window.run_on_main_thread(move || match target.gtk_window() {
+            Ok(gtk_window) => native::init_layer(&gtk_window),
+            Err(e) => warn!("Failed to get GTK window: {}", e),
+        });
+        if let Err(e) = result {
+            warn!("Failed to initialize layer shell: {}", e);
+        }
+    }
+    #[cfg(not(all(unix, not(target_os = "macos"))))]
+    let _ = window;
+}
+
+// 按物理像素坐标移动窗口，Wayland 下不能定位时返回错误而不是静默忽略
+pub fn move_window(window: &Window, position: PhysicalPosition<i32>) -> Result<(), AppError> {
+    match capabilities().positioning {
+        Positioning::Absolute => window.set_position(position).map_err(AppError::from),
+        Positioning::Unsupported => Err(unsupported()),
+        Positioning::Anchored => {
+            let logical = position.to_logical::<i32>(window.scale_factor()?);
+            {
+                let mut positions = ANCHORED_POSITIONS.lock()?;
+                positions.retain(|(label, _)| label != window.label());
+                positions.push((window.label().to_string(), position));
+            }
+            #[cfg(all(unix, not(target_os = "macos")))]
+            {
+                let target = window.clone();
+                window.run_on_main_thread(move || match target.gtk_window() {
+                    Ok(gtk_window) => native::anchor(&gtk_window, logical.x, logical.y),
+                    Err(e) => warn!("Failed to get GTK window: {}", e),
+                })?;
+            }
+            #[cfg(not(all(unix, not(target_os = "macos"))))]
+            let _ = logical;
+            Ok(())
+        }
+    }
+}
+
+pub fn window_position(window: &Window) -> Result<PhysicalPosition<i32>, AppError> {
+    if capabilities().positioning == Positioning::Anchored {
+        let positions = ANCHORED_POSITIONS.lock()?;
+        if let Some((_, position)) = positions.iter().find(|(label, _)| label == window.label()) {
+            return Ok(*position);
+        }
+    }
+    window.outer_position().map_err(AppError::from)
+}
+
+// 光标的屏幕坐标（物理像素）；Wayland 不提供全局光标位置，返回 None
+#[cfg(all(unix, not(target_os = "macos")))]
+pub async fn cursor_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    if capabilities().session == SessionType::Wayland {
+        return None;
+    }
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    if let Err(e) = app.run_on_main_thread(move || {
+        sender.send(native::cursor_position()).ok();
+    }) {
+        warn!("Failed to read cursor position: {}", e);
+        return None;
+    }
+    receiver.await.ok().flatten().map(|(x, y)| PhysicalPosition::new(x, y))
+}
+
+#[tauri::command]
+pub fn get_display_capabilities() -> DisplayCapabilities {
+    capabilities()
+}
+
+// 没有合成器时由前端把返回的截图铺在桌宠背后，模拟透明效果；桌宠移动后需要重新获取
+#[tauri::command]
+pub async fn capture_pet_backdrop(app: AppHandle) -> Result<Screenshot, AppError> {
+    let window = pet::pet_window(&app)?;
+    let position = window_position(&window)?;
+    let size = window.outer_size()?;
+
+    let visible = window.is_visible()?;
+    if visible {
+        window.hide()?;
+        tokio::time::sleep(BACKDROP_DELAY).await;
+    }
+    let captured = tauri::async_runtime::spawn_blocking(move || {
+        let monitor =
+            xcap::Monitor::from_point(position.x, position.y).map_err(|e| AppError::unavailable("无法访问屏幕", e))?;
+        let origin_x = monitor.x().unwrap_or_default();
+        let origin_y = monitor.y().unwrap_or_default();
+        let x = (position.x - origin_x).max(0) as u32;
+        let y = (position.y - origin_y).max(0) as u32;
+        let width = size.width.min(monitor.width().unwrap_or(size.width).saturating_sub(x));
+        let height = size
+            .height
+            .min(monitor.height().unwrap_or(size.height).saturating_sub(y));
+        monitor
+            .capture_region(x, y, width, height)
+            .map_err(|e| AppError::Internal(format!("截图失败: {}", e)))
+    })
+    .await;
+    if visible {
+        window.show()?;
+    }
+    screenshot::encode(captured??, CaptureOutput::Base64)
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::{ChatMessage, ChatModel, LlmProvider};
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::network::{self, ClientConfig};
+use crate::storage::Role;
+use crate::AppState;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+// 后端一句话接口使用的用户凭证和会话参数
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct BackendCredentials {
+    pub api_key: String,
+    pub api_secret: String,
+    pub assistant_id: Option<i64>,
+    pub session_id: Option<String>,
+    pub language: Option<String>,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OneShotRequest<'a> {
+    api_key: &'a str,
+    api_secret: &'a str,
+    text: &'a str,
+    assistant_id: i64,
+    language: &'a str,
+    session_id: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct OneShotResponse {
+    code: i32,
+    #[serde(default)]
+    msg: String,
+    data: Option<OneShotData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OneShotData {
+    #[serde(default)]
+    text: String,
+}
+
+pub struct BackendChatModel {
+    url: String,
+    credentials: BackendCredentials,
+    network: ClientConfig,
+}
+
+impl BackendChatModel {
+    pub fn new(app: &AppHandle, credentials: BackendCredentials) -> Self {
+        let path = app
+            .state::<AppState>()
+            .settings
+            .lock()
+            .map(|settings| settings.pipeline.llm_path.clone())
+            .unwrap_or_default();
+        Self {
+            url: format!("{}{}", app.state::<BackendManager>().url(), path),
+            credentials,
+            network: network::config(app),
+        }
+    }
+}
+
+impl ChatModel for BackendChatModel {
+    fn provider(&self) -> LlmProvider {
+        LlmProvider::Backend
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    // 后端按会话保存上下文，只发送最后一条用户消息；回答一次返回，整体作为一段文本推送
+    fn generate(&self, messages: &[ChatMessage], on_token: &mut dyn FnMut(&str) -> bool) -> Result<String, AppError> {
+        let text = messages
+            .iter()
+            .rev()
+            .find(|message| message.role == Role::User)
+            .map(|message| message.content.as_str())
+            .ok_or_else(|| AppError::invalid("对话内容为空"))?;
+        let credentials = &self.credentials;
+        let body = OneShotRequest {
+            api_key: &credentials.api_key,
+            api_secret: &credentials.api_secret,
+            text,
+            assistant_id: credentials.assistant_id.unwrap_or_default(),
+            language: credentials.language.as_deref().unwrap_or_default(),
+            session_id: credentials.session_id.as_deref().unwrap_or_default(),
+        };
+        let client = self.network.blocking_client(REQUEST_TIMEOUT)?;
+        let mut request = client.post(&self.url).json(&body);
+        if let Some(token) = credentials.token.as_deref() {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().map_err(|e| AppError::network("无法连接对话服务", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("对话服务返回错误", response.status()));
+        }
+        let body: OneShotResponse = response.json()?;
+        if body.code != 200 {
+            return Err(AppError::unavailable("对话服务返回错误", body.msg));
+        }
+        let reply = body.data.map(|data| data.text).unwrap_or_default();
+        if reply.trim().is_empty() {
+            return Err(AppError::Internal("对话服务没有返回回答".to_string()));
+        }
+        on_token(&reply);
+        Ok(reply)
+    }
+}
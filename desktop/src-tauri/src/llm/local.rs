@@ -0,0 +1,427 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tracing::{debug, info, warn};
+
+use super::{ChatMessage, ChatModel, LlmProvider};
+use crate::acceleration::{self, AccelBackend, Engine};
+use crate::error::AppError;
+use crate::storage::Role;
+
+pub const MODELS_DIR: &str = "models/llm";
+// 随应用打包的 llama.cpp 服务程序，见 src-tauri/llama/README.md
+const BUNDLED_DIR: &str = "llama";
+const DEFAULT_COMMAND: &str = "llama-server";
+
+// 加载大模型可能需要较长时间
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(120);
+const HEALTH_INTERVAL: Duration = Duration::from_millis(250);
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LocalLlmSettings {
+    // llama.cpp 的 llama-server 程序；保持默认时使用随应用打包的版本，没有打包时在 PATH 中查找
+    pub command: String,
+    // 模型名称（对应应用目录下的 <name>.gguf）或 GGUF 模型文件的完整路径
+    pub model: Option<String>,
+    // 上下文长度（token），超出时丢弃最早的消息
+    pub context_length: u32,
+    // 单次回答最多生成的 token 数
+    pub max_tokens: u32,
+    pub temperature: f32,
+    // 推理线程数，0 表示自动选择
+    pub threads: u32,
+}
+
+impl Default for LocalLlmSettings {
+    fn default() -> Self {
+        Self {
+            command: DEFAULT_COMMAND.to_string(),
+            model: None,
+            context_length: 4096,
+            max_tokens: 1024,
+            temperature: 0.7,
+            threads: 0,
+        }
+    }
+}
+
+fn resolve_command(app: &AppHandle, command: &str) -> String {
+    let command = command.trim();
+    if !command.is_empty() && command != DEFAULT_COMMAND {
+        return command.to_string();
+    }
+    crate::backend::bundled_binary(app, BUNDLED_DIR, DEFAULT_COMMAND)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| DEFAULT_COMMAND.to_string())
+}
+
+pub fn model_path(app: &AppHandle, model: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(model);
+    if path.is_absolute() {
+        return Some(path);
+    }
+    let dir = app.path_resolver().app_data_dir()?.join(MODELS_DIR);
+    Some(dir.join(format!("{}.gguf", model)))
+}
+
+// 启动参数不变时复用已经加载好模型的进程
+#[derive(Debug, Clone, PartialEq)]
+struct Launch {
+    command: String,
+    model: PathBuf,
+    context_length: u32,
+    threads: usize,
+    gpu: bool,
+}
+
+struct Running {
+    child: Child,
+    port: u16,
+    launch: Launch,
+    // stderr 的最后一行，进程意外退出时作为错误信息
+    last_line: Arc<Mutex<String>>,
+    // 模型是否已加载完成
+    ready: bool,
+}
+
+// 按需启动的 llama-server 进程，只监听本机端口
+#[derive(Default)]
+pub struct LlamaServer {
+    running: Mutex<Option<Running>>,
+}
+
+fn free_port() -> Result<u16, AppError> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    Ok(listener.local_addr()?.port())
+}
+
+impl LlamaServer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn pid(&self) -> Option<u32> {
+        let mut running = self.running.lock().ok()?;
+        let current = running.as_mut()?;
+        matches!(current.child.try_wait(), Ok(None)).then(|| current.child.id())
+    }
+
+    // 返回可用的端口；参数变化或进程已退出时重新启动，模型加载完成后才返回
+    // 进程启动后即登记并释放锁，等待加载期间 stop() 可以随时结束进程
+    fn ensure(&self, launch: &Launch) -> Result<u16, AppError> {
+        let pid = {
+            let mut running = self.running.lock()?;
+            let reusable = running.as_mut().and_then(|current| {
+                let alive = matches!(current.child.try_wait(), Ok(None));
+                (alive && current.launch == *launch).then_some(current)
+            });
+            match reusable {
+                Some(current) if current.ready => return Ok(current.port),
+                // 另一个调用正在等待同一个进程加载模型，一起等待
+                Some(current) => current.child.id(),
+                None => {
+                    if let Some(previous) = running.take() {
+                        stop(previous);
+                    }
+                    let current = spawn(launch)?;
+                    let pid = current.child.id();
+                    *running = Some(current);
+                    pid
+                }
+            }
+        };
+        self.wait_ready(launch, pid)
+    }
+
+    // 在锁内访问仍登记为 pid 的进程；已被 stop() 结束或替换时返回 Cancelled
+    fn current_state<T>(&self, pid: u32, read: impl FnOnce(&mut Running) -> T) -> Result<T, AppError> {
+        let mut running = self.running.lock()?;
+        match running.as_mut().filter(|current| current.child.id() == pid) {
+            Some(current) => Ok(read(current)),
+            None => Err(AppError::Cancelled("本地模型服务已停止".to_string())),
+        }
+    }
+
+    // /health 在模型加载期间返回 503，加载完成后返回 200
+    fn wait_ready(&self, launch: &Launch, pid: u32) -> Result<u16, AppError> {
+        let client = reqwest::blocking::Client::builder()
+            .no_proxy()
+            .timeout(Duration::from_secs(2))
+            .build()?;
+        let started = Instant::now();
+        loop {
+            let (port, ready, exited) = self.current_state(pid, |current| {
+                let exited = match current.child.try_wait() {
+                    Ok(Some(status)) => {
+                        let detail = current.last_line.lock().map(|line| line.clone()).unwrap_or_default();
+                        Some((status, detail))
+                    }
+                    _ => None,
+                };
+                (current.port, current.ready, exited)
+            })?;
+            if let Some((status, detail)) = exited {
+                self.discard(pid);
+                return Err(AppError::unavailable(
+                    format!("本地模型服务已退出（{}）", status),
+                    detail.trim(),
+                ));
+            }
+            // 一起等待的其他调用已确认加载完成
+            if ready {
+                return Ok(port);
+            }
+            let health = client.get(format!("http://127.0.0.1:{}/health", port)).send();
+            if health.is_ok_and(|response| response.status().is_success()) {
+                self.current_state(pid, |current| current.ready = true)?;
+                info!(
+                    "Local model {} ready in {} ms",
+                    launch.model.display(),
+                    started.elapsed().as_millis()
+                );
+                return Ok(port);
+            }
+            if started.elapsed() >= STARTUP_TIMEOUT {
+                self.discard(pid);
+                return Err(AppError::unavailable("本地模型加载超时", launch.model.display()));
+            }
+            std::thread::sleep(HEALTH_INTERVAL);
+        }
+    }
+
+    // 只结束仍登记为当前进程的那一个
+    fn discard(&self, pid: u32) {
+        let previous = self.running.lock().ok().and_then(|mut running| {
+            running
+                .as_ref()
+                .is_some_and(|current| current.child.id() == pid)
+                .then(|| running.take())
+                .flatten()
+        });
+        if let Some(previous) = previous {
+            stop(previous);
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(running) = self.running.lock().ok().and_then(|mut running| running.take()) {
+            stop(running);
+        }
+    }
+}
+
+fn spawn(launch: &Launch) -> Result<Running, AppError> {
+    let port = free_port()?;
+    let mut command = Command::new(&launch.command);
+    command
+        .arg("-m")
+        .arg(&launch.model)
+        .arg("-c")
+        .arg(launch.context_length.to_string())
+        .args(["--host", "127.0.0.1", "--port", &port.to_string()])
+        .args(["-ngl", if launch.gpu { "999" } else { "0" }])
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped());
+    if launch.threads > 0 {
+        command.arg("-t").arg(launch.threads.to_string());
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(crate::backend::CREATE_NO_WINDOW);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| AppError::unavailable(format!("无法启动本地模型服务 {}", launch.command), e))?;
+    info!("Started llama-server (pid {}) on port {}", child.id(), port);
+
+    // 单独读取 stderr，避免管道写满阻塞子进程
+    let last_line = Arc::new(Mutex::new(String::new()));
+    if let Some(stderr) = child.stderr.take() {
+        let last_line = last_line.clone();
+        std::thread::spawn(move || {
+            for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                debug!(target: "llama_server", "{}", line);
+                if let Ok(mut last) = last_line.lock() {
+                    *last = line;
+                }
+            }
+        });
+    }
+    Ok(Running {
+        child,
+        port,
+        launch: launch.clone(),
+        last_line,
+        ready: false,
+    })
+}
+
+fn stop(mut running: Running) {
+    let pid = running.child.id();
+    if let Err(e) = running.child.kill() {
+        warn!("Failed to stop llama-server (pid {}): {}", pid, e);
+    }
+    running.child.wait().ok();
+    info!("Stopped llama-server (pid {})", pid);
+}
+
+#[derive(Debug, Serialize)]
+struct CompletionRequest<'a> {
+    messages: &'a [ChatMessage],
+    stream: bool,
+    max_tokens: u32,
+    temperature: f32,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
+// 粗略估算 token 数：中文大约一字一个 token，其他文字按四个字符一个 token
+fn estimate_tokens(text: &str) -> usize {
+    let (wide, narrow) = text.chars().fold((0, 0), |(wide, narrow), c| {
+        if c.is_ascii() {
+            (wide, narrow + 1)
+        } else {
+            (wide + 1, narrow)
+        }
+    });
+    wide + narrow / 4 + 1
+}
+
+// 保留系统提示和最新的消息，丢弃放不进上下文的早期消息
+fn fit_context(messages: &[ChatMessage], budget: usize) -> Vec<ChatMessage> {
+    let (system, rest): (Vec<&ChatMessage>, Vec<&ChatMessage>) =
+        messages.iter().partition(|message| message.role == Role::System);
+    let mut used: usize = system.iter().map(|message| estimate_tokens(&message.content)).sum();
+    let mut kept = Vec::new();
+    for message in rest.into_iter().rev() {
+        let tokens = estimate_tokens(&message.content);
+        if !kept.is_empty() && used + tokens > budget {
+            break;
+        }
+        used += tokens;
+        kept.push(message.clone());
+    }
+    kept.reverse();
+    system.into_iter().cloned().chain(kept).collect()
+}
+
+pub struct LocalChatModel {
+    app: AppHandle,
+    settings: LocalLlmSettings,
+    model: Option<PathBuf>,
+    backend: AccelBackend,
+}
+
+impl LocalChatModel {
+    pub fn new(app: &AppHandle, settings: LocalLlmSettings) -> Self {
+        let model = settings.model.as_deref().and_then(|model| model_path(app, model));
+        let backend = acceleration::select(app, Engine::Llm);
+        Self {
+            app: app.clone(),
+            settings,
+            model,
+            backend,
+        }
+    }
+}
+
+impl ChatModel for LocalChatModel {
+    fn provider(&self) -> LlmProvider {
+        LlmProvider::Local
+    }
+
+    fn is_available(&self) -> bool {
+        self.model.as_ref().is_some_and(|model| model.exists())
+    }
+
+    // 使用 llama-server 的 OpenAI 兼容接口，按 SSE 逐段读取生成结果
+    fn generate(&self, messages: &[ChatMessage], on_token: &mut dyn FnMut(&str) -> bool) -> Result<String, AppError> {
+        let model = self
+            .model
+            .clone()
+            .filter(|model| model.exists())
+            .ok_or_else(|| AppError::NotFound("未找到本地模型，请先在设置中选择 GGUF 模型文件".to_string()))?;
+        let threads = match (self.backend, self.settings.threads) {
+            (AccelBackend::Cpu, 0) => acceleration::cpu_threads(),
+            (_, threads) => threads as usize,
+        };
+        let launch = Launch {
+            command: resolve_command(&self.app, &self.settings.command),
+            model,
+            context_length: self.settings.context_length,
+            threads,
+            gpu: self.backend != AccelBackend::Cpu,
+        };
+        let port = self.app.state::<LlamaServer>().ensure(&launch)?;
+
+        let budget = (self.settings.context_length.saturating_sub(self.settings.max_tokens) as usize).max(256);
+        let messages = fit_context(messages, budget);
+        let body = CompletionRequest {
+            messages: &messages,
+            stream: true,
+            max_tokens: self.settings.max_tokens,
+            temperature: self.settings.temperature,
+        };
+        // 本机进程不经过代理，与健康检查一致
+        let client = reqwest::blocking::Client::builder()
+            .no_proxy()
+            .timeout(GENERATION_TIMEOUT)
+            .build()?;
+        let response = client
+            .post(format!("http://127.0.0.1:{}/v1/chat/completions", port))
+            .json(&body)
+            .send()
+            .map_err(|e| AppError::network("无法连接本地模型服务", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("本地模型返回错误", response.status()));
+        }
+
+        // 提前返回时丢弃响应即断开连接，llama-server 随之停止生成
+        let mut text = String::new();
+        for line in BufReader::new(response).lines() {
+            let line = line?;
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                continue;
+            };
+            if data == "[DONE]" {
+                break;
+            }
+            let chunk: StreamChunk = serde_json::from_str(data)?;
+            let Some(token) = chunk.choices.into_iter().next().and_then(|choice| choice.delta.content) else {
+                continue;
+            };
+            if token.is_empty() {
+                continue;
+            }
+            text.push_str(&token);
+            if !on_token(&token) {
+                debug!("Local generation stopped after {} chars", text.chars().count());
+                break;
+            }
+        }
+        Ok(text)
+    }
+}
@@ -0,0 +1,302 @@
+// 大语言模型：统一的 ChatModel 接口，可在后端服务和本地 llama.cpp 之间切换
+pub mod backend;
+pub mod local;
+pub mod stream;
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::offline;
+use crate::storage::Role;
+use crate::{settings, AppState};
+
+pub use backend::{BackendChatModel, BackendCredentials};
+pub use local::{LlamaServer, LocalChatModel, LocalLlmSettings};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProvider {
+    // Go 后端的一句话文本接口
+    #[default]
+    Backend,
+    // 本地 llama.cpp，完全离线
+    Local,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LlmSettings {
+    pub provider: LlmProvider,
+    // 后端不可用时改用本地模型（需要已配置模型）
+    pub fallback_to_local: bool,
+    // 按对话单独指定的模型来源，覆盖 provider
+    pub conversations: HashMap<String, LlmProvider>,
+    pub local: LocalLlmSettings,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        Self {
+            provider: LlmProvider::Backend,
+            fallback_to_local: true,
+            conversations: HashMap::new(),
+            local: LocalLlmSettings::default(),
+        }
+    }
+}
+
+impl LlmSettings {
+    pub fn provider_for(&self, conversation_id: Option<&str>) -> LlmProvider {
+        conversation_id
+            .and_then(|id| self.conversations.get(id).copied())
+            .unwrap_or(self.provider)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: Role,
+    pub content: String,
+}
+
+impl ChatMessage {
+    pub fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: Role::User,
+            content: content.into(),
+        }
+    }
+}
+
+// 生成过程中推送的增量文本，id 对应一次生成
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatToken {
+    pub id: u64,
+    pub conversation_id: Option<String>,
+    pub token: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Generation {
+    pub id: u64,
+    pub text: String,
+    pub provider: LlmProvider,
+    pub elapsed_ms: u64,
+    // 被 cancel_generation 中断时为 true，text 为中断前已生成的部分
+    pub cancelled: bool,
+}
+
+pub trait ChatModel: Send + Sync {
+    fn provider(&self) -> LlmProvider;
+
+    // 可以离线生成时返回 true，用于后端不可用时的降级判断
+    fn is_available(&self) -> bool;
+
+    // 在阻塞线程中调用；每生成一段文本调用一次 on_token，返回 false 时停止生成
+    fn generate(&self, messages: &[ChatMessage], on_token: &mut dyn FnMut(&str) -> bool) -> Result<String, AppError>;
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn next_id() -> u64 {
+    NEXT_ID.fetch_add(1, Ordering::SeqCst)
+}
+
+// 进行中的生成及其取消标记
+#[derive(Default)]
+pub struct Generations {
+    active: Mutex<HashMap<u64, Arc<AtomicBool>>>,
+}
+
+impl Generations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 可以在生成开始前调用，保证开始前到达的取消请求不会丢失
+    pub fn register(&self, id: u64) -> Arc<AtomicBool> {
+        match self.active.lock() {
+            Ok(mut active) => active.entry(id).or_default().clone(),
+            Err(_) => Arc::default(),
+        }
+    }
+
+    fn finish(&self, id: u64) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(&id);
+        }
+    }
+
+    pub fn cancel(&self, id: u64) -> bool {
+        let flag = self.active.lock().ok().and_then(|active| active.get(&id).cloned());
+        match flag {
+            Some(flag) => !flag.swap(true, Ordering::SeqCst),
+            None => false,
+        }
+    }
+
+    pub fn cancel_all(&self) {
+        if let Ok(active) = self.active.lock() {
+            for flag in active.values() {
+                flag.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+}
+
+pub fn model(
+    app: &AppHandle,
+    settings: &LlmSettings,
+    provider: LlmProvider,
+    credentials: BackendCredentials,
+) -> Box<dyn ChatModel> {
+    match provider {
+        LlmProvider::Backend => Box::new(BackendChatModel::new(app, credentials)),
+        LlmProvider::Local => Box::new(LocalChatModel::new(app, settings.local.clone())),
+    }
+}
+
+// 在阻塞线程中调用；生成期间可以通过 cancel_generation 中断，temperature 覆盖本地模型的设置
+pub fn generate(
+    app: &AppHandle,
+    id: u64,
+    conversation_id: Option<&str>,
+    messages: &[ChatMessage],
+    credentials: BackendCredentials,
+    temperature: Option<f32>,
+    on_token: &mut dyn FnMut(&str) -> bool,
+) -> Result<Generation, AppError> {
+    if messages.is_empty() {
+        return Err(AppError::invalid("对话内容为空"));
+    }
+    let mut settings = app.state::<AppState>().settings.lock()?.llm.clone();
+    if let Some(temperature) = temperature {
+        settings.local.temperature = temperature;
+    }
+    let started = Instant::now();
+    let generations = app.state::<Generations>();
+    let cancelled = generations.register(id);
+    let result = run(app, &settings, conversation_id, messages, credentials, &mut |token| {
+        !cancelled.load(Ordering::SeqCst) && on_token(token)
+    });
+    generations.finish(id);
+    let (provider, text) = result?;
+
+    let generation = Generation {
+        id,
+        text: text.trim().to_string(),
+        provider,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        cancelled: cancelled.load(Ordering::SeqCst),
+    };
+    info!(
+        "Generated {} chars with {:?} in {} ms{}",
+        generation.text.chars().count(),
+        generation.provider,
+        generation.elapsed_ms,
+        if generation.cancelled { " (cancelled)" } else { "" }
+    );
+    Ok(generation)
+}
+
+// 按对话选择模型来源，离线或后端失败时按设置改用本地模型
+fn run(
+    app: &AppHandle,
+    settings: &LlmSettings,
+    conversation_id: Option<&str>,
+    messages: &[ChatMessage],
+    credentials: BackendCredentials,
+    on_token: &mut dyn FnMut(&str) -> bool,
+) -> Result<(LlmProvider, String), AppError> {
+    let mut primary = model(
+        app,
+        settings,
+        settings.provider_for(conversation_id),
+        credentials.clone(),
+    );
+    if primary.provider() == LlmProvider::Backend && offline::prefer_local(app) {
+        let local = model(app, settings, LlmProvider::Local, credentials.clone());
+        if local.is_available() {
+            primary = local;
+        }
+    }
+    match primary.generate(messages, on_token) {
+        Ok(text) => Ok((primary.provider(), text)),
+        Err(e) if primary.provider() == LlmProvider::Backend && settings.fallback_to_local => {
+            let local = model(app, settings, LlmProvider::Local, credentials);
+            if !local.is_available() {
+                return Err(e);
+            }
+            warn!("Backend generation failed, falling back to local model: {}", e);
+            Ok((local.provider(), local.generate(messages, on_token)?))
+        }
+        Err(e) => Err(e),
+    }
+}
+
+// 传入 conversation_id 时只修改该对话，provider 为空表示恢复默认
+#[tauri::command]
+pub fn set_llm_provider(
+    app: AppHandle,
+    provider: Option<LlmProvider>,
+    conversation_id: Option<String>,
+) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    match (conversation_id, provider) {
+        (Some(id), Some(provider)) => {
+            settings.llm.conversations.insert(id, provider);
+        }
+        (Some(id), None) => {
+            settings.llm.conversations.remove(&id);
+        }
+        (None, Some(provider)) => settings.llm.provider = provider,
+        (None, None) => return Err(AppError::invalid("请指定模型来源")),
+    }
+    settings::save_and_notify(&app, &settings, &["llm"])?;
+    Ok(())
+}
+
+// 会话被删除后移除它单独指定的模型来源
+pub fn forget_deleted_conversations(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let Ok(mut settings) = state.settings.lock() else {
+        return;
+    };
+    let before = settings.llm.conversations.len();
+    settings
+        .llm
+        .conversations
+        .retain(|id, _| state.storage.conversation_exists(id).unwrap_or(true));
+    if settings.llm.conversations.len() == before {
+        return;
+    }
+    if let Err(e) = settings::save_and_notify(app, &settings, &["llm"]) {
+        warn!("Failed to save model settings: {}", e);
+    }
+}
+
+// 修改本地模型设置后，运行中的 llama.cpp 服务在下一次生成时按新参数重启
+#[tauri::command]
+pub fn set_local_llm(app: AppHandle, local: LocalLlmSettings) -> Result<(), AppError> {
+    if local.context_length < 256 {
+        return Err(AppError::invalid("上下文长度不能小于 256"));
+    }
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.llm.local = local;
+    settings::save_and_notify(&app, &settings, &["llm"])?;
+    Ok(())
+}
+
+// 中断进行中的生成，已生成的部分照常返回；生成已结束时返回 false
+#[tauri::command]
+pub fn cancel_generation(generations: State<'_, Generations>, id: u64) -> Result<bool, AppError> {
+    Ok(generations.cancel(id))
+}
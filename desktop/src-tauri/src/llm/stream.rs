@@ -0,0 +1,150 @@
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::{BackendCredentials, ChatToken, Generations, LlmProvider};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::memory::{self, MemoryJob};
+use crate::persona;
+use crate::storage::{Message, Role};
+use crate::AppState;
+
+// 生成线程最多领先前端这么多段文本，超过后阻塞等待转发
+const CHANNEL_CAPACITY: usize = 32;
+// 两次 chat-token 事件的最短间隔，期间到达的文本合并发送
+const FLUSH_INTERVAL: Duration = Duration::from_millis(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatStreamStarted {
+    pub id: u64,
+    pub conversation_id: String,
+    pub message: Message,
+}
+
+// 生成结束、被取消或失败时推送；reply 为保存的回答消息
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatFinished {
+    pub id: u64,
+    pub conversation_id: String,
+    pub reply: Option<Message>,
+    pub provider: Option<LlmProvider>,
+    pub cancelled: bool,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+// 把生成线程送来的文本转发为 chat-token 事件，发送后等待一个间隔，期间积压的文本合并为一个事件
+async fn forward(app: AppHandle, id: u64, conversation_id: String, mut tokens: mpsc::Receiver<String>) {
+    while let Some(mut token) = tokens.recv().await {
+        while let Ok(next) = tokens.try_recv() {
+            token.push_str(&next);
+        }
+        let event = ChatToken {
+            id,
+            conversation_id: Some(conversation_id.clone()),
+            token,
+        };
+        events::publish(&app, AppEvent::ChatToken(event));
+        tokio::time::sleep(FLUSH_INTERVAL).await;
+    }
+}
+
+async fn run(app: AppHandle, id: u64, conversation_id: String, credentials: BackendCredentials) -> ChatFinished {
+    let (sender, receiver) = mpsc::channel::<String>(CHANNEL_CAPACITY);
+    let forwarder = tauri::async_runtime::spawn(forward(app.clone(), id, conversation_id.clone(), receiver));
+
+    let handle = app.clone();
+    let conversation = conversation_id.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || {
+        let persona = persona::active(&handle);
+        let mut messages = memory::context(&handle, &conversation)?;
+        persona::apply(persona.as_ref(), &mut messages);
+        let temperature = persona.and_then(|persona| persona.temperature);
+        // 转发任务已退出时停止生成
+        super::generate(
+            &handle,
+            id,
+            Some(&conversation),
+            &messages,
+            credentials,
+            temperature,
+            &mut |token| sender.blocking_send(token.to_string()).is_ok(),
+        )
+    })
+    .await
+    .map_err(AppError::from)
+    .and_then(|result| result);
+    app.state::<Generations>().finish(id);
+    // 发送端随生成线程结束而释放，等待剩余文本发送完
+    forwarder.await.ok();
+
+    let mut finished = ChatFinished {
+        id,
+        conversation_id: conversation_id.clone(),
+        reply: None,
+        provider: None,
+        cancelled: false,
+        error: None,
+        elapsed_ms: 0,
+    };
+    match result {
+        Ok(generation) => {
+            finished.provider = Some(generation.provider);
+            finished.cancelled = generation.cancelled;
+            finished.elapsed_ms = generation.elapsed_ms;
+            // 取消时保留已生成的部分，与前端已显示的内容一致
+            if !generation.text.is_empty() {
+                let storage = &app.state::<AppState>().storage;
+                match storage.save_message(Some(conversation_id), Role::Assistant, generation.text, None) {
+                    Ok(message) => finished.reply = Some(message),
+                    Err(e) => finished.error = Some(e.to_string()),
+                }
+            }
+        }
+        Err(e) => {
+            warn!("Chat generation {} failed: {}", id, e);
+            finished.error = Some(e.to_string());
+        }
+    }
+    finished
+}
+
+// 保存用户消息后立即返回生成 id，回答以 chat-token 事件推送，结束时推送 chat-finished
+#[tauri::command]
+pub async fn chat_stream(
+    app: AppHandle,
+    conversation_id: Option<String>,
+    message: String,
+    credentials: Option<BackendCredentials>,
+) -> Result<ChatStreamStarted, AppError> {
+    if message.trim().is_empty() {
+        return Err(AppError::invalid("消息内容不能为空"));
+    }
+    let message = app
+        .state::<AppState>()
+        .storage
+        .save_message(conversation_id, Role::User, message, None)?;
+    let id = super::next_id();
+    // 先登记，保证立即到达的 cancel_generation 也能生效
+    app.state::<Generations>().register(id);
+    let conversation_id = message.conversation_id.clone();
+    info!("Chat generation {} started in conversation {}", id, conversation_id);
+
+    let credentials = credentials.unwrap_or_default();
+    app.state::<MemoryJob>().remember_credentials(&credentials);
+
+    let handle = app.clone();
+    let conversation = conversation_id.clone();
+    tauri::async_runtime::spawn(async move {
+        let finished = run(handle.clone(), id, conversation, credentials).await;
+        events::publish(&handle, AppEvent::ChatFinished(finished));
+    });
+    Ok(ChatStreamStarted {
+        id,
+        conversation_id,
+        message,
+    })
+}
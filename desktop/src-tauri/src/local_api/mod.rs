@@ -0,0 +1,380 @@
+use hyper::body::HttpBody;
+use hyper::header::{AUTHORIZATION, CONTENT_TYPE, HOST};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::convert::Infallible;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Mutex;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::memory::MemoryJob;
+use crate::notifications;
+use crate::pet::{self, bubble, PET_LABEL};
+use crate::pipeline::{self, TurnRequest};
+use crate::{i18n, secrets, AppState};
+
+mod openai;
+
+// 请求体的大小上限；chat/completions 会带上完整的对话历史，需要留出足够空间
+const MAX_BODY_BYTES: usize = 8 * 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct LocalApiSettings {
+    pub enabled: bool,
+    // 只监听 127.0.0.1
+    pub port: u16,
+}
+
+impl Default for LocalApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 47821,
+        }
+    }
+}
+
+impl LocalApiSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.port < 1024 {
+            return Err(AppError::invalid("本地 API 端口需在 1024-65535 之间"));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LocalApiStatus {
+    pub running: bool,
+    pub address: Option<String>,
+    // 最近一次启动失败的原因，例如端口被占用
+    pub error: Option<String>,
+}
+
+// 供 Stream Deck、Raycast 或脚本调用的本地 HTTP 接口，/v1/ 下为 OpenAI 兼容接口；
+// 请求需要带上 Authorization: Bearer <令牌>，令牌保存在系统钥匙串中
+#[derive(Default)]
+pub struct LocalApi {
+    task: Mutex<Option<JoinHandle<()>>>,
+    status: Mutex<LocalApiStatus>,
+    token: Mutex<Option<String>>,
+}
+
+impl LocalApi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> LocalApiStatus {
+        self.status.lock().map(|status| status.clone()).unwrap_or_default()
+    }
+
+    fn set_status(&self, status: LocalApiStatus) {
+        if let Ok(mut current) = self.status.lock() {
+            *current = status;
+        }
+    }
+
+    pub fn stop(&self) -> Option<JoinHandle<()>> {
+        let task = self.task.lock().ok().and_then(|mut task| task.take());
+        if let Some(task) = &task {
+            task.abort();
+        }
+        self.set_status(LocalApiStatus::default());
+        task
+    }
+}
+
+fn generate_token() -> String {
+    format!("{}{}", uuid::Uuid::new_v4().simple(), uuid::Uuid::new_v4().simple())
+}
+
+// 钥匙串中没有令牌时生成一个新的
+fn token(app: &AppHandle) -> Result<String, AppError> {
+    let api = app.state::<LocalApi>();
+    let mut cached = api.token.lock()?;
+    if let Some(token) = cached.as_ref() {
+        return Ok(token.clone());
+    }
+    let token = match secrets::get(secrets::LOCAL_API_TOKEN)? {
+        Some(token) => token,
+        None => {
+            let token = generate_token();
+            secrets::store(secrets::LOCAL_API_TOKEN, &token)?;
+            info!("Generated a new local API token");
+            token
+        }
+    };
+    *cached = Some(token.clone());
+    Ok(token)
+}
+
+// 按当前设置重新启动服务，未启用时停止
+pub fn apply(app: &AppHandle) {
+    let api = app.state::<LocalApi>();
+    let previous = api.stop();
+    let Ok(settings) = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|settings| settings.local_api.clone())
+    else {
+        return;
+    };
+    if !settings.enabled {
+        return;
+    }
+
+    let handle = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        // 等旧的服务释放端口后再监听
+        if let Some(previous) = previous {
+            let _ = previous.await;
+        }
+        let api = handle.state::<LocalApi>();
+        if let Err(e) = serve(&handle, settings.port).await {
+            warn!("Local API server stopped: {}", e);
+            api.set_status(LocalApiStatus {
+                running: false,
+                address: None,
+                error: Some(e.to_string()),
+            });
+        }
+    });
+    if let Ok(mut current) = api.task.lock() {
+        *current = Some(task);
+    };
+}
+
+pub fn start(app: &AppHandle) {
+    apply(app);
+}
+
+async fn serve(app: &AppHandle, port: u16) -> Result<(), AppError> {
+    token(app)?;
+    let address = SocketAddr::from((Ipv4Addr::LOCALHOST, port));
+    let builder =
+        Server::try_bind(&address).map_err(|e| AppError::unavailable(format!("无法监听本地 API 端口 {}", port), e))?;
+    let handle = app.clone();
+    let service = make_service_fn(move |_| {
+        let app = handle.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let app = app.clone();
+                async move { Ok::<_, Infallible>(respond(&app, port, request).await) }
+            }))
+        }
+    });
+    info!("Local API listening on {}", address);
+    app.state::<LocalApi>().set_status(LocalApiStatus {
+        running: true,
+        address: Some(format!("http://{}", address)),
+        error: None,
+    });
+    builder
+        .serve(service)
+        .await
+        .map_err(|e| AppError::unavailable("本地 API 服务异常退出", e))
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    let mut response = Response::new(Body::from(body.to_string()));
+    *response.status_mut() = status;
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, "application/json; charset=utf-8".parse().unwrap());
+    response
+}
+
+fn error_status(error: &AppError) -> StatusCode {
+    match error {
+        AppError::InvalidInput(_) => StatusCode::BAD_REQUEST,
+        AppError::NotFound(_) => StatusCode::NOT_FOUND,
+        AppError::PermissionDenied(_) => StatusCode::FORBIDDEN,
+        AppError::Cancelled(_) => StatusCode::CONFLICT,
+        AppError::Unavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
+        AppError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn error_response(status: StatusCode, error: &AppError) -> Response<Body> {
+    json_response(status, &serde_json::to_value(error).unwrap_or(Value::Null))
+}
+
+// 逐字节比较全部内容，耗时不随匹配的前缀长度变化
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+// 只接受指向本机的 Host，防止网页通过 DNS 重绑定访问接口
+fn host_allowed(request: &Request<Body>, port: u16) -> bool {
+    let Some(host) = request.headers().get(HOST).and_then(|host| host.to_str().ok()) else {
+        return false;
+    };
+    [format!("127.0.0.1:{}", port), format!("localhost:{}", port)]
+        .iter()
+        .any(|allowed| host.eq_ignore_ascii_case(allowed))
+}
+
+fn authorized(app: &AppHandle, request: &Request<Body>) -> bool {
+    let Some(provided) = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+    else {
+        return false;
+    };
+    match token(app) {
+        Ok(token) => constant_time_eq(provided.trim().as_bytes(), token.as_bytes()),
+        Err(e) => {
+            warn!("Failed to load local API token: {}", e);
+            false
+        }
+    }
+}
+
+async fn respond(app: &AppHandle, port: u16, request: Request<Body>) -> Response<Body> {
+    if !host_allowed(&request, port) {
+        return error_response(
+            StatusCode::FORBIDDEN,
+            &AppError::PermissionDenied("不允许的 Host".into()),
+        );
+    }
+    if !authorized(app, &request) {
+        return error_response(
+            StatusCode::UNAUTHORIZED,
+            &AppError::PermissionDenied("缺少或错误的访问令牌".into()),
+        );
+    }
+    if request.uri().path().starts_with("/v1/") {
+        return openai::respond(app, request).await;
+    }
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    match route(app, request).await {
+        Ok(body) => json_response(StatusCode::OK, &body),
+        Err(e) => {
+            warn!("Local API {} {} failed: {}", method, path, e);
+            error_response(error_status(&e), &e)
+        }
+    }
+}
+
+async fn read_json<T: DeserializeOwned>(request: Request<Body>) -> Result<T, AppError> {
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|e| AppError::unavailable("读取请求内容失败", e))?;
+        if bytes.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(AppError::invalid("请求内容过大"));
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+    serde_json::from_slice(&bytes).map_err(|e| AppError::invalid(format!("请求内容不是有效的 JSON: {}", e)))
+}
+
+#[derive(Deserialize)]
+struct AskRequest {
+    text: String,
+    language: Option<String>,
+    conversation_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct NotifyRequest {
+    title: Option<String>,
+    body: String,
+}
+
+#[derive(Deserialize)]
+struct SayRequest {
+    text: String,
+    duration_ms: Option<u64>,
+}
+
+fn pet_visible(app: &AppHandle) -> bool {
+    app.get_window(PET_LABEL)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false)
+}
+
+async fn route(app: &AppHandle, request: Request<Body>) -> Result<Value, AppError> {
+    let method = request.method().clone();
+    let path = request.uri().path().trim_end_matches('/').to_string();
+    match (method, path.as_str()) {
+        (Method::GET, "/status") => Ok(json!({
+            "name": i18n::t("app.name"),
+            "version": app.package_info().version.to_string(),
+            "pet_visible": pet_visible(app),
+        })),
+        // 走完整的对话流程并朗读回答；后端模型使用最近一次对话提供的凭证
+        (Method::POST, "/ask") => {
+            let ask: AskRequest = read_json(request).await?;
+            if ask.text.trim().is_empty() {
+                return Err(AppError::invalid("问题不能为空"));
+            }
+            let credentials = app.state::<MemoryJob>().credentials();
+            let turn = TurnRequest {
+                text: Some(ask.text),
+                api_key: credentials.api_key,
+                api_secret: credentials.api_secret,
+                assistant_id: credentials.assistant_id,
+                session_id: credentials.session_id,
+                language: ask.language.or(credentials.language),
+                token: credentials.token,
+                conversation_id: ask.conversation_id,
+                ..TurnRequest::default()
+            };
+            let result = pipeline::run_turn(app.clone(), turn).await?;
+            Ok(serde_json::to_value(result)?)
+        }
+        (Method::POST, "/notify") => {
+            let notify: NotifyRequest = read_json(request).await?;
+            if notify.body.trim().is_empty() {
+                return Err(AppError::invalid("通知内容不能为空"));
+            }
+            let title = notify.title.unwrap_or_else(|| i18n::t("app.name"));
+            let delivered = notifications::send(app, notifications::CATEGORY_GENERAL, &title, &notify.body, None)?;
+            Ok(json!({ "delivered": delivered }))
+        }
+        (Method::POST, "/pet/toggle") => {
+            let visible = pet::toggle_desktop_pet(app.clone()).await?;
+            Ok(json!({ "visible": visible }))
+        }
+        (Method::POST, "/pet/say") => {
+            let say: SayRequest = read_json(request).await?;
+            let shown = bubble::show(app, &say.text, say.duration_ms)?;
+            Ok(json!({ "shown": shown.is_some() }))
+        }
+        (_, path) => Err(AppError::NotFound(format!("未知的接口: {}", path))),
+    }
+}
+
+#[tauri::command]
+pub fn get_local_api_status(api: State<'_, LocalApi>) -> Result<LocalApiStatus, AppError> {
+    Ok(api.status())
+}
+
+#[tauri::command]
+pub fn get_local_api_token(app: AppHandle) -> Result<String, AppError> {
+    token(&app)
+}
+
+// 旧令牌立即失效
+#[tauri::command]
+pub fn regenerate_local_api_token(app: AppHandle) -> Result<String, AppError> {
+    let token = generate_token();
+    secrets::store(secrets::LOCAL_API_TOKEN, &token)?;
+    *app.state::<LocalApi>().token.lock()? = Some(token.clone());
+    info!("Regenerated the local API token");
+    Ok(token)
+}
@@ -0,0 +1,213 @@
+use hyper::body::Bytes;
+use hyper::header::{CACHE_CONTROL, CONTENT_TYPE};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+use super::{error_status, json_response, read_json};
+use crate::error::AppError;
+use crate::i18n;
+use crate::llm::{self, ChatMessage};
+use crate::memory::MemoryJob;
+use crate::storage::Role;
+
+// OpenAI 兼容的 /v1/chat/completions，转发给设置中的模型来源；
+// 其他桌面工具把 API Key 设为本地 API 令牌即可复用助手的凭证和本地模型
+const MODEL: &str = "lingecho";
+
+#[derive(Deserialize)]
+struct CompletionRequest {
+    #[serde(default)]
+    model: Option<String>,
+    messages: Vec<RequestMessage>,
+    #[serde(default)]
+    stream: bool,
+    #[serde(default)]
+    temperature: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct RequestMessage {
+    role: String,
+    #[serde(default)]
+    content: Option<Content>,
+}
+
+// content 可以是字符串，也可以是分段数组；只取其中的文本段
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Content {
+    Text(String),
+    Parts(Vec<ContentPart>),
+}
+
+#[derive(Deserialize)]
+struct ContentPart {
+    #[serde(default)]
+    text: Option<String>,
+}
+
+impl RequestMessage {
+    fn into_chat(self) -> ChatMessage {
+        let role = match self.role.as_str() {
+            "assistant" => Role::Assistant,
+            "system" | "developer" => Role::System,
+            _ => Role::User,
+        };
+        let content = match self.content {
+            Some(Content::Text(text)) => text,
+            Some(Content::Parts(parts)) => parts
+                .into_iter()
+                .filter_map(|part| part.text)
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => String::new(),
+        };
+        ChatMessage { role, content }
+    }
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+fn error_response(error: &AppError) -> Response<Body> {
+    json_response(
+        error_status(error),
+        &json!({
+            "error": {
+                "message": i18n::translate(error.message()),
+                "type": error.code(),
+                "code": error.code(),
+            }
+        }),
+    )
+}
+
+pub async fn respond(app: &AppHandle, request: Request<Body>) -> Response<Body> {
+    let method = request.method().clone();
+    let path = request.uri().path().trim_end_matches('/').to_string();
+    let result = match (method.clone(), path.as_str()) {
+        (Method::GET, "/v1/models") => Ok(json_response(
+            StatusCode::OK,
+            &json!({
+                "object": "list",
+                "data": [{ "id": MODEL, "object": "model", "created": 0, "owned_by": MODEL }],
+            }),
+        )),
+        (Method::POST, "/v1/chat/completions") => chat_completions(app, request).await,
+        (_, path) => Err(AppError::NotFound(format!("未知的接口: {}", path))),
+    };
+    result.unwrap_or_else(|e| {
+        warn!("Local API {} {} failed: {}", method, path, e);
+        error_response(&e)
+    })
+}
+
+// 不写入对话历史，也不套用人设，上下文完全由调用方提供
+async fn chat_completions(app: &AppHandle, request: Request<Body>) -> Result<Response<Body>, AppError> {
+    let completion: CompletionRequest = read_json(request).await?;
+    let messages: Vec<ChatMessage> = completion
+        .messages
+        .into_iter()
+        .map(RequestMessage::into_chat)
+        .filter(|message| !message.content.trim().is_empty())
+        .collect();
+    if messages.is_empty() {
+        return Err(AppError::invalid("对话内容为空"));
+    }
+    let model = completion
+        .model
+        .filter(|model| !model.trim().is_empty())
+        .unwrap_or_else(|| MODEL.to_string());
+    let id = llm::next_id();
+    let completion_id = format!("chatcmpl-{}", id);
+    let created = unix_secs();
+    // 后端模型使用最近一次对话提供的凭证
+    let credentials = app.state::<MemoryJob>().credentials();
+    let temperature = completion.temperature;
+    let handle = app.clone();
+    info!("Local API chat completion {} with {} messages", id, messages.len());
+
+    if !completion.stream {
+        let generation = tauri::async_runtime::spawn_blocking(move || {
+            llm::generate(&handle, id, None, &messages, credentials, temperature, &mut |_| true)
+        })
+        .await??;
+        return Ok(json_response(
+            StatusCode::OK,
+            &json!({
+                "id": completion_id,
+                "object": "chat.completion",
+                "created": created,
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": generation.text },
+                    "finish_reason": "stop",
+                }],
+            }),
+        ));
+    }
+
+    // 调用方断开后发送失败，生成线程在下一段文本时停止
+    let (tokens, mut received) = mpsc::unbounded_channel::<String>();
+    let generation = tauri::async_runtime::spawn_blocking(move || {
+        llm::generate(&handle, id, None, &messages, credentials, temperature, &mut |token| {
+            tokens.send(token.to_string()).is_ok()
+        })
+    });
+    let (mut sender, body) = Body::channel();
+    tauri::async_runtime::spawn(async move {
+        let chunk = |delta: Value, finish_reason: Option<&str>| {
+            let chunk = json!({
+                "id": completion_id,
+                "object": "chat.completion.chunk",
+                "created": created,
+                "model": model,
+                "choices": [{ "index": 0, "delta": delta, "finish_reason": finish_reason }],
+            });
+            Bytes::from(format!("data: {}\n\n", chunk))
+        };
+        if sender
+            .send_data(chunk(json!({ "role": "assistant" }), None))
+            .await
+            .is_err()
+        {
+            return;
+        }
+        while let Some(token) = received.recv().await {
+            if sender
+                .send_data(chunk(json!({ "content": token }), None))
+                .await
+                .is_err()
+            {
+                return;
+            }
+        }
+        let last = match generation.await.map_err(AppError::from).and_then(|result| result) {
+            Ok(_) => chunk(json!({}), Some("stop")),
+            Err(e) => {
+                warn!("Local API chat completion {} failed: {}", id, e);
+                let error = json!({ "error": { "message": i18n::translate(e.message()), "type": e.code() } });
+                Bytes::from(format!("data: {}\n\n", error))
+            }
+        };
+        if sender.send_data(last).await.is_ok() {
+            let _ = sender.send_data(Bytes::from_static(b"data: [DONE]\n\n")).await;
+        }
+    });
+
+    let mut response = Response::new(body);
+    let headers = response.headers_mut();
+    headers.insert(CONTENT_TYPE, "text/event-stream".parse().unwrap());
+    headers.insert(CACHE_CONTROL, "no-cache".parse().unwrap());
+    Ok(response)
+}
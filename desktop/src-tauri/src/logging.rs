@@ -0,0 +1,264 @@
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, reload, EnvFilter, Registry};
+
+use crate::error::AppError;
+use crate::{settings, AppState};
+
+const LOG_PREFIX: &str = "lingecho";
+// 单个日志文件超过该大小时切换到同一天的下一个文件
+const MAX_FILE_BYTES: u64 = 10 * 1024 * 1024;
+const MAX_LOG_FILES: usize = 14;
+// 供应用内日志查看器读取的最近日志行数
+const RECENT_LINES: usize = 2000;
+// 设置该环境变量时优先于设置文件中的日志级别
+const LEVEL_ENV: &str = "LINGECHO_LOG";
+const LEVELS: [&str; 5] = ["error", "warn", "info", "debug", "trace"];
+
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+static GUARD: Mutex<Option<WorkerGuard>> = Mutex::new(None);
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+// UTC 日期，用于按天切分日志文件
+fn utc_date() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // 公历换算，参考 Howard Hinnant 的 civil_from_days
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn remember_recent(buf: &[u8]) {
+    let Ok(mut recent) = RECENT.lock() else {
+        return;
+    };
+    for line in String::from_utf8_lossy(buf).lines().filter(|line| !line.is_empty()) {
+        if recent.len() >= RECENT_LINES {
+            recent.pop_front();
+        }
+        recent.push_back(line.to_string());
+    }
+}
+
+// 按天和大小切分的日志文件，同时把写入的内容保留在内存缓冲区
+struct RollingFile {
+    dir: PathBuf,
+    date: String,
+    index: u32,
+    size: u64,
+    file: Option<File>,
+}
+
+impl RollingFile {
+    fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            date: String::new(),
+            index: 0,
+            size: 0,
+            file: None,
+        }
+    }
+
+    fn path(&self) -> PathBuf {
+        match self.index {
+            0 => self.dir.join(format!("{}-{}.log", LOG_PREFIX, self.date)),
+            index => self.dir.join(format!("{}-{}.{}.log", LOG_PREFIX, self.date, index)),
+        }
+    }
+
+    fn open(&mut self) -> std::io::Result<()> {
+        std::fs::create_dir_all(&self.dir)?;
+        // 重启后继续写当天未写满的文件
+        loop {
+            let size = std::fs::metadata(self.path()).map(|m| m.len()).unwrap_or(0);
+            if size < MAX_FILE_BYTES {
+                self.size = size;
+                break;
+            }
+            self.index += 1;
+        }
+        self.file = Some(OpenOptions::new().create(true).append(true).open(self.path())?);
+        self.prune();
+        Ok(())
+    }
+
+    fn prune(&self) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut logs: Vec<(SystemTime, PathBuf)> = entries
+            .filter_map(Result::ok)
+            .filter(|entry| {
+                let name = entry.file_name().to_string_lossy().to_string();
+                name.starts_with(LOG_PREFIX) && name.ends_with(".log")
+            })
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .collect();
+        if logs.len() <= MAX_LOG_FILES {
+            return;
+        }
+        logs.sort();
+        for (_, path) in &logs[..logs.len() - MAX_LOG_FILES] {
+            std::fs::remove_file(path).ok();
+        }
+    }
+}
+
+impl Write for RollingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        remember_recent(buf);
+
+        let today = utc_date();
+        if today != self.date {
+            self.date = today;
+            self.index = 0;
+            self.file = None;
+        } else if self.size >= MAX_FILE_BYTES {
+            self.index += 1;
+            self.file = None;
+        }
+        if self.file.is_none() {
+            self.open()?;
+        }
+
+        let written = self.file.as_mut().map(|file| file.write(buf)).unwrap_or(Ok(0))?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.file.as_mut() {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+pub fn parse_level(level: &str) -> Result<String, AppError> {
+    let level = level.trim().to_ascii_lowercase();
+    if LEVELS.contains(&level.as_str()) {
+        Ok(level)
+    } else {
+        Err(AppError::invalid(format!("无效的日志级别: {}", level)))
+    }
+}
+
+// 第三方库的调试日志量很大，只保留警告以上；backend、plugin、llama_server 是转发子进程输出时使用的 target
+fn filter_for(level: &str) -> EnvFilter {
+    EnvFilter::new(format!(
+        "warn,voice_pilot_core={level},backend={level},plugin={level},llama_server={level}"
+    ))
+}
+
+// 在创建 Tauri 应用之前调用，log_dir 为空时只输出到控制台
+pub fn init(log_dir: Option<&Path>) {
+    let level = std::env::var(LEVEL_ENV)
+        .ok()
+        .and_then(|level| parse_level(&level).ok())
+        .unwrap_or_else(|| "info".to_string());
+    let (filter, handle) = reload::Layer::new(filter_for(&level));
+    FILTER.set(handle).ok();
+
+    let file_layer = log_dir.map(|dir| {
+        let (writer, guard) = tracing_appender::non_blocking(RollingFile::new(dir));
+        if let Ok(mut slot) = GUARD.lock() {
+            *slot = Some(guard);
+        }
+        fmt::layer().with_ansi(false).with_writer(writer)
+    });
+    let console_layer = cfg!(debug_assertions).then(fmt::layer);
+
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(file_layer)
+        .with(console_layer)
+        .init();
+
+    if let Some(dir) = log_dir {
+        info!("Logging to {}", dir.display());
+    }
+}
+
+// 应用退出前调用，确保缓冲中的日志写入文件
+pub fn shutdown() {
+    if let Ok(mut guard) = GUARD.lock() {
+        guard.take();
+    }
+}
+
+pub fn set_level(level: &str) -> Result<(), AppError> {
+    let level = parse_level(level)?;
+    FILTER
+        .get()
+        .ok_or("日志系统尚未初始化")?
+        .reload(filter_for(&level))
+        .map_err(|e| AppError::Internal(e.to_string()))
+}
+
+// 启动时应用设置中保存的级别
+pub fn apply_saved_level(level: &str) {
+    if std::env::var(LEVEL_ENV).is_ok() {
+        return;
+    }
+    if let Err(e) = set_level(level) {
+        tracing::warn!("{}", e);
+    }
+}
+
+fn line_level(line: &str) -> Option<usize> {
+    line.split_whitespace()
+        .take(3)
+        .find_map(|token| LEVELS.iter().position(|level| token.eq_ignore_ascii_case(level)))
+}
+
+#[tauri::command]
+pub fn set_log_level(app: AppHandle, level: String) -> Result<(), AppError> {
+    set_level(&level)?;
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.log_level = parse_level(&level)?;
+    settings::save_and_notify(&app, &settings, &["log_level"])
+}
+
+// level 为最低级别，例如 warn 时返回 warn 和 error
+#[tauri::command]
+pub fn get_recent_logs(lines: Option<usize>, level: Option<String>) -> Result<Vec<String>, AppError> {
+    let min_level = level
+        .map(|level| parse_level(&level))
+        .transpose()?
+        .and_then(|level| LEVELS.iter().position(|l| *l == level));
+
+    let recent = RECENT.lock()?;
+    let mut matched: Vec<String> = recent
+        .iter()
+        .rev()
+        .filter(|line| match min_level {
+            Some(min) => line_level(line).is_some_and(|level| level <= min),
+            None => true,
+        })
+        .take(lines.unwrap_or(200))
+        .cloned()
+        .collect();
+    matched.reverse();
+    Ok(matched)
+}
@@ -0,0 +1,192 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Window};
+#[cfg(target_os = "macos")]
+use tauri::PhysicalPosition;
+#[cfg(target_os = "macos")]
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{settings, AppState};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MacosSettings {
+    // 作为辅助应用运行：不显示程序坞图标，只能从托盘菜单打开主窗口
+    pub hide_dock_icon: bool,
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use objc::runtime::{Object, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    // NSWindowCollectionBehavior
+    const CAN_JOIN_ALL_SPACES: u64 = 1 << 0;
+    const STATIONARY: u64 = 1 << 4;
+    const IGNORES_CYCLE: u64 = 1 << 6;
+    const FULL_SCREEN_AUXILIARY: u64 = 1 << 8;
+
+    // NSApplicationActivationPolicy
+    const POLICY_REGULAR: i64 = 0;
+    const POLICY_ACCESSORY: i64 = 1;
+
+    // 出现在所有桌面空间和全屏应用之上，切换空间时不跟随移动，也不参与 Cmd+` 窗口切换
+    pub fn join_all_spaces(ns_window: *mut Object) {
+        // SAFETY: ns_window 是 Tauri 创建的有效 NSWindow，调用发生在主线程
+        unsafe {
+            let behavior: u64 = msg_send![ns_window, collectionBehavior];
+            let behavior = behavior | CAN_JOIN_ALL_SPACES | STATIONARY | IGNORES_CYCLE | FULL_SCREEN_AUXILIARY;
+            let _: () = msg_send![ns_window, setCollectionBehavior: behavior];
+        }
+    }
+
+    pub fn set_accessory(accessory: bool) {
+        let policy = if accessory { POLICY_ACCESSORY } else { POLICY_REGULAR };
+        // SAFETY: NSApplication 单例在应用启动后一直存在，调用发生在主线程
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: bool = msg_send![app, setActivationPolicy: policy];
+        }
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Size {
+        width: f64,
+        height: f64,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy)]
+    struct Rect {
+        origin: Point,
+        size: Size,
+    }
+
+    // AppKit 的坐标以主屏幕左下角为原点、单位为点，换算成左上角为原点的物理像素，
+    // 与 tao 给出的显示器位置（逻辑坐标乘以该显示器的缩放比例）一致
+    pub fn cursor_position() -> Option<(i32, i32)> {
+        // SAFETY: NSEvent 和 NSScreen 的类方法在主线程调用，screens 数组在本次调用期间有效
+        unsafe {
+            let location: Point = msg_send![class!(NSEvent), mouseLocation];
+            let screens: *mut Object = msg_send![class!(NSScreen), screens];
+            let count: usize = msg_send![screens, count];
+            if count == 0 {
+                return None;
+            }
+            let primary: *mut Object = msg_send![screens, objectAtIndex: 0usize];
+            let primary: Rect = msg_send![primary, frame];
+            let mut scale: f64 = 1.0;
+            for index in 0..count {
+                let screen: *mut Object = msg_send![screens, objectAtIndex: index];
+                let frame: Rect = msg_send![screen, frame];
+                let inside_x = location.x >= frame.origin.x && location.x <= frame.origin.x + frame.size.width;
+                let inside_y = location.y >= frame.origin.y && location.y <= frame.origin.y + frame.size.height;
+                if inside_x && inside_y {
+                    scale = msg_send![screen, backingScaleFactor];
+                    break;
+                }
+            }
+            let x = location.x * scale;
+            let y = (primary.size.height - location.y) * scale;
+            Some((x.round() as i32, y.round() as i32))
+        }
+    }
+
+    // 辅助应用不会因为窗口显示而自动成为前台应用
+    pub fn activate() {
+        // SAFETY: 同上
+        unsafe {
+            let app: *mut Object = msg_send![class!(NSApplication), sharedApplication];
+            let _: () = msg_send![app, activateIgnoringOtherApps: YES];
+        }
+    }
+}
+
+// 让桌宠在切换桌面空间和进入全屏应用时保持可见，其他平台无需处理
+pub fn join_all_spaces(window: &Window) {
+    #[cfg(target_os = "macos")]
+    {
+        let target = window.clone();
+        let result = window.run_on_main_thread(move || match target.ns_window() {
+            Ok(ns_window) => native::join_all_spaces(ns_window.cast()),
+            Err(e) => warn!("Failed to get NSWindow: {}", e),
+        });
+        if let Err(e) = result {
+            warn!("Failed to set window collection behavior: {}", e);
+        }
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = window;
+}
+
+// 光标的屏幕坐标（物理像素），AppKit 只能在主线程调用
+#[cfg(target_os = "macos")]
+pub async fn cursor_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    if let Err(e) = app.run_on_main_thread(move || {
+        sender.send(native::cursor_position()).ok();
+    }) {
+        warn!("Failed to read cursor position: {}", e);
+        return None;
+    }
+    receiver.await.ok().flatten().map(|(x, y)| PhysicalPosition::new(x, y))
+}
+
+// 按设置切换程序坞图标，启动时和设置变化时调用
+pub fn apply(app: &AppHandle, settings: &MacosSettings) {
+    #[cfg(target_os = "macos")]
+    {
+        let accessory = settings.hide_dock_icon;
+        if let Err(e) = app.run_on_main_thread(move || native::set_accessory(accessory)) {
+            warn!("Failed to set activation policy: {}", e);
+            return;
+        }
+        info!("Dock icon {}", if accessory { "hidden" } else { "shown" });
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = (app, settings);
+}
+
+pub fn start(app: &AppHandle) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.macos.clone(),
+        Err(_) => return,
+    };
+    if settings.hide_dock_icon {
+        apply(app, &settings);
+    }
+}
+
+// 从托盘打开主窗口时把应用带到前台
+pub fn activate(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    if let Err(e) = app.run_on_main_thread(native::activate) {
+        warn!("Failed to activate app: {}", e);
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = app;
+}
+
+#[tauri::command]
+pub fn set_hide_dock_icon(app: AppHandle, hidden: bool) -> Result<MacosSettings, AppError> {
+    if !cfg!(target_os = "macos") {
+        return Err(AppError::invalid("只有 macOS 支持隐藏程序坞图标"));
+    }
+    let state = app.state::<AppState>();
+    let macos = {
+        let mut settings = state.settings.lock()?;
+        settings.macos.hide_dock_icon = hidden;
+        settings::save_and_notify(&app, &settings, &["macos"])?;
+        settings.macos.clone()
+    };
+    apply(&app, &macos);
+    Ok(macos)
+}
@@ -1,14 +1,132 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use tauri::{Manager, State, WindowBuilder, WindowUrl};
-use serde::{Deserialize, Serialize};
-use std::process::{Command, Stdio};
-use std::path::Path;
+mod acceleration;
+mod analytics;
+mod api;
+mod app_context;
+mod audio;
+mod autostart;
+mod backend;
+mod backup;
+mod calendar;
+mod capabilities;
+mod captions;
+mod cli;
+mod crypto;
+mod data;
+mod deep_link;
+mod dnd;
+mod error;
+mod events;
+mod file_search;
+mod fullscreen;
+mod hotkeys;
+mod i18n;
+mod intents;
+mod knowledge;
+mod launcher;
+mod linux;
+mod llm;
+mod local_api;
+mod logging;
+mod macos;
+mod memory;
+mod models;
+mod mqtt;
+mod network;
+mod notes;
+mod notifications;
+mod ocr;
+mod offline;
+mod onboarding;
+mod permissions;
+mod persona;
+mod pet;
+mod pipeline;
+mod platform;
+mod plugins;
+mod power;
+mod privacy;
+mod profiles;
+mod quick_ask;
+mod resources;
+mod retention;
+mod scheduler;
+mod screenshot;
+mod settings;
+mod secrets;
+mod shutdown;
+mod single_instance;
+mod storage;
+mod stt;
+mod system_control;
+mod timers;
+mod tray;
+mod tts;
+mod updater;
+mod weather;
+mod web_clip;
+mod webhooks;
+mod window_state;
+mod wipe;
+mod ws_bridge;
+
+use tauri::{Manager, RunEvent, State, WindowEvent};
+use std::sync::Mutex;
+use tracing::{info, warn};
+
+use analytics::Analytics;
+use audio::{AudioCapture, AudioMeter, AudioPlayer, MicIndicator, TtsStreamer, WakeWordListener};
+use backend::{BackendLaunch, BackendLogLine, BackendManager};
+use calendar::Calendar;
+use data::{ExportBundle, ExportFormat, ImportReport, ImportStrategy};
+use dnd::Dnd;
+use error::AppError;
+use events::{AppEvent, EventBus};
+use file_search::FileIndex;
+use fullscreen::FullscreenWatcher;
+use intents::IntentRouter;
+use knowledge::KnowledgeBase;
+use launcher::AppLauncher;
+use llm::{Generations, LlamaServer};
+use local_api::LocalApi;
+use memory::MemoryJob;
+use models::ModelDownloads;
+use mqtt::MqttClient;
+use network::Network;
+use notes::Notes;
+use notifications::Notifier;
+use offline::Offline;
+use persona::Personas;
+use pet::bubble::PetBubble;
+use pet::state::PetStateMachine;
+use pipeline::Pipeline;
+use platform::TaskbarProgress;
+use plugins::Plugins;
+use power::{Power, PowerConsumer};
+use privacy::Privacy;
+use resources::ResourceMonitor;
+use retention::Retention;
+use scheduler::Scheduler;
+use settings::{Settings, SettingsStore};
+use shutdown::Shutdown;
+use single_instance::Instance;
+use storage::Storage;
+use timers::Timers;
+use updater::Updater;
+use weather::WeatherCache;
+use wipe::Wipe;
+use ws_bridge::WsBridge;
 
-#[derive(Debug, Serialize, Deserialize)]
 struct AppState {
-    theme: String,
+    settings: Mutex<Settings>,
+    store: SettingsStore,
+    storage: Storage,
+    knowledge: KnowledgeBase,
+    scheduler: Scheduler,
+    offline: Offline,
+    analytics: Analytics,
     window_title: String,
 }
 
@@ -21,192 +139,628 @@ fn greet(name: &str) -> String {
 #[tauri::command]
 fn get_app_info() -> serde_json::Value {
     serde_json::json!({
-        "name": "声驭智核",
+        "name": i18n::t("app.name"),
         "version": "0.0.0",
-        "description": "智能语音助手，提供语音交互、桌面宠物、知识管理等功能"
+        "description": i18n::t("app.description")
     })
 }
 
 #[tauri::command]
-fn set_theme(theme: &str, _state: State<AppState>) -> Result<(), String> {
-    println!("Setting theme to: {}", theme);
-    // Here you could implement theme switching logic
+fn set_theme(app: tauri::AppHandle, theme: &str, state: State<AppState>) -> Result<(), AppError> {
+    if theme.trim().is_empty() {
+        return Err(AppError::invalid("主题名称不能为空"));
+    }
+
+    info!("Setting theme to: {}", theme);
+    let mut settings = state.settings.lock()?;
+    settings.theme = theme.to_string();
+    settings::save_and_notify(&app, &settings, &["theme"])?;
     Ok(())
 }
 
 #[tauri::command]
-fn get_theme(state: State<AppState>) -> String {
-    state.theme.clone()
+fn get_theme(state: State<AppState>) -> Result<String, AppError> {
+    let settings = state.settings.lock()?;
+    Ok(settings.theme.clone())
 }
 
 #[tauri::command]
-async fn export_data() -> Result<String, String> {
-    // Implement data export logic here
-    Ok("Data exported successfully".to_string())
+async fn export_data(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    format: Option<ExportFormat>,
+    history: Option<Vec<serde_json::Value>>,
+    password: Option<String>,
+) -> Result<Option<String>, AppError> {
+    let format = format.unwrap_or_default();
+    let password = password.filter(|password| !password.is_empty());
+    data::emit_progress(&app, AppEvent::ExportProgress, "collecting", 10);
+    platform::set_progress(&app, TaskbarProgress::Normal(10));
+
+    let settings = state.settings.lock()?.clone();
+    let history = match history {
+        Some(history) => history,
+        None => state.storage.export_history()?,
+    };
+    let bundle = ExportBundle::new(settings, history, data::collect_pet_state(&app));
+
+    // 弹出系统保存对话框，用户取消时返回 None
+    // 加密的导出文件追加 .enc 扩展名
+    let extension = if password.is_some() { "enc" } else { format.extension() };
+    let mut file_name = format!("lingecho-export-{}.{}", bundle.manifest.exported_at, format.extension());
+    if password.is_some() {
+        file_name.push_str(".enc");
+    }
+    let path = tauri::api::dialog::blocking::FileDialogBuilder::new()
+        .set_file_name(&file_name)
+        .add_filter(i18n::t("dialog.export_filter"), &[extension])
+        .save_file();
+    let Some(path) = path else {
+        data::emit_progress(&app, AppEvent::ExportProgress, "cancelled", 0);
+        platform::set_progress(&app, TaskbarProgress::Hidden);
+        return Ok(None);
+    };
+
+    data::emit_progress(&app, AppEvent::ExportProgress, "writing", 50);
+    platform::set_progress(&app, TaskbarProgress::Normal(50));
+    if let Err(e) = bundle.write_to(&path, format, password.as_deref()) {
+        platform::failed(
+            &app,
+            "export",
+            i18n::t("toast.export_failed"),
+            i18n::translate(e.message()),
+        );
+        return Err(e);
+    }
+    data::emit_progress(&app, AppEvent::ExportProgress, "done", 100);
+    platform::finished(
+        &app,
+        i18n::t("toast.export_done"),
+        path.to_string_lossy().to_string(),
+        Some(&path),
+    );
+
+    info!("Data exported to {}", path.display());
+    Ok(Some(path.to_string_lossy().to_string()))
 }
 
 #[tauri::command]
-async fn import_data() -> Result<String, String> {
-    // Implement data import logic here
-    Ok("Data imported successfully".to_string())
+async fn import_data(
+    app: tauri::AppHandle,
+    state: State<'_, AppState>,
+    path: Option<String>,
+    strategy: Option<ImportStrategy>,
+    password: Option<String>,
+) -> Result<Option<ImportReport>, AppError> {
+    let strategy = strategy.unwrap_or_default();
+
+    // 未指定路径时弹出系统打开对话框
+    let path = match path {
+        Some(path) => std::path::PathBuf::from(path),
+        None => {
+            let picked = tauri::api::dialog::blocking::FileDialogBuilder::new()
+                .add_filter(i18n::t("dialog.export_filter"), &["zip", "json", "enc"])
+                .pick_file();
+            match picked {
+                Some(path) => path,
+                None => return Ok(None),
+            }
+        }
+    };
+
+    data::emit_progress(&app, AppEvent::ImportProgress, "reading", 10);
+    // 加密文件未提供密码或密码错误时返回 password_required，前端询问密码后带上同一路径重试
+    let bundle = ExportBundle::read_from(&path, password.as_deref())?;
+
+    data::emit_progress(&app, AppEvent::ImportProgress, "applying", 50);
+    let (before, after, report) = {
+        let mut settings = state.settings.lock()?;
+        let before = settings.clone();
+        let report = data::apply_import(&app, bundle, strategy, &mut settings, &state.storage)?;
+        state.store.save(&settings)?;
+        (before, settings.clone(), report)
+    };
+    settings::announce_replaced(&app, &before, &after);
+    data::emit_progress(&app, AppEvent::ImportProgress, "done", 100);
+
+    info!(
+        "Data imported from {}: {} imported, {} skipped, {} conflicts",
+        path.display(),
+        report.imported,
+        report.skipped,
+        report.conflicts.len()
+    );
+    Ok(Some(report))
 }
 
 #[tauri::command]
-async fn check_backend_status() -> Result<bool, String> {
+async fn check_backend_status(
+    backend: State<'_, BackendManager>,
+    network: State<'_, Network>,
+) -> Result<bool, AppError> {
     // 检查后端服务是否运行
-    match reqwest::get("http://localhost:7072").await {
-        Ok(response) => Ok(response.status().is_success()),
-        Err(_) => Ok(false),
-    }
+    Ok(backend::ping(&network.client(), &backend.url()).await)
+}
+
+#[tauri::command]
+fn get_backend_url(backend: State<BackendManager>) -> String {
+    backend.url()
+}
+
+#[tauri::command]
+fn get_backend_logs(lines: Option<usize>, backend: State<BackendManager>) -> Vec<BackendLogLine> {
+    backend.recent_logs(lines.unwrap_or(200))
 }
 
 #[tauri::command]
-async fn show_main_window(app: tauri::AppHandle) -> Result<(), String> {
+async fn restart_backend(backend: State<'_, BackendManager>) -> Result<u32, AppError> {
+    backend.restart()
+}
+
+#[tauri::command]
+async fn stop_backend(backend: State<'_, BackendManager>) -> Result<(), AppError> {
+    backend.stop()
+}
+
+#[tauri::command]
+async fn show_main_window(app: tauri::AppHandle) -> Result<(), AppError> {
     // 获取主窗口
     if let Some(main_window) = app.get_window("main") {
         // 显示窗口
-        main_window.show().map_err(|e| e.to_string())?;
+        main_window.show()?;
         // 聚焦窗口
-        main_window.set_focus().map_err(|e| e.to_string())?;
-        println!("主窗口已唤起");
+        main_window.set_focus()?;
+        info!("主窗口已唤起");
     } else {
-        return Err("主窗口不存在".to_string());
+        return Err(AppError::Window("主窗口不存在".to_string()));
     }
     Ok(())
 }
 
-#[tauri::command]
-async fn create_desktop_pet_window(app: tauri::AppHandle) -> Result<(), String> {
-    // 检查窗口是否已存在
-    if app.get_window("desktop-pet").is_some() {
-        println!("Desktop pet window already exists");
-        return Ok(());
-    }
-
-    // 创建透明的桌宠窗口
-    let window = WindowBuilder::new(
-        &app,
-        "desktop-pet",
-        WindowUrl::App("desktop-pet-window".into())
-    )
-    .title("")  // 空标题
-    .inner_size(250.0, 280.0)
-    .fullscreen(false)
-    .transparent(true)  // 关键：启用操作系统级别的透明窗口
-    .always_on_top(true)
-    .skip_taskbar(true)
-    .decorations(false)  // 无边框，配合透明效果
-    .resizable(false)
-    .visible(true)
-    .focused(false)
-    .min_inner_size(250.0, 280.0)
-    .max_inner_size(250.0, 280.0)
-    .build()
-    .map_err(|e| e.to_string())?;
-
-    // 定位到右下角
-    if let Ok(monitor) = window.primary_monitor() {
-        if let Some(monitor) = monitor {
-            let screen_size = monitor.size();
-            let x = screen_size.width as i32 - 250 - 20; // 窗口宽度250px + 边距20px
-            let y = screen_size.height as i32 - 280 - 20; // 窗口高度280px + 边距20px
-            
-            window.set_position(tauri::LogicalPosition::new(x, y)).map_err(|e| e.to_string())?;
-            println!("Desktop pet window created and positioned at bottom right: ({}, {})", x, y);
-        }
-    }
-
-    Ok(())
-}
-
-
-
-fn start_backend_server() {
-    // 检查 Go 是否安装
-    let go_available = Command::new("go")
-        .arg("version")
-        .output()
-        .is_ok();
-
-    if !go_available {
-        println!("Warning: Go is not installed or not in PATH. Backend server will not start.");
-        return;
-    }
+fn main() {
+    let cli_args = cli::CliArgs::from_env();
+    let context = tauri::generate_context!();
+    logging::init(tauri::api::path::app_log_dir(context.config()).as_deref());
+    let cli_args = cli_args.unwrap_or_else(|e| {
+        warn!("Ignoring invalid command line arguments: {}", e);
+        cli::CliArgs::default()
+    });
 
-    // 检查 server 目录是否存在
-    let server_path = Path::new("../server");
-    if !server_path.exists() {
-        println!("Warning: Server directory not found. Backend server will not start.");
+    // 已有实例运行时把参数转发过去并直接退出，避免重复启动后端和桌宠
+    let instance = single_instance::acquire();
+    if let Instance::Secondary = instance {
+        info!("声驭智核 is already running, forwarded arguments to the existing instance");
         return;
     }
-
-    // 启动 Go 后端服务
-    let mut child = match Command::new("go")
-        .arg("run")
-        .arg("cmd/server/main.go")
-        .arg("-mode=test")
-        .arg("-addr=:7072")
-        .current_dir("../server")
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-    {
-        Ok(child) => {
-            println!("Go backend server started on port 7072");
-            child
-        }
-        Err(e) => {
-            println!("Failed to start Go backend server: {}", e);
-            return;
-        }
+    let instance_listener = match instance {
+        Instance::Primary(listener) => Some(listener),
+        _ => None,
     };
-
-    // 在后台运行，不等待进程结束
-    std::thread::spawn(move || {
-        let _ = child.wait();
-    });
-}
-
-fn main() {
-        let app_state = AppState {
-            theme: "dark".to_string(),
-            window_title: "声驭智核".to_string(),
-        };
+    let pick_profile = profiles::select(context.config(), cli_args.profile.as_deref());
 
     tauri::Builder::default()
-        .manage(app_state)
-        .invoke_handler(tauri::generate_handler![
+        .manage(BackendManager::new())
+        .manage(Network::new())
+        .manage(AudioCapture::new())
+        .manage(AudioMeter::new())
+        .manage(AudioPlayer::new())
+        .manage(TtsStreamer::new())
+        .manage(WakeWordListener::new())
+        .manage(MicIndicator::new())
+        .manage(WsBridge::new())
+        .manage(MqttClient::new())
+        .manage(LocalApi::new())
+        .manage(Updater::new())
+        .manage(ModelDownloads::new())
+        .manage(Notifier::new())
+        .manage(Privacy::new())
+        .manage(PetStateMachine::new())
+        .manage(PetBubble::new())
+        .manage(FullscreenWatcher::new())
+        .manage(Pipeline::new())
+        .manage(LlamaServer::new())
+        .manage(Generations::new())
+        .manage(MemoryJob::new())
+        .manage(Retention::new())
+        .manage(ResourceMonitor::new())
+        .manage(Power::new())
+        .manage(Wipe::new())
+        .manage(Shutdown::new())
+        .manage(IntentRouter::new())
+        .manage(EventBus::new())
+        .manage(Dnd::new())
+        .manage(WeatherCache::new())
+        .manage(Timers::new())
+        .manage(AppLauncher::new())
+        .system_tray(tray::build_tray())
+        .on_system_tray_event(tray::handle_tray_event)
+        .invoke_handler(capabilities::guard(tauri::generate_handler![
             greet,
             get_app_info,
             set_theme,
             get_theme,
+            settings::get_settings,
+            settings::update_settings,
             export_data,
             import_data,
             check_backend_status,
-            create_desktop_pet_window,
+            get_backend_url,
+            get_backend_logs,
+            restart_backend,
+            stop_backend,
+            pet::create_desktop_pet_window,
+            pet::start_pet_drag,
+            pet::set_pet_position,
+            pet::get_pet_position,
+            pet::list_monitors,
+            pet::move_pet_to_monitor,
+            pet::set_pet_click_through,
+            pet::toggle_pet_click_through,
+            pet::show_desktop_pet,
+            pet::hide_desktop_pet,
+            pet::toggle_desktop_pet,
+            pet::set_pet_size,
+            hotkeys::list_hotkeys,
+            hotkeys::register_hotkey,
+            hotkeys::unregister_hotkey,
+            autostart::get_autostart,
+            autostart::set_autostart,
+            audio::capture::list_input_devices,
+            audio::indicator::get_mic_status,
+            audio::capture::start_recording,
+            audio::capture::stop_recording,
+            audio::meter::start_audio_metering,
+            audio::meter::stop_audio_metering,
+            audio::processing::set_audio_processing,
+            audio::playback::list_output_devices,
+            audio::playback::play_audio,
+            audio::playback::stop_audio,
+            audio::playback::set_playback_volume,
+            audio::playback::set_playback_device,
+            audio::tts_stream::stream_tts,
+            audio::tts_stream::cancel_tts,
+            audio::wakeword::get_wake_word_status,
+            audio::wakeword::set_wake_word_enabled,
+            audio::wakeword::set_wake_word_sensitivity,
+            storage::save_message,
+            storage::list_conversations,
+            storage::list_messages,
+            storage::search_messages,
+            storage::delete_conversation,
+            storage::get_database_encryption,
+            storage::encrypt_database,
+            scheduler::create_reminder,
+            scheduler::list_reminders,
+            scheduler::cancel_reminder,
+            timers::start_timer,
+            timers::start_stopwatch,
+            timers::list_timers,
+            timers::cancel_timer,
+            calendar::list_upcoming_events,
+            calendar::create_event,
+            calendar::sync_calendars,
+            calendar::add_calendar_source,
+            calendar::remove_calendar_source,
+            weather::get_weather,
+            screenshot::capture_screen,
+            screenshot::capture_window,
+            screenshot::set_screen_capture_allowed,
+            pet::state::set_pet_state,
+            pet::idle::set_pet_idle_settings,
+            pet::state::get_pet_state,
+            pet::bubble::show_pet_bubble,
+            pet::bubble::hide_pet_bubble,
+            pet::bubble::get_pet_bubble,
+            pet::menu::show_pet_menu,
+            pet::menu::select_pet_menu_item,
+            fullscreen::get_fullscreen_status,
+            fullscreen::set_fullscreen_auto_hide,
+            stt::transcribe,
+            stt::file::transcribe_file,
+            stt::set_stt_provider,
+            tts::synthesize,
+            tts::list_voices,
+            tts::set_voice,
+            pipeline::run_turn,
+            pipeline::cancel_turn,
+            pipeline::get_last_turn_timing,
+            llm::set_llm_provider,
+            llm::set_local_llm,
+            llm::stream::chat_stream,
+            llm::cancel_generation,
+            memory::list_memories,
+            memory::update_memory,
+            memory::delete_memory,
+            memory::summarize_memories,
+            retention::purge_history,
+            resources::get_resource_usage,
+            power::get_power_state,
+            power::set_power_policy,
+            wipe::request_wipe_token,
+            wipe::wipe_all_data,
+            persona::list_personas,
+            persona::get_active_persona,
+            persona::create_persona,
+            persona::set_active_persona,
+            persona::delete_persona,
+            profiles::list_profiles,
+            profiles::create_profile,
+            profiles::switch_profile,
+            profiles::set_profile_picker,
+            plugins::list_plugins,
+            plugins::enable_plugin,
+            plugins::reload_plugins,
+            intents::test_intent,
+            intents::set_intent_rules,
+            deep_link::open_deep_link,
+            privacy::set_privacy_mode,
+            privacy::get_privacy_status,
+            privacy::set_privacy_auto_apps,
+            app_context::get_active_app_context,
+            app_context::set_app_context,
+            system_control::get_system_volume,
+            system_control::set_system_volume,
+            system_control::media_play_pause,
+            system_control::media_next,
+            system_control::media_prev,
+            system_control::lock_screen,
+            system_control::sleep_display,
+            system_control::set_system_control,
+            launcher::launch_app,
+            launcher::open_path,
+            launcher::open_url,
+            launcher::list_apps,
+            file_search::search_files,
+            file_search::set_file_search_folders,
+            file_search::reindex_files,
+            file_search::get_file_index_status,
+            notes::create_note,
+            notes::update_note,
+            notes::get_note,
+            notes::list_notes,
+            notes::list_note_tags,
+            notes::delete_note,
+            notes::set_notes_folder,
+            web_clip::clip_url,
+            ocr::ocr_image,
+            ocr::list_ocr_languages,
+            ocr::install_ocr_language,
+            ocr::set_ocr_language,
+            models::list_models,
+            models::download_model,
+            models::cancel_model_download,
+            models::delete_model,
+            models::get_models_disk_usage,
+            acceleration::get_system_capabilities,
+            acceleration::set_acceleration_backend,
+            notifications::notify,
+            notifications::set_notification_muted,
+            updater::check_for_updates,
+            updater::download_update,
+            updater::install_update_and_restart,
+            updater::set_update_channel,
+            logging::set_log_level,
+            logging::get_recent_logs,
+            secrets::store_secret,
+            secrets::get_secret,
+            secrets::delete_secret,
+            knowledge::index_document,
+            knowledge::import_documents,
+            knowledge::search_knowledge,
+            knowledge::list_knowledge_documents,
+            knowledge::remove_knowledge_document,
+            knowledge::reindex_knowledge,
+            knowledge::embeddings::semantic_search,
+            knowledge::embeddings::build_embeddings,
+            ws_bridge::connect_ws,
+            ws_bridge::disconnect_ws,
+            ws_bridge::get_ws_status,
+            ws_bridge::send_ws_message,
+            mqtt::mqtt_publish,
+            mqtt::mqtt_subscribe,
+            mqtt::mqtt_unsubscribe,
+            mqtt::get_mqtt_status,
+            mqtt::reconnect_mqtt,
+            webhooks::list_webhooks,
+            webhooks::save_webhook,
+            webhooks::delete_webhook,
+            webhooks::run_webhook,
+            local_api::get_local_api_status,
+            local_api::get_local_api_token,
+            local_api::regenerate_local_api_token,
+            api::backend_request,
+            network::set_network_settings,
+            offline::queue_backend_write,
+            offline::get_connectivity_status,
+            offline::list_queued_writes,
+            offline::discard_queued_write,
+            backup::backup_now,
+            backup::list_backups,
+            backup::restore_backup,
+            events::set_event_filter,
+            quick_ask::show_quick_ask,
+            quick_ask::close_quick_ask,
+            quick_ask::submit_quick_ask,
+            captions::set_captions_enabled,
+            captions::set_caption_monitor,
+            captions::set_caption_font_size,
+            dnd::set_dnd,
+            dnd::get_dnd_status,
+            i18n::set_locale,
+            i18n::get_locale,
+            macos::set_hide_dock_icon,
+            onboarding::get_onboarding_state,
+            onboarding::complete_step,
+            permissions::get_permission_status,
+            permissions::request_permission,
+            permissions::open_permission_settings,
+            linux::get_display_capabilities,
+            linux::capture_pet_backdrop,
+            analytics::record_feature_usage,
+            analytics::get_usage_stats,
+            analytics::clear_usage_stats,
+            analytics::export_usage_stats,
             show_main_window
-        ])
-        .setup(|app| {
+        ]))
+        .setup(move |app| {
+            if let Some(listener) = instance_listener {
+                single_instance::listen(app.handle(), listener);
+            }
+
+            // 加载持久化的用户设置
+            let config_dir = profiles::config_dir(&app.handle())?;
+            let data_dir = profiles::data_dir(&app.handle())?;
+            wipe::apply_pending(&data_dir, &config_dir);
+            // 上次运行中选择恢复的备份在这里替换数据库和设置文件
+            backup::apply_pending_restore(&data_dir, &config_dir);
+            let store = SettingsStore::new(&config_dir);
+            let mut settings = store.load();
+            info!("Loaded settings from {}", store.path().display());
+            logging::apply_saved_level(&settings.log_level);
+            i18n::init(&settings.language);
+            if secrets::migrate_plaintext(&mut settings) {
+                store.save(&settings).ok();
+            }
+            let backend_settings = settings.backend.clone();
+
+            app.manage(AppState {
+                settings: Mutex::new(settings),
+                store,
+                storage: Storage::open(&data_dir)?,
+                knowledge: KnowledgeBase::open(&data_dir)?,
+                scheduler: Scheduler::open(&data_dir)?,
+                offline: Offline::open(&data_dir)?,
+                analytics: Analytics::open(&data_dir)?,
+                window_title: profiles::decorate_title(&i18n::t("app.name")),
+            });
+            app.manage(Calendar::open(&data_dir)?);
+            app.manage(FileIndex::open(&data_dir)?);
+            app.manage(Notes::open(&data_dir)?);
+            app.manage(Personas::open(&data_dir)?);
+            app.manage(Plugins::open(&data_dir));
+
+            // 只执行导出等命令行动作时不启动界面和后端
+            if cli_args.is_headless() {
+                cli::run(&app.handle(), &cli_args);
+                app.handle().exit(0);
+                return Ok(());
+            }
+
             let window = app.get_window("main").unwrap();
             
             // Set window properties
-            window.set_title("声驭智核").unwrap();
+            let state = app.state::<AppState>();
+            window.set_title(&state.window_title).unwrap();
+            window_state::restore(&window);
+            // 选择配置后再显示主窗口
+            if pick_profile {
+                if let Err(e) = profiles::open_picker(&app.handle()) {
+                    warn!("Failed to open profile picker: {}", e);
+                    window.show().ok();
+                }
+            } else if !cli_args.minimized && !autostart::launched_minimized() {
+                window.show().ok();
+            }
             
+            linux::detect();
+            // 托盘在加载设置前创建，按设置的语言重设文案
+            tray::retitle(&app.handle());
+            network::start(&app.handle());
+            mqtt::start(&app.handle());
+            local_api::start(&app.handle());
+
+            // 注册全局快捷键
+            hotkeys::register_all(&app.handle());
+            audio::indicator::start(&app.handle());
+            power::start(&app.handle());
+            resources::start(&app.handle());
+            privacy::start(&app.handle());
+            dnd::start(&app.handle());
+            macos::start(&app.handle());
+            analytics::prune_expired(&app.handle());
+            acceleration::start();
+            power::register(
+                &app.handle(),
+                PowerConsumer::WakeWord,
+                audio::wakeword::on_power_changed,
+            );
+            audio::wakeword::start_if_enabled(&app.handle());
+            knowledge::start_watching(&app.handle());
+            if let Err(e) = settings::watch(&app.handle()) {
+                warn!("Failed to watch settings file: {}", e);
+            }
+            scheduler::start(app.handle());
+            timers::start(app.handle());
+            calendar::start(app.handle());
+            file_search::start(app.handle());
+            memory::start(app.handle());
+            retention::start(app.handle());
+            pet::idle::start(app.handle());
+            fullscreen::start(app.handle());
+            captions::start_if_enabled(&app.handle());
+
+            // 注册 lingecho:// 协议
+            if let Err(e) = deep_link::register_scheme() {
+                warn!("Failed to register URL scheme: {}", e);
+            }
+
             // 启动 Go 后端服务
-            start_backend_server();
+            let backend = app.state::<BackendManager>();
+            let started = BackendLaunch::resolve(&app.handle(), &backend_settings)
+                .and_then(|launch| backend.configure(app.handle(), launch))
+                .and_then(|_| backend.start());
+            if let Err(e) = started {
+                warn!("{}. Backend server will not start.", e);
+            }
+            backend::spawn_health_monitor(app.handle());
+            offline::start(app.handle());
+            backup::start(app.handle());
             
-            // 创建透明的桌宠窗口
+            // 创建透明的桌宠窗口，之后执行启动参数中的动作
             let app_handle = app.handle().clone();
+            let startup_args = cli_args.clone();
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = create_desktop_pet_window(app_handle).await {
-                    println!("Failed to create desktop pet window: {}", e);
+                if let Err(e) = pet::create_desktop_pet_window(app_handle.clone()).await {
+                    warn!("Failed to create desktop pet window: {}", e);
                 }
+                cli::run(&app_handle, &startup_args);
             });
             
-            println!("声驭智核 application started!");
+            info!("声驭智核 application started!");
             
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .on_window_event(|event| match event.event() {
+            // 关闭主窗口时隐藏到托盘，通过托盘菜单退出应用
+            WindowEvent::CloseRequested { api, .. } if event.window().label() == "main" => {
+                api.prevent_close();
+                event.window().hide().ok();
+            }
+            // 主窗口销毁时结束后端，避免遗留占用端口的 Go 进程
+            WindowEvent::Destroyed if event.window().label() == "main" => {
+                event.window().state::<BackendManager>().stop().ok();
+            }
+            WindowEvent::Moved(_) | WindowEvent::Resized(_) if event.window().label() == window_state::MAIN_LABEL => {
+                window_state::remember(event.window());
+            }
+            WindowEvent::Focused(true) if event.window().label() == "main" => {
+                notifications::handle_focus(&event.window().app_handle());
+            }
+            WindowEvent::Moved(position) if event.window().label() == pet::PET_LABEL => {
+                pet::remember_position(event.window(), *position);
+                pet::bubble::follow(&event.window().app_handle());
+            }
+            WindowEvent::Focused(false) if event.window().label() == quick_ask::QUICK_ASK_LABEL => {
+                event.window().close().ok();
+            }
+            _ => {}
+        })
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| match event {
+            #[cfg(feature = "updater")]
+            RunEvent::Updater(event) => updater::handle_event(app, event),
+            RunEvent::Exit => shutdown::on_exit(app),
+            _ => {}
+        });
 }
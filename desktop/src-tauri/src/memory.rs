@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+use crate::llm::{self, BackendCredentials, ChatMessage};
+use crate::storage::{Memory, Message, Role};
+use crate::{i18n, AppState};
+
+const MIN_INTERVAL_MINS: u32 = 5;
+// 作为上下文发送的原始消息条数上限，更早的内容只通过摘要提供
+const CONTEXT_MESSAGES: usize = 40;
+const MAX_SUMMARY_CHARS: usize = 2000;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MemorySettings {
+    pub enabled: bool,
+    pub interval_mins: u32,
+    // 每个会话最近的若干条消息保持原样，不参与摘要
+    pub keep_recent: u32,
+    // 每条记忆最多覆盖的消息数
+    pub batch_size: u32,
+    // 待摘要的消息少于该数量时等待下一轮
+    pub min_batch: u32,
+}
+
+impl Default for MemorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_mins: 30,
+            keep_recent: 20,
+            batch_size: 40,
+            min_batch: 10,
+        }
+    }
+}
+
+// 后台摘要任务；后端模型需要用户凭证，使用最近一次对话提供的凭证（只保存在内存中）
+#[derive(Default)]
+pub struct MemoryJob {
+    wake: Notify,
+    credentials: Mutex<Option<BackendCredentials>>,
+}
+
+impl MemoryJob {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn remember_credentials(&self, credentials: &BackendCredentials) {
+        if credentials.api_key.is_empty() && credentials.token.is_none() {
+            return;
+        }
+        if let Ok(mut current) = self.credentials.lock() {
+            *current = Some(credentials.clone());
+        }
+    }
+
+    pub fn credentials(&self) -> BackendCredentials {
+        self.credentials
+            .lock()
+            .ok()
+            .and_then(|credentials| credentials.clone())
+            .unwrap_or_default()
+    }
+}
+
+fn memory_settings(app: &AppHandle) -> Result<MemorySettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.memory.clone())
+}
+
+fn transcript(messages: &[Message]) -> String {
+    let (user, assistant) = (i18n::t("memory.user"), i18n::t("memory.assistant"));
+    messages
+        .iter()
+        .filter(|message| message.role != Role::System && !message.text.trim().is_empty())
+        .map(|message| {
+            let speaker = if message.role == Role::User { &user } else { &assistant };
+            format!("{}: {}", speaker, message.text.trim())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// 在阻塞线程中调用；说明和对话放在同一条用户消息里，后端接口只接收一条文本
+fn summarize(app: &AppHandle, conversation_id: &str, messages: &[Message]) -> Result<String, AppError> {
+    let prompt = format!("{}\n\n{}", i18n::t("memory.prompt"), transcript(messages));
+    let credentials = app.state::<MemoryJob>().credentials();
+    let generation = llm::generate(
+        app,
+        llm::next_id(),
+        Some(conversation_id),
+        &[ChatMessage::user(prompt)],
+        credentials,
+        None,
+        &mut |_| true,
+    )?;
+    let summary: String = generation.text.trim().chars().take(MAX_SUMMARY_CHARS).collect();
+    if summary.is_empty() {
+        return Err(AppError::Internal("模型没有返回摘要".to_string()));
+    }
+    Ok(summary)
+}
+
+// 在阻塞线程中调用：把每个会话里较早的消息按批摘要，最近的 keep_recent 条保持原样
+fn summarize_all(app: &AppHandle, settings: &MemorySettings) -> Result<usize, AppError> {
+    let storage = &app.state::<AppState>().storage;
+    let batch_size = settings.batch_size.max(settings.min_batch).max(1) as usize;
+    let min_batch = settings.min_batch.max(1) as usize;
+    let mut created = 0;
+    for conversation_id in storage.conversations_to_summarize(settings.keep_recent + settings.min_batch.max(1))? {
+        let messages = storage.unsummarized_messages(&conversation_id)?;
+        let eligible = messages.len().saturating_sub(settings.keep_recent as usize);
+        for batch in messages[..eligible].chunks(batch_size) {
+            if batch.len() < min_batch {
+                break;
+            }
+            match summarize(app, &conversation_id, batch) {
+                Ok(summary) => {
+                    storage.save_memory(&conversation_id, &summary, batch)?;
+                    created += 1;
+                }
+                // 模型暂时不可用时下一轮再试
+                Err(e) => {
+                    warn!("Failed to summarize conversation {}: {}", conversation_id, e);
+                    return Ok(created);
+                }
+            }
+        }
+    }
+    Ok(created)
+}
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let settings = memory_settings(&app).unwrap_or_default();
+            if settings.enabled {
+                let handle = app.clone();
+                let job_settings = settings.clone();
+                match tauri::async_runtime::spawn_blocking(move || summarize_all(&handle, &job_settings)).await {
+                    Ok(Ok(0)) => debug!("No conversation needs summarizing"),
+                    Ok(Ok(created)) => info!("Created {} conversation memories", created),
+                    Ok(Err(e)) => warn!("Memory summarization failed: {}", e),
+                    Err(e) => warn!("Memory summarization task failed: {}", e),
+                }
+            }
+            let interval = settings.interval_mins.max(MIN_INTERVAL_MINS) as u64 * 60;
+            let job = app.state::<MemoryJob>();
+            tokio::select! {
+                _ = tokio::time::sleep(Duration::from_secs(interval)) => {}
+                _ = job.wake.notified() => {}
+            }
+        }
+    });
+}
+
+// 组装对话上下文：较早内容的摘要合并为一条系统消息，随后是尚未摘要的最近消息
+pub fn context(app: &AppHandle, conversation_id: &str) -> Result<Vec<ChatMessage>, AppError> {
+    let storage = &app.state::<AppState>().storage;
+    let memories = storage.list_memories(Some(conversation_id))?;
+    let mut context = Vec::new();
+    if !memories.is_empty() {
+        let points: Vec<String> = memories
+            .iter()
+            .map(|memory| format!("- {}", memory.summary.trim()))
+            .collect();
+        context.push(ChatMessage {
+            role: Role::System,
+            content: format!("{}\n{}", i18n::t("memory.context"), points.join("\n")),
+        });
+    }
+    let messages = storage.unsummarized_messages(conversation_id)?;
+    let skip = messages.len().saturating_sub(CONTEXT_MESSAGES);
+    context.extend(
+        messages
+            .into_iter()
+            .skip(skip)
+            .filter(|message| !message.text.trim().is_empty())
+            .map(|message| ChatMessage {
+                role: message.role,
+                content: message.text,
+            }),
+    );
+    Ok(context)
+}
+
+#[tauri::command]
+pub fn list_memories(state: State<'_, AppState>, conversation_id: Option<String>) -> Result<Vec<Memory>, AppError> {
+    state.storage.list_memories(conversation_id.as_deref())
+}
+
+#[tauri::command]
+pub fn update_memory(state: State<'_, AppState>, id: String, summary: String) -> Result<Memory, AppError> {
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err(AppError::invalid("记忆内容不能为空"));
+    }
+    state.storage.update_memory(&id, summary)
+}
+
+// 删除后对应的消息不会被重新摘要
+#[tauri::command]
+pub fn delete_memory(state: State<'_, AppState>, id: String) -> Result<bool, AppError> {
+    state.storage.delete_memory(&id)
+}
+
+// 立即执行一轮摘要，不等待下一个周期
+#[tauri::command]
+pub fn summarize_memories(job: State<'_, MemoryJob>) -> Result<(), AppError> {
+    job.wake.notify_one();
+    Ok(())
+}
@@ -0,0 +1,608 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::platform::{self, TaskbarProgress};
+use crate::power::{self, PowerConsumer};
+use crate::stt::whisper;
+use crate::tts::piper;
+use crate::{i18n, network};
+
+// 模型托管在 Hugging Face，文件大小和 SHA-256 从 Hub 的目录接口获取
+const HUB_URL: &str = "https://huggingface.co";
+const MODELS_DIR: &str = "models";
+const EMBEDDINGS_DIR: &str = "models/embeddings";
+const PART_EXTENSION: &str = "part";
+// 下载进度事件的最小间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+// 因电源策略暂停时检查恢复和取消的间隔
+const POWER_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelKind {
+    Stt,
+    Tts,
+    Embedding,
+}
+
+impl ModelKind {
+    // 与各引擎查找模型的目录一致，下载后无需额外配置即可使用
+    fn dir(self) -> &'static str {
+        match self {
+            ModelKind::Stt => whisper::MODELS_DIR,
+            ModelKind::Tts => piper::MODELS_DIR,
+            ModelKind::Embedding => EMBEDDINGS_DIR,
+        }
+    }
+}
+
+struct CatalogEntry {
+    id: &'static str,
+    kind: ModelKind,
+    name: &'static str,
+    repo: &'static str,
+    revision: &'static str,
+    // 仓库中的文件路径，下载后按文件名保存到对应类型的模型目录
+    files: &'static [&'static str],
+    // 大致大小，仅用于下载前展示
+    size: u64,
+}
+
+static CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        id: "whisper-tiny",
+        kind: ModelKind::Stt,
+        name: "Whisper Tiny",
+        repo: "ggerganov/whisper.cpp",
+        revision: "main",
+        files: &["ggml-tiny.bin"],
+        size: 75 * MB,
+    },
+    CatalogEntry {
+        id: "whisper-base",
+        kind: ModelKind::Stt,
+        name: "Whisper Base",
+        repo: "ggerganov/whisper.cpp",
+        revision: "main",
+        files: &["ggml-base.bin"],
+        size: 142 * MB,
+    },
+    CatalogEntry {
+        id: "whisper-small",
+        kind: ModelKind::Stt,
+        name: "Whisper Small",
+        repo: "ggerganov/whisper.cpp",
+        revision: "main",
+        files: &["ggml-small.bin"],
+        size: 466 * MB,
+    },
+    CatalogEntry {
+        id: "piper-zh-huayan",
+        kind: ModelKind::Tts,
+        name: "Piper zh_CN-huayan-medium",
+        repo: "rhasspy/piper-voices",
+        revision: "v1.0.0",
+        files: &[
+            "zh/zh_CN/huayan/medium/zh_CN-huayan-medium.onnx",
+            "zh/zh_CN/huayan/medium/zh_CN-huayan-medium.onnx.json",
+        ],
+        size: 61 * MB,
+    },
+    CatalogEntry {
+        id: "piper-en-lessac",
+        kind: ModelKind::Tts,
+        name: "Piper en_US-lessac-medium",
+        repo: "rhasspy/piper-voices",
+        revision: "v1.0.0",
+        files: &[
+            "en/en_US/lessac/medium/en_US-lessac-medium.onnx",
+            "en/en_US/lessac/medium/en_US-lessac-medium.onnx.json",
+        ],
+        size: 61 * MB,
+    },
+    CatalogEntry {
+        id: "nomic-embed-text",
+        kind: ModelKind::Embedding,
+        name: "nomic-embed-text v1.5 (Q8_0)",
+        repo: "nomic-ai/nomic-embed-text-v1.5-GGUF",
+        revision: "main",
+        files: &["nomic-embed-text-v1.5.Q8_0.gguf"],
+        size: 140 * MB,
+    },
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelStatus {
+    pub id: String,
+    pub kind: ModelKind,
+    pub name: String,
+    pub size: u64,
+    pub installed: bool,
+    pub downloading: bool,
+    // 未下载完的部分，下次下载时从这里续传
+    pub partial_bytes: u64,
+    pub disk_bytes: u64,
+}
+
+// 模型目录的磁盘占用，包括用户自行放入的模型
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDiskUsage {
+    pub total_bytes: u64,
+    pub stt_bytes: u64,
+    pub tts_bytes: u64,
+    pub embedding_bytes: u64,
+    pub partial_bytes: u64,
+}
+
+// stage 依次为 resolving、downloading、verifying，最后是 done、failed 或 cancelled；电源策略不允许时为 paused
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelDownloadProgress {
+    pub id: String,
+    pub stage: &'static str,
+    pub downloaded: u64,
+    pub total: u64,
+    pub progress: u8,
+}
+
+// 正在进行的下载及其取消标志，同一模型同时只允许一个下载
+#[derive(Default)]
+pub struct ModelDownloads {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+impl ModelDownloads {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn begin(&self, id: &str) -> Result<Arc<AtomicBool>, AppError> {
+        let mut active = self.active.lock()?;
+        if active.contains_key(id) {
+            return Err(AppError::invalid("该模型正在下载"));
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        active.insert(id.to_string(), cancel.clone());
+        Ok(cancel)
+    }
+
+    fn finish(&self, id: &str) {
+        if let Ok(mut active) = self.active.lock() {
+            active.remove(id);
+        }
+    }
+
+    fn is_active(&self, id: &str) -> bool {
+        self.active
+            .lock()
+            .map(|active| active.contains_key(id))
+            .unwrap_or(false)
+    }
+
+    fn cancel(&self, id: &str) -> bool {
+        let active = self.active.lock();
+        match active.as_ref().ok().and_then(|active| active.get(id)) {
+            Some(cancel) => {
+                cancel.store(true, Ordering::SeqCst);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn entry(id: &str) -> Result<&'static CatalogEntry, AppError> {
+    CATALOG
+        .iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("未知的模型: {}", id)))
+}
+
+fn data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    Ok(app.path_resolver().app_data_dir().ok_or("无法获取应用数据目录")?)
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn part_path(target: &Path) -> PathBuf {
+    let mut name = target.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(PART_EXTENSION);
+    target.with_file_name(name)
+}
+
+fn file_len(path: &Path) -> u64 {
+    std::fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0)
+}
+
+fn targets(app: &AppHandle, entry: &CatalogEntry) -> Result<Vec<PathBuf>, AppError> {
+    let dir = data_dir(app)?.join(entry.kind.dir());
+    Ok(entry.files.iter().map(|file| dir.join(file_name(file))).collect())
+}
+
+// 是否已安装至少一个该类型的模型
+pub fn any_installed(app: &AppHandle, kind: ModelKind) -> bool {
+    CATALOG
+        .iter()
+        .filter(|entry| entry.kind == kind)
+        .any(|entry| targets(app, entry).is_ok_and(|targets| targets.iter().all(|target| target.is_file())))
+}
+
+fn status(app: &AppHandle, entry: &CatalogEntry, downloads: &ModelDownloads) -> Result<ModelStatus, AppError> {
+    let targets = targets(app, entry)?;
+    Ok(ModelStatus {
+        id: entry.id.to_string(),
+        kind: entry.kind,
+        name: entry.name.to_string(),
+        size: entry.size,
+        installed: targets.iter().all(|target| target.is_file()),
+        downloading: downloads.is_active(entry.id),
+        partial_bytes: targets.iter().map(|target| file_len(&part_path(target))).sum(),
+        disk_bytes: targets.iter().map(|target| file_len(target)).sum(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct HubEntry {
+    path: String,
+    size: u64,
+    // 只有 LFS 文件带有 SHA-256，配置文件等小文件只校验大小
+    lfs: Option<HubLfs>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HubLfs {
+    oid: String,
+}
+
+struct RemoteFile {
+    url: String,
+    target: PathBuf,
+    size: u64,
+    sha256: Option<String>,
+}
+
+async fn list_hub_dir(app: &AppHandle, entry: &CatalogEntry, dir: &str) -> Result<Vec<HubEntry>, AppError> {
+    let mut url = format!("{}/api/models/{}/tree/{}", HUB_URL, entry.repo, entry.revision);
+    if !dir.is_empty() {
+        url = format!("{}/{}", url, dir);
+    }
+    let response = network::client(app)
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::network("获取模型信息失败", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("获取模型信息失败", response.status()));
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| AppError::network("获取模型信息失败", e))
+}
+
+async fn resolve(app: &AppHandle, entry: &CatalogEntry) -> Result<Vec<RemoteFile>, AppError> {
+    let targets = targets(app, entry)?;
+    let mut listings: HashMap<&str, Vec<HubEntry>> = HashMap::new();
+    let mut files = Vec::new();
+    for (path, target) in entry.files.iter().zip(targets) {
+        let dir = path.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        if !listings.contains_key(dir) {
+            listings.insert(dir, list_hub_dir(app, entry, dir).await?);
+        }
+        let remote = listings[dir]
+            .iter()
+            .find(|remote| remote.path == *path)
+            .ok_or_else(|| AppError::NotFound(format!("模型仓库中找不到文件: {}", path)))?;
+        files.push(RemoteFile {
+            url: format!("{}/{}/resolve/{}/{}", HUB_URL, entry.repo, entry.revision, path),
+            target,
+            size: remote.size,
+            sha256: remote.lfs.as_ref().map(|lfs| lfs.oid.to_lowercase()),
+        });
+    }
+    Ok(files)
+}
+
+struct Reporter<'a> {
+    app: &'a AppHandle,
+    id: &'a str,
+    downloaded: u64,
+    total: u64,
+    last_emit: Option<Instant>,
+}
+
+impl Reporter<'_> {
+    fn progress(&self) -> u8 {
+        if self.total == 0 {
+            return 0;
+        }
+        (self.downloaded * 100 / self.total).min(100) as u8
+    }
+
+    fn emit(&mut self, stage: &'static str) {
+        self.last_emit = Some(Instant::now());
+        let progress = self.progress();
+        if stage == "downloading" || stage == "verifying" {
+            platform::set_progress(self.app, TaskbarProgress::Normal(progress));
+        }
+        let payload = ModelDownloadProgress {
+            id: self.id.to_string(),
+            stage,
+            downloaded: self.downloaded,
+            total: self.total,
+            progress,
+        };
+        events::publish(self.app, AppEvent::ModelDownloadProgress(payload));
+    }
+
+    fn advance(&mut self, bytes: u64) {
+        self.downloaded += bytes;
+        if self.last_emit.is_none_or(|last| last.elapsed() >= PROGRESS_INTERVAL) {
+            self.emit("downloading");
+        }
+    }
+}
+
+// 电源策略不允许时等待，期间仍可以取消
+async fn wait_for_power(app: &AppHandle, cancel: &AtomicBool, reporter: &mut Reporter<'_>) -> Result<(), AppError> {
+    if power::allowed(app, PowerConsumer::ModelDownloads) {
+        return Ok(());
+    }
+    info!("Model download {} paused by power policy", reporter.id);
+    reporter.emit("paused");
+    while !power::allowed(app, PowerConsumer::ModelDownloads) {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(AppError::Cancelled("模型下载已取消".to_string()));
+        }
+        tokio::time::sleep(POWER_CHECK_INTERVAL).await;
+    }
+    info!("Model download {} resumed", reporter.id);
+    reporter.emit("downloading");
+    Ok(())
+}
+
+// 下载到 .part 文件；已有部分时用 Range 请求续传，服务器不支持续传则从头下载；
+// 下载中电源策略变为不允许时返回 None，已下载的部分保留，之后续传
+async fn download_file(
+    app: &AppHandle,
+    file: &RemoteFile,
+    cancel: &AtomicBool,
+    reporter: &mut Reporter<'_>,
+) -> Result<Option<PathBuf>, AppError> {
+    let part = part_path(&file.target);
+    let mut offset = file_len(&part);
+    if offset > file.size {
+        std::fs::remove_file(&part)?;
+        offset = 0;
+    }
+    reporter.advance(offset);
+    if offset == file.size {
+        return Ok(Some(part));
+    }
+
+    let mut request = network::client(app).get(&file.url);
+    if offset > 0 {
+        info!("Resuming {} from {} bytes", file.url, offset);
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", offset));
+    }
+    let mut response = request.send().await.map_err(|e| AppError::network("下载模型失败", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("下载模型失败", response.status()));
+    }
+    let mut output = if response.status() == reqwest::StatusCode::PARTIAL_CONTENT {
+        OpenOptions::new().append(true).open(&part)?
+    } else {
+        reporter.downloaded -= offset;
+        offset = 0;
+        File::create(&part)?
+    };
+
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| AppError::network("下载模型失败", e))?
+    {
+        if cancel.load(Ordering::SeqCst) {
+            return Err(AppError::Cancelled("模型下载已取消".to_string()));
+        }
+        if !power::allowed(app, PowerConsumer::ModelDownloads) {
+            output.flush()?;
+            // 续传时会重新计入已下载的部分
+            reporter.downloaded -= offset;
+            return Ok(None);
+        }
+        output.write_all(&chunk)?;
+        offset += chunk.len() as u64;
+        reporter.advance(chunk.len() as u64);
+    }
+    output.flush()?;
+    if offset != file.size {
+        return Err(AppError::Network {
+            message: "模型文件下载不完整".to_string(),
+            details: Some(format!("{} / {} bytes", offset, file.size)),
+        });
+    }
+    Ok(Some(part))
+}
+
+fn file_sha256(path: &Path) -> Result<String, AppError> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// 校验通过后才替换为正式文件名，校验失败删除已下载的内容
+async fn verify(part: PathBuf, file: &RemoteFile) -> Result<(), AppError> {
+    if let Some(expected) = file.sha256.clone() {
+        let path = part.clone();
+        let actual = tauri::async_runtime::spawn_blocking(move || file_sha256(&path)).await??;
+        if actual != expected {
+            std::fs::remove_file(&part)?;
+            return Err(AppError::Network {
+                message: "模型文件校验失败，请重新下载".to_string(),
+                details: Some(format!("expected sha256 {}, got {}", expected, actual)),
+            });
+        }
+    }
+    std::fs::rename(&part, &file.target)?;
+    Ok(())
+}
+
+async fn install(
+    app: &AppHandle,
+    entry: &CatalogEntry,
+    cancel: &AtomicBool,
+    reporter: &mut Reporter<'_>,
+) -> Result<PathBuf, AppError> {
+    reporter.emit("resolving");
+    let files = resolve(app, entry).await?;
+    let dir = data_dir(app)?.join(entry.kind.dir());
+    std::fs::create_dir_all(&dir)?;
+
+    // 已经存在且大小一致的文件不再下载
+    let pending: Vec<&RemoteFile> = files
+        .iter()
+        .filter(|file| file_len(&file.target) != file.size || !file.target.is_file())
+        .collect();
+    reporter.total = pending.iter().map(|file| file.size).sum();
+    for file in pending {
+        let part = loop {
+            wait_for_power(app, cancel, reporter).await?;
+            if let Some(part) = download_file(app, file, cancel, reporter).await? {
+                break part;
+            }
+        };
+        reporter.emit("verifying");
+        verify(part, file).await?;
+    }
+    Ok(dir)
+}
+
+#[tauri::command]
+pub fn list_models(app: AppHandle, downloads: State<'_, ModelDownloads>) -> Result<Vec<ModelStatus>, AppError> {
+    CATALOG.iter().map(|entry| status(&app, entry, &downloads)).collect()
+}
+
+// 下载目录中的模型，进度以 model-download-progress 事件推送；中断后再次调用会续传
+#[tauri::command]
+pub async fn download_model(
+    app: AppHandle,
+    downloads: State<'_, ModelDownloads>,
+    id: String,
+) -> Result<ModelStatus, AppError> {
+    let entry = entry(&id)?;
+    let cancel = downloads.begin(&id)?;
+    info!("Downloading model {}", id);
+    platform::set_progress(&app, TaskbarProgress::Indeterminate);
+    let mut reporter = Reporter {
+        app: &app,
+        id: &id,
+        downloaded: 0,
+        total: 0,
+        last_emit: None,
+    };
+    let result = install(&app, entry, &cancel, &mut reporter).await;
+    downloads.finish(&id);
+
+    match &result {
+        Ok(dir) => {
+            info!("Model {} installed to {}", id, dir.display());
+            reporter.emit("done");
+            platform::finished(&app, i18n::t("toast.download_done"), entry.name.to_string(), Some(dir));
+        }
+        Err(AppError::Cancelled(_)) => {
+            info!("Model download {} cancelled", id);
+            reporter.emit("cancelled");
+            platform::set_progress(&app, TaskbarProgress::Hidden);
+        }
+        Err(e) => {
+            warn!("Failed to download model {}: {}", id, e);
+            reporter.emit("failed");
+            let operation = format!("model?id={}", id);
+            platform::failed(
+                &app,
+                &operation,
+                i18n::t("toast.download_failed"),
+                i18n::translate(e.message()),
+            );
+        }
+    }
+    result?;
+    status(&app, entry, &downloads)
+}
+
+// 已下载的部分会保留，下次下载时续传
+#[tauri::command]
+pub fn cancel_model_download(downloads: State<'_, ModelDownloads>, id: String) -> Result<bool, AppError> {
+    entry(&id)?;
+    Ok(downloads.cancel(&id))
+}
+
+// 同时删除未下载完的部分
+#[tauri::command]
+pub fn delete_model(app: AppHandle, downloads: State<'_, ModelDownloads>, id: String) -> Result<ModelStatus, AppError> {
+    let entry = entry(&id)?;
+    if downloads.is_active(&id) {
+        return Err(AppError::invalid("模型正在下载，请先取消下载"));
+    }
+    for target in targets(&app, entry)? {
+        for path in [part_path(&target), target] {
+            match std::fs::remove_file(&path) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(AppError::io(format!("无法删除模型文件 {}", path.display()), e)),
+            }
+        }
+    }
+    info!("Deleted model {}", id);
+    status(&app, entry, &downloads)
+}
+
+// 返回 (全部文件大小, 其中 .part 文件的大小)
+fn dir_usage(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+    let mut usage = (0, 0);
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let (total, partial) = if path.is_dir() {
+            dir_usage(&path)
+        } else {
+            let len = file_len(&path);
+            let partial = path.extension().is_some_and(|ext| ext == PART_EXTENSION);
+            (len, if partial { len } else { 0 })
+        };
+        usage.0 += total;
+        usage.1 += partial;
+    }
+    usage
+}
+
+#[tauri::command]
+pub fn get_models_disk_usage(app: AppHandle) -> Result<ModelDiskUsage, AppError> {
+    let data = data_dir(&app)?;
+    let (total_bytes, partial_bytes) = dir_usage(&data.join(MODELS_DIR));
+    Ok(ModelDiskUsage {
+        total_bytes,
+        stt_bytes: dir_usage(&data.join(ModelKind::Stt.dir())).0,
+        tts_bytes: dir_usage(&data.join(ModelKind::Tts.dir())).0,
+        embedding_bytes: dir_usage(&data.join(ModelKind::Embedding.dir())).0,
+        partial_bytes,
+    })
+}
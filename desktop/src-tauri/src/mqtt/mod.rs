@@ -0,0 +1,429 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{network, secrets, AppState};
+
+pub mod packet;
+
+use packet::{Packet, Publish};
+
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(60);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+const MIN_KEEP_ALIVE_SECS: u16 = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub tls: bool,
+    // 密码保存在系统钥匙串的 mqtt.password 中
+    pub username: Option<String>,
+    // 为空时自动生成；同一个 id 的两个客户端会互相挤掉连接
+    pub client_id: String,
+    pub keep_alive_secs: u16,
+    // 连接后订阅的主题，支持 + 和 # 通配符
+    pub subscriptions: Vec<String>,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: "localhost".to_string(),
+            port: 1883,
+            tls: false,
+            username: None,
+            client_id: String::new(),
+            keep_alive_secs: 60,
+            subscriptions: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MqttStatus {
+    pub connected: bool,
+    pub broker: Option<String>,
+    pub subscriptions: Vec<String>,
+}
+
+// 收到的消息；payload 能按 JSON 解析时为解析结果，否则为文本
+#[derive(Debug, Clone, Serialize)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: Value,
+    pub retained: bool,
+}
+
+enum Command {
+    Publish(Publish),
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+// 常驻的 MQTT 客户端，断线后按退避间隔自动重连并恢复订阅
+#[derive(Default)]
+pub struct MqttClient {
+    task: Mutex<Option<JoinHandle<()>>>,
+    outgoing: Mutex<Option<mpsc::UnboundedSender<Command>>>,
+    broker: Mutex<Option<String>>,
+    // 设置中的订阅加上运行期间通过命令增加的订阅
+    topics: Mutex<BTreeSet<String>>,
+    connected: AtomicBool,
+}
+
+impl MqttClient {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> MqttStatus {
+        MqttStatus {
+            connected: self.connected.load(Ordering::SeqCst),
+            broker: self.broker.lock().ok().and_then(|broker| broker.clone()),
+            subscriptions: self
+                .topics
+                .lock()
+                .map(|topics| topics.iter().cloned().collect())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn stop(&self) {
+        if let Some(task) = self.task.lock().ok().and_then(|mut task| task.take()) {
+            task.abort();
+        }
+        if let Ok(mut outgoing) = self.outgoing.lock() {
+            outgoing.take();
+        }
+        if let Ok(mut broker) = self.broker.lock() {
+            broker.take();
+        }
+        self.connected.store(false, Ordering::SeqCst);
+    }
+
+    fn send(&self, command: Command) -> Result<(), AppError> {
+        let outgoing = self.outgoing.lock()?;
+        let sender = outgoing
+            .as_ref()
+            .ok_or_else(|| AppError::unavailable("MQTT 未连接", "请先在设置中配置并启用 MQTT"))?;
+        sender
+            .send(command)
+            .map_err(|e| AppError::unavailable("MQTT 连接已断开", e))
+    }
+}
+
+// 按当前设置重新连接，未启用时断开
+pub fn apply(app: &AppHandle) {
+    let client = app.state::<MqttClient>();
+    client.stop();
+    let Ok(settings) = app
+        .state::<AppState>()
+        .settings
+        .lock()
+        .map(|settings| settings.mqtt.clone())
+    else {
+        return;
+    };
+    if let Ok(mut topics) = client.topics.lock() {
+        *topics = settings
+            .subscriptions
+            .iter()
+            .map(|topic| topic.trim().to_string())
+            .filter(|topic| !topic.is_empty())
+            .collect();
+    }
+    if !settings.enabled || settings.host.trim().is_empty() {
+        events::publish(app, AppEvent::MqttStatusChanged(client.status()));
+        return;
+    }
+    let broker = format!("{}:{}", settings.host.trim(), settings.port);
+    if let Ok(mut current) = client.broker.lock() {
+        *current = Some(broker);
+    }
+    let task = tauri::async_runtime::spawn(run(app.clone(), settings));
+    if let Ok(mut current) = client.task.lock() {
+        *current = Some(task);
+    };
+}
+
+pub fn start(app: &AppHandle) {
+    apply(app);
+}
+
+fn set_connected(app: &AppHandle, connected: bool) {
+    let client = app.state::<MqttClient>();
+    if client.connected.swap(connected, Ordering::SeqCst) != connected {
+        events::publish(app, AppEvent::MqttStatusChanged(client.status()));
+    }
+}
+
+fn forward(app: &AppHandle, publish: Publish) {
+    let text = String::from_utf8_lossy(&publish.payload).into_owned();
+    let payload = serde_json::from_str::<Value>(&text).unwrap_or(Value::String(text));
+    let message = MqttMessage {
+        topic: publish.topic,
+        payload,
+        retained: publish.retain,
+    };
+    events::publish(app, AppEvent::MqttMessage(message));
+}
+
+// 与 HTTP 请求一样遵循网络设置中的代理和额外信任的 CA 证书
+async fn open(app: &AppHandle, settings: &MqttSettings) -> Result<Box<dyn network::Stream>, AppError> {
+    let host = settings.host.trim();
+    tokio::time::timeout(CONNECT_TIMEOUT, network::config(app).connect(host, settings.port, settings.tls))
+        .await
+        .map_err(|_| AppError::unavailable("连接 MQTT 服务器超时", host))?
+}
+
+fn refused(code: u8) -> AppError {
+    let reason = match code {
+        1 => "unacceptable protocol version",
+        2 => "client identifier rejected",
+        3 => "server unavailable",
+        4 => "bad username or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    };
+    AppError::unavailable("MQTT 服务器拒绝连接", format!("{} ({})", reason, code))
+}
+
+// 报文标识只需在未确认的报文之间唯一，跳过 0
+fn next_packet_id(current: &mut u16) -> u16 {
+    *current = current.checked_add(1).unwrap_or(1);
+    *current
+}
+
+// 一次连接的完整生命周期，连接成功后重置退避间隔
+async fn session(app: &AppHandle, settings: &MqttSettings, backoff: &mut Duration) -> Result<(), AppError> {
+    let password = secrets::get(secrets::MQTT_PASSWORD).unwrap_or_else(|e| {
+        warn!("Failed to read MQTT password: {}", e);
+        None
+    });
+    let client_id = match settings.client_id.trim() {
+        "" => format!("lingecho-{}", &uuid::Uuid::new_v4().simple().to_string()[..12]),
+        id => id.to_string(),
+    };
+    let keep_alive_secs = settings.keep_alive_secs.max(MIN_KEEP_ALIVE_SECS);
+
+    let (reader, mut writer) = tokio::io::split(open(app, settings).await?);
+    let connect = packet::Connect {
+        client_id: &client_id,
+        username: settings.username.as_deref().filter(|username| !username.is_empty()),
+        password: password.as_deref(),
+        keep_alive_secs,
+    };
+    let connect = packet::connect(&connect).map_err(|e| AppError::invalid(format!("MQTT 连接参数无效: {}", e)))?;
+    writer.write_all(&connect).await?;
+    let mut reader = BufReader::new(reader);
+    match tokio::time::timeout(CONNECT_TIMEOUT, packet::read(&mut reader)).await {
+        Ok(Ok(Packet::ConnAck { code: 0 })) => {}
+        Ok(Ok(Packet::ConnAck { code })) => return Err(refused(code)),
+        Ok(Ok(_)) => return Err(AppError::unavailable("MQTT 服务器拒绝连接", "unexpected packet")),
+        Ok(Err(e)) => return Err(AppError::io("MQTT 连接已断开", e)),
+        Err(_) => return Err(AppError::unavailable("连接 MQTT 服务器超时", &settings.host)),
+    }
+    *backoff = RECONNECT_MIN;
+    info!("MQTT connected to {}:{} as {}", settings.host, settings.port, client_id);
+
+    // 读取放在单独的任务里：select! 取消未完成的读取会丢失半个报文
+    let (packets_tx, mut packets) = mpsc::channel(64);
+    let read_task = tauri::async_runtime::spawn(async move {
+        loop {
+            let packet = packet::read(&mut reader).await;
+            let failed = packet.is_err();
+            if packets_tx.send(packet).await.is_err() || failed {
+                break;
+            }
+        }
+    });
+
+    let client = app.state::<MqttClient>();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    *client.outgoing.lock()? = Some(tx);
+    set_connected(app, true);
+
+    let result: Result<(), AppError> = async {
+        let mut packet_id = 0u16;
+        let topics: Vec<String> = client.topics.lock()?.iter().cloned().collect();
+        if !topics.is_empty() {
+            writer
+                .write_all(&packet::subscribe(next_packet_id(&mut packet_id), &topics, 1)?)
+                .await?;
+        }
+        let keep_alive = Duration::from_secs(keep_alive_secs as u64);
+        let mut ping = tokio::time::interval(keep_alive);
+        ping.tick().await;
+        let mut last_seen = Instant::now();
+        loop {
+            tokio::select! {
+                incoming = packets.recv() => {
+                    let Some(incoming) = incoming else {
+                        return Ok(());
+                    };
+                    last_seen = Instant::now();
+                    match incoming.map_err(|e| AppError::io("MQTT 连接已断开", e))? {
+                        Packet::Publish(publish) => {
+                            if let Some(id) = publish.packet_id.filter(|_| publish.qos > 0) {
+                                writer.write_all(&packet::puback(id)).await?;
+                            }
+                            forward(app, publish);
+                        }
+                        Packet::SubAck { packet_id, codes } if codes.contains(&packet::SUBSCRIBE_FAILURE) => {
+                            warn!("MQTT broker rejected subscription request {}", packet_id);
+                        }
+                        Packet::PubAck(id) => debug!("MQTT publish {} acknowledged", id),
+                        Packet::UnsubAck(id) => debug!("MQTT unsubscribe {} acknowledged", id),
+                        Packet::Other(kind) => debug!("Ignoring MQTT packet type {}", kind),
+                        _ => {}
+                    }
+                }
+                command = rx.recv() => match command {
+                    Some(Command::Publish(mut publish)) => {
+                        if publish.qos > 0 {
+                            publish.packet_id = Some(next_packet_id(&mut packet_id));
+                        }
+                        // 单条消息过长时只丢弃这条，不断开连接
+                        match packet::publish(&publish) {
+                            Ok(encoded) => writer.write_all(&encoded).await?,
+                            Err(e) => warn!("Dropping MQTT publish to {}: {}", publish.topic, e),
+                        }
+                    }
+                    Some(Command::Subscribe(topic)) => {
+                        writer
+                            .write_all(&packet::subscribe(next_packet_id(&mut packet_id), &[topic], 1)?)
+                            .await?;
+                    }
+                    Some(Command::Unsubscribe(topic)) => {
+                        writer
+                            .write_all(&packet::unsubscribe(next_packet_id(&mut packet_id), &[topic])?)
+                            .await?;
+                    }
+                    None => {
+                        writer.write_all(&packet::disconnect()).await.ok();
+                        return Ok(());
+                    }
+                },
+                _ = ping.tick() => {
+                    // 超过 1.5 倍保活时间没有收到任何报文，视为连接已失效
+                    if last_seen.elapsed() > keep_alive * 3 / 2 {
+                        return Err(AppError::unavailable("MQTT 连接已断开", "keep-alive timeout"));
+                    }
+                    writer.write_all(&packet::pingreq()).await?;
+                }
+            }
+        }
+    }
+    .await;
+
+    read_task.abort();
+    if let Ok(mut outgoing) = client.outgoing.lock() {
+        outgoing.take();
+    }
+    result
+}
+
+async fn run(app: AppHandle, settings: MqttSettings) {
+    let broker = format!("{}:{}", settings.host.trim(), settings.port);
+    let mut backoff = RECONNECT_MIN;
+    loop {
+        match session(&app, &settings, &mut backoff).await {
+            Ok(()) => info!("MQTT connection to {} closed", broker),
+            Err(e) => warn!("MQTT connection to {} failed: {}", broker, e),
+        }
+        set_connected(&app, false);
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RECONNECT_MAX);
+    }
+}
+
+fn validate_topic(topic: &str, filter: bool) -> Result<String, AppError> {
+    let topic = topic.trim();
+    if topic.is_empty() || topic.len() > u16::MAX as usize {
+        return Err(AppError::invalid("MQTT 主题无效"));
+    }
+    if !filter && topic.contains(['+', '#']) {
+        return Err(AppError::invalid("发布的主题不能包含通配符"));
+    }
+    Ok(topic.to_string())
+}
+
+// 供语音命令等内部调用；字符串原样发送，其他值按 JSON 文本发送
+pub fn publish(app: &AppHandle, topic: &str, payload: Value, qos: u8, retain: bool) -> Result<(), AppError> {
+    let topic = validate_topic(topic, false)?;
+    if qos > 1 {
+        return Err(AppError::invalid("只支持 QoS 0 和 1"));
+    }
+    let payload = match payload {
+        Value::String(text) => text,
+        other => other.to_string(),
+    };
+    app.state::<MqttClient>().send(Command::Publish(Publish {
+        topic,
+        payload: payload.into_bytes(),
+        qos,
+        retain,
+        packet_id: None,
+    }))
+}
+
+#[tauri::command]
+pub fn mqtt_publish(
+    app: AppHandle,
+    topic: String,
+    payload: Value,
+    qos: Option<u8>,
+    retain: Option<bool>,
+) -> Result<(), AppError> {
+    publish(&app, &topic, payload, qos.unwrap_or(0), retain.unwrap_or(false))
+}
+
+// 运行期间的订阅不写入设置，断线重连后仍然有效
+#[tauri::command]
+pub fn mqtt_subscribe(app: AppHandle, client: State<'_, MqttClient>, topic: String) -> Result<MqttStatus, AppError> {
+    let topic = validate_topic(&topic, true)?;
+    if client.topics.lock()?.insert(topic.clone()) && client.connected.load(Ordering::SeqCst) {
+        client.send(Command::Subscribe(topic))?;
+    }
+    let status = client.status();
+    events::publish(&app, AppEvent::MqttStatusChanged(status.clone()));
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn mqtt_unsubscribe(app: AppHandle, client: State<'_, MqttClient>, topic: String) -> Result<MqttStatus, AppError> {
+    let topic = validate_topic(&topic, true)?;
+    if client.topics.lock()?.remove(&topic) && client.connected.load(Ordering::SeqCst) {
+        client.send(Command::Unsubscribe(topic))?;
+    }
+    let status = client.status();
+    events::publish(&app, AppEvent::MqttStatusChanged(status.clone()));
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn get_mqtt_status(client: State<'_, MqttClient>) -> MqttStatus {
+    client.status()
+}
+
+// 密码写入钥匙串后调用以重新连接
+#[tauri::command]
+pub fn reconnect_mqtt(app: AppHandle, client: State<'_, MqttClient>) -> MqttStatus {
+    apply(&app);
+    client.status()
+}
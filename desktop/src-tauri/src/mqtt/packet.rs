@@ -0,0 +1,222 @@
+use std::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+// MQTT 3.1.1 报文的编解码，只实现客户端用到的部分
+
+// 超过该长度的报文视为异常
+const MAX_PACKET_BYTES: usize = 1024 * 1024;
+// 剩余长度最多用 4 个字节编码
+const MAX_REMAINING_LENGTH: usize = 268_435_455;
+
+const CONNECT: u8 = 1;
+const CONNACK: u8 = 2;
+const PUBLISH: u8 = 3;
+const PUBACK: u8 = 4;
+const SUBSCRIBE: u8 = 8;
+const SUBACK: u8 = 9;
+const UNSUBSCRIBE: u8 = 10;
+const UNSUBACK: u8 = 11;
+const PINGREQ: u8 = 12;
+const PINGRESP: u8 = 13;
+const DISCONNECT: u8 = 14;
+
+// SUBACK 中表示订阅被拒绝的返回码
+pub const SUBSCRIBE_FAILURE: u8 = 0x80;
+
+pub struct Connect<'a> {
+    pub client_id: &'a str,
+    pub username: Option<&'a str>,
+    pub password: Option<&'a str>,
+    pub keep_alive_secs: u16,
+}
+
+#[derive(Debug, Clone)]
+pub struct Publish {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub qos: u8,
+    pub retain: bool,
+    pub packet_id: Option<u16>,
+}
+
+#[derive(Debug)]
+pub enum Packet {
+    ConnAck { code: u8 },
+    Publish(Publish),
+    PubAck(u16),
+    SubAck { packet_id: u16, codes: Vec<u8> },
+    UnsubAck(u16),
+    PingResp,
+    // 客户端不处理的其他报文类型
+    Other(u8),
+}
+
+fn malformed(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("malformed MQTT packet: {}", message),
+    )
+}
+
+fn put_u16(buf: &mut Vec<u8>, value: u16) {
+    buf.extend_from_slice(&value.to_be_bytes());
+}
+
+// 字符串和二进制字段以 2 字节长度开头，超长时报错而不是截断
+fn put_bytes(buf: &mut Vec<u8>, bytes: &[u8]) -> io::Result<()> {
+    let length = u16::try_from(bytes.len()).map_err(|_| too_long("field"))?;
+    put_u16(buf, length);
+    buf.extend_from_slice(bytes);
+    Ok(())
+}
+
+fn too_long(what: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, format!("MQTT {} too long", what))
+}
+
+// 固定报头：类型和标志位，随后是变长编码的剩余长度
+fn frame(header: u8, body: Vec<u8>) -> io::Result<Vec<u8>> {
+    if body.len() > MAX_REMAINING_LENGTH {
+        return Err(too_long("packet"));
+    }
+    let mut packet = vec![header];
+    let mut length = body.len();
+    loop {
+        let mut byte = (length % 128) as u8;
+        length /= 128;
+        if length > 0 {
+            byte |= 0x80;
+        }
+        packet.push(byte);
+        if length == 0 {
+            break;
+        }
+    }
+    packet.extend(body);
+    Ok(packet)
+}
+
+// 使用 clean session，断线重连后由客户端重新订阅
+pub fn connect(connect: &Connect<'_>) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    put_bytes(&mut body, b"MQTT")?;
+    body.push(4);
+    let mut flags = 0x02;
+    if connect.username.is_some() {
+        flags |= 0x80;
+    }
+    if connect.password.is_some() {
+        flags |= 0x40;
+    }
+    body.push(flags);
+    put_u16(&mut body, connect.keep_alive_secs);
+    put_bytes(&mut body, connect.client_id.as_bytes())?;
+    if let Some(username) = connect.username {
+        put_bytes(&mut body, username.as_bytes())?;
+    }
+    if let Some(password) = connect.password {
+        put_bytes(&mut body, password.as_bytes())?;
+    }
+    frame(CONNECT << 4, body)
+}
+
+pub fn publish(publish: &Publish) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    put_bytes(&mut body, publish.topic.as_bytes())?;
+    if let Some(packet_id) = publish.packet_id.filter(|_| publish.qos > 0) {
+        put_u16(&mut body, packet_id);
+    }
+    body.extend_from_slice(&publish.payload);
+    frame(PUBLISH << 4 | (publish.qos & 0x03) << 1 | publish.retain as u8, body)
+}
+
+pub fn puback(packet_id: u16) -> Vec<u8> {
+    vec![PUBACK << 4, 2, (packet_id >> 8) as u8, packet_id as u8]
+}
+
+pub fn subscribe(packet_id: u16, filters: &[String], qos: u8) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    put_u16(&mut body, packet_id);
+    for filter in filters {
+        put_bytes(&mut body, filter.as_bytes())?;
+        body.push(qos);
+    }
+    frame(SUBSCRIBE << 4 | 0x02, body)
+}
+
+pub fn unsubscribe(packet_id: u16, filters: &[String]) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    put_u16(&mut body, packet_id);
+    for filter in filters {
+        put_bytes(&mut body, filter.as_bytes())?;
+    }
+    frame(UNSUBSCRIBE << 4 | 0x02, body)
+}
+
+pub fn pingreq() -> Vec<u8> {
+    vec![PINGREQ << 4, 0]
+}
+
+pub fn disconnect() -> Vec<u8> {
+    vec![DISCONNECT << 4, 0]
+}
+
+fn read_u16(body: &[u8], offset: usize) -> io::Result<u16> {
+    body.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .ok_or_else(|| malformed("truncated"))
+}
+
+fn parse(header: u8, body: &[u8]) -> io::Result<Packet> {
+    match header >> 4 {
+        CONNACK => Ok(Packet::ConnAck {
+            code: *body.get(1).ok_or_else(|| malformed("truncated CONNACK"))?,
+        }),
+        PUBLISH => {
+            let qos = (header >> 1) & 0x03;
+            let topic_len = read_u16(body, 0)? as usize;
+            let topic = body.get(2..2 + topic_len).ok_or_else(|| malformed("truncated topic"))?;
+            let topic = String::from_utf8(topic.to_vec()).map_err(|_| malformed("topic is not UTF-8"))?;
+            let mut offset = 2 + topic_len;
+            let packet_id = if qos > 0 {
+                offset += 2;
+                Some(read_u16(body, offset - 2)?)
+            } else {
+                None
+            };
+            Ok(Packet::Publish(Publish {
+                topic,
+                payload: body[offset..].to_vec(),
+                qos,
+                retain: header & 0x01 != 0,
+                packet_id,
+            }))
+        }
+        PUBACK => Ok(Packet::PubAck(read_u16(body, 0)?)),
+        SUBACK => Ok(Packet::SubAck {
+            packet_id: read_u16(body, 0)?,
+            codes: body[2..].to_vec(),
+        }),
+        UNSUBACK => Ok(Packet::UnsubAck(read_u16(body, 0)?)),
+        PINGRESP => Ok(Packet::PingResp),
+        other => Ok(Packet::Other(other)),
+    }
+}
+
+pub async fn read<R: AsyncRead + Unpin>(reader: &mut R) -> io::Result<Packet> {
+    let header = reader.read_u8().await?;
+    let mut length = 0usize;
+    for shift in (0..4).map(|index| index * 7) {
+        let byte = reader.read_u8().await?;
+        length |= ((byte & 0x7f) as usize) << shift;
+        if byte & 0x80 == 0 {
+            if length > MAX_PACKET_BYTES {
+                return Err(malformed("packet too large"));
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await?;
+            return parse(header, &body);
+        }
+    }
+    Err(malformed("invalid remaining length"))
+}
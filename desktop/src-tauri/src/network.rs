@@ -0,0 +1,462 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_native_tls::native_tls;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{secrets, settings, AppState};
+
+const PAC_TIMEOUT: Duration = Duration::from_secs(10);
+// 本机后端不经过代理
+const LOCAL_HOSTS: &str = "localhost,127.0.0.1,::1";
+// 代理对 CONNECT 请求的响应头上限
+const MAX_TUNNEL_RESPONSE: usize = 8 * 1024;
+
+// system：使用环境变量和系统代理设置；direct：不使用代理
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyMode {
+    #[default]
+    System,
+    Direct,
+    Manual,
+    Pac,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NetworkSettings {
+    pub proxy_mode: ProxyMode,
+    // 手动代理，scheme 为 http 或 https
+    pub proxy_scheme: String,
+    pub proxy_host: String,
+    pub proxy_port: u16,
+    // 代理认证的用户名，密码保存在系统钥匙串中
+    pub proxy_username: Option<String>,
+    // PAC 脚本地址；脚本不会被执行，只取其中第一个 PROXY 条目
+    pub pac_url: Option<String>,
+    // 不经过代理的主机，规则同 NO_PROXY 环境变量
+    pub no_proxy: Vec<String>,
+    // 额外信任的 CA 证书（PEM 文件，可包含多个证书），用于企业内网的 HTTPS 代理
+    pub ca_certificates: Vec<String>,
+}
+
+impl Default for NetworkSettings {
+    fn default() -> Self {
+        Self {
+            proxy_mode: ProxyMode::System,
+            proxy_scheme: "http".to_string(),
+            proxy_host: String::new(),
+            proxy_port: 8080,
+            proxy_username: None,
+            pac_url: None,
+            no_proxy: Vec::new(),
+            ca_certificates: Vec::new(),
+        }
+    }
+}
+
+// 按设置解析出的客户端配置，异步与阻塞客户端共用
+#[derive(Clone, Default)]
+pub struct ClientConfig {
+    // None 时沿用 reqwest 的系统代理检测
+    proxy: Option<ProxyChoice>,
+    certificates: Vec<reqwest::Certificate>,
+    // WebSocket、MQTT 等非 HTTP 连接使用的代理和证书
+    tunnel: Option<Tunnel>,
+    bypass: Vec<String>,
+    tls_certificates: Vec<native_tls::Certificate>,
+}
+
+#[derive(Clone)]
+enum ProxyChoice {
+    Direct,
+    Proxy(reqwest::Proxy),
+}
+
+// 通过 HTTP 代理的 CONNECT 请求建立隧道
+#[derive(Clone)]
+struct Tunnel {
+    secure: bool,
+    host: String,
+    port: u16,
+    authorization: Option<String>,
+}
+
+pub trait Stream: AsyncRead + AsyncWrite + Unpin + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Unpin + Send> Stream for T {}
+
+// 两种客户端的 builder 方法同名但类型不同
+macro_rules! apply_config {
+    ($builder:expr, $config:expr) => {{
+        let mut builder = $builder;
+        match &$config.proxy {
+            Some(ProxyChoice::Direct) => builder = builder.no_proxy(),
+            Some(ProxyChoice::Proxy(proxy)) => builder = builder.proxy(proxy.clone()),
+            None => {}
+        }
+        for certificate in &$config.certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        builder
+    }};
+}
+
+impl ClientConfig {
+    pub fn client(&self) -> Result<reqwest::Client, AppError> {
+        Ok(apply_config!(reqwest::Client::builder(), self).build()?)
+    }
+
+    // 阻塞客户端只能在阻塞线程中创建和释放
+    pub fn blocking_client(&self, timeout: Duration) -> Result<reqwest::blocking::Client, AppError> {
+        Ok(self.blocking_builder().timeout(timeout).build()?)
+    }
+
+    // 需要调整重定向等选项时在此基础上继续设置
+    pub fn blocking_builder(&self) -> reqwest::blocking::ClientBuilder {
+        apply_config!(reqwest::blocking::Client::builder(), self)
+    }
+
+    pub fn tls_connector(&self) -> Result<tokio_native_tls::TlsConnector, AppError> {
+        let mut builder = native_tls::TlsConnector::builder();
+        for certificate in &self.tls_certificates {
+            builder.add_root_certificate(certificate.clone());
+        }
+        let connector = builder.build().map_err(|e| AppError::unavailable("无法初始化 TLS", e))?;
+        Ok(connector.into())
+    }
+
+    // 非 HTTP 的连接按同样的设置经代理连接，需要时建立 TLS
+    pub async fn connect(&self, host: &str, port: u16, tls: bool) -> Result<Box<dyn Stream>, AppError> {
+        let tcp = match self.tunnel.as_ref().filter(|_| !self.bypassed(host)) {
+            Some(tunnel) => tunnel.open(host, port).await?,
+            None => TcpStream::connect((host, port))
+                .await
+                .map_err(|e| AppError::unavailable(format!("无法连接 {}:{}", host, port), e))?,
+        };
+        if !tls {
+            return Ok(Box::new(tcp));
+        }
+        let stream = self
+            .tls_connector()?
+            .connect(host, tcp)
+            .await
+            .map_err(|e| AppError::unavailable(format!("与 {} 的 TLS 握手失败", host), e))?;
+        Ok(Box::new(stream))
+    }
+
+    // 规则同 NO_PROXY，但不支持网段
+    fn bypassed(&self, host: &str) -> bool {
+        let host = host.trim_start_matches('[').trim_end_matches(']').to_ascii_lowercase();
+        self.bypass.iter().any(|entry| {
+            let entry = entry.trim().trim_start_matches('.').to_ascii_lowercase();
+            entry == "*" || host == entry || host.ends_with(&format!(".{}", entry))
+        })
+    }
+}
+
+impl Tunnel {
+    fn parse(url: &str, authorization: Option<String>) -> Option<Self> {
+        let url = if url.contains("://") {
+            url.to_string()
+        } else {
+            format!("http://{}", url)
+        };
+        let url = reqwest::Url::parse(&url).ok()?;
+        // 环境变量中的代理可以在地址里带上用户名和密码
+        let authorization = authorization.or_else(|| {
+            (!url.username().is_empty()).then(|| {
+                let credentials = format!("{}:{}", url.username(), url.password().unwrap_or_default());
+                base64::engine::general_purpose::STANDARD.encode(credentials)
+            })
+        });
+        Some(Self {
+            secure: url.scheme() == "https",
+            host: url.host_str()?.trim_start_matches('[').trim_end_matches(']').to_string(),
+            port: url.port_or_known_default()?,
+            authorization,
+        })
+    }
+
+    async fn open(&self, host: &str, port: u16) -> Result<TcpStream, AppError> {
+        if self.secure {
+            return Err(AppError::unavailable(
+                "无法通过代理建立连接",
+                "HTTPS proxies are only supported for HTTP requests",
+            ));
+        }
+        let mut stream = TcpStream::connect((self.host.as_str(), self.port))
+            .await
+            .map_err(|e| AppError::unavailable(format!("无法连接代理服务器 {}:{}", self.host, self.port), e))?;
+        let target = if host.contains(':') && !host.starts_with('[') {
+            format!("[{}]:{}", host, port)
+        } else {
+            format!("{}:{}", host, port)
+        };
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+        if let Some(authorization) = &self.authorization {
+            request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", authorization));
+        }
+        request.push_str("\r\n");
+        stream.write_all(request.as_bytes()).await?;
+
+        // 逐字节读到响应头结束，不能多读隧道中的数据
+        let mut response = Vec::new();
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() >= MAX_TUNNEL_RESPONSE {
+                return Err(AppError::unavailable("无法通过代理建立连接", "response header too long"));
+            }
+            response.push(stream.read_u8().await?);
+        }
+        let status = String::from_utf8_lossy(&response);
+        let status = status.lines().next().unwrap_or_default();
+        if status.split_whitespace().nth(1) != Some("200") {
+            return Err(AppError::unavailable("无法通过代理建立连接", status));
+        }
+        Ok(stream)
+    }
+}
+
+// 所有对外请求共用的 HTTP 客户端，网络设置变化时整体替换
+pub struct Network {
+    config: Mutex<ClientConfig>,
+    client: Mutex<reqwest::Client>,
+}
+
+impl Network {
+    pub fn new() -> Self {
+        Self {
+            config: Mutex::new(ClientConfig::default()),
+            client: Mutex::new(reqwest::Client::new()),
+        }
+    }
+
+    pub fn client(&self) -> reqwest::Client {
+        self.client.lock().map(|client| client.clone()).unwrap_or_default()
+    }
+
+    pub fn config(&self) -> ClientConfig {
+        self.config.lock().map(|config| config.clone()).unwrap_or_default()
+    }
+
+    pub async fn configure(&self, settings: &NetworkSettings) -> Result<(), AppError> {
+        self.configure_with(settings, None).await
+    }
+
+    // proxy_password 为 None 时使用钥匙串中保存的代理密码
+    pub async fn configure_with(&self, settings: &NetworkSettings, proxy_password: Option<&str>) -> Result<(), AppError> {
+        let config = resolve(settings, proxy_password).await?;
+        let client = config.client()?;
+        *self.config.lock()? = config;
+        *self.client.lock()? = client;
+        info!("Network configured with {:?} proxy", settings.proxy_mode);
+        Ok(())
+    }
+}
+
+pub fn client(app: &AppHandle) -> reqwest::Client {
+    app.state::<Network>().client()
+}
+
+pub fn config(app: &AppHandle) -> ClientConfig {
+    app.state::<Network>().config()
+}
+
+fn bypass_hosts(settings: &NetworkSettings) -> Vec<String> {
+    let mut hosts: Vec<String> = LOCAL_HOSTS.split(',').map(str::to_string).collect();
+    hosts.extend(settings.no_proxy.iter().cloned());
+    hosts
+}
+
+fn no_proxy(settings: &NetworkSettings) -> Option<reqwest::NoProxy> {
+    reqwest::NoProxy::from_string(&bypass_hosts(settings).join(","))
+}
+
+// 启动时环境变量中的 NO_PROXY，多次应用设置时不重复追加
+fn env_no_proxy() -> &'static str {
+    static ORIGINAL: OnceLock<String> = OnceLock::new();
+    ORIGINAL.get_or_init(|| {
+        std::env::var("NO_PROXY")
+            .or_else(|_| std::env::var("no_proxy"))
+            .unwrap_or_default()
+    })
+}
+
+// reqwest 的系统代理只读取 NO_PROXY 环境变量，把本机地址和设置中的例外追加进去
+fn apply_system_no_proxy(settings: &NetworkSettings) -> Vec<String> {
+    let mut hosts = bypass_hosts(settings);
+    hosts.extend(env_no_proxy().split(',').map(str::trim).filter(|host| !host.is_empty()).map(str::to_string));
+    std::env::set_var("NO_PROXY", hosts.join(","));
+    hosts
+}
+
+// 非 HTTP 连接在系统代理模式下只能取环境变量中的代理
+fn env_proxy() -> Option<String> {
+    ["HTTPS_PROXY", "https_proxy", "ALL_PROXY", "all_proxy"]
+        .iter()
+        .find_map(|name| std::env::var(name).ok().filter(|value| !value.trim().is_empty()))
+}
+
+fn proxy_password(proxy_password: Option<&str>) -> Result<String, AppError> {
+    match proxy_password {
+        Some(password) => Ok(password.to_string()),
+        None => Ok(secrets::get(secrets::PROXY_PASSWORD)?.unwrap_or_default()),
+    }
+}
+
+fn proxy_for(settings: &NetworkSettings, url: &str, password: &str) -> Result<reqwest::Proxy, AppError> {
+    let mut proxy = reqwest::Proxy::all(url)
+        .map_err(|e| AppError::invalid(format!("无效的代理地址 {}: {}", url, e)))?
+        .no_proxy(no_proxy(settings));
+    if let Some(username) = settings.proxy_username.as_deref().filter(|name| !name.is_empty()) {
+        proxy = proxy.basic_auth(username, password);
+    }
+    Ok(proxy)
+}
+
+fn tunnel_authorization(settings: &NetworkSettings, password: &str) -> Option<String> {
+    let username = settings.proxy_username.as_deref().filter(|name| !name.is_empty())?;
+    Some(base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", username, password)))
+}
+
+// PEM 文件可以包含多个证书，native-tls 需要逐个解析
+fn tls_certificates(path: &str, pem: &[u8]) -> Result<Vec<native_tls::Certificate>, AppError> {
+    const END: &str = "-----END CERTIFICATE-----";
+    let text = String::from_utf8_lossy(pem);
+    let mut certificates = Vec::new();
+    for block in text.split_inclusive(END).filter(|block| block.contains(END)) {
+        let certificate = native_tls::Certificate::from_pem(block.trim().as_bytes())
+            .map_err(|e| AppError::invalid(format!("无效的证书 {}: {}", path, e)))?;
+        certificates.push(certificate);
+    }
+    Ok(certificates)
+}
+
+async fn resolve(settings: &NetworkSettings, password: Option<&str>) -> Result<ClientConfig, AppError> {
+    let mut bypass = bypass_hosts(settings);
+    let mut tunnel = None;
+    let proxy = match settings.proxy_mode {
+        ProxyMode::System => {
+            bypass = apply_system_no_proxy(settings);
+            tunnel = env_proxy().and_then(|url| Tunnel::parse(&url, None));
+            None
+        }
+        ProxyMode::Direct => Some(ProxyChoice::Direct),
+        ProxyMode::Manual => {
+            if settings.proxy_host.trim().is_empty() {
+                return Err(AppError::invalid("代理主机不能为空"));
+            }
+            let url = format!(
+                "{}://{}:{}",
+                settings.proxy_scheme,
+                settings.proxy_host.trim(),
+                settings.proxy_port
+            );
+            let password = proxy_password(password)?;
+            tunnel = Tunnel::parse(&url, tunnel_authorization(settings, &password));
+            Some(ProxyChoice::Proxy(proxy_for(settings, &url, &password)?))
+        }
+        ProxyMode::Pac => {
+            let pac_url = settings
+                .pac_url
+                .as_deref()
+                .filter(|url| !url.is_empty())
+                .ok_or_else(|| AppError::invalid("PAC 地址不能为空"))?;
+            match fetch_pac_proxy(pac_url).await? {
+                Some(host) => {
+                    let url = format!("http://{}", host);
+                    let password = proxy_password(password)?;
+                    tunnel = Tunnel::parse(&url, tunnel_authorization(settings, &password));
+                    Some(ProxyChoice::Proxy(proxy_for(settings, &url, &password)?))
+                }
+                None => Some(ProxyChoice::Direct),
+            }
+        }
+    };
+
+    let mut certificates = Vec::new();
+    let mut native = Vec::new();
+    for path in &settings.ca_certificates {
+        let pem = std::fs::read(path).map_err(|e| AppError::io(format!("无法读取证书 {}", path), e))?;
+        let bundle = reqwest::Certificate::from_pem_bundle(&pem)
+            .map_err(|e| AppError::invalid(format!("无效的证书 {}: {}", path, e)))?;
+        certificates.extend(bundle);
+        native.extend(tls_certificates(path, &pem)?);
+    }
+    Ok(ClientConfig {
+        proxy,
+        certificates,
+        tunnel,
+        bypass,
+        tls_certificates: native,
+    })
+}
+
+// PAC 脚本直接下载，不经过代理
+async fn fetch_pac_proxy(url: &str) -> Result<Option<String>, AppError> {
+    let client = reqwest::Client::builder().no_proxy().timeout(PAC_TIMEOUT).build()?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::network("无法下载 PAC 脚本", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("无法下载 PAC 脚本", response.status()));
+    }
+    let script = response.text().await?;
+    Ok(parse_pac(&script))
+}
+
+// 从 return "PROXY host:port; DIRECT" 之类的语句中取第一个代理
+fn parse_pac(script: &str) -> Option<String> {
+    script
+        .split(['"', '\'', ';'])
+        .map(str::trim)
+        .find_map(|entry| entry.strip_prefix("PROXY "))
+        .map(|host| host.trim().to_string())
+        .filter(|host| !host.is_empty())
+}
+
+// 启动时在后台应用网络设置（PAC 需要下载），失败时保留默认配置
+pub fn start(app: &AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let settings = match app.state::<AppState>().settings.lock() {
+            Ok(settings) => settings.network.clone(),
+            Err(_) => return,
+        };
+        if let Err(e) = app.state::<Network>().configure(&settings).await {
+            warn!("Failed to apply network settings: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn set_network_settings(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    network: State<'_, Network>,
+    settings: NetworkSettings,
+    proxy_password: Option<String>,
+) -> Result<(), AppError> {
+    // 先用新设置创建客户端，证书或代理地址无效时密码和设置都不保存
+    network.configure_with(&settings, proxy_password.as_deref()).await?;
+    if let Some(password) = proxy_password {
+        if password.is_empty() {
+            secrets::delete(secrets::PROXY_PASSWORD)?;
+        } else {
+            secrets::store(secrets::PROXY_PASSWORD, &password)?;
+        }
+    }
+
+    let mut current = state.settings.lock()?;
+    current.network = settings;
+    settings::save_and_notify(&app, &current, &["network"])?;
+    Ok(())
+}
@@ -0,0 +1,438 @@
+use chrono::{DateTime, Local};
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::knowledge::embeddings;
+use crate::storage::{self, now_millis, DATABASE_FILE};
+use crate::{settings, AppState};
+
+// 标签以 JSON 数组保存，按标签筛选时用 json_each 展开
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS notes (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL,
+    body TEXT NOT NULL,
+    tags TEXT NOT NULL DEFAULT '[]',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL,
+    file TEXT,
+    source_url TEXT
+);
+CREATE INDEX IF NOT EXISTS idx_notes_updated ON notes(updated_at);
+";
+const DEFAULT_LIMIT: u32 = 50;
+const MAX_LIMIT: u32 = 200;
+// 没有标题时取正文第一行的前若干字
+const DERIVED_TITLE_CHARS: usize = 30;
+const FILE_NAME_CHARS: usize = 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotesSettings {
+    // 设置后每条笔记同时保存为该目录下的 .md 文件，方便用其他编辑器查看
+    pub mirror_folder: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Note {
+    pub id: String,
+    pub title: String,
+    pub body_md: String,
+    pub tags: Vec<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+    // 镜像的 Markdown 文件路径
+    pub file: Option<String>,
+    // 网页摘录等外部内容的来源链接
+    pub source_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct NoteFilter {
+    // 匹配标题和正文
+    pub query: Option<String>,
+    pub tag: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteTag {
+    pub tag: String,
+    pub count: u32,
+}
+
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_string();
+        if !tag.is_empty()
+            && !normalized
+                .iter()
+                .any(|known| known.to_lowercase() == tag.to_lowercase())
+        {
+            normalized.push(tag);
+        }
+    }
+    normalized
+}
+
+fn derive_title(title: Option<&str>, body: &str) -> String {
+    if let Some(title) = title.map(str::trim).filter(|title| !title.is_empty()) {
+        return title.to_string();
+    }
+    let first_line = body
+        .lines()
+        .map(|line| line.trim().trim_start_matches('#').trim())
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    first_line.chars().take(DERIVED_TITLE_CHARS).collect()
+}
+
+fn row_to_note(row: &Row<'_>) -> rusqlite::Result<Note> {
+    let tags: String = row.get(3)?;
+    Ok(Note {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        body_md: row.get(2)?,
+        tags: serde_json::from_str(&tags).unwrap_or_default(),
+        created_at: row.get(4)?,
+        updated_at: row.get(5)?,
+        file: row.get(6)?,
+        source_url: row.get(7)?,
+    })
+}
+
+fn local_time(millis: i64) -> String {
+    DateTime::from_timestamp_millis(millis)
+        .map(|time| time.with_timezone(&Local).to_rfc3339())
+        .unwrap_or_default()
+}
+
+// 文件名取标题并附上 ID 前缀，标题重复时也不会覆盖
+fn file_name(note: &Note) -> String {
+    let title: String = note
+        .title
+        .chars()
+        .map(|c| {
+            if c.is_control() || "\\/:*?\"<>|".contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(FILE_NAME_CHARS)
+        .collect();
+    let title = title.trim().trim_matches('.');
+    let title = if title.is_empty() { "note" } else { title };
+    format!("{}-{}.md", title, &note.id[..8.min(note.id.len())])
+}
+
+fn markdown(note: &Note) -> String {
+    let tags = note
+        .tags
+        .iter()
+        .map(|tag| serde_json::to_string(tag).unwrap_or_default())
+        .collect::<Vec<_>>()
+        .join(", ");
+    let source = note
+        .source_url
+        .as_ref()
+        .map(|url| format!("source: {}\n", serde_json::to_string(url).unwrap_or_default()))
+        .unwrap_or_default();
+    format!(
+        "---\ntitle: {}\ntags: [{}]\n{}created: {}\nupdated: {}\n---\n\n{}\n",
+        serde_json::to_string(&note.title).unwrap_or_default(),
+        tags,
+        source,
+        local_time(note.created_at),
+        local_time(note.updated_at),
+        note.body_md.trim_end()
+    )
+}
+
+// 用户可能在外部编辑器里改过镜像文件，这里总是以数据库中的内容为准覆盖
+fn write_mirror(folder: &Path, note: &Note) -> Result<PathBuf, AppError> {
+    std::fs::create_dir_all(folder).map_err(|e| AppError::io("无法创建笔记目录", e))?;
+    let path = folder.join(file_name(note));
+    std::fs::write(&path, markdown(note)).map_err(|e| AppError::io("无法写入笔记文件", e))?;
+    Ok(path)
+}
+
+// 笔记保存在对话历史所在的数据库中
+pub struct Notes {
+    conn: Mutex<Connection>,
+}
+
+impl Notes {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Self::add_source_url(&conn)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    // 早期版本创建的表没有 source_url 列
+    fn add_source_url(conn: &Connection) -> Result<(), AppError> {
+        let exists: bool = conn.query_row(
+            "SELECT COUNT(*) > 0 FROM pragma_table_info('notes') WHERE name = 'source_url'",
+            [],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            conn.execute_batch("ALTER TABLE notes ADD COLUMN source_url TEXT;")?;
+            info!("Added source_url column to notes");
+        }
+        Ok(())
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    fn get(&self, id: &str) -> Result<Note, AppError> {
+        self.conn()?
+            .query_row(
+                "SELECT id, title, body, tags, created_at, updated_at, file, source_url FROM notes WHERE id = ?1",
+                params![id],
+                row_to_note,
+            )
+            .optional()?
+            .ok_or_else(|| AppError::NotFound(format!("笔记不存在: {}", id)))
+    }
+
+    fn save(&self, note: &Note) -> Result<(), AppError> {
+        self.conn()?.execute(
+            "INSERT INTO notes (id, title, body, tags, created_at, updated_at, file, source_url)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+                 ON CONFLICT(id) DO UPDATE SET title = excluded.title, body = excluded.body,
+                     tags = excluded.tags, updated_at = excluded.updated_at, file = excluded.file",
+            params![
+                note.id,
+                note.title,
+                note.body_md,
+                serde_json::to_string(&note.tags)?,
+                note.created_at,
+                note.updated_at,
+                note.file,
+                note.source_url
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn delete(&self, id: &str) -> Result<Option<String>, AppError> {
+        let conn = self.conn()?;
+        let file: Option<Option<String>> = conn
+            .query_row("SELECT file FROM notes WHERE id = ?1", params![id], |row| row.get(0))
+            .optional()?;
+        conn.execute("DELETE FROM notes WHERE id = ?1", params![id])?;
+        file.ok_or_else(|| AppError::NotFound(format!("笔记不存在: {}", id)))
+    }
+
+    fn list(&self, filter: &NoteFilter) -> Result<Vec<Note>, AppError> {
+        let query = filter.query.as_deref().map(str::trim).filter(|query| !query.is_empty());
+        let tag = filter.tag.as_deref().map(str::trim).filter(|tag| !tag.is_empty());
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, title, body, tags, created_at, updated_at, file, source_url FROM notes
+                 WHERE (?1 IS NULL OR title LIKE ?1 ESCAPE '\\' OR body LIKE ?1 ESCAPE '\\')
+                   AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(notes.tags) WHERE lower(value) = lower(?2)))
+                 ORDER BY updated_at DESC
+                 LIMIT ?3 OFFSET ?4",
+        )?;
+        let notes = stmt
+            .query_map(
+                params![
+                    query.map(storage::like_pattern),
+                    tag,
+                    filter.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT),
+                    filter.offset.unwrap_or(0)
+                ],
+                row_to_note,
+            )
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+        notes.map_err(AppError::from)
+    }
+
+    fn tags(&self) -> Result<Vec<NoteTag>, AppError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT value, COUNT(*) FROM notes, json_each(notes.tags) GROUP BY lower(value) ORDER BY COUNT(*) DESC, value",
+        )?;
+        let tags = stmt
+            .query_map([], |row| {
+                Ok(NoteTag {
+                    tag: row.get(0)?,
+                    count: row.get(1)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+        tags.map_err(AppError::from)
+    }
+}
+
+fn mirror_folder(app: &AppHandle) -> Option<PathBuf> {
+    let state = app.state::<AppState>();
+    let folder = state.settings.lock().ok()?.notes.mirror_folder.clone();
+    folder.filter(|folder| !folder.is_empty()).map(PathBuf::from)
+}
+
+// 写入索引和镜像文件，失败只记录日志，不影响笔记本身的保存
+fn sync_note(app: &AppHandle, note: &mut Note, previous_file: Option<&str>) {
+    let state = app.state::<AppState>();
+    let content = format!(
+        "{}\n{}",
+        note.tags
+            .iter()
+            .map(|tag| format!("#{}", tag))
+            .collect::<Vec<_>>()
+            .join(" "),
+        note.body_md
+    );
+    match state.knowledge.index_note(&note.id, &note.title, &content) {
+        Ok(document) if document.changed => embeddings::spawn_embed_pending(app),
+        Ok(_) => {}
+        Err(e) => warn!("Failed to index note {}: {}", note.id, e),
+    }
+
+    let Some(folder) = mirror_folder(app) else {
+        return;
+    };
+    match write_mirror(&folder, note) {
+        Ok(path) => {
+            let path = path.to_string_lossy().to_string();
+            // 标题改变后文件名随之改变，删除旧文件
+            if let Some(previous) = previous_file.filter(|previous| *previous != path) {
+                let _ = std::fs::remove_file(previous);
+            }
+            note.file = Some(path);
+        }
+        Err(e) => warn!("Failed to mirror note {}: {}", note.id, e),
+    }
+}
+
+// 保存新笔记并写入知识库索引，供命令和网页摘录共用
+pub fn create(
+    app: &AppHandle,
+    title: Option<&str>,
+    body_md: String,
+    tags: Vec<String>,
+    source_url: Option<String>,
+) -> Result<Note, AppError> {
+    if body_md.trim().is_empty() && title.is_none_or(|title| title.trim().is_empty()) {
+        return Err(AppError::invalid("笔记内容不能为空"));
+    }
+    let notes = app.state::<Notes>();
+    let now = now_millis();
+    let mut note = Note {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: derive_title(title, &body_md),
+        body_md,
+        tags: normalize_tags(tags),
+        created_at: now,
+        updated_at: now,
+        file: None,
+        source_url,
+    };
+    notes.save(&note)?;
+    sync_note(app, &mut note, None);
+    if note.file.is_some() {
+        notes.save(&note)?;
+    }
+    info!("Note {} created", note.id);
+    Ok(note)
+}
+
+#[tauri::command]
+pub async fn create_note(
+    app: AppHandle,
+    title: Option<String>,
+    body_md: String,
+    tags: Option<Vec<String>>,
+) -> Result<Note, AppError> {
+    create(&app, title.as_deref(), body_md, tags.unwrap_or_default(), None)
+}
+
+// 只更新传入的字段
+#[tauri::command]
+pub async fn update_note(
+    app: AppHandle,
+    notes: State<'_, Notes>,
+    id: String,
+    title: Option<String>,
+    body_md: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Note, AppError> {
+    let mut note = notes.get(&id)?;
+    let previous_file = note.file.clone();
+    if let Some(body) = body_md {
+        note.body_md = body;
+    }
+    if title.is_some() {
+        note.title = derive_title(title.as_deref(), &note.body_md);
+    }
+    if let Some(tags) = tags {
+        note.tags = normalize_tags(tags);
+    }
+    note.updated_at = now_millis();
+    notes.save(&note)?;
+    sync_note(&app, &mut note, previous_file.as_deref());
+    if note.file != previous_file {
+        notes.save(&note)?;
+    }
+    Ok(note)
+}
+
+#[tauri::command]
+pub fn get_note(notes: State<'_, Notes>, id: String) -> Result<Note, AppError> {
+    notes.get(&id)
+}
+
+#[tauri::command]
+pub fn list_notes(notes: State<'_, Notes>, filter: Option<NoteFilter>) -> Result<Vec<Note>, AppError> {
+    notes.list(&filter.unwrap_or_default())
+}
+
+#[tauri::command]
+pub fn list_note_tags(notes: State<'_, Notes>) -> Result<Vec<NoteTag>, AppError> {
+    notes.tags()
+}
+
+#[tauri::command]
+pub fn delete_note(app: AppHandle, notes: State<'_, Notes>, id: String) -> Result<(), AppError> {
+    let file = notes.delete(&id)?;
+    if let Err(e) = app.state::<AppState>().knowledge.remove_note(&id) {
+        warn!("Failed to remove note {} from knowledge base: {}", id, e);
+    }
+    if let Some(file) = file {
+        let _ = std::fs::remove_file(file);
+    }
+    info!("Note {} deleted", id);
+    Ok(())
+}
+
+// folder 为空时停止镜像，已经写出的文件保留
+#[tauri::command]
+pub fn set_notes_folder(app: AppHandle, folder: Option<String>) -> Result<NotesSettings, AppError> {
+    let folder = folder
+        .map(|folder| folder.trim().to_string())
+        .filter(|folder| !folder.is_empty());
+    if let Some(folder) = &folder {
+        if !Path::new(folder).is_absolute() {
+            return Err(AppError::invalid(format!("目录不存在: {}", folder)));
+        }
+        std::fs::create_dir_all(folder).map_err(|e| AppError::io("无法创建笔记目录", e))?;
+    }
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.notes.mirror_folder = folder;
+    settings::save_and_notify(&app, &settings, &["notes"])?;
+    Ok(settings.notes.clone())
+}
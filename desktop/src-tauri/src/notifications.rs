@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::api::notification::Notification;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::platform::{self, Toast};
+use crate::{deep_link, dnd, fullscreen, settings, AppState};
+
+pub const CATEGORY_GENERAL: &str = "general";
+pub const CATEGORY_ASSISTANT: &str = "assistant";
+pub const CATEGORY_REMINDER: &str = "reminder";
+// 导出、导入、下载等长时间操作的结果
+pub const CATEGORY_TASK: &str = "task";
+// 点击系统通知后激活应用的时间窗口，超时则丢弃对应的跳转
+const ACTION_TIMEOUT: Duration = Duration::from_secs(120);
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct NotificationSettings {
+    // 被静音的通知类别
+    pub muted: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NotificationAction {
+    pub category: String,
+    pub url: String,
+}
+
+// Tauri 1 的系统通知没有点击回调；点击通知会激活应用窗口，
+// 因此在通知发出后主窗口首次获得焦点时执行最近一次通知的跳转
+struct PendingAction {
+    action: NotificationAction,
+    shown_at: Instant,
+}
+
+#[derive(Default)]
+pub struct Notifier {
+    pending: Mutex<Option<PendingAction>>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn take_pending(&self) -> Option<NotificationAction> {
+        let pending = self.pending.lock().ok()?.take()?;
+        (pending.shown_at.elapsed() < ACTION_TIMEOUT).then_some(pending.action)
+    }
+}
+
+fn is_muted(app: &AppHandle, category: &str) -> bool {
+    let state = app.state::<AppState>();
+    let muted = match state.settings.lock() {
+        Ok(settings) => settings.notifications.muted.iter().any(|c| c == category),
+        Err(_) => false,
+    };
+    muted
+}
+
+// 发送系统通知，返回 false 表示该类别已被静音、正在全屏应用中或处于免打扰
+pub fn send(
+    app: &AppHandle,
+    category: &str,
+    title: &str,
+    body: &str,
+    action: Option<String>,
+) -> Result<bool, AppError> {
+    if is_muted(app, category) || fullscreen::is_active(app) || dnd::is_active(app) {
+        return Ok(false);
+    }
+
+    Notification::new(&app.config().tauri.bundle.identifier)
+        .title(title)
+        .body(body)
+        .show()
+        .map_err(|e| format!("发送通知失败: {}", e))?;
+
+    if let Some(url) = action {
+        let notifier = app.state::<Notifier>();
+        let mut pending = notifier.pending.lock()?;
+        *pending = Some(PendingAction {
+            action: NotificationAction {
+                category: category.to_string(),
+                url,
+            },
+            shown_at: Instant::now(),
+        });
+    }
+    Ok(true)
+}
+
+// 带按钮的通知；平台不支持按钮时退回普通通知，点击通知执行第一个按钮的动作
+pub fn send_toast(app: &AppHandle, category: &str, toast: Toast) -> Result<bool, AppError> {
+    if is_muted(app, category) || fullscreen::is_active(app) || dnd::is_active(app) {
+        return Ok(false);
+    }
+    if platform::current().show_toast(app, &toast)? {
+        return Ok(true);
+    }
+    // 普通通知只能执行深度链接，打开文件夹等动作需要平台支持
+    let action = toast
+        .actions
+        .into_iter()
+        .map(|action| action.url)
+        .find(|url| url.starts_with(deep_link::SCHEME));
+    send(app, category, &toast.title, &toast.body, action)
+}
+
+// 主窗口获得焦点时调用
+pub fn handle_focus(app: &AppHandle) {
+    let Some(action) = app.state::<Notifier>().take_pending() else {
+        return;
+    };
+    info!("Opening {} notification action", action.category);
+    if let Err(e) = deep_link::open(app, &action.url) {
+        warn!("{}", e);
+    }
+}
+
+// action 为点击通知后跳转的深度链接，例如 lingecho://conversation/<id>
+#[tauri::command]
+pub fn notify(
+    app: AppHandle,
+    title: String,
+    body: String,
+    action: Option<String>,
+    category: Option<String>,
+) -> Result<bool, AppError> {
+    let category = category.unwrap_or_else(|| CATEGORY_GENERAL.to_string());
+    send(&app, &category, &title, &body, action)
+}
+
+#[tauri::command]
+pub fn set_notification_muted(app: AppHandle, category: String, muted: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let list = &mut settings.notifications.muted;
+    list.retain(|c| *c != category);
+    if muted {
+        list.push(category);
+    }
+    settings::save_and_notify(&app, &settings, &["notifications"])
+}
@@ -0,0 +1,229 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::knowledge::IndexedDocument;
+use crate::platform::{self, TaskbarProgress};
+use crate::{i18n, network, settings, AppState};
+
+const TESSDATA_DIR: &str = "tessdata";
+const TESSDATA_URL: &str = "https://github.com/tesseract-ocr/tessdata_fast/raw/main";
+const DEFAULT_LANG: &str = "zh-CN+en";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OcrSettings {
+    // Tesseract 可执行文件，不在 PATH 中时填写完整路径
+    pub command: String,
+    pub lang: String,
+}
+
+impl Default for OcrSettings {
+    fn default() -> Self {
+        Self {
+            command: "tesseract".to_string(),
+            lang: DEFAULT_LANG.to_string(),
+        }
+    }
+}
+
+// 待识别的图片：文件路径、base64 字符串或字节数组
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum ImageInput {
+    Path { path: String },
+    Base64 { data: String },
+    Bytes(Vec<u8>),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrResult {
+    pub text: String,
+    pub lang: String,
+    // 设置 save_to_knowledge 时写入知识库的文档
+    pub document: Option<IndexedDocument>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OcrLanguage {
+    pub code: String,
+    // 语言包是否已下载到应用目录
+    pub installed: bool,
+}
+
+// zh-CN+en 形式的语言列表转换为 Tesseract 的语言包名称
+fn tesseract_langs(lang: &str) -> Result<Vec<&'static str>, AppError> {
+    lang.split('+')
+        .map(|code| match code.trim().to_ascii_lowercase().as_str() {
+            "zh-cn" | "zh" | "chi_sim" => Ok("chi_sim"),
+            "en" | "en-us" | "eng" => Ok("eng"),
+            other => Err(AppError::invalid(format!("不支持的识别语言: {}", other))),
+        })
+        .collect()
+}
+
+fn tessdata_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let dir = app
+        .path_resolver()
+        .app_data_dir()
+        .ok_or("无法获取应用数据目录")?
+        .join(TESSDATA_DIR);
+    Ok(dir)
+}
+
+fn run_tesseract(settings: &OcrSettings, tessdata: &Path, image: &Path, langs: &[&str]) -> Result<String, AppError> {
+    let mut command = Command::new(&settings.command);
+    command.arg(image).arg("stdout").arg("-l").arg(langs.join("+"));
+    // 已下载语言包时使用应用目录中的 tessdata，否则使用 Tesseract 自带的
+    if langs.iter().all(|lang| tessdata.join(format!("{}.traineddata", lang)).exists()) {
+        command.arg("--tessdata-dir").arg(tessdata);
+    }
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(crate::backend::CREATE_NO_WINDOW);
+    }
+
+    let output = command
+        .output()
+        .map_err(|e| AppError::unavailable(format!("无法启动 OCR 引擎 {}", settings.command), e))?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "文字识别失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn recognize(app: &AppHandle, image: ImageInput, lang: Option<String>) -> Result<(String, String), AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.ocr.clone();
+    let lang = lang.unwrap_or_else(|| settings.lang.clone());
+    let langs = tesseract_langs(&lang)?;
+    let tessdata = tessdata_dir(app)?;
+
+    // 非文件输入先写入临时文件
+    let (path, temp) = match image {
+        ImageInput::Path { path } => (PathBuf::from(path), false),
+        ImageInput::Base64 { data } => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("图片数据不是有效的 base64: {}", e))?;
+            (write_temp(&bytes)?, true)
+        }
+        ImageInput::Bytes(bytes) => (write_temp(&bytes)?, true),
+    };
+    let result = run_tesseract(&settings, &tessdata, &path, &langs);
+    if temp {
+        std::fs::remove_file(&path).ok();
+    }
+    Ok((result?, lang))
+}
+
+fn write_temp(bytes: &[u8]) -> Result<PathBuf, AppError> {
+    let path = std::env::temp_dir().join(format!("lingecho-ocr-{}.img", uuid::Uuid::new_v4()));
+    std::fs::write(&path, bytes)?;
+    Ok(path)
+}
+
+#[tauri::command]
+pub async fn ocr_image(
+    app: AppHandle,
+    image: ImageInput,
+    lang: Option<String>,
+    save_to_knowledge: Option<bool>,
+    title: Option<String>,
+) -> Result<OcrResult, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let (text, lang) = recognize(&app, image, lang)?;
+        info!("OCR recognized {} chars ({})", text.chars().count(), lang);
+
+        let document = if save_to_knowledge.unwrap_or(false) && !text.is_empty() {
+            Some(app.state::<AppState>().knowledge.index_text(title, &text)?)
+        } else {
+            None
+        };
+        Ok(OcrResult { text, lang, document })
+    })
+    .await?
+}
+
+#[tauri::command]
+pub fn list_ocr_languages(app: AppHandle) -> Result<Vec<OcrLanguage>, AppError> {
+    let tessdata = tessdata_dir(&app)?;
+    Ok(["zh-CN", "en"]
+        .iter()
+        .map(|code| {
+            let installed = tesseract_langs(code)
+                .map(|langs| tessdata.join(format!("{}.traineddata", langs[0])).exists())
+                .unwrap_or(false);
+            OcrLanguage {
+                code: code.to_string(),
+                installed,
+            }
+        })
+        .collect())
+}
+
+// 从 tessdata_fast 下载语言包到应用数据目录，下载期间任务栏显示进度
+#[tauri::command]
+pub async fn install_ocr_language(app: AppHandle, lang: String) -> Result<(), AppError> {
+    let tessdata = tessdata_dir(&app)?;
+    std::fs::create_dir_all(&tessdata)?;
+
+    platform::set_progress(&app, TaskbarProgress::Indeterminate);
+    match download_languages(&app, &tessdata, &lang).await {
+        Ok(0) => platform::set_progress(&app, TaskbarProgress::Hidden),
+        Ok(_) => platform::finished(&app, i18n::t("toast.download_done"), lang.clone(), Some(&tessdata)),
+        Err(e) => {
+            let operation = format!("ocr-language?lang={}", lang);
+            platform::failed(
+                &app,
+                &operation,
+                i18n::t("toast.download_failed"),
+                i18n::translate(e.message()),
+            );
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+// 返回实际下载的语言包数量
+async fn download_languages(app: &AppHandle, tessdata: &Path, lang: &str) -> Result<usize, AppError> {
+    let mut downloaded = 0;
+    for name in tesseract_langs(lang)? {
+        let target = tessdata.join(format!("{}.traineddata", name));
+        if target.exists() {
+            continue;
+        }
+        info!("Downloading OCR language pack {}", name);
+        let response = network::client(app)
+            .get(format!("{}/{}.traineddata", TESSDATA_URL, name))
+            .send()
+            .await
+            .map_err(|e| AppError::network("下载语言包失败", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("下载语言包失败", response.status()));
+        }
+        let bytes = response.bytes().await?;
+        let tmp = target.with_extension("part");
+        std::fs::write(&tmp, &bytes)?;
+        std::fs::rename(&tmp, &target)?;
+        downloaded += 1;
+    }
+    Ok(downloaded)
+}
+
+#[tauri::command]
+pub fn set_ocr_language(app: AppHandle, lang: String) -> Result<(), AppError> {
+    tesseract_langs(&lang)?;
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.ocr.lang = lang;
+    settings::save_and_notify(&app, &settings, &["ocr"])
+}
@@ -0,0 +1,328 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::api::{self, Method};
+use crate::backend::{self, BackendManager};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::network;
+use crate::storage::{now_millis, DATABASE_FILE};
+use crate::AppState;
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+const CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+// 连续失败多次才判定为离线，避免后端重启时来回切换
+const OFFLINE_AFTER_FAILURES: u32 = 2;
+const WRITE_TIMEOUT: Duration = Duration::from_secs(30);
+// 被后端拒绝多次的请求不再重放
+const MAX_REPLAY_ATTEMPTS: u32 = 5;
+// 后端运行在本机，能连上后端不代表能连上外网；没有设置检查地址时依次尝试这些地址，任一可达即视为在线
+const DEFAULT_CHECK_URLS: &[&str] = &["https://www.baidu.com", "https://www.bing.com"];
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    method TEXT NOT NULL,
+    path TEXT NOT NULL,
+    body TEXT,
+    created_at INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT
+);
+";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OfflineSettings {
+    // 离线时直接使用本地识别和合成引擎，不再等待后端请求超时
+    pub use_local_engines: bool,
+    // 额外检查的外网地址，为空时检查内置的公共地址
+    pub check_url: Option<String>,
+}
+
+impl Default for OfflineSettings {
+    fn default() -> Self {
+        Self {
+            use_local_engines: true,
+            check_url: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConnectivityStatus {
+    pub online: bool,
+    // 等待重放的请求数
+    pub queued: u32,
+}
+
+// 时间为毫秒时间戳
+#[derive(Debug, Clone, Serialize)]
+pub struct QueuedWrite {
+    pub id: i64,
+    pub method: Method,
+    pub path: String,
+    pub body: Option<serde_json::Value>,
+    pub created_at: i64,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum WriteOutcome {
+    // 已发送，data 为后端返回的数据
+    Sent { data: serde_json::Value },
+    // 已排队，恢复联网后自动重放
+    Queued { id: i64 },
+}
+
+fn write_from_row(row: &rusqlite::Row) -> rusqlite::Result<QueuedWrite> {
+    let method: String = row.get(1)?;
+    let body: Option<String> = row.get(3)?;
+    Ok(QueuedWrite {
+        id: row.get(0)?,
+        method: Method::parse(&method).unwrap_or(Method::Post),
+        path: row.get(2)?,
+        body: body.and_then(|body| serde_json::from_str(&body).ok()),
+        created_at: row.get(4)?,
+        attempts: row.get(5)?,
+        last_error: row.get(6)?,
+    })
+}
+
+// 联网状态与离线期间排队的后端写入请求，队列与对话历史共用同一个数据库文件
+pub struct Offline {
+    conn: Mutex<Connection>,
+    online: AtomicBool,
+}
+
+impl Offline {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            online: AtomicBool::new(true),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    pub fn is_online(&self) -> bool {
+        self.online.load(Ordering::SeqCst)
+    }
+
+    pub fn enqueue(&self, method: Method, path: &str, body: Option<&serde_json::Value>) -> Result<i64, AppError> {
+        let body = body.map(serde_json::to_string).transpose()?;
+        let conn = self.conn()?;
+        conn.execute(
+            "INSERT INTO outbox (method, path, body, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![method.as_str(), path, body, now_millis()],
+        )?;
+        Ok(conn.last_insert_rowid())
+    }
+
+    pub fn queued(&self) -> Result<u32, AppError> {
+        let count = self
+            .conn()?
+            .query_row("SELECT COUNT(*) FROM outbox", [], |row| row.get(0))?;
+        Ok(count)
+    }
+
+    // 按排队顺序返回
+    pub fn list(&self) -> Result<Vec<QueuedWrite>, AppError> {
+        let conn = self.conn()?;
+        let mut stmt =
+            conn.prepare("SELECT id, method, path, body, created_at, attempts, last_error FROM outbox ORDER BY id")?;
+        let rows = stmt.query_map([], write_from_row)?;
+        let writes = rows.collect::<Result<Vec<_>, _>>();
+        writes.map_err(AppError::from)
+    }
+
+    pub fn remove(&self, id: i64) -> Result<bool, AppError> {
+        let deleted = self.conn()?.execute("DELETE FROM outbox WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    fn record_failure(&self, id: i64, error: &AppError) -> Result<(), AppError> {
+        self.conn()?.execute(
+            "UPDATE outbox SET attempts = attempts + 1, last_error = ?1 WHERE id = ?2",
+            params![error.to_string(), id],
+        )?;
+        Ok(())
+    }
+
+    fn status(&self) -> ConnectivityStatus {
+        ConnectivityStatus {
+            online: self.is_online(),
+            queued: self.queued().unwrap_or(0),
+        }
+    }
+}
+
+// 离线且设置允许时，识别和合成直接使用本地引擎
+pub fn prefer_local(app: &AppHandle) -> bool {
+    let state = app.state::<AppState>();
+    let enabled = state
+        .settings
+        .lock()
+        .is_ok_and(|settings| settings.offline.use_local_engines);
+    enabled && !state.offline.is_online()
+}
+
+// 后端可用且外网地址有响应时视为在线，外网地址返回任何状态码都算可达
+async fn check(app: &AppHandle) -> bool {
+    let client = network::client(app);
+    if !backend::ping(&client, &app.state::<BackendManager>().url()).await {
+        return false;
+    }
+    let check_url = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.offline.check_url.clone().filter(|url| !url.is_empty()),
+        Err(_) => None,
+    };
+    let urls = match &check_url {
+        Some(url) => vec![url.as_str()],
+        None => DEFAULT_CHECK_URLS.to_vec(),
+    };
+    for url in urls {
+        if client.head(url).timeout(CHECK_TIMEOUT).send().await.is_ok() {
+            return true;
+        }
+    }
+    false
+}
+
+// 按顺序重放排队的请求；网络再次中断时停止，等待下次检测
+async fn replay(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let writes = match state.offline.list() {
+        Ok(writes) => writes,
+        Err(e) => {
+            warn!("Failed to load queued writes: {}", e);
+            return;
+        }
+    };
+
+    let mut sent = 0;
+    for write in &writes {
+        let result = api::request(app, write.method, &write.path, write.body.as_ref(), None, WRITE_TIMEOUT).await;
+        let updated = match result {
+            Ok(_) => {
+                sent += 1;
+                state.offline.remove(write.id).map(|_| ())
+            }
+            Err(e) if e.retryable() => {
+                warn!("Replay of queued write {} interrupted: {}", write.id, e);
+                break;
+            }
+            Err(e) if write.attempts + 1 >= MAX_REPLAY_ATTEMPTS => {
+                warn!(
+                    "Dropping queued write {} {} after {} attempts: {}",
+                    write.id,
+                    write.path,
+                    write.attempts + 1,
+                    e
+                );
+                state.offline.remove(write.id).map(|_| ())
+            }
+            Err(e) => {
+                warn!("Queued write {} {} rejected: {}", write.id, write.path, e);
+                state.offline.record_failure(write.id, &e)
+            }
+        };
+        if let Err(e) = updated {
+            warn!("Failed to update queued write {}: {}", write.id, e);
+        }
+    }
+    if sent > 0 {
+        info!("Replayed {} of {} queued writes", sent, writes.len());
+    }
+}
+
+// 在 setup 中调用：定期检测联网状态，状态变化时通知所有窗口，在线时重放队列
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut failures = 0u32;
+        loop {
+            tokio::time::sleep(CHECK_INTERVAL).await;
+
+            if check(&app).await {
+                failures = 0;
+            } else {
+                failures = failures.saturating_add(1);
+            }
+            let online = failures < OFFLINE_AFTER_FAILURES;
+
+            let state = app.state::<AppState>();
+            if state.offline.online.swap(online, Ordering::SeqCst) != online {
+                info!("Connectivity changed: {}", if online { "online" } else { "offline" });
+                events::publish(&app, AppEvent::ConnectivityChanged(state.offline.status()));
+            }
+            if online && state.offline.queued().unwrap_or(0) > 0 {
+                replay(&app).await;
+            }
+        }
+    });
+}
+
+// 非紧急的后端写入：在线时直接发送，离线或网络错误时排队，恢复联网后重放
+pub async fn submit(
+    app: &AppHandle,
+    method: Method,
+    path: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<WriteOutcome, AppError> {
+    if method == Method::Get {
+        return Err(AppError::invalid("只有写入请求可以排队"));
+    }
+    api::check_path(path)?;
+
+    // 队列中还有请求时也排队，保证按提交顺序写入
+    let state = app.state::<AppState>();
+    if state.offline.is_online() && state.offline.queued()? == 0 {
+        match api::request(app, method, path, body, None, WRITE_TIMEOUT).await {
+            Ok(data) => return Ok(WriteOutcome::Sent { data }),
+            Err(e) if e.retryable() => warn!("{:?} {} failed, queueing: {}", method, path, e),
+            Err(e) => return Err(e),
+        }
+    }
+    let id = state.offline.enqueue(method, path, body)?;
+    Ok(WriteOutcome::Queued { id })
+}
+
+#[tauri::command]
+pub async fn queue_backend_write(
+    app: AppHandle,
+    method: Method,
+    path: String,
+    body: Option<serde_json::Value>,
+) -> Result<WriteOutcome, AppError> {
+    submit(&app, method, &path, body.as_ref()).await
+}
+
+#[tauri::command]
+pub fn get_connectivity_status(state: State<'_, AppState>) -> Result<ConnectivityStatus, AppError> {
+    Ok(ConnectivityStatus {
+        online: state.offline.is_online(),
+        queued: state.offline.queued()?,
+    })
+}
+
+#[tauri::command]
+pub fn list_queued_writes(state: State<'_, AppState>) -> Result<Vec<QueuedWrite>, AppError> {
+    state.offline.list()
+}
+
+#[tauri::command]
+pub fn discard_queued_write(state: State<'_, AppState>, id: i64) -> Result<bool, AppError> {
+    state.offline.remove(id)
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::backend::{self, BackendManager};
+use crate::error::AppError;
+use crate::hotkeys::{self, HotkeyAction};
+use crate::models::{self, ModelKind};
+use crate::network::Network;
+use crate::permissions::{self, PermissionKind, PermissionStatus};
+use crate::{settings, AppState};
+
+// 首次运行的引导步骤，按顺序进行；每一步完成前重新检查实际状态，也可以跳过
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum OnboardingStep {
+    MicPermission,
+    ModelDownload,
+    BackendCheck,
+    Hotkey,
+}
+
+impl OnboardingStep {
+    pub const ALL: [OnboardingStep; 4] = [
+        OnboardingStep::MicPermission,
+        OnboardingStep::ModelDownload,
+        OnboardingStep::BackendCheck,
+        OnboardingStep::Hotkey,
+    ];
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct OnboardingSettings {
+    pub completed: Vec<OnboardingStep>,
+    pub skipped: Vec<OnboardingStep>,
+}
+
+impl OnboardingSettings {
+    fn done(&self, step: OnboardingStep) -> bool {
+        self.completed.contains(&step) || self.skipped.contains(&step)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StepState {
+    pub step: OnboardingStep,
+    pub completed: bool,
+    pub skipped: bool,
+    // 当前检查是否通过，未通过时 detail 说明原因
+    pub ready: bool,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct OnboardingState {
+    pub finished: bool,
+    // 下一个未完成的步骤
+    pub current: Option<OnboardingStep>,
+    pub steps: Vec<StepState>,
+    pub permissions: Vec<PermissionStatus>,
+}
+
+// 检查通过返回 None，否则返回原因
+async fn check(app: &AppHandle, step: OnboardingStep) -> Option<String> {
+    match step {
+        OnboardingStep::MicPermission => {
+            let state = permissions::check(app, PermissionKind::Microphone);
+            (!state.usable()).then(|| "尚未获得麦克风权限".to_string())
+        }
+        OnboardingStep::ModelDownload => {
+            (!models::any_installed(app, ModelKind::Stt)).then(|| "尚未下载语音识别模型".to_string())
+        }
+        OnboardingStep::BackendCheck => {
+            let client = app.state::<Network>().client();
+            let url = app.state::<BackendManager>().url();
+            (!backend::ping(&client, &url).await).then(|| format!("无法连接后端服务: {}", url))
+        }
+        OnboardingStep::Hotkey => hotkeys::registered(app, HotkeyAction::VoiceActivation)
+            .is_none()
+            .then(|| "语音唤醒快捷键未注册".to_string()),
+    }
+}
+
+async fn state(app: &AppHandle) -> Result<OnboardingState, AppError> {
+    let saved = app.state::<AppState>().settings.lock()?.onboarding.clone();
+    let mut steps = Vec::new();
+    for step in OnboardingStep::ALL {
+        let detail = check(app, step).await;
+        steps.push(StepState {
+            step,
+            completed: saved.completed.contains(&step),
+            skipped: saved.skipped.contains(&step),
+            ready: detail.is_none(),
+            detail,
+        });
+    }
+    let current = OnboardingStep::ALL.into_iter().find(|step| !saved.done(*step));
+    Ok(OnboardingState {
+        finished: current.is_none(),
+        current,
+        steps,
+        permissions: [PermissionKind::Microphone, PermissionKind::Accessibility]
+            .into_iter()
+            .map(|kind| permissions::status(app, kind))
+            .collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn get_onboarding_state(app: AppHandle) -> Result<OnboardingState, AppError> {
+    state(&app).await
+}
+
+// skip 为 true 时不检查直接跳过，之后仍可以再次完成该步骤
+#[tauri::command]
+pub async fn complete_step(
+    app: AppHandle,
+    step: OnboardingStep,
+    skip: Option<bool>,
+) -> Result<OnboardingState, AppError> {
+    let skip = skip.unwrap_or(false);
+    if !skip {
+        if let Some(detail) = check(&app, step).await {
+            return Err(AppError::invalid(detail));
+        }
+    }
+    {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock()?;
+        let onboarding = &mut settings.onboarding;
+        onboarding.completed.retain(|s| *s != step);
+        onboarding.skipped.retain(|s| *s != step);
+        if skip {
+            onboarding.skipped.push(step);
+        } else {
+            onboarding.completed.push(step);
+        }
+        settings::save_and_notify(&app, &settings, &["onboarding"])?;
+    }
+    info!(
+        "Onboarding step {:?} {}",
+        step,
+        if skip { "skipped" } else { "completed" }
+    );
+    state(&app).await
+}
@@ -0,0 +1,361 @@
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{audio, autostart, launcher, platform};
+
+// 系统级权限检查：读取授权状态不会触发系统弹窗；
+// 请求权限时平台允许的直接弹出系统授权，否则打开系统设置中对应的页面
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionKind {
+    Microphone,
+    Notifications,
+    // macOS 上全局快捷键和模拟按键需要辅助功能权限
+    Accessibility,
+    // macOS 上截图和识别屏幕文字需要屏幕录制权限
+    ScreenRecording,
+    // 开机自启是否已注册
+    Autostart,
+}
+
+impl PermissionKind {
+    pub const ALL: [PermissionKind; 5] = [
+        PermissionKind::Microphone,
+        PermissionKind::Notifications,
+        PermissionKind::Accessibility,
+        PermissionKind::ScreenRecording,
+        PermissionKind::Autostart,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PermissionState {
+    Granted,
+    Denied,
+    // 尚未询问过用户，第一次使用时系统会弹窗
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    NotDetermined,
+    // 被家长控制或企业策略禁止，用户无法自行开启
+    Restricted,
+    // 当前平台不需要该权限
+    NotRequired,
+    Unknown,
+}
+
+impl PermissionState {
+    pub fn usable(self) -> bool {
+        matches!(self, PermissionState::Granted | PermissionState::NotRequired)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PermissionStatus {
+    pub kind: PermissionKind,
+    pub state: PermissionState,
+    // 能否直接弹出系统授权
+    pub can_request: bool,
+    // 能否打开系统设置中的对应页面
+    pub can_open_settings: bool,
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use objc::runtime::{Class, Object, YES};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    use super::PermissionState;
+
+    #[link(name = "AVFoundation", kind = "framework")]
+    extern "C" {}
+
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+        fn AXIsProcessTrustedWithOptions(options: *const Object) -> bool;
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGPreflightScreenCaptureAccess() -> bool;
+        fn CGRequestScreenCaptureAccess() -> bool;
+    }
+
+    // AVMediaTypeAudio 的值
+    const MEDIA_TYPE_AUDIO: &[u8] = b"soun\0";
+
+    pub fn microphone() -> PermissionState {
+        let Some(device) = Class::get("AVCaptureDevice") else {
+            return PermissionState::Unknown;
+        };
+        // SAFETY: 字符串以 NUL 结尾，类方法在任意线程都可以调用
+        let status: i64 = unsafe {
+            let media: *mut Object = msg_send![class!(NSString), stringWithUTF8String: MEDIA_TYPE_AUDIO.as_ptr()];
+            msg_send![device, authorizationStatusForMediaType: media]
+        };
+        // AVAuthorizationStatus
+        match status {
+            0 => PermissionState::NotDetermined,
+            1 => PermissionState::Restricted,
+            2 => PermissionState::Denied,
+            3 => PermissionState::Granted,
+            _ => PermissionState::Unknown,
+        }
+    }
+
+    pub fn accessibility() -> PermissionState {
+        // SAFETY: 无参数，只读取当前进程的授权状态
+        if unsafe { AXIsProcessTrusted() } {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+
+    // 未授权时弹出系统提示，引导用户到设置中勾选本应用
+    pub fn request_accessibility() {
+        // SAFETY: 字符串以 NUL 结尾，NSDictionary 与 CFDictionaryRef 可以直接互换
+        unsafe {
+            let key: *mut Object =
+                msg_send![class!(NSString), stringWithUTF8String: b"AXTrustedCheckOptionPrompt\0".as_ptr()];
+            let value: *mut Object = msg_send![class!(NSNumber), numberWithBool: YES];
+            let options: *mut Object = msg_send![class!(NSDictionary), dictionaryWithObject: value forKey: key];
+            AXIsProcessTrustedWithOptions(options);
+        }
+    }
+
+    // 无法区分尚未询问和已拒绝
+    pub fn screen_recording() -> PermissionState {
+        // SAFETY: 无参数，只读取当前进程的授权状态
+        if unsafe { CGPreflightScreenCaptureAccess() } {
+            PermissionState::Granted
+        } else {
+            PermissionState::Denied
+        }
+    }
+
+    // 系统只在第一次请求时弹窗，之后需要用户在设置中开启
+    pub fn request_screen_recording() {
+        // SAFETY: 同上
+        unsafe {
+            CGRequestScreenCaptureAccess();
+        }
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use windows_sys::Win32::Foundation::ERROR_SUCCESS;
+    use windows_sys::Win32::System::Registry::{
+        RegGetValueW, HKEY, HKEY_CURRENT_USER, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ,
+    };
+
+    use super::PermissionState;
+
+    const CONSENT_STORE: &str =
+        "Software\\Microsoft\\Windows\\CurrentVersion\\CapabilityAccessManager\\ConsentStore\\microphone";
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().chain(std::iter::once(0)).collect()
+    }
+
+    fn consent(root: HKEY, subkey: &str) -> Option<String> {
+        let subkey = wide(subkey);
+        let value = wide("Value");
+        let mut buffer = [0u16; 16];
+        let mut size = std::mem::size_of_val(&buffer) as u32;
+        // SAFETY: 字符串以 NUL 结尾，缓冲区大小与 size 一致
+        let result = unsafe {
+            RegGetValueW(
+                root,
+                subkey.as_ptr(),
+                value.as_ptr(),
+                RRF_RT_REG_SZ,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr().cast(),
+                &mut size,
+            )
+        };
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+        let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..len]))
+    }
+
+    // 系统级开关、当前用户开关和“允许桌面应用访问麦克风”任一为 Deny 都会拒绝采集
+    pub fn microphone() -> PermissionState {
+        if consent(HKEY_LOCAL_MACHINE, CONSENT_STORE).as_deref() == Some("Deny") {
+            return PermissionState::Restricted;
+        }
+        let user = consent(HKEY_CURRENT_USER, CONSENT_STORE);
+        let desktop = consent(HKEY_CURRENT_USER, &format!("{}\\NonPackaged", CONSENT_STORE));
+        match (user.as_deref(), desktop.as_deref()) {
+            (Some("Deny"), _) | (_, Some("Deny")) => PermissionState::Denied,
+            (Some("Allow"), _) => PermissionState::Granted,
+            _ => PermissionState::Unknown,
+        }
+    }
+}
+
+fn microphone() -> PermissionState {
+    #[cfg(any(target_os = "macos", windows))]
+    return native::microphone();
+    #[cfg(not(any(target_os = "macos", windows)))]
+    {
+        PermissionState::NotRequired
+    }
+}
+
+fn notifications(app: &AppHandle) -> PermissionState {
+    match platform::current().notifications_enabled(app) {
+        Some(true) => PermissionState::Granted,
+        Some(false) => PermissionState::Denied,
+        None if cfg!(target_os = "macos") => PermissionState::Unknown,
+        None => PermissionState::NotRequired,
+    }
+}
+
+fn accessibility() -> PermissionState {
+    #[cfg(target_os = "macos")]
+    return native::accessibility();
+    #[cfg(not(target_os = "macos"))]
+    {
+        PermissionState::NotRequired
+    }
+}
+
+fn screen_recording() -> PermissionState {
+    #[cfg(target_os = "macos")]
+    return native::screen_recording();
+    #[cfg(not(target_os = "macos"))]
+    {
+        PermissionState::NotRequired
+    }
+}
+
+fn autostart(app: &AppHandle) -> PermissionState {
+    match autostart::is_enabled(app) {
+        Ok(true) => PermissionState::Granted,
+        Ok(false) => PermissionState::Denied,
+        Err(e) => {
+            warn!("Failed to read autostart status: {}", e);
+            PermissionState::Unknown
+        }
+    }
+}
+
+pub fn check(app: &AppHandle, kind: PermissionKind) -> PermissionState {
+    match kind {
+        PermissionKind::Microphone => microphone(),
+        PermissionKind::Notifications => notifications(app),
+        PermissionKind::Accessibility => accessibility(),
+        PermissionKind::ScreenRecording => screen_recording(),
+        PermissionKind::Autostart => autostart(app),
+    }
+}
+
+// 麦克风只有尚未询问时才会弹窗，已拒绝的需要到系统设置中开启
+fn can_request(kind: PermissionKind, state: PermissionState) -> bool {
+    if state.usable() {
+        return false;
+    }
+    match kind {
+        PermissionKind::Microphone => state == PermissionState::NotDetermined,
+        PermissionKind::Accessibility | PermissionKind::ScreenRecording => cfg!(target_os = "macos"),
+        PermissionKind::Autostart => true,
+        PermissionKind::Notifications => false,
+    }
+}
+
+fn settings_url(kind: PermissionKind) -> Option<&'static str> {
+    let url = if cfg!(target_os = "macos") {
+        match kind {
+            PermissionKind::Microphone => "x-apple.systempreferences:com.apple.preference.security?Privacy_Microphone",
+            PermissionKind::Notifications => "x-apple.systempreferences:com.apple.preference.notifications",
+            PermissionKind::Accessibility => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_Accessibility"
+            }
+            PermissionKind::ScreenRecording => {
+                "x-apple.systempreferences:com.apple.preference.security?Privacy_ScreenCapture"
+            }
+            PermissionKind::Autostart => "x-apple.systempreferences:com.apple.LoginItems-Settings.extension",
+        }
+    } else if cfg!(windows) {
+        match kind {
+            PermissionKind::Microphone => "ms-settings:privacy-microphone",
+            PermissionKind::Notifications => "ms-settings:notifications",
+            PermissionKind::Autostart => "ms-settings:startupapps",
+            PermissionKind::Accessibility | PermissionKind::ScreenRecording => return None,
+        }
+    } else {
+        return None;
+    };
+    Some(url)
+}
+
+pub fn status(app: &AppHandle, kind: PermissionKind) -> PermissionStatus {
+    let state = check(app, kind);
+    PermissionStatus {
+        kind,
+        state,
+        can_request: can_request(kind, state),
+        can_open_settings: settings_url(kind).is_some(),
+    }
+}
+
+fn open_settings(kind: PermissionKind) -> Result<(), AppError> {
+    let url = settings_url(kind).ok_or_else(|| AppError::invalid("当前系统没有对应的权限设置页面"))?;
+    info!("Opening system settings for {:?}", kind);
+    launcher::open_native(url)
+}
+
+fn request(app: &AppHandle, kind: PermissionKind) -> Result<(), AppError> {
+    match kind {
+        PermissionKind::Microphone => audio::capture::probe_input(app),
+        #[cfg(target_os = "macos")]
+        PermissionKind::Accessibility => {
+            native::request_accessibility();
+            Ok(())
+        }
+        #[cfg(target_os = "macos")]
+        PermissionKind::ScreenRecording => {
+            native::request_screen_recording();
+            Ok(())
+        }
+        PermissionKind::Autostart => autostart::set_autostart(app.clone(), true, None).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+#[tauri::command]
+pub fn get_permission_status(app: AppHandle) -> Vec<PermissionStatus> {
+    PermissionKind::ALL.into_iter().map(|kind| status(&app, kind)).collect()
+}
+
+// 返回请求后的状态；系统弹窗是异步的，用户作出选择后需要重新查询
+#[tauri::command]
+pub async fn request_permission(app: AppHandle, kind: PermissionKind) -> Result<PermissionStatus, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let current = status(&app, kind);
+        if current.state.usable() {
+            return Ok(current);
+        }
+        if current.can_request {
+            info!("Requesting permission {:?}", kind);
+            request(&app, kind)?;
+        } else {
+            open_settings(kind)?;
+        }
+        Ok(status(&app, kind))
+    })
+    .await?
+}
+
+#[tauri::command]
+pub async fn open_permission_settings(kind: PermissionKind) -> Result<(), AppError> {
+    tauri::async_runtime::spawn_blocking(move || open_settings(kind)).await?
+}
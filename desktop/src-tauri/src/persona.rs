@@ -0,0 +1,258 @@
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::llm::ChatMessage;
+use crate::storage::{now_millis, Role, DATABASE_FILE};
+use crate::{settings, AppState};
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS personas (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    system_prompt TEXT NOT NULL DEFAULT '',
+    voice TEXT,
+    pet_skin TEXT,
+    temperature REAL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+";
+const COLUMNS: &str = "id, name, system_prompt, voice, pet_skin, temperature, created_at, updated_at";
+const MAX_NAME_CHARS: usize = 40;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PersonaSettings {
+    // 当前使用的人设，None 时使用默认的助手设定
+    pub active: Option<String>,
+}
+
+// 人设：系统提示、语音、桌宠外观和生成温度的组合；为空的项沿用全局设置
+#[derive(Debug, Clone, Serialize)]
+pub struct Persona {
+    pub id: String,
+    pub name: String,
+    pub system_prompt: String,
+    pub voice: Option<String>,
+    // 桌宠窗口使用的外观名称，由前端解释
+    pub pet_skin: Option<String>,
+    pub temperature: Option<f32>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PersonaInput {
+    pub name: String,
+    pub system_prompt: String,
+    pub voice: Option<String>,
+    pub pet_skin: Option<String>,
+    pub temperature: Option<f32>,
+}
+
+// 切换人设时推送给主窗口和桌宠，persona 为 None 表示恢复默认
+#[derive(Debug, Clone, Serialize)]
+pub struct PersonaChanged {
+    pub persona: Option<Persona>,
+}
+
+fn row_to_persona(row: &Row<'_>) -> rusqlite::Result<Persona> {
+    Ok(Persona {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        system_prompt: row.get(2)?,
+        voice: row.get(3)?,
+        pet_skin: row.get(4)?,
+        temperature: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+pub struct Personas {
+    conn: Mutex<Connection>,
+}
+
+impl Personas {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn: Mutex::new(conn) })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    fn find(&self, id: &str) -> Result<Option<Persona>, AppError> {
+        Ok(self
+            .conn()?
+            .query_row(
+                &format!("SELECT {} FROM personas WHERE id = ?1", COLUMNS),
+                params![id],
+                row_to_persona,
+            )
+            .optional()?)
+    }
+
+    fn list(&self) -> Result<Vec<Persona>, AppError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM personas ORDER BY created_at", COLUMNS))?;
+        let personas = stmt
+            .query_map([], row_to_persona)
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())?;
+        Ok(personas)
+    }
+
+    fn insert(&self, persona: &Persona) -> Result<(), AppError> {
+        self.conn()?.execute(
+            &format!(
+                "INSERT INTO personas ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                COLUMNS
+            ),
+            params![
+                persona.id,
+                persona.name,
+                persona.system_prompt,
+                persona.voice,
+                persona.pet_skin,
+                persona.temperature,
+                persona.created_at,
+                persona.updated_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    fn delete(&self, id: &str) -> Result<bool, AppError> {
+        let deleted = self
+            .conn()?
+            .execute("DELETE FROM personas WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+}
+
+// 当前人设；设置中记录的人设已被删除时视为没有
+pub fn active(app: &AppHandle) -> Option<Persona> {
+    let id = app.state::<AppState>().settings.lock().ok()?.persona.active.clone()?;
+    app.state::<Personas>().find(&id).ok().flatten()
+}
+
+// 在对话上下文最前面加上人设的系统提示
+pub fn apply(persona: Option<&Persona>, messages: &mut Vec<ChatMessage>) {
+    if let Some(prompt) = persona
+        .map(|persona| persona.system_prompt.trim())
+        .filter(|prompt| !prompt.is_empty())
+    {
+        messages.insert(
+            0,
+            ChatMessage {
+                role: Role::System,
+                content: prompt.to_string(),
+            },
+        );
+    }
+}
+
+fn activate(app: &AppHandle, persona: Option<Persona>) -> Result<Option<Persona>, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.persona.active = persona.as_ref().map(|persona| persona.id.clone());
+    settings::save_and_notify(app, &settings, &["persona"])?;
+    drop(settings);
+    info!(
+        "Active persona: {}",
+        persona
+            .as_ref()
+            .map(|persona| persona.name.as_str())
+            .unwrap_or("default")
+    );
+    events::publish(
+        app,
+        AppEvent::PersonaChanged(PersonaChanged {
+            persona: persona.clone(),
+        }),
+    );
+    Ok(persona)
+}
+
+#[tauri::command]
+pub fn list_personas(personas: State<'_, Personas>) -> Result<Vec<Persona>, AppError> {
+    personas.list()
+}
+
+#[tauri::command]
+pub fn get_active_persona(app: AppHandle) -> Result<Option<Persona>, AppError> {
+    Ok(active(&app))
+}
+
+#[tauri::command]
+pub fn create_persona(personas: State<'_, Personas>, persona: PersonaInput) -> Result<Persona, AppError> {
+    let name = persona.name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid("人设名称不能为空"));
+    }
+    if name.chars().count() > MAX_NAME_CHARS {
+        return Err(AppError::invalid(format!("人设名称不能超过 {} 个字", MAX_NAME_CHARS)));
+    }
+    if persona
+        .temperature
+        .is_some_and(|temperature| !(0.0..=2.0).contains(&temperature))
+    {
+        return Err(AppError::invalid("温度必须在 0 到 2 之间"));
+    }
+    let now = now_millis();
+    let created = Persona {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: name.to_string(),
+        system_prompt: persona.system_prompt.trim().to_string(),
+        voice: non_empty(persona.voice),
+        pet_skin: non_empty(persona.pet_skin),
+        temperature: persona.temperature,
+        created_at: now,
+        updated_at: now,
+    };
+    personas.insert(&created)?;
+    Ok(created)
+}
+
+// id 为空时恢复默认设定
+#[tauri::command]
+pub fn set_active_persona(
+    app: AppHandle,
+    personas: State<'_, Personas>,
+    id: Option<String>,
+) -> Result<Option<Persona>, AppError> {
+    let persona = match id {
+        Some(id) => Some(
+            personas
+                .find(&id)?
+                .ok_or_else(|| AppError::NotFound(format!("人设不存在: {}", id)))?,
+        ),
+        None => None,
+    };
+    activate(&app, persona)
+}
+
+#[tauri::command]
+pub fn delete_persona(app: AppHandle, personas: State<'_, Personas>, id: String) -> Result<bool, AppError> {
+    let was_active = active(&app).is_some_and(|persona| persona.id == id);
+    let deleted = personas.delete(&id)?;
+    if deleted && was_active {
+        activate(&app, None)?;
+    }
+    Ok(deleted)
+}
@@ -0,0 +1,204 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, LogicalSize, Manager, PhysicalPosition, PhysicalSize, State, WindowBuilder, WindowUrl};
+use tracing::{debug, warn};
+
+use super::{clamp_to_monitor, PET_LABEL};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::linux;
+
+pub const BUBBLE_LABEL: &str = "pet-bubble";
+
+// 气泡尺寸（逻辑像素），文字由前端自动换行
+const WIDTH: f64 = 260.0;
+const HEIGHT: f64 = 96.0;
+// 气泡与桌宠之间的间距（逻辑像素）
+const GAP: f64 = 8.0;
+// 未指定时长时按字数估算显示时间
+const MIN_DURATION_MS: u64 = 3000;
+const MAX_DURATION_MS: u64 = 15000;
+const MS_PER_CHAR: u64 = 120;
+const MAX_CHARS: usize = 200;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BubbleMessage {
+    pub id: u64,
+    pub text: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Default)]
+pub struct PetBubble {
+    current: Mutex<Option<BubbleMessage>>,
+    next_id: AtomicU64,
+}
+
+impl PetBubble {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+fn default_duration(text: &str) -> u64 {
+    (text.chars().count() as u64 * MS_PER_CHAR).clamp(MIN_DURATION_MS, MAX_DURATION_MS)
+}
+
+// 过长的回答只显示开头，完整内容在主窗口查看
+fn truncate(text: &str) -> String {
+    let text = text.trim();
+    if text.chars().count() <= MAX_CHARS {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(MAX_CHARS).collect();
+    truncated.push('…');
+    truncated
+}
+
+// 优先放在桌宠正上方，上方空间不够时放在下方，并限制在桌宠所在的显示器内
+fn position(pet: &tauri::Window) -> Option<PhysicalPosition<i32>> {
+    let monitor = pet.current_monitor().ok().flatten()?;
+    let scale = monitor.scale_factor();
+    let pet_position = linux::window_position(pet).ok()?;
+    let pet_size = pet.outer_size().ok()?;
+    let size = PhysicalSize::new((WIDTH * scale).round() as i32, (HEIGHT * scale).round() as i32);
+    let gap = (GAP * scale).round() as i32;
+
+    let x = pet_position.x + pet_size.width as i32 / 2 - size.width / 2;
+    let above = pet_position.y - gap - size.height;
+    let y = if above >= monitor.position().y {
+        above
+    } else {
+        pet_position.y + pet_size.height as i32 + gap
+    };
+    Some(clamp_to_monitor(&monitor, size, x, y))
+}
+
+fn place(app: &AppHandle, bubble: &tauri::Window) {
+    let Some(pet) = app.get_window(PET_LABEL) else {
+        return;
+    };
+    if let Some(position) = position(&pet) {
+        if let Err(e) = linux::move_window(bubble, position) {
+            debug!("Failed to move pet bubble: {}", e);
+        }
+    }
+}
+
+fn open(app: &AppHandle) -> Result<tauri::Window, AppError> {
+    if let Some(window) = app.get_window(BUBBLE_LABEL) {
+        return Ok(window);
+    }
+    let window = WindowBuilder::new(app, BUBBLE_LABEL, WindowUrl::App("pet-bubble-window".into()))
+        .title("")
+        .inner_size(WIDTH, HEIGHT)
+        .decorations(false)
+        .transparent(linux::capabilities().transparent)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .focused(false)
+        .visible(false)
+        .build()?;
+    window.set_size(LogicalSize::new(WIDTH, HEIGHT))?;
+    linux::prepare_pet(&window);
+    crate::macos::join_all_spaces(&window);
+    Ok(window)
+}
+
+// 在桌宠旁显示气泡，新的消息会替换正在显示的消息；桌宠未显示时不做任何事
+pub fn show(app: &AppHandle, text: &str, duration_ms: Option<u64>) -> Result<Option<BubbleMessage>, AppError> {
+    let text = truncate(text);
+    if text.is_empty() {
+        return Err(AppError::invalid("气泡内容不能为空"));
+    }
+    let pet_visible = app
+        .get_window(PET_LABEL)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    if !pet_visible {
+        return Ok(None);
+    }
+
+    let bubble = app.state::<PetBubble>();
+    let message = BubbleMessage {
+        id: bubble.next_id.fetch_add(1, Ordering::SeqCst) + 1,
+        duration_ms: duration_ms.unwrap_or_else(|| default_duration(&text)),
+        text,
+    };
+    *bubble.current.lock()? = Some(message.clone());
+
+    let window = open(app)?;
+    place(app, &window);
+    window.show()?;
+    events::publish(app, AppEvent::PetBubble(message.clone()));
+
+    // 到时后只隐藏仍在显示的同一条消息
+    let handle = app.clone();
+    let id = message.id;
+    let duration = Duration::from_millis(message.duration_ms);
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(duration).await;
+        let current = handle
+            .state::<PetBubble>()
+            .current
+            .lock()
+            .ok()
+            .and_then(|current| current.as_ref().map(|message| message.id));
+        if current == Some(id) {
+            if let Err(e) = hide(&handle) {
+                warn!("Failed to hide pet bubble: {}", e);
+            }
+        }
+    });
+    Ok(Some(message))
+}
+
+// 只写日志的便捷版本，用于回答、提醒和空闲提示
+pub fn say(app: &AppHandle, text: &str) {
+    if let Err(e) = show(app, text, None) {
+        warn!("Failed to show pet bubble: {}", e);
+    }
+}
+
+pub fn hide(app: &AppHandle) -> Result<(), AppError> {
+    app.state::<PetBubble>().current.lock()?.take();
+    if let Some(window) = app.get_window(BUBBLE_LABEL) {
+        window.hide()?;
+    }
+    Ok(())
+}
+
+// 桌宠移动后调用，让显示中的气泡跟随
+pub fn follow(app: &AppHandle) {
+    let Some(window) = app.get_window(BUBBLE_LABEL) else {
+        return;
+    };
+    if window.is_visible().unwrap_or(false) {
+        place(app, &window);
+    }
+}
+
+// duration 为毫秒，省略时按字数估算；桌宠未显示时返回 None
+// 同步命令在主线程执行，Windows 上在其中创建窗口会死锁，因此声明为 async
+#[tauri::command]
+pub async fn show_pet_bubble(
+    app: AppHandle,
+    text: String,
+    duration: Option<u64>,
+) -> Result<Option<BubbleMessage>, AppError> {
+    show(&app, &text, duration)
+}
+
+#[tauri::command]
+pub fn hide_pet_bubble(app: AppHandle) -> Result<(), AppError> {
+    hide(&app)
+}
+
+// 气泡页面加载完成后读取当前消息，避免错过创建窗口时推送的事件
+#[tauri::command]
+pub fn get_pet_bubble(bubble: State<'_, PetBubble>) -> Result<Option<BubbleMessage>, AppError> {
+    Ok(bubble.current.lock()?.clone())
+}
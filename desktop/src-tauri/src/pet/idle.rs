@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, PhysicalPosition};
+use tracing::debug;
+
+use super::state::{PetEvent, PetState, PetStateMachine};
+use super::PET_LABEL;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{dnd, fullscreen, i18n, linux, privacy, resources, settings, AppState};
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct IdleSettings {
+    pub enabled: bool,
+    // 两次空闲动作之间的随机间隔范围（秒）
+    pub min_interval_secs: u64,
+    pub max_interval_secs: u64,
+    // 闲逛时单次移动的最大距离（逻辑像素）
+    pub wander_distance: f64,
+    // 持续空闲超过该时长后入睡，0 表示不入睡
+    pub sleep_after_secs: u64,
+    pub tips: Vec<String>,
+}
+
+impl Default for IdleSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            min_interval_secs: 45,
+            max_interval_secs: 120,
+            wander_distance: 12.0,
+            sleep_after_secs: 600,
+            tips: vec![
+                "说出唤醒词就可以和我对话哦".to_string(),
+                "把文档拖进知识库，我就能帮你查找内容".to_string(),
+                "在托盘菜单里可以开启隐私模式".to_string(),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum IdleBehavior {
+    Wander { dx: f64, dy: f64 },
+    Yawn,
+    Tip { text: String },
+}
+
+// 简单的 xorshift 随机数，空闲动作不需要密码学强度
+struct Rng(u64);
+
+impl Rng {
+    fn seeded() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0x2545_f491_4f6c_dd1d);
+        Self(seed | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn range(&mut self, min: u64, max: u64) -> u64 {
+        if max <= min {
+            return min;
+        }
+        min + self.next() % (max - min + 1)
+    }
+
+    // -1.0 到 1.0 之间的浮点数
+    fn signed_unit(&mut self) -> f64 {
+        (self.next() % 2001) as f64 / 1000.0 - 1.0
+    }
+}
+
+fn idle_settings(app: &AppHandle) -> Option<IdleSettings> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock().ok()?;
+    Some(settings.pet.idle.clone())
+}
+
+// 隐私模式、免打扰、全屏应用或桌宠隐藏时不做任何动作
+fn suppressed(app: &AppHandle) -> bool {
+    if privacy::is_active(app) || fullscreen::is_active(app) || dnd::is_active(app) {
+        return true;
+    }
+    let visible = app
+        .get_window(PET_LABEL)
+        .and_then(|window| window.is_visible().ok())
+        .unwrap_or(false);
+    !visible
+}
+
+fn wander(app: &AppHandle, distance: f64, rng: &mut Rng) -> Option<IdleBehavior> {
+    let window = app.get_window(PET_LABEL)?;
+    let scale = window.scale_factor().ok()?;
+    let position = linux::window_position(&window).ok()?;
+    let size = window.outer_size().ok()?;
+    let monitor = window.current_monitor().ok().flatten()?;
+
+    let (dx, dy) = (rng.signed_unit() * distance, rng.signed_unit() * distance / 2.0);
+    // 保持整个窗口在当前显示器内
+    let (min_x, min_y) = (monitor.position().x, monitor.position().y);
+    let max_x = min_x + monitor.size().width as i32 - size.width as i32;
+    let max_y = min_y + monitor.size().height as i32 - size.height as i32;
+    let x = (position.x + (dx * scale) as i32).clamp(min_x, max_x.max(min_x));
+    let y = (position.y + (dy * scale) as i32).clamp(min_y, max_y.max(min_y));
+    super::move_to(&window, PhysicalPosition::new(x, y)).ok()?;
+    Some(IdleBehavior::Wander { dx, dy })
+}
+
+fn pick_behavior(app: &AppHandle, settings: &IdleSettings, rng: &mut Rng) -> Option<IdleBehavior> {
+    match rng.range(0, 9) {
+        0..=4 => wander(app, settings.wander_distance, rng),
+        5..=7 => Some(IdleBehavior::Yawn),
+        _ if settings.tips.is_empty() => Some(IdleBehavior::Yawn),
+        _ => {
+            let index = rng.range(0, settings.tips.len() as u64 - 1) as usize;
+            Some(IdleBehavior::Tip {
+                text: i18n::translate(&settings.tips[index]),
+            })
+        }
+    }
+}
+
+fn tick(app: &AppHandle, settings: &IdleSettings, rng: &mut Rng) {
+    let machine = app.state::<PetStateMachine>();
+    if machine.current() != PetState::Idle || suppressed(app) {
+        return;
+    }
+
+    if settings.sleep_after_secs > 0 && machine.idle_for() >= Duration::from_secs(settings.sleep_after_secs) {
+        machine.handle(app, PetEvent::Sleep);
+        return;
+    }
+
+    if let Some(behavior) = pick_behavior(app, settings, rng) {
+        debug!("Pet idle behavior {:?}", behavior);
+        if let IdleBehavior::Tip { text } = &behavior {
+            super::bubble::say(app, text);
+        }
+        events::publish(app, AppEvent::PetIdleBehavior(behavior));
+    }
+}
+
+// 在 setup 中调用，间隔从设置中实时读取
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let mut rng = Rng::seeded();
+        loop {
+            let settings = idle_settings(&app).unwrap_or_default();
+            let min = settings.min_interval_secs.max(5);
+            let wait = rng.range(min, settings.max_interval_secs.max(min));
+            tokio::time::sleep(Duration::from_secs(wait)).await;
+
+            // 等待期间设置可能已修改；资源节流时跳过空闲动画
+            let settings = idle_settings(&app).unwrap_or_default();
+            if settings.enabled && !resources::is_throttled(&app) {
+                tick(&app, &settings, &mut rng);
+            }
+        }
+    });
+}
+
+#[tauri::command]
+pub fn set_pet_idle_settings(app: AppHandle, idle: IdleSettings) -> Result<(), AppError> {
+    if idle.min_interval_secs > idle.max_interval_secs {
+        return Err(AppError::invalid("最小间隔不能大于最大间隔"));
+    }
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.pet.idle = idle;
+    settings::save_and_notify(&app, &settings, &["pet"])
+}
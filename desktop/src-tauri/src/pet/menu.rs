@@ -0,0 +1,142 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::deep_link::{self, DeepLink};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{i18n, quick_ask, shutdown, AppState};
+
+const SNOOZE: Duration = Duration::from_secs(60 * 60);
+
+// 每次暂时隐藏或重新显示桌宠都会递增，过期的定时器不再恢复显示
+static SNOOZE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PetMenuAction {
+    Ask,
+    HideForHour,
+    ToggleClickThrough,
+    Settings,
+    Quit,
+}
+
+impl PetMenuAction {
+    fn label_key(self) -> &'static str {
+        match self {
+            PetMenuAction::Ask => "pet_menu.ask",
+            PetMenuAction::HideForHour => "pet_menu.hide_for_hour",
+            PetMenuAction::ToggleClickThrough => "pet_menu.click_through",
+            PetMenuAction::Settings => "pet_menu.settings",
+            PetMenuAction::Quit => "pet_menu.quit",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PetMenuItem {
+    pub action: PetMenuAction,
+    pub label: String,
+    // 只有开关类菜单项有值
+    pub checked: Option<bool>,
+    // 在该项之前显示分隔线
+    pub separator: bool,
+}
+
+// x、y 为桌宠窗口内的逻辑坐标，由前端在该位置绘制菜单
+#[derive(Debug, Clone, Serialize)]
+pub struct PetMenu {
+    pub x: f64,
+    pub y: f64,
+    pub items: Vec<PetMenuItem>,
+}
+
+fn item(action: PetMenuAction, checked: Option<bool>, separator: bool) -> PetMenuItem {
+    PetMenuItem {
+        action,
+        label: i18n::t(action.label_key()),
+        checked,
+        separator,
+    }
+}
+
+fn build(app: &AppHandle, x: f64, y: f64) -> Result<PetMenu, AppError> {
+    let click_through = app.state::<AppState>().settings.lock()?.pet.click_through;
+    Ok(PetMenu {
+        x,
+        y,
+        items: vec![
+            item(PetMenuAction::Ask, None, false),
+            item(PetMenuAction::HideForHour, None, false),
+            item(PetMenuAction::ToggleClickThrough, Some(click_through), false),
+            item(PetMenuAction::Settings, None, true),
+            item(PetMenuAction::Quit, None, false),
+        ],
+    })
+}
+
+// 桌宠重新显示时调用，取消尚未到时的自动恢复
+pub fn cancel_snooze() {
+    SNOOZE_GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+fn snooze(app: &AppHandle) -> Result<(), AppError> {
+    super::hide_desktop_pet(app.clone())?;
+    let generation = SNOOZE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SNOOZE).await;
+        if SNOOZE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        if let Err(e) = super::show_desktop_pet(handle).await {
+            warn!("Failed to show desktop pet after snooze: {}", e);
+        }
+    });
+    info!("Desktop pet hidden for {} minutes", SNOOZE.as_secs() / 60);
+    Ok(())
+}
+
+fn run(app: &AppHandle, action: PetMenuAction) -> Result<(), AppError> {
+    match action {
+        PetMenuAction::Ask => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = quick_ask::show_quick_ask(handle).await {
+                    warn!("Failed to open quick ask: {}", e);
+                }
+            });
+        }
+        PetMenuAction::HideForHour => snooze(app)?,
+        PetMenuAction::ToggleClickThrough => {
+            super::toggle_pet_click_through(app.clone())?;
+        }
+        PetMenuAction::Settings => deep_link::dispatch(
+            app,
+            &DeepLink::Navigate {
+                route: "/settings".to_string(),
+            },
+        )?,
+        PetMenuAction::Quit => shutdown::exit(app, 0),
+    }
+    Ok(())
+}
+
+// 右键桌宠时由前端调用：按当前状态生成菜单，并通过 pet-menu-opened 事件通知桌宠窗口绘制
+#[tauri::command]
+pub fn show_pet_menu(app: AppHandle, x: f64, y: f64) -> Result<PetMenu, AppError> {
+    let menu = build(&app, x, y)?;
+    events::publish(&app, AppEvent::PetMenuOpened(menu.clone()));
+    Ok(menu)
+}
+
+// 执行选中的菜单项，完成后发出 pet-menu-selected 事件，前端据此关闭菜单
+#[tauri::command]
+pub fn select_pet_menu_item(app: AppHandle, action: PetMenuAction) -> Result<(), AppError> {
+    run(&app, action)?;
+    events::publish(&app, AppEvent::PetMenuSelected(action));
+    Ok(())
+}
@@ -0,0 +1,484 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{LogicalPosition, Manager, Monitor, PhysicalPosition, PhysicalSize, WindowBuilder, WindowUrl};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::linux::{self, Positioning};
+use crate::settings::{PetPlacement, PetSize};
+use crate::{settings, AppState};
+use state::PetEvent;
+
+pub mod bubble;
+pub mod idle;
+pub mod menu;
+pub mod state;
+
+pub const PET_LABEL: &str = "desktop-pet";
+
+// 距屏幕边缘的默认间距（逻辑像素）
+const EDGE_MARGIN: f64 = 20.0;
+// 拖动过程中会持续触发 Moved 事件，停止移动一段时间后再写入设置
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+
+// 自定义尺寸的允许范围（逻辑像素）
+const MIN_PET_SIZE: f64 = 120.0;
+const MAX_PET_SIZE: f64 = 800.0;
+
+static MOVE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PetPosition {
+    pub x: f64,
+    pub y: f64,
+    pub scale_factor: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonitorInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub primary: bool,
+}
+
+// 目标显示器可以用 available_monitors() 中的序号或显示器名称指定
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum MonitorSelector {
+    Index(usize),
+    Name(String),
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum PetCorner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+}
+
+impl PetSize {
+    pub fn dimensions(&self) -> (f64, f64) {
+        match *self {
+            PetSize::Small => (180.0, 200.0),
+            PetSize::Medium => (250.0, 280.0),
+            PetSize::Large => (340.0, 380.0),
+            PetSize::Custom { width, height } => (width, height),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), AppError> {
+        let (width, height) = self.dimensions();
+        let range = MIN_PET_SIZE..=MAX_PET_SIZE;
+        if !range.contains(&width) || !range.contains(&height) {
+            return Err(AppError::invalid(format!(
+                "桌宠尺寸需在 {} - {} 之间，当前为 {}x{}",
+                MIN_PET_SIZE, MAX_PET_SIZE, width, height
+            )));
+        }
+        Ok(())
+    }
+}
+
+pub fn pet_window(app: &tauri::AppHandle) -> Result<tauri::Window, AppError> {
+    app.get_window(PET_LABEL)
+        .ok_or_else(|| AppError::Window("桌宠窗口不存在".to_string()))
+}
+
+#[tauri::command]
+pub async fn create_desktop_pet_window(app: tauri::AppHandle) -> Result<(), AppError> {
+    // 检查窗口是否已存在
+    if app.get_window(PET_LABEL).is_some() {
+        info!("Desktop pet window already exists");
+        return Ok(());
+    }
+
+    let pet_settings = app.state::<AppState>().settings.lock()?.pet.clone();
+    let (width, height) = pet_settings.size.dimensions();
+
+    // 创建透明的桌宠窗口
+    let window = WindowBuilder::new(&app, PET_LABEL, WindowUrl::App("desktop-pet-window".into()))
+        .title("") // 空标题
+        .inner_size(width, height)
+        .fullscreen(false)
+        .transparent(linux::capabilities().transparent) // 关键：启用操作系统级别的透明窗口，没有合成器时由前端模拟
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .decorations(false) // 无边框，配合透明效果
+        .resizable(false)
+        .visible(false) // 定位完成后再显示，避免窗口闪现在默认位置
+        .focused(false)
+        .min_inner_size(width, height)
+        .max_inner_size(width, height)
+        .build()?;
+    linux::prepare_pet(&window);
+
+    // 恢复上次的位置，没有记录时定位到主显示器右下角
+    let position = match pet_settings.position {
+        Some(saved) => restored_position(&window, &saved),
+        None => default_position(&window),
+    };
+    // 不能定位时（Wayland 且没有 layer-shell）由合成器决定位置
+    if let Some(position) = position {
+        match linux::move_window(&window, position) {
+            Ok(()) => info!(
+                "Desktop pet window created and positioned at ({}, {})",
+                position.x, position.y
+            ),
+            Err(e) => warn!("Desktop pet window created without positioning: {}", e),
+        }
+    }
+
+    if pet_settings.click_through {
+        window.set_ignore_cursor_events(true)?;
+    }
+    crate::macos::join_all_spaces(&window);
+
+    window.show()?;
+    Ok(())
+}
+
+fn window_size(window: &tauri::Window) -> PhysicalSize<i32> {
+    window
+        .outer_size()
+        .map(|size| PhysicalSize::new(size.width as i32, size.height as i32))
+        .unwrap_or_else(|_| PhysicalSize::new(250, 280))
+}
+
+pub fn monitor_contains(monitor: &Monitor, x: i32, y: i32) -> bool {
+    let origin = monitor.position();
+    let size = monitor.size();
+    x >= origin.x
+        && y >= origin.y
+        && x < origin.x + size.width as i32
+        && y < origin.y + size.height as i32
+}
+
+// 将窗口限制在显示器范围内，保证整个桌宠可见
+pub fn clamp_to_monitor(monitor: &Monitor, window: PhysicalSize<i32>, x: i32, y: i32) -> PhysicalPosition<i32> {
+    let origin = monitor.position();
+    let size = monitor.size();
+    let max_x = origin.x + (size.width as i32 - window.width).max(0);
+    let max_y = origin.y + (size.height as i32 - window.height).max(0);
+    PhysicalPosition::new(x.clamp(origin.x, max_x), y.clamp(origin.y, max_y))
+}
+
+// 换算窗口在目标显示器上的物理尺寸，不同 DPI 的显示器之间移动时尺寸会变化
+fn window_size_on(window: &tauri::Window, monitor: &Monitor) -> PhysicalSize<i32> {
+    let current = window_size(window);
+    let current_scale = window.scale_factor().unwrap_or(1.0);
+    let ratio = monitor.scale_factor() / current_scale;
+    PhysicalSize::new(
+        (current.width as f64 * ratio).round() as i32,
+        (current.height as f64 * ratio).round() as i32,
+    )
+}
+
+fn corner_position(window: &tauri::Window, monitor: &Monitor, corner: PetCorner) -> PhysicalPosition<i32> {
+    let size = window_size_on(window, monitor);
+    let margin = (EDGE_MARGIN * monitor.scale_factor()).round() as i32;
+    let origin = monitor.position();
+    let screen = monitor.size();
+    let left = origin.x + margin;
+    let top = origin.y + margin;
+    let right = origin.x + screen.width as i32 - size.width - margin;
+    let bottom = origin.y + screen.height as i32 - size.height - margin;
+
+    match corner {
+        PetCorner::TopLeft => PhysicalPosition::new(left, top),
+        PetCorner::TopRight => PhysicalPosition::new(right, top),
+        PetCorner::BottomLeft => PhysicalPosition::new(left, bottom),
+        PetCorner::BottomRight => PhysicalPosition::new(right, bottom),
+    }
+}
+
+fn default_position(window: &tauri::Window) -> Option<PhysicalPosition<i32>> {
+    let monitor = window.primary_monitor().ok()??;
+    Some(corner_position(window, &monitor, PetCorner::BottomRight))
+}
+
+// 显示器布局变化后，优先找同名显示器，其次找包含该坐标的显示器，最后回退到主显示器
+fn restored_position(window: &tauri::Window, saved: &PetPlacement) -> Option<PhysicalPosition<i32>> {
+    let monitors = window.available_monitors().unwrap_or_default();
+    let by_name = saved.monitor.as_ref().and_then(|name| {
+        monitors
+            .iter()
+            .find(|monitor| monitor.name() == Some(name))
+            .cloned()
+    });
+    let target = by_name
+        .or_else(|| {
+            monitors
+                .iter()
+                .find(|monitor| monitor_contains(monitor, saved.x, saved.y))
+                .cloned()
+        })
+        .or_else(|| window.primary_monitor().ok().flatten());
+
+    match target {
+        Some(monitor) => Some(clamp_to_monitor(&monitor, window_size(window), saved.x, saved.y)),
+        None => default_position(window),
+    }
+}
+
+// 处理桌宠窗口的 Moved 事件，防抖后把位置写入设置
+pub fn remember_position(window: &tauri::Window, position: PhysicalPosition<i32>) {
+    let generation = MOVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let window = window.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        if MOVE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        state::notify(&window.app_handle(), PetEvent::DragEnded);
+
+        let monitor = window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .and_then(|monitor| monitor.name().cloned());
+        let state = window.state::<AppState>();
+        let Ok(mut settings) = state.settings.lock() else {
+            return;
+        };
+        settings.pet.position = Some(PetPlacement {
+            x: position.x,
+            y: position.y,
+            monitor,
+        });
+        if let Err(e) = state.store.save(&settings) {
+            warn!("Failed to save pet position: {}", e);
+        }
+    });
+}
+
+// 移动桌宠窗口；layer-shell 窗口移动后不会触发 Moved 事件，直接记录位置
+pub fn move_to(window: &tauri::Window, position: PhysicalPosition<i32>) -> Result<(), AppError> {
+    linux::move_window(window, position)?;
+    if linux::capabilities().positioning == Positioning::Anchored {
+        remember_position(window, position);
+        bubble::follow(&window.app_handle());
+    }
+    Ok(())
+}
+
+// 由前端在 mousedown 时调用，交给系统处理窗口拖动；layer-shell 窗口不支持系统拖动，需由前端调用 set_pet_position
+#[tauri::command]
+pub fn start_pet_drag(app: tauri::AppHandle) -> Result<(), AppError> {
+    if linux::capabilities().positioning != Positioning::Absolute {
+        return Err(AppError::Unavailable {
+            message: "当前桌面环境不支持拖动窗口".to_string(),
+            details: Some("Wayland".to_string()),
+        });
+    }
+    pet_window(&app)?.start_dragging()?;
+    state::notify(&app, PetEvent::DragStarted);
+
+    // 没有移动就松开时不会收到 Moved 事件，超时后直接结束拖动状态
+    let generation = MOVE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        if MOVE_GENERATION.load(Ordering::SeqCst) == generation {
+            state::notify(&app, PetEvent::DragEnded);
+        }
+    });
+    Ok(())
+}
+
+// 坐标均为逻辑像素，便于前端按屏幕边缘计算吸附位置
+#[tauri::command]
+pub fn set_pet_position(app: tauri::AppHandle, x: f64, y: f64) -> Result<(), AppError> {
+    let window = pet_window(&app)?;
+    let position = LogicalPosition::new(x, y).to_physical(window.scale_factor()?);
+    move_to(&window, position)
+}
+
+#[tauri::command]
+pub fn get_pet_position(app: tauri::AppHandle) -> Result<PetPosition, AppError> {
+    let window = pet_window(&app)?;
+    let scale_factor = window.scale_factor()?;
+    let position = linux::window_position(&window)?.to_logical::<f64>(scale_factor);
+    Ok(PetPosition {
+        x: position.x,
+        y: position.y,
+        scale_factor,
+    })
+}
+
+#[tauri::command]
+pub fn list_monitors(app: tauri::AppHandle) -> Result<Vec<MonitorInfo>, AppError> {
+    let window = pet_window(&app).or_else(|_| app.get_window("main").ok_or_else(|| "主窗口不存在".to_string()))?;
+    let primary = window.primary_monitor()?.and_then(|monitor| monitor.name().cloned());
+    let monitors = window.available_monitors()?;
+
+    Ok(monitors
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            index,
+            name: monitor.name().cloned(),
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+            scale_factor: monitor.scale_factor(),
+            primary: primary.is_some() && monitor.name() == primary.as_ref(),
+        })
+        .collect())
+}
+
+#[tauri::command]
+pub fn move_pet_to_monitor(
+    app: tauri::AppHandle,
+    monitor: MonitorSelector,
+    corner: Option<PetCorner>,
+) -> Result<PetPosition, AppError> {
+    let window = pet_window(&app)?;
+    let monitors = window.available_monitors()?;
+    let target = match &monitor {
+        MonitorSelector::Index(index) => monitors.get(*index),
+        MonitorSelector::Name(name) => monitors
+            .iter()
+            .find(|monitor| monitor.name() == Some(name)),
+    }
+    .ok_or_else(|| format!("找不到显示器: {:?}", monitor))?;
+
+    let position = corner_position(&window, target, corner.unwrap_or_default());
+    move_to(&window, position)?;
+
+    let logical = position.to_logical::<f64>(target.scale_factor());
+    Ok(PetPosition {
+        x: logical.x,
+        y: logical.y,
+        scale_factor: target.scale_factor(),
+    })
+}
+
+fn apply_click_through(app: &tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    pet_window(app)?.set_ignore_cursor_events(enabled)?;
+
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.pet.click_through = enabled;
+    settings::save_and_notify(app, &settings, &["pet"])?;
+    drop(settings);
+
+    events::publish(app, AppEvent::PetClickThroughChanged(enabled));
+    info!("Desktop pet click-through: {}", enabled);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn set_pet_click_through(app: tauri::AppHandle, enabled: bool) -> Result<(), AppError> {
+    apply_click_through(&app, enabled)
+}
+
+#[tauri::command]
+pub fn toggle_pet_click_through(app: tauri::AppHandle) -> Result<bool, AppError> {
+    let enabled = !app.state::<AppState>().settings.lock()?.pet.click_through;
+    apply_click_through(&app, enabled)?;
+    Ok(enabled)
+}
+
+fn emit_visibility(app: &tauri::AppHandle, visible: bool) {
+    if visible {
+        menu::cancel_snooze();
+    } else {
+        if let Err(e) = bubble::hide(app) {
+            warn!("Failed to hide pet bubble: {}", e);
+        }
+    }
+    events::publish(app, AppEvent::PetVisibilityChanged(visible));
+}
+
+// 窗口被关闭过时重新创建，否则复用已有窗口
+#[tauri::command]
+pub async fn show_desktop_pet(app: tauri::AppHandle) -> Result<(), AppError> {
+    match app.get_window(PET_LABEL) {
+        Some(window) => window.show()?,
+        None => create_desktop_pet_window(app.clone()).await?,
+    }
+    emit_visibility(&app, true);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn hide_desktop_pet(app: tauri::AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_window(PET_LABEL) {
+        window.hide()?;
+    }
+    emit_visibility(&app, false);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn toggle_desktop_pet(app: tauri::AppHandle) -> Result<bool, AppError> {
+    let visible = match app.get_window(PET_LABEL) {
+        Some(window) => window.is_visible()?,
+        None => false,
+    };
+
+    if visible {
+        hide_desktop_pet(app)?;
+    } else {
+        show_desktop_pet(app).await?;
+    }
+    Ok(!visible)
+}
+
+// 调整尺寸时保持桌宠贴靠的角不变：靠右则右边缘不动，靠下则下边缘不动
+#[tauri::command]
+pub fn set_pet_size(app: tauri::AppHandle, preset: PetSize) -> Result<PetSize, AppError> {
+    preset.validate()?;
+    let window = pet_window(&app)?;
+    let (width, height) = preset.dimensions();
+
+    let old_position = linux::window_position(&window)?;
+    let old_size = window_size(&window);
+    let monitor = window.current_monitor()?;
+
+    let size = tauri::LogicalSize::new(width, height);
+    window.set_min_size(Some(size))?;
+    window.set_max_size(Some(size))?;
+    window.set_size(size)?;
+
+    if let Some(monitor) = monitor {
+        let scale = monitor.scale_factor();
+        let new_size = PhysicalSize::new((width * scale).round() as i32, (height * scale).round() as i32);
+        let origin = monitor.position();
+        let screen = monitor.size();
+        let center_x = old_position.x + old_size.width / 2;
+        let center_y = old_position.y + old_size.height / 2;
+        let anchored_right = center_x > origin.x + screen.width as i32 / 2;
+        let anchored_bottom = center_y > origin.y + screen.height as i32 / 2;
+
+        let x = if anchored_right {
+            old_position.x + old_size.width - new_size.width
+        } else {
+            old_position.x
+        };
+        let y = if anchored_bottom {
+            old_position.y + old_size.height - new_size.height
+        } else {
+            old_position.y
+        };
+        let position = clamp_to_monitor(&monitor, new_size, x, y);
+        move_to(&window, position)?;
+    }
+
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.pet.size = preset;
+    settings::save_and_notify(&app, &settings, &["pet"])?;
+    Ok(preset)
+}
@@ -0,0 +1,165 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::debug;
+
+use crate::events::{self, AppEvent};
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PetState {
+    #[default]
+    Idle,
+    Listening,
+    Thinking,
+    Speaking,
+    Sleeping,
+    Dragging,
+}
+
+// 驱动状态切换的助手活动
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PetEvent {
+    ListeningStarted,
+    ListeningStopped,
+    SpeakingStarted,
+    SpeakingFinished,
+    DragStarted,
+    DragEnded,
+    // 对话被取消或没有可播放的回答
+    TurnEnded,
+    // 持续空闲时由空闲动作任务触发
+    Sleep,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PetStateChanged {
+    pub state: PetState,
+    pub previous: PetState,
+}
+
+#[derive(Debug)]
+struct Machine {
+    current: PetState,
+    // 拖动期间发生的活动，松开后恢复到该状态
+    resume: PetState,
+    // 进入当前状态的时间
+    since: Instant,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            current: PetState::Idle,
+            resume: PetState::Idle,
+            since: Instant::now(),
+        }
+    }
+}
+
+impl Machine {
+    fn next(&self, event: PetEvent) -> PetState {
+        use PetEvent::*;
+        use PetState::*;
+        match (self.current, event) {
+            (Dragging, DragEnded) => self.resume,
+            (Dragging, _) => Dragging,
+            (_, DragStarted) => Dragging,
+            (_, ListeningStarted) => Listening,
+            // 录音结束后等待识别和回答
+            (Listening, ListeningStopped) => Thinking,
+            (_, SpeakingStarted) => Speaking,
+            (Speaking, SpeakingFinished) => Idle,
+            (Listening | Thinking, TurnEnded) => Idle,
+            // 只从空闲进入睡眠，避免打断正在进行的对话
+            (Idle, Sleep) => Sleeping,
+            (current, _) => current,
+        }
+    }
+
+    // 拖动时记录其他活动对应的状态，供松开后恢复
+    fn track_resume(&mut self, event: PetEvent) {
+        if self.current != PetState::Dragging || event == PetEvent::DragEnded {
+            return;
+        }
+        let shadow = Machine {
+            current: self.resume,
+            resume: self.resume,
+            since: self.since,
+        };
+        self.resume = shadow.next(event);
+    }
+}
+
+#[derive(Default)]
+pub struct PetStateMachine {
+    machine: Mutex<Machine>,
+}
+
+impl PetStateMachine {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn current(&self) -> PetState {
+        self.machine.lock().map(|m| m.current).unwrap_or_default()
+    }
+
+    pub fn idle_for(&self) -> Duration {
+        self.machine.lock().map(|m| m.since.elapsed()).unwrap_or_default()
+    }
+
+    fn apply(&self, app: &AppHandle, update: impl FnOnce(&mut Machine) -> PetState) {
+        let Ok(mut machine) = self.machine.lock() else {
+            return;
+        };
+        let previous = machine.current;
+        let state = update(&mut machine);
+        if state == PetState::Dragging && previous != PetState::Dragging {
+            machine.resume = previous;
+        }
+        if state != previous {
+            machine.since = Instant::now();
+        }
+        machine.current = state;
+        drop(machine);
+
+        if state != previous {
+            debug!("Pet state {:?} -> {:?}", previous, state);
+            events::publish(app, AppEvent::PetStateChanged(PetStateChanged { state, previous }));
+        }
+    }
+
+    pub fn handle(&self, app: &AppHandle, event: PetEvent) {
+        self.apply(app, |machine| {
+            machine.track_resume(event);
+            machine.next(event)
+        });
+    }
+
+    // 前端直接指定状态，拖动中只更新松开后的状态
+    pub fn set(&self, app: &AppHandle, state: PetState) {
+        self.apply(app, |machine| {
+            if machine.current == PetState::Dragging && state != PetState::Dragging {
+                machine.resume = state;
+                return PetState::Dragging;
+            }
+            state
+        });
+    }
+}
+
+pub fn notify(app: &AppHandle, event: PetEvent) {
+    app.state::<PetStateMachine>().handle(app, event);
+}
+
+#[tauri::command]
+pub fn set_pet_state(app: AppHandle, machine: State<'_, PetStateMachine>, state: PetState) {
+    machine.set(&app, state);
+}
+
+#[tauri::command]
+pub fn get_pet_state(machine: State<'_, PetStateMachine>) -> PetState {
+    machine.current()
+}
@@ -0,0 +1,528 @@
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::{debug, info, info_span, warn, Instrument};
+
+use crate::audio::capture::{self, AudioCapture};
+use crate::audio::AudioPlayer;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::llm::{self, BackendCredentials, ChatMessage, ChatToken};
+use crate::memory::{self, MemoryJob};
+use crate::persona::{self, Persona};
+use crate::pet::state::PetEvent;
+use crate::storage::now_millis;
+use crate::{analytics, captions, intents, pet, plugins, stt, tts, AppState};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+const CANCELLED: &str = "对话已取消";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PipelineSettings {
+    // 后端的一句话文本接口，模型来源为后端时使用
+    pub llm_path: String,
+    // 单次聆听的最长时间（秒）
+    pub listen_timeout_secs: u64,
+    // 播放回答时用户开口则打断播放，这段录音作为下一轮的输入
+    pub barge_in: bool,
+    // 打断检测的能量阈值（dBFS），高于平时以免把扬声器的回声当成说话
+    pub barge_in_threshold_db: f32,
+}
+
+impl Default for PipelineSettings {
+    fn default() -> Self {
+        Self {
+            llm_path: "/api/voice/oneshot_text".to_string(),
+            listen_timeout_secs: 30,
+            barge_in: true,
+            barge_in_threshold_db: -30.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct TurnRequest {
+    // 直接提供文本时跳过录音和识别
+    pub text: Option<String>,
+    // 后端一句话接口使用的用户凭证
+    pub api_key: String,
+    pub api_secret: String,
+    pub assistant_id: Option<i64>,
+    pub session_id: Option<String>,
+    pub language: Option<String>,
+    pub device_id: Option<String>,
+    pub token: Option<String>,
+    // 所属对话，用于选择该对话指定的模型来源
+    pub conversation_id: Option<String>,
+}
+
+impl TurnRequest {
+    fn credentials(&self) -> BackendCredentials {
+        BackendCredentials {
+            api_key: self.api_key.clone(),
+            api_secret: self.api_secret.clone(),
+            assistant_id: self.assistant_id,
+            session_id: self.session_id.clone(),
+            language: self.language.clone(),
+            token: self.token.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Listening,
+    Transcribing,
+    Thinking,
+    Speaking,
+}
+
+impl Stage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Listening => "listening",
+            Stage::Transcribing => "transcribing",
+            Stage::Thinking => "thinking",
+            Stage::Speaking => "speaking",
+        }
+    }
+}
+
+// 阶段开始时 duration_ms 为 None，结束时为该阶段耗时
+#[derive(Debug, Clone, Serialize)]
+pub struct StageEvent {
+    pub turn: u64,
+    pub stage: Stage,
+    pub duration_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StageTiming {
+    pub stage: Stage,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TurnOutcome {
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl TurnOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TurnOutcome::Completed => "completed",
+            TurnOutcome::Cancelled => "cancelled",
+            TurnOutcome::Failed => "failed",
+        }
+    }
+}
+
+// 一轮对话的耗时分解，失败或取消时只包含已完成的阶段
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnTiming {
+    pub turn: u64,
+    // 毫秒时间戳
+    pub started_at: i64,
+    pub total_ms: u64,
+    pub outcome: TurnOutcome,
+    pub stages: Vec<StageTiming>,
+    // 从开始合成到开始播放的耗时，说话阶段的其余时间为播放本身
+    pub first_audio_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TurnResult {
+    pub turn: u64,
+    pub transcript: String,
+    pub reply: String,
+    // 用户在播放回答时开口，录音仍在进行，下一次 run_turn 会直接使用它
+    pub barged_in: bool,
+    pub timings: Vec<StageTiming>,
+}
+
+// 同一时间只进行一轮对话，新的对话或 cancel_turn 会让当前对话在下一个检查点退出
+#[derive(Default)]
+pub struct Pipeline {
+    active: AtomicU64,
+    next_id: AtomicU64,
+    last_timing: Mutex<Option<TurnTiming>>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn begin(&self) -> u64 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+        self.active.store(id, Ordering::SeqCst);
+        id
+    }
+
+    fn is_current(&self, id: u64) -> bool {
+        self.active.load(Ordering::SeqCst) == id
+    }
+
+    fn finish(&self, id: u64) {
+        self.active.compare_exchange(id, 0, Ordering::SeqCst, Ordering::SeqCst).ok();
+    }
+}
+
+struct Turn {
+    app: AppHandle,
+    id: u64,
+    settings: PipelineSettings,
+    timings: Vec<StageTiming>,
+    first_audio_ms: Option<u64>,
+}
+
+impl Turn {
+    fn check(&self) -> Result<(), AppError> {
+        if self.app.state::<Pipeline>().is_current(self.id) {
+            Ok(())
+        } else {
+            Err(AppError::Cancelled(CANCELLED.to_string()))
+        }
+    }
+
+    async fn cancelled(&self) {
+        while self.check().is_ok() {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    fn emit(&self, stage: Stage, duration_ms: Option<u64>) {
+        let event = StageEvent {
+            turn: self.id,
+            stage,
+            duration_ms,
+        };
+        events::publish(&self.app, AppEvent::PipelineStage(event));
+    }
+
+    // 执行一个阶段并记录耗时，取消时立即返回
+    async fn stage<T>(&mut self, stage: Stage, task: impl Future<Output = Result<T, AppError>>) -> Result<T, AppError> {
+        self.check()?;
+        self.emit(stage, None);
+        let span = info_span!("pipeline_stage", turn = self.id, stage = stage.as_str());
+        let started = Instant::now();
+        let result = async {
+            tokio::select! {
+                result = task => result,
+                _ = self.cancelled() => Err(AppError::Cancelled(CANCELLED.to_string())),
+            }
+        }
+        .instrument(span.clone())
+        .await;
+        let duration_ms = started.elapsed().as_millis() as u64;
+        debug!(parent: &span, duration_ms, ok = result.is_ok(), "Stage finished");
+        self.emit(stage, Some(duration_ms));
+        self.timings.push(StageTiming { stage, duration_ms });
+        result
+    }
+}
+
+// 开始录音并等待 VAD 判定一句话结束；打断时录音已经开始，直接等待它结束
+async fn listen(app: &AppHandle, settings: &PipelineSettings, device_id: Option<String>) -> Result<Vec<i16>, AppError> {
+    let capture = app.state::<AudioCapture>();
+    if !capture.is_recording() {
+        let mut vad = capture::vad_settings(app)?;
+        vad.enabled = true;
+        vad.auto_stop = true;
+        capture::begin_recording(app, device_id, None, vad)?;
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(settings.listen_timeout_secs.max(1));
+    let mut heard = false;
+    while capture.is_recording() {
+        heard |= capture.speech_detected();
+        if Instant::now() >= deadline {
+            capture::finish_recording(app)?;
+            break;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    if !heard {
+        return Err(AppError::invalid("没有检测到说话"));
+    }
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || stt::load_audio(&handle, None)).await?
+}
+
+async fn transcribe(
+    app: &AppHandle,
+    audio: Vec<i16>,
+    language: Option<String>,
+    token: Option<String>,
+) -> Result<String, AppError> {
+    let handle = app.clone();
+    let transcript =
+        tauri::async_runtime::spawn_blocking(move || stt::transcribe_samples(&handle, &audio, language, token))
+            .await??;
+    if transcript.text.is_empty() {
+        return Err(AppError::invalid("没有识别到内容"));
+    }
+    Ok(transcript.text)
+}
+
+// 回答以 chat-token 事件逐段推送；对话被取消后停止生成
+async fn ask(
+    app: &AppHandle,
+    turn_id: u64,
+    request: &TurnRequest,
+    persona: Option<&Persona>,
+    text: &str,
+) -> Result<String, AppError> {
+    // 本地意图规则和已启用的插件优先，命中时不再调用模型
+    if let Some(reply) = intents::handle(app, text).await? {
+        return Ok(reply);
+    }
+    if let Some(reply) = plugins::handle(app, text, request.language.as_deref()).await? {
+        return Ok(reply);
+    }
+    let handle = app.clone();
+    let conversation_id = request.conversation_id.clone();
+    let credentials = request.credentials();
+    app.state::<MemoryJob>().remember_credentials(&credentials);
+    let text = text.to_string();
+    let persona = persona.cloned();
+    let generation = tauri::async_runtime::spawn_blocking(move || {
+        // 指定对话时带上该对话的记忆和最近消息
+        let mut messages = match conversation_id.as_deref() {
+            Some(conversation_id) => memory::context(&handle, conversation_id)?,
+            None => Vec::new(),
+        };
+        persona::apply(persona.as_ref(), &mut messages);
+        messages.push(ChatMessage::user(text));
+        let id = llm::next_id();
+        llm::generate(
+            &handle,
+            id,
+            conversation_id.as_deref(),
+            &messages,
+            credentials,
+            persona.and_then(|persona| persona.temperature),
+            &mut |token| {
+                let event = ChatToken {
+                    id,
+                    conversation_id: conversation_id.clone(),
+                    token: token.to_string(),
+                };
+                events::publish(&handle, AppEvent::ChatToken(event));
+                handle.state::<Pipeline>().is_current(turn_id)
+            },
+        )
+    })
+    .await??;
+    if generation.text.is_empty() {
+        return Err(AppError::Internal("对话服务没有返回回答".to_string()));
+    }
+    Ok(generation.text)
+}
+
+struct Spoken {
+    barged_in: bool,
+    first_audio_ms: u64,
+}
+
+// 播放回答直到结束；开启打断时同时录音，检测到说话即停止播放，返回是否被打断
+async fn speak(
+    app: &AppHandle,
+    settings: &PipelineSettings,
+    reply: String,
+    voice: Option<String>,
+    token: Option<String>,
+) -> Result<Spoken, AppError> {
+    let started = Instant::now();
+    let id = tts::speak(app.clone(), reply.clone(), voice, token).await?;
+    let first_audio_ms = started.elapsed().as_millis() as u64;
+    debug!(first_audio_ms, "Playback started");
+    captions::feed(app, id, &reply);
+    let (player, capture) = (app.state::<AudioPlayer>(), app.state::<AudioCapture>());
+
+    // 打断检测的录音不通知前端和桌宠，确认用户开口后才视为开始聆听
+    let monitoring = settings.barge_in
+        && !capture.is_recording()
+        && player.is_playing()
+        && match capture::vad_settings(app) {
+            Ok(mut vad) => {
+                vad.enabled = true;
+                vad.auto_stop = true;
+                vad.threshold_db = settings.barge_in_threshold_db;
+                capture
+                    .start(app.clone(), None, None, vad)
+                    .map_err(|e| warn!("Barge-in monitor unavailable: {}", e))
+                    .is_ok()
+            }
+            Err(_) => false,
+        };
+
+    while player.is_playing() {
+        if monitoring && capture.speech_detected() {
+            info!("User barged in, stopping playback {}", id);
+            player.stop_if(id);
+            pet::state::notify(app, PetEvent::ListeningStarted);
+            return Ok(Spoken {
+                barged_in: true,
+                first_audio_ms,
+            });
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    if monitoring {
+        capture.stop()?;
+    }
+    Ok(Spoken {
+        barged_in: false,
+        first_audio_ms,
+    })
+}
+
+async fn run(turn: &mut Turn, request: TurnRequest) -> Result<TurnResult, AppError> {
+    let app = turn.app.clone();
+    let settings = turn.settings.clone();
+    // 当前人设的系统提示、温度和语音用于本轮对话
+    let persona = persona::active(&app);
+
+    let transcript = match request.text.clone().filter(|text| !text.trim().is_empty()) {
+        Some(text) => text.trim().to_string(),
+        None => {
+            let audio = turn
+                .stage(Stage::Listening, listen(&app, &settings, request.device_id.clone()))
+                .await?;
+            turn.stage(
+                Stage::Transcribing,
+                transcribe(&app, audio, request.language.clone(), request.token.clone()),
+            )
+            .await?
+        }
+    };
+    let reply = turn
+        .stage(
+            Stage::Thinking,
+            ask(&app, turn.id, &request, persona.as_ref(), &transcript),
+        )
+        .await?;
+    let spoken = turn
+        .stage(
+            Stage::Speaking,
+            speak(
+                &app,
+                &settings,
+                reply.clone(),
+                persona.and_then(|persona| persona.voice),
+                request.token.clone(),
+            ),
+        )
+        .await?;
+    turn.first_audio_ms = Some(spoken.first_audio_ms);
+
+    Ok(TurnResult {
+        turn: turn.id,
+        transcript,
+        reply,
+        barged_in: spoken.barged_in,
+        timings: turn.timings.clone(),
+    })
+}
+
+// 完成一轮语音对话：录音 → 识别 → 回答 → 播放；传入 text 时从回答开始
+#[tauri::command]
+pub async fn run_turn(app: AppHandle, request: TurnRequest) -> Result<TurnResult, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.pipeline.clone();
+    let pipeline = app.state::<Pipeline>();
+    let mut turn = Turn {
+        app: app.clone(),
+        id: pipeline.begin(),
+        settings,
+        timings: Vec::new(),
+        first_audio_ms: None,
+    };
+    info!("Voice turn {} started", turn.id);
+
+    let started_at = now_millis();
+    let started = Instant::now();
+    let span = info_span!("voice_turn", turn = turn.id);
+    let result = run(&mut turn, request).instrument(span).await;
+    pipeline.finish(turn.id);
+
+    let timing = TurnTiming {
+        turn: turn.id,
+        started_at,
+        total_ms: started.elapsed().as_millis() as u64,
+        outcome: match &result {
+            Ok(_) => TurnOutcome::Completed,
+            Err(AppError::Cancelled(_)) => TurnOutcome::Cancelled,
+            Err(_) => TurnOutcome::Failed,
+        },
+        stages: turn.timings.clone(),
+        first_audio_ms: turn.first_audio_ms,
+    };
+    let breakdown: Vec<String> = timing
+        .stages
+        .iter()
+        .map(|stage| format!("{} {}ms", stage.stage.as_str(), stage.duration_ms))
+        .collect();
+    info!(
+        "Voice turn {} {} in {}ms ({}; first audio {:?}ms)",
+        timing.turn,
+        timing.outcome.as_str(),
+        timing.total_ms,
+        breakdown.join(", "),
+        timing.first_audio_ms
+    );
+    analytics::record_turn(&app, &timing);
+    if let Ok(mut last) = pipeline.last_timing.lock() {
+        *last = Some(timing);
+    }
+    match &result {
+        Ok(result) => {
+            events::publish(&app, AppEvent::PipelineTurnFinished(result.clone()));
+            if !result.reply.trim().is_empty() {
+                pet::bubble::say(&app, &result.reply);
+            }
+        }
+        // 取消时由 cancel_turn 或新的对话负责更新桌宠状态
+        Err(AppError::Cancelled(_)) => info!("Voice turn {} cancelled", turn.id),
+        Err(e) => {
+            warn!("Voice turn {} failed: {}", turn.id, e);
+            pet::state::notify(&app, PetEvent::TurnEnded);
+        }
+    }
+    result
+}
+
+// 最近一轮对话的耗时分解，还没有对话时返回 None
+#[tauri::command]
+pub fn get_last_turn_timing(pipeline: State<'_, Pipeline>) -> Result<Option<TurnTiming>, AppError> {
+    Ok(pipeline.last_timing.lock()?.clone())
+}
+
+// 取消当前对话：停止录音和播放，正在进行的阶段在下一个检查点退出
+pub fn cancel(app: &AppHandle) -> Result<bool, AppError> {
+    let active = app.state::<Pipeline>().active.swap(0, Ordering::SeqCst);
+    if active == 0 {
+        return Ok(false);
+    }
+    if app.state::<AudioCapture>().is_recording() {
+        capture::finish_recording(app)?;
+    }
+    app.state::<AudioPlayer>().stop();
+    pet::state::notify(app, PetEvent::TurnEnded);
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn cancel_turn(app: AppHandle) -> Result<bool, AppError> {
+    cancel(&app)
+}
@@ -0,0 +1,132 @@
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Manager, Url, Window};
+use tracing::warn;
+
+use crate::deep_link::SCHEME;
+use crate::error::AppError;
+use crate::i18n;
+use crate::notifications::{self, CATEGORY_TASK};
+use crate::window_state::MAIN_LABEL;
+
+#[cfg(windows)]
+pub mod windows;
+
+// 主窗口任务栏按钮上的进度，百分比为 0 - 100
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(tag = "state", content = "percent", rename_all = "lowercase")]
+pub enum TaskbarProgress {
+    Hidden,
+    Indeterminate,
+    Normal(u8),
+    Error(u8),
+}
+
+// 通知上的按钮，点击后打开 url（深度链接或 file:// 路径）
+#[derive(Debug, Clone, Serialize)]
+pub struct ToastAction {
+    pub label: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Toast {
+    pub title: String,
+    pub body: String,
+    pub actions: Vec<ToastAction>,
+}
+
+// 各平台的桌面集成；没有对应能力的平台使用 Generic
+pub trait DesktopIntegration: Send + Sync {
+    fn set_progress(&self, window: &Window, progress: TaskbarProgress) -> Result<(), AppError>;
+
+    // 返回 false 表示平台不支持带按钮的通知，由调用方退回普通通知
+    fn show_toast(&self, app: &AppHandle, toast: &Toast) -> Result<bool, AppError>;
+
+    // 在阻塞线程中调用，要求用户通过系统认证；返回 None 表示没有可用的系统认证
+    fn verify_user(&self, reason: &str) -> Result<Option<bool>, AppError>;
+
+    // 系统设置中是否允许本应用显示通知，None 表示无法查询
+    fn notifications_enabled(&self, app: &AppHandle) -> Option<bool>;
+}
+
+#[cfg(not(windows))]
+pub struct Generic;
+
+#[cfg(not(windows))]
+impl DesktopIntegration for Generic {
+    fn set_progress(&self, _window: &Window, _progress: TaskbarProgress) -> Result<(), AppError> {
+        Ok(())
+    }
+
+    fn show_toast(&self, _app: &AppHandle, _toast: &Toast) -> Result<bool, AppError> {
+        Ok(false)
+    }
+
+    fn notifications_enabled(&self, _app: &AppHandle) -> Option<bool> {
+        None
+    }
+
+    // macOS 和 Linux 能直接调用的认证（osascript 管理员授权、pkexec）都要求管理员账户，
+    // 普通用户会因此无法清除自己的数据，这里不提供系统认证，由调用方改用确认对话框
+    fn verify_user(&self, _reason: &str) -> Result<Option<bool>, AppError> {
+        Ok(None)
+    }
+}
+
+#[cfg(windows)]
+pub fn current() -> &'static dyn DesktopIntegration {
+    &windows::WindowsIntegration
+}
+
+#[cfg(not(windows))]
+pub fn current() -> &'static dyn DesktopIntegration {
+    &Generic
+}
+
+// 长时间操作的进度显示在主窗口的任务栏按钮上，失败只写日志
+pub fn set_progress(app: &AppHandle, progress: TaskbarProgress) {
+    let Some(window) = app.get_window(MAIN_LABEL) else {
+        return;
+    };
+    if let Err(e) = current().set_progress(&window, progress) {
+        warn!("Failed to update taskbar progress: {}", e);
+    }
+}
+
+fn open_folder_action(path: &Path) -> Option<ToastAction> {
+    let folder = if path.is_dir() { path } else { path.parent()? };
+    let url = Url::from_directory_path(folder).ok()?;
+    Some(ToastAction {
+        label: i18n::t("toast.open_folder"),
+        url: url.to_string(),
+    })
+}
+
+// operation 与 lingecho://retry/<operation> 对应，由前端按原参数重新执行
+fn retry_action(operation: &str) -> ToastAction {
+    ToastAction {
+        label: i18n::t("toast.retry"),
+        url: format!("{}://retry/{}", SCHEME, operation),
+    }
+}
+
+fn notify(app: &AppHandle, toast: Toast) {
+    if let Err(e) = notifications::send_toast(app, CATEGORY_TASK, toast) {
+        warn!("{}", e);
+    }
+}
+
+// 操作完成：清除任务栏进度，通知中提供“打开文件夹”
+pub fn finished(app: &AppHandle, title: String, body: String, output: Option<&Path>) {
+    set_progress(app, TaskbarProgress::Hidden);
+    let actions = output.and_then(open_folder_action).into_iter().collect();
+    notify(app, Toast { title, body, actions });
+}
+
+// 操作失败：任务栏显示错误状态，通知中提供“重试”
+pub fn failed(app: &AppHandle, operation: &str, title: String, body: String) {
+    set_progress(app, TaskbarProgress::Error(100));
+    let actions = vec![retry_action(operation)];
+    notify(app, Toast { title, body, actions });
+}
@@ -0,0 +1,129 @@
+use tauri::{AppHandle, Window};
+use tracing::warn;
+use windows::core::HSTRING;
+use windows::Data::Xml::Dom::XmlDocument;
+use windows::Security::Credentials::UI::{
+    UserConsentVerificationResult, UserConsentVerifier, UserConsentVerifierAvailability,
+};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_INPROC_SERVER};
+use windows::Win32::UI::Shell::{
+    ITaskbarList3, TaskbarList, TBPF_ERROR, TBPF_INDETERMINATE, TBPF_NOPROGRESS, TBPF_NORMAL,
+};
+use windows::UI::Notifications::{NotificationSetting, ToastNotification, ToastNotificationManager};
+
+use super::{DesktopIntegration, TaskbarProgress, Toast};
+use crate::error::AppError;
+
+// 开发构建没有注册 AppUserModelID，借用 PowerShell 的 ID 显示通知（与 Tauri 的做法一致）
+const DEV_APP_ID: &str = "{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\\WindowsPowerShell\\v1.0\\powershell.exe";
+
+pub struct WindowsIntegration;
+
+fn app_id(app: &AppHandle) -> String {
+    if cfg!(debug_assertions) {
+        DEV_APP_ID.to_string()
+    } else {
+        app.config().tauri.bundle.identifier.clone()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+// 按钮使用 protocol 激活：点击后由系统打开链接，应用未运行时也能响应
+fn toast_xml(toast: &Toast) -> String {
+    let mut xml = format!(
+        "<toast><visual><binding template=\"ToastGeneric\"><text>{}</text><text>{}</text></binding></visual>",
+        escape(&toast.title),
+        escape(&toast.body)
+    );
+    if !toast.actions.is_empty() {
+        xml.push_str("<actions>");
+        for action in &toast.actions {
+            xml.push_str(&format!(
+                "<action content=\"{}\" arguments=\"{}\" activationType=\"protocol\"/>",
+                escape(&action.label),
+                escape(&action.url)
+            ));
+        }
+        xml.push_str("</actions>");
+    }
+    xml.push_str("</toast>");
+    xml
+}
+
+// 需要在已初始化 COM 的主线程上调用
+fn apply_progress(hwnd: HWND, progress: TaskbarProgress) -> windows::core::Result<()> {
+    // SAFETY: hwnd 来自 Tauri 创建的窗口，COM 已由 WebView2 在主线程初始化
+    unsafe {
+        let taskbar: ITaskbarList3 = CoCreateInstance(&TaskbarList, None, CLSCTX_INPROC_SERVER)?;
+        taskbar.HrInit()?;
+        match progress {
+            TaskbarProgress::Hidden => taskbar.SetProgressState(hwnd, TBPF_NOPROGRESS),
+            TaskbarProgress::Indeterminate => taskbar.SetProgressState(hwnd, TBPF_INDETERMINATE),
+            TaskbarProgress::Normal(percent) => {
+                taskbar.SetProgressState(hwnd, TBPF_NORMAL)?;
+                taskbar.SetProgressValue(hwnd, percent.min(100) as u64, 100)
+            }
+            TaskbarProgress::Error(percent) => {
+                taskbar.SetProgressState(hwnd, TBPF_ERROR)?;
+                taskbar.SetProgressValue(hwnd, percent.min(100) as u64, 100)
+            }
+        }
+    }
+}
+
+impl DesktopIntegration for WindowsIntegration {
+    fn set_progress(&self, window: &Window, progress: TaskbarProgress) -> Result<(), AppError> {
+        let hwnd = window.hwnd()?;
+        window.run_on_main_thread(move || {
+            if let Err(e) = apply_progress(hwnd, progress) {
+                warn!("Failed to set taskbar progress: {}", e);
+            }
+        })?;
+        Ok(())
+    }
+
+    fn show_toast(&self, app: &AppHandle, toast: &Toast) -> Result<bool, AppError> {
+        let app_id = app_id(app);
+        let show = || -> windows::core::Result<()> {
+            let document = XmlDocument::new()?;
+            document.LoadXml(&HSTRING::from(toast_xml(toast)))?;
+            let notification = ToastNotification::CreateToastNotification(&document)?;
+            ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id))?.Show(&notification)
+        };
+        show().map_err(|e| AppError::Internal(format!("发送通知失败: {}", e)))?;
+        Ok(true)
+    }
+
+    // Windows Hello（PIN、指纹或面部识别），未设置时返回 None
+    fn verify_user(&self, reason: &str) -> Result<Option<bool>, AppError> {
+        let verify = || -> windows::core::Result<Option<bool>> {
+            if UserConsentVerifier::CheckAvailabilityAsync()?.get()? != UserConsentVerifierAvailability::Available {
+                return Ok(None);
+            }
+            let result = UserConsentVerifier::RequestVerificationAsync(&HSTRING::from(reason))?.get()?;
+            Ok(Some(result == UserConsentVerificationResult::Verified))
+        };
+        verify().map_err(|e| AppError::unavailable("系统认证失败", e))
+    }
+
+    // 用户、组策略或清单都可能关闭通知
+    fn notifications_enabled(&self, app: &AppHandle) -> Option<bool> {
+        let setting = ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(app_id(app)))
+            .and_then(|notifier| notifier.Setting());
+        match setting {
+            Ok(setting) => Some(setting == NotificationSetting::Enabled),
+            Err(e) => {
+                warn!("Failed to read notification setting: {}", e);
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,744 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State, Url};
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+use crate::network::{self, ClientConfig};
+use crate::notifications::{self, CATEGORY_ASSISTANT};
+use crate::{i18n, launcher, settings, AppState};
+
+pub const PLUGINS_DIR: &str = "plugins";
+const MANIFEST_FILE: &str = "plugin.json";
+// 插件自己的数据目录，始终允许读写
+const DATA_DIR: &str = "data";
+// 插件通过宿主读取文件或网络响应的大小上限
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+// 传给插件进程的环境变量，其余全部清除
+const INHERITED_ENV: &[&str] = &["PATH", "SYSTEMROOT", "TEMP", "TMP", "LANG"];
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PluginSettings {
+    // 已启用的插件 id，新安装的插件默认不启用
+    pub enabled: Vec<String>,
+    // 单次调用等待插件回答的时间
+    pub timeout_secs: u32,
+}
+
+impl Default for PluginSettings {
+    fn default() -> Self {
+        Self {
+            enabled: Vec::new(),
+            timeout_secs: 15,
+        }
+    }
+}
+
+// 插件目录下的 plugin.json
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Manifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    // 插件程序，插件目录中存在同名文件时使用该文件，否则在 PATH 中查找（如 python、node）
+    pub command: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub intents: Vec<Intent>,
+    // 只约束经由宿主代理的请求，不限制插件进程本身
+    #[serde(default)]
+    pub permissions: Permissions,
+}
+
+// 关键词按不区分大小写的包含匹配，patterns 为正则表达式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Intent {
+    pub name: String,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Permissions {
+    // 允许访问的主机名，"*.example.com" 同时匹配其子域名
+    pub network: Vec<String>,
+    pub filesystem: Vec<FsPermission>,
+}
+
+// path 支持以 ~ 开头表示用户主目录，write 为 false 时只读
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FsPermission {
+    pub path: String,
+    #[serde(default)]
+    pub write: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PluginInfo {
+    #[serde(flatten)]
+    pub manifest: Manifest,
+    pub enabled: bool,
+    pub running: bool,
+}
+
+// 插件执行完成后由宿主处理的动作
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PluginAction {
+    OpenUrl { url: String },
+    Notify { title: String, body: String },
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginResponse {
+    // 由助手说出的回答，为空时只执行动作
+    pub reply: String,
+    pub actions: Vec<PluginAction>,
+}
+
+#[derive(Debug, Serialize)]
+struct Invocation<'a> {
+    intent: &'a str,
+    text: &'a str,
+    language: &'a str,
+}
+
+// 插件输出的一行：带 method 的是插件向宿主发起的请求，其余是对调用的回答
+#[derive(Debug, Deserialize)]
+struct Incoming {
+    #[serde(default)]
+    id: Value,
+    method: Option<String>,
+    #[serde(default)]
+    params: Value,
+    result: Option<Value>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HttpRequest {
+    #[serde(default)]
+    method: Option<String>,
+    url: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    body: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileRequest {
+    path: String,
+    #[serde(default)]
+    content: String,
+}
+
+struct Matcher {
+    name: String,
+    keywords: Vec<String>,
+    patterns: Vec<Regex>,
+}
+
+impl Matcher {
+    fn new(intent: &Intent) -> Result<Self, AppError> {
+        let patterns = intent
+            .patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| AppError::invalid(format!("意图规则无效 {}: {}", pattern, e)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            name: intent.name.clone(),
+            keywords: intent
+                .keywords
+                .iter()
+                .map(|keyword| keyword.trim().to_lowercase())
+                .filter(|keyword| !keyword.is_empty())
+                .collect(),
+            patterns,
+        })
+    }
+
+    fn matches(&self, text: &str, lowered: &str) -> bool {
+        self.keywords.iter().any(|keyword| lowered.contains(keyword.as_str()))
+            || self.patterns.iter().any(|pattern| pattern.is_match(text))
+    }
+}
+
+struct Process {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    // stderr 的最后一行，进程意外退出时作为错误信息
+    last_error: Arc<Mutex<String>>,
+    next_id: u64,
+}
+
+fn stop(mut process: Process) {
+    let pid = process.child.id();
+    process.child.kill().ok();
+    process.child.wait().ok();
+    debug!("Stopped plugin process (pid {})", pid);
+}
+
+fn write_line(stdin: &mut ChildStdin, value: &Value) -> Result<(), AppError> {
+    writeln!(stdin, "{}", value)?;
+    stdin.flush()?;
+    Ok(())
+}
+
+// 插件通过 read_file、write_file、http_request 请求宿主代为访问文件和网络，按清单中的权限检查；
+// 这不是隔离：插件进程以当前用户的全部权限运行，可以绕过宿主直接访问，因此启用前需要用户确认完全信任
+struct HostApi {
+    data_dir: PathBuf,
+    network: Vec<String>,
+    filesystem: Vec<FsPermission>,
+    client: ClientConfig,
+}
+
+fn expand_home(path: &str) -> Option<PathBuf> {
+    match path.strip_prefix('~') {
+        Some(rest) => tauri::api::path::home_dir().map(|home| home.join(rest.trim_start_matches(['/', '\\']))),
+        None => Some(PathBuf::from(path)),
+    }
+}
+
+fn host_allowed(allowed: &[String], host: &str) -> bool {
+    allowed.iter().any(|pattern| {
+        let pattern = pattern.trim().to_ascii_lowercase();
+        match pattern.strip_prefix("*.") {
+            Some(domain) => host == domain || host.ends_with(&format!(".{}", domain)),
+            None => host == pattern,
+        }
+    })
+}
+
+impl HostApi {
+    fn handle(&self, method: &str, params: Value) -> Result<Value, AppError> {
+        match method {
+            "http_request" => self.http_request(serde_json::from_value(params)?),
+            "read_file" => {
+                let request: FileRequest = serde_json::from_value(params)?;
+                let path = self.resolve(&request.path, false)?;
+                if fs::metadata(&path)?.len() > MAX_BODY_BYTES as u64 {
+                    return Err(AppError::invalid("文件过大"));
+                }
+                Ok(json!({ "content": fs::read_to_string(&path)? }))
+            }
+            "write_file" => {
+                let request: FileRequest = serde_json::from_value(params)?;
+                let path = self.resolve(&request.path, true)?;
+                fs::write(&path, request.content)?;
+                Ok(json!({}))
+            }
+            _ => Err(AppError::invalid(format!("不支持的插件请求: {}", method))),
+        }
+    }
+
+    // 相对路径以插件数据目录为基准；解析符号链接后必须位于授权目录内
+    fn resolve(&self, path: &str, write: bool) -> Result<PathBuf, AppError> {
+        let denied = || AppError::PermissionDenied(format!("插件无权访问: {}", path));
+        let requested = self.data_dir.join(expand_home(path).ok_or_else(denied)?);
+        let resolved = if write && !requested.exists() {
+            let name = requested.file_name().ok_or_else(denied)?;
+            let parent = requested.parent().ok_or_else(denied)?;
+            fs::canonicalize(parent)?.join(name)
+        } else {
+            fs::canonicalize(&requested)?
+        };
+        let data_dir = fs::canonicalize(&self.data_dir)?;
+        let allowed = resolved.starts_with(&data_dir)
+            || self
+                .filesystem
+                .iter()
+                .filter(|permission| permission.write || !write)
+                .filter_map(|permission| expand_home(&permission.path))
+                .filter_map(|root| fs::canonicalize(root).ok())
+                .any(|root| resolved.starts_with(root));
+        if !allowed {
+            return Err(denied());
+        }
+        Ok(resolved)
+    }
+
+    // 不自动跟随重定向，插件需要对新地址重新发起请求，从而再次经过主机检查
+    fn http_request(&self, request: HttpRequest) -> Result<Value, AppError> {
+        let url = Url::parse(&request.url).map_err(|e| AppError::invalid(format!("链接无效: {}", e)))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(AppError::invalid(format!("不支持的链接协议: {}", url.scheme())));
+        }
+        let host = url.host_str().unwrap_or_default().to_ascii_lowercase();
+        if !host_allowed(&self.network, &host) {
+            return Err(AppError::PermissionDenied(format!("插件无权访问: {}", host)));
+        }
+        let method = request.method.as_deref().unwrap_or("GET").to_uppercase();
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|_| AppError::invalid(format!("不支持的请求方法: {}", method)))?;
+        let client = self
+            .client
+            .blocking_builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .timeout(Duration::from_secs(30))
+            .build()?;
+        let mut builder = client.request(method, url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder.body(body);
+        }
+        let response = builder.send().map_err(|e| AppError::network("插件请求失败", e))?;
+        let status = response.status().as_u16();
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string);
+        let mut body = Vec::new();
+        response.take(MAX_BODY_BYTES as u64 + 1).read_to_end(&mut body)?;
+        if body.len() > MAX_BODY_BYTES {
+            return Err(AppError::invalid("响应内容过大"));
+        }
+        Ok(json!({
+            "status": status,
+            "location": location,
+            "body": String::from_utf8_lossy(&body),
+        }))
+    }
+}
+
+struct Plugin {
+    manifest: Manifest,
+    dir: PathBuf,
+    matchers: Vec<Matcher>,
+    process: Mutex<Option<Process>>,
+}
+
+impl Plugin {
+    fn load(dir: &Path) -> Result<Self, AppError> {
+        let manifest: Manifest = serde_json::from_str(&fs::read_to_string(dir.join(MANIFEST_FILE))?)?;
+        let valid_id = !manifest.id.is_empty()
+            && manifest
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'));
+        if !valid_id {
+            return Err(AppError::invalid(format!("插件 id 无效: {}", manifest.id)));
+        }
+        let matchers = manifest
+            .intents
+            .iter()
+            .map(Matcher::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            manifest,
+            dir: dir.to_path_buf(),
+            matchers,
+            process: Mutex::new(None),
+        })
+    }
+
+    fn matching_intent(&self, text: &str, lowered: &str) -> Option<&str> {
+        self.matchers
+            .iter()
+            .find(|matcher| matcher.matches(text, lowered))
+            .map(|matcher| matcher.name.as_str())
+    }
+
+    fn is_running(&self) -> bool {
+        self.process
+            .lock()
+            .ok()
+            .and_then(|mut process| {
+                process
+                    .as_mut()
+                    .map(|process| matches!(process.child.try_wait(), Ok(None)))
+            })
+            .unwrap_or(false)
+    }
+
+    fn stop(&self) {
+        if let Some(process) = self.process.lock().ok().and_then(|mut process| process.take()) {
+            stop(process);
+        }
+    }
+
+    fn spawn(&self) -> Result<Process, AppError> {
+        let data_dir = self.dir.join(DATA_DIR);
+        fs::create_dir_all(&data_dir)?;
+        let bundled = self.dir.join(&self.manifest.command);
+        let program = if bundled.is_file() {
+            bundled
+        } else {
+            PathBuf::from(&self.manifest.command)
+        };
+        let mut command = Command::new(&program);
+        command
+            .args(&self.manifest.args)
+            .current_dir(&self.dir)
+            .env_clear()
+            .envs(
+                INHERITED_ENV
+                    .iter()
+                    .filter_map(|name| Some((name, std::env::var_os(name)?))),
+            )
+            .env("LINGECHO_PLUGIN_ID", &self.manifest.id)
+            .env("LINGECHO_PLUGIN_DATA", &data_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(crate::backend::CREATE_NO_WINDOW);
+        }
+        let mut child = command
+            .spawn()
+            .map_err(|e| AppError::unavailable(format!("无法启动插件 {}", self.manifest.name), e))?;
+        info!("Started plugin {} (pid {})", self.manifest.id, child.id());
+
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| AppError::Internal("无法连接插件进程".to_string()))?;
+        let (sender, lines) = mpsc::channel();
+        if let Some(stdout) = child.stdout.take() {
+            std::thread::spawn(move || {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        let last_error = Arc::new(Mutex::new(String::new()));
+        if let Some(stderr) = child.stderr.take() {
+            let last_error = last_error.clone();
+            let id = self.manifest.id.clone();
+            std::thread::spawn(move || {
+                for line in BufReader::new(stderr).lines().map_while(Result::ok) {
+                    debug!(target: "plugin", "[{}] {}", id, line);
+                    if let Ok(mut last) = last_error.lock() {
+                        *last = line;
+                    }
+                }
+            });
+        }
+        Ok(Process {
+            child,
+            stdin,
+            lines,
+            last_error,
+            next_id: 0,
+        })
+    }
+
+    // 进程按需启动并在调用之间保持运行；超时或通信失败时结束进程，下次调用重新启动
+    fn invoke(
+        &self,
+        host: &HostApi,
+        invocation: &Invocation,
+        timeout: Duration,
+    ) -> Result<PluginResponse, AppError> {
+        let mut slot = self.process.lock()?;
+        let alive = slot
+            .as_mut()
+            .is_some_and(|process| matches!(process.child.try_wait(), Ok(None)));
+        if !alive {
+            if let Some(previous) = slot.take() {
+                stop(previous);
+            }
+            *slot = Some(self.spawn()?);
+        }
+        let Some(process) = slot.as_mut() else {
+            return Err(AppError::Internal("插件进程未启动".to_string()));
+        };
+        match self.exchange(process, host, invocation, timeout) {
+            Ok(result) => result.map_err(|e| AppError::unavailable(format!("插件 {} 返回错误", self.manifest.name), e)),
+            Err(e) => {
+                if let Some(process) = slot.take() {
+                    stop(process);
+                }
+                Err(e)
+            }
+        }
+    }
+
+    // 外层错误表示与进程的通信失败，内层为插件自己返回的错误
+    fn exchange(
+        &self,
+        process: &mut Process,
+        host: &HostApi,
+        invocation: &Invocation,
+        timeout: Duration,
+    ) -> Result<Result<PluginResponse, String>, AppError> {
+        process.next_id += 1;
+        let id = Value::from(process.next_id);
+        write_line(
+            &mut process.stdin,
+            &json!({ "id": id, "method": "invoke", "params": invocation }),
+        )?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let line = match process
+                .lines
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(line) => line,
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err(AppError::unavailable("插件响应超时", &self.manifest.name));
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    let detail = process.last_error.lock().map(|line| line.clone()).unwrap_or_default();
+                    return Err(AppError::unavailable(
+                        format!("插件 {} 已退出", self.manifest.name),
+                        detail.trim(),
+                    ));
+                }
+            };
+            let Ok(message) = serde_json::from_str::<Incoming>(&line) else {
+                debug!(target: "plugin", "[{}] {}", self.manifest.id, line);
+                continue;
+            };
+            if let Some(method) = message.method {
+                let reply = match host.handle(&method, message.params) {
+                    Ok(result) => json!({ "id": message.id, "result": result }),
+                    Err(e) => {
+                        warn!("Plugin {} request {} failed: {}", self.manifest.id, method, e);
+                        json!({ "id": message.id, "error": e.to_string() })
+                    }
+                };
+                write_line(&mut process.stdin, &reply)?;
+                continue;
+            }
+            // 超时后才到达的旧回答直接丢弃
+            if message.id != id {
+                continue;
+            }
+            if let Some(error) = message.error {
+                return Ok(Err(error));
+            }
+            return Ok(Ok(serde_json::from_value(message.result.unwrap_or_default())?));
+        }
+    }
+}
+
+// 应用数据目录下 plugins/<目录>/plugin.json 描述的插件
+pub struct Plugins {
+    dir: PathBuf,
+    plugins: Mutex<Vec<Arc<Plugin>>>,
+}
+
+fn scan(dir: &Path) -> Vec<Arc<Plugin>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut plugins: Vec<Arc<Plugin>> = Vec::new();
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if !path.join(MANIFEST_FILE).is_file() {
+            continue;
+        }
+        match Plugin::load(&path) {
+            Ok(plugin) if plugins.iter().any(|loaded| loaded.manifest.id == plugin.manifest.id) => {
+                warn!("Duplicate plugin id {} in {}", plugin.manifest.id, path.display());
+            }
+            Ok(plugin) => plugins.push(Arc::new(plugin)),
+            Err(e) => warn!("Failed to load plugin {}: {}", path.display(), e),
+        }
+    }
+    plugins.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    info!("Loaded {} plugins from {}", plugins.len(), dir.display());
+    plugins
+}
+
+impl Plugins {
+    pub fn open(data_dir: &Path) -> Self {
+        let dir = data_dir.join(PLUGINS_DIR);
+        let plugins = scan(&dir);
+        Self {
+            dir,
+            plugins: Mutex::new(plugins),
+        }
+    }
+
+    fn find(&self, id: &str) -> Result<Arc<Plugin>, AppError> {
+        self.plugins
+            .lock()?
+            .iter()
+            .find(|plugin| plugin.manifest.id == id)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("插件不存在: {}", id)))
+    }
+
+    fn reload(&self) -> Result<(), AppError> {
+        let plugins = scan(&self.dir);
+        let previous = std::mem::replace(&mut *self.plugins.lock()?, plugins);
+        previous.iter().for_each(|plugin| plugin.stop());
+        Ok(())
+    }
+
+    fn infos(&self, enabled: &[String]) -> Result<Vec<PluginInfo>, AppError> {
+        Ok(self
+            .plugins
+            .lock()?
+            .iter()
+            .map(|plugin| info_of(plugin, enabled))
+            .collect())
+    }
+
+    // 第一个声明了匹配意图的已启用插件
+    fn matching(&self, text: &str, enabled: &[String]) -> Option<(Arc<Plugin>, String)> {
+        let lowered = text.to_lowercase();
+        self.plugins
+            .lock()
+            .ok()?
+            .iter()
+            .filter(|plugin| enabled.contains(&plugin.manifest.id))
+            .find_map(|plugin| {
+                let intent = plugin.matching_intent(text, &lowered)?.to_string();
+                Some((plugin.clone(), intent))
+            })
+    }
+
+    pub fn stop_all(&self) {
+        if let Ok(plugins) = self.plugins.lock() {
+            plugins.iter().for_each(|plugin| plugin.stop());
+        }
+    }
+}
+
+fn info_of(plugin: &Plugin, enabled: &[String]) -> PluginInfo {
+    PluginInfo {
+        manifest: plugin.manifest.clone(),
+        enabled: enabled.contains(&plugin.manifest.id),
+        running: plugin.is_running(),
+    }
+}
+
+fn plugin_settings(app: &AppHandle) -> Result<PluginSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.plugins.clone())
+}
+
+async fn perform(app: &AppHandle, action: PluginAction) -> Result<(), AppError> {
+    match action {
+        PluginAction::OpenUrl { url } => launcher::open_url(url).await,
+        PluginAction::Notify { title, body } => {
+            notifications::send(app, CATEGORY_ASSISTANT, &title, &body, None)?;
+            Ok(())
+        }
+    }
+}
+
+// 文本匹配到已启用插件声明的意图时交给插件处理并返回要说出的回答，没有匹配时返回 None
+pub async fn handle(app: &AppHandle, text: &str, language: Option<&str>) -> Result<Option<String>, AppError> {
+    let settings = plugin_settings(app)?;
+    let Some((plugin, intent)) = app.state::<Plugins>().matching(text, &settings.enabled) else {
+        return Ok(None);
+    };
+    info!("Routing to plugin {} intent {}", plugin.manifest.id, intent);
+    let host = HostApi {
+        data_dir: plugin.dir.join(DATA_DIR),
+        network: plugin.manifest.permissions.network.clone(),
+        filesystem: plugin.manifest.permissions.filesystem.clone(),
+        client: network::config(app),
+    };
+    let text = text.to_string();
+    let language = language.map(str::to_string).unwrap_or_else(i18n::current);
+    let timeout = Duration::from_secs(settings.timeout_secs.max(1) as u64);
+    let response = tauri::async_runtime::spawn_blocking(move || {
+        let invocation = Invocation {
+            intent: &intent,
+            text: &text,
+            language: &language,
+        };
+        plugin.invoke(&host, &invocation, timeout)
+    })
+    .await??;
+
+    let performed = !response.actions.is_empty();
+    for action in response.actions {
+        if let Err(e) = perform(app, action).await {
+            warn!("Plugin action failed: {}", e);
+        }
+    }
+    let reply = response.reply.trim();
+    if reply.is_empty() && performed {
+        return Ok(Some(i18n::t("plugins.done")));
+    }
+    Ok(Some(reply.to_string()))
+}
+
+#[tauri::command]
+pub fn list_plugins(state: State<'_, AppState>, plugins: State<'_, Plugins>) -> Result<Vec<PluginInfo>, AppError> {
+    let enabled = state.settings.lock()?.plugins.enabled.clone();
+    plugins.infos(&enabled)
+}
+
+// 插件进程不受隔离，启用时由用户在系统对话框中确认完全信任该插件；前端无法跳过这一步
+fn confirm_full_trust(app: &AppHandle, plugin: &Plugin) -> Result<(), AppError> {
+    let window = app.get_window("main");
+    let granted = tauri::api::dialog::blocking::ask(
+        window.as_ref(),
+        i18n::t("plugins.trust_title"),
+        i18n::tf("plugins.trust_message", &[&plugin.manifest.name, &plugin.manifest.id]),
+    );
+    if !granted {
+        return Err(AppError::Cancelled("用户未确认信任插件".to_string()));
+    }
+    info!("User granted full trust to plugin {}", plugin.manifest.id);
+    Ok(())
+}
+
+// 停用时结束插件进程
+// 确认对话框会阻塞，Windows 上同步命令在主线程执行会死锁，因此声明为 async 并在后台线程询问
+#[tauri::command]
+pub async fn enable_plugin(app: AppHandle, id: String, enabled: bool) -> Result<PluginInfo, AppError> {
+    let plugin = app.state::<Plugins>().find(&id)?;
+    let already_enabled = plugin_settings(&app)?.enabled.contains(&id);
+    if enabled && !already_enabled {
+        let handle = app.clone();
+        let target = plugin.clone();
+        tauri::async_runtime::spawn_blocking(move || confirm_full_trust(&handle, &target)).await??;
+    }
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.plugins.enabled.retain(|current| *current != id);
+    if enabled {
+        settings.plugins.enabled.push(id.clone());
+    }
+    settings::save_and_notify(&app, &settings, &["plugins"])?;
+    let info = info_of(&plugin, &settings.plugins.enabled);
+    drop(settings);
+    if !enabled {
+        plugin.stop();
+    }
+    info!("Plugin {} {}", id, if enabled { "enabled" } else { "disabled" });
+    Ok(info)
+}
+
+// 重新扫描插件目录，正在运行的插件进程会被结束
+#[tauri::command]
+pub fn reload_plugins(state: State<'_, AppState>, plugins: State<'_, Plugins>) -> Result<Vec<PluginInfo>, AppError> {
+    plugins.reload()?;
+    let enabled = state.settings.lock()?.plugins.enabled.clone();
+    plugins.infos(&enabled)
+}
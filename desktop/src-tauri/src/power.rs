@@ -0,0 +1,393 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{settings, AppState};
+
+// 检测使用交流电还是电池以及电量，状态变化时推送 power-state-changed；
+// 唤醒词、后台索引、模型下载等耗电的功能按各自的策略暂停和恢复
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerSource {
+    Ac,
+    Battery,
+    // 台式机或无法读取电源信息
+    Unknown,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PowerState {
+    pub source: PowerSource,
+    pub battery_percent: Option<u8>,
+    pub low_battery: bool,
+}
+
+impl Default for PowerState {
+    fn default() -> Self {
+        Self {
+            source: PowerSource::Unknown,
+            battery_percent: None,
+            low_battery: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerPolicy {
+    #[default]
+    Always,
+    NotOnLowBattery,
+    OnlyOnAc,
+}
+
+impl PowerPolicy {
+    // 无法确定电源时不限制
+    fn allows(self, state: &PowerState) -> bool {
+        match self {
+            PowerPolicy::Always => true,
+            PowerPolicy::NotOnLowBattery => !state.low_battery,
+            PowerPolicy::OnlyOnAc => state.source != PowerSource::Battery,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PowerConsumer {
+    WakeWord,
+    Indexing,
+    ModelDownloads,
+}
+
+impl PowerConsumer {
+    pub const ALL: [PowerConsumer; 3] = [
+        PowerConsumer::WakeWord,
+        PowerConsumer::Indexing,
+        PowerConsumer::ModelDownloads,
+    ];
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PowerSettings {
+    // 使用电池且电量不高于该值时视为低电量
+    pub low_battery_percent: u8,
+    pub wake_word: PowerPolicy,
+    pub indexing: PowerPolicy,
+    pub model_downloads: PowerPolicy,
+}
+
+impl Default for PowerSettings {
+    fn default() -> Self {
+        Self {
+            low_battery_percent: 20,
+            wake_word: PowerPolicy::NotOnLowBattery,
+            indexing: PowerPolicy::OnlyOnAc,
+            model_downloads: PowerPolicy::NotOnLowBattery,
+        }
+    }
+}
+
+impl PowerSettings {
+    pub fn policy(&self, consumer: PowerConsumer) -> PowerPolicy {
+        match consumer {
+            PowerConsumer::WakeWord => self.wake_word,
+            PowerConsumer::Indexing => self.indexing,
+            PowerConsumer::ModelDownloads => self.model_downloads,
+        }
+    }
+
+    fn policy_mut(&mut self, consumer: PowerConsumer) -> &mut PowerPolicy {
+        match consumer {
+            PowerConsumer::WakeWord => &mut self.wake_word,
+            PowerConsumer::Indexing => &mut self.indexing,
+            PowerConsumer::ModelDownloads => &mut self.model_downloads,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsumerStatus {
+    pub consumer: PowerConsumer,
+    pub policy: PowerPolicy,
+    pub allowed: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PowerStatus {
+    pub state: PowerState,
+    pub consumers: Vec<ConsumerStatus>,
+}
+
+// 允许状态变化时调用，参数为新的允许状态
+pub type PowerHook = fn(&AppHandle, bool);
+
+struct Registration {
+    consumer: PowerConsumer,
+    hook: PowerHook,
+    allowed: bool,
+}
+
+#[derive(Default)]
+pub struct Power {
+    state: Mutex<PowerState>,
+    registrations: Mutex<Vec<Registration>>,
+}
+
+impl Power {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use super::PowerSource;
+    use windows_sys::Win32::System::Power::{GetSystemPowerStatus, SYSTEM_POWER_STATUS};
+
+    // BatteryFlag 的这一位表示没有电池
+    const NO_SYSTEM_BATTERY: u8 = 128;
+    const UNKNOWN: u8 = 255;
+
+    pub fn read() -> Option<(PowerSource, Option<u8>)> {
+        // SAFETY: 纯数据结构，由系统填充
+        let mut status: SYSTEM_POWER_STATUS = unsafe { std::mem::zeroed() };
+        if unsafe { GetSystemPowerStatus(&mut status) } == 0 {
+            return None;
+        }
+        let source = match status.ACLineStatus {
+            0 => PowerSource::Battery,
+            1 => PowerSource::Ac,
+            _ => PowerSource::Unknown,
+        };
+        let has_battery = status.BatteryFlag != UNKNOWN && status.BatteryFlag & NO_SYSTEM_BATTERY == 0;
+        let percent = (has_battery && status.BatteryLifePercent != UNKNOWN).then_some(status.BatteryLifePercent);
+        Some((source, percent))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use super::PowerSource;
+    use std::process::Command;
+
+    // 输出形如：
+    // Now drawing from 'Battery Power'
+    //  -InternalBattery-0 (id=1234567)	85%; discharging; 4:12 remaining present: true
+    pub fn read() -> Option<(PowerSource, Option<u8>)> {
+        let output = Command::new("pmset").args(["-g", "batt"]).output().ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut lines = text.lines();
+        let first = lines.next()?;
+        let source = if first.contains("Battery Power") {
+            PowerSource::Battery
+        } else if first.contains("AC Power") {
+            PowerSource::Ac
+        } else {
+            PowerSource::Unknown
+        };
+        let percent = lines
+            .flat_map(|line| line.split_whitespace())
+            .find_map(|word| word.trim_end_matches(';').strip_suffix('%')?.parse().ok());
+        Some((source, percent))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod native {
+    use super::PowerSource;
+    use std::fs;
+    use std::path::Path;
+
+    fn read_value(dir: &Path, name: &str) -> Option<String> {
+        fs::read_to_string(dir.join(name))
+            .map(|text| text.trim().to_string())
+            .ok()
+    }
+
+    pub fn read() -> Option<(PowerSource, Option<u8>)> {
+        let mut mains_online = None;
+        let mut discharging = None;
+        let mut percent = None;
+        for entry in fs::read_dir("/sys/class/power_supply").ok()?.flatten() {
+            let dir = entry.path();
+            match read_value(&dir, "type").as_deref() {
+                Some("Mains") => {
+                    let online = read_value(&dir, "online").as_deref() == Some("1");
+                    mains_online = Some(mains_online.unwrap_or(false) || online);
+                }
+                // 外设（鼠标、耳机）的电池 scope 为 Device，不计入
+                Some("Battery") if read_value(&dir, "scope").as_deref() != Some("Device") => {
+                    discharging = Some(read_value(&dir, "status").as_deref() == Some("Discharging"));
+                    percent = percent.or_else(|| read_value(&dir, "capacity")?.parse().ok());
+                }
+                _ => {}
+            }
+        }
+        let source = match (mains_online, discharging) {
+            (Some(true), _) => PowerSource::Ac,
+            (_, Some(true)) => PowerSource::Battery,
+            (Some(false), _) | (_, Some(false)) => PowerSource::Ac,
+            (None, None) => PowerSource::Unknown,
+        };
+        Some((source, percent))
+    }
+}
+
+fn power_settings(app: &AppHandle) -> Result<PowerSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.power.clone())
+}
+
+fn read_state(settings: &PowerSettings) -> PowerState {
+    let (source, battery_percent) = native::read().unwrap_or((PowerSource::Unknown, None));
+    PowerState {
+        source,
+        battery_percent,
+        low_battery: source == PowerSource::Battery
+            && battery_percent.is_some_and(|percent| percent <= settings.low_battery_percent),
+    }
+}
+
+pub fn state(app: &AppHandle) -> PowerState {
+    app.try_state::<Power>()
+        .and_then(|power| power.state.lock().ok().map(|state| state.clone()))
+        .unwrap_or_default()
+}
+
+pub fn on_battery(app: &AppHandle) -> Option<bool> {
+    match state(app).source {
+        PowerSource::Ac => Some(false),
+        PowerSource::Battery => Some(true),
+        PowerSource::Unknown => None,
+    }
+}
+
+pub fn allowed(app: &AppHandle, consumer: PowerConsumer) -> bool {
+    let policy = power_settings(app).unwrap_or_default().policy(consumer);
+    policy.allows(&state(app))
+}
+
+// 后台任务在每一轮开始前调用，策略不允许时一直等待
+pub async fn wait_allowed(app: &AppHandle, consumer: PowerConsumer) {
+    while !allowed(app, consumer) {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+// 需要主动停止和恢复的功能在 setup 中注册，电源状态或策略变化导致允许状态改变时调用 hook
+pub fn register(app: &AppHandle, consumer: PowerConsumer, hook: PowerHook) {
+    let allowed = allowed(app, consumer);
+    match app.state::<Power>().registrations.lock() {
+        Ok(mut registrations) => registrations.push(Registration {
+            consumer,
+            hook,
+            allowed,
+        }),
+        Err(e) => warn!("Failed to register power hook: {}", e),
+    }
+}
+
+// 重新计算各功能的允许状态，hook 在锁外调用
+fn notify_consumers(app: &AppHandle) {
+    let settings = power_settings(app).unwrap_or_default();
+    let state = state(app);
+    let mut changed = Vec::new();
+    if let Ok(mut registrations) = app.state::<Power>().registrations.lock() {
+        for registration in registrations.iter_mut() {
+            let allowed = settings.policy(registration.consumer).allows(&state);
+            if allowed != registration.allowed {
+                registration.allowed = allowed;
+                changed.push((registration.consumer, registration.hook, allowed));
+            }
+        }
+    }
+    for (consumer, hook, allowed) in changed {
+        info!(
+            "Power policy {} {:?}",
+            if allowed { "resumed" } else { "paused" },
+            consumer
+        );
+        hook(app, allowed);
+    }
+}
+
+fn refresh(app: &AppHandle) {
+    let settings = power_settings(app).unwrap_or_default();
+    let next = read_state(&settings);
+    let previous = match app.state::<Power>().state.lock() {
+        Ok(mut state) => std::mem::replace(&mut *state, next.clone()),
+        Err(e) => {
+            warn!("Failed to update power state: {}", e);
+            return;
+        }
+    };
+    if previous != next {
+        if previous.source != next.source || previous.low_battery != next.low_battery {
+            info!(
+                "Power source {:?}, battery {:?}%, low battery: {}",
+                next.source, next.battery_percent, next.low_battery
+            );
+        }
+        events::publish(app, AppEvent::PowerStateChanged(next));
+    }
+    notify_consumers(app);
+}
+
+// 在 setup 中调用：先同步读取一次，之后后台定期刷新
+pub fn start(app: &AppHandle) {
+    refresh(app);
+    let handle = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        refresh(&handle);
+    });
+}
+
+// 设置变化时调用：低电量阈值和策略都可能改变允许状态
+pub fn apply(app: &AppHandle) {
+    let app = app.clone();
+    std::thread::spawn(move || refresh(&app));
+}
+
+fn status(app: &AppHandle) -> Result<PowerStatus, AppError> {
+    let settings = power_settings(app)?;
+    let state = state(app);
+    let consumers = PowerConsumer::ALL
+        .into_iter()
+        .map(|consumer| {
+            let policy = settings.policy(consumer);
+            ConsumerStatus {
+                consumer,
+                policy,
+                allowed: policy.allows(&state),
+            }
+        })
+        .collect();
+    Ok(PowerStatus { state, consumers })
+}
+
+#[tauri::command]
+pub fn get_power_state(app: AppHandle) -> Result<PowerStatus, AppError> {
+    status(&app)
+}
+
+#[tauri::command]
+pub fn set_power_policy(app: AppHandle, consumer: PowerConsumer, policy: PowerPolicy) -> Result<PowerStatus, AppError> {
+    {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock()?;
+        *settings.power.policy_mut(consumer) = policy;
+        settings::save_and_notify(&app, &settings, &["power"])?;
+    }
+    info!("Power policy for {:?} set to {:?}", consumer, policy);
+    apply(&app);
+    status(&app)
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Icon, Manager, State};
+use tracing::{info, warn};
+
+use crate::audio::{self, indicator, WakeWordListener};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::{i18n, settings, AppState};
+
+// 检查前台应用的间隔
+const FOREGROUND_POLL: Duration = Duration::from_secs(2);
+const TRAY_ICON: &[u8] = include_bytes!("../icons/32x32.png");
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PrivacySettings {
+    // 手动开启的隐私模式，重启后保持
+    pub enabled: bool,
+    // 这些应用位于前台时自动进入隐私模式，按进程名或应用名匹配（不区分大小写）
+    pub auto_apps: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PrivacyStatus {
+    pub active: bool,
+    pub manual: bool,
+    // 触发自动隐私模式的前台应用
+    pub auto_app: Option<String>,
+}
+
+// 隐私模式下停止录音和唤醒词监听，剪贴板等读取用户内容的功能在执行前应检查 is_active
+#[derive(Default)]
+pub struct Privacy {
+    manual: AtomicBool,
+    auto_app: Mutex<Option<String>>,
+    active: AtomicBool,
+}
+
+impl Privacy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> PrivacyStatus {
+        PrivacyStatus {
+            active: self.active.load(Ordering::SeqCst),
+            manual: self.manual.load(Ordering::SeqCst),
+            auto_app: self.auto_app.lock().ok().and_then(|app| app.clone()),
+        }
+    }
+}
+
+pub fn is_active(app: &AppHandle) -> bool {
+    app.state::<Privacy>().active.load(Ordering::SeqCst)
+}
+
+// 托盘图标在隐私模式下变为半透明的灰色，麦克风采集中在右下角加红点
+fn tray_icon(dimmed: bool, recording: bool) -> Option<Icon> {
+    let image = xcap::image::load_from_memory(TRAY_ICON).ok()?;
+    let mut rgba = if dimmed {
+        image.grayscale().to_rgba8()
+    } else {
+        image.to_rgba8()
+    };
+    if dimmed {
+        for pixel in rgba.pixels_mut() {
+            pixel[3] /= 2;
+        }
+    }
+    if recording {
+        let (width, height) = rgba.dimensions();
+        let radius = width.min(height) as f32 * 0.22;
+        let (cx, cy) = (width as f32 - radius - 1.0, height as f32 - radius - 1.0);
+        for (x, y, pixel) in rgba.enumerate_pixels_mut() {
+            let distance = ((x as f32 + 0.5 - cx).powi(2) + (y as f32 + 0.5 - cy).powi(2)).sqrt();
+            if distance <= radius {
+                pixel.0 = [230, 40, 40, 255];
+            } else if distance <= radius + 1.0 {
+                pixel.0 = [255, 255, 255, 255];
+            }
+        }
+    }
+    let (width, height) = rgba.dimensions();
+    Some(Icon::Rgba {
+        rgba: rgba.into_raw(),
+        width,
+        height,
+    })
+}
+
+pub fn update_tray(app: &AppHandle, active: bool) {
+    let tray = app.tray_handle();
+    let recording = indicator::is_active(app);
+    if let Some(icon) = tray_icon(active, recording) {
+        tray.set_icon(icon).ok();
+    }
+    let tooltip = match (active, recording) {
+        (true, _) => "app.title_privacy",
+        (false, true) => "tray.recording",
+        (false, false) => "app.name",
+    };
+    tray.set_tooltip(&i18n::t(tooltip)).ok();
+    tray.get_item(crate::tray::MENU_TOGGLE_PRIVACY).set_selected(active).ok();
+}
+
+// 根据手动开关和前台应用重新计算状态，状态变化时停止或恢复麦克风相关功能
+fn refresh(app: &AppHandle) {
+    let privacy = app.state::<Privacy>();
+    let status = privacy.status();
+    let active = status.manual || status.auto_app.is_some();
+    let was_active = privacy.active.swap(active, Ordering::SeqCst);
+
+    if active != was_active {
+        if active {
+            info!("Privacy mode on ({:?})", status.auto_app);
+            if let Err(e) = audio::capture::finish_recording(app) {
+                warn!("Failed to stop recording: {}", e);
+            }
+            app.state::<WakeWordListener>().stop();
+        } else {
+            info!("Privacy mode off");
+            audio::wakeword::start_if_enabled(app);
+        }
+        update_tray(app, active);
+    }
+
+    events::publish(app, AppEvent::PrivacyModeChanged(PrivacyStatus { active, ..status }));
+}
+
+pub fn set_manual(app: &AppHandle, enabled: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    {
+        let mut settings = state.settings.lock()?;
+        settings.privacy.enabled = enabled;
+        settings::save_and_notify(app, &settings, &["privacy"])?;
+    }
+    app.state::<Privacy>().manual.store(enabled, Ordering::SeqCst);
+    refresh(app);
+    Ok(())
+}
+
+fn focused_app() -> Option<String> {
+    let windows = xcap::Window::all().ok()?;
+    let focused = windows.into_iter().find(|w| w.is_focused().unwrap_or(false))?;
+    focused.app_name().ok()
+}
+
+// 在 setup 中调用：恢复手动设置，并在后台线程中检查前台应用
+pub fn start(app: &AppHandle) {
+    let settings = match app.state::<AppState>().settings.lock() {
+        Ok(settings) => settings.privacy.clone(),
+        Err(_) => return,
+    };
+    if settings.enabled {
+        app.state::<Privacy>().manual.store(true, Ordering::SeqCst);
+        refresh(app);
+    }
+
+    let handle = app.clone();
+    std::thread::spawn(move || loop {
+        std::thread::sleep(FOREGROUND_POLL);
+        let auto_apps = match handle.state::<AppState>().settings.lock() {
+            Ok(settings) => settings.privacy.auto_apps.clone(),
+            Err(_) => continue,
+        };
+        let matched = if auto_apps.is_empty() {
+            None
+        } else {
+            focused_app().filter(|name| {
+                let name = name.to_lowercase();
+                auto_apps.iter().any(|app| name.contains(&app.to_lowercase()))
+            })
+        };
+
+        let privacy = handle.state::<Privacy>();
+        let changed = match privacy.auto_app.lock() {
+            Ok(mut current) if *current != matched => {
+                *current = matched;
+                true
+            }
+            _ => false,
+        };
+        if changed {
+            refresh(&handle);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn set_privacy_mode(app: AppHandle, enabled: bool) -> Result<PrivacyStatus, AppError> {
+    set_manual(&app, enabled)?;
+    Ok(app.state::<Privacy>().status())
+}
+
+#[tauri::command]
+pub fn get_privacy_status(privacy: State<'_, Privacy>) -> PrivacyStatus {
+    privacy.status()
+}
+
+#[tauri::command]
+pub fn set_privacy_auto_apps(app: AppHandle, apps: Vec<String>) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.privacy.auto_apps = apps
+        .into_iter()
+        .map(|app| app.trim().to_string())
+        .filter(|app| !app.is_empty())
+        .collect();
+    settings::save_and_notify(&app, &settings, &["privacy"])
+}
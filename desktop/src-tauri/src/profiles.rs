@@ -0,0 +1,262 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{AppHandle, Config, Manager, WindowBuilder, WindowEvent, WindowUrl};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::storage::now_millis;
+use crate::{shutdown, tray};
+
+// 每个用户配置有独立的设置、数据库和钥匙串命名空间；
+// 默认配置沿用原来的目录，其他配置位于 profiles/<名称> 下，模型文件仍然共用
+
+pub const DEFAULT_PROFILE: &str = "default";
+pub const PICKER_LABEL: &str = "profile-picker";
+const REGISTRY_FILE: &str = "profiles.json";
+const PROFILES_DIR: &str = "profiles";
+const MAX_NAME_LEN: usize = 32;
+
+// 启动时确定，运行期间不变；切换配置需要重启
+static ACTIVE: OnceLock<String> = OnceLock::new();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    pub name: String,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Registry {
+    profiles: Vec<Profile>,
+    last: Option<String>,
+    // 有多个配置时启动先显示选择窗口
+    pick_on_startup: bool,
+    // switch_profile 重启前写入，下次启动时优先于命令行参数
+    pending: Option<String>,
+}
+
+impl Registry {
+    fn path(config_root: &Path) -> PathBuf {
+        config_root.join(REGISTRY_FILE)
+    }
+
+    fn load(config_root: &Path) -> Self {
+        let path = Self::path(config_root);
+        let mut registry: Registry = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring invalid profile registry {}: {}", path.display(), e);
+                Registry::default()
+            }),
+            Err(_) => Registry::default(),
+        };
+        if registry.find(DEFAULT_PROFILE).is_none() {
+            registry.profiles.insert(
+                0,
+                Profile {
+                    name: DEFAULT_PROFILE.to_string(),
+                    created_at: 0,
+                },
+            );
+        }
+        registry
+    }
+
+    fn save(&self, config_root: &Path) -> Result<(), AppError> {
+        fs::create_dir_all(config_root)?;
+        fs::write(Self::path(config_root), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    // 大小写不敏感的文件系统上只差大小写的名称会指向同一目录
+    fn find(&self, name: &str) -> Option<&Profile> {
+        self.profiles
+            .iter()
+            .find(|profile| profile.name.to_lowercase() == name.to_lowercase())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileList {
+    pub current: String,
+    pub profiles: Vec<Profile>,
+    pub pick_on_startup: bool,
+}
+
+// 名称用作目录名和钥匙串服务名的后缀
+fn validate_name(name: &str) -> Result<(), AppError> {
+    if name.is_empty() {
+        return Err(AppError::invalid("配置名称不能为空"));
+    }
+    if name.chars().count() > MAX_NAME_LEN {
+        return Err(AppError::invalid(format!("配置名称不能超过 {} 个字符", MAX_NAME_LEN)));
+    }
+    if !name.chars().all(|c| c.is_alphanumeric() || matches!(c, '-' | '_')) {
+        return Err(AppError::invalid("配置名称只能包含字母、数字、- 和 _"));
+    }
+    Ok(())
+}
+
+// 在 setup 之前调用：待切换的配置优先，其次是 --profile，最后是上次使用的配置；
+// 返回是否需要先显示配置选择窗口
+pub fn select(config: &Config, requested: Option<&str>) -> bool {
+    let Some(config_root) = tauri::api::path::app_config_dir(config) else {
+        activate(DEFAULT_PROFILE.to_string());
+        return false;
+    };
+    let mut registry = Registry::load(&config_root);
+    let pending = registry.pending.take();
+    let requested = requested.and_then(|name| match registry.find(name.trim()) {
+        Some(profile) => Some(profile.name.clone()),
+        None => {
+            warn!("Profile {} does not exist, ignoring --profile", name);
+            None
+        }
+    });
+    let pick = pending.is_none() && requested.is_none() && registry.pick_on_startup && registry.profiles.len() > 1;
+    let name = pending
+        .or(requested)
+        .or(registry.last.clone())
+        .and_then(|name| registry.find(&name).map(|profile| profile.name.clone()))
+        .unwrap_or_else(|| DEFAULT_PROFILE.to_string());
+    registry.last = Some(name.clone());
+    if let Err(e) = registry.save(&config_root) {
+        warn!("Failed to save profile registry: {}", e);
+    }
+    activate(name);
+    pick
+}
+
+fn activate(name: String) {
+    info!("Using profile {}", name);
+    ACTIVE.set(name).ok();
+}
+
+pub fn current() -> &'static str {
+    ACTIVE.get().map(String::as_str).unwrap_or(DEFAULT_PROFILE)
+}
+
+fn scoped(root: PathBuf) -> PathBuf {
+    match current() {
+        DEFAULT_PROFILE => root,
+        name => root.join(PROFILES_DIR).join(name),
+    }
+}
+
+pub fn data_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let root = app.path_resolver().app_data_dir().ok_or("无法获取应用数据目录")?;
+    Ok(scoped(root))
+}
+
+pub fn config_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
+    let root = app.path_resolver().app_config_dir().ok_or("无法获取应用配置目录")?;
+    Ok(scoped(root))
+}
+
+fn config_root(app: &AppHandle) -> Result<PathBuf, AppError> {
+    Ok(app.path_resolver().app_config_dir().ok_or("无法获取应用配置目录")?)
+}
+
+// 默认配置沿用原来的服务名，已保存的密钥不受影响
+pub fn keychain_service(base: &str) -> String {
+    match current() {
+        DEFAULT_PROFILE => base.to_string(),
+        name => format!("{}.{}", base, name),
+    }
+}
+
+// 非默认配置在窗口标题中显示名称，便于区分
+pub fn decorate_title(title: &str) -> String {
+    match current() {
+        DEFAULT_PROFILE => title.to_string(),
+        name => format!("{} - {}", title, name),
+    }
+}
+
+pub fn open_picker(app: &AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_window(PICKER_LABEL) {
+        window.set_focus()?;
+        return Ok(());
+    }
+    let window = WindowBuilder::new(app, PICKER_LABEL, WindowUrl::App("profile-picker".into()))
+        .title(decorate_title(&crate::i18n::t("app.name")))
+        .inner_size(420.0, 480.0)
+        .resizable(false)
+        .center()
+        .build()?;
+    // 选择当前配置或直接关闭窗口时继续使用当前配置
+    let handle = app.clone();
+    window.on_window_event(move |event| {
+        if let WindowEvent::Destroyed = event {
+            tray::show_main_window(&handle);
+        }
+    });
+    Ok(())
+}
+
+// 选择当前配置只关闭选择窗口，选择其他配置时保存设置后重启
+pub fn switch(app: &AppHandle, name: &str) -> Result<bool, AppError> {
+    let config_root = config_root(app)?;
+    let mut registry = Registry::load(&config_root);
+    let name = registry
+        .find(name.trim())
+        .map(|profile| profile.name.clone())
+        .ok_or_else(|| AppError::NotFound(format!("配置不存在: {}", name)))?;
+    if name == current() {
+        if let Some(window) = app.get_window(PICKER_LABEL) {
+            window.close()?;
+        }
+        return Ok(false);
+    }
+    registry.pending = Some(name.clone());
+    registry.save(&config_root)?;
+    info!("Switching to profile {}, restarting", name);
+    shutdown::restart(app);
+    Ok(true)
+}
+
+#[tauri::command]
+pub fn list_profiles(app: AppHandle) -> Result<ProfileList, AppError> {
+    let registry = Registry::load(&config_root(&app)?);
+    Ok(ProfileList {
+        current: current().to_string(),
+        profiles: registry.profiles,
+        pick_on_startup: registry.pick_on_startup,
+    })
+}
+
+// 目录在第一次使用时创建
+#[tauri::command]
+pub fn create_profile(app: AppHandle, name: String) -> Result<Profile, AppError> {
+    let name = name.trim().to_string();
+    validate_name(&name)?;
+    let config_root = config_root(&app)?;
+    let mut registry = Registry::load(&config_root);
+    if registry.find(&name).is_some() {
+        return Err(AppError::invalid(format!("配置已存在: {}", name)));
+    }
+    let profile = Profile {
+        name,
+        created_at: now_millis(),
+    };
+    registry.profiles.push(profile.clone());
+    registry.save(&config_root)?;
+    info!("Created profile {}", profile.name);
+    Ok(profile)
+}
+
+// 返回 true 表示应用即将重启
+#[tauri::command]
+pub fn switch_profile(app: AppHandle, name: String) -> Result<bool, AppError> {
+    switch(&app, &name)
+}
+
+#[tauri::command]
+pub fn set_profile_picker(app: AppHandle, enabled: bool) -> Result<(), AppError> {
+    let config_root = config_root(&app)?;
+    let mut registry = Registry::load(&config_root);
+    registry.pick_on_startup = enabled;
+    registry.save(&config_root)
+}
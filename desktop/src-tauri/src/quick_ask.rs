@@ -0,0 +1,117 @@
+use tauri::{AppHandle, Manager, PhysicalPosition, PhysicalSize, WindowBuilder, WindowUrl};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::pet;
+use crate::pipeline::{self, TurnRequest};
+
+pub const QUICK_ASK_LABEL: &str = "quick-ask";
+
+// 输入框尺寸（逻辑像素）
+const WIDTH: f64 = 560.0;
+const HEIGHT: f64 = 72.0;
+// 窗口中心位于光标上方的距离（逻辑像素），避免遮住光标所在的内容
+const CURSOR_OFFSET: f64 = 120.0;
+
+// 光标的屏幕坐标（物理像素），取不到时返回 None
+#[cfg(windows)]
+async fn cursor_position(_app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    use windows_sys::Win32::Foundation::POINT;
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+    let mut point = POINT { x: 0, y: 0 };
+    // SAFETY: point 是有效的输出指针
+    let ok = unsafe { GetCursorPos(&mut point) };
+    (ok != 0).then(|| PhysicalPosition::new(point.x, point.y))
+}
+
+#[cfg(target_os = "macos")]
+async fn cursor_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    crate::macos::cursor_position(app).await
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+async fn cursor_position(app: &AppHandle) -> Option<PhysicalPosition<i32>> {
+    crate::linux::cursor_position(app).await
+}
+
+// 在光标所在的显示器上居中于光标附近，取不到光标位置时居中于主显示器
+fn position(window: &tauri::Window, cursor: Option<PhysicalPosition<i32>>) -> Option<PhysicalPosition<i32>> {
+    let monitors = window.available_monitors().unwrap_or_default();
+    let monitor = cursor
+        .and_then(|cursor| {
+            monitors
+                .iter()
+                .find(|monitor| pet::monitor_contains(monitor, cursor.x, cursor.y))
+                .cloned()
+        })
+        .or_else(|| window.primary_monitor().ok().flatten())?;
+
+    let scale = monitor.scale_factor();
+    let size = PhysicalSize::new((WIDTH * scale).round() as i32, (HEIGHT * scale).round() as i32);
+    let center = cursor.unwrap_or_else(|| {
+        let origin = monitor.position();
+        let screen = monitor.size();
+        PhysicalPosition::new(origin.x + screen.width as i32 / 2, origin.y + screen.height as i32 / 3)
+    });
+    let offset = if cursor.is_some() {
+        (CURSOR_OFFSET * scale).round() as i32
+    } else {
+        0
+    };
+    let x = center.x - size.width / 2;
+    let y = center.y - offset - size.height / 2;
+    Some(pet::clamp_to_monitor(&monitor, size, x, y))
+}
+
+// 显示快速提问输入框，已打开时只重新聚焦；与主窗口无关，失去焦点时自动关闭
+#[tauri::command]
+pub async fn show_quick_ask(app: AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_window(QUICK_ASK_LABEL) {
+        window.set_focus()?;
+        return Ok(());
+    }
+
+    let window = WindowBuilder::new(&app, QUICK_ASK_LABEL, WindowUrl::App("quick-ask-window".into()))
+        .title("")
+        .inner_size(WIDTH, HEIGHT)
+        .decorations(false)
+        .transparent(true)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .resizable(false)
+        .visible(false)
+        .build()?;
+
+    let cursor = cursor_position(&app).await;
+    if let Some(position) = position(&window, cursor) {
+        window.set_position(position)?;
+    }
+    window.show()?;
+    window.set_focus()?;
+    info!("Quick ask window opened");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn close_quick_ask(app: AppHandle) -> Result<(), AppError> {
+    if let Some(window) = app.get_window(QUICK_ASK_LABEL) {
+        window.close()?;
+    }
+    Ok(())
+}
+
+// 关闭输入框后在后台执行一轮对话；不带 text 时由管线录音识别，结果通过 pipeline-turn-finished 事件通知
+#[tauri::command]
+pub fn submit_quick_ask(app: AppHandle, request: TurnRequest) -> Result<(), AppError> {
+    if request.text.as_deref().is_some_and(|text| text.trim().is_empty()) {
+        return Err(AppError::invalid("问题不能为空"));
+    }
+    close_quick_ask(app.clone())?;
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = pipeline::run_turn(app, request).await {
+            warn!("Quick ask turn failed: {}", e);
+        }
+    });
+    Ok(())
+}
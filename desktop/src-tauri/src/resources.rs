@@ -0,0 +1,344 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::llm::LlamaServer;
+use crate::power;
+use crate::storage::now_millis;
+use crate::AppState;
+
+// 定期采样本进程和子进程的 CPU、内存占用；超过阈值或使用电池时进入节流状态，
+// 桌宠动画降帧、后台索引暂停、唤醒词分批处理
+
+const SAMPLE_INTERVAL: Duration = Duration::from_secs(5);
+// 连续超过阈值的次数达到后才节流，避免短暂的峰值触发
+const HIGH_SAMPLES: u32 = 2;
+// 降到阈值的这个比例以下才解除
+const RELEASE_RATIO: f32 = 0.8;
+const MB: u64 = 1024 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ResourceSettings {
+    pub auto_throttle: bool,
+    // 本进程和子进程的总 CPU 占用，按全部核心计算的百分比
+    pub cpu_percent: f32,
+    pub memory_mb: u64,
+    pub throttle_on_battery: bool,
+}
+
+impl Default for ResourceSettings {
+    fn default() -> Self {
+        Self {
+            auto_throttle: true,
+            cpu_percent: 50.0,
+            memory_mb: 2048,
+            throttle_on_battery: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ThrottleReason {
+    OnBattery,
+    HighCpu,
+    HighMemory,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct ThrottleStatus {
+    pub throttled: bool,
+    pub reasons: Vec<ThrottleReason>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProcessUsage {
+    pub pid: u32,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ResourceUsage {
+    pub app: Option<ProcessUsage>,
+    pub backend: Option<ProcessUsage>,
+    pub llama: Option<ProcessUsage>,
+    pub cpu_percent: f32,
+    pub memory_bytes: u64,
+    pub on_battery: Option<bool>,
+    pub throttle: ThrottleStatus,
+    pub sampled_at: i64,
+}
+
+#[derive(Default)]
+pub struct ResourceMonitor {
+    usage: Mutex<Option<ResourceUsage>>,
+    throttled: AtomicBool,
+}
+
+impl ResourceMonitor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub fn is_throttled(app: &AppHandle) -> bool {
+    app.try_state::<ResourceMonitor>()
+        .is_some_and(|monitor| monitor.throttled.load(Ordering::SeqCst))
+}
+
+// 后台任务在每一轮开始前调用，节流期间一直等待
+pub async fn wait_unthrottled(app: &AppHandle) {
+    while is_throttled(app) {
+        tokio::time::sleep(SAMPLE_INTERVAL).await;
+    }
+}
+
+#[cfg(windows)]
+mod native {
+    use std::time::Duration;
+    use windows_sys::Win32::Foundation::{CloseHandle, FILETIME, HANDLE};
+    use windows_sys::Win32::System::ProcessStatus::{K32GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows_sys::Win32::System::Threading::{GetProcessTimes, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    struct Process(HANDLE);
+
+    impl Drop for Process {
+        fn drop(&mut self) {
+            // SAFETY: 句柄由 OpenProcess 返回且只关闭一次
+            unsafe {
+                CloseHandle(self.0);
+            }
+        }
+    }
+
+    fn ticks(time: &FILETIME) -> u64 {
+        ((time.dwHighDateTime as u64) << 32) | time.dwLowDateTime as u64
+    }
+
+    // 累计 CPU 时间和工作集大小
+    pub fn sample(pid: u32) -> Option<(Duration, u64)> {
+        // SAFETY: 返回的句柄为空时表示失败，非空时由 Process 负责关闭
+        let handle = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid) };
+        if handle.is_null() {
+            return None;
+        }
+        let process = Process(handle);
+        let empty = FILETIME {
+            dwLowDateTime: 0,
+            dwHighDateTime: 0,
+        };
+        let (mut creation, mut exit, mut kernel, mut user) = (empty, empty, empty, empty);
+        // SAFETY: 句柄有效，输出参数都指向栈上的变量
+        let ok = unsafe { GetProcessTimes(process.0, &mut creation, &mut exit, &mut kernel, &mut user) };
+        if ok == 0 {
+            return None;
+        }
+        // SAFETY: PROCESS_MEMORY_COUNTERS 是纯数据结构，全零是合法值
+        let mut counters: PROCESS_MEMORY_COUNTERS = unsafe { std::mem::zeroed() };
+        counters.cb = std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32;
+        // SAFETY: 同上，cb 与结构体大小一致
+        let ok = unsafe { K32GetProcessMemoryInfo(process.0, &mut counters, counters.cb) };
+        if ok == 0 {
+            return None;
+        }
+        // FILETIME 的单位是 100 纳秒
+        let cpu = Duration::from_nanos((ticks(&kernel) + ticks(&user)) * 100);
+        Some((cpu, counters.WorkingSetSize as u64))
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use std::process::Command;
+    use std::time::Duration;
+
+    // ps 的 time 格式为 [[dd-]hh:]mm:ss.ss
+    fn parse_cpu_time(text: &str) -> Option<Duration> {
+        let (days, clock) = match text.split_once('-') {
+            Some((days, clock)) => (days.parse::<f64>().ok()?, clock),
+            None => (0.0, text),
+        };
+        let mut secs = 0.0;
+        for part in clock.split(':') {
+            secs = secs * 60.0 + part.parse::<f64>().ok()?;
+        }
+        Some(Duration::from_secs_f64(days * 86400.0 + secs))
+    }
+
+    pub fn sample(pid: u32) -> Option<(Duration, u64)> {
+        let output = Command::new("ps")
+            .args(["-o", "time=,rss=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        let text = String::from_utf8_lossy(&output.stdout);
+        let mut parts = text.split_whitespace();
+        let cpu = parse_cpu_time(parts.next()?)?;
+        let rss_kb: u64 = parts.next()?.parse().ok()?;
+        Some((cpu, rss_kb * 1024))
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod native {
+    use std::fs;
+    use std::time::Duration;
+
+    // 几乎所有 Linux 发行版的 USER_HZ 都是 100
+    const CLOCK_TICKS: u64 = 100;
+
+    pub fn sample(pid: u32) -> Option<(Duration, u64)> {
+        let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        // 进程名可能包含空格和括号，从最后一个右括号之后开始解析；utime、stime 是第 14、15 个字段
+        let fields: Vec<&str> = stat.rsplit_once(')')?.1.split_whitespace().collect();
+        let ticks = fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+        let status = fs::read_to_string(format!("/proc/{}/status", pid)).ok()?;
+        let rss_kb: u64 = status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmRSS:"))?
+            .trim()
+            .trim_end_matches("kB")
+            .trim()
+            .parse()
+            .ok()?;
+        Some((Duration::from_millis(ticks * 1000 / CLOCK_TICKS), rss_kb * 1024))
+    }
+}
+
+struct Sampler {
+    cores: f32,
+    // 上一次采样的累计 CPU 时间
+    previous: HashMap<u32, (Duration, Instant)>,
+    high_cpu: u32,
+    status: ThrottleStatus,
+}
+
+impl Sampler {
+    fn new() -> Self {
+        Self {
+            cores: std::thread::available_parallelism().map_or(1, |n| n.get()) as f32,
+            previous: HashMap::new(),
+            high_cpu: 0,
+            status: ThrottleStatus::default(),
+        }
+    }
+
+    fn process(&mut self, pid: Option<u32>) -> Option<ProcessUsage> {
+        let pid = pid?;
+        let (cpu, memory_bytes) = native::sample(pid)?;
+        let now = Instant::now();
+        let cpu_percent = match self.previous.insert(pid, (cpu, now)) {
+            Some((last_cpu, last_at)) if cpu >= last_cpu => {
+                let elapsed = now.duration_since(last_at).as_secs_f32().max(0.001);
+                (cpu - last_cpu).as_secs_f32() / elapsed / self.cores * 100.0
+            }
+            _ => 0.0,
+        };
+        Some(ProcessUsage {
+            pid,
+            cpu_percent,
+            memory_bytes,
+        })
+    }
+
+    fn sample(&mut self, app: &AppHandle, settings: &ResourceSettings) -> ResourceUsage {
+        let usage = [
+            self.process(Some(std::process::id())),
+            self.process(app.state::<BackendManager>().pid()),
+            self.process(app.state::<LlamaServer>().pid()),
+        ];
+        let alive: Vec<u32> = usage.iter().flatten().map(|usage| usage.pid).collect();
+        self.previous.retain(|pid, _| alive.contains(pid));
+        let cpu_percent: f32 = usage.iter().flatten().map(|usage| usage.cpu_percent).sum();
+        let memory_bytes: u64 = usage.iter().flatten().map(|usage| usage.memory_bytes).sum();
+        let on_battery = power::on_battery(app);
+
+        let was = |reason| self.status.reasons.contains(&reason);
+        let limit = |value: f32, threshold: f32, active: bool| {
+            if active {
+                value >= threshold * RELEASE_RATIO
+            } else {
+                value > threshold
+            }
+        };
+        let cpu_high = limit(cpu_percent, settings.cpu_percent, was(ThrottleReason::HighCpu));
+        self.high_cpu = if cpu_high { self.high_cpu + 1 } else { 0 };
+        let memory_high = limit(
+            (memory_bytes / MB) as f32,
+            settings.memory_mb as f32,
+            was(ThrottleReason::HighMemory),
+        );
+
+        let mut reasons = Vec::new();
+        if settings.throttle_on_battery && on_battery == Some(true) {
+            reasons.push(ThrottleReason::OnBattery);
+        }
+        if self.high_cpu >= HIGH_SAMPLES || (cpu_high && was(ThrottleReason::HighCpu)) {
+            reasons.push(ThrottleReason::HighCpu);
+        }
+        if memory_high {
+            reasons.push(ThrottleReason::HighMemory);
+        }
+        self.status = ThrottleStatus {
+            throttled: settings.auto_throttle && !reasons.is_empty(),
+            reasons,
+        };
+
+        let [app_usage, backend, llama] = usage;
+        ResourceUsage {
+            app: app_usage,
+            backend,
+            llama,
+            cpu_percent,
+            memory_bytes,
+            on_battery,
+            throttle: self.status.clone(),
+            sampled_at: now_millis(),
+        }
+    }
+}
+
+fn resource_settings(app: &AppHandle) -> Result<ResourceSettings, AppError> {
+    Ok(app.state::<AppState>().settings.lock()?.resources.clone())
+}
+
+// 在 setup 中调用：后台线程定期采样，节流状态变化时推送事件
+pub fn start(app: &AppHandle) {
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        let mut sampler = Sampler::new();
+        loop {
+            let settings = resource_settings(&handle).unwrap_or_default();
+            let usage = sampler.sample(&handle, &settings);
+            let monitor = handle.state::<ResourceMonitor>();
+            let throttled = usage.throttle.throttled;
+            if monitor.throttled.swap(throttled, Ordering::SeqCst) != throttled {
+                info!(
+                    "Resource throttling {}: {:?}",
+                    if throttled { "on" } else { "off" },
+                    usage.throttle.reasons
+                );
+                events::publish(&handle, AppEvent::ResourceThrottleChanged(usage.throttle.clone()));
+            }
+            match monitor.usage.lock() {
+                Ok(mut last) => *last = Some(usage),
+                Err(e) => warn!("Failed to store resource usage: {}", e),
+            }
+            std::thread::sleep(SAMPLE_INTERVAL);
+        }
+    });
+}
+
+// 返回最近一次采样结果，启动后第一次采样完成前为 None
+#[tauri::command]
+pub fn get_resource_usage(monitor: State<'_, ResourceMonitor>) -> Result<Option<ResourceUsage>, AppError> {
+    Ok(monitor.usage.lock()?.clone())
+}
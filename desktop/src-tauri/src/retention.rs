@@ -0,0 +1,225 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::sync::Notify;
+use tracing::{debug, info, warn};
+
+use crate::error::AppError;
+use crate::storage::{now_millis, PurgeReport, PurgeScope};
+use crate::{llm, profiles, AppState};
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+const CLEANUP_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+const SHRED_CHUNK: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct RetentionSettings {
+    // 会话最后一次更新超过该天数后删除，0 表示永久保留
+    pub keep_days: u32,
+    // 只保留最近的若干个会话，0 表示不限制
+    pub keep_conversations: u32,
+    // 关闭后不再保存消息的录音，已有的录音在下一次清理时删除
+    pub store_audio: bool,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            keep_days: 0,
+            keep_conversations: 0,
+            store_audio: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PurgeType {
+    Messages,
+    Audio,
+    Memories,
+}
+
+// 毫秒时间戳，含两端，为空表示不限
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PurgeRange {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+// 定时按保留策略清理历史，修改策略后立即执行一次
+#[derive(Default)]
+pub struct Retention {
+    wake: Notify,
+}
+
+impl Retention {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub fn apply(app: &AppHandle) {
+    app.state::<Retention>().wake.notify_one();
+}
+
+// 先用零覆盖再删除；固态硬盘和写时复制的文件系统上不能保证原数据被擦除
+pub fn shred(path: &Path) -> io::Result<()> {
+    let len = fs::metadata(path)?.len();
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    let zeros = vec![0u8; SHRED_CHUNK];
+    let mut remaining = len;
+    while remaining > 0 {
+        let chunk = remaining.min(SHRED_CHUNK as u64) as usize;
+        file.write_all(&zeros[..chunk])?;
+        remaining -= chunk as u64;
+    }
+    file.sync_all()?;
+    drop(file);
+    fs::remove_file(path)
+}
+
+// 应用数据目录下保存消息录音的目录
+const RECORDINGS_DIR: &str = "recordings";
+// 语音合成和识别写在临时目录中的音频文件名前缀（见 tts::temp_wav 和 stt::whisper）
+const TEMP_AUDIO_PREFIXES: &[&str] = &["lingecho-tts-", "lingecho-stt-"];
+
+// 只删除本应用写入的录音：数据目录的 recordings 目录，以及临时目录中本应用生成的音频文件；
+// 消息中的其他路径（包括数据库、备份和设置所在的目录）只解除关联
+fn deletable(app: &AppHandle, path: &Path) -> bool {
+    let Ok(path) = path.canonicalize() else {
+        return false;
+    };
+    let in_recordings = profiles::data_dir(app)
+        .ok()
+        .and_then(|dir| dir.join(RECORDINGS_DIR).canonicalize().ok())
+        .is_some_and(|root| path.starts_with(root));
+    let temp_audio = std::env::temp_dir()
+        .canonicalize()
+        .is_ok_and(|temp| path.parent() == Some(temp.as_path()))
+        && path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| TEMP_AUDIO_PREFIXES.iter().any(|prefix| name.starts_with(prefix)));
+    in_recordings || temp_audio
+}
+
+pub fn remove_audio(app: &AppHandle, paths: &[String]) -> usize {
+    let mut removed = 0;
+    for path in paths.iter().map(Path::new) {
+        if !path.exists() {
+            continue;
+        }
+        if !deletable(app, path) {
+            warn!("Not deleting audio outside the recordings directory: {}", path.display());
+            continue;
+        }
+        match shred(path) {
+            Ok(()) => removed += 1,
+            Err(e) => warn!("Failed to delete audio {}: {}", path.display(), e),
+        }
+    }
+    removed
+}
+
+// 不保存录音时直接删除前端传入的录音文件
+pub fn discard_audio(app: &AppHandle, path: &str) {
+    remove_audio(app, &[path.to_string()]);
+}
+
+pub fn store_audio(app: &AppHandle) -> bool {
+    app.state::<AppState>()
+        .settings
+        .lock()
+        .map(|settings| settings.retention.store_audio)
+        .unwrap_or(true)
+}
+
+fn enforce(app: &AppHandle) -> Result<PurgeReport, AppError> {
+    let state = app.state::<AppState>();
+    let settings = state.settings.lock()?.retention.clone();
+    let before = (settings.keep_days > 0).then(|| now_millis() - settings.keep_days as i64 * DAY_MS);
+    let mut report = state.storage.prune_conversations(before, settings.keep_conversations)?;
+    if report.conversations > 0 {
+        llm::forget_deleted_conversations(app);
+    }
+    if !settings.store_audio {
+        let detached = state.storage.purge(
+            None,
+            None,
+            PurgeScope {
+                audio: true,
+                ..PurgeScope::default()
+            },
+        )?;
+        report.audio_files += detached.audio_files;
+        report.audio_paths.extend(detached.audio_paths);
+    }
+    remove_audio(app, &report.audio_paths);
+    if !report.is_empty() {
+        state.storage.compact()?;
+    }
+    Ok(report)
+}
+
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let handle = app.clone();
+            match tauri::async_runtime::spawn_blocking(move || enforce(&handle)).await {
+                Ok(Ok(report)) if report.is_empty() => debug!("No history exceeds the retention policy"),
+                Ok(Ok(report)) => info!(
+                    "Retention removed {} conversations, {} messages and {} audio files",
+                    report.conversations, report.messages, report.audio_files
+                ),
+                Ok(Err(e)) => warn!("History retention failed: {}", e),
+                Err(e) => warn!("History retention task failed: {}", e),
+            }
+            let retention = app.state::<Retention>();
+            tokio::select! {
+                _ = tokio::time::sleep(CLEANUP_INTERVAL) => {}
+                _ = retention.wake.notified() => {}
+            }
+        }
+    });
+}
+
+// 删除指定时间范围内的内容并压缩数据库，录音文件覆盖后删除
+#[tauri::command]
+pub async fn purge_history(
+    app: AppHandle,
+    range: Option<PurgeRange>,
+    types: Vec<PurgeType>,
+) -> Result<PurgeReport, AppError> {
+    if types.is_empty() {
+        return Err(AppError::invalid("请选择要清理的内容"));
+    }
+    let range = range.unwrap_or_default();
+    if let (Some(from), Some(to)) = (range.from, range.to) {
+        if from > to {
+            return Err(AppError::invalid("清理范围的开始时间晚于结束时间"));
+        }
+    }
+    let scope = PurgeScope {
+        messages: types.contains(&PurgeType::Messages),
+        audio: types.contains(&PurgeType::Audio),
+        memories: types.contains(&PurgeType::Memories),
+    };
+    tauri::async_runtime::spawn_blocking(move || {
+        let storage = &app.state::<AppState>().storage;
+        let report = storage.purge(range.from, range.to, scope)?;
+        let removed = remove_audio(&app, &report.audio_paths);
+        storage.compact()?;
+        info!(
+            "Purged {} messages, {} memories and {} of {} audio files",
+            report.messages, report.memories, removed, report.audio_files
+        );
+        Ok(report)
+    })
+    .await?
+}
@@ -0,0 +1,301 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::i18n;
+use crate::notifications::{self, CATEGORY_REMINDER};
+use crate::pet;
+use crate::storage::{now_millis, DATABASE_FILE};
+use crate::tts;
+use crate::AppState;
+
+// 没有待触发的提醒时，后台任务最长休眠的时间
+const IDLE_POLL: Duration = Duration::from_secs(60);
+// 启动时错过超过该时长的提醒只标记为已错过，不再播报
+const CATCH_UP_WINDOW_MS: i64 = 24 * 60 * 60 * 1000;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS reminders (
+    id TEXT PRIMARY KEY,
+    message TEXT NOT NULL,
+    due_at INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    fired_at INTEGER
+);
+CREATE INDEX IF NOT EXISTS idx_reminders_due ON reminders(status, due_at);
+";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ReminderStatus {
+    Pending,
+    Fired,
+    // 应用关闭期间到期且超过补发时间窗口
+    Missed,
+    Cancelled,
+}
+
+impl ReminderStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ReminderStatus::Pending => "pending",
+            ReminderStatus::Fired => "fired",
+            ReminderStatus::Missed => "missed",
+            ReminderStatus::Cancelled => "cancelled",
+        }
+    }
+
+    fn parse(value: &str) -> ReminderStatus {
+        match value {
+            "fired" => ReminderStatus::Fired,
+            "missed" => ReminderStatus::Missed,
+            "cancelled" => ReminderStatus::Cancelled,
+            _ => ReminderStatus::Pending,
+        }
+    }
+}
+
+// 时间均为毫秒时间戳
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reminder {
+    pub id: String,
+    pub message: String,
+    pub due_at: i64,
+    pub created_at: i64,
+    pub status: ReminderStatus,
+    pub fired_at: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ReminderFired {
+    #[serde(flatten)]
+    pub reminder: Reminder,
+    // 应用关闭期间到期、启动后补发的提醒
+    pub late: bool,
+}
+
+fn reminder_from_row(row: &rusqlite::Row) -> rusqlite::Result<Reminder> {
+    Ok(Reminder {
+        id: row.get(0)?,
+        message: row.get(1)?,
+        due_at: row.get(2)?,
+        created_at: row.get(3)?,
+        status: ReminderStatus::parse(&row.get::<_, String>(4)?),
+        fired_at: row.get(5)?,
+    })
+}
+
+pub struct Scheduler {
+    conn: Mutex<Connection>,
+    // 新建或取消提醒时唤醒后台任务重新计算休眠时间
+    wake: Notify,
+}
+
+impl Scheduler {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            wake: Notify::new(),
+        })
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    pub fn create(&self, message: &str, due_at: i64) -> Result<Reminder, AppError> {
+        let reminder = Reminder {
+            id: uuid::Uuid::new_v4().to_string(),
+            message: message.to_string(),
+            due_at,
+            created_at: now_millis(),
+            status: ReminderStatus::Pending,
+            fired_at: None,
+        };
+        self.conn()?.execute(
+            "INSERT INTO reminders (id, message, due_at, created_at, status) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                reminder.id,
+                reminder.message,
+                reminder.due_at,
+                reminder.created_at,
+                reminder.status.as_str()
+            ],
+        )?;
+        self.wake.notify_one();
+        Ok(reminder)
+    }
+
+    pub fn list(&self, include_done: bool) -> Result<Vec<Reminder>, AppError> {
+        let conn = self.conn()?;
+        let sql = if include_done {
+            "SELECT id, message, due_at, created_at, status, fired_at FROM reminders ORDER BY due_at DESC"
+        } else {
+            "SELECT id, message, due_at, created_at, status, fired_at FROM reminders
+             WHERE status = 'pending' ORDER BY due_at"
+        };
+        let mut stmt = conn.prepare(sql)?;
+        let rows = stmt.query_map([], reminder_from_row)?;
+        let reminders = rows.collect::<Result<Vec<_>, _>>();
+        reminders.map_err(AppError::from)
+    }
+
+    pub fn cancel(&self, id: &str) -> Result<bool, AppError> {
+        let changed = self.conn()?.execute(
+            "UPDATE reminders SET status = 'cancelled' WHERE id = ?1 AND status = 'pending'",
+            params![id],
+        )?;
+        self.wake.notify_one();
+        Ok(changed > 0)
+    }
+
+    fn next_due(&self) -> Result<Option<i64>, AppError> {
+        let next = self
+            .conn()?
+            .query_row(
+                "SELECT MIN(due_at) FROM reminders WHERE status = 'pending'",
+                [],
+                |row| row.get::<_, Option<i64>>(0),
+            )
+            .optional()?;
+        Ok(next.flatten())
+    }
+
+    // 取出所有已到期的提醒并标记状态，返回需要通知的提醒
+    fn take_due(&self, now: i64, catch_up: bool) -> Result<Vec<ReminderFired>, AppError> {
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let due = {
+            let mut stmt = tx.prepare(
+                "SELECT id, message, due_at, created_at, status, fired_at FROM reminders
+                     WHERE status = 'pending' AND due_at <= ?1 ORDER BY due_at",
+            )?;
+            let rows = stmt.query_map(params![now], reminder_from_row)?;
+            rows.collect::<Result<Vec<_>, _>>()?
+        };
+
+        let mut fired = Vec::new();
+        for mut reminder in due {
+            let status = if catch_up && now - reminder.due_at > CATCH_UP_WINDOW_MS {
+                ReminderStatus::Missed
+            } else {
+                ReminderStatus::Fired
+            };
+            tx.execute(
+                "UPDATE reminders SET status = ?1, fired_at = ?2 WHERE id = ?3",
+                params![status.as_str(), now, reminder.id],
+            )?;
+            reminder.status = status;
+            reminder.fired_at = Some(now);
+            if status == ReminderStatus::Fired {
+                fired.push(ReminderFired { reminder, late: catch_up });
+            }
+        }
+        tx.commit()?;
+        Ok(fired)
+    }
+}
+
+fn announce(app: &AppHandle, event: &ReminderFired) {
+    let reminder = &event.reminder;
+    info!("Reminder {} fired (late: {})", reminder.id, event.late);
+
+    let title = if event.late {
+        "reminder.missed_title"
+    } else {
+        "reminder.title"
+    };
+    let title = i18n::t(title);
+    let action = format!("lingecho://reminders/{}", reminder.id);
+    if let Err(e) = notifications::send(app, CATEGORY_REMINDER, &title, &reminder.message, Some(action)) {
+        warn!("{}", e);
+    }
+    events::publish(app, AppEvent::ReminderFired(event.clone()));
+    pet::bubble::say(app, &reminder.message);
+
+    // 补发的提醒只发通知，避免启动时连续播报
+    if !event.late {
+        let handle = app.clone();
+        let text = i18n::tf("reminder.spoken", &[&reminder.message]);
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = tts::speak(handle, text, None, None).await {
+                warn!("Failed to announce reminder: {}", e);
+            }
+        });
+    }
+}
+
+fn fire_due(app: &AppHandle, catch_up: bool) {
+    let state = app.state::<AppState>();
+    match state.scheduler.take_due(now_millis(), catch_up) {
+        Ok(fired) => fired.iter().for_each(|event| announce(app, event)),
+        Err(e) => warn!("Failed to load due reminders: {}", e),
+    }
+}
+
+// 在 setup 中调用：先补发应用关闭期间到期的提醒，再按最近的到期时间休眠
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        fire_due(&app, true);
+        loop {
+            let state = app.state::<AppState>();
+            let next = state.scheduler.next_due().unwrap_or_else(|e| {
+                warn!("Failed to query reminders: {}", e);
+                None
+            });
+            let wait = next
+                .map(|due| Duration::from_millis((due - now_millis()).max(0) as u64).min(IDLE_POLL))
+                .unwrap_or(IDLE_POLL);
+
+            tokio::select! {
+                _ = tokio::time::sleep(wait) => {}
+                _ = state.scheduler.wake.notified() => continue,
+            }
+            fire_due(&app, false);
+        }
+    });
+}
+
+// due_at 为毫秒时间戳，也可以用 delay_secs 表示"多少秒后提醒"
+#[tauri::command]
+pub fn create_reminder(
+    state: State<'_, AppState>,
+    message: String,
+    due_at: Option<i64>,
+    delay_secs: Option<u64>,
+) -> Result<Reminder, AppError> {
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(AppError::invalid("提醒内容不能为空"));
+    }
+    let due_at = match (due_at, delay_secs) {
+        (Some(due_at), _) => due_at,
+        (None, Some(delay)) => i64::try_from(delay)
+            .ok()
+            .and_then(|delay| delay.checked_mul(1000))
+            .and_then(|delay| now_millis().checked_add(delay))
+            .ok_or_else(|| AppError::invalid("提醒时间超出范围"))?,
+        (None, None) => return Err(AppError::invalid("需要指定提醒时间")),
+    };
+    state.scheduler.create(message, due_at)
+}
+
+#[tauri::command]
+pub fn list_reminders(state: State<'_, AppState>, include_done: Option<bool>) -> Result<Vec<Reminder>, AppError> {
+    state.scheduler.list(include_done.unwrap_or(false))
+}
+
+#[tauri::command]
+pub fn cancel_reminder(state: State<'_, AppState>, id: String) -> Result<bool, AppError> {
+    state.scheduler.cancel(&id)
+}
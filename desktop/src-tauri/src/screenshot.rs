@@ -0,0 +1,158 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::io::Cursor;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager};
+use tracing::info;
+use xcap::image::{ImageFormat, RgbaImage};
+use xcap::{Monitor, Window};
+
+use crate::error::AppError;
+use crate::i18n;
+use crate::{settings, AppState};
+
+const SCREENSHOT_DIR: &str = "lingecho-screenshots";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ScreenCaptureSettings {
+    // 用户首次截图时在确认对话框中授权，之后不再询问
+    pub allowed: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CaptureOutput {
+    // 保存到临时目录并返回路径
+    #[default]
+    File,
+    // 以 base64 编码的 PNG 返回
+    Base64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub path: Option<String>,
+    pub data: Option<String>,
+}
+
+fn ensure_allowed(app: &AppHandle) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    if state.settings.lock()?.screen_capture.allowed {
+        return Ok(());
+    }
+
+    let window = app.get_window("main");
+    let granted = tauri::api::dialog::blocking::ask(
+        window.as_ref(),
+        i18n::t("screenshot.consent_title"),
+        i18n::t("screenshot.consent_message"),
+    );
+    if !granted {
+        return Err(AppError::PermissionDenied("用户拒绝了屏幕截图权限".to_string()));
+    }
+
+    let mut settings = state.settings.lock()?;
+    settings.screen_capture.allowed = true;
+    settings::save_and_notify(app, &settings, &["screen_capture"])
+}
+
+// monitor 可以是显示器名称或 id，为空时使用主显示器
+fn find_monitor(monitor: Option<&str>) -> Result<Monitor, AppError> {
+    let monitors = Monitor::all().map_err(|e| AppError::unavailable("无法访问屏幕", e))?;
+    let found = match monitor {
+        Some(key) => monitors.into_iter().find(|m| {
+            m.name().is_ok_and(|name| name == key) || m.id().is_ok_and(|id| id.to_string() == key)
+        }),
+        None => monitors.into_iter().find(|m| m.is_primary().unwrap_or(false)),
+    };
+    found.ok_or_else(|| AppError::NotFound(format!("找不到显示器: {}", monitor.unwrap_or("primary"))))
+}
+
+// 优先按本应用的窗口标签查找，否则按其他应用的窗口标题匹配
+fn find_window(app: &AppHandle, label: &str) -> Result<Window, AppError> {
+    let windows = Window::all().map_err(|e| AppError::unavailable("无法访问屏幕", e))?;
+    let own = app.get_window(label).and_then(|window| window.title().ok());
+    let pid = std::process::id();
+    let found = match own {
+        Some(title) => windows
+            .into_iter()
+            .find(|w| w.pid().is_ok_and(|p| p == pid) && w.title().is_ok_and(|t| t == title)),
+        None => windows
+            .into_iter()
+            .filter(|w| !w.is_minimized().unwrap_or(true))
+            .find(|w| w.title().is_ok_and(|t| t.contains(label))),
+    };
+    found.ok_or_else(|| AppError::NotFound(format!("找不到窗口: {}", label)))
+}
+
+pub fn encode(image: RgbaImage, output: CaptureOutput) -> Result<Screenshot, AppError> {
+    let (width, height) = image.dimensions();
+    let mut screenshot = Screenshot {
+        width,
+        height,
+        path: None,
+        data: None,
+    };
+    match output {
+        CaptureOutput::File => {
+            let dir = std::env::temp_dir().join(SCREENSHOT_DIR);
+            std::fs::create_dir_all(&dir)?;
+            let path: PathBuf = dir.join(format!("{}.png", uuid::Uuid::new_v4()));
+            image
+                .save_with_format(&path, ImageFormat::Png)
+                .map_err(|e| AppError::Internal(format!("无法编码截图: {}", e)))?;
+            screenshot.path = Some(path.to_string_lossy().to_string());
+        }
+        CaptureOutput::Base64 => {
+            let mut bytes = Cursor::new(Vec::new());
+            image
+                .write_to(&mut bytes, ImageFormat::Png)
+                .map_err(|e| AppError::Internal(format!("无法编码截图: {}", e)))?;
+            screenshot.data = Some(base64::engine::general_purpose::STANDARD.encode(bytes.into_inner()));
+        }
+    }
+    Ok(screenshot)
+}
+
+#[tauri::command]
+pub async fn capture_screen(
+    app: AppHandle,
+    monitor: Option<String>,
+    output: Option<CaptureOutput>,
+) -> Result<Screenshot, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        ensure_allowed(&app)?;
+        let monitor = find_monitor(monitor.as_deref())?;
+        let image = monitor.capture_image().map_err(|e| format!("截图失败: {}", e))?;
+        info!("Captured monitor {}", monitor.name().unwrap_or_default());
+        encode(image, output.unwrap_or_default())
+    })
+    .await?
+}
+
+#[tauri::command]
+pub async fn capture_window(
+    app: AppHandle,
+    label: String,
+    output: Option<CaptureOutput>,
+) -> Result<Screenshot, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        ensure_allowed(&app)?;
+        let window = find_window(&app, &label)?;
+        let image = window.capture_image().map_err(|e| format!("截图失败: {}", e))?;
+        info!("Captured window {}", window.title().unwrap_or_default());
+        encode(image, output.unwrap_or_default())
+    })
+    .await?
+}
+
+#[tauri::command]
+pub fn set_screen_capture_allowed(app: AppHandle, allowed: bool) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.screen_capture.allowed = allowed;
+    settings::save_and_notify(&app, &settings, &["screen_capture"])
+}
@@ -0,0 +1,156 @@
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::profiles;
+use crate::{settings, AppState};
+
+// 与 tauri.conf.json 中的 identifier 保持一致，非默认配置追加配置名称
+const SERVICE: &str = "com.cetiprobe.desktop";
+const MAX_NAME_LEN: usize = 128;
+
+// 嵌入服务的 API Key
+pub const EMBEDDING_API_KEY: &str = "embedding.api_key";
+// 登录后端后获得的访问令牌，由 backend_request 自动附带
+pub const BACKEND_TOKEN: &str = "backend.token";
+// 手动代理的认证密码
+pub const PROXY_PASSWORD: &str = "network.proxy_password";
+// MQTT 服务器的认证密码
+pub const MQTT_PASSWORD: &str = "mqtt.password";
+// 本地 HTTP 接口的访问令牌
+pub const LOCAL_API_TOKEN: &str = "local_api.token";
+// 对话历史的字段加密密钥，只在应用内部使用
+pub const DATABASE_KEY: &str = "storage.database_key";
+// 本配置写入过的密钥名称，每行一个；钥匙串无法列出条目，清除数据时据此删除
+pub const INDEX: &str = "secrets.index";
+
+fn entry(name: &str) -> Result<keyring::Entry, AppError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid {
+        return Err(AppError::invalid(format!("无效的密钥名称: {}", name)));
+    }
+    keyring::Entry::new(&profiles::keychain_service(SERVICE), name)
+        .map_err(|e| AppError::unavailable("无法访问系统钥匙串", e))
+}
+
+fn write(name: &str, value: &str) -> Result<(), AppError> {
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| AppError::unavailable("无法写入系统钥匙串", e))
+}
+
+// 写入过的全部密钥名称，不含索引本身
+pub fn stored_names() -> Vec<String> {
+    match get(INDEX) {
+        Ok(index) => index
+            .unwrap_or_default()
+            .lines()
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(e) => {
+            warn!("Failed to read keychain index: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+// 索引写入失败不影响密钥本身，只写日志
+fn update_index(name: &str, present: bool) {
+    let mut names = stored_names();
+    if names.iter().any(|stored| stored == name) == present {
+        return;
+    }
+    names.retain(|stored| stored != name);
+    if present {
+        names.push(name.to_string());
+    }
+    if let Err(e) = write(INDEX, &names.join("\n")) {
+        warn!("Failed to update keychain index: {}", e);
+    }
+}
+
+pub fn store(name: &str, value: &str) -> Result<(), AppError> {
+    write(name, value)?;
+    update_index(name, true);
+    Ok(())
+}
+
+pub fn get(name: &str) -> Result<Option<String>, AppError> {
+    match entry(name)?.get_password() {
+        Ok(value) => Ok(Some(value)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(AppError::unavailable("无法读取系统钥匙串", e)),
+    }
+}
+
+pub fn delete(name: &str) -> Result<bool, AppError> {
+    let deleted = match entry(name)?.delete_password() {
+        Ok(()) => true,
+        Err(keyring::Error::NoEntry) => false,
+        Err(e) => return Err(AppError::unavailable("无法删除钥匙串条目", e)),
+    };
+    if name != INDEX {
+        update_index(name, false);
+    }
+    Ok(deleted)
+}
+
+// 把旧版本明文保存在设置文件中的密钥移入钥匙串，返回是否需要重新保存设置
+pub fn migrate_plaintext(settings: &mut crate::settings::Settings) -> bool {
+    let Some(key) = settings.knowledge.embedding.api_key.take() else {
+        return false;
+    };
+    match store(EMBEDDING_API_KEY, &key) {
+        Ok(()) => {
+            info!("Moved plaintext embedding API key into the system keychain");
+            true
+        }
+        Err(e) => {
+            // 写入失败时保留原值，避免密钥丢失
+            warn!("Failed to migrate embedding API key: {}", e);
+            settings.knowledge.embedding.api_key = Some(key);
+            false
+        }
+    }
+}
+
+// 前端命令不能读写内部密钥，丢失数据库密钥会导致历史记录无法解密
+fn check_accessible(name: &str) -> Result<(), AppError> {
+    if name == DATABASE_KEY || name == INDEX {
+        return Err(AppError::PermissionDenied(format!("不允许访问密钥 {}", name)));
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn store_secret(name: String, value: String) -> Result<(), AppError> {
+    check_accessible(&name)?;
+    if value.is_empty() {
+        return Err(AppError::invalid("密钥内容不能为空"));
+    }
+    store(&name, &value)
+}
+
+#[tauri::command]
+pub fn get_secret(name: String) -> Result<Option<String>, AppError> {
+    check_accessible(&name)?;
+    get(&name)
+}
+
+#[tauri::command]
+pub fn delete_secret(app: AppHandle, name: String) -> Result<bool, AppError> {
+    check_accessible(&name)?;
+    // 同时清理可能残留在设置文件中的明文副本
+    if name == EMBEDDING_API_KEY {
+        let state = app.state::<AppState>();
+        let mut settings = state.settings.lock()?;
+        if settings.knowledge.embedding.api_key.take().is_some() {
+            settings::save_and_notify(&app, &settings, &["knowledge"])?;
+        }
+    }
+    delete(&name)
+}
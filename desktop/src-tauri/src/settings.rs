@@ -0,0 +1,669 @@
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::acceleration::AccelerationSettings;
+use crate::analytics::AnalyticsSettings;
+use crate::app_context::AppContextSettings;
+use crate::audio::playback::{AudioPlayer, PlaybackSettings};
+use crate::audio::processing::ProcessingSettings;
+use crate::audio::tts_stream::TtsStreamSettings;
+use crate::audio::vad::VadConfig;
+use crate::audio::wakeword::WakeWordConfig;
+use crate::backend::{BackendLaunch, BackendManager};
+use crate::backup::BackupSettings;
+use crate::calendar::CalendarSettings;
+use crate::captions::{self, CaptionSettings};
+use crate::dnd::{self, DndSettings};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::file_search::FileSearchSettings;
+use crate::fullscreen::FullscreenSettings;
+use crate::hotkeys::{self, HotkeyBindings};
+use crate::intents::IntentSettings;
+use crate::knowledge::embeddings::EmbeddingSettings;
+use crate::llm::LlmSettings;
+use crate::local_api::{self, LocalApiSettings};
+use crate::macos::{self, MacosSettings};
+use crate::memory::MemorySettings;
+use crate::mqtt::{self, MqttSettings};
+use crate::network::{Network, NetworkSettings};
+use crate::notes::NotesSettings;
+use crate::notifications::NotificationSettings;
+use crate::ocr::OcrSettings;
+use crate::offline::OfflineSettings;
+use crate::onboarding::OnboardingSettings;
+use crate::persona::PersonaSettings;
+use crate::pet::idle::IdleSettings;
+use crate::pipeline::PipelineSettings;
+use crate::plugins::PluginSettings;
+use crate::power::{self, PowerSettings};
+use crate::privacy::PrivacySettings;
+use crate::resources::ResourceSettings;
+use crate::retention::{self, RetentionSettings};
+use crate::screenshot::ScreenCaptureSettings;
+use crate::stt::SttSettings;
+use crate::system_control::SystemControlSettings;
+use crate::tts::TtsSettings;
+use crate::updater::UpdateChannel;
+use crate::weather::WeatherSettings;
+use crate::webhooks::WebhookSettings;
+use crate::window_state::WindowGeometry;
+use crate::{i18n, logging, AppState};
+
+const SETTINGS_FILE: &str = "settings.json";
+// 连续修改设置时合并写盘
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+// 值为字典的设置项，补丁中可以出现新的键
+const MAP_FIELDS: [&str; 1] = ["hotkeys"];
+// 编辑器保存文件时会连续触发多个事件
+const RELOAD_DEBOUNCE: Duration = Duration::from_millis(300);
+
+// 用户偏好设置，序列化后保存在应用配置目录下
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub theme: String,
+    // 界面语言，例如 zh-CN、en-US
+    pub language: String,
+    pub backend: BackendSettings,
+    pub pet: PetSettings,
+    pub hotkeys: HotkeyBindings,
+    // 开机自启时是否隐藏主窗口，只显示桌宠
+    pub autostart_minimized: bool,
+    pub audio: AudioSettings,
+    pub knowledge: KnowledgeSettings,
+    pub file_search: FileSearchSettings,
+    pub notes: NotesSettings,
+    // 主窗口上次的位置与尺寸
+    pub main_window: Option<WindowGeometry>,
+    // error / warn / info / debug / trace
+    pub log_level: String,
+    pub update_channel: UpdateChannel,
+    pub notifications: NotificationSettings,
+    pub screen_capture: ScreenCaptureSettings,
+    pub ocr: OcrSettings,
+    pub privacy: PrivacySettings,
+    pub app_context: AppContextSettings,
+    pub system_control: SystemControlSettings,
+    pub fullscreen: FullscreenSettings,
+    pub stt: SttSettings,
+    pub tts: TtsSettings,
+    pub pipeline: PipelineSettings,
+    pub llm: LlmSettings,
+    pub memory: MemorySettings,
+    pub persona: PersonaSettings,
+    pub plugins: PluginSettings,
+    pub intents: IntentSettings,
+    pub mqtt: MqttSettings,
+    pub webhooks: WebhookSettings,
+    pub local_api: LocalApiSettings,
+    pub retention: RetentionSettings,
+    pub resources: ResourceSettings,
+    pub power: PowerSettings,
+    pub acceleration: AccelerationSettings,
+    pub network: NetworkSettings,
+    pub offline: OfflineSettings,
+    pub backup: BackupSettings,
+    pub calendar: CalendarSettings,
+    pub weather: WeatherSettings,
+    pub captions: CaptionSettings,
+    pub dnd: DndSettings,
+    pub analytics: AnalyticsSettings,
+    pub macos: MacosSettings,
+    pub onboarding: OnboardingSettings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            theme: "dark".to_string(),
+            language: i18n::SYSTEM_LOCALE.to_string(),
+            backend: BackendSettings::default(),
+            pet: PetSettings::default(),
+            hotkeys: HotkeyBindings::new(),
+            autostart_minimized: false,
+            audio: AudioSettings::default(),
+            knowledge: KnowledgeSettings::default(),
+            file_search: FileSearchSettings::default(),
+            notes: NotesSettings::default(),
+            main_window: None,
+            log_level: "info".to_string(),
+            update_channel: UpdateChannel::default(),
+            notifications: NotificationSettings::default(),
+            screen_capture: ScreenCaptureSettings::default(),
+            ocr: OcrSettings::default(),
+            privacy: PrivacySettings::default(),
+            app_context: AppContextSettings::default(),
+            system_control: SystemControlSettings::default(),
+            fullscreen: FullscreenSettings::default(),
+            stt: SttSettings::default(),
+            tts: TtsSettings::default(),
+            pipeline: PipelineSettings::default(),
+            llm: LlmSettings::default(),
+            memory: MemorySettings::default(),
+            persona: PersonaSettings::default(),
+            plugins: PluginSettings::default(),
+            intents: IntentSettings::default(),
+            mqtt: MqttSettings::default(),
+            webhooks: WebhookSettings::default(),
+            local_api: LocalApiSettings::default(),
+            retention: RetentionSettings::default(),
+            resources: ResourceSettings::default(),
+            power: PowerSettings::default(),
+            acceleration: AccelerationSettings::default(),
+            network: NetworkSettings::default(),
+            offline: OfflineSettings::default(),
+            backup: BackupSettings::default(),
+            calendar: CalendarSettings::default(),
+            weather: WeatherSettings::default(),
+            captions: CaptionSettings::default(),
+            dnd: DndSettings::default(),
+            analytics: AnalyticsSettings::default(),
+            macos: MacosSettings::default(),
+            onboarding: OnboardingSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BackendSettings {
+    // 后端监听端口，被占用时自动选择空闲端口
+    pub port: u16,
+    // 传给后端的 -mode 参数（development / test / production）
+    pub mode: String,
+    // 追加到后端启动命令的额外参数
+    pub extra_args: Vec<String>,
+    // 后端 WebSocket 推送接口路径
+    pub ws_path: String,
+}
+
+impl Default for BackendSettings {
+    fn default() -> Self {
+        Self {
+            port: 7072,
+            mode: "test".to_string(),
+            extra_args: Vec::new(),
+            ws_path: "/api/ws".to_string(),
+        }
+    }
+}
+
+impl Settings {
+    // 检查各项取值范围，所有问题合并为一条错误返回
+    pub fn validate(&self) -> Result<(), AppError> {
+        let mut problems = Vec::new();
+        if self.theme.trim().is_empty() {
+            problems.push("主题名称不能为空".to_string());
+        }
+        if self.language.trim().is_empty() {
+            problems.push("界面语言不能为空".to_string());
+        }
+        if let Err(e) = logging::parse_level(&self.log_level) {
+            problems.push(e.to_string());
+        }
+        if self.backend.port == 0 {
+            problems.push("后端端口不能为 0".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.audio.playback.volume) {
+            problems.push("音量需在 0 - 1 之间".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.audio.wake_word.sensitivity) {
+            problems.push("灵敏度需在 0 - 1 之间".to_string());
+        }
+        if let Err(e) = self.pet.size.validate() {
+            problems.push(e.to_string());
+        }
+        if self.pet.idle.min_interval_secs > self.pet.idle.max_interval_secs {
+            problems.push("最小间隔不能大于最大间隔".to_string());
+        }
+        if self.pipeline.listen_timeout_secs == 0 {
+            problems.push("聆听超时不能为 0".to_string());
+        }
+        if self.backup.interval_hours == 0 {
+            problems.push("备份间隔不能为 0".to_string());
+        }
+        if let Err(e) = self.captions.validate() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.dnd.validate() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.intents.validate() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.webhooks.validate() {
+            problems.push(e.to_string());
+        }
+        if let Err(e) = self.local_api.validate() {
+            problems.push(e.to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(AppError::invalid(problems.join("；")))
+        }
+    }
+}
+
+// 按 JSON Merge Patch 的规则合并：对象逐键合并，其他值直接替换。
+// 设置结构中不存在的键视为错误，避免拼写错误被静默忽略
+fn merge_patch(target: &mut serde_json::Value, patch: serde_json::Value, path: &str) -> Result<(), AppError> {
+    let serde_json::Value::Object(patch) = patch else {
+        *target = patch;
+        return Ok(());
+    };
+    let serde_json::Value::Object(fields) = target else {
+        *target = serde_json::Value::Object(patch);
+        return Ok(());
+    };
+    let open = fields.is_empty() || MAP_FIELDS.contains(&path);
+    for (key, value) in patch {
+        let field = if path.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", path, key)
+        };
+        match fields.get_mut(&key) {
+            Some(current) => merge_patch(current, value, &field)?,
+            None if open => {
+                fields.insert(key, value);
+            }
+            None => return Err(AppError::invalid(format!("未知的设置项: {}", field))),
+        }
+    }
+    Ok(())
+}
+
+// 应用补丁，返回新的设置和发生变化的顶层设置项
+pub fn apply_patch(settings: &Settings, patch: serde_json::Value) -> Result<(Settings, Vec<String>), AppError> {
+    if !patch.is_object() {
+        return Err(AppError::invalid("设置补丁必须是对象"));
+    }
+    let current = serde_json::to_value(settings)?;
+    let mut merged = current.clone();
+    merge_patch(&mut merged, patch, "")?;
+    let updated: Settings =
+        serde_json::from_value(merged.clone()).map_err(|e| AppError::invalid(format!("设置格式错误: {}", e)))?;
+    updated.validate()?;
+
+    let changed = changed_fields(&current, &merged);
+    Ok((updated, changed))
+}
+
+fn changed_fields(before: &serde_json::Value, after: &serde_json::Value) -> Vec<String> {
+    match (before, after) {
+        (serde_json::Value::Object(before), serde_json::Value::Object(after)) => after
+            .iter()
+            .filter(|(key, value)| before.get(*key) != Some(*value))
+            .map(|(key, _)| key.clone())
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+// 手动编辑的设置文件无效时发给前端的诊断信息，行列号从 1 开始
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsFileError {
+    pub path: String,
+    pub message: String,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SettingsChanged {
+    pub changed: Vec<String>,
+    pub settings: Settings,
+}
+
+pub struct SettingsStore {
+    path: PathBuf,
+    // 有尚未写盘的修改
+    dirty: AtomicBool,
+    generation: AtomicU64,
+    // 本进程最后一次写入的文件内容，用于区分自己的写入和外部修改
+    written: Mutex<Option<String>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    reload_generation: AtomicU64,
+}
+
+impl SettingsStore {
+    pub fn new(config_dir: &Path) -> Self {
+        Self {
+            path: config_dir.join(SETTINGS_FILE),
+            dirty: AtomicBool::new(false),
+            generation: AtomicU64::new(0),
+            written: Mutex::new(None),
+            watcher: Mutex::new(None),
+            reload_generation: AtomicU64::new(0),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    // 读取设置文件，文件不存在或格式错误时回退到默认值
+    pub fn load(&self) -> Settings {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(_) => return Settings::default(),
+        };
+
+        match serde_json::from_str(&content) {
+            Ok(settings) => settings,
+            Err(e) => {
+                warn!("Failed to parse settings file {}: {}", self.path.display(), e);
+                Settings::default()
+            }
+        }
+    }
+
+    // 与 load 不同，格式错误时返回带位置的诊断信息；内容与本进程最后一次写入的相同时返回 None
+    fn read_external(&self) -> Result<Option<Settings>, SettingsFileError> {
+        let diagnostic = |message: String, line: Option<usize>, column: Option<usize>| SettingsFileError {
+            path: self.path.to_string_lossy().to_string(),
+            message,
+            line,
+            column,
+        };
+        let content = fs::read_to_string(&self.path).map_err(|e| diagnostic(e.to_string(), None, None))?;
+        if self
+            .written
+            .lock()
+            .is_ok_and(|written| written.as_deref() == Some(content.as_str()))
+        {
+            return Ok(None);
+        }
+        let settings: Settings =
+            serde_json::from_str(&content).map_err(|e| diagnostic(e.to_string(), Some(e.line()), Some(e.column())))?;
+        settings.validate().map_err(|e| diagnostic(e.to_string(), None, None))?;
+        Ok(Some(settings))
+    }
+
+    // 先写临时文件再重命名，避免写入中途退出导致设置文件损坏
+    pub fn save(&self, settings: &Settings) -> Result<(), AppError> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let content = serde_json::to_string_pretty(settings)?;
+        let tmp_path = self.path.with_extension("json.tmp");
+        // 先记录再写入，文件变化事件到达时已能识别为自己的写入
+        *self.written.lock()? = Some(content.clone());
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, &self.path)?;
+        self.dirty.store(false, Ordering::SeqCst);
+        Ok(())
+    }
+
+    // 延迟写盘，期间的新修改会重新计时，写入的是届时内存中的最新设置
+    pub fn schedule_save(&self, app: &AppHandle) {
+        self.dirty.store(true, Ordering::SeqCst);
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(SAVE_DEBOUNCE).await;
+            let state = app.state::<AppState>();
+            if state.store.generation.load(Ordering::SeqCst) != generation {
+                return;
+            }
+            if let Err(e) = state.store.flush(&state.settings) {
+                warn!("Failed to save settings: {}", e);
+            }
+        });
+    }
+
+    // 退出前调用，写入尚未保存的修改
+    pub fn flush(&self, settings: &std::sync::Mutex<Settings>) -> Result<(), AppError> {
+        if !self.dirty.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let settings = settings.lock()?;
+        self.save(&settings)
+    }
+}
+
+// 重命名写入会替换文件本身，因此监听所在目录
+pub fn watch(app: &AppHandle) -> Result<(), AppError> {
+    let store = &app.state::<AppState>().store;
+    let dir = store.path.parent().ok_or("设置文件路径无效")?.to_path_buf();
+    fs::create_dir_all(&dir)?;
+    let handle = app.clone();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        let Ok(event) = event else {
+            return;
+        };
+        if event.kind.is_access() {
+            return;
+        }
+        let store = &handle.state::<AppState>().store;
+        if event.paths.iter().any(|path| path == &store.path) {
+            schedule_reload(&handle);
+        }
+    })
+    .map_err(|e| AppError::unavailable("无法监听设置文件", e))?;
+    watcher
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .map_err(|e| AppError::unavailable("无法监听设置文件", e))?;
+    *store.watcher.lock()? = Some(watcher);
+    info!("Watching {} for changes", store.path.display());
+    Ok(())
+}
+
+fn schedule_reload(app: &AppHandle) {
+    let store = &app.state::<AppState>().store;
+    let generation = store.reload_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(RELOAD_DEBOUNCE).await;
+        if app.state::<AppState>().store.reload_generation.load(Ordering::SeqCst) == generation {
+            reload(&app);
+        }
+    });
+}
+
+// 本应用自己写入的内容不重新加载：防抖写盘之后内存中可能已有新的修改，重新加载会把它们覆盖掉。
+// 外部修改的内容与内存中一致时也不做任何处理
+fn reload(app: &AppHandle) {
+    let state = app.state::<AppState>();
+    let loaded = match state.store.read_external() {
+        Ok(Some(settings)) => settings,
+        Ok(None) => return,
+        Err(diagnostic) => {
+            warn!("Ignoring invalid settings file: {}", diagnostic.message);
+            events::publish(app, AppEvent::SettingsFileError(diagnostic));
+            return;
+        }
+    };
+    let (before, changed) = {
+        let Ok(mut settings) = state.settings.lock() else {
+            return;
+        };
+        let (Ok(current), Ok(updated)) = (serde_json::to_value(&*settings), serde_json::to_value(&loaded)) else {
+            return;
+        };
+        let changed = changed_fields(&current, &updated);
+        if changed.is_empty() {
+            return;
+        }
+        (std::mem::replace(&mut *settings, loaded.clone()), changed)
+    };
+
+    info!("Settings file changed: {}", changed.join(", "));
+    apply_changes(app, &before, &loaded);
+    notify_changed(app, changed, &loaded);
+}
+
+// 整体替换设置后调用（例如导入数据），与 update_settings 一样立即生效并通知所有窗口
+pub fn announce_replaced(app: &AppHandle, before: &Settings, after: &Settings) {
+    let (Ok(current), Ok(updated)) = (serde_json::to_value(before), serde_json::to_value(after)) else {
+        return;
+    };
+    let changed = changed_fields(&current, &updated);
+    if changed.is_empty() {
+        return;
+    }
+    info!("Settings replaced: {}", changed.join(", "));
+    apply_changes(app, before, after);
+    notify_changed(app, changed, after);
+}
+
+// 命令直接修改设置后调用：立即写盘并通知所有窗口，changed 为修改的顶层设置项
+pub fn save_and_notify(app: &AppHandle, settings: &Settings, changed: &[&str]) -> Result<(), AppError> {
+    app.state::<AppState>().store.save(settings)?;
+    notify_changed(app, changed.iter().map(|key| key.to_string()).collect(), settings);
+    Ok(())
+}
+
+// 修改设置后通知所有窗口
+pub fn notify_changed(app: &AppHandle, changed: Vec<String>, settings: &Settings) {
+    let payload = SettingsChanged {
+        changed,
+        settings: settings.clone(),
+    };
+    events::publish(app, AppEvent::SettingsChanged(Box::new(payload)));
+}
+
+// 日志级别、音量、快捷键、网络和后端配置需要立即生效，其他设置在下次使用时读取
+fn apply_changes(app: &AppHandle, before: &Settings, after: &Settings) {
+    if before.log_level != after.log_level {
+        if let Err(e) = logging::set_level(&after.log_level) {
+            warn!("Failed to apply log level: {}", e);
+        }
+    }
+    if before.audio.playback.volume != after.audio.playback.volume {
+        app.state::<AudioPlayer>().set_volume(after.audio.playback.volume);
+    }
+    if before.hotkeys != after.hotkeys {
+        hotkeys::rebind(app, &before.hotkeys);
+    }
+    if before.language != after.language {
+        i18n::apply(app);
+    }
+    if before.captions != after.captions {
+        captions::apply(app, &after.captions);
+    }
+    if before.macos != after.macos {
+        macos::apply(app, &after.macos);
+    }
+    if before.dnd != after.dnd {
+        dnd::refresh(app);
+    }
+    if before.mqtt != after.mqtt {
+        mqtt::apply(app);
+    }
+    if before.local_api != after.local_api {
+        local_api::apply(app);
+    }
+    if before.retention != after.retention {
+        retention::apply(app);
+    }
+    if before.power != after.power {
+        power::apply(app);
+    }
+    if before.network != after.network {
+        let app = app.clone();
+        let network = after.network.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = app.state::<Network>().configure(&network).await {
+                warn!("Failed to apply network settings: {}", e);
+            }
+        });
+    }
+    if before.backend != after.backend {
+        // 重启需要等待旧进程退出，放到后台线程
+        let app = app.clone();
+        let backend_settings = after.backend.clone();
+        std::thread::spawn(move || {
+            let backend = app.state::<BackendManager>();
+            let restarted = BackendLaunch::resolve(&app, &backend_settings)
+                .and_then(|launch| backend.configure(app.clone(), launch))
+                .and_then(|_| backend.restart());
+            match restarted {
+                Ok(pid) => info!("Backend restarted with new settings (pid {})", pid),
+                Err(e) => warn!("Failed to restart backend: {}", e),
+            }
+        });
+    }
+}
+
+#[tauri::command]
+pub fn get_settings(state: State<'_, AppState>) -> Result<Settings, AppError> {
+    Ok(state.settings.lock()?.clone())
+}
+
+#[tauri::command]
+pub fn update_settings(app: AppHandle, patch: serde_json::Value) -> Result<Settings, AppError> {
+    let state = app.state::<AppState>();
+    let (before, after, changed) = {
+        let mut settings = state.settings.lock()?;
+        let (updated, changed) = apply_patch(&settings, patch)?;
+        let before = std::mem::replace(&mut *settings, updated.clone());
+        (before, updated, changed)
+    };
+    if changed.is_empty() {
+        return Ok(after);
+    }
+
+    info!("Settings updated: {}", changed.join(", "));
+    apply_changes(&app, &before, &after);
+    state.store.schedule_save(&app);
+    notify_changed(&app, changed, &after);
+    Ok(after)
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PetSettings {
+    // 上次关闭时的窗口位置，None 表示使用默认的右下角
+    pub position: Option<PetPlacement>,
+    // 点击穿透：鼠标事件直接穿过桌宠窗口
+    pub click_through: bool,
+    pub size: PetSize,
+    pub idle: IdleSettings,
+}
+
+// 桌宠窗口尺寸预设，custom 为自定义逻辑像素尺寸
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum PetSize {
+    Small,
+    #[default]
+    Medium,
+    Large,
+    Custom { width: f64, height: f64 },
+}
+
+// 物理像素坐标，以及窗口所在显示器的名称
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PetPlacement {
+    pub x: i32,
+    pub y: i32,
+    pub monitor: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AudioSettings {
+    pub vad: VadConfig,
+    pub wake_word: WakeWordConfig,
+    pub playback: PlaybackSettings,
+    pub tts: TtsStreamSettings,
+    pub processing: ProcessingSettings,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct KnowledgeSettings {
+    pub embedding: EmbeddingSettings,
+}
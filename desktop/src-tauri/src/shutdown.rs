@@ -0,0 +1,130 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::audio::{self, AudioPlayer, WakeWordListener};
+use crate::backend::BackendManager;
+use crate::llm::{Generations, LlamaServer};
+use crate::local_api::LocalApi;
+use crate::mqtt::MqttClient;
+use crate::plugins::Plugins;
+use crate::window_state::{self, MAIN_LABEL};
+use crate::ws_bridge::WsBridge;
+use crate::{logging, pipeline, AppState};
+
+// 退出和重启都经过这里：先停止正在进行的对话和音频，保存状态，再结束子进程并合并数据库 WAL；
+// 任何一步卡住时，超时后强制退出，避免残留在托盘中
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Exit(i32),
+    Restart,
+}
+
+#[derive(Default)]
+pub struct Shutdown {
+    started: AtomicBool,
+    // 清理完成后 RunEvent::Exit 不再重复执行
+    cleaned: AtomicBool,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// 停止对话、音频和所有后台服务，清除数据前也会调用；可以重复调用
+pub fn stop_services(app: &AppHandle) {
+    if let Err(e) = pipeline::cancel(app) {
+        warn!("Failed to cancel pipeline turn: {}", e);
+    }
+    app.state::<Generations>().cancel_all();
+    if let Err(e) = audio::capture::finish_recording(app) {
+        warn!("Failed to stop recording: {}", e);
+    }
+    app.state::<WakeWordListener>().stop();
+    app.state::<AudioPlayer>().stop();
+
+    app.state::<WsBridge>().disconnect();
+    app.state::<MqttClient>().stop();
+    app.state::<LocalApi>().stop();
+    if let Some(plugins) = app.try_state::<Plugins>() {
+        plugins.stop_all();
+    }
+    if let Err(e) = app.state::<BackendManager>().stop() {
+        warn!("Failed to stop backend: {}", e);
+    }
+    app.state::<LlamaServer>().stop();
+}
+
+fn cleanup(app: &AppHandle) {
+    stop_services(app);
+    if let Some(window) = app.get_window(MAIN_LABEL) {
+        window_state::save(&window);
+    }
+    if let Some(state) = app.try_state::<AppState>() {
+        if let Err(e) = state.store.flush(&state.settings) {
+            warn!("Failed to save settings: {}", e);
+        }
+        if let Err(e) = state.storage.checkpoint() {
+            warn!("Failed to checkpoint database: {}", e);
+        }
+    }
+    app.state::<Shutdown>().cleaned.store(true, Ordering::SeqCst);
+}
+
+fn finish(app: &AppHandle, action: Action) {
+    match action {
+        Action::Exit(code) => app.exit(code),
+        Action::Restart => app.restart(),
+    }
+}
+
+fn run(app: &AppHandle, action: Action) {
+    if app.state::<Shutdown>().started.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    info!("Shutting down ({:?})", action);
+
+    // 超时后跳过剩余步骤，Tauri 的退出流程也可能卡住，直接结束进程
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(SHUTDOWN_TIMEOUT);
+        warn!("Shutdown timed out after {:?}, forcing exit", SHUTDOWN_TIMEOUT);
+        logging::shutdown();
+        match action {
+            Action::Exit(code) => std::process::exit(code),
+            Action::Restart => tauri::api::process::restart(&handle.env()),
+        }
+    });
+
+    // 清理过程中会读取窗口位置，需要主线程空闲，放到独立线程执行
+    let handle = app.clone();
+    std::thread::spawn(move || {
+        cleanup(&handle);
+        info!("Shutdown cleanup finished");
+        finish(&handle, action);
+    });
+}
+
+pub fn exit(app: &AppHandle, code: i32) {
+    run(app, Action::Exit(code));
+}
+
+pub fn restart(app: &AppHandle) {
+    run(app, Action::Restart);
+}
+
+// RunEvent::Exit 中调用：系统注销等没有经过 exit 的退出也执行一次清理
+pub fn on_exit(app: &AppHandle) {
+    let shutdown = app.state::<Shutdown>();
+    if !shutdown.cleaned.load(Ordering::SeqCst) {
+        shutdown.started.store(true, Ordering::SeqCst);
+        cleanup(app);
+    }
+    logging::shutdown();
+}
@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream};
+use std::time::Duration;
+use tauri::AppHandle;
+use tracing::{info, warn};
+
+use crate::cli::{self, CliArgs};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::profiles;
+use crate::tray;
+
+// 首个实例在本地回环端口上监听，后续实例通过该端口转发启动参数
+const INSTANCE_PORT: u16 = 47072;
+const MAGIC: &str = "lingecho-instance";
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceMessage {
+    magic: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+pub enum Instance {
+    Primary(TcpListener),
+    // 已有实例在运行，参数已转发
+    Secondary,
+    // 端口被其他程序占用，无法保证单实例，按普通方式启动
+    Unguarded,
+}
+
+pub fn acquire() -> Instance {
+    match TcpListener::bind((Ipv4Addr::LOCALHOST, INSTANCE_PORT)) {
+        Ok(listener) => Instance::Primary(listener),
+        Err(_) => match forward_to_primary() {
+            Ok(()) => Instance::Secondary,
+            Err(e) => {
+                warn!("Single-instance port {} is unavailable: {}", INSTANCE_PORT, e);
+                Instance::Unguarded
+            }
+        },
+    }
+}
+
+fn forward_to_primary() -> Result<(), AppError> {
+    let addr = (Ipv4Addr::LOCALHOST, INSTANCE_PORT).into();
+    let mut stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT)).ok();
+
+    let message = InstanceMessage {
+        magic: MAGIC.to_string(),
+        args: std::env::args().skip(1).collect(),
+        cwd: std::env::current_dir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+    let mut line = serde_json::to_string(&message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    // 只有收到确认才说明对端是本应用，否则视为端口被其他程序占用
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    if reply.trim() != MAGIC {
+        return Err(AppError::Internal("unexpected reply from instance port".to_string()));
+    }
+    Ok(())
+}
+
+pub fn listen(app: AppHandle, listener: TcpListener) {
+    std::thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(stream) = stream else {
+                continue;
+            };
+            if let Err(e) = handle_connection(&app, stream) {
+                warn!("Ignoring instance message: {}", e);
+            }
+        }
+    });
+}
+
+fn handle_connection(app: &AppHandle, mut stream: TcpStream) -> Result<(), AppError> {
+    stream.set_read_timeout(Some(CONNECT_TIMEOUT)).ok();
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let message: InstanceMessage = serde_json::from_str(&line)?;
+    if message.magic != MAGIC {
+        return Err(AppError::invalid("bad magic"));
+    }
+    stream.write_all(format!("{}\n", MAGIC).as_bytes())?;
+
+    info!("Second instance launched with args {:?}", message.args);
+    // 带有动作参数（例如链接、--ask）时由动作决定打开的窗口
+    let handled = match CliArgs::from_forwarded(&message.args, &message.cwd) {
+        // 指定了其他配置时切换并重启，其余参数不再执行
+        Ok(CliArgs {
+            profile: Some(profile), ..
+        }) if profile.trim() != profiles::current() => match profiles::switch(app, &profile) {
+            Ok(_) => true,
+            Err(e) => {
+                warn!("Failed to switch profile: {}", e);
+                false
+            }
+        },
+        Ok(args) => cli::run(app, &args),
+        Err(e) => {
+            warn!("Invalid forwarded arguments: {}", e);
+            false
+        }
+    };
+    if !handled {
+        tray::show_main_window(app);
+    }
+    events::publish(app, AppEvent::SecondInstance(message));
+    Ok(())
+}
@@ -0,0 +1,953 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::crypto::{self, FieldCipher};
+use crate::error::AppError;
+use crate::{file_search, i18n, llm, retention, secrets, AppState};
+
+pub const DATABASE_FILE: &str = "lingecho.db";
+// 旧版本由前端写入的历史记录文件，首次打开数据库时迁移
+const LEGACY_HISTORY_FILE: &str = "history.json";
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+const TITLE_CHARS: usize = 30;
+// meta 表中记录加密方式的键
+const ENCRYPTION_KEY: &str = "encryption";
+const ENCRYPTION_METHOD: &str = "aes-256-gcm";
+// 缺少密钥时代替无法解密的内容显示
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS conversations (
+    id TEXT PRIMARY KEY,
+    title TEXT NOT NULL DEFAULT '',
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE TABLE IF NOT EXISTS messages (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    role TEXT NOT NULL,
+    text TEXT NOT NULL,
+    audio_path TEXT,
+    created_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_messages_conversation ON messages(conversation_id, created_at);
+CREATE TABLE IF NOT EXISTS meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS memories (
+    id TEXT PRIMARY KEY,
+    conversation_id TEXT NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+    summary TEXT NOT NULL,
+    first_message_at INTEGER NOT NULL,
+    last_message_at INTEGER NOT NULL,
+    message_count INTEGER NOT NULL,
+    created_at INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+CREATE INDEX IF NOT EXISTS idx_memories_conversation ON memories(conversation_id, last_message_at);
+CREATE TABLE IF NOT EXISTS memory_progress (
+    conversation_id TEXT PRIMARY KEY REFERENCES conversations(id) ON DELETE CASCADE,
+    summarized_until INTEGER NOT NULL
+);
+";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    User,
+    Assistant,
+    System,
+}
+
+impl Role {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+            Role::System => "system",
+        }
+    }
+
+    fn parse(value: &str) -> Role {
+        match value {
+            "user" => Role::User,
+            "system" => Role::System,
+            _ => Role::Assistant,
+        }
+    }
+}
+
+// 时间戳均为毫秒
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Message {
+    pub id: String,
+    pub conversation_id: String,
+    pub role: Role,
+    pub text: String,
+    pub audio_path: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationSummary {
+    #[serde(flatten)]
+    pub conversation: Conversation,
+    pub message_count: u32,
+    pub last_message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ConversationPage {
+    pub items: Vec<ConversationSummary>,
+    pub total: u32,
+    pub page: u32,
+    pub page_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageHit {
+    #[serde(flatten)]
+    pub message: Message,
+    pub conversation_title: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DatabaseEncryption {
+    pub enabled: bool,
+    // 已加密但无法从钥匙串读取密钥时为 false
+    pub key_available: bool,
+}
+
+// 一段较早对话的摘要；first_message_at 到 last_message_at 为覆盖的消息时间范围
+#[derive(Debug, Clone, Serialize)]
+pub struct Memory {
+    pub id: String,
+    pub conversation_id: String,
+    pub summary: String,
+    pub first_message_at: i64,
+    pub last_message_at: i64,
+    pub message_count: u32,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+// 导出包中的一条历史记录：会话及其全部消息
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ConversationRecord {
+    #[serde(flatten)]
+    pub conversation: Conversation,
+    #[serde(default)]
+    pub messages: Vec<Message>,
+}
+
+// 清理历史时要删除的内容
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PurgeScope {
+    pub messages: bool,
+    pub audio: bool,
+    pub memories: bool,
+}
+
+// audio_paths 为被删除或解除关联的录音文件，由调用方删除文件
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PurgeReport {
+    pub conversations: usize,
+    pub messages: usize,
+    pub memories: usize,
+    pub audio_files: usize,
+    #[serde(skip)]
+    pub audio_paths: Vec<String>,
+}
+
+impl PurgeReport {
+    pub fn is_empty(&self) -> bool {
+        self.conversations == 0 && self.messages == 0 && self.memories == 0 && self.audio_files == 0
+    }
+}
+
+pub fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+fn message_from_row(row: &rusqlite::Row) -> rusqlite::Result<Message> {
+    Ok(Message {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        role: Role::parse(&row.get::<_, String>(2)?),
+        text: row.get(3)?,
+        audio_path: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+const MEMORY_COLUMNS: &str =
+    "id, conversation_id, summary, first_message_at, last_message_at, message_count, created_at, updated_at";
+
+fn memory_from_row(row: &rusqlite::Row) -> rusqlite::Result<Memory> {
+    Ok(Memory {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        summary: row.get(2)?,
+        first_message_at: row.get(3)?,
+        last_message_at: row.get(4)?,
+        message_count: row.get(5)?,
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+fn conversation_from_row(row: &rusqlite::Row) -> rusqlite::Result<Conversation> {
+    Ok(Conversation {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        created_at: row.get(2)?,
+        updated_at: row.get(3)?,
+    })
+}
+
+// LIKE 查询中转义通配符
+pub fn like_pattern(query: &str) -> String {
+    let escaped = query.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+// 应用数据目录下的 SQLite 数据库，保存对话历史。
+// 启用加密后消息正文和会话标题逐字段加密，密钥保存在系统钥匙串中
+pub struct Storage {
+    conn: Mutex<Connection>,
+    encrypted: AtomicBool,
+    cipher: Mutex<Option<FieldCipher>>,
+}
+
+impl Storage {
+    pub fn open(data_dir: &Path) -> Result<Self, AppError> {
+        std::fs::create_dir_all(data_dir)?;
+        let conn = Connection::open(data_dir.join(DATABASE_FILE))?;
+        conn.execute_batch("PRAGMA journal_mode = WAL; PRAGMA foreign_keys = ON;")?;
+        conn.execute_batch(SCHEMA)?;
+
+        let method: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1",
+                params![ENCRYPTION_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let encrypted = method.as_deref() == Some(ENCRYPTION_METHOD);
+        let mut cipher = None;
+        if encrypted {
+            // 删除的明文不残留在空闲页中
+            conn.execute_batch("PRAGMA secure_delete = ON;")?;
+            match secrets::get(secrets::DATABASE_KEY).and_then(|key| match key {
+                Some(key) => FieldCipher::from_key(&key),
+                None => Err(AppError::NotFound("钥匙串中没有数据库密钥".to_string())),
+            }) {
+                Ok(loaded) => cipher = Some(loaded),
+                // 仍然打开数据库，加密的内容显示为占位文本
+                Err(e) => warn!("Database is encrypted but the key is unavailable: {}", e),
+            }
+        }
+
+        let storage = Self {
+            conn: Mutex::new(conn),
+            encrypted: AtomicBool::new(encrypted),
+            cipher: Mutex::new(cipher),
+        };
+        storage.migrate_legacy_history(&data_dir.join(LEGACY_HISTORY_FILE));
+        Ok(storage)
+    }
+
+    fn is_encrypted(&self) -> bool {
+        self.encrypted.load(Ordering::SeqCst)
+    }
+
+    fn cipher(&self) -> Result<Option<FieldCipher>, AppError> {
+        Ok(self.cipher.lock()?.clone())
+    }
+
+    // 写入前加密；启用了加密但没有密钥时拒绝写入，避免混入明文
+    fn seal(&self, cipher: &Option<FieldCipher>, text: &str) -> Result<String, AppError> {
+        match cipher {
+            Some(cipher) => cipher.encrypt(text),
+            None if self.is_encrypted() => Err(AppError::unavailable(
+                "无法写入加密的对话历史",
+                "系统钥匙串中没有数据库密钥",
+            )),
+            None => Ok(text.to_string()),
+        }
+    }
+
+    fn reveal(cipher: &Option<FieldCipher>, text: String) -> String {
+        if !crypto::is_encrypted_field(&text) {
+            return text;
+        }
+        match cipher.as_ref().map(|cipher| cipher.decrypt(&text)) {
+            Some(Ok(plain)) => plain,
+            Some(Err(e)) => {
+                warn!("Failed to decrypt stored text: {}", e);
+                i18n::t("storage.unreadable")
+            }
+            None => i18n::t("storage.unreadable"),
+        }
+    }
+
+    fn reveal_message(cipher: &Option<FieldCipher>, mut message: Message) -> Message {
+        message.text = Self::reveal(cipher, message.text);
+        message
+    }
+
+    fn reveal_memory(cipher: &Option<FieldCipher>, mut memory: Memory) -> Memory {
+        memory.summary = Self::reveal(cipher, memory.summary);
+        memory
+    }
+
+    fn reveal_conversation(cipher: &Option<FieldCipher>, mut conversation: Conversation) -> Conversation {
+        conversation.title = Self::reveal(cipher, conversation.title);
+        conversation
+    }
+
+    fn migrate_legacy_history(&self, path: &Path) {
+        let Ok(content) = std::fs::read_to_string(path) else {
+            return;
+        };
+        let items: Vec<serde_json::Value> = serde_json::from_str(&content).unwrap_or_default();
+        let mut migrated = 0;
+        for item in items {
+            if self.import_record(item).unwrap_or(false) {
+                migrated += 1;
+            }
+        }
+        std::fs::rename(path, path.with_extension("json.migrated")).ok();
+        info!("Migrated {} conversations from {}", migrated, path.display());
+    }
+
+    fn conn(&self) -> Result<std::sync::MutexGuard<'_, Connection>, AppError> {
+        self.conn.lock().map_err(AppError::from)
+    }
+
+    // 会话不存在时自动创建，标题取第一条消息的开头
+    pub fn save_message(
+        &self,
+        conversation_id: Option<String>,
+        role: Role,
+        text: String,
+        audio_path: Option<String>,
+    ) -> Result<Message, AppError> {
+        let now = now_millis();
+        let conversation_id = conversation_id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let message = Message {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id,
+            role,
+            text,
+            audio_path,
+            created_at: now,
+        };
+        let title: String = message.text.trim().chars().take(TITLE_CHARS).collect();
+        // 持有连接锁后再取密钥，与 encrypt_existing 互斥，避免加密过程中写入明文
+        let mut conn = self.conn()?;
+        let cipher = self.cipher()?;
+        let title = self.seal(&cipher, &title)?;
+        let text = self.seal(&cipher, &message.text)?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?3)
+             ON CONFLICT(id) DO UPDATE SET updated_at = excluded.updated_at,
+                 title = CASE WHEN title = '' THEN excluded.title ELSE title END",
+            params![message.conversation_id, title, now],
+        )?;
+        tx.execute(
+            "INSERT INTO messages (id, conversation_id, role, text, audio_path, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                message.id,
+                message.conversation_id,
+                message.role.as_str(),
+                text,
+                message.audio_path,
+                message.created_at
+            ],
+        )?;
+        tx.commit()?;
+        Ok(message)
+    }
+
+    pub fn list_conversations(&self, page: u32, page_size: u32) -> Result<ConversationPage, AppError> {
+        let page = page.max(1);
+        let page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        let offset = (page - 1)
+            .checked_mul(page_size)
+            .ok_or_else(|| AppError::invalid("页码超出范围"))?;
+        let cipher = self.cipher()?;
+        let conn = self.conn()?;
+
+        let total: u32 = conn.query_row("SELECT COUNT(*) FROM conversations", [], |row| row.get(0))?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id, c.title, c.created_at, c.updated_at,
+                        (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id),
+                        (SELECT m.text FROM messages m WHERE m.conversation_id = c.id
+                         ORDER BY m.created_at DESC LIMIT 1)
+                 FROM conversations c
+                 ORDER BY c.updated_at DESC
+                 LIMIT ?1 OFFSET ?2",
+        )?;
+        let items = stmt
+            .query_map(params![page_size, offset], |row| {
+                Ok(ConversationSummary {
+                    conversation: Self::reveal_conversation(&cipher, conversation_from_row(row)?),
+                    message_count: row.get(4)?,
+                    last_message: row.get::<_, Option<String>>(5)?.map(|text| Self::reveal(&cipher, text)),
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())?;
+
+        Ok(ConversationPage {
+            items,
+            total,
+            page,
+            page_size,
+        })
+    }
+
+    pub fn list_messages(&self, conversation_id: &str) -> Result<Vec<Message>, AppError> {
+        let cipher = self.cipher()?;
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, conversation_id, role, text, audio_path, created_at
+                 FROM messages WHERE conversation_id = ?1 ORDER BY created_at, rowid",
+        )?;
+        let messages = stmt
+            .query_map(params![conversation_id], |row| {
+                Ok(Self::reveal_message(&cipher, message_from_row(row)?))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())?;
+        Ok(messages)
+    }
+
+    pub fn search_messages(&self, query: &str, limit: u32) -> Result<Vec<MessageHit>, AppError> {
+        if self.is_encrypted() {
+            return self.search_encrypted(query, limit);
+        }
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.conversation_id, m.role, m.text, m.audio_path, m.created_at, c.title
+                 FROM messages m JOIN conversations c ON c.id = m.conversation_id
+                 WHERE m.text LIKE ?1 ESCAPE '\\'
+                 ORDER BY m.created_at DESC
+                 LIMIT ?2",
+        )?;
+        let hits = stmt
+            .query_map(params![like_pattern(query), limit.clamp(1, MAX_PAGE_SIZE)], |row| {
+                Ok(MessageHit {
+                    message: message_from_row(row)?,
+                    conversation_title: row.get(6)?,
+                })
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())?;
+        Ok(hits)
+    }
+
+    // 密文无法用 LIKE 匹配，逐条解密后在内存中查找
+    fn search_encrypted(&self, query: &str, limit: u32) -> Result<Vec<MessageHit>, AppError> {
+        let cipher = self.cipher()?;
+        let query = query.to_lowercase();
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT m.id, m.conversation_id, m.role, m.text, m.audio_path, m.created_at, c.title
+                 FROM messages m JOIN conversations c ON c.id = m.conversation_id
+                 ORDER BY m.created_at DESC",
+        )?;
+        let mut rows = stmt.query([])?;
+        let mut hits = Vec::new();
+        while let Some(row) = rows.next()? {
+            let message = Self::reveal_message(&cipher, message_from_row(row)?);
+            if !message.text.to_lowercase().contains(&query) {
+                continue;
+            }
+            hits.push(MessageHit {
+                message,
+                conversation_title: Self::reveal(&cipher, row.get(6)?),
+            });
+            if hits.len() >= limit.clamp(1, MAX_PAGE_SIZE) as usize {
+                break;
+            }
+        }
+        Ok(hits)
+    }
+
+    // 尚未摘要的消息数达到 threshold 的会话
+    pub fn conversations_to_summarize(&self, threshold: u32) -> Result<Vec<String>, AppError> {
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT c.id FROM conversations c LEFT JOIN memory_progress p ON p.conversation_id = c.id
+                 WHERE (SELECT COUNT(*) FROM messages m
+                        WHERE m.conversation_id = c.id AND m.created_at > COALESCE(p.summarized_until, 0)) >= ?1
+                 ORDER BY c.updated_at",
+        )?;
+        let ids = stmt
+            .query_map(params![threshold], |row| row.get(0))
+            .and_then(|rows| rows.collect::<Result<Vec<String>, _>>())?;
+        Ok(ids)
+    }
+
+    // 还没有被摘要覆盖的消息，按时间顺序
+    pub fn unsummarized_messages(&self, conversation_id: &str) -> Result<Vec<Message>, AppError> {
+        let cipher = self.cipher()?;
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(
+            "SELECT id, conversation_id, role, text, audio_path, created_at FROM messages
+                 WHERE conversation_id = ?1 AND created_at > COALESCE(
+                     (SELECT summarized_until FROM memory_progress WHERE conversation_id = ?1), 0)
+                 ORDER BY created_at, rowid",
+        )?;
+        let messages = stmt
+            .query_map(params![conversation_id], |row| {
+                Ok(Self::reveal_message(&cipher, message_from_row(row)?))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())?;
+        Ok(messages)
+    }
+
+    // 保存摘要并把这些消息标记为已摘要；之后删除摘要也不会重新摘要这些消息
+    pub fn save_memory(&self, conversation_id: &str, summary: &str, messages: &[Message]) -> Result<Memory, AppError> {
+        let (Some(first), Some(last)) = (messages.first(), messages.last()) else {
+            return Err(AppError::invalid("没有需要摘要的消息"));
+        };
+        let now = now_millis();
+        let memory = Memory {
+            id: uuid::Uuid::new_v4().to_string(),
+            conversation_id: conversation_id.to_string(),
+            summary: summary.to_string(),
+            first_message_at: first.created_at,
+            last_message_at: last.created_at,
+            message_count: messages.len() as u32,
+            created_at: now,
+            updated_at: now,
+        };
+        let mut conn = self.conn()?;
+        let cipher = self.cipher()?;
+        let sealed = self.seal(&cipher, &memory.summary)?;
+        let tx = conn.transaction()?;
+        tx.execute(
+            &format!(
+                "INSERT INTO memories ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                MEMORY_COLUMNS
+            ),
+            params![
+                memory.id,
+                memory.conversation_id,
+                sealed,
+                memory.first_message_at,
+                memory.last_message_at,
+                memory.message_count,
+                memory.created_at,
+                memory.updated_at
+            ],
+        )?;
+        tx.execute(
+            "INSERT INTO memory_progress (conversation_id, summarized_until) VALUES (?1, ?2)
+             ON CONFLICT(conversation_id) DO UPDATE SET
+                 summarized_until = MAX(summarized_until, excluded.summarized_until)",
+            params![memory.conversation_id, memory.last_message_at],
+        )?;
+        tx.commit()?;
+        Ok(memory)
+    }
+
+    // 未指定会话时返回全部摘要，按会话和时间顺序
+    pub fn list_memories(&self, conversation_id: Option<&str>) -> Result<Vec<Memory>, AppError> {
+        let cipher = self.cipher()?;
+        let conn = self.conn()?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM memories WHERE ?1 IS NULL OR conversation_id = ?1
+                 ORDER BY conversation_id, last_message_at",
+            MEMORY_COLUMNS
+        ))?;
+        let memories = stmt
+            .query_map(params![conversation_id], |row| {
+                Ok(Self::reveal_memory(&cipher, memory_from_row(row)?))
+            })
+            .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())?;
+        Ok(memories)
+    }
+
+    pub fn update_memory(&self, id: &str, summary: &str) -> Result<Memory, AppError> {
+        let conn = self.conn()?;
+        let cipher = self.cipher()?;
+        let sealed = self.seal(&cipher, summary)?;
+        let updated = conn.execute(
+            "UPDATE memories SET summary = ?1, updated_at = ?2 WHERE id = ?3",
+            params![sealed, now_millis(), id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::NotFound(format!("记忆不存在: {}", id)));
+        }
+        let memory = conn.query_row(
+            &format!("SELECT {} FROM memories WHERE id = ?1", MEMORY_COLUMNS),
+            params![id],
+            memory_from_row,
+        )?;
+        Ok(Self::reveal_memory(&cipher, memory))
+    }
+
+    pub fn delete_memory(&self, id: &str) -> Result<bool, AppError> {
+        let deleted = self
+            .conn()?
+            .execute("DELETE FROM memories WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    pub fn delete_conversation(&self, id: &str) -> Result<bool, AppError> {
+        let deleted = self
+            .conn()?
+            .execute("DELETE FROM conversations WHERE id = ?1", params![id])?;
+        Ok(deleted > 0)
+    }
+
+    pub fn conversation_exists(&self, id: &str) -> Result<bool, AppError> {
+        let found = self
+            .conn()?
+            .query_row("SELECT 1 FROM conversations WHERE id = ?1", params![id], |_| Ok(()))
+            .optional()?;
+        Ok(found.is_some())
+    }
+
+    // 导出全部会话，每个会话连同消息序列化为一个 JSON 对象
+    pub fn export_history(&self) -> Result<Vec<serde_json::Value>, AppError> {
+        let cipher = self.cipher()?;
+        let conversations = {
+            let conn = self.conn()?;
+            let mut stmt =
+                conn.prepare("SELECT id, title, created_at, updated_at FROM conversations ORDER BY created_at")?;
+            let rows = stmt
+                .query_map([], conversation_from_row)
+                .and_then(|rows| rows.collect::<Result<Vec<_>, _>>());
+            rows?
+        };
+
+        let mut records = Vec::with_capacity(conversations.len());
+        for conversation in conversations {
+            let conversation = Self::reveal_conversation(&cipher, conversation);
+            let messages = self.list_messages(&conversation.id)?;
+            let record = ConversationRecord { conversation, messages };
+            records.push(serde_json::to_value(record)?);
+        }
+        Ok(records)
+    }
+
+    // 导入一条导出记录，格式不符或会话已存在时返回 false
+    pub fn import_record(&self, item: serde_json::Value) -> Result<bool, AppError> {
+        let Ok(record) = serde_json::from_value::<ConversationRecord>(item) else {
+            return Ok(false);
+        };
+
+        let mut conn = self.conn()?;
+        let cipher = self.cipher()?;
+        let tx = conn.transaction()?;
+        let exists = tx
+            .query_row(
+                "SELECT 1 FROM conversations WHERE id = ?1",
+                params![record.conversation.id],
+                |_| Ok(()),
+            )
+            .optional()?
+            .is_some();
+        if exists {
+            return Ok(false);
+        }
+
+        let conversation = &record.conversation;
+        tx.execute(
+            "INSERT INTO conversations (id, title, created_at, updated_at) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                conversation.id,
+                self.seal(&cipher, &conversation.title)?,
+                conversation.created_at,
+                conversation.updated_at
+            ],
+        )?;
+        for message in &record.messages {
+            tx.execute(
+                "INSERT OR IGNORE INTO messages (id, conversation_id, role, text, audio_path, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    message.id,
+                    conversation.id,
+                    message.role.as_str(),
+                    self.seal(&cipher, &message.text)?,
+                    message.audio_path,
+                    message.created_at
+                ],
+            )?;
+        }
+        tx.commit()?;
+        Ok(true)
+    }
+
+    // 删除 before 之前没有更新过的会话，以及最近 keep 个之外的会话；keep 为 0 表示不限制
+    pub fn prune_conversations(&self, before: Option<i64>, keep: u32) -> Result<PurgeReport, AppError> {
+        const EXPIRED: &str = "SELECT id FROM conversations WHERE ?1 IS NOT NULL AND updated_at < ?1
+             UNION SELECT id FROM (SELECT id FROM conversations ORDER BY updated_at DESC LIMIT -1 OFFSET ?2)
+             WHERE ?2 > 0";
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        let audio_paths = {
+            let mut stmt = tx.prepare(&format!(
+                "SELECT audio_path FROM messages WHERE audio_path IS NOT NULL AND conversation_id IN ({})",
+                EXPIRED
+            ))?;
+            let rows = stmt.query_map(params![before, keep], |row| row.get(0))?;
+            rows.collect::<Result<Vec<String>, _>>()?
+        };
+        let count = |table: &str| -> Result<usize, AppError> {
+            Ok(tx.query_row(
+                &format!("SELECT COUNT(*) FROM {} WHERE conversation_id IN ({})", table, EXPIRED),
+                params![before, keep],
+                |row| row.get(0),
+            )?)
+        };
+        let messages = count("messages")?;
+        let memories = count("memories")?;
+        let conversations = tx.execute(
+            &format!("DELETE FROM conversations WHERE id IN ({})", EXPIRED),
+            params![before, keep],
+        )?;
+        tx.commit()?;
+        Ok(PurgeReport {
+            conversations,
+            messages,
+            memories,
+            audio_files: audio_paths.len(),
+            audio_paths,
+        })
+    }
+
+    // from 和 to 为消息的时间范围（含两端），为空表示不限
+    pub fn purge(&self, from: Option<i64>, to: Option<i64>, scope: PurgeScope) -> Result<PurgeReport, AppError> {
+        let (from, to) = (from.unwrap_or(i64::MIN), to.unwrap_or(i64::MAX));
+        let range = params![from, to];
+        let mut report = PurgeReport::default();
+        let mut conn = self.conn()?;
+        let tx = conn.transaction()?;
+        if scope.messages || scope.audio {
+            let mut stmt = tx.prepare(
+                "SELECT audio_path FROM messages WHERE audio_path IS NOT NULL AND created_at BETWEEN ?1 AND ?2",
+            )?;
+            let rows = stmt.query_map(range, |row| row.get(0))?;
+            report.audio_paths = rows.collect::<Result<Vec<String>, _>>()?;
+        }
+        if scope.messages {
+            let touched = {
+                let mut stmt =
+                    tx.prepare("SELECT DISTINCT conversation_id FROM messages WHERE created_at BETWEEN ?1 AND ?2")?;
+                let rows = stmt.query_map(range, |row| row.get(0))?;
+                rows.collect::<Result<Vec<String>, _>>()?
+            };
+            report.messages = tx.execute("DELETE FROM messages WHERE created_at BETWEEN ?1 AND ?2", range)?;
+            for id in touched {
+                report.conversations += tx.execute(
+                    "DELETE FROM conversations WHERE id = ?1
+                     AND NOT EXISTS (SELECT 1 FROM messages WHERE conversation_id = ?1)",
+                    params![id],
+                )?;
+            }
+        } else if scope.audio {
+            tx.execute(
+                "UPDATE messages SET audio_path = NULL WHERE audio_path IS NOT NULL AND created_at BETWEEN ?1 AND ?2",
+                range,
+            )?;
+        }
+        // 摘要包含原消息的内容，删除消息时一并删除覆盖该时间段的摘要
+        if scope.messages || scope.memories {
+            report.memories += tx.execute(
+                "DELETE FROM memories WHERE first_message_at <= ?2 AND last_message_at >= ?1",
+                range,
+            )?;
+        }
+        tx.commit()?;
+        report.audio_files = report.audio_paths.len();
+        Ok(report)
+    }
+
+    // 重写数据库文件并清空 WAL，删除的内容不残留在空闲页中
+    pub fn compact(&self) -> Result<(), AppError> {
+        self.conn()?
+            .execute_batch("VACUUM; PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(AppError::from)
+    }
+
+    // 把 WAL 中的内容合并回数据库文件，退出前调用
+    pub fn checkpoint(&self) -> Result<(), AppError> {
+        self.conn()?
+            .execute_batch("PRAGMA wal_checkpoint(TRUNCATE);")
+            .map_err(AppError::from)
+    }
+
+    pub fn clear_history(&self) -> Result<(), AppError> {
+        self.conn()?
+            .execute("DELETE FROM conversations", [])
+            .map(|_| ())
+            .map_err(AppError::from)
+    }
+
+    pub fn encryption(&self) -> Result<DatabaseEncryption, AppError> {
+        Ok(DatabaseEncryption {
+            enabled: self.is_encrypted(),
+            key_available: self.cipher()?.is_some(),
+        })
+    }
+
+    // 原地加密现有的明文记录，返回加密的字段数；已加密的字段跳过，可以重复执行
+    pub fn encrypt_existing(&self) -> Result<usize, AppError> {
+        // 整个过程持有连接锁，写入方在同一把锁下取密钥，不会在加密期间写入明文
+        let mut conn = self.conn()?;
+        let cipher = match self.cipher()? {
+            Some(cipher) => cipher,
+            None if self.is_encrypted() => {
+                return Err(AppError::unavailable("无法加密对话历史", "系统钥匙串中没有数据库密钥"))
+            }
+            None => {
+                // 先把密钥写入钥匙串并读回确认，再开始改写数据
+                secrets::store(secrets::DATABASE_KEY, &FieldCipher::generate_key())?;
+                let key = secrets::get(secrets::DATABASE_KEY)?.ok_or("写入钥匙串后无法读回数据库密钥")?;
+                FieldCipher::from_key(&key)?
+            }
+        };
+
+        let tx = conn.transaction()?;
+        let mut encrypted = 0;
+        for (table, column) in [
+            ("messages", "text"),
+            ("conversations", "title"),
+            ("memories", "summary"),
+        ] {
+            let rows: Vec<(String, String)> = {
+                let mut stmt = tx.prepare(&format!("SELECT id, {} FROM {}", column, table))?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<Result<Vec<_>, _>>()?
+            };
+            for (id, value) in rows {
+                if value.is_empty() || crypto::is_encrypted_field(&value) {
+                    continue;
+                }
+                tx.execute(
+                    &format!("UPDATE {} SET {} = ?1 WHERE id = ?2", table, column),
+                    params![cipher.encrypt(&value)?, id],
+                )?;
+                encrypted += 1;
+            }
+        }
+        tx.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            params![ENCRYPTION_KEY, ENCRYPTION_METHOD],
+        )?;
+        tx.commit()?;
+
+        *self.cipher.lock()? = Some(cipher);
+        self.encrypted.store(true, Ordering::SeqCst);
+        // 重写数据库文件并清空 WAL，不在磁盘上留下旧的明文
+        if let Err(e) = conn.execute_batch("PRAGMA secure_delete = ON; VACUUM; PRAGMA wal_checkpoint(TRUNCATE);") {
+            warn!("Failed to compact database after encryption: {}", e);
+        }
+        info!("Encrypted {} stored fields", encrypted);
+        Ok(encrypted)
+    }
+}
+
+#[tauri::command]
+pub fn save_message(
+    app: AppHandle,
+    conversation_id: Option<String>,
+    role: Role,
+    text: String,
+    audio_path: Option<String>,
+) -> Result<Message, AppError> {
+    // 设置为不保存录音时只保存文本；消息保存成功后才删除录音，校验或保存失败时文件保留
+    let store_audio = retention::store_audio(&app);
+    if text.trim().is_empty() && (audio_path.is_none() || !store_audio) {
+        return Err(AppError::invalid("消息内容不能为空"));
+    }
+    let (kept, discarded) = match audio_path {
+        Some(path) if !store_audio => (None, Some(path)),
+        path => (path, None),
+    };
+    let message = app
+        .state::<AppState>()
+        .storage
+        .save_message(conversation_id, role, text, kept)?;
+    if let Some(path) = discarded {
+        retention::discard_audio(&app, &path);
+    }
+    Ok(message)
+}
+
+#[tauri::command]
+pub fn list_conversations(
+    state: State<'_, AppState>,
+    page: Option<u32>,
+    page_size: Option<u32>,
+) -> Result<ConversationPage, AppError> {
+    state
+        .storage
+        .list_conversations(page.unwrap_or(1), page_size.unwrap_or(DEFAULT_PAGE_SIZE))
+}
+
+#[tauri::command]
+pub fn list_messages(state: State<'_, AppState>, conversation_id: String) -> Result<Vec<Message>, AppError> {
+    state.storage.list_messages(&conversation_id)
+}
+
+#[tauri::command]
+pub fn search_messages(
+    state: State<'_, AppState>,
+    query: String,
+    limit: Option<u32>,
+) -> Result<Vec<MessageHit>, AppError> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(Vec::new());
+    }
+    state.storage.search_messages(query, limit.unwrap_or(DEFAULT_PAGE_SIZE))
+}
+
+#[tauri::command]
+pub fn delete_conversation(app: AppHandle, id: String) -> Result<bool, AppError> {
+    let deleted = app.state::<AppState>().storage.delete_conversation(&id)?;
+    if deleted {
+        llm::forget_deleted_conversations(&app);
+    }
+    Ok(deleted)
+}
+
+#[tauri::command]
+pub fn get_database_encryption(state: State<'_, AppState>) -> Result<DatabaseEncryption, AppError> {
+    state.storage.encryption()
+}
+
+// 启用对话历史加密并加密已有记录
+#[tauri::command]
+pub async fn encrypt_database(app: AppHandle) -> Result<usize, AppError> {
+    let handle = app.clone();
+    let encrypted = tauri::async_runtime::spawn_blocking(move || handle.state::<AppState>().storage.encrypt_existing()).await??;
+    file_search::refresh(&app);
+    Ok(encrypted)
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::{encode_wav, SttProvider, Transcriber, SAMPLE_RATE};
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::network::{self, ClientConfig};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct BackendSttSettings {
+    // 后端识别接口路径，请求体为 16 kHz 单声道 WAV
+    pub path: String,
+}
+
+impl Default for BackendSttSettings {
+    fn default() -> Self {
+        Self {
+            path: "/api/voice/transcribe".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscribeResponse {
+    #[serde(default)]
+    text: String,
+    // 后端统一响应格式中的数据字段
+    data: Option<TranscribeData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscribeData {
+    text: String,
+}
+
+pub struct BackendTranscriber {
+    url: String,
+    token: Option<String>,
+    network: ClientConfig,
+}
+
+impl BackendTranscriber {
+    pub fn new(app: &AppHandle, settings: BackendSttSettings, token: Option<String>) -> Self {
+        Self {
+            url: format!("{}{}", app.state::<BackendManager>().url(), settings.path),
+            token,
+            network: network::config(app),
+        }
+    }
+}
+
+impl Transcriber for BackendTranscriber {
+    fn provider(&self) -> SttProvider {
+        SttProvider::Backend
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    // 后端一次返回完整结果，整体作为一次中间结果推送
+    fn transcribe(&self, audio: &[i16], language: &str, on_partial: &mut dyn FnMut(&str)) -> Result<String, AppError> {
+        let client = self.network.blocking_client(REQUEST_TIMEOUT)?;
+        let mut request = client
+            .post(&self.url)
+            .query(&[("language", language)])
+            .header(reqwest::header::CONTENT_TYPE, "audio/wav")
+            .body(encode_wav(audio, SAMPLE_RATE));
+        if let Some(token) = self.token.as_deref() {
+            request = request.bearer_auth(token);
+        }
+
+        let response = request.send().map_err(|e| AppError::network("无法连接识别服务", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("识别服务返回错误", response.status()));
+        }
+        let body: TranscribeResponse = response.json()?;
+        let text = body.data.map(|data| data.text).unwrap_or(body.text);
+        on_partial(&text);
+        Ok(text)
+    }
+}
@@ -0,0 +1,173 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use super::{transcribe_samples, SttProvider, SAMPLE_RATE};
+use crate::audio::decode;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::knowledge::{embeddings, IndexedDocument};
+use crate::platform::{self, TaskbarProgress};
+use crate::{i18n, AppState};
+
+// 长录音按段识别，便于上报进度，也避免单次上传过大
+const SEGMENT_SECS: usize = 30;
+// 在每段末尾的这段时间内寻找最安静的位置切分，尽量不切断句子
+const SPLIT_SEARCH_SECS: usize = 2;
+// 计算能量的窗口，20 ms
+const SPLIT_WINDOW: usize = SAMPLE_RATE as usize / 50;
+
+// stage 依次为 decoding、transcribing、indexing、done 或 failed；text 为目前已识别的全部文本
+#[derive(Debug, Clone, Serialize)]
+pub struct TranscriptionProgress {
+    pub path: String,
+    pub stage: String,
+    pub progress: u8,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileTranscript {
+    pub path: String,
+    pub text: String,
+    pub language: String,
+    pub provider: SttProvider,
+    pub audio_ms: u64,
+    pub elapsed_ms: u64,
+    // 保存到知识库的文档，来源为 audio:<原始文件路径>
+    pub document: IndexedDocument,
+}
+
+fn energy(samples: &[i16]) -> u64 {
+    samples.iter().map(|s| (*s as i64 * *s as i64) as u64).sum()
+}
+
+// 按 SEGMENT_SECS 切分，切点选在段尾附近能量最低的窗口
+fn segments(audio: &[i16]) -> Vec<&[i16]> {
+    let segment_len = SEGMENT_SECS * SAMPLE_RATE as usize;
+    let search_len = SPLIT_SEARCH_SECS * SAMPLE_RATE as usize;
+    let mut segments = Vec::new();
+    let mut start = 0;
+    while audio.len() - start > segment_len {
+        let end = start + segment_len;
+        let split = (end - search_len..end)
+            .step_by(SPLIT_WINDOW)
+            .min_by_key(|offset| energy(&audio[*offset..*offset + SPLIT_WINDOW]))
+            .unwrap_or(end);
+        segments.push(&audio[start..split]);
+        start = split;
+    }
+    if start < audio.len() {
+        segments.push(&audio[start..]);
+    }
+    segments
+}
+
+fn run(
+    app: &AppHandle,
+    path: &Path,
+    language: Option<String>,
+    token: Option<String>,
+    report: &dyn Fn(&str, u8, &str),
+) -> Result<FileTranscript, AppError> {
+    let started = Instant::now();
+    // 解码占前 20%，识别占 20% - 90%，写入知识库占剩余部分
+    report("decoding", 0, "");
+    let audio = decode::decode_file(path, SAMPLE_RATE, &mut |progress| {
+        report("decoding", (progress as u32 * 20 / 100) as u8, "")
+    })?;
+    if audio.is_empty() {
+        return Err(AppError::invalid("音频为空"));
+    }
+
+    let segments = segments(&audio);
+    let mut texts: Vec<String> = Vec::new();
+    let mut transcript_language = String::new();
+    let mut provider = SttProvider::default();
+    for (index, segment) in segments.iter().enumerate() {
+        let transcript = transcribe_samples(app, segment, language.clone(), token.clone())?;
+        if !transcript.text.is_empty() {
+            texts.push(transcript.text);
+        }
+        transcript_language = transcript.language;
+        provider = transcript.provider;
+        let progress = 20 + (index + 1) * 70 / segments.len();
+        report("transcribing", progress as u8, &texts.join("\n"));
+    }
+    let text = texts.join("\n");
+    if text.is_empty() {
+        return Err(AppError::invalid("没有识别出任何文字"));
+    }
+
+    report("indexing", 90, &text);
+    let document = app
+        .state::<AppState>()
+        .knowledge
+        .index_transcript(path, &text, &|done, chunks| {
+            report("indexing", (90 + done * 10 / chunks.max(1)).min(100) as u8, &text)
+        })?;
+    Ok(FileTranscript {
+        path: path.to_string_lossy().to_string(),
+        text,
+        language: transcript_language,
+        provider,
+        audio_ms: audio.len() as u64 * 1000 / SAMPLE_RATE as u64,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        document,
+    })
+}
+
+// 转写导入的录音并保存到知识库，进度以 transcription-progress 事件推送
+#[tauri::command]
+pub async fn transcribe_file(
+    app: AppHandle,
+    path: String,
+    language: Option<String>,
+    token: Option<String>,
+) -> Result<FileTranscript, AppError> {
+    let path = PathBuf::from(path);
+    if !path.is_file() {
+        return Err(AppError::NotFound(format!("找不到音频文件: {}", path.display())));
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let source = path.to_string_lossy().to_string();
+        let report = |stage: &str, progress: u8, text: &str| {
+            platform::set_progress(&app, TaskbarProgress::Normal(progress));
+            let payload = TranscriptionProgress {
+                path: source.clone(),
+                stage: stage.to_string(),
+                progress,
+                text: text.to_string(),
+            };
+            events::publish(&app, AppEvent::TranscriptionProgress(payload));
+        };
+
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| source.clone());
+        match run(&app, &path, language, token, &report) {
+            Ok(transcript) => {
+                report("done", 100, &transcript.text);
+                info!(
+                    "Transcribed {} ({} ms of audio) in {} ms",
+                    source, transcript.audio_ms, transcript.elapsed_ms
+                );
+                let body = i18n::tf("toast.transcribe_summary", &[&name]);
+                platform::finished(&app, i18n::t("toast.transcribe_done"), body, Some(&path));
+                embeddings::spawn_embed_pending(&app);
+                Ok(transcript)
+            }
+            Err(e) => {
+                warn!("Failed to transcribe {}: {}", source, e);
+                report("failed", 100, "");
+                platform::failed(&app, "transcribe-file", i18n::t("toast.transcribe_failed"), name);
+                Err(e)
+            }
+        }
+    })
+    .await?
+}
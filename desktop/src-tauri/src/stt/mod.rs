@@ -0,0 +1,256 @@
+// 语音识别：统一的 Transcriber 接口，可在后端服务和本地 whisper.cpp 之间切换
+pub mod backend;
+pub mod file;
+pub mod whisper;
+
+use base64::Engine;
+use rodio::Source;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Instant;
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::audio::capture::Recording;
+use crate::audio::{AudioCapture, Resampler};
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::offline;
+use crate::{settings, AppState};
+
+pub use backend::BackendTranscriber;
+pub use whisper::WhisperTranscriber;
+
+// whisper.cpp 只接受 16 kHz 单声道输入，后端也使用同样的格式
+pub const SAMPLE_RATE: u32 = 16_000;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SttProvider {
+    // Go 后端的识别接口
+    #[default]
+    Backend,
+    // 本地 whisper.cpp，完全离线
+    Whisper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SttSettings {
+    pub provider: SttProvider,
+    // zh、en 等语言代码，auto 表示自动检测
+    pub language: String,
+    // 后端不可用时改用本地引擎（需要已安装模型）
+    pub fallback_to_local: bool,
+    pub backend: backend::BackendSttSettings,
+    pub whisper: whisper::WhisperSettings,
+}
+
+impl Default for SttSettings {
+    fn default() -> Self {
+        Self {
+            provider: SttProvider::Backend,
+            language: "zh".to_string(),
+            fallback_to_local: true,
+            backend: backend::BackendSttSettings::default(),
+            whisper: whisper::WhisperSettings::default(),
+        }
+    }
+}
+
+// 待识别的音频：文件路径、base64 编码的 16 位 PCM，省略时使用最近一次录音
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum AudioInput {
+    Path { path: String },
+    Pcm { data: String, sample_rate: u32 },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Transcript {
+    pub id: u64,
+    pub text: String,
+    pub language: String,
+    pub provider: SttProvider,
+    pub audio_ms: u64,
+    pub elapsed_ms: u64,
+}
+
+// 识别过程中推送的中间结果，text 为目前已识别的全部文本
+#[derive(Debug, Clone, Serialize)]
+pub struct PartialTranscript {
+    pub id: u64,
+    pub text: String,
+}
+
+pub trait Transcriber: Send + Sync {
+    fn provider(&self) -> SttProvider;
+
+    // 可以离线识别时返回 true，用于后端不可用时的降级判断
+    fn is_available(&self) -> bool;
+
+    // 在阻塞线程中调用；audio 为 16 kHz 单声道，每识别出一段文本调用一次 on_partial
+    fn transcribe(&self, audio: &[i16], language: &str, on_partial: &mut dyn FnMut(&str)) -> Result<String, AppError>;
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+pub fn transcriber(app: &AppHandle, settings: &SttSettings, provider: SttProvider, token: Option<String>) -> Box<dyn Transcriber> {
+    match provider {
+        SttProvider::Backend => Box::new(BackendTranscriber::new(app, settings.backend.clone(), token)),
+        SttProvider::Whisper => Box::new(WhisperTranscriber::new(app, settings.whisper.clone())),
+    }
+}
+
+// 混为单声道并重采样到 16 kHz；采样率为 0 时重采样器无法推进
+fn to_mono_16k(samples: &[i16], sample_rate: u32, channels: u16) -> Result<Vec<i16>, AppError> {
+    if sample_rate == 0 {
+        return Err(AppError::invalid("音频采样率无效"));
+    }
+    let channels = channels.max(1) as usize;
+    let mono: Vec<f32> = samples
+        .chunks(channels)
+        .map(|frame| frame.iter().map(|s| *s as f32 / i16::MAX as f32).sum::<f32>() / channels as f32)
+        .collect();
+    Ok(Resampler::new(sample_rate, SAMPLE_RATE)
+        .process(&mono)
+        .into_iter()
+        .map(|s| (s.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+        .collect())
+}
+
+pub(crate) fn load_audio(app: &AppHandle, input: Option<AudioInput>) -> Result<Vec<i16>, AppError> {
+    match input {
+        None => {
+            let recording: Recording = app
+                .state::<AudioCapture>()
+                .last_recording()
+                .ok_or("没有可识别的录音")?;
+            to_mono_16k(&recording.samples, recording.sample_rate, 1)
+        }
+        Some(AudioInput::Pcm { data, sample_rate }) => {
+            let bytes = base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| format!("音频数据不是有效的 base64: {}", e))?;
+            let samples: Vec<i16> = bytes
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect();
+            to_mono_16k(&samples, sample_rate, 1)
+        }
+        Some(AudioInput::Path { path }) => {
+            let file = std::fs::File::open(&path).map_err(|e| format!("无法打开音频文件 {}: {}", path, e))?;
+            let decoder = rodio::Decoder::new(std::io::BufReader::new(file))
+                .map_err(|e| format!("无法解码音频: {}", e))?;
+            let (sample_rate, channels) = (decoder.sample_rate(), decoder.channels());
+            let samples: Vec<i16> = decoder.collect();
+            to_mono_16k(&samples, sample_rate, channels)
+        }
+    }
+}
+
+// 16 位单声道 WAV 编码，供后端上传和 whisper.cpp 读取
+pub(crate) fn encode_wav(samples: &[i16], sample_rate: u32) -> Vec<u8> {
+    let data_len = (samples.len() * 2) as u32;
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&(sample_rate * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        wav.extend_from_slice(&sample.to_le_bytes());
+    }
+    wav
+}
+
+// 在阻塞线程中调用：audio 为 16 kHz 单声道；识别中间结果以 stt-partial 事件推送
+pub fn transcribe_samples(
+    app: &AppHandle,
+    audio: &[i16],
+    language: Option<String>,
+    token: Option<String>,
+) -> Result<Transcript, AppError> {
+    if audio.is_empty() {
+        return Err(AppError::invalid("音频为空"));
+    }
+    let settings = app.state::<AppState>().settings.lock()?.stt.clone();
+    let language = language.unwrap_or_else(|| settings.language.clone());
+    let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+    let started = Instant::now();
+
+    let mut on_partial = |text: &str| {
+        let partial = PartialTranscript {
+            id,
+            text: text.to_string(),
+        };
+        events::publish(app, AppEvent::SttPartial(partial));
+    };
+
+    let mut primary = transcriber(app, &settings, settings.provider, token.clone());
+    // 离线时直接使用本地引擎，不再等待后端请求失败
+    if primary.provider() == SttProvider::Backend && offline::prefer_local(app) {
+        let local = transcriber(app, &settings, SttProvider::Whisper, None);
+        if local.is_available() {
+            primary = local;
+        }
+    }
+    let (provider, text) = match primary.transcribe(audio, &language, &mut on_partial) {
+        Ok(text) => (primary.provider(), text),
+        Err(e) if primary.provider() == SttProvider::Backend && settings.fallback_to_local => {
+            let local = transcriber(app, &settings, SttProvider::Whisper, None);
+            if !local.is_available() {
+                return Err(e);
+            }
+            warn!("Backend transcription failed, falling back to whisper: {}", e);
+            (local.provider(), local.transcribe(audio, &language, &mut on_partial)?)
+        }
+        Err(e) => return Err(e),
+    };
+
+    let transcript = Transcript {
+        id,
+        text: text.trim().to_string(),
+        language,
+        provider,
+        audio_ms: audio.len() as u64 * 1000 / SAMPLE_RATE as u64,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+    };
+    info!(
+        "Transcribed {} ms of audio with {:?} in {} ms",
+        transcript.audio_ms, transcript.provider, transcript.elapsed_ms
+    );
+    Ok(transcript)
+}
+
+#[tauri::command]
+pub async fn transcribe(
+    app: AppHandle,
+    audio: Option<AudioInput>,
+    language: Option<String>,
+    token: Option<String>,
+) -> Result<Transcript, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let samples = load_audio(&app, audio)?;
+        transcribe_samples(&app, &samples, language, token)
+    })
+    .await?
+}
+
+#[tauri::command]
+pub fn set_stt_provider(app: AppHandle, provider: SttProvider, language: Option<String>) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.stt.provider = provider;
+    if let Some(language) = language {
+        settings.stt.language = language;
+    }
+    settings::save_and_notify(&app, &settings, &["stt"])
+}
@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use tauri::AppHandle;
+
+use super::{encode_wav, SttProvider, Transcriber, SAMPLE_RATE};
+use crate::acceleration::{self, AccelBackend, Engine};
+use crate::error::AppError;
+
+pub const MODELS_DIR: &str = "models/whisper";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WhisperSettings {
+    // whisper.cpp 的命令行程序，不在 PATH 中时填写完整路径
+    pub command: String,
+    // 模型名称（对应应用目录下的 ggml-<name>.bin）或模型文件的完整路径
+    pub model: String,
+    // 推理线程数，0 表示由 whisper.cpp 决定
+    pub threads: u32,
+}
+
+impl Default for WhisperSettings {
+    fn default() -> Self {
+        Self {
+            command: "whisper-cli".to_string(),
+            model: "base".to_string(),
+            threads: 0,
+        }
+    }
+}
+
+pub fn model_path(app: &AppHandle, model: &str) -> Option<PathBuf> {
+    let path = PathBuf::from(model);
+    if path.is_absolute() {
+        return Some(path);
+    }
+    let dir = app.path_resolver().app_data_dir()?.join(MODELS_DIR);
+    Some(dir.join(format!("ggml-{}.bin", model)))
+}
+
+// whisper.cpp 把静音等非语音片段输出为 [BLANK_AUDIO]、(音乐) 之类的标记
+fn is_annotation(line: &str) -> bool {
+    (line.starts_with('[') && line.ends_with(']')) || (line.starts_with('(') && line.ends_with(')'))
+}
+
+pub struct WhisperTranscriber {
+    settings: WhisperSettings,
+    model: Option<PathBuf>,
+    backend: AccelBackend,
+}
+
+impl WhisperTranscriber {
+    pub fn new(app: &AppHandle, settings: WhisperSettings) -> Self {
+        let model = model_path(app, &settings.model);
+        let backend = acceleration::select(app, Engine::Whisper);
+        Self {
+            settings,
+            model,
+            backend,
+        }
+    }
+}
+
+impl Transcriber for WhisperTranscriber {
+    fn provider(&self) -> SttProvider {
+        SttProvider::Whisper
+    }
+
+    fn is_available(&self) -> bool {
+        self.model.as_ref().is_some_and(|model| model.exists())
+    }
+
+    // whisper.cpp 每解码完一段就输出一行，逐行读取作为中间结果
+    fn transcribe(&self, audio: &[i16], language: &str, on_partial: &mut dyn FnMut(&str)) -> Result<String, AppError> {
+        let model =
+            self.model.as_ref().filter(|model| model.exists()).ok_or_else(|| {
+                AppError::NotFound(format!("未找到 Whisper 模型 {}，请先下载模型", self.settings.model))
+            })?;
+
+        let wav = std::env::temp_dir().join(format!("lingecho-stt-{}.wav", uuid::Uuid::new_v4()));
+        std::fs::write(&wav, encode_wav(audio, SAMPLE_RATE))?;
+
+        let mut command = Command::new(&self.settings.command);
+        command
+            .arg("-m")
+            .arg(model)
+            .arg("-f")
+            .arg(&wav)
+            .args(["-l", language, "-nt", "-np"])
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        // 带 GPU 后端编译的 whisper.cpp 默认使用显卡；选择 CPU 时关闭，并用满可用线程（默认最多 4 个）
+        let threads = match self.backend {
+            AccelBackend::Cpu => {
+                command.arg("-ng");
+                match self.settings.threads {
+                    0 => acceleration::cpu_threads(),
+                    threads => threads as usize,
+                }
+            }
+            _ => self.settings.threads as usize,
+        };
+        if threads > 0 {
+            command.arg("-t").arg(threads.to_string());
+        }
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            command.creation_flags(crate::backend::CREATE_NO_WINDOW);
+        }
+
+        let result = (|| -> Result<String, AppError> {
+            let mut child = command
+                .spawn()
+                .map_err(|e| AppError::unavailable(format!("无法启动 Whisper 引擎 {}", self.settings.command), e))?;
+            // 单独读取 stderr，避免管道写满阻塞子进程
+            let stderr = child.stderr.take();
+            let errors = std::thread::spawn(move || {
+                let mut output = String::new();
+                if let Some(mut stderr) = stderr {
+                    stderr.read_to_string(&mut output).ok();
+                }
+                output
+            });
+
+            let mut text = String::new();
+            if let Some(stdout) = child.stdout.take() {
+                for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                    let line = line.trim();
+                    if line.is_empty() || is_annotation(line) {
+                        continue;
+                    }
+                    if needs_space(&text, line) {
+                        text.push(' ');
+                    }
+                    text.push_str(line);
+                    on_partial(&text);
+                }
+            }
+
+            let status = child.wait()?;
+            let errors = errors.join().unwrap_or_default();
+            if !status.success() {
+                let detail = errors.lines().last().unwrap_or_default().trim();
+                return Err(AppError::Internal(format!("语音识别失败: {}", detail)));
+            }
+            Ok(text)
+        })();
+
+        std::fs::remove_file(&wav).ok();
+        result
+    }
+}
+
+// 每行是一个片段，中文、日文之间不加空格，其他语言用空格分隔
+fn needs_space(text: &str, next: &str) -> bool {
+    let unspaced = |c: char| {
+        matches!(c, '\u{3000}'..='\u{30ff}' | '\u{3400}'..='\u{4dbf}' | '\u{4e00}'..='\u{9fff}' | '\u{ff00}'..='\u{ffef}')
+    };
+    match (text.chars().last(), next.chars().next()) {
+        (Some(last), Some(first)) => !unspaced(last) && !unspaced(first),
+        _ => false,
+    }
+}
@@ -0,0 +1,425 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tracing::info;
+
+use crate::error::AppError;
+use crate::{settings, AppState};
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SystemControlSettings {
+    // 默认关闭，用户明确允许后语音助手才能调节音量、控制媒体播放、锁屏和关闭显示器
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct SystemVolume {
+    // 0-100
+    pub level: u32,
+    pub muted: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MediaKey {
+    PlayPause,
+    Next,
+    Previous,
+}
+
+#[cfg(windows)]
+mod native {
+    use std::ptr;
+    use windows::Win32::Media::Audio::Endpoints::IAudioEndpointVolume;
+    use windows::Win32::Media::Audio::{eConsole, eRender, IMMDeviceEnumerator, MMDeviceEnumerator};
+    use windows::Win32::System::Com::{CoCreateInstance, CoInitializeEx, CLSCTX_ALL, COINIT_MULTITHREADED};
+    use windows_sys::Win32::System::Shutdown::LockWorkStation;
+    use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+        SendInput, INPUT, INPUT_0, INPUT_KEYBOARD, KEYBDINPUT, KEYEVENTF_EXTENDEDKEY, KEYEVENTF_KEYUP,
+        VK_MEDIA_NEXT_TRACK, VK_MEDIA_PLAY_PAUSE, VK_MEDIA_PREV_TRACK,
+    };
+    use windows_sys::Win32::UI::WindowsAndMessaging::{PostMessageW, HWND_BROADCAST, SC_MONITORPOWER, WM_SYSCOMMAND};
+
+    use super::{MediaKey, SystemVolume};
+    use crate::error::AppError;
+
+    // 在阻塞线程中调用，线程池线程可能尚未初始化 COM，已初始化时忽略返回的错误
+    fn endpoint() -> Result<IAudioEndpointVolume, AppError> {
+        let unavailable = |e: windows::core::Error| AppError::unavailable("无法访问系统音量", e);
+        // SAFETY: 只在当前线程上使用创建的 COM 对象
+        unsafe {
+            let _ = CoInitializeEx(ptr::null(), COINIT_MULTITHREADED);
+            let enumerator: IMMDeviceEnumerator =
+                CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL).map_err(unavailable)?;
+            let device = enumerator
+                .GetDefaultAudioEndpoint(eRender, eConsole)
+                .map_err(unavailable)?;
+            device.Activate(CLSCTX_ALL, ptr::null()).map_err(unavailable)
+        }
+    }
+
+    pub fn volume() -> Result<SystemVolume, AppError> {
+        let endpoint = endpoint()?;
+        // SAFETY: endpoint 是有效的 IAudioEndpointVolume
+        unsafe {
+            let level = endpoint
+                .GetMasterVolumeLevelScalar()
+                .map_err(|e| AppError::unavailable("无法访问系统音量", e))?;
+            let muted = endpoint
+                .GetMute()
+                .map_err(|e| AppError::unavailable("无法访问系统音量", e))?;
+            Ok(SystemVolume {
+                level: (level * 100.0).round() as u32,
+                muted: muted.as_bool(),
+            })
+        }
+    }
+
+    pub fn set_volume(level: Option<u32>, muted: Option<bool>) -> Result<(), AppError> {
+        let endpoint = endpoint()?;
+        // SAFETY: 同上
+        unsafe {
+            if let Some(level) = level {
+                endpoint
+                    .SetMasterVolumeLevelScalar(level as f32 / 100.0, ptr::null())
+                    .map_err(|e| AppError::unavailable("无法调节系统音量", e))?;
+            }
+            if let Some(muted) = muted {
+                endpoint
+                    .SetMute(muted, ptr::null())
+                    .map_err(|e| AppError::unavailable("无法调节系统音量", e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn key_input(vk: u16, flags: u32) -> INPUT {
+        INPUT {
+            r#type: INPUT_KEYBOARD,
+            Anonymous: INPUT_0 {
+                ki: KEYBDINPUT {
+                    wVk: vk,
+                    wScan: 0,
+                    dwFlags: KEYEVENTF_EXTENDEDKEY | flags,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        }
+    }
+
+    // 模拟多媒体键，由系统转发给当前的媒体会话
+    pub fn media_key(key: MediaKey) -> Result<(), AppError> {
+        let vk = match key {
+            MediaKey::PlayPause => VK_MEDIA_PLAY_PAUSE,
+            MediaKey::Next => VK_MEDIA_NEXT_TRACK,
+            MediaKey::Previous => VK_MEDIA_PREV_TRACK,
+        };
+        let inputs = [key_input(vk, 0), key_input(vk, KEYEVENTF_KEYUP)];
+        // SAFETY: inputs 在调用期间有效，cbsize 为单个 INPUT 的大小
+        let sent = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_ptr(),
+                std::mem::size_of::<INPUT>() as i32,
+            )
+        };
+        if sent as usize != inputs.len() {
+            return Err(AppError::unavailable(
+                "无法发送媒体按键",
+                std::io::Error::last_os_error(),
+            ));
+        }
+        Ok(())
+    }
+
+    pub fn lock_screen() -> Result<(), AppError> {
+        // SAFETY: 无参数的系统调用
+        if unsafe { LockWorkStation() } == 0 {
+            return Err(AppError::unavailable("无法锁定屏幕", std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+
+    pub fn sleep_display() -> Result<(), AppError> {
+        // SAFETY: 广播 SC_MONITORPOWER，2 表示关闭显示器；PostMessage 不等待各窗口处理
+        if unsafe { PostMessageW(HWND_BROADCAST, WM_SYSCOMMAND, SC_MONITORPOWER as usize, 2) } == 0 {
+            return Err(AppError::unavailable("无法关闭显示器", std::io::Error::last_os_error()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(windows))]
+fn run(program: &str, args: &[&str]) -> Result<String, AppError> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| AppError::unavailable(format!("无法运行 {}", program), e))?;
+    if !output.status.success() {
+        return Err(AppError::unavailable(
+            format!("无法运行 {}", program),
+            String::from_utf8_lossy(&output.stderr).trim(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(target_os = "macos")]
+mod native {
+    use objc::runtime::Object;
+    use objc::{class, msg_send, sel, sel_impl};
+    use std::ffi::c_void;
+
+    use super::{run, MediaKey, SystemVolume};
+    use crate::error::AppError;
+
+    // NX_KEYTYPE_*，见 IOKit/hidsystem/ev_keymap.h
+    const KEY_PLAY: i64 = 16;
+    const KEY_NEXT: i64 = 17;
+    const KEY_PREVIOUS: i64 = 18;
+    const NS_EVENT_TYPE_SYSTEM_DEFINED: u64 = 14;
+    const AUX_CONTROL_BUTTONS: i16 = 8;
+    const HID_EVENT_TAP: u32 = 0;
+
+    #[repr(C)]
+    struct NSPoint {
+        x: f64,
+        y: f64,
+    }
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGEventPost(tap: u32, event: *mut c_void);
+    }
+
+    fn osascript(script: &str) -> Result<String, AppError> {
+        run("osascript", &["-e", script])
+    }
+
+    pub fn volume() -> Result<SystemVolume, AppError> {
+        let level = osascript("output volume of (get volume settings)")?;
+        let muted = osascript("output muted of (get volume settings)")?;
+        Ok(SystemVolume {
+            level: level.parse().unwrap_or(0),
+            muted: muted == "true",
+        })
+    }
+
+    pub fn set_volume(level: Option<u32>, muted: Option<bool>) -> Result<(), AppError> {
+        if let Some(level) = level {
+            osascript(&format!("set volume output volume {}", level))?;
+        }
+        if let Some(muted) = muted {
+            osascript(&format!("set volume output muted {}", muted))?;
+        }
+        Ok(())
+    }
+
+    // 与键盘上的媒体键一样发送 NSSystemDefined 事件，由系统转发给正在播放的应用
+    pub fn media_key(key: MediaKey) -> Result<(), AppError> {
+        let code = match key {
+            MediaKey::PlayPause => KEY_PLAY,
+            MediaKey::Next => KEY_NEXT,
+            MediaKey::Previous => KEY_PREVIOUS,
+        };
+        for state in [0xa_i64, 0xb] {
+            let flags = (state << 8) as u64;
+            let data1 = (code << 16) | (state << 8);
+            // SAFETY: NSEvent 类方法返回自动释放的对象，CGEvent 在事件对象存活期间有效
+            unsafe {
+                let event: *mut Object = msg_send![class!(NSEvent),
+                    otherEventWithType: NS_EVENT_TYPE_SYSTEM_DEFINED
+                    location: NSPoint { x: 0.0, y: 0.0 }
+                    modifierFlags: flags
+                    timestamp: 0.0_f64
+                    windowNumber: 0_i64
+                    context: std::ptr::null_mut::<Object>()
+                    subtype: AUX_CONTROL_BUTTONS
+                    data1: data1
+                    data2: -1_i64];
+                if event.is_null() {
+                    return Err(AppError::unavailable("无法发送媒体按键", "NSEvent"));
+                }
+                let cg_event: *mut c_void = msg_send![event, CGEvent];
+                CGEventPost(HID_EVENT_TAP, cg_event);
+            }
+        }
+        Ok(())
+    }
+
+    // 相当于 Ctrl+Cmd+Q，需要授予辅助功能权限
+    pub fn lock_screen() -> Result<(), AppError> {
+        osascript("tell application \"System Events\" to keystroke \"q\" using {control down, command down}")?;
+        Ok(())
+    }
+
+    pub fn sleep_display() -> Result<(), AppError> {
+        run("pmset", &["displaysleepnow"])?;
+        Ok(())
+    }
+}
+
+// Linux 依赖桌面环境常见的命令行工具：PipeWire (wpctl) 或 PulseAudio (pactl)、playerctl、loginctl
+#[cfg(all(unix, not(target_os = "macos")))]
+mod native {
+    use super::{run, MediaKey, SystemVolume};
+    use crate::error::AppError;
+
+    // wpctl 输出 "Volume: 0.45 [MUTED]"
+    fn wpctl_volume() -> Result<SystemVolume, AppError> {
+        let output = run("wpctl", &["get-volume", "@DEFAULT_AUDIO_SINK@"])?;
+        let level = output
+            .split_whitespace()
+            .nth(1)
+            .and_then(|value| value.parse::<f64>().ok())
+            .ok_or_else(|| AppError::unavailable("无法访问系统音量", &output))?;
+        Ok(SystemVolume {
+            level: (level * 100.0).round() as u32,
+            muted: output.contains("[MUTED]"),
+        })
+    }
+
+    // pactl 输出 "Volume: front-left: 29491 /  45% / ..."，取第一个声道
+    fn pactl_volume() -> Result<SystemVolume, AppError> {
+        let output = run("pactl", &["get-sink-volume", "@DEFAULT_SINK@"])?;
+        let level = output
+            .split('/')
+            .nth(1)
+            .and_then(|value| value.trim().trim_end_matches('%').parse::<u32>().ok())
+            .ok_or_else(|| AppError::unavailable("无法访问系统音量", &output))?;
+        let muted = run("pactl", &["get-sink-mute", "@DEFAULT_SINK@"])?;
+        Ok(SystemVolume {
+            level,
+            muted: muted.ends_with("yes"),
+        })
+    }
+
+    pub fn volume() -> Result<SystemVolume, AppError> {
+        wpctl_volume().or_else(|_| pactl_volume())
+    }
+
+    pub fn set_volume(level: Option<u32>, muted: Option<bool>) -> Result<(), AppError> {
+        if let Some(level) = level {
+            let fraction = format!("{:.2}", level as f64 / 100.0);
+            let percent = format!("{}%", level);
+            run("wpctl", &["set-volume", "@DEFAULT_AUDIO_SINK@", &fraction])
+                .or_else(|_| run("pactl", &["set-sink-volume", "@DEFAULT_SINK@", &percent]))?;
+        }
+        if let Some(muted) = muted {
+            let flag = if muted { "1" } else { "0" };
+            run("wpctl", &["set-mute", "@DEFAULT_AUDIO_SINK@", flag])
+                .or_else(|_| run("pactl", &["set-sink-mute", "@DEFAULT_SINK@", flag]))?;
+        }
+        Ok(())
+    }
+
+    // 通过 MPRIS 控制当前的播放器
+    pub fn media_key(key: MediaKey) -> Result<(), AppError> {
+        let action = match key {
+            MediaKey::PlayPause => "play-pause",
+            MediaKey::Next => "next",
+            MediaKey::Previous => "previous",
+        };
+        run("playerctl", &[action])?;
+        Ok(())
+    }
+
+    pub fn lock_screen() -> Result<(), AppError> {
+        run("loginctl", &["lock-session"])?;
+        Ok(())
+    }
+
+    // 只支持 X11；Wayland 下各合成器没有统一的接口
+    pub fn sleep_display() -> Result<(), AppError> {
+        run("xset", &["dpms", "force", "off"])?;
+        Ok(())
+    }
+}
+
+fn ensure_enabled(app: &AppHandle) -> Result<(), AppError> {
+    if app.state::<AppState>().settings.lock()?.system_control.enabled {
+        return Ok(());
+    }
+    Err(AppError::PermissionDenied("系统控制未开启，请先在设置中允许".into()))
+}
+
+// 系统调用可能较慢（启动外部进程、初始化 COM），统一放到阻塞线程
+async fn blocking<T: Send + 'static>(
+    app: AppHandle,
+    action: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    ensure_enabled(&app)?;
+    tauri::async_runtime::spawn_blocking(action).await?
+}
+
+#[tauri::command]
+pub async fn get_system_volume(app: AppHandle) -> Result<SystemVolume, AppError> {
+    blocking(app, native::volume).await
+}
+
+// level 为 0-100 的绝对音量，change 为相对调节量（如 -10 表示调低 10%）
+#[tauri::command]
+pub async fn set_system_volume(
+    app: AppHandle,
+    level: Option<u32>,
+    change: Option<i32>,
+    muted: Option<bool>,
+) -> Result<SystemVolume, AppError> {
+    if level.is_none() && change.is_none() && muted.is_none() {
+        return Err(AppError::invalid("需要指定音量或静音状态"));
+    }
+    blocking(app, move || {
+        let target = match (level, change) {
+            (Some(level), _) => Some(level.min(100)),
+            (None, Some(change)) => Some((native::volume()?.level.min(100) as i32).saturating_add(change).clamp(0, 100) as u32),
+            (None, None) => None,
+        };
+        // 调高音量时自动取消静音
+        let muted = muted.or(match change {
+            Some(change) if change > 0 => Some(false),
+            _ => None,
+        });
+        native::set_volume(target, muted)?;
+        let volume = native::volume()?;
+        info!("System volume set to {} (muted: {})", volume.level, volume.muted);
+        Ok(volume)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn media_play_pause(app: AppHandle) -> Result<(), AppError> {
+    blocking(app, || native::media_key(MediaKey::PlayPause)).await
+}
+
+#[tauri::command]
+pub async fn media_next(app: AppHandle) -> Result<(), AppError> {
+    blocking(app, || native::media_key(MediaKey::Next)).await
+}
+
+#[tauri::command]
+pub async fn media_prev(app: AppHandle) -> Result<(), AppError> {
+    blocking(app, || native::media_key(MediaKey::Previous)).await
+}
+
+#[tauri::command]
+pub async fn lock_screen(app: AppHandle) -> Result<(), AppError> {
+    info!("Locking screen");
+    blocking(app, native::lock_screen).await
+}
+
+#[tauri::command]
+pub async fn sleep_display(app: AppHandle) -> Result<(), AppError> {
+    info!("Turning off display");
+    blocking(app, native::sleep_display).await
+}
+
+#[tauri::command]
+pub fn set_system_control(app: AppHandle, enabled: bool) -> Result<SystemControlSettings, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.system_control.enabled = enabled;
+    settings::save_and_notify(&app, &settings, &["system_control"])?;
+    info!("System control {}", if enabled { "enabled" } else { "disabled" });
+    Ok(settings.system_control.clone())
+}
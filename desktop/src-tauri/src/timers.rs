@@ -0,0 +1,247 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::Notify;
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::i18n;
+use crate::notifications::{self, CATEGORY_REMINDER};
+use crate::pet;
+use crate::storage::now_millis;
+use crate::tts;
+
+// 有计时器运行时的倒计时事件间隔
+const TICK: Duration = Duration::from_secs(1);
+const MAX_DURATION_SECS: u64 = 24 * 60 * 60;
+const MAX_TIMERS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerKind {
+    Countdown,
+    Stopwatch,
+}
+
+// 计时器只保存在内存中，应用退出后不再恢复；需要持久化的用提醒
+struct Entry {
+    id: String,
+    kind: TimerKind,
+    label: String,
+    started_at: i64,
+    started: Instant,
+    duration: Option<Duration>,
+}
+
+impl Entry {
+    fn status(&self, now: Instant) -> TimerStatus {
+        let elapsed = now.saturating_duration_since(self.started);
+        let remaining = self.duration.map(|duration| duration.saturating_sub(elapsed));
+        TimerStatus {
+            id: self.id.clone(),
+            kind: self.kind,
+            label: self.label.clone(),
+            started_at: self.started_at,
+            duration_ms: self.duration.map(|duration| duration.as_millis() as u64),
+            elapsed_ms: elapsed.as_millis() as u64,
+            remaining_ms: remaining.map(|remaining| remaining.as_millis() as u64),
+            // 倒计时已完成的比例，桌宠据此绘制进度环
+            progress: self
+                .duration
+                .map(|duration| (elapsed.as_secs_f64() / duration.as_secs_f64().max(0.001)).min(1.0)),
+        }
+    }
+
+    fn expired(&self, now: Instant) -> bool {
+        self.duration
+            .is_some_and(|duration| now.saturating_duration_since(self.started) >= duration)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimerStatus {
+    pub id: String,
+    pub kind: TimerKind,
+    pub label: String,
+    pub started_at: i64,
+    pub duration_ms: Option<u64>,
+    pub elapsed_ms: u64,
+    pub remaining_ms: Option<u64>,
+    pub progress: Option<f64>,
+}
+
+pub struct Timers {
+    entries: Mutex<Vec<Entry>>,
+    wake: Notify,
+}
+
+impl Timers {
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(Vec::new()),
+            wake: Notify::new(),
+        }
+    }
+
+    fn add(&self, kind: TimerKind, label: String, duration: Option<Duration>) -> Result<TimerStatus, AppError> {
+        let mut entries = self.entries.lock()?;
+        if entries.len() >= MAX_TIMERS {
+            return Err(AppError::invalid("同时运行的计时器过多"));
+        }
+        let entry = Entry {
+            id: uuid::Uuid::new_v4().to_string(),
+            kind,
+            label,
+            started_at: now_millis(),
+            started: Instant::now(),
+            duration,
+        };
+        let status = entry.status(entry.started);
+        entries.push(entry);
+        self.wake.notify_one();
+        Ok(status)
+    }
+
+    fn list(&self) -> Result<Vec<TimerStatus>, AppError> {
+        let now = Instant::now();
+        Ok(self.entries.lock()?.iter().map(|entry| entry.status(now)).collect())
+    }
+
+    fn remove(&self, id: &str) -> Result<Option<TimerStatus>, AppError> {
+        let mut entries = self.entries.lock()?;
+        let Some(index) = entries.iter().position(|entry| entry.id == id) else {
+            return Ok(None);
+        };
+        let status = entries.remove(index).status(Instant::now());
+        self.wake.notify_one();
+        Ok(Some(status))
+    }
+
+    // 移除到期的倒计时，返回 (到期的, 仍在运行的)
+    fn take_expired(&self) -> Result<(Vec<TimerStatus>, Vec<TimerStatus>), AppError> {
+        let now = Instant::now();
+        let mut entries = self.entries.lock()?;
+        let (expired, active): (Vec<Entry>, Vec<Entry>) = entries.drain(..).partition(|entry| entry.expired(now));
+        *entries = active;
+        Ok((
+            expired.iter().map(|entry| entry.status(now)).collect(),
+            entries.iter().map(|entry| entry.status(now)).collect(),
+        ))
+    }
+
+    fn next_wait(&self) -> Result<Option<Duration>, AppError> {
+        let now = Instant::now();
+        let entries = self.entries.lock()?;
+        if entries.is_empty() {
+            return Ok(None);
+        }
+        let nearest = entries
+            .iter()
+            .filter_map(|entry| {
+                entry
+                    .duration
+                    .map(|duration| duration.saturating_sub(now - entry.started))
+            })
+            .min()
+            .unwrap_or(TICK);
+        Ok(Some(nearest.min(TICK)))
+    }
+}
+
+fn announce(app: &AppHandle, timer: &TimerStatus) {
+    info!("Timer {} finished", timer.id);
+    let title = i18n::t("timer.finished_title");
+    let text = i18n::tf("timer.spoken", &[&timer.label]);
+    if let Err(e) = notifications::send(app, CATEGORY_REMINDER, &title, &text, None) {
+        warn!("{}", e);
+    }
+    events::publish(app, AppEvent::TimerFinished(timer.clone()));
+    pet::bubble::say(app, &text);
+
+    let handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = tts::speak(handle, text, None, None).await {
+            warn!("Failed to announce timer: {}", e);
+        }
+    });
+}
+
+// 在 setup 中调用：没有计时器时等待唤醒，否则每秒发送一次倒计时事件
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let timers = app.state::<Timers>();
+        // 最后一个计时器结束后再发送一次空列表，让桌宠收起进度环
+        let mut was_active = false;
+        loop {
+            match timers.next_wait() {
+                Ok(Some(wait)) => {
+                    tokio::select! {
+                        _ = tokio::time::sleep(wait) => {}
+                        _ = timers.wake.notified() => {}
+                    }
+                }
+                Ok(None) if was_active => {}
+                Ok(None) => {
+                    timers.wake.notified().await;
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Failed to query timers: {}", e);
+                    tokio::time::sleep(TICK).await;
+                    continue;
+                }
+            }
+            let (expired, active) = match timers.take_expired() {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Failed to update timers: {}", e);
+                    continue;
+                }
+            };
+            expired.iter().for_each(|timer| announce(&app, timer));
+            was_active = !active.is_empty();
+            events::publish(&app, AppEvent::TimerTick(active));
+        }
+    });
+}
+
+// duration_secs 为倒计时长度，label 为到期时播报的名称
+#[tauri::command]
+pub fn start_timer(
+    timers: State<'_, Timers>,
+    duration_secs: u64,
+    label: Option<String>,
+) -> Result<TimerStatus, AppError> {
+    if duration_secs == 0 || duration_secs > MAX_DURATION_SECS {
+        return Err(AppError::invalid("计时时长需要在 1 秒到 24 小时之间"));
+    }
+    let label = label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .unwrap_or_else(|| i18n::t("timer.default_label"));
+    timers.add(TimerKind::Countdown, label, Some(Duration::from_secs(duration_secs)))
+}
+
+#[tauri::command]
+pub fn start_stopwatch(timers: State<'_, Timers>, label: Option<String>) -> Result<TimerStatus, AppError> {
+    let label = label
+        .map(|label| label.trim().to_string())
+        .filter(|label| !label.is_empty())
+        .unwrap_or_else(|| i18n::t("timer.stopwatch_label"));
+    timers.add(TimerKind::Stopwatch, label, None)
+}
+
+#[tauri::command]
+pub fn list_timers(timers: State<'_, Timers>) -> Result<Vec<TimerStatus>, AppError> {
+    timers.list()
+}
+
+// 取消倒计时或停止秒表，返回停止时的状态
+#[tauri::command]
+pub fn cancel_timer(timers: State<'_, Timers>, id: String) -> Result<TimerStatus, AppError> {
+    timers
+        .remove(&id)?
+        .ok_or_else(|| AppError::NotFound(format!("计时器不存在: {}", id)))
+}
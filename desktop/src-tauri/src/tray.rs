@@ -0,0 +1,108 @@
+use tauri::{
+    AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem,
+};
+use tracing::{info, warn};
+
+use crate::backend::BackendManager;
+use crate::{i18n, macos, pet, privacy, shutdown};
+
+const MENU_SHOW_MAIN: &str = "show_main";
+const MENU_TOGGLE_PET: &str = "toggle_pet";
+const MENU_TOGGLE_CLICK_THROUGH: &str = "toggle_click_through";
+pub const MENU_TOGGLE_PRIVACY: &str = "toggle_privacy";
+const MENU_RESTART_BACKEND: &str = "restart_backend";
+const MENU_QUIT: &str = "quit";
+
+// 菜单项 id 与文案键名
+const MENU_LABELS: &[(&str, &str)] = &[
+    (MENU_SHOW_MAIN, "tray.show_main"),
+    (MENU_TOGGLE_PET, "tray.toggle_pet"),
+    (MENU_TOGGLE_CLICK_THROUGH, "tray.toggle_click_through"),
+    (MENU_TOGGLE_PRIVACY, "tray.privacy"),
+    (MENU_RESTART_BACKEND, "tray.restart_backend"),
+    (MENU_QUIT, "tray.quit"),
+];
+
+fn item(id: &str) -> CustomMenuItem {
+    let key = MENU_LABELS
+        .iter()
+        .find(|(item, _)| *item == id)
+        .map_or(id, |(_, key)| *key);
+    CustomMenuItem::new(id, i18n::t(key))
+}
+
+pub fn build_tray() -> SystemTray {
+    let menu = SystemTrayMenu::new()
+        .add_item(item(MENU_SHOW_MAIN))
+        .add_item(item(MENU_TOGGLE_PET))
+        .add_item(item(MENU_TOGGLE_CLICK_THROUGH))
+        .add_item(item(MENU_TOGGLE_PRIVACY))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(item(MENU_RESTART_BACKEND))
+        .add_native_item(SystemTrayMenuItem::Separator)
+        .add_item(item(MENU_QUIT));
+
+    SystemTray::new().with_menu(menu).with_tooltip(&i18n::t("app.name"))
+}
+
+// 切换语言后按当前语言重设菜单文案
+pub fn retitle(app: &AppHandle) {
+    let tray = app.tray_handle();
+    for (id, key) in MENU_LABELS {
+        if let Err(e) = tray.get_item(id).set_title(i18n::t(key)) {
+            warn!("Failed to retitle tray item {}: {}", id, e);
+        }
+    }
+}
+
+pub fn show_main_window(app: &AppHandle) {
+    macos::activate(app);
+    if let Some(window) = app.get_window("main") {
+        window.unminimize().ok();
+        window.show().ok();
+        window.set_focus().ok();
+    }
+}
+
+pub fn handle_tray_event(app: &AppHandle, event: SystemTrayEvent) {
+    match event {
+        SystemTrayEvent::LeftClick { .. } => show_main_window(app),
+        SystemTrayEvent::MenuItemClick { id, .. } => handle_menu_click(app, &id),
+        _ => {}
+    }
+}
+
+fn handle_menu_click(app: &AppHandle, id: &str) {
+    match id {
+        MENU_SHOW_MAIN => show_main_window(app),
+        MENU_TOGGLE_PET => {
+            let handle = app.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = pet::toggle_desktop_pet(handle).await {
+                    warn!("Failed to toggle desktop pet: {}", e);
+                }
+            });
+        }
+        MENU_TOGGLE_CLICK_THROUGH => {
+            if let Err(e) = pet::toggle_pet_click_through(app.clone()) {
+                warn!("Failed to toggle pet click-through: {}", e);
+            }
+        }
+        MENU_TOGGLE_PRIVACY => {
+            let enabled = !app.state::<privacy::Privacy>().status().manual;
+            if let Err(e) = privacy::set_manual(app, enabled) {
+                warn!("Failed to toggle privacy mode: {}", e);
+            }
+        }
+        MENU_RESTART_BACKEND => {
+            // 重启会等待旧进程退出，放到后台线程避免阻塞事件循环
+            let handle = app.clone();
+            std::thread::spawn(move || match handle.state::<BackendManager>().restart() {
+                Ok(pid) => info!("Backend restarted from tray (pid {})", pid),
+                Err(e) => warn!("Failed to restart backend: {}", e),
+            });
+        }
+        MENU_QUIT => shutdown::exit(app, 0),
+        _ => {}
+    }
+}
@@ -0,0 +1,111 @@
+use serde::Deserialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use super::{Speech, Synthesizer, TtsProvider, Voice};
+use crate::audio::tts_stream::TtsStreamSettings;
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::network::{self, ClientConfig};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+// 用户在后端训练的克隆音色
+const VOICES_PATH: &str = "/api/voice/clones";
+
+#[derive(Debug, Deserialize)]
+struct VoicesResponse {
+    #[serde(default)]
+    data: Vec<VoiceClone>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VoiceClone {
+    asset_id: String,
+    voice_name: String,
+}
+
+pub struct BackendSynthesizer {
+    base_url: String,
+    settings: TtsStreamSettings,
+    token: Option<String>,
+    network: ClientConfig,
+}
+
+impl BackendSynthesizer {
+    pub fn new(app: &AppHandle, settings: TtsStreamSettings, token: Option<String>) -> Self {
+        Self {
+            base_url: app.state::<BackendManager>().url(),
+            settings,
+            token,
+            network: network::config(app),
+        }
+    }
+
+    fn client(&self) -> Result<reqwest::blocking::Client, AppError> {
+        self.network.blocking_client(REQUEST_TIMEOUT)
+    }
+
+    fn authorize(&self, request: reqwest::blocking::RequestBuilder) -> reqwest::blocking::RequestBuilder {
+        match self.token.as_deref() {
+            Some(token) => request.bearer_auth(token),
+            None => request,
+        }
+    }
+}
+
+// 播放时优先使用 tts_stream 的流式接口，这里一次取回完整音频
+impl Synthesizer for BackendSynthesizer {
+    fn provider(&self) -> TtsProvider {
+        TtsProvider::Backend
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn voices(&self) -> Result<Vec<Voice>, AppError> {
+        let request = self.client()?.get(format!("{}{}", self.base_url, VOICES_PATH));
+        let response = self
+            .authorize(request)
+            .send()
+            .map_err(|e| AppError::network("无法连接合成服务", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("合成服务返回错误", response.status()));
+        }
+        let body: VoicesResponse = response.json()?;
+        Ok(body
+            .data
+            .into_iter()
+            .map(|clone| Voice {
+                id: clone.asset_id,
+                name: clone.voice_name,
+                language: None,
+                provider: TtsProvider::Backend,
+            })
+            .collect())
+    }
+
+    fn synthesize(&self, text: &str, voice: Option<&str>) -> Result<Speech, AppError> {
+        let request = self
+            .client()?
+            .post(format!("{}{}", self.base_url, self.settings.stream_path))
+            .json(&serde_json::json!({
+                "text": text,
+                "voice": voice.or(self.settings.voice.as_deref()),
+                "format": "wav",
+                "sampleRate": self.settings.sample_rate,
+            }));
+        let response = self
+            .authorize(request)
+            .send()
+            .map_err(|e| AppError::network("无法连接合成服务", e))?;
+        if !response.status().is_success() {
+            return Err(AppError::status("合成服务返回错误", response.status()));
+        }
+        let bytes = response.bytes()?;
+        Ok(Speech::Audio {
+            bytes: bytes.to_vec(),
+            format: None,
+        })
+    }
+}
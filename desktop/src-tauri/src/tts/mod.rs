@@ -0,0 +1,249 @@
+// 语音合成：统一的 Synthesizer 接口，后端服务不可用时自动改用本地引擎
+pub mod backend;
+pub mod piper;
+pub mod system;
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Stdio};
+use tauri::{AppHandle, Manager};
+use tracing::{info, warn};
+
+use crate::audio::playback::{self, AudioFormat, AudioPlayer};
+use crate::audio::tts_stream;
+use crate::error::AppError;
+use crate::offline;
+use crate::{settings, AppState};
+
+pub use backend::BackendSynthesizer;
+pub use piper::PiperSynthesizer;
+pub use system::SystemSynthesizer;
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TtsProvider {
+    // Go 后端的流式合成接口
+    #[default]
+    Backend,
+    // 操作系统自带的语音（SAPI / AVSpeech / speech-dispatcher）
+    System,
+    // 本地 Piper 神经网络模型
+    Piper,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct TtsSettings {
+    pub provider: TtsProvider,
+    // 后端合成失败时依次尝试 Piper 和系统语音
+    pub fallback: bool,
+    pub system: system::SystemTtsSettings,
+    pub piper: piper::PiperSettings,
+}
+
+impl Default for TtsSettings {
+    fn default() -> Self {
+        Self {
+            provider: TtsProvider::Backend,
+            fallback: true,
+            system: system::SystemTtsSettings::default(),
+            piper: piper::PiperSettings::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Voice {
+    pub id: String,
+    pub name: String,
+    pub language: Option<String>,
+    pub provider: TtsProvider,
+}
+
+pub enum Speech {
+    // 合成好的音频，交给 AudioPlayer 播放
+    Audio { bytes: Vec<u8>, format: Option<AudioFormat> },
+    // 引擎已自行播放完毕（speech-dispatcher 不支持输出到文件）
+    #[cfg(all(unix, not(target_os = "macos")))]
+    Played,
+}
+
+pub trait Synthesizer: Send + Sync {
+    fn provider(&self) -> TtsProvider;
+
+    fn is_available(&self) -> bool;
+
+    fn voices(&self) -> Result<Vec<Voice>, AppError>;
+
+    // 在阻塞线程中调用，voice 为 None 时使用设置中的音色
+    fn synthesize(&self, text: &str, voice: Option<&str>) -> Result<Speech, AppError>;
+}
+
+pub fn synthesizer(
+    app: &AppHandle,
+    provider: TtsProvider,
+    token: Option<String>,
+) -> Result<Box<dyn Synthesizer>, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.clone();
+    Ok(match provider {
+        TtsProvider::Backend => Box::new(BackendSynthesizer::new(app, settings.audio.tts, token)),
+        TtsProvider::System => Box::new(SystemSynthesizer::new(settings.tts.system)),
+        TtsProvider::Piper => Box::new(PiperSynthesizer::new(app, settings.tts.piper)),
+    })
+}
+
+// 运行命令行合成引擎，text 通过标准输入传入以避免转义问题
+pub(crate) fn run_engine(command: &mut Command, text: &str) -> Result<Vec<u8>, AppError> {
+    command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        use std::os::windows::process::CommandExt;
+        command.creation_flags(crate::backend::CREATE_NO_WINDOW);
+    }
+
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .spawn()
+        .map_err(|e| AppError::unavailable(format!("无法启动合成引擎 {}", program), e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(AppError::Internal(format!(
+            "语音合成失败: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(output.stdout)
+}
+
+pub(crate) fn temp_wav() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("lingecho-tts-{}.wav", uuid::Uuid::new_v4()))
+}
+
+// 读取引擎输出的音频文件后删除
+pub(crate) fn take_file(path: &std::path::Path) -> Result<Speech, AppError> {
+    let bytes = std::fs::read(path).map_err(|e| format!("无法读取合成结果: {}", e));
+    std::fs::remove_file(path).ok();
+    Ok(Speech::Audio {
+        bytes: bytes?,
+        format: Some(AudioFormat::Wav),
+    })
+}
+
+fn play(app: &AppHandle, speech: Speech) -> Result<u64, AppError> {
+    match speech {
+        Speech::Audio { bytes, format } => {
+            app.state::<AudioPlayer>()
+                .play(app.clone(), bytes, format, playback::playback_settings(app)?)
+        }
+        // 没有经过 AudioPlayer，不会收到 playback-finished
+        #[cfg(all(unix, not(target_os = "macos")))]
+        Speech::Played => Ok(0),
+    }
+}
+
+async fn speak_local(
+    app: &AppHandle,
+    provider: TtsProvider,
+    text: String,
+    voice: Option<String>,
+) -> Result<u64, AppError> {
+    let handle = app.clone();
+    let speech = tauri::async_runtime::spawn_blocking(move || {
+        let synthesizer = synthesizer(&handle, provider, None)?;
+        if !synthesizer.is_available() {
+            return Err(AppError::unavailable(
+                format!("{:?} 语音合成不可用", synthesizer.provider()),
+                "未安装引擎或模型",
+            ));
+        }
+        synthesizer.synthesize(&text, voice.as_deref())
+    })
+    .await??;
+    play(app, speech)
+}
+
+// 合成并播放，返回播放 id；后端使用流式接口，失败时按设置降级到本地引擎
+pub async fn speak(
+    app: AppHandle,
+    text: String,
+    voice: Option<String>,
+    token: Option<String>,
+) -> Result<u64, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.tts.clone();
+    if settings.provider != TtsProvider::Backend {
+        return speak_local(&app, settings.provider, text, voice).await;
+    }
+
+    // 离线时跳过后端，直接改用本地引擎
+    let error = if settings.fallback && offline::prefer_local(&app) {
+        AppError::unavailable("后端语音合成不可用", "当前处于离线状态")
+    } else {
+        match tts_stream::speak(app.clone(), text.clone(), voice, token).await {
+            Ok(id) => return Ok(id),
+            Err(e) if settings.fallback && !text.trim().is_empty() => e,
+            Err(e) => return Err(e),
+        }
+    };
+    warn!("Backend TTS unavailable, falling back to local engines: {}", error);
+    // 后端音色对本地引擎无效，使用各引擎自己的设置
+    for provider in [TtsProvider::Piper, TtsProvider::System] {
+        match speak_local(&app, provider, text.clone(), None).await {
+            Ok(id) => {
+                info!("Spoke with fallback engine {:?}", provider);
+                return Ok(id);
+            }
+            Err(e) => warn!("Fallback TTS {:?} failed: {}", provider, e),
+        }
+    }
+    Err(error)
+}
+
+#[tauri::command]
+pub async fn synthesize(
+    app: AppHandle,
+    text: String,
+    voice: Option<String>,
+    token: Option<String>,
+) -> Result<u64, AppError> {
+    speak(app, text, voice, token).await
+}
+
+#[tauri::command]
+pub async fn list_voices(
+    app: AppHandle,
+    provider: Option<TtsProvider>,
+    token: Option<String>,
+) -> Result<Vec<Voice>, AppError> {
+    tauri::async_runtime::spawn_blocking(move || {
+        if let Some(provider) = provider {
+            return synthesizer(&app, provider, token)?.voices();
+        }
+        // 列出全部引擎时跳过不可用的引擎
+        let mut voices = Vec::new();
+        for provider in [TtsProvider::Backend, TtsProvider::System, TtsProvider::Piper] {
+            match synthesizer(&app, provider, token.clone())?.voices() {
+                Ok(list) => voices.extend(list),
+                Err(e) => warn!("Failed to list {:?} voices: {}", provider, e),
+            }
+        }
+        Ok(voices)
+    })
+    .await?
+}
+
+// 切换合成引擎并设置该引擎使用的音色
+#[tauri::command]
+pub fn set_voice(app: AppHandle, provider: TtsProvider, voice: Option<String>) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.tts.provider = provider;
+    match provider {
+        TtsProvider::Backend => settings.audio.tts.voice = voice,
+        TtsProvider::System => settings.tts.system.voice = voice,
+        TtsProvider::Piper => settings.tts.piper.voice = voice,
+    }
+    settings::save_and_notify(&app, &settings, &["tts", "audio"])
+}
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::process::Command;
+use tauri::AppHandle;
+
+use super::{run_engine, take_file, temp_wav, Speech, Synthesizer, TtsProvider, Voice};
+use crate::error::AppError;
+
+pub const MODELS_DIR: &str = "models/piper";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PiperSettings {
+    // Piper 命令行程序，不在 PATH 中时填写完整路径
+    pub command: String,
+    // 模型名称（应用目录下的 <name>.onnx），None 时使用找到的第一个模型
+    pub voice: Option<String>,
+}
+
+impl Default for PiperSettings {
+    fn default() -> Self {
+        Self {
+            command: "piper".to_string(),
+            voice: None,
+        }
+    }
+}
+
+pub struct PiperSynthesizer {
+    settings: PiperSettings,
+    models_dir: Option<PathBuf>,
+}
+
+impl PiperSynthesizer {
+    pub fn new(app: &AppHandle, settings: PiperSettings) -> Self {
+        let models_dir = app.path_resolver().app_data_dir().map(|dir| dir.join(MODELS_DIR));
+        Self { settings, models_dir }
+    }
+
+    // 模型目录下的 .onnx 文件，每个模型对应一个音色
+    fn models(&self) -> Vec<PathBuf> {
+        let Some(entries) = self.models_dir.as_ref().and_then(|dir| std::fs::read_dir(dir).ok()) else {
+            return Vec::new();
+        };
+        let mut models: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "onnx"))
+            .collect();
+        models.sort();
+        models
+    }
+
+    fn resolve(&self, voice: Option<&str>) -> Option<PathBuf> {
+        let models = self.models();
+        match voice.or(self.settings.voice.as_deref()) {
+            Some(name) => models
+                .into_iter()
+                .find(|path| path.file_stem().is_some_and(|stem| stem == name)),
+            None => models.into_iter().next(),
+        }
+    }
+}
+
+impl Synthesizer for PiperSynthesizer {
+    fn provider(&self) -> TtsProvider {
+        TtsProvider::Piper
+    }
+
+    fn is_available(&self) -> bool {
+        !self.models().is_empty()
+    }
+
+    fn voices(&self) -> Result<Vec<Voice>, AppError> {
+        Ok(self
+            .models()
+            .into_iter()
+            .filter_map(|path| {
+                let id = path.file_stem()?.to_string_lossy().to_string();
+                // Piper 模型按 zh_CN-huayan-medium 的格式命名
+                let language = id.split('-').next().map(|lang| lang.replace('_', "-"));
+                Some(Voice {
+                    name: id.clone(),
+                    id,
+                    language,
+                    provider: TtsProvider::Piper,
+                })
+            })
+            .collect())
+    }
+
+    fn synthesize(&self, text: &str, voice: Option<&str>) -> Result<Speech, AppError> {
+        let model = self
+            .resolve(voice)
+            .ok_or_else(|| AppError::NotFound("未找到 Piper 语音模型，请先下载模型".to_string()))?;
+        let output = temp_wav();
+        let mut command = Command::new(&self.settings.command);
+        command.arg("--model").arg(&model).arg("--output_file").arg(&output);
+        if let Err(e) = run_engine(&mut command, text) {
+            std::fs::remove_file(&output).ok();
+            return Err(e);
+        }
+        take_file(&output)
+    }
+}
@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+use super::{run_engine, Speech, Synthesizer, TtsProvider, Voice};
+#[cfg(not(all(unix, not(target_os = "macos"))))]
+use super::{take_file, temp_wav};
+use crate::error::AppError;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SystemTtsSettings {
+    // 系统音色名称，None 表示系统默认音色
+    pub voice: Option<String>,
+}
+
+pub struct SystemSynthesizer {
+    settings: SystemTtsSettings,
+}
+
+impl SystemSynthesizer {
+    pub fn new(settings: SystemTtsSettings) -> Self {
+        Self { settings }
+    }
+}
+
+// Windows：通过 PowerShell 调用 System.Speech（SAPI），参数用环境变量传递
+#[cfg(windows)]
+mod platform {
+    use super::*;
+
+    const PRELUDE: &str = "[Console]::InputEncoding = [Text.Encoding]::UTF8; \
+        [Console]::OutputEncoding = [Text.Encoding]::UTF8; \
+        Add-Type -AssemblyName System.Speech; \
+        $s = New-Object System.Speech.Synthesis.SpeechSynthesizer;";
+
+    fn powershell(script: &str) -> Command {
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &format!("{} {}", PRELUDE, script)]);
+        command
+    }
+
+    pub fn available() -> bool {
+        true
+    }
+
+    pub fn voices() -> Result<Vec<Voice>, AppError> {
+        let script = "$s.GetInstalledVoices() | Where-Object { $_.Enabled } | \
+            ForEach-Object { $_.VoiceInfo.Name + \"`t\" + $_.VoiceInfo.Culture.Name }";
+        let output = run_engine(&mut powershell(script), "")?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|line| {
+                let (name, culture) = line.trim().split_once('\t')?;
+                Some(Voice {
+                    id: name.to_string(),
+                    name: name.to_string(),
+                    language: Some(culture.to_string()).filter(|c| !c.is_empty()),
+                    provider: TtsProvider::System,
+                })
+            })
+            .collect())
+    }
+
+    pub fn synthesize(text: &str, voice: Option<&str>) -> Result<Speech, AppError> {
+        let output = temp_wav();
+        let script = "if ($env:LINGECHO_TTS_VOICE) { $s.SelectVoice($env:LINGECHO_TTS_VOICE) }; \
+            $s.SetOutputToWaveFile($env:LINGECHO_TTS_OUTPUT); \
+            $s.Speak([Console]::In.ReadToEnd()); $s.Dispose()";
+        let mut command = powershell(script);
+        command.env("LINGECHO_TTS_OUTPUT", &output);
+        command.env("LINGECHO_TTS_VOICE", voice.unwrap_or_default());
+        if let Err(e) = run_engine(&mut command, text) {
+            std::fs::remove_file(&output).ok();
+            return Err(e);
+        }
+        take_file(&output)
+    }
+}
+
+// macOS：say 命令使用 AVSpeech 的系统音色
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub fn available() -> bool {
+        true
+    }
+
+    // 每行格式为 "Name    zh_CN    # 示例句子"，音色名称可能包含空格
+    pub fn voices() -> Result<Vec<Voice>, AppError> {
+        let output = run_engine(Command::new("say").args(["-v", "?"]), "")?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|line| {
+                let head = line.split('#').next()?.trim();
+                let (name, language) = head.rsplit_once(char::is_whitespace)?;
+                let name = name.trim().to_string();
+                Some(Voice {
+                    id: name.clone(),
+                    name,
+                    language: Some(language.replace('_', "-")),
+                    provider: TtsProvider::System,
+                })
+            })
+            .collect())
+    }
+
+    pub fn synthesize(text: &str, voice: Option<&str>) -> Result<Speech, AppError> {
+        let output = temp_wav();
+        let mut command = Command::new("say");
+        command
+            .arg("-o")
+            .arg(&output)
+            .args(["--file-format=WAVE", "--data-format=LEI16@22050", "-f", "-"]);
+        if let Some(voice) = voice {
+            command.args(["-v", voice]);
+        }
+        if let Err(e) = run_engine(&mut command, text) {
+            std::fs::remove_file(&output).ok();
+            return Err(e);
+        }
+        take_file(&output)
+    }
+}
+
+// Linux：speech-dispatcher 只能直接播放，音色列表来自 spd-say -L
+#[cfg(all(unix, not(target_os = "macos")))]
+mod platform {
+    use super::*;
+
+    pub fn available() -> bool {
+        Command::new("spd-say")
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    // 第一行为表头：NAME LANGUAGE VARIANT
+    pub fn voices() -> Result<Vec<Voice>, AppError> {
+        let output = run_engine(Command::new("spd-say").arg("-L"), "")?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let name = parts.next()?.to_string();
+                Some(Voice {
+                    id: name.clone(),
+                    name,
+                    language: parts.next().map(str::to_string),
+                    provider: TtsProvider::System,
+                })
+            })
+            .collect())
+    }
+
+    pub fn synthesize(text: &str, voice: Option<&str>) -> Result<Speech, AppError> {
+        let mut command = Command::new("spd-say");
+        command.args(["--wait", "-e"]);
+        if let Some(voice) = voice {
+            command.args(["-y", voice]);
+        }
+        run_engine(&mut command, text)?;
+        Ok(Speech::Played)
+    }
+}
+
+impl Synthesizer for SystemSynthesizer {
+    fn provider(&self) -> TtsProvider {
+        TtsProvider::System
+    }
+
+    fn is_available(&self) -> bool {
+        platform::available()
+    }
+
+    fn voices(&self) -> Result<Vec<Voice>, AppError> {
+        platform::voices()
+    }
+
+    fn synthesize(&self, text: &str, voice: Option<&str>) -> Result<Speech, AppError> {
+        platform::synthesize(text, voice.or(self.settings.voice.as_deref()))
+    }
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "updater")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(feature = "updater")]
+use std::sync::Mutex;
+#[cfg(feature = "updater")]
+use tauri::updater::UpdateResponse;
+#[cfg(feature = "updater")]
+use tauri::{State, UpdaterEvent, Wry};
+use tauri::{AppHandle, Manager};
+#[cfg(feature = "updater")]
+use tracing::warn;
+use tracing::info;
+
+use crate::error::AppError;
+#[cfg(feature = "updater")]
+use crate::events::{self, AppEvent};
+use crate::{settings, shutdown, AppState};
+
+// 各发布通道的更新清单。自动更新需要签名公钥：用 `tauri signer generate` 生成后填入 tauri.conf.json，
+// 同时把 updater.active 改为 true，并以 `--features updater` 构建；未启用时检查更新直接返回错误
+const STABLE_ENDPOINT: &str =
+    "https://github.com/code-100-precent/LingEcho-App/releases/latest/download/latest.json";
+const BETA_ENDPOINT: &str =
+    "https://github.com/code-100-precent/LingEcho-App/releases/download/beta/latest.json";
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+#[cfg_attr(not(feature = "updater"), allow(dead_code))]
+impl UpdateChannel {
+    fn endpoint(&self) -> &'static str {
+        match self {
+            UpdateChannel::Stable => STABLE_ENDPOINT,
+            UpdateChannel::Beta => BETA_ENDPOINT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(not(feature = "updater"), allow(dead_code))]
+pub struct UpdateInfo {
+    pub available: bool,
+    pub channel: UpdateChannel,
+    pub current_version: String,
+    pub version: String,
+    pub date: Option<String>,
+    // 更新日志
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[cfg_attr(not(feature = "updater"), allow(dead_code))]
+pub struct UpdateProgress {
+    pub stage: &'static str,
+    pub downloaded: u64,
+    pub total: Option<u64>,
+    pub error: Option<String>,
+}
+
+// 保存最近一次检查到的更新，供 download_update 使用
+#[derive(Default)]
+pub struct Updater {
+    #[cfg(feature = "updater")]
+    pending: Mutex<Option<UpdateResponse<Wry>>>,
+    #[cfg(feature = "updater")]
+    downloaded: AtomicU64,
+}
+
+impl Updater {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(not(feature = "updater"))]
+fn disabled() -> AppError {
+    AppError::Unavailable {
+        message: "此版本未启用自动更新".to_string(),
+        details: None,
+    }
+}
+
+#[cfg(feature = "updater")]
+fn emit_progress(app: &AppHandle, progress: UpdateProgress) {
+    events::publish(app, AppEvent::UpdateProgress(progress));
+}
+
+// 把 Tauri 内置的更新事件转换为带累计进度的 update-progress 事件
+#[cfg(feature = "updater")]
+pub fn handle_event(app: &AppHandle, event: UpdaterEvent) {
+    let updater = app.state::<Updater>();
+    let progress = |stage, total, error| UpdateProgress {
+        stage,
+        downloaded: updater.downloaded.load(Ordering::SeqCst),
+        total,
+        error,
+    };
+
+    match event {
+        UpdaterEvent::Pending => {
+            updater.downloaded.store(0, Ordering::SeqCst);
+            emit_progress(app, progress("pending", None, None));
+        }
+        UpdaterEvent::DownloadProgress {
+            chunk_length,
+            content_length,
+        } => {
+            updater
+                .downloaded
+                .fetch_add(chunk_length as u64, Ordering::SeqCst);
+            emit_progress(app, progress("downloading", content_length, None));
+        }
+        UpdaterEvent::Downloaded => emit_progress(app, progress("downloaded", None, None)),
+        UpdaterEvent::Updated => {
+            info!("Update installed");
+            emit_progress(app, progress("installed", None, None));
+        }
+        UpdaterEvent::Error(e) => {
+            warn!("Update failed: {}", e);
+            emit_progress(app, progress("error", None, Some(e)));
+        }
+        _ => {}
+    }
+}
+
+#[cfg(feature = "updater")]
+#[tauri::command]
+pub async fn check_for_updates(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    updater: State<'_, Updater>,
+) -> Result<UpdateInfo, AppError> {
+    let channel = state.settings.lock()?.update_channel;
+    let response = tauri::updater::builder(app.clone())
+        .endpoints(&[channel.endpoint().to_string()])
+        .check()
+        .await
+        .map_err(|e| format!("检查更新失败: {}", e))?;
+
+    let info = UpdateInfo {
+        available: response.is_update_available(),
+        channel,
+        current_version: response.current_version().to_string(),
+        version: response.latest_version().to_string(),
+        date: response.date().map(|date| date.to_string()),
+        notes: response.body().cloned(),
+    };
+    info!(
+        "Update check on {:?} channel: current {}, latest {}",
+        channel, info.current_version, info.version
+    );
+
+    *updater.pending.lock()? = info.available.then_some(response);
+    Ok(info)
+}
+
+#[cfg(not(feature = "updater"))]
+#[tauri::command]
+pub async fn check_for_updates() -> Result<UpdateInfo, AppError> {
+    Err(disabled())
+}
+
+// 下载并安装更新包；Windows 上安装程序会接管并关闭应用，其他平台需调用 install_update_and_restart
+#[cfg(feature = "updater")]
+#[tauri::command]
+pub async fn download_update(updater: State<'_, Updater>) -> Result<(), AppError> {
+    let pending = updater
+        .pending
+        .lock()?
+        .take()
+        .ok_or_else(|| AppError::NotFound("没有可用的更新，请先检查更新".to_string()))?;
+    pending
+        .download_and_install()
+        .await
+        .map_err(|e| AppError::unavailable("下载更新失败", e))
+}
+
+#[cfg(not(feature = "updater"))]
+#[tauri::command]
+pub async fn download_update() -> Result<(), AppError> {
+    Err(disabled())
+}
+
+#[tauri::command]
+pub fn install_update_and_restart(app: AppHandle) {
+    info!("Restarting to apply update");
+    shutdown::restart(&app);
+}
+
+#[tauri::command]
+pub fn set_update_channel(app: AppHandle, channel: UpdateChannel) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    settings.update_channel = channel;
+    settings::save_and_notify(&app, &settings, &["update_channel"])
+}
@@ -0,0 +1,472 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::api::{self, Method};
+use crate::error::AppError;
+use crate::storage::now_millis;
+use crate::{i18n, network, AppState};
+
+const FORECAST_DAYS: u32 = 3;
+const IP_LOCATION_URL: &str = "https://ipapi.co/json/";
+// IP 定位结果的缓存时间，网络环境很少在一小时内变化
+const IP_LOCATION_TTL: Duration = Duration::from_secs(60 * 60);
+const BACKEND_TIMEOUT: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum WeatherProvider {
+    // 直接请求 Open-Meteo（或兼容的自建实例），不需要 API Key
+    #[default]
+    OpenMeteo,
+    // 由 Go 后端转发，后端按 Open-Meteo 的响应格式返回 data
+    Backend,
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum WeatherUnits {
+    #[default]
+    Metric,
+    Imperial,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WeatherSettings {
+    pub provider: WeatherProvider,
+    pub api_url: String,
+    pub geocoding_url: String,
+    pub backend_path: String,
+    pub units: WeatherUnits,
+    // 未指定地点时查询的城市
+    pub default_location: Option<String>,
+    // 没有默认城市时按 IP 粗略定位，需要用户同意后开启；系统定位需要各平台单独授权，暂不使用
+    pub allow_ip_location: bool,
+    pub cache_ttl_mins: u32,
+}
+
+impl Default for WeatherSettings {
+    fn default() -> Self {
+        Self {
+            provider: WeatherProvider::OpenMeteo,
+            api_url: "https://api.open-meteo.com/v1/forecast".to_string(),
+            geocoding_url: "https://geocoding-api.open-meteo.com/v1/search".to_string(),
+            backend_path: "/api/weather".to_string(),
+            units: WeatherUnits::Metric,
+            default_location: None,
+            allow_ip_location: false,
+            cache_ttl_mins: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LocationSource {
+    Query,
+    Default,
+    Ip,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WeatherLocation {
+    pub name: String,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub source: LocationSource,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CurrentWeather {
+    pub temperature: f64,
+    pub apparent_temperature: Option<f64>,
+    pub humidity: Option<f64>,
+    pub wind_speed: Option<f64>,
+    // WMO 天气代码
+    pub weather_code: u32,
+    pub description: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyForecast {
+    pub date: String,
+    pub weather_code: u32,
+    pub description: String,
+    pub temperature_max: Option<f64>,
+    pub temperature_min: Option<f64>,
+    pub precipitation_probability: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Weather {
+    pub location: WeatherLocation,
+    pub current: CurrentWeather,
+    pub daily: Vec<DailyForecast>,
+    pub units: WeatherUnits,
+    pub fetched_at: i64,
+    pub cached: bool,
+    // 刷新失败时返回过期的缓存
+    pub stale: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    current: ForecastCurrent,
+    #[serde(default)]
+    daily: Option<ForecastDaily>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastCurrent {
+    temperature_2m: f64,
+    apparent_temperature: Option<f64>,
+    relative_humidity_2m: Option<f64>,
+    wind_speed_10m: Option<f64>,
+    weather_code: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastDaily {
+    time: Vec<String>,
+    weather_code: Vec<Option<u32>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    #[serde(default)]
+    precipitation_probability_max: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeocodingResponse {
+    #[serde(default)]
+    results: Vec<GeocodingResult>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeocodingResult {
+    name: String,
+    latitude: f64,
+    longitude: f64,
+    admin1: Option<String>,
+    country: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IpLocation {
+    city: Option<String>,
+    latitude: f64,
+    longitude: f64,
+}
+
+// 天气按坐标、单位和数据源缓存；地名解析结果在本次运行期间一直有效
+#[derive(Default)]
+pub struct WeatherCache {
+    forecasts: Mutex<HashMap<String, (Instant, Weather)>>,
+    places: Mutex<HashMap<String, WeatherLocation>>,
+    ip_location: Mutex<Option<(Instant, WeatherLocation)>>,
+}
+
+impl WeatherCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+// WMO 天气代码对应的描述
+fn describe(code: u32) -> String {
+    let key = match code {
+        0 => "weather.clear",
+        1 | 2 => "weather.partly_cloudy",
+        3 => "weather.overcast",
+        45 | 48 => "weather.fog",
+        51..=57 => "weather.drizzle",
+        61..=67 => "weather.rain",
+        71..=77 => "weather.snow",
+        80..=82 => "weather.showers",
+        85 | 86 => "weather.snow_showers",
+        95..=99 => "weather.thunderstorm",
+        _ => "weather.unknown",
+    };
+    i18n::t(key)
+}
+
+// "31.23,121.47" 形式的坐标
+fn parse_coordinates(query: &str) -> Option<(f64, f64)> {
+    let (latitude, longitude) = query.split_once(',')?;
+    let latitude: f64 = latitude.trim().parse().ok()?;
+    let longitude: f64 = longitude.trim().parse().ok()?;
+    ((-90.0..=90.0).contains(&latitude) && (-180.0..=180.0).contains(&longitude)).then_some((latitude, longitude))
+}
+
+async fn geocode(
+    app: &AppHandle,
+    settings: &WeatherSettings,
+    query: &str,
+    source: LocationSource,
+) -> Result<WeatherLocation, AppError> {
+    if let Some((latitude, longitude)) = parse_coordinates(query) {
+        return Ok(WeatherLocation {
+            name: query.to_string(),
+            latitude,
+            longitude,
+            source,
+        });
+    }
+    let cache = app.state::<WeatherCache>();
+    let key = query.to_lowercase();
+    if let Some(place) = cache.places.lock()?.get(&key) {
+        return Ok(WeatherLocation {
+            source,
+            ..place.clone()
+        });
+    }
+
+    let language = i18n::current();
+    let language = language.split('-').next().unwrap_or("zh");
+    let response = network::client(app)
+        .get(&settings.geocoding_url)
+        .query(&[
+            ("name", query),
+            ("count", "1"),
+            ("language", language),
+            ("format", "json"),
+        ])
+        .send()
+        .await
+        .map_err(|e| AppError::network("无法查询地点", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("无法查询地点", response.status()));
+    }
+    let body: GeocodingResponse = response.json().await?;
+    let result = body
+        .results
+        .into_iter()
+        .next()
+        .ok_or_else(|| AppError::NotFound(format!("找不到地点: {}", query)))?;
+    let name = [Some(result.name), result.admin1, result.country]
+        .into_iter()
+        .flatten()
+        .fold(Vec::<String>::new(), |mut parts, part| {
+            if !parts.contains(&part) {
+                parts.push(part);
+            }
+            parts
+        })
+        .join(", ");
+    let place = WeatherLocation {
+        name,
+        latitude: result.latitude,
+        longitude: result.longitude,
+        source,
+    };
+    cache.places.lock()?.insert(key, place.clone());
+    Ok(place)
+}
+
+async fn locate_by_ip(app: &AppHandle) -> Result<WeatherLocation, AppError> {
+    let cache = app.state::<WeatherCache>();
+    if let Some((at, location)) = cache.ip_location.lock()?.as_ref() {
+        if at.elapsed() < IP_LOCATION_TTL {
+            return Ok(location.clone());
+        }
+    }
+    let response = network::client(app)
+        .get(IP_LOCATION_URL)
+        .send()
+        .await
+        .map_err(|e| AppError::network("无法获取当前位置", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("无法获取当前位置", response.status()));
+    }
+    let body: IpLocation = response.json().await?;
+    let location = WeatherLocation {
+        name: body
+            .city
+            .unwrap_or_else(|| format!("{:.2},{:.2}", body.latitude, body.longitude)),
+        latitude: body.latitude,
+        longitude: body.longitude,
+        source: LocationSource::Ip,
+    };
+    info!("Located {} by IP", location.name);
+    *cache.ip_location.lock()? = Some((Instant::now(), location.clone()));
+    Ok(location)
+}
+
+async fn resolve_location(
+    app: &AppHandle,
+    settings: &WeatherSettings,
+    query: Option<&str>,
+) -> Result<WeatherLocation, AppError> {
+    if let Some(query) = query {
+        return geocode(app, settings, query, LocationSource::Query).await;
+    }
+    if let Some(default) = settings
+        .default_location
+        .as_deref()
+        .filter(|name| !name.trim().is_empty())
+    {
+        return geocode(app, settings, default.trim(), LocationSource::Default).await;
+    }
+    if settings.allow_ip_location {
+        return locate_by_ip(app).await;
+    }
+    Err(AppError::invalid(
+        "需要指定地点，或在设置中填写默认城市、允许按 IP 定位",
+    ))
+}
+
+fn forecast_query(location: &WeatherLocation, units: WeatherUnits) -> Vec<(&'static str, String)> {
+    let mut query = vec![
+        ("latitude", location.latitude.to_string()),
+        ("longitude", location.longitude.to_string()),
+        (
+            "current",
+            "temperature_2m,apparent_temperature,relative_humidity_2m,wind_speed_10m,weather_code".to_string(),
+        ),
+        (
+            "daily",
+            "weather_code,temperature_2m_max,temperature_2m_min,precipitation_probability_max".to_string(),
+        ),
+        ("forecast_days", FORECAST_DAYS.to_string()),
+        ("timezone", "auto".to_string()),
+    ];
+    if units == WeatherUnits::Imperial {
+        query.push(("temperature_unit", "fahrenheit".to_string()));
+        query.push(("wind_speed_unit", "mph".to_string()));
+    }
+    query
+}
+
+async fn fetch_forecast(
+    app: &AppHandle,
+    settings: &WeatherSettings,
+    location: &WeatherLocation,
+) -> Result<ForecastResponse, AppError> {
+    let query = forecast_query(location, settings.units);
+    match settings.provider {
+        WeatherProvider::OpenMeteo => {
+            let response = network::client(app)
+                .get(&settings.api_url)
+                .query(&query)
+                .send()
+                .await
+                .map_err(|e| AppError::network("无法获取天气", e))?;
+            if !response.status().is_success() {
+                return Err(AppError::status("天气服务返回错误", response.status()));
+            }
+            Ok(response.json().await?)
+        }
+        WeatherProvider::Backend => {
+            let query: HashMap<String, String> =
+                query.into_iter().map(|(key, value)| (key.to_string(), value)).collect();
+            let value = api::request(
+                app,
+                Method::Get,
+                &settings.backend_path,
+                None,
+                Some(&query),
+                BACKEND_TIMEOUT,
+            )
+            .await?;
+            let data = value.get("data").cloned().unwrap_or(value);
+            Ok(serde_json::from_value(data)?)
+        }
+    }
+}
+
+fn build(location: WeatherLocation, response: ForecastResponse, units: WeatherUnits) -> Weather {
+    let current = CurrentWeather {
+        temperature: response.current.temperature_2m,
+        apparent_temperature: response.current.apparent_temperature,
+        humidity: response.current.relative_humidity_2m,
+        wind_speed: response.current.wind_speed_10m,
+        weather_code: response.current.weather_code,
+        description: describe(response.current.weather_code),
+    };
+    let daily = response
+        .daily
+        .map(|daily| {
+            daily
+                .time
+                .iter()
+                .enumerate()
+                .map(|(index, date)| {
+                    let code = daily.weather_code.get(index).copied().flatten().unwrap_or(u32::MAX);
+                    DailyForecast {
+                        date: date.clone(),
+                        weather_code: code,
+                        description: describe(code),
+                        temperature_max: daily.temperature_2m_max.get(index).copied().flatten(),
+                        temperature_min: daily.temperature_2m_min.get(index).copied().flatten(),
+                        precipitation_probability: daily.precipitation_probability_max.get(index).copied().flatten(),
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    Weather {
+        location,
+        current,
+        daily,
+        units,
+        fetched_at: now_millis(),
+        cached: false,
+        stale: false,
+    }
+}
+
+// 坐标保留两位小数（约 1 公里），附近的查询共用缓存
+fn cache_key(settings: &WeatherSettings, location: &WeatherLocation) -> String {
+    format!(
+        "{:?}:{:?}:{:.2},{:.2}",
+        settings.provider, settings.units, location.latitude, location.longitude
+    )
+}
+
+// location 可以是城市名或 "纬度,经度"，省略时使用默认城市或 IP 定位
+#[tauri::command]
+pub async fn get_weather(
+    app: AppHandle,
+    cache: State<'_, WeatherCache>,
+    location: Option<String>,
+) -> Result<Weather, AppError> {
+    let settings = app.state::<AppState>().settings.lock()?.weather.clone();
+    let query = location.as_deref().map(str::trim).filter(|query| !query.is_empty());
+    let place = resolve_location(&app, &settings, query).await?;
+    let key = cache_key(&settings, &place);
+    let ttl = Duration::from_secs(settings.cache_ttl_mins as u64 * 60);
+
+    let cached = cache.forecasts.lock()?.get(&key).cloned();
+    if let Some((at, weather)) = cached.as_ref() {
+        if at.elapsed() < ttl {
+            return Ok(Weather {
+                location: place,
+                cached: true,
+                ..weather.clone()
+            });
+        }
+    }
+
+    match fetch_forecast(&app, &settings, &place).await {
+        Ok(response) => {
+            let weather = build(place, response, settings.units);
+            cache.forecasts.lock()?.insert(key, (Instant::now(), weather.clone()));
+            Ok(weather)
+        }
+        Err(e) => match cached {
+            Some((_, weather)) => {
+                warn!("Failed to refresh weather, using cached data: {}", e);
+                Ok(Weather {
+                    location: place,
+                    cached: true,
+                    stale: true,
+                    ..weather
+                })
+            }
+            None => Err(e),
+        },
+    }
+}
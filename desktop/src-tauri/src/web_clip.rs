@@ -0,0 +1,515 @@
+use encoding_rs::Encoding;
+use kuchikiki::traits::TendrilSink;
+use kuchikiki::NodeRef;
+use reqwest::header::CONTENT_TYPE;
+use tauri::{AppHandle, Url};
+use tracing::{debug, info};
+
+use crate::error::AppError;
+use crate::network;
+use crate::notes::{self, Note};
+
+// 超过该大小的页面直接拒绝，避免下载大文件或二进制内容
+const MAX_PAGE_BYTES: usize = 5 * 1024 * 1024;
+// 在页面开头查找 <meta charset> 的范围
+const CHARSET_SNIFF_BYTES: usize = 4096;
+// 段落短于该长度时不参与正文评分
+const MIN_PARAGRAPH_CHARS: usize = 25;
+const CLIP_TAG: &str = "clip";
+
+// 这些元素不可能是正文，评分前先移除
+const JUNK_ELEMENTS: &str =
+    "script, style, noscript, template, iframe, svg, canvas, form, button, input, select, nav, header, footer, aside";
+// class 或 id 包含这些词的元素通常是侧栏、评论、广告等
+const NEGATIVE_HINTS: &[&str] = &[
+    "comment",
+    "sidebar",
+    "footer",
+    "advert",
+    "ads",
+    "banner",
+    "share",
+    "social",
+    "related",
+    "recommend",
+    "promo",
+    "popup",
+    "cookie",
+    "subscribe",
+    "newsletter",
+    "breadcrumb",
+    "menu",
+];
+const POSITIVE_HINTS: &[&str] = &["article", "content", "post", "entry", "main", "body", "text", "story"];
+
+struct Page {
+    title: String,
+    site_name: Option<String>,
+    markdown: String,
+}
+
+fn attr(node: &NodeRef, name: &str) -> Option<String> {
+    let element = node.as_element()?;
+    let value = element.attributes.borrow().get(name).map(str::to_string);
+    value
+}
+
+fn tag(node: &NodeRef) -> Option<String> {
+    node.as_element().map(|element| element.name.local.to_string())
+}
+
+fn meta(document: &NodeRef, keys: &[&str]) -> Option<String> {
+    for key in keys {
+        let selector = format!("meta[property=\"{0}\"], meta[name=\"{0}\"]", key);
+        let Ok(mut matches) = document.select(&selector) else {
+            continue;
+        };
+        if let Some(content) = matches
+            .find_map(|node| attr(node.as_node(), "content"))
+            .map(|content| content.trim().to_string())
+            .filter(|content| !content.is_empty())
+        {
+            return Some(content);
+        }
+    }
+    None
+}
+
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+// 页面声明的编码优先取响应头，其次是 <meta charset>，都没有时按 UTF-8 解码
+fn decode(bytes: &[u8], content_type: Option<&str>) -> String {
+    let from_header = content_type
+        .and_then(|value| value.split(';').find_map(|part| part.trim().strip_prefix("charset=")))
+        .and_then(|label| Encoding::for_label(label.trim_matches('"').as_bytes()));
+    let from_meta = || {
+        let head = String::from_utf8_lossy(&bytes[..bytes.len().min(CHARSET_SNIFF_BYTES)]).to_lowercase();
+        let start = head.find("charset=")? + "charset=".len();
+        let label: String = head[start..]
+            .trim_start_matches(['"', '\''])
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+            .collect();
+        Encoding::for_label(label.as_bytes())
+    };
+    let encoding = from_header.or_else(from_meta).unwrap_or(encoding_rs::UTF_8);
+    // BOM 优先于声明的编码
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+fn class_weight(node: &NodeRef) -> f64 {
+    let hints = format!(
+        "{} {}",
+        attr(node, "class").unwrap_or_default(),
+        attr(node, "id").unwrap_or_default()
+    )
+    .to_lowercase();
+    let mut weight = 0.0;
+    if NEGATIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight -= 25.0;
+    }
+    if POSITIVE_HINTS.iter().any(|hint| hints.contains(hint)) {
+        weight += 25.0;
+    }
+    weight
+}
+
+fn link_density(node: &NodeRef) -> f64 {
+    let total = node.text_contents().chars().count().max(1);
+    let linked: usize = node
+        .select("a")
+        .map(|links| links.map(|link| link.text_contents().chars().count()).sum())
+        .unwrap_or(0);
+    linked as f64 / total as f64
+}
+
+fn remove_junk(document: &NodeRef) {
+    if let Ok(junk) = document.select(JUNK_ELEMENTS) {
+        junk.collect::<Vec<_>>().iter().for_each(|node| node.as_node().detach());
+    }
+    // 负面提示的容器只在文字不多或链接占多数时移除，避免误删正文外层
+    if let Ok(containers) = document.select("div, section, ul, table") {
+        let noisy: Vec<NodeRef> = containers
+            .map(|node| node.as_node().clone())
+            .filter(|node| class_weight(node) < 0.0)
+            .filter(|node| node.text_contents().chars().count() < 500 || link_density(node) > 0.5)
+            .collect();
+        noisy.iter().for_each(|node| node.detach());
+    }
+}
+
+// 类似 Readability：每个段落按长度和逗号数给父元素和祖父元素加分，取得分最高且链接不多的容器
+fn main_content(document: &NodeRef) -> Option<NodeRef> {
+    let mut scores: Vec<(NodeRef, f64)> = Vec::new();
+    let mut add = |node: NodeRef, score: f64| match scores.iter_mut().find(|(candidate, _)| *candidate == node) {
+        Some((_, total)) => *total += score,
+        None => {
+            let base = class_weight(&node)
+                + match tag(&node).as_deref() {
+                    Some("article" | "main") => 30.0,
+                    Some("div") => 5.0,
+                    Some("pre" | "td" | "blockquote") => 3.0,
+                    _ => 0.0,
+                };
+            scores.push((node, base + score));
+        }
+    };
+    for paragraph in document.select("p, pre, td").ok()? {
+        let text = paragraph.text_contents();
+        let length = text.trim().chars().count();
+        if length < MIN_PARAGRAPH_CHARS {
+            continue;
+        }
+        let commas = text.matches([',', '，', '。']).count() as f64;
+        let score = 1.0 + commas + (length as f64 / 100.0).min(3.0);
+        let node = paragraph.as_node();
+        if let Some(parent) = node.parent() {
+            if let Some(grandparent) = parent.parent() {
+                add(grandparent, score / 2.0);
+            }
+            add(parent, score);
+        }
+    }
+    scores
+        .into_iter()
+        .map(|(node, score)| {
+            let density = link_density(&node);
+            (node, score * (1.0 - density))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(node, _)| node)
+}
+
+struct Markdown<'a> {
+    base: &'a Url,
+    out: String,
+    // 列表嵌套层级和每层的编号（无序列表为 None）
+    lists: Vec<Option<usize>>,
+    // 上一段文字以空白结尾，下一段内联内容前需要补一个空格
+    space: bool,
+}
+
+impl<'a> Markdown<'a> {
+    fn new(base: &'a Url) -> Self {
+        Self {
+            base,
+            out: String::new(),
+            lists: Vec::new(),
+            space: false,
+        }
+    }
+
+    fn absolute(&self, href: &str) -> Option<String> {
+        let url = self.base.join(href).ok()?;
+        matches!(url.scheme(), "http" | "https" | "mailto").then(|| url.to_string())
+    }
+
+    fn block_break(&mut self) {
+        self.space = false;
+        let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+        self.out.truncate(trimmed);
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+
+    // 原文中的空白只折叠成一个空格，避免在中文之间凭空插入空格
+    fn push(&mut self, text: &str) {
+        if self.space && !self.out.is_empty() && !self.out.ends_with([' ', '\n']) {
+            self.out.push(' ');
+        }
+        self.space = false;
+        self.out.push_str(text);
+    }
+
+    fn inline(&mut self, raw: &str, text: &str) {
+        if raw.starts_with(char::is_whitespace) {
+            self.space = true;
+        }
+        if !text.is_empty() {
+            self.push(text);
+        }
+        if raw.ends_with(char::is_whitespace) {
+            self.space = true;
+        }
+    }
+
+    fn children(&mut self, node: &NodeRef) {
+        for child in node.children() {
+            self.node(&child);
+        }
+    }
+
+    fn wrapped(&mut self, node: &NodeRef, marker: &str) {
+        let raw = node.text_contents();
+        let text = collapse(&raw);
+        let wrapped = if text.is_empty() {
+            text
+        } else {
+            format!("{}{}{}", marker, text, marker)
+        };
+        self.inline(&raw, &wrapped);
+    }
+
+    fn node(&mut self, node: &NodeRef) {
+        if let Some(text) = node.as_text() {
+            let raw = text.borrow();
+            self.inline(&raw, &collapse(&raw));
+            return;
+        }
+        let Some(name) = tag(node) else {
+            self.children(node);
+            return;
+        };
+        match name.as_str() {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse::<usize>().unwrap_or(1);
+                let text = collapse(&node.text_contents());
+                if !text.is_empty() {
+                    self.block_break();
+                    self.out.push_str(&format!("{} {}", "#".repeat(level), text));
+                    self.block_break();
+                }
+            }
+            "p" | "div" | "section" | "article" | "main" | "figure" | "figcaption" | "dl" | "dd" | "dt" => {
+                self.block_break();
+                self.children(node);
+                self.block_break();
+            }
+            "br" => self.out.push('\n'),
+            "hr" => {
+                self.block_break();
+                self.out.push_str("---");
+                self.block_break();
+            }
+            "strong" | "b" => self.wrapped(node, "**"),
+            "em" | "i" => self.wrapped(node, "*"),
+            "code" => self.wrapped(node, "`"),
+            "pre" => {
+                self.block_break();
+                self.out.push_str("```\n");
+                self.out.push_str(node.text_contents().trim_end());
+                self.out.push_str("\n```");
+                self.block_break();
+            }
+            "blockquote" => {
+                let mut inner = Markdown::new(self.base);
+                inner.children(node);
+                let quoted: Vec<String> = inner.out.trim().lines().map(|line| format!("> {}", line)).collect();
+                if !quoted.is_empty() {
+                    self.block_break();
+                    self.out.push_str(&quoted.join("\n"));
+                    self.block_break();
+                }
+            }
+            "a" => {
+                let raw = node.text_contents();
+                let text = collapse(&raw);
+                match attr(node, "href").and_then(|href| self.absolute(&href)) {
+                    Some(href) if !text.is_empty() => self.inline(&raw, &format!("[{}]({})", text, href)),
+                    _ => self.inline(&raw, &text),
+                }
+            }
+            "img" => {
+                if let Some(src) = attr(node, "src").and_then(|src| self.absolute(&src)) {
+                    let alt = collapse(&attr(node, "alt").unwrap_or_default());
+                    self.push(&format!("![{}]({})", alt, src));
+                }
+            }
+            "ul" | "ol" => {
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+                self.lists.push((name == "ol").then_some(0));
+                self.children(node);
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.block_break();
+                }
+            }
+            "li" => {
+                let depth = self.lists.len().saturating_sub(1);
+                let marker = match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}.", number)
+                    }
+                    _ => "-".to_string(),
+                };
+                let trimmed = self.out.trim_end_matches([' ', '\t']).len();
+                self.out.truncate(trimmed);
+                if !self.out.is_empty() && !self.out.ends_with('\n') {
+                    self.out.push('\n');
+                }
+                self.space = false;
+                self.out.push_str(&format!("{}{} ", "  ".repeat(depth), marker));
+                self.children(node);
+            }
+            "table" => self.table(node),
+            _ => self.children(node),
+        }
+    }
+
+    // 表格转为 Markdown 表格，第一行作为表头；单元格内只保留文字
+    fn table(&mut self, node: &NodeRef) {
+        let Ok(rows) = node.select("tr") else {
+            return;
+        };
+        let rows: Vec<Vec<String>> = rows
+            .map(|row| {
+                row.as_node()
+                    .children()
+                    .filter(|cell| matches!(tag(cell).as_deref(), Some("td" | "th")))
+                    .map(|cell| collapse(&cell.text_contents()).replace('|', "\\|"))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|cells| !cells.is_empty())
+            .collect();
+        let Some(columns) = rows.iter().map(Vec::len).max() else {
+            return;
+        };
+        self.block_break();
+        for (index, row) in rows.iter().enumerate() {
+            let mut cells = row.clone();
+            cells.resize(columns, String::new());
+            self.out.push_str(&format!("| {} |\n", cells.join(" | ")));
+            if index == 0 {
+                self.out.push_str(&format!("|{}\n", " --- |".repeat(columns)));
+            }
+        }
+        self.block_break();
+    }
+}
+
+fn to_markdown(node: &NodeRef, base: &Url) -> String {
+    let mut markdown = Markdown::new(base);
+    markdown.node(node);
+    // 合并多余的空行
+    let mut result = String::new();
+    let mut blank = 0;
+    for line in markdown.out.lines() {
+        if line.trim().is_empty() {
+            blank += 1;
+            if blank > 1 {
+                continue;
+            }
+        } else {
+            blank = 0;
+        }
+        result.push_str(line.trim_end());
+        result.push('\n');
+    }
+    result.trim().to_string()
+}
+
+fn extract(html: &str, base: &Url) -> Page {
+    let document = kuchikiki::parse_html().one(html);
+    let title = meta(&document, &["og:title", "twitter:title"])
+        .or_else(|| {
+            document
+                .select_first("title")
+                .ok()
+                .map(|title| collapse(&title.text_contents()))
+        })
+        .filter(|title| !title.is_empty())
+        .unwrap_or_else(|| base.host_str().unwrap_or_default().to_string());
+    let site_name = meta(&document, &["og:site_name", "application-name"]);
+
+    remove_junk(&document);
+    let content = main_content(&document)
+        .or_else(|| document.select_first("body").ok().map(|body| body.as_node().clone()))
+        .unwrap_or(document);
+    let mut markdown = to_markdown(&content, base);
+    // 正文里重复的大标题去掉，标题单独保存在笔记标题中
+    if let Some(rest) = markdown.strip_prefix(&format!("# {}", title)) {
+        markdown = rest.trim_start().to_string();
+    }
+    Page {
+        title,
+        site_name,
+        markdown,
+    }
+}
+
+async fn fetch(app: &AppHandle, url: &Url) -> Result<(Url, String), AppError> {
+    // reqwest 默认跟随最多 10 次重定向，最终地址用于解析相对链接
+    let mut response = network::client(app)
+        .get(url.as_str())
+        .header("Accept", "text/html,application/xhtml+xml;q=0.9,text/plain;q=0.8")
+        .send()
+        .await
+        .map_err(|e| AppError::network("无法获取网页", e))?;
+    if !response.status().is_success() {
+        return Err(AppError::status("无法获取网页", response.status()));
+    }
+    let final_url = response.url().clone();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if let Some(kind) = content_type.as_deref() {
+        let kind = kind.to_ascii_lowercase();
+        if !kind.starts_with("text/html") && !kind.starts_with("application/xhtml") && !kind.starts_with("text/plain") {
+            return Err(AppError::invalid(format!("不支持的网页类型: {}", kind)));
+        }
+    }
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > MAX_PAGE_BYTES)
+    {
+        return Err(AppError::invalid("网页过大"));
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| AppError::network("无法获取网页", e))?
+    {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_PAGE_BYTES {
+            return Err(AppError::invalid("网页过大"));
+        }
+    }
+    debug!("Fetched {} ({} bytes)", final_url, bytes.len());
+    let text = decode(&bytes, content_type.as_deref());
+    let is_plain = content_type.is_some_and(|kind| kind.to_ascii_lowercase().starts_with("text/plain"));
+    // 纯文本按 HTML 转义后包进 <pre>，走同一套转换
+    if is_plain {
+        let escaped = text.replace('&', "&amp;").replace('<', "&lt;");
+        return Ok((final_url, format!("<html><body><pre>{}</pre></body></html>", escaped)));
+    }
+    Ok((final_url, text))
+}
+
+// 抓取网页正文转为 Markdown，保存为带来源链接的笔记并写入知识库
+#[tauri::command]
+pub async fn clip_url(app: AppHandle, url: String, tags: Option<Vec<String>>) -> Result<Note, AppError> {
+    let url = Url::parse(url.trim()).map_err(|e| AppError::invalid(format!("链接无效: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::invalid(format!("不支持的链接协议: {}", url.scheme())));
+    }
+    let (final_url, html) = fetch(&app, &url).await?;
+    let base = final_url.clone();
+    let page = tauri::async_runtime::spawn_blocking(move || extract(&html, &base)).await?;
+    if page.markdown.is_empty() {
+        return Err(AppError::invalid("网页没有可提取的正文"));
+    }
+
+    let source = match &page.site_name {
+        Some(site) => format!("> {}: [{}]({})", site, page.title, final_url),
+        None => format!("> [{}]({})", page.title, final_url),
+    };
+    let body = format!("{}\n\n{}", source, page.markdown);
+    let mut tags = tags.unwrap_or_default();
+    tags.push(CLIP_TAG.to_string());
+    let note = notes::create(&app, Some(&page.title), body, tags, Some(final_url.to_string()))?;
+    info!("Clipped {} ({} chars)", final_url, page.markdown.chars().count());
+    Ok(note)
+}
@@ -0,0 +1,337 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State, Url};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::{network, secrets, settings, AppState};
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(15);
+// 返回给前端的响应内容上限
+const MAX_RESPONSE_CHARS: usize = 64 * 1024;
+const SECRET_MASK: &str = "******";
+
+// 模板中的 {{名称}} 替换为调用时传入的变量，{{secret:名称}} 替换为钥匙串中的密钥；
+// 可以追加 |url 或 |json 对值做转义，url 中的变量默认按 URL 编码
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Webhook {
+    pub name: String,
+    #[serde(default = "default_method")]
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub body: Option<String>,
+}
+
+fn default_method() -> String {
+    "POST".to_string()
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct WebhookSettings {
+    pub actions: Vec<Webhook>,
+}
+
+impl WebhookSettings {
+    pub fn validate(&self) -> Result<(), AppError> {
+        for (index, webhook) in self.actions.iter().enumerate() {
+            if webhook.name.trim().is_empty() {
+                return Err(AppError::invalid("Webhook 名称不能为空"));
+            }
+            if self.actions[..index].iter().any(|other| other.name == webhook.name) {
+                return Err(AppError::invalid(format!("Webhook 名称重复: {}", webhook.name)));
+            }
+            reqwest::Method::from_bytes(webhook.method.to_uppercase().as_bytes())
+                .map_err(|_| AppError::invalid(format!("不支持的请求方法: {}", webhook.method)))?;
+            if webhook.url.trim().is_empty() {
+                return Err(AppError::invalid("Webhook 地址不能为空"));
+            }
+        }
+        Ok(())
+    }
+}
+
+// dry_run 时只返回渲染后的请求，密钥以 ****** 显示
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookRun {
+    pub name: String,
+    pub method: String,
+    pub url: String,
+    pub headers: BTreeMap<String, String>,
+    pub body: Option<String>,
+    pub dry_run: bool,
+    pub status: Option<u16>,
+    pub response: Option<String>,
+    pub elapsed_ms: u64,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Escape {
+    None,
+    Url,
+    Json,
+}
+
+// 除 RFC 3986 的非保留字符外全部编码，变量中的 / ? & 不会改变 URL 结构
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+struct Renderer<'a> {
+    vars: &'a HashMap<String, String>,
+    // 为 false 时密钥替换为掩码，用于预览
+    reveal: bool,
+    secrets: HashMap<String, String>,
+}
+
+impl Renderer<'_> {
+    fn value(&mut self, name: &str) -> Result<String, AppError> {
+        let Some(secret) = name.strip_prefix("secret:") else {
+            return self
+                .vars
+                .get(name)
+                .cloned()
+                .ok_or_else(|| AppError::invalid(format!("缺少模板变量: {}", name)));
+        };
+        if let Some(value) = self.secrets.get(secret) {
+            return Ok(value.clone());
+        }
+        // 数据库密钥只供应用内部使用
+        if secret == secrets::DATABASE_KEY {
+            return Err(AppError::PermissionDenied(format!("不允许访问密钥 {}", secret)));
+        }
+        let value = secrets::get(secret)?.ok_or_else(|| AppError::NotFound(format!("钥匙串中没有密钥: {}", secret)))?;
+        self.secrets.insert(secret.to_string(), value.clone());
+        Ok(value)
+    }
+
+    fn render(&mut self, template: &str, default_escape: Escape) -> Result<String, AppError> {
+        let mut output = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find("{{") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find("}}")
+                .ok_or_else(|| AppError::invalid("模板中的 {{ 没有闭合"))?;
+            let (name, escape) = match after[..end].trim().split_once('|') {
+                Some((name, "url")) => (name.trim(), Escape::Url),
+                Some((name, "json")) => (name.trim(), Escape::Json),
+                Some((_, filter)) => return Err(AppError::invalid(format!("未知的模板过滤器: {}", filter))),
+                None => (after[..end].trim(), default_escape),
+            };
+            let secret = name.starts_with("secret:");
+            let value = if secret && !self.reveal {
+                // 先确认密钥存在，预览时也能发现配置错误
+                self.value(name)?;
+                SECRET_MASK.to_string()
+            } else {
+                self.value(name)?
+            };
+            match escape {
+                Escape::None => output.push_str(&value),
+                Escape::Url => output.push_str(&percent_encode(&value)),
+                // 只输出引号内的部分，模板里自行写引号
+                Escape::Json => {
+                    let quoted = serde_json::to_string(&value)?;
+                    output.push_str(&quoted[1..quoted.len() - 1]);
+                }
+            }
+            rest = &after[end + 2..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+}
+
+struct Rendered {
+    url: String,
+    headers: BTreeMap<String, String>,
+    body: Option<String>,
+}
+
+fn render_request(webhook: &Webhook, vars: &HashMap<String, String>, reveal: bool) -> Result<Rendered, AppError> {
+    let mut renderer = Renderer {
+        vars,
+        reveal,
+        secrets: HashMap::new(),
+    };
+    let url = renderer.render(webhook.url.trim(), Escape::Url)?;
+    let headers = webhook
+        .headers
+        .iter()
+        .map(|(name, value)| Ok((name.clone(), renderer.render(value, Escape::None)?)))
+        .collect::<Result<BTreeMap<_, _>, AppError>>()?;
+    let body = webhook
+        .body
+        .as_deref()
+        .map(|body| renderer.render(body, Escape::None))
+        .transpose()?;
+    Ok(Rendered { url, headers, body })
+}
+
+// 模板中通过 {{secret:名称}} 引用的钥匙串密钥
+pub fn secret_names(settings: &WebhookSettings) -> BTreeSet<String> {
+    let mut names = BTreeSet::new();
+    for webhook in &settings.actions {
+        let templates = std::iter::once(&webhook.url)
+            .chain(webhook.headers.values())
+            .chain(webhook.body.as_ref());
+        for template in templates {
+            let mut rest = template.as_str();
+            while let Some((_, after)) = rest.split_once("{{") {
+                let Some((inner, tail)) = after.split_once("}}") else {
+                    break;
+                };
+                let name = inner.split('|').next().unwrap_or_default().trim();
+                if let Some(secret) = name.strip_prefix("secret:") {
+                    names.insert(secret.to_string());
+                }
+                rest = tail;
+            }
+        }
+    }
+    names
+}
+
+fn find(app: &AppHandle, name: &str) -> Result<Webhook, AppError> {
+    app.state::<AppState>()
+        .settings
+        .lock()?
+        .webhooks
+        .actions
+        .iter()
+        .find(|webhook| webhook.name == name)
+        .cloned()
+        .ok_or_else(|| AppError::NotFound(format!("Webhook 不存在: {}", name)))
+}
+
+// 渲染并发送请求；非 2xx 的响应也作为结果返回，由调用方判断
+pub async fn run(
+    app: &AppHandle,
+    name: &str,
+    vars: &HashMap<String, String>,
+    dry_run: bool,
+) -> Result<WebhookRun, AppError> {
+    let webhook = find(app, name)?;
+    let method = webhook.method.to_uppercase();
+    let preview = render_request(&webhook, vars, false)?;
+    let mut run = WebhookRun {
+        name: webhook.name.clone(),
+        method: method.clone(),
+        url: preview.url,
+        headers: preview.headers,
+        body: preview.body,
+        dry_run,
+        status: None,
+        response: None,
+        elapsed_ms: 0,
+    };
+    if dry_run {
+        return Ok(run);
+    }
+
+    let Rendered { url, headers, body } = render_request(&webhook, vars, true)?;
+    let url = Url::parse(&url).map_err(|e| AppError::invalid(format!("链接无效: {}", e)))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(AppError::invalid(format!("不支持的链接协议: {}", url.scheme())));
+    }
+    let method = reqwest::Method::from_bytes(method.as_bytes())
+        .map_err(|_| AppError::invalid(format!("不支持的请求方法: {}", method)))?;
+    let mut request = network::client(app).request(method, url).timeout(REQUEST_TIMEOUT);
+    for (name, value) in &headers {
+        request = request.header(name, value);
+    }
+    if let Some(body) = body {
+        request = request.body(body);
+    }
+    let started = Instant::now();
+    let response = request
+        .send()
+        .await
+        .map_err(|e| AppError::network(format!("Webhook {} 请求失败", webhook.name), e))?;
+    let status = response.status();
+    let text = read_response(response).await;
+    run.status = Some(status.as_u16());
+    run.response = Some(text.chars().take(MAX_RESPONSE_CHARS).collect());
+    run.elapsed_ms = started.elapsed().as_millis() as u64;
+    if status.is_success() {
+        info!("Webhook {} returned {} in {} ms", webhook.name, status, run.elapsed_ms);
+    } else {
+        warn!("Webhook {} returned {}", webhook.name, status);
+    }
+    Ok(run)
+}
+
+// 只读取保存结果所需的部分（按每个字符最多 4 字节），响应体再大也不会整个读入内存
+async fn read_response(mut response: reqwest::Response) -> String {
+    let limit = MAX_RESPONSE_CHARS * 4;
+    let mut bytes = Vec::new();
+    while bytes.len() < limit {
+        match response.chunk().await {
+            Ok(Some(chunk)) => bytes.extend_from_slice(&chunk),
+            _ => break,
+        }
+    }
+    bytes.truncate(limit);
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[tauri::command]
+pub fn list_webhooks(state: State<'_, AppState>) -> Result<Vec<Webhook>, AppError> {
+    Ok(state.settings.lock()?.webhooks.actions.clone())
+}
+
+// 同名的 Webhook 会被替换
+#[tauri::command]
+pub fn save_webhook(app: AppHandle, webhook: Webhook) -> Result<Vec<Webhook>, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let mut webhooks = settings.webhooks.clone();
+    let webhook = Webhook {
+        name: webhook.name.trim().to_string(),
+        method: webhook.method.trim().to_uppercase(),
+        ..webhook
+    };
+    match webhooks.actions.iter_mut().find(|current| current.name == webhook.name) {
+        Some(current) => *current = webhook,
+        None => webhooks.actions.push(webhook),
+    }
+    webhooks.validate()?;
+    settings.webhooks = webhooks;
+    settings::save_and_notify(&app, &settings, &["webhooks"])?;
+    Ok(settings.webhooks.actions.clone())
+}
+
+#[tauri::command]
+pub fn delete_webhook(app: AppHandle, name: String) -> Result<bool, AppError> {
+    let state = app.state::<AppState>();
+    let mut settings = state.settings.lock()?;
+    let before = settings.webhooks.actions.len();
+    settings.webhooks.actions.retain(|webhook| webhook.name != name);
+    if settings.webhooks.actions.len() == before {
+        return Ok(false);
+    }
+    settings::save_and_notify(&app, &settings, &["webhooks"])?;
+    Ok(true)
+}
+
+#[tauri::command]
+pub async fn run_webhook(
+    app: AppHandle,
+    name: String,
+    vars: Option<HashMap<String, String>>,
+    dry_run: Option<bool>,
+) -> Result<WebhookRun, AppError> {
+    run(&app, &name, &vars.unwrap_or_default(), dry_run.unwrap_or(false)).await
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tauri::{Manager, Monitor, PhysicalPosition, PhysicalSize};
+use tracing::warn;
+
+use crate::AppState;
+
+pub const MAIN_LABEL: &str = "main";
+const SAVE_DEBOUNCE: Duration = Duration::from_millis(500);
+// 标题栏至少要有这么多像素落在某个显示器内，才认为保存的位置仍然可用
+const VISIBLE_MARGIN: i32 = 48;
+
+static CHANGE_GENERATION: AtomicU64 = AtomicU64::new(0);
+
+// 主窗口的正常（非最大化）位置与尺寸，单位为物理像素
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub maximized: bool,
+    pub fullscreen: bool,
+    pub monitor: Option<String>,
+}
+
+fn title_bar_visible(monitor: &Monitor, geometry: &WindowGeometry) -> bool {
+    let origin = monitor.position();
+    let size = monitor.size();
+    let left = geometry.x.max(origin.x);
+    let right = (geometry.x + geometry.width as i32).min(origin.x + size.width as i32);
+    let top_visible = geometry.y >= origin.y && geometry.y + VISIBLE_MARGIN <= origin.y + size.height as i32;
+    right - left >= VISIBLE_MARGIN && top_visible
+}
+
+// 显示器布局变化后，把窗口移回主显示器并限制尺寸
+fn sanitize(window: &tauri::Window, geometry: &WindowGeometry) -> WindowGeometry {
+    let monitors = window.available_monitors().unwrap_or_default();
+    if let Some(monitor) = monitors.iter().find(|monitor| title_bar_visible(monitor, geometry)) {
+        let size = monitor.size();
+        return WindowGeometry {
+            width: geometry.width.min(size.width),
+            height: geometry.height.min(size.height),
+            monitor: monitor.name().cloned(),
+            ..geometry.clone()
+        };
+    }
+
+    let Some(monitor) = window.primary_monitor().ok().flatten().or_else(|| monitors.into_iter().next()) else {
+        return geometry.clone();
+    };
+    let origin = monitor.position();
+    let size = monitor.size();
+    let width = geometry.width.min(size.width);
+    let height = geometry.height.min(size.height);
+    WindowGeometry {
+        x: origin.x + (size.width - width) as i32 / 2,
+        y: origin.y + (size.height - height) as i32 / 2,
+        width,
+        height,
+        monitor: monitor.name().cloned(),
+        ..geometry.clone()
+    }
+}
+
+// 在窗口显示之前调用，避免启动时窗口跳动
+pub fn restore(window: &tauri::Window) {
+    let saved = {
+        let state = window.state::<AppState>();
+        let Ok(settings) = state.settings.lock() else {
+            return;
+        };
+        settings.main_window.clone()
+    };
+    let Some(saved) = saved else {
+        window.center().ok();
+        return;
+    };
+
+    let geometry = sanitize(window, &saved);
+    window
+        .set_size(PhysicalSize::new(geometry.width, geometry.height))
+        .ok();
+    window
+        .set_position(PhysicalPosition::new(geometry.x, geometry.y))
+        .ok();
+    if geometry.fullscreen {
+        window.set_fullscreen(true).ok();
+    } else if geometry.maximized {
+        window.maximize().ok();
+    }
+}
+
+fn capture(window: &tauri::Window, previous: Option<&WindowGeometry>) -> Option<WindowGeometry> {
+    // 最小化时系统会把窗口移到屏幕外，不记录
+    if window.is_minimized().unwrap_or(false) {
+        return None;
+    }
+    let maximized = window.is_maximized().unwrap_or(false);
+    let fullscreen = window.is_fullscreen().unwrap_or(false);
+    let monitor = window
+        .current_monitor()
+        .ok()
+        .flatten()
+        .and_then(|monitor| monitor.name().cloned());
+
+    // 最大化或全屏时只更新状态，保留之前的正常尺寸，取消最大化后能恢复原位
+    if maximized || fullscreen {
+        let previous = previous.cloned()?;
+        return Some(WindowGeometry {
+            maximized,
+            fullscreen,
+            monitor,
+            ..previous
+        });
+    }
+
+    let position = window.outer_position().ok()?;
+    let size = window.inner_size().ok()?;
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+        maximized,
+        fullscreen,
+        monitor,
+    })
+}
+
+// 窗口移动或缩放时调用，连续变化只在停止后保存一次
+pub fn remember(window: &tauri::Window) {
+    let generation = CHANGE_GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+    let window = window.clone();
+
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(SAVE_DEBOUNCE).await;
+        if CHANGE_GENERATION.load(Ordering::SeqCst) != generation {
+            return;
+        }
+        save(&window);
+    });
+}
+
+// 立即保存当前位置，退出前调用
+pub fn save(window: &tauri::Window) {
+    let state = window.state::<AppState>();
+    let Ok(mut settings) = state.settings.lock() else {
+        return;
+    };
+    let Some(geometry) = capture(window, settings.main_window.as_ref()) else {
+        return;
+    };
+    if settings.main_window.as_ref() == Some(&geometry) {
+        return;
+    }
+    settings.main_window = Some(geometry);
+    if let Err(e) = state.store.save(&settings) {
+        warn!("Failed to save window state: {}", e);
+    }
+}
@@ -0,0 +1,184 @@
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+use tracing::{info, warn};
+
+use crate::error::AppError;
+use crate::retention::{self, shred};
+use crate::storage::PurgeScope;
+use crate::{calendar, i18n, platform, profiles, secrets, shutdown, webhooks, AppState};
+
+// 清除当前配置的全部数据：立即清空对话历史、删除录音和钥匙串条目，
+// 数据库和设置文件仍被占用，写入标记后重启，在下次启动打开之前删除
+
+const TOKEN_TTL: Duration = Duration::from_secs(120);
+const PENDING_FILE: &str = "wipe-pending";
+// 下载的模型和其他配置的数据不属于当前配置，保留
+const KEEP_DATA: &[&str] = &["models", "tessdata", "profiles"];
+const KEEP_CONFIG: &[&str] = &["profiles", "profiles.json"];
+// 临时目录中本应用创建的文件前缀
+const TEMP_PREFIX: &str = "lingecho-";
+
+const KNOWN_SECRETS: &[&str] = &[
+    secrets::EMBEDDING_API_KEY,
+    secrets::BACKEND_TOKEN,
+    secrets::PROXY_PASSWORD,
+    secrets::MQTT_PASSWORD,
+    secrets::LOCAL_API_TOKEN,
+    secrets::DATABASE_KEY,
+];
+
+// 确认令牌只能使用一次，过期后需要重新获取
+#[derive(Default)]
+pub struct Wipe {
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+impl Wipe {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WipeToken {
+    pub token: String,
+    pub expires_in_secs: u64,
+}
+
+fn shred_tree(path: &Path) {
+    let result = if path.is_dir() {
+        fs::read_dir(path)
+            .map(|entries| {
+                for entry in entries.flatten() {
+                    shred_tree(&entry.path());
+                }
+            })
+            .and_then(|_| fs::remove_dir(path))
+    } else {
+        shred(path)
+    };
+    if let Err(e) = result {
+        warn!("Failed to wipe {}: {}", path.display(), e);
+    }
+}
+
+fn shred_children(dir: &Path, keep: &[&str]) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if keep.iter().any(|name| entry.file_name() == *name) {
+            continue;
+        }
+        shred_tree(&entry.path());
+    }
+}
+
+// 在 setup 中打开数据库之前调用
+pub fn apply_pending(data_dir: &Path, config_dir: &Path) {
+    if !config_dir.join(PENDING_FILE).exists() {
+        return;
+    }
+    // 非默认配置的目录只属于该配置，不需要保留其他内容
+    let (keep_data, keep_config): (&[&str], &[&str]) = if profiles::current() == profiles::DEFAULT_PROFILE {
+        (KEEP_DATA, KEEP_CONFIG)
+    } else {
+        (&[], &[])
+    };
+    shred_children(data_dir, keep_data);
+    shred_children(config_dir, keep_config);
+    info!("Wiped data of profile {}", profiles::current());
+}
+
+fn wipe(app: &AppHandle) -> Result<(), AppError> {
+    let state = app.state::<AppState>();
+    let scope = PurgeScope {
+        messages: true,
+        audio: true,
+        memories: true,
+    };
+    let report = state.storage.purge(None, None, scope)?;
+    retention::remove_audio(app, &report.audio_paths);
+    if let Err(e) = state.storage.compact() {
+        warn!("Failed to compact database before wiping: {}", e);
+    }
+
+    let temp = std::env::temp_dir();
+    if let Ok(entries) = fs::read_dir(&temp) {
+        entries
+            .flatten()
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(TEMP_PREFIX))
+            .for_each(|entry| shred_tree(&entry.path()));
+    }
+
+    // 索引记录了所有写入过的密钥；早于索引写入的密钥按设置中能推算出的名称删除
+    let mut names: BTreeSet<String> = KNOWN_SECRETS.iter().map(|name| name.to_string()).collect();
+    {
+        let settings = state.settings.lock()?;
+        names.extend(webhooks::secret_names(&settings.webhooks));
+        names.extend(calendar::secret_names(&settings.calendar));
+    }
+    names.extend(secrets::stored_names());
+    // 索引最后删除，删除其他条目时会更新索引
+    for name in names.iter().map(String::as_str).chain([secrets::INDEX]) {
+        if let Err(e) = secrets::delete(name) {
+            warn!("Failed to delete keychain entry {}: {}", name, e);
+        }
+    }
+
+    let config_dir = profiles::config_dir(app)?;
+    fs::create_dir_all(&config_dir)?;
+    fs::write(config_dir.join(PENDING_FILE), b"")?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn request_wipe_token(wipe: State<'_, Wipe>) -> Result<WipeToken, AppError> {
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    *wipe.token.lock()? = Some((token.clone(), Instant::now() + TOKEN_TTL));
+    Ok(WipeToken {
+        token,
+        expires_in_secs: TOKEN_TTL.as_secs(),
+    })
+}
+
+// 先校验确认令牌，再通过系统认证（Windows Hello）或在没有可用认证时由系统对话框确认；成功后应用重启，不会返回
+#[tauri::command]
+pub async fn wipe_all_data(app: AppHandle, confirm_token: String) -> Result<(), AppError> {
+    let expected = app.state::<Wipe>().token.lock()?.take();
+    let valid =
+        expected.is_some_and(|(token, expires_at)| token == confirm_token.trim() && Instant::now() < expires_at);
+    if !valid {
+        return Err(AppError::PermissionDenied("确认令牌无效或已过期".into()));
+    }
+    let handle = app.clone();
+    let verified = tauri::async_runtime::spawn_blocking(move || {
+        let reason = i18n::t("wipe.reason");
+        Ok::<_, AppError>(match platform::current().verify_user(&reason)? {
+            Some(verified) => verified,
+            // 系统对话框由后端弹出，前端脚本无法代替用户确认
+            None => {
+                info!("No system authentication available, asking for confirmation");
+                let window = handle.get_window("main");
+                tauri::api::dialog::blocking::ask(window.as_ref(), reason, i18n::t("wipe.confirm_message"))
+            }
+        })
+    })
+    .await??;
+    if !verified {
+        return Err(AppError::PermissionDenied("系统认证未通过".into()));
+    }
+    info!("User confirmed data wipe");
+
+    warn!("Wiping all data of profile {}", profiles::current());
+    shutdown::stop_services(&app);
+    let handle = app.clone();
+    tauri::async_runtime::spawn_blocking(move || wipe(&handle)).await??;
+    shutdown::restart(&app);
+    Ok(())
+}
@@ -0,0 +1,218 @@
+use futures_util::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Manager, State};
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+use tracing::{error, info, warn};
+
+use crate::backend::BackendManager;
+use crate::error::AppError;
+use crate::events::{self, AppEvent};
+use crate::i18n;
+use crate::network;
+use crate::notifications;
+use crate::AppState;
+
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct WsStatus {
+    pub connected: bool,
+    pub url: Option<String>,
+}
+
+// 后端 WebSocket 的常驻客户端，收到的消息以 ws-message 事件转发给所有窗口
+#[derive(Default)]
+pub struct WsBridge {
+    task: Mutex<Option<JoinHandle<()>>>,
+    outgoing: Mutex<Option<mpsc::UnboundedSender<Message>>>,
+    url: Mutex<Option<String>>,
+    connected: AtomicBool,
+}
+
+impl WsBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn status(&self) -> WsStatus {
+        WsStatus {
+            connected: self.connected.load(Ordering::SeqCst),
+            url: self.url.lock().ok().and_then(|url| url.clone()),
+        }
+    }
+
+    // token 在重连时复用；重复调用会替换现有连接
+    pub fn connect(&self, app: AppHandle, token: Option<String>) -> Result<(), AppError> {
+        self.disconnect();
+
+        let path = app.state::<AppState>().settings.lock()?.backend.ws_path.clone();
+        let port = app.state::<BackendManager>().port();
+        let url = format!("ws://localhost:{}{}", port, path);
+        *self.url.lock()? = Some(url.clone());
+
+        let task = tauri::async_runtime::spawn(run_bridge(app, url, token));
+        *self.task.lock()? = Some(task);
+        Ok(())
+    }
+
+    pub fn disconnect(&self) {
+        if let Some(task) = self.task.lock().ok().and_then(|mut task| task.take()) {
+            task.abort();
+        }
+        if let Ok(mut outgoing) = self.outgoing.lock() {
+            outgoing.take();
+        }
+        if let Ok(mut url) = self.url.lock() {
+            url.take();
+        }
+        self.connected.store(false, Ordering::SeqCst);
+    }
+
+    pub fn send(&self, message: Message) -> Result<(), AppError> {
+        let outgoing = self.outgoing.lock()?;
+        let sender = outgoing
+            .as_ref()
+            .ok_or_else(|| AppError::unavailable("WebSocket 未连接", "请先连接后端"))?;
+        sender
+            .send(message)
+            .map_err(|e| AppError::unavailable("WebSocket 连接已断开", e))
+    }
+}
+
+fn set_connected(app: &AppHandle, connected: bool) {
+    let bridge = app.state::<WsBridge>();
+    if bridge.connected.swap(connected, Ordering::SeqCst) != connected {
+        events::publish(app, AppEvent::WsStatusChanged(bridge.status()));
+    }
+}
+
+// 文本消息按 JSON 解析后转发，无法解析时原样作为字符串转发
+fn forward(app: &AppHandle, text: String) {
+    let payload = serde_json::from_str::<Value>(&text).unwrap_or(Value::String(text));
+    notify_if_hidden(app, &payload);
+    events::publish(app, AppEvent::WsMessage(payload));
+}
+
+// 主窗口不在前台时，把后端推送的 notification 消息转为系统通知
+fn notify_if_hidden(app: &AppHandle, payload: &Value) {
+    if payload["type"] != "notification" {
+        return;
+    }
+    let focused = app
+        .get_window("main")
+        .and_then(|window| window.is_focused().ok())
+        .unwrap_or(false);
+    if focused {
+        return;
+    }
+    let fallback = i18n::t("app.name");
+    let title = payload["title"].as_str().unwrap_or(&fallback);
+    let body = payload["content"].as_str().unwrap_or_default();
+    let action = payload["url"].as_str().map(|url| format!("lingecho://{}", url.trim_start_matches('/')));
+    if let Err(e) = notifications::send(app, notifications::CATEGORY_ASSISTANT, title, body, action) {
+        warn!("{}", e);
+    }
+}
+
+// 每次连接时读取网络设置，代理和额外的 CA 证书与 HTTP 请求一致
+async fn open(app: &AppHandle, target: &str) -> Result<WebSocketStream<Box<dyn network::Stream>>, AppError> {
+    let url = reqwest::Url::parse(target).map_err(|e| AppError::Internal(format!("无效的 WebSocket 地址: {}", e)))?;
+    let host = url.host_str().unwrap_or("localhost");
+    let port = url.port_or_known_default().unwrap_or(80);
+    let stream = network::config(app).connect(host, port, url.scheme() == "wss").await?;
+    let (socket, _) = tokio_tungstenite::client_async(target, stream)
+        .await
+        .map_err(|e| AppError::unavailable("WebSocket 握手失败", e))?;
+    Ok(socket)
+}
+
+async fn run_bridge(app: AppHandle, url: String, token: Option<String>) {
+    let target = match token.as_deref() {
+        Some(token) => format!("{}?token={}", url, token),
+        None => url.clone(),
+    };
+    let mut backoff = RECONNECT_MIN;
+
+    loop {
+        match open(&app, &target).await {
+            Ok(socket) => {
+                info!("WebSocket bridge connected to {}", url);
+                backoff = RECONNECT_MIN;
+                let (mut writer, mut reader) = socket.split();
+                let (tx, mut rx) = mpsc::unbounded_channel();
+                if let Ok(mut outgoing) = app.state::<WsBridge>().outgoing.lock() {
+                    *outgoing = Some(tx);
+                }
+                set_connected(&app, true);
+
+                loop {
+                    tokio::select! {
+                        incoming = reader.next() => match incoming {
+                            Some(Ok(Message::Text(text))) => forward(&app, text),
+                            Some(Ok(Message::Binary(bytes))) => forward(&app, String::from_utf8_lossy(&bytes).into_owned()),
+                            Some(Ok(Message::Close(_))) | None => break,
+                            Some(Ok(_)) => {}
+                            Some(Err(e)) => {
+                                error!("WebSocket bridge error: {}", e);
+                                break;
+                            }
+                        },
+                        outgoing = rx.recv() => match outgoing {
+                            Some(message) => {
+                                if let Err(e) = writer.send(message).await {
+                                    warn!("Failed to send WebSocket message: {}", e);
+                                    break;
+                                }
+                            }
+                            None => break,
+                        },
+                    }
+                }
+
+                if let Ok(mut outgoing) = app.state::<WsBridge>().outgoing.lock() {
+                    outgoing.take();
+                }
+                set_connected(&app, false);
+                info!("WebSocket bridge disconnected, reconnecting");
+            }
+            Err(e) => warn!("WebSocket bridge failed to connect to {}: {}", url, e),
+        }
+
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(RECONNECT_MAX);
+    }
+}
+
+#[tauri::command]
+pub fn connect_ws(app: AppHandle, bridge: State<'_, WsBridge>, token: Option<String>) -> Result<(), AppError> {
+    bridge.connect(app.clone(), token)
+}
+
+#[tauri::command]
+pub fn disconnect_ws(app: AppHandle, bridge: State<'_, WsBridge>) {
+    bridge.disconnect();
+    events::publish(&app, AppEvent::WsStatusChanged(bridge.status()));
+}
+
+#[tauri::command]
+pub fn get_ws_status(bridge: State<'_, WsBridge>) -> WsStatus {
+    bridge.status()
+}
+
+// 对象和数组按 JSON 文本发送，字符串原样发送
+#[tauri::command]
+pub fn send_ws_message(bridge: State<'_, WsBridge>, message: Value) -> Result<(), AppError> {
+    let text = match message {
+        Value::String(text) => text,
+        other => other.to_string(),
+    };
+    bridge.send(Message::Text(text))
+}
@@ -15,8 +15,33 @@ import CredentialManager from "@/pages/CredentialManager.tsx";
 import ProtectedRoute from "@/components/Auth/ProtectedRoute.tsx";
 import JSTemplateManager from "@/pages/JSTemplateManager.tsx";
 import DesktopPetWindow from "@/pages/DesktopPetWindow.tsx";
+import QuickAskWindow from "@/pages/QuickAskWindow.tsx";
+import CaptionsWindow from "@/pages/CaptionsWindow.tsx";
+import PetBubbleWindow from "@/pages/PetBubbleWindow.tsx";
+import ProfilePicker from "@/pages/ProfilePicker.tsx";
+
+// 后端单独打开的窗口：不套主界面的背景、PWA 提示和通知，页面自己控制背景
+const OVERLAY_PATHS = ['/quick-ask-window', '/captions-window', '/pet-bubble-window', '/profile-picker'];
+
+function OverlayApp() {
+    return (
+        <ErrorBoundary>
+            <Router>
+                <Routes>
+                    <Route path="/quick-ask-window" element={<QuickAskWindow />} />
+                    <Route path="/captions-window" element={<CaptionsWindow />} />
+                    <Route path="/pet-bubble-window" element={<PetBubbleWindow />} />
+                    <Route path="/profile-picker" element={<ProfilePicker />} />
+                </Routes>
+            </Router>
+        </ErrorBoundary>
+    );
+}
 
 function App() {
+    if (OVERLAY_PATHS.includes(window.location.pathname)) {
+        return <OverlayApp />;
+    }
 
     return (
         <ErrorBoundary>
@@ -63,7 +63,7 @@ export const DesktopPetPanel: React.FC<DesktopPetPanelProps> = ({
   const handleToggleClickThrough = async (enabled: boolean) => {
     try {
       if (typeof window !== 'undefined' && window.__TAURI__) {
-        await invoke('set_desktop_pet_click_through', { clickThrough: enabled });
+        await invoke('set_pet_click_through', { enabled });
       }
       setIsClickThrough(enabled);
     } catch (error) {
@@ -0,0 +1,26 @@
+import { useEffect, useRef } from 'react'
+import { listen } from '@tauri-apps/api/event'
+
+// 订阅后端推送的事件，组件卸载时自动取消订阅
+export function useDesktopEvent<T>(event: string, handler: (payload: T) => void) {
+  const handlerRef = useRef(handler)
+  handlerRef.current = handler
+
+  useEffect(() => {
+    let unlisten: (() => void) | null = null
+    let cancelled = false
+    listen<T>(event, (e) => handlerRef.current(e.payload))
+      .then((fn) => {
+        if (cancelled) {
+          fn()
+        } else {
+          unlisten = fn
+        }
+      })
+      .catch((error) => console.error(`订阅 ${event} 失败:`, error))
+    return () => {
+      cancelled = true
+      unlisten?.()
+    }
+  }, [event])
+}
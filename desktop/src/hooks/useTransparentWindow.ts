@@ -0,0 +1,15 @@
+import { useEffect } from 'react'
+
+// 无边框的小窗口（快速提问、字幕、桌宠气泡）需要透明背景，只显示页面中的内容
+export function useTransparentWindow(title: string) {
+  useEffect(() => {
+    document.title = title
+    for (const element of [document.documentElement, document.body, document.getElementById('root')]) {
+      if (element) {
+        element.style.background = 'transparent'
+        element.style.margin = '0'
+        element.style.padding = '0'
+      }
+    }
+  }, [title])
+}
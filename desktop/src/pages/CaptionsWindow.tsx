@@ -0,0 +1,69 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useDesktopEvent } from '@/hooks/useDesktopEvent';
+import { useTransparentWindow } from '@/hooks/useTransparentWindow';
+
+interface CaptionCue {
+    playback: number;
+    sentence: string;
+    word: string;
+    offset: number;
+    start_ms: number;
+    duration_ms: number;
+}
+
+interface SettingsChanged {
+    changed: string[];
+    settings: { captions?: { font_size?: number } };
+}
+
+const DEFAULT_FONT_SIZE = 28;
+
+// 朗读字幕：显示当前整句，已读到的部分高亮；窗口本身透明且不接收鼠标
+const CaptionsWindow: React.FC = () => {
+    const [cue, setCue] = useState<CaptionCue | null>(null);
+    const [fontSize, setFontSize] = useState(DEFAULT_FONT_SIZE);
+
+    useTransparentWindow('字幕 - 声驭智核');
+
+    useEffect(() => {
+        invoke<{ captions?: { font_size?: number } }>('get_settings')
+            .then((settings) => setFontSize(settings.captions?.font_size ?? DEFAULT_FONT_SIZE))
+            .catch((e) => console.error('读取字幕设置失败:', e));
+    }, []);
+
+    useDesktopEvent<CaptionCue>('caption', setCue);
+
+    // 只清除同一次播放的字幕，避免上一段的清除事件盖掉新开始的朗读
+    useDesktopEvent<{ playback: number }>('caption-cleared', ({ playback }) => {
+        setCue((current) => (current && current.playback === playback ? null : current));
+    });
+
+    useDesktopEvent<SettingsChanged>('settings-changed', ({ changed, settings }) => {
+        if (changed.includes('captions')) {
+            setFontSize(settings.captions?.font_size ?? DEFAULT_FONT_SIZE);
+        }
+    });
+
+    if (!cue) {
+        return null;
+    }
+
+    const chars = Array.from(cue.sentence);
+    const spoken = chars.slice(0, cue.offset).join('');
+    const rest = chars.slice(cue.offset).join('');
+
+    return (
+        <div className="flex h-screen w-screen items-end justify-center pb-4 select-none">
+            <div
+                className="max-w-full rounded-lg bg-black/60 px-4 py-2 text-center leading-snug"
+                style={{ fontSize, textShadow: '0 1px 3px rgba(0, 0, 0, 0.8)' }}
+            >
+                <span className="text-yellow-300">{spoken}</span>
+                <span className="text-white">{rest}</span>
+            </div>
+        </div>
+    );
+};
+
+export default CaptionsWindow;
@@ -0,0 +1,47 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { useDesktopEvent } from '@/hooks/useDesktopEvent';
+import { useTransparentWindow } from '@/hooks/useTransparentWindow';
+
+interface BubbleMessage {
+    id: number;
+    text: string;
+    duration_ms: number;
+}
+
+// 桌宠气泡：显示和到时隐藏都由后端控制，页面只负责渲染当前消息，点击气泡提前关闭
+const PetBubbleWindow: React.FC = () => {
+    const [message, setMessage] = useState<BubbleMessage | null>(null);
+
+    useTransparentWindow('桌宠气泡 - 声驭智核');
+
+    // 窗口创建时推送的第一条消息可能早于页面加载，需要主动读取一次
+    useEffect(() => {
+        invoke<BubbleMessage | null>('get_pet_bubble')
+            .then((current) => current && setMessage(current))
+            .catch((e) => console.error('读取气泡消息失败:', e));
+    }, []);
+
+    useDesktopEvent<BubbleMessage>('pet-bubble', setMessage);
+
+    const dismiss = () => {
+        invoke('hide_pet_bubble').catch((e) => console.error('隐藏气泡失败:', e));
+    };
+
+    if (!message) {
+        return null;
+    }
+
+    return (
+        <div className="flex h-screen w-screen items-center justify-center p-1 select-none">
+            <div
+                onClick={dismiss}
+                className="max-h-full w-full cursor-pointer overflow-hidden rounded-2xl border border-gray-200 bg-white/95 px-3 py-2 text-sm leading-relaxed text-gray-800 shadow-md dark:border-gray-700 dark:bg-gray-800/95 dark:text-gray-100"
+            >
+                <p className="line-clamp-3 break-words">{message.text}</p>
+            </div>
+        </div>
+    );
+};
+
+export default PetBubbleWindow;
@@ -0,0 +1,135 @@
+import React, { useEffect, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Check, Plus, User } from 'lucide-react';
+
+interface Profile {
+    name: string;
+    created_at: number;
+}
+
+interface ProfileList {
+    current: string;
+    profiles: Profile[];
+    pick_on_startup: boolean;
+}
+
+const errorMessage = (e: any) => e?.message ?? String(e);
+
+// 配置选择窗口：选择当前配置只关闭窗口，选择其他配置后应用会重启；关闭窗口继续使用当前配置
+const ProfilePicker: React.FC = () => {
+    const [list, setList] = useState<ProfileList | null>(null);
+    const [name, setName] = useState('');
+    const [error, setError] = useState<string | null>(null);
+    const [switching, setSwitching] = useState<string | null>(null);
+
+    const load = async () => {
+        try {
+            setList(await invoke<ProfileList>('list_profiles'));
+        } catch (e) {
+            setError(errorMessage(e));
+        }
+    };
+
+    useEffect(() => {
+        document.title = '选择配置 - 声驭智核';
+        load();
+    }, []);
+
+    const create = async () => {
+        if (!name.trim()) return;
+        setError(null);
+        try {
+            await invoke('create_profile', { name: name.trim() });
+            setName('');
+            await load();
+        } catch (e) {
+            setError(errorMessage(e));
+        }
+    };
+
+    const choose = async (profile: string) => {
+        setError(null);
+        setSwitching(profile);
+        try {
+            await invoke<boolean>('switch_profile', { name: profile });
+        } catch (e) {
+            setError(errorMessage(e));
+            setSwitching(null);
+        }
+    };
+
+    const togglePickOnStartup = async (enabled: boolean) => {
+        setError(null);
+        try {
+            await invoke('set_profile_picker', { enabled });
+            setList((current) => (current ? { ...current, pick_on_startup: enabled } : current));
+        } catch (e) {
+            setError(errorMessage(e));
+        }
+    };
+
+    return (
+        <div className="flex h-screen flex-col bg-gray-50 p-4 text-gray-900 dark:bg-gray-900 dark:text-gray-100">
+            <h1 className="mb-3 text-lg font-semibold">选择配置</h1>
+
+            <div className="flex-1 space-y-2 overflow-y-auto">
+                {list?.profiles.map((profile) => (
+                    <button
+                        key={profile.name}
+                        type="button"
+                        onClick={() => choose(profile.name)}
+                        disabled={switching !== null}
+                        className="flex w-full items-center gap-3 rounded-lg border border-gray-200 bg-white px-3 py-2 text-left hover:border-blue-400 disabled:opacity-60 dark:border-gray-700 dark:bg-gray-800"
+                    >
+                        <User className="h-5 w-5 text-gray-500" />
+                        <div className="flex-1">
+                            <div className="font-medium">{profile.name}</div>
+                            {profile.created_at > 0 && (
+                                <div className="text-xs text-gray-500">
+                                    创建于 {new Date(profile.created_at).toLocaleDateString()}
+                                </div>
+                            )}
+                        </div>
+                        {profile.name === list.current && <Check className="h-5 w-5 text-blue-600" />}
+                        {switching === profile.name && profile.name !== list.current && (
+                            <span className="text-xs text-gray-500">正在重启…</span>
+                        )}
+                    </button>
+                ))}
+            </div>
+
+            <div className="mt-3 flex gap-2">
+                <input
+                    value={name}
+                    onChange={(e) => setName(e.target.value)}
+                    onKeyDown={(e) => e.key === 'Enter' && !e.nativeEvent.isComposing && create()}
+                    maxLength={32}
+                    placeholder="新配置名称（字母、数字、- 和 _）"
+                    className="flex-1 rounded-lg border border-gray-300 bg-white px-3 py-2 text-sm outline-none focus:border-blue-500 dark:border-gray-600 dark:bg-gray-800"
+                />
+                <button
+                    type="button"
+                    onClick={create}
+                    disabled={!name.trim()}
+                    className="flex items-center gap-1 rounded-lg bg-blue-600 px-3 py-2 text-sm text-white hover:bg-blue-700 disabled:opacity-50"
+                >
+                    <Plus className="h-4 w-4" />
+                    新建
+                </button>
+            </div>
+
+            {error && <p className="mt-2 text-sm text-red-500">{error}</p>}
+
+            <label className="mt-3 flex items-center gap-2 text-sm text-gray-600 dark:text-gray-400">
+                <input
+                    type="checkbox"
+                    checked={list?.pick_on_startup ?? false}
+                    onChange={(e) => togglePickOnStartup(e.target.checked)}
+                />
+                启动时显示此窗口
+            </label>
+        </div>
+    );
+};
+
+export default ProfilePicker;
@@ -0,0 +1,85 @@
+import React, { useEffect, useRef, useState } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Mic, Send } from 'lucide-react';
+import { useTransparentWindow } from '@/hooks/useTransparentWindow';
+
+// 快速提问输入框：回车提交文字，麦克风按钮改为录音提问，Esc 关闭；提交后窗口由后端关闭
+const QuickAskWindow: React.FC = () => {
+    const [text, setText] = useState('');
+    const [error, setError] = useState<string | null>(null);
+    const [submitting, setSubmitting] = useState(false);
+    const inputRef = useRef<HTMLInputElement>(null);
+
+    useTransparentWindow('快速提问 - 声驭智核');
+
+    useEffect(() => {
+        inputRef.current?.focus();
+    }, []);
+
+    const submit = async (question: string | null) => {
+        if (submitting) return;
+        setSubmitting(true);
+        setError(null);
+        try {
+            await invoke('submit_quick_ask', {
+                request: {
+                    text: question,
+                    token: localStorage.getItem('auth_token'),
+                },
+            });
+        } catch (e: any) {
+            setError(e?.message ?? String(e));
+            setSubmitting(false);
+        }
+    };
+
+    const close = () => {
+        invoke('close_quick_ask').catch((e) => console.error('关闭快速提问失败:', e));
+    };
+
+    const handleKeyDown = (event: React.KeyboardEvent<HTMLInputElement>) => {
+        if (event.key === 'Escape') {
+            close();
+        } else if (event.key === 'Enter' && !event.nativeEvent.isComposing && text.trim()) {
+            submit(text.trim());
+        }
+    };
+
+    return (
+        <div className="h-screen w-screen p-2">
+            <div className="flex h-full items-center gap-2 rounded-xl border border-gray-200 bg-white/95 px-3 shadow-lg dark:border-gray-700 dark:bg-gray-800/95">
+                <input
+                    ref={inputRef}
+                    value={text}
+                    onChange={(e) => setText(e.target.value)}
+                    onKeyDown={handleKeyDown}
+                    disabled={submitting}
+                    placeholder={error ?? '有什么想问的？回车发送，Esc 关闭'}
+                    className={`flex-1 bg-transparent text-base outline-none ${
+                        error ? 'placeholder-red-500' : 'placeholder-gray-400'
+                    }`}
+                />
+                <button
+                    type="button"
+                    title="语音提问"
+                    onClick={() => submit(null)}
+                    disabled={submitting}
+                    className="rounded-lg p-2 text-gray-500 hover:bg-gray-100 hover:text-gray-900 disabled:opacity-50 dark:hover:bg-gray-700 dark:hover:text-gray-100"
+                >
+                    <Mic className="h-5 w-5" />
+                </button>
+                <button
+                    type="button"
+                    title="发送"
+                    onClick={() => text.trim() && submit(text.trim())}
+                    disabled={submitting || !text.trim()}
+                    className="rounded-lg p-2 text-blue-600 hover:bg-blue-50 disabled:opacity-50 dark:hover:bg-gray-700"
+                >
+                    <Send className="h-5 w-5" />
+                </button>
+            </div>
+        </div>
+    );
+};
+
+export default QuickAskWindow;